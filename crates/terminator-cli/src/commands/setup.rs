@@ -458,7 +458,7 @@ impl SetupCommand {
         ];
 
         #[cfg(target_os = "linux")]
-        let chrome_paths = vec!["/usr/bin/google-chrome", "/usr/bin/chromium"];
+        let chrome_paths = ["/usr/bin/google-chrome", "/usr/bin/chromium"];
 
         chrome_paths
             .iter()
@@ -29,6 +29,9 @@ use tokio::sync::Mutex;
 
 mod commands;
 mod mcp_client;
+mod recording_converter;
+mod selector_validator;
+mod trace;
 mod typescript_workflow;
 mod workflow_result;
 mod workflow_validator;
@@ -69,6 +72,25 @@ struct ReleaseArgs {
     level: BumpLevel,
 }
 
+#[derive(Parser, Debug)]
+struct TraceArgs {
+    /// Workflow id, or a directory containing execution logs
+    workflow: String,
+
+    /// Run to pack, by the timestamp prefix of its execute_sequence log
+    /// (e.g. 20250101_093000). Defaults to the latest run
+    #[arg(long)]
+    run: Option<String>,
+
+    /// Archive path. Defaults to trace_<workflow>_<timestamp>.tar.gz
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Also write the HTML report to this path
+    #[arg(long)]
+    html: Option<String>,
+}
+
 #[derive(Parser, Debug)]
 struct McpChatArgs {
     /// MCP server URL (e.g., http://localhost:3000)
@@ -183,10 +205,46 @@ enum McpCommands {
     Exec(McpExecArgs),
     /// Execute a workflow sequence from a local file or GitHub gist
     Run(McpRunArgs),
-    /// Validate workflow output structure
+    /// Validate workflow output structure, or the selectors of a workflow
     Validate(McpValidateArgs),
     /// Generate TypeScript SDK snippet from MCP tool call
     Snippet(McpSnippetArgs),
+    /// Convert a workflow recording into an execute_sequence or TypeScript workflow
+    Convert(McpConvertArgs),
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum ConvertFormat {
+    /// execute_sequence arguments
+    Json,
+    /// `@mediar-ai/workflow` TypeScript workflow
+    Typescript,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct McpConvertArgs {
+    /// Recording saved by the workflow recorder (JSON)
+    input: String,
+
+    /// Output file. Prints to stdout when omitted
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Output format. Defaults to typescript for a .ts output file, json otherwise
+    #[arg(long, value_enum)]
+    format: Option<ConvertFormat>,
+
+    /// Do not replay the pauses between recorded actions
+    #[arg(long)]
+    no_timing: bool,
+
+    /// Longest pause replayed between two actions
+    #[arg(long, default_value_t = 3000)]
+    max_delay_ms: u64,
+
+    /// Do not attach expected_ui_changes to the steps
+    #[arg(long)]
+    no_expected_changes: bool,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -204,12 +262,16 @@ struct McpSnippetArgs {
 
 #[derive(Parser, Debug, Clone)]
 struct McpValidateArgs {
-    /// Input file containing workflow output (JSON format). Use '-' or omit to read from stdin
+    /// Input file containing workflow output or a workflow (JSON format). Use '-' or omit to read from stdin
     input: Option<String>,
 
     /// Show quality score (0-100)
     #[clap(long)]
     score: bool,
+
+    /// Reject unknown selector prefixes instead of reading `foo:bar` as `role:foo|name:bar`
+    #[clap(long)]
+    strict: bool,
 }
 
 #[derive(Subcommand)]
@@ -235,6 +297,8 @@ enum Commands {
     Setup(commands::setup::SetupCommand),
     /// Create a new TypeScript workflow project
     Init(commands::init::InitCommand),
+    /// Pack one workflow run's execution logs into an archive with an HTML report
+    Trace(TraceArgs),
 }
 
 fn main() {
@@ -297,6 +361,12 @@ fn main() {
                     }
                 });
         }
+        Commands::Trace(args) => {
+            if let Err(e) = pack_trace(args) {
+                eprintln!("❌ Trace failed: {e:#}");
+                std::process::exit(1);
+            }
+        }
     }
 }
 
@@ -1028,6 +1098,15 @@ fn handle_mcp_command(cmd: McpCommands) {
         return;
     }
 
+    // Handle recording conversion - doesn't need MCP connection
+    if let McpCommands::Convert(args) = cmd {
+        if let Err(e) = convert_recording(args) {
+            eprintln!("❌ Conversion error: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let transport = match cmd {
         McpCommands::Chat(ref args) => parse_transport(args.url.clone(), args.command.clone()),
         McpCommands::AiChat(ref args) => parse_transport(args.url.clone(), args.command.clone()),
@@ -1035,6 +1114,7 @@ fn handle_mcp_command(cmd: McpCommands) {
         McpCommands::Run(ref args) => parse_transport(args.url.clone(), args.command.clone()),
         McpCommands::Validate(_) => unreachable!(), // Handled above
         McpCommands::Snippet(_) => unreachable!(),  // Handled above
+        McpCommands::Convert(_) => unreachable!(),  // Handled above
     };
 
     let rt = tokio::runtime::Runtime::new().expect("Failed to create Tokio runtime");
@@ -1049,6 +1129,7 @@ fn handle_mcp_command(cmd: McpCommands) {
             McpCommands::Run(args) => run_workflow(transport, args).await,
             McpCommands::Validate(_) => unreachable!(), // Handled above
             McpCommands::Snippet(_) => unreachable!(),  // Handled above
+            McpCommands::Convert(_) => unreachable!(),  // Handled above
         }
     });
 
@@ -1079,6 +1160,21 @@ fn validate_workflow_output(args: McpValidateArgs) -> Result<()> {
     // Parse as JSON
     let output: Value = serde_json::from_str(&content).context("Failed to parse JSON input")?;
 
+    // A workflow (rather than its output) gets its selectors checked
+    if let Ok(workflow) = parse_workflow_content(&content) {
+        let mode = if args.strict {
+            terminator::SelectorParseMode::Strict
+        } else {
+            terminator::SelectorParseMode::Lenient
+        };
+        let issues = selector_validator::validate_workflow_selectors(&workflow, mode);
+        selector_validator::display_selector_issues(&issues);
+        if !issues.is_empty() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     // Validate the structure
     let validation_result = WorkflowOutputValidator::validate(&output);
 
@@ -1187,6 +1283,67 @@ fn generate_snippet(args: McpSnippetArgs) -> Result<()> {
     Ok(())
 }
 
+fn convert_recording(args: McpConvertArgs) -> Result<()> {
+    let recording = terminator_workflow_recorder::RecordedWorkflow::load_from_file(&args.input)
+        .map_err(|e| anyhow::anyhow!("Failed to load recording {}: {e}", args.input))?;
+    let options = terminator_workflow_recorder::ConversionOptions {
+        preserve_timing: !args.no_timing,
+        max_delay_ms: args.max_delay_ms,
+        expected_ui_changes: !args.no_expected_changes,
+    };
+    let converted = terminator_workflow_recorder::convert_workflow(&recording, &options);
+
+    let format = args.format.unwrap_or_else(|| {
+        if args.output.as_deref().is_some_and(|o| o.ends_with(".ts")) {
+            ConvertFormat::Typescript
+        } else {
+            ConvertFormat::Json
+        }
+    });
+    let output = match format {
+        ConvertFormat::Json => serde_json::to_string_pretty(&converted.to_execute_sequence())?,
+        ConvertFormat::Typescript => recording_converter::typescript_workflow_source(&converted),
+    };
+
+    for warning in &converted.warnings {
+        eprintln!("⚠️  {warning}");
+    }
+    match &args.output {
+        Some(path) => {
+            fs::write(path, output).with_context(|| format!("Failed to write {path}"))?;
+            eprintln!(
+                "✅ Converted {} recorded events into {} steps: {path}",
+                recording.events.len(),
+                converted.steps.len()
+            );
+        }
+        None => println!("{output}"),
+    }
+    Ok(())
+}
+
+fn pack_trace(args: TraceArgs) -> Result<()> {
+    let dir = if Path::new(&args.workflow).is_dir() {
+        Path::new(&args.workflow).to_path_buf()
+    } else {
+        terminator_mcp_agent::execution_logger::get_workflow_executions_dir(&args.workflow)
+    };
+    let run = trace::load_run(&dir, args.run.as_deref())?;
+    let report = trace::render_html(&run);
+
+    let output = args.output.unwrap_or_else(|| run.default_archive_name());
+    trace::write_archive(&run, &report, Path::new(&output))?;
+    if let Some(html) = &args.html {
+        fs::write(html, &report).with_context(|| format!("Failed to write {html}"))?;
+    }
+    eprintln!(
+        "✅ Packed {} tool calls from {} into {output}",
+        run.entries.len(),
+        dir.display()
+    );
+    Ok(())
+}
+
 fn parse_transport(url: Option<String>, command: Option<String>) -> mcp_client::Transport {
     if let Some(url) = url {
         // Check for MCP_AUTH_TOKEN environment variable
@@ -2297,6 +2454,36 @@ fn validate_workflow(workflow: &serde_json::Value) -> anyhow::Result<()> {
         }
     }
 
+    // A condition that does not parse would otherwise skip its step at run time,
+    // and a malformed assertion would only fail once the step has run
+    let section =
+        |name: &str| -> anyhow::Result<Option<Vec<terminator_mcp_agent::utils::SequenceStep>>> {
+            obj.get(name)
+                .map(|steps| serde_json::from_value(steps.clone()))
+                .transpose()
+                .map_err(|e| anyhow::anyhow!("Invalid '{name}': {e}"))
+        };
+    let (steps, troubleshooting) = (section("steps")?, section("troubleshooting")?);
+    let issues = terminator_mcp_agent::server_sequence::validate_steps(
+        steps.as_deref(),
+        troubleshooting.as_deref(),
+    );
+    if let Some(issue) = issues.first() {
+        return Err(anyhow::anyhow!("{issue}"));
+    }
+
+    // Malformed selectors still run (the parser guesses at them), but rarely match
+    for issue in selector_validator::validate_workflow_selectors(
+        workflow,
+        terminator::SelectorParseMode::Lenient,
+    ) {
+        tracing::warn!(
+            "Selector at {} is malformed:\n{}",
+            issue.location,
+            issue.error.render(&issue.selector)
+        );
+    }
+
     // Validate variables if present
     if let Some(variables) = obj.get("variables") {
         if let Some(vars_obj) = variables.as_object() {
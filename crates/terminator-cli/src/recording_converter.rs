@@ -0,0 +1,109 @@
+use serde_json::json;
+use terminator_mcp_agent::execution_logger::generate_typescript_snippet;
+use terminator_workflow_recorder::ConvertedWorkflow;
+
+/// Render converted steps as a `@mediar-ai/workflow` TypeScript workflow
+pub fn typescript_workflow_source(workflow: &ConvertedWorkflow) -> String {
+    let mut source = String::from(
+        r#"import { createStep, createWorkflow, z } from "@mediar-ai/workflow";
+
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+"#,
+    );
+
+    for (i, step) in workflow.steps.iter().enumerate() {
+        // The snippet generator reads the step delay from the arguments
+        let mut arguments = step.arguments.clone();
+        if let Some(delay_ms) = step.delay_ms {
+            arguments["delay_ms"] = json!(delay_ms);
+        }
+        let snippet =
+            generate_typescript_snippet(&step.tool_name, &arguments, Ok(&serde_json::Value::Null));
+        let snippet = snippet
+            .strip_prefix("// Status: SUCCESS\n")
+            .unwrap_or(&snippet);
+
+        let mut body = String::new();
+        if let Some(changes) = &step.expected_ui_changes {
+            body.push_str("    // Expected UI changes:\n");
+            for line in changes.lines() {
+                body.push_str(&format!("    //   {line}\n"));
+            }
+        }
+        for line in snippet.trim_end().lines() {
+            if line.is_empty() {
+                body.push('\n');
+            } else {
+                body.push_str(&format!("    {line}\n"));
+            }
+        }
+
+        source.push_str(&format!(
+            r#"
+const step{n} = createStep({{
+  id: "step_{n}",
+  name: {name},
+  execute: async ({{ desktop }}) => {{
+{body}  }},
+}});
+"#,
+            n = i + 1,
+            name = serde_json::to_string(&step.description).unwrap_or_default(),
+        ));
+    }
+
+    let steps: Vec<String> = (1..=workflow.steps.len())
+        .map(|n| format!("    step{n},\n"))
+        .collect();
+    source.push_str(&format!(
+        r#"
+// Converted from the recording "{}"
+export default createWorkflow({{
+  input: z.object({{}}),
+  steps: [
+{}  ],
+}});
+"#,
+        workflow.name.replace('"', "'"),
+        steps.concat(),
+    ));
+    source
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use terminator_workflow_recorder::McpToolStep;
+
+    #[test]
+    fn test_typescript_workflow_wraps_each_step() {
+        let workflow = ConvertedWorkflow {
+            name: "login".to_string(),
+            steps: vec![McpToolStep {
+                tool_name: "click_element".to_string(),
+                arguments: json!({
+                    "process": "notepad",
+                    "selector": "role:Button|name:Save",
+                    "verify_element_exists": "",
+                    "verify_element_not_exists": "",
+                }),
+                description: "Click `role:Button|name:Save`".to_string(),
+                timeout_ms: None,
+                continue_on_error: None,
+                delay_ms: Some(1500),
+                expected_ui_changes: Some("+ - [Window] Save As".to_string()),
+                expected_dom_changes: None,
+            }],
+            warnings: Vec::new(),
+        };
+
+        let source = typescript_workflow_source(&workflow);
+        assert!(source.starts_with("import { createStep, createWorkflow, z }"));
+        assert!(source.contains("const step1 = createStep({\n  id: \"step_1\","));
+        assert!(source.contains("    //   + - [Window] Save As\n"));
+        assert!(source.contains("await sleep(1500);"));
+        assert!(source.contains("role:Button|name:Save"));
+        assert!(!source.contains("// Status:"));
+        assert!(source.contains("steps: [\n    step1,\n  ],"));
+    }
+}
@@ -0,0 +1,125 @@
+use colored::*;
+use serde_json::Value;
+use terminator::{Selector, SelectorParseError, SelectorParseMode};
+use terminator_mcp_agent::helpers::parse_alternative_selectors;
+
+/// Step arguments holding selectors; the `*_selectors` ones are comma-separated lists
+const SELECTOR_ARGUMENTS: [&str; 3] = ["selector", "alternative_selectors", "fallback_selectors"];
+
+/// A workflow selector that does not parse
+#[derive(Debug)]
+pub struct SelectorIssue {
+    /// Where the selector lives, e.g. `steps[2].steps[0].arguments.selector`
+    pub location: String,
+    pub selector: String,
+    pub error: SelectorParseError,
+}
+
+/// Check the selectors of every step (grouped steps and troubleshooting steps included)
+pub fn validate_workflow_selectors(
+    workflow: &Value,
+    mode: SelectorParseMode,
+) -> Vec<SelectorIssue> {
+    let mut issues = Vec::new();
+    for field in ["steps", "troubleshooting"] {
+        if let Some(steps) = workflow.get(field).and_then(|s| s.as_array()) {
+            check_steps(steps, field, mode, &mut issues);
+        }
+    }
+    issues
+}
+
+fn check_steps(
+    steps: &[Value],
+    path: &str,
+    mode: SelectorParseMode,
+    issues: &mut Vec<SelectorIssue>,
+) {
+    for (i, step) in steps.iter().enumerate() {
+        let step_path = format!("{path}[{i}]");
+        if let Some(arguments) = step.get("arguments") {
+            for key in SELECTOR_ARGUMENTS {
+                let Some(value) = arguments.get(key).and_then(|v| v.as_str()) else {
+                    continue;
+                };
+                let selectors = if key == "selector" {
+                    vec![value.to_string()]
+                } else {
+                    parse_alternative_selectors(Some(value))
+                };
+                for selector in selectors {
+                    // Variables are substituted at run time
+                    if selector.contains("{{") {
+                        continue;
+                    }
+                    if let Err(error) = Selector::parse_with(&selector, mode) {
+                        issues.push(SelectorIssue {
+                            location: format!("{step_path}.arguments.{key}"),
+                            selector,
+                            error,
+                        });
+                    }
+                }
+            }
+        }
+        if let Some(nested) = step.get("steps").and_then(|s| s.as_array()) {
+            check_steps(nested, &format!("{step_path}.steps"), mode, issues);
+        }
+    }
+}
+
+/// Print each issue with a caret under the offending part of the selector
+pub fn display_selector_issues(issues: &[SelectorIssue]) {
+    if issues.is_empty() {
+        println!("{} All selectors are valid", "✓".green());
+        return;
+    }
+    println!(
+        "{}",
+        format!("Selector errors ({}):", issues.len()).red().bold()
+    );
+    for issue in issues {
+        println!("\n  {}", issue.location.bold());
+        for line in issue.error.render(&issue.selector).lines() {
+            println!("    {line}");
+        }
+    }
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_reports_selector_locations() {
+        let workflow = json!({
+            "steps": [
+                {"tool_name": "click_element", "arguments": {"selector": "role:Button && name:OK"}},
+                {"group_name": "login", "steps": [
+                    {"tool_name": "click_element", "arguments": {
+                        "selector": "role:Button|name:{{label}}",
+                        "alternative_selectors": "name:OK, role:Button &&"
+                    }}
+                ]}
+            ],
+            "troubleshooting": [
+                {"tool_name": "click_element", "arguments": {"selector": "nmae:Retry"}}
+            ]
+        });
+
+        let issues = validate_workflow_selectors(&workflow, SelectorParseMode::Lenient);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(
+            issues[0].location,
+            "steps[1].steps[0].arguments.alternative_selectors"
+        );
+        assert_eq!(issues[0].selector, "role:Button &&");
+
+        let issues = validate_workflow_selectors(&workflow, SelectorParseMode::Strict);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[1].location, "troubleshooting[0].arguments.selector");
+        assert_eq!(issues[1].error.span, 0..5);
+    }
+}
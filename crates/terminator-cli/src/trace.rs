@@ -0,0 +1,496 @@
+//! Packs one workflow run from the execution logs into a single `.tar.gz`
+//! archive, together with a self-contained HTML report of the run.
+
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use flate2::{write::GzEncoder, Compression};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use terminator_mcp_agent::execution_logger::{self, LoggedExecution};
+use terminator_mcp_agent::helpers::substitute_variables;
+
+/// Files belonging to a tool call, relative to the executions directory
+fn files(entry: &LoggedExecution) -> Vec<String> {
+    let mut files = vec![format!("{}.json", entry.stem), format!("{}.ts", entry.stem)];
+    if let Some(screenshots) = &entry.log.screenshots {
+        files.extend(screenshots.before.iter().cloned());
+        files.extend(screenshots.after.iter().cloned());
+    }
+    files
+}
+
+/// The tool calls of one workflow run, in execution order
+pub struct TraceRun {
+    /// Directory holding the execution logs
+    pub dir: PathBuf,
+    pub entries: Vec<LoggedExecution>,
+    /// Variables the run was started with, built like execute_sequence does:
+    /// the variable defaults, overridden by the inputs, plus the selectors
+    pub variables: Value,
+}
+
+impl TraceRun {
+    /// Workflow id from the logs, or the name of the directory they were read from
+    pub fn workflow_name(&self) -> String {
+        self.entries
+            .iter()
+            .find_map(|e| e.log.workflow_id.clone())
+            .unwrap_or_else(|| {
+                let dir = match self.dir.file_name().and_then(|n| n.to_str()) {
+                    Some("executions") => self.dir.parent().unwrap_or(&self.dir),
+                    _ => &self.dir,
+                };
+                dir.file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "workflow".to_string())
+            })
+    }
+
+    /// `trace_{workflow}_{YYYYMMDD_HHMMSS}.tar.gz`
+    pub fn default_archive_name(&self) -> String {
+        let started = self.entries[0].started.format("%Y%m%d_%H%M%S");
+        let workflow: String = self
+            .workflow_name()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!("trace_{workflow}_{started}.tar.gz")
+    }
+
+    /// Arguments of a tool call with any `{{variable}}` left in them resolved
+    fn resolved_arguments(&self, entry: &LoggedExecution) -> Value {
+        let mut arguments = entry.log.request.clone();
+        if !entry.is_sequence() {
+            substitute_variables(&mut arguments, &self.variables);
+        }
+        arguments
+    }
+}
+
+/// Load one workflow run from the execution logs in `dir`, see
+/// [`execution_logger::load_run`]
+pub fn load_run(dir: &Path, run: Option<&str>) -> Result<TraceRun> {
+    let entries = execution_logger::load_run(dir, run).map_err(|e| anyhow!(e))?;
+    let variables = entries
+        .iter()
+        .find(|e| e.is_sequence())
+        .map(|e| run_variables(&e.log.request))
+        .unwrap_or_else(|| Value::Object(Map::new()));
+
+    Ok(TraceRun {
+        dir: dir.to_path_buf(),
+        entries,
+        variables,
+    })
+}
+
+fn run_variables(request: &Value) -> Value {
+    let mut variables = Map::new();
+    if let Some(schema) = request.get("variables").and_then(|v| v.as_object()) {
+        for (name, definition) in schema {
+            if let Some(default) = definition.get("default") {
+                variables.insert(name.clone(), default.clone());
+            }
+        }
+    }
+    if let Some(inputs) = request.get("inputs").and_then(|v| v.as_object()) {
+        for (name, value) in inputs {
+            variables.insert(name.clone(), value.clone());
+        }
+    }
+    if let Some(selectors) = request.get("selectors") {
+        let selectors = match selectors {
+            Value::String(s) => serde_json::from_str(s).unwrap_or_else(|_| selectors.clone()),
+            _ => selectors.clone(),
+        };
+        variables.insert("selectors".to_string(), selectors);
+    }
+    Value::Object(variables)
+}
+
+/// Write the report and every log, snippet and screenshot of the run into a `.tar.gz`
+pub fn write_archive(run: &TraceRun, report_html: &str, output: &Path) -> Result<()> {
+    let file = fs::File::create(output)
+        .with_context(|| format!("Failed to create {}", output.display()))?;
+    let mtime = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let mut append = |path: &str, data: &[u8]| -> Result<()> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(mtime);
+        tar.append_data(&mut header, path, data)
+            .with_context(|| format!("Failed to add {path} to the archive"))
+    };
+    append("report.html", report_html.as_bytes())?;
+    for entry in &run.entries {
+        for name in files(entry) {
+            // Snippets are missing for logs written by older versions
+            let Ok(data) = fs::read(run.dir.join(&name)) else {
+                continue;
+            };
+            append(&format!("executions/{name}"), &data)?;
+        }
+    }
+    tar.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// Render the run as one HTML page with the screenshots embedded, so it can be
+/// opened offline or attached to a bug report
+pub fn render_html(run: &TraceRun) -> String {
+    let workflow = escape_html(&run.workflow_name());
+    let failed = run.entries.iter().filter(|e| e.failed()).count();
+    let total_ms: u64 = match run.entries.iter().find(|e| e.is_sequence()) {
+        Some(sequence) => sequence.log.response.duration_ms,
+        None => run.entries.iter().map(|e| e.log.response.duration_ms).sum(),
+    };
+
+    let mut timeline = String::new();
+    let mut steps = String::new();
+    for (i, entry) in run.entries.iter().enumerate() {
+        let class = if entry.failed() { "err" } else { "ok" };
+        if !entry.is_sequence() {
+            timeline.push_str(&format!(
+                r##"<a class="bar {class}" href="#step-{i}" style="flex-grow:{}" title="{} ({} ms)"></a>"##,
+                entry.log.response.duration_ms.max(1),
+                escape_html(&step_label(entry)),
+                entry.log.response.duration_ms,
+            ));
+        }
+        steps.push_str(&render_step(run, i, entry));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Trace: {workflow}</title>
+<style>{STYLE}</style>
+</head>
+<body>
+<h1>{workflow}</h1>
+<p class="summary">Started {started} &middot; {calls} tool calls &middot; {total_ms} ms &middot; <span class="{failed_class}">{failed} failed</span></p>
+<div class="timeline">{timeline}</div>
+{steps}</body>
+</html>
+"#,
+        started = escape_html(&run.entries[0].log.timestamp),
+        calls = run.entries.len(),
+        failed_class = if failed > 0 { "err" } else { "ok" },
+    )
+}
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2em;color:#222}\
+h2{font-size:1.1em;margin:0 0 .5em}\
+pre{background:#f6f6f6;padding:.75em;overflow:auto;max-height:30em;font-size:12px}\
+.summary .err,.step.err h2 .status{color:#c62828}.summary .ok,.step.ok h2 .status{color:#2e7d32}\
+.timeline{display:flex;height:24px;gap:2px;margin:1em 0 2em}\
+.bar{min-width:4px;border-radius:2px}.bar.ok{background:#66bb6a}.bar.err{background:#e53935}\
+.step{border:1px solid #ddd;border-left:6px solid #66bb6a;padding:1em;margin-bottom:1em}\
+.step.err{border-left-color:#e53935;background:#fff5f5}\
+.index{color:#888;margin-right:.5em}.duration{color:#888;font-weight:normal}\
+pre.error{background:#ffebee;color:#b71c1c;border:1px solid #e57373}\
+.screenshots{display:flex;gap:1em}.screenshots figure{flex:1;margin:0}\
+.screenshots img{max-width:100%;border:1px solid #ccc}\
+table{border-collapse:collapse;font-size:12px}td{padding:2px 8px;vertical-align:top}";
+
+fn step_label(entry: &LoggedExecution) -> String {
+    if entry.is_sequence() {
+        return "Workflow".to_string();
+    }
+    match (&entry.log.step_id, entry.log.step_index) {
+        (Some(id), _) => id.clone(),
+        (None, Some(index)) => format!("Step {}", index + 1),
+        (None, None) => entry.log.tool_name.clone(),
+    }
+}
+
+fn render_step(run: &TraceRun, i: usize, entry: &LoggedExecution) -> String {
+    let log = &entry.log;
+    let mut html = format!(
+        r#"<section id="step-{i}" class="step {class}">
+<h2><span class="index">#{n}</span>{label} <code>{tool}</code> <span class="status">{status}</span> <span class="duration">{ms} ms</span></h2>
+"#,
+        class = if entry.failed() { "err" } else { "ok" },
+        n = i + 1,
+        label = escape_html(&step_label(entry)),
+        tool = escape_html(&log.tool_name),
+        status = escape_html(&log.response.status),
+        ms = log.response.duration_ms,
+    );
+
+    if let Some(error) = &log.response.error {
+        let error = serde_json::from_str::<Value>(error)
+            .map(|e| pretty(&e))
+            .unwrap_or_else(|_| error.clone());
+        html.push_str(&format!(
+            "<pre class=\"error\">{}</pre>\n",
+            escape_html(&error)
+        ));
+    }
+
+    html.push_str(&details(
+        "Arguments",
+        &pretty(&run.resolved_arguments(entry)),
+        !entry.is_sequence(),
+    ));
+
+    if let Some(screenshots) = &log.screenshots {
+        let mut figures = String::new();
+        let named = screenshots
+            .before
+            .iter()
+            .map(|f| ("Before", f))
+            .chain(screenshots.after.iter().map(|f| ("After", f)));
+        for (caption, file) in named {
+            if let Some(src) = data_url(&run.dir.join(file)) {
+                figures.push_str(&format!(
+                    "<figure><figcaption>{caption}</figcaption><img src=\"{src}\" alt=\"{}\"></figure>",
+                    escape_html(file)
+                ));
+            }
+        }
+        if !figures.is_empty() {
+            html.push_str(&format!("<div class=\"screenshots\">{figures}</div>\n"));
+        }
+    }
+
+    // A sequence result holds the trees of all its steps, which are shown per step
+    if let Some(result) = log
+        .response
+        .result
+        .as_ref()
+        .filter(|_| !entry.is_sequence())
+    {
+        if let Some(tree) = find_field(result, "ui_tree") {
+            let tree = match tree {
+                Value::String(text) => text,
+                other => pretty(&other),
+            };
+            html.push_str(&details("UI tree", &tree, false));
+        }
+        if let Some(changes) = find_field(result, "ui_changes") {
+            html.push_str(&details("UI changes", &pretty(&changes), true));
+        }
+    }
+
+    if let Some(logs) = log.logs.as_ref().filter(|l| !l.is_empty()) {
+        let rows: String = logs
+            .iter()
+            .map(|l| {
+                format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    l.timestamp.format("%H:%M:%S%.3f"),
+                    escape_html(&l.level),
+                    escape_html(&l.message)
+                )
+            })
+            .collect();
+        html.push_str(&format!(
+            "<details><summary>Logs ({})</summary><table>{rows}</table></details>\n",
+            logs.len()
+        ));
+    }
+
+    if let Some(result) = &log.response.result {
+        html.push_str(&details("Result", &pretty(result), false));
+    }
+
+    html.push_str("</section>\n");
+    html
+}
+
+fn details(summary: &str, body: &str, open: bool) -> String {
+    format!(
+        "<details{}><summary>{summary}</summary><pre>{}</pre></details>\n",
+        if open { " open" } else { "" },
+        escape_html(body)
+    )
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+fn data_url(path: &Path) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    let mime = match path.extension().and_then(|e| e.to_str()) {
+        Some("jpg" | "jpeg") => "image/jpeg",
+        _ => "image/png",
+    };
+    Some(format!("data:{mime};base64,{}", BASE64.encode(bytes)))
+}
+
+/// Find `key` anywhere in a tool result, including inside JSON text content
+fn find_field(value: &Value, key: &str) -> Option<Value> {
+    match value {
+        Value::Object(map) => match map.get(key).filter(|v| !v.is_null()) {
+            Some(found) => Some(found.clone()),
+            None => map.values().find_map(|v| find_field(v, key)),
+        },
+        Value::Array(items) => items.iter().find_map(|v| find_field(v, key)),
+        Value::String(text) if text.starts_with('{') => serde_json::from_str::<Value>(text)
+            .ok()
+            .and_then(|parsed| find_field(&parsed, key)),
+        _ => None,
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use serde_json::json;
+
+    fn write_log(dir: &Path, stem: &str, log: Value) {
+        fs::write(dir.join(format!("{stem}.json")), log.to_string()).unwrap();
+    }
+
+    fn fixture_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("terminator_trace_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        // An earlier run that must not end up in the trace
+        write_log(
+            &dir,
+            "20250101_090000_wf_full_execute_sequence",
+            json!({
+                "timestamp": "2025-01-01T09:00:00+00:00",
+                "tool_name": "execute_sequence",
+                "request": {},
+                "response": {"status": "executed_without_error", "duration_ms": 1000},
+            }),
+        );
+        write_log(
+            &dir,
+            "20250101_100000_wf_full_execute_sequence",
+            json!({
+                "timestamp": "2025-01-01T10:00:00+00:00",
+                "workflow_id": "wf",
+                "tool_name": "execute_sequence",
+                "request": {"inputs": {"user": "alice"}, "variables": {"app": {"default": "notepad"}}},
+                "response": {"status": "executed_with_error", "duration_ms": 5000},
+            }),
+        );
+        write_log(
+            &dir,
+            "20250101_100001_wf_open_click_element",
+            json!({
+                "timestamp": "2025-01-01T10:00:01.500+00:00",
+                "workflow_id": "wf",
+                "step_id": "open",
+                "step_index": 0,
+                "tool_name": "click_element",
+                "request": {"selector": "role:Button|name:{{user}}", "process": "{{app}}"},
+                "response": {
+                    "status": "executed_without_error",
+                    "duration_ms": 800,
+                    "result": {"content": [{"ui_tree": "- [Window] Notepad <script>"}]},
+                },
+                "screenshots": {"after": ["20250101_100001_wf_open_click_element_after.png"]},
+            }),
+        );
+        fs::write(
+            dir.join("20250101_100001_wf_open_click_element_after.png"),
+            [0x89, b'P', b'N', b'G'],
+        )
+        .unwrap();
+        write_log(
+            &dir,
+            "20250101_100003_wf_save_the_document_under_a_new_name_after_confirming_the_dialog_press_key",
+            json!({
+                "timestamp": "2025-01-01T10:00:03+00:00",
+                "workflow_id": "wf",
+                "step_id": "save_the_document_under_a_new_name_after_confirming_the_dialog",
+                "step_index": 1,
+                "tool_name": "press_key",
+                "request": {"key": "{Ctrl}s"},
+                "response": {"status": "executed_with_error", "duration_ms": 300, "error": "{\"message\":\"Element not found\"}"},
+            }),
+        );
+        fs::write(dir.join("state.json"), "{\"last_step_id\":\"open\"}").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_load_run_selects_latest_sequence_and_renders_report() {
+        let dir = fixture_dir("report");
+        let run = load_run(&dir, None).unwrap();
+        let tools: Vec<&str> = run
+            .entries
+            .iter()
+            .map(|e| e.log.tool_name.as_str())
+            .collect();
+        assert_eq!(tools, ["execute_sequence", "click_element", "press_key"]);
+        assert_eq!(run.workflow_name(), "wf");
+        assert_eq!(
+            run.default_archive_name(),
+            "trace_wf_20250101_100000.tar.gz"
+        );
+
+        let html = render_html(&run);
+        assert!(html.contains("role:Button|name:alice"));
+        assert!(html.contains("&quot;process&quot;: &quot;notepad&quot;"));
+        assert!(html.contains("data:image/png;base64,iVBORw=="));
+        assert!(html.contains("- [Window] Notepad &lt;script&gt;"));
+        assert!(html.contains("<pre class=\"error\">"));
+        assert!(html.contains("Element not found"));
+        assert!(html.contains("2 failed"));
+
+        let earlier = load_run(&dir, Some("20250101_0900")).unwrap();
+        assert_eq!(earlier.entries.len(), 1);
+        assert!(load_run(&dir, Some("20240101")).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_archive_packs_report_and_files() {
+        let dir = fixture_dir("archive");
+        let run = load_run(&dir, None).unwrap();
+        let output = dir.join("trace.tar.gz");
+        write_archive(&run, "<html></html>", &output).unwrap();
+
+        let mut archive = tar::Archive::new(GzDecoder::new(fs::File::open(&output).unwrap()));
+        let names: Vec<String> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                entry.path().unwrap().to_string_lossy().replace('\\', "/")
+            })
+            .collect();
+
+        assert_eq!(
+            names,
+            [
+                "report.html",
+                "executions/20250101_100000_wf_full_execute_sequence.json",
+                "executions/20250101_100001_wf_open_click_element.json",
+                "executions/20250101_100001_wf_open_click_element_after.png",
+                "executions/20250101_100003_wf_save_the_document_under_a_new_name_after_confirming_the_dialog_press_key.json",
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -196,122 +196,6 @@ pub async fn call_computer_use_backend(
     })
 }
 
-// ===== Key Translation =====
-
-/// Translate Gemini Computer Use key format to uiautomation format.
-///
-/// Gemini format: "enter", "control+a", "Meta+Shift+T"
-/// uiautomation format: "{Enter}", "{Ctrl}a", "{Win}{Shift}t"
-///
-/// # Arguments
-/// * `gemini_keys` - Key combination in Gemini format
-///
-/// # Returns
-/// * `Ok(String)` - Translated key string for uiautomation
-/// * `Err(String)` - If the key format is invalid
-pub fn translate_gemini_keys(gemini_keys: &str) -> Result<String, String> {
-    let parts: Vec<&str> = gemini_keys.split('+').collect();
-    let mut result = String::new();
-
-    for (i, part) in parts.iter().enumerate() {
-        let lower = part.trim().to_lowercase();
-        let is_last = i == parts.len() - 1;
-
-        let translated: &str = match lower.as_str() {
-            // Modifiers
-            "control" | "ctrl" => "{Ctrl}",
-            "alt" => "{Alt}",
-            "shift" => "{Shift}",
-            "meta" | "cmd" | "command" | "win" | "windows" | "super" => "{Win}",
-
-            // Common special keys
-            "enter" | "return" => "{Enter}",
-            "tab" => "{Tab}",
-            "escape" | "esc" => "{Escape}",
-            "backspace" | "back" => "{Backspace}",
-            "delete" | "del" => "{Delete}",
-            "space" => "{Space}",
-            "insert" | "ins" => "{Insert}",
-            "home" => "{Home}",
-            "end" => "{End}",
-            "pageup" | "pgup" => "{PageUp}",
-            "pagedown" | "pgdown" | "pgdn" => "{PageDown}",
-            "printscreen" | "prtsc" => "{PrintScreen}",
-
-            // Arrow keys
-            "up" | "arrowup" => "{Up}",
-            "down" | "arrowdown" => "{Down}",
-            "left" | "arrowleft" => "{Left}",
-            "right" | "arrowright" => "{Right}",
-
-            // Function keys - check for f1-f24
-            s if s.starts_with('f') && s.len() >= 2 => {
-                if let Ok(num) = s[1..].parse::<u8>() {
-                    if (1..=24).contains(&num) {
-                        match num {
-                            1 => "{F1}",
-                            2 => "{F2}",
-                            3 => "{F3}",
-                            4 => "{F4}",
-                            5 => "{F5}",
-                            6 => "{F6}",
-                            7 => "{F7}",
-                            8 => "{F8}",
-                            9 => "{F9}",
-                            10 => "{F10}",
-                            11 => "{F11}",
-                            12 => "{F12}",
-                            13 => "{F13}",
-                            14 => "{F14}",
-                            15 => "{F15}",
-                            16 => "{F16}",
-                            17 => "{F17}",
-                            18 => "{F18}",
-                            19 => "{F19}",
-                            20 => "{F20}",
-                            21 => "{F21}",
-                            22 => "{F22}",
-                            23 => "{F23}",
-                            24 => "{F24}",
-                            _ => unreachable!(),
-                        }
-                    } else {
-                        return Err(format!(
-                            "Invalid function key '{}' in '{}'. Use f1-f24.",
-                            part, gemini_keys
-                        ));
-                    }
-                } else {
-                    return Err(format!(
-                        "Invalid function key '{}' in '{}'. Use f1-f24.",
-                        part, gemini_keys
-                    ));
-                }
-            }
-
-            // Single character (a-z, 0-9) - only valid as last part of combination
-            s if s.len() == 1 && is_last => {
-                result.push_str(s);
-                continue;
-            }
-
-            // Unknown key
-            unknown => {
-                return Err(format!(
-                    "Unknown key '{}' in combination '{}'. Valid: enter, tab, escape, \
-                     backspace, delete, space, up/down/left/right, home, end, pageup, \
-                     pagedown, f1-f24, or modifiers (ctrl, alt, shift, meta) with letters.",
-                    unknown, gemini_keys
-                ));
-            }
-        };
-
-        result.push_str(translated);
-    }
-
-    Ok(result)
-}
-
 // ===== Coordinate Conversion =====
 
 /// Convert normalized coordinates (0-999) to absolute screen coordinates.
@@ -362,30 +246,6 @@ pub fn convert_normalized_to_screen(
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_translate_gemini_keys_simple() {
-        assert_eq!(translate_gemini_keys("enter").unwrap(), "{Enter}");
-        assert_eq!(translate_gemini_keys("tab").unwrap(), "{Tab}");
-        assert_eq!(translate_gemini_keys("escape").unwrap(), "{Escape}");
-    }
-
-    #[test]
-    fn test_translate_gemini_keys_modifiers() {
-        assert_eq!(translate_gemini_keys("control+a").unwrap(), "{Ctrl}a");
-        assert_eq!(translate_gemini_keys("ctrl+c").unwrap(), "{Ctrl}c");
-        assert_eq!(
-            translate_gemini_keys("Meta+Shift+T").unwrap(),
-            "{Win}{Shift}t"
-        );
-    }
-
-    #[test]
-    fn test_translate_gemini_keys_function() {
-        assert_eq!(translate_gemini_keys("f1").unwrap(), "{F1}");
-        assert_eq!(translate_gemini_keys("f12").unwrap(), "{F12}");
-        assert_eq!(translate_gemini_keys("alt+f4").unwrap(), "{Alt}{F4}");
-    }
-
     #[test]
     fn test_convert_normalized_coords() {
         // Simple case: no scaling, window at origin
@@ -305,8 +305,10 @@ mod tests {
     }
     #[test]
     fn test_init_job_object() {
-        let _result = init_job_object();
+        let result = init_job_object();
         #[cfg(not(target_os = "windows"))]
         assert!(result);
+        #[cfg(target_os = "windows")]
+        let _ = result;
     }
 }
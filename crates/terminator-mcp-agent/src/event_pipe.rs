@@ -19,9 +19,12 @@
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+#[cfg(windows)]
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::sync::mpsc;
-use tracing::{debug, error, info};
+use tracing::debug;
+#[cfg(windows)]
+use tracing::{error, info};
 
 /// Workflow event emitted from TypeScript workflows
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -4,11 +4,11 @@
 //! with associated before/after screenshots. 7-day retention with automatic cleanup.
 
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
-use chrono::{DateTime, Local, Utc};
+use chrono::{DateTime, FixedOffset, Local, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use tracing::{debug, error, info, warn};
 
@@ -664,6 +664,93 @@ fn strip_screenshot_base64(value: &Value) -> Value {
     result
 }
 
+/// An execution log read back from disk
+#[derive(Debug)]
+pub struct LoggedExecution {
+    /// File name without `.json`, shared with the `.ts` snippet and the screenshots
+    pub stem: String,
+    pub log: ExecutionLog,
+    pub started: DateTime<FixedOffset>,
+}
+
+impl LoggedExecution {
+    pub fn is_sequence(&self) -> bool {
+        self.log.tool_name == "execute_sequence"
+    }
+
+    pub fn failed(&self) -> bool {
+        self.log.response.error.is_some() || self.log.response.status == "executed_with_error"
+    }
+}
+
+/// Load the execution logs in `dir` that belong to one workflow run, oldest first.
+///
+/// A run is an `execute_sequence` call plus every tool call logged while it
+/// was executing. `run` picks the sequence whose log file name starts with
+/// it (e.g. `20250101_093000`), otherwise the latest run is used. Folders
+/// without any `execute_sequence` log are treated as a single run.
+pub fn load_run(dir: &Path, run: Option<&str>) -> Result<Vec<LoggedExecution>, String> {
+    let files = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read execution logs from {}: {e}", dir.display()))?;
+    let mut entries = Vec::new();
+    for path in files.filter_map(|f| f.ok()).map(|f| f.path()) {
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        // Other JSON files (e.g. workflow state) live next to the logs
+        let Some(log) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str::<ExecutionLog>(&text).ok())
+        else {
+            continue;
+        };
+        let Ok(started) = DateTime::parse_from_rfc3339(&log.timestamp) else {
+            continue;
+        };
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        entries.push(LoggedExecution { stem, log, started });
+    }
+    entries.sort_by(|a, b| a.started.cmp(&b.started).then(a.stem.cmp(&b.stem)));
+    if entries.is_empty() {
+        return Err(format!("No execution logs found in {}", dir.display()));
+    }
+
+    // Durations are whole milliseconds, leave a little slack at the end
+    let window = entries
+        .iter()
+        .rev()
+        .filter(|e| e.is_sequence())
+        .find(|e| run.is_none_or(|run| e.stem.starts_with(run)))
+        .map(|sequence| {
+            let start = sequence.started;
+            let duration = chrono::Duration::milliseconds(sequence.log.response.duration_ms as i64);
+            (start, start + duration + chrono::Duration::seconds(1))
+        });
+    let has_sequences = entries.iter().any(|e| e.is_sequence());
+    let entries: Vec<LoggedExecution> = match window {
+        Some((start, end)) => entries
+            .into_iter()
+            .filter(|e| e.started >= start && e.started <= end)
+            .collect(),
+        None if has_sequences => Vec::new(),
+        None => entries
+            .into_iter()
+            .filter(|e| run.is_none_or(|run| e.stem.starts_with(run)))
+            .collect(),
+    };
+    if entries.is_empty() {
+        return Err(format!(
+            "No run matching '{}' in {}",
+            run.unwrap_or_default(),
+            dir.display()
+        ));
+    }
+    Ok(entries)
+}
+
 /// Generate TypeScript SDK snippet from MCP tool call
 /// This creates a .ts file alongside the .json execution log
 /// Generate TypeScript SDK snippet from MCP tool call.
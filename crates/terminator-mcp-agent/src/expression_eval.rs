@@ -1,6 +1,58 @@
+//! Expression language for workflow `if` and `jumps` conditions
+//!
+//! Expressions are tokenized, parsed into an [`Expr`] tree and evaluated against
+//! the workflow variables. Precedence, from loosest to tightest:
+//!
+//! - `||`
+//! - `&&`
+//! - `!` (applies to a whole comparison, so `!a == 'x'` is `!(a == 'x')`)
+//! - `==` `!=` `<` `<=` `>` `>=` `in`
+//! - `+` `-`
+//! - `*` `/` `%`
+//! - unary `-`
+//! - member access `a.b`, indexing `a[0]` and `a['key']`
+//!
+//! Functions: `always()`, `contains(a, b)`, `startsWith(a, b)`, `endsWith(a, b)`,
+//! `len(a)`, `matches(a, regex)`, `coalesce(a, b, ...)` and `now()` (the local
+//! time as RFC 3339).
+//!
+//! Undefined variables never raise an error: they are falsy, unequal to everything,
+//! and sort before any value.
+
+use regex::Regex;
 use serde_json::Value;
+use std::cmp::Ordering;
+use std::fmt;
 use tracing::warn;
 
+/// Why an expression could not be parsed or evaluated
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprError {
+    /// Invalid syntax at a character offset of the normalized expression
+    Syntax { message: String, position: usize },
+    /// An unknown function, or a call with the wrong number of arguments
+    Function { name: String, message: String },
+    /// An operator or function applied to values it does not support
+    Type(String),
+    /// `matches()` with an invalid regular expression
+    Regex(String),
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExprError::Syntax { message, position } => {
+                write!(f, "{message} at position {position}")
+            }
+            ExprError::Function { name, message } => write!(f, "{name}(): {message}"),
+            ExprError::Type(message) => write!(f, "{message}"),
+            ExprError::Regex(message) => write!(f, "invalid regex: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ExprError {}
+
 /// Normalizes an expression by replacing smart quotes and other Unicode characters
 /// with their ASCII equivalents to handle copy-paste from various sources.
 fn normalize_expression(expr: &str) -> String {
@@ -29,323 +81,732 @@ pub fn get_value<'a>(path: &str, variables: &'a Value) -> Option<&'a Value> {
     Some(current)
 }
 
-// Main evaluation function.
+/// Evaluate a condition, treating invalid expressions as false.
+///
+/// Prefer [`try_evaluate`] where the error can be reported.
 pub fn evaluate(expression: &str, variables: &Value) -> bool {
-    // Normalize the expression to handle smart quotes and other Unicode characters
-    let normalized = normalize_expression(expression);
-    evaluate_internal(&normalized, variables)
+    match try_evaluate(expression, variables) {
+        Ok(result) => result,
+        Err(e) => {
+            warn!(
+                "Could not evaluate expression '{}': {}. Defaulting to false.",
+                expression, e
+            );
+            false
+        }
+    }
+}
+
+/// Evaluate a condition to its truthiness
+pub fn try_evaluate(expression: &str, variables: &Value) -> Result<bool, ExprError> {
+    let expr = parse(expression)?;
+    Ok(is_truthy(expr.eval(variables)?.as_ref()))
 }
 
-// Internal evaluation function that works with normalized expressions
-fn evaluate_internal(expression: &str, variables: &Value) -> bool {
-    // Trim whitespace
-    let expr = expression.trim();
+/// Evaluate an expression to its value; undefined variables evaluate to `null`
+pub fn evaluate_value(expression: &str, variables: &Value) -> Result<Value, ExprError> {
+    Ok(parse(expression)?.eval(variables)?.unwrap_or(Value::Null))
+}
 
-    // Handle negation operator
-    if let Some(inner_expr) = expr.strip_prefix('!') {
-        let inner_expr = inner_expr.trim();
-        return !evaluate_internal(inner_expr, variables);
+/// Parse an expression, checking function names, arities and literal regexes
+pub fn parse(expression: &str) -> Result<Expr, ExprError> {
+    let normalized = normalize_expression(expression);
+    let tokens = tokenize(&normalized)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.or()?;
+    match parser.peek() {
+        Token::End => Ok(expr),
+        token => Err(parser.error(format!("unexpected {}", token.describe()))),
     }
+}
 
-    // Handle logical operators (&&, ||) with proper precedence
-    if let Some(pos) = expr.find("&&") {
-        let left = &expr[..pos].trim();
-        let right = &expr[pos + 2..].trim();
-        return evaluate_internal(left, variables) && evaluate_internal(right, variables);
+/// Binary operators, loosest binding last
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Mul,
+    Div,
+    Rem,
+    Add,
+    Sub,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+    And,
+    Or,
+}
+
+/// Parsed expression tree
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Value),
+    /// A top-level variable
+    Variable(String),
+    /// `[a, b]`
+    Array(Vec<Expr>),
+    /// `a.b`, `a[0]` and `a['b']`
+    Index(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Str(String),
+    Ident(String),
+    /// Operators and punctuation
+    Op(&'static str),
+    End,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Number(n) => format!("number {n}"),
+            Token::Str(s) => format!("string '{s}'"),
+            Token::Ident(name) => format!("`{name}`"),
+            Token::Op(op) => format!("`{op}`"),
+            Token::End => "end of expression".to_string(),
+        }
     }
+}
 
-    if let Some(pos) = expr.find("||") {
-        let left = &expr[..pos].trim();
-        let right = &expr[pos + 2..].trim();
-        return evaluate_internal(left, variables) || evaluate_internal(right, variables);
+/// Longest operators first, so `<=` is not read as `<`
+const OPERATORS: [&str; 20] = [
+    "&&", "||", "==", "!=", "<=", ">=", "<", ">", "!", "+", "-", "*", "/", "%", "(", ")", "[", "]",
+    ",", ".",
+];
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, ExprError> {
+    let chars: Vec<(usize, char)> = input.char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (start, c) = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '\'' || c == '"' {
+            let mut value = String::new();
+            i += 1;
+            loop {
+                let Some(&(_, next)) = chars.get(i) else {
+                    return Err(ExprError::Syntax {
+                        message: "unterminated string".to_string(),
+                        position: start,
+                    });
+                };
+                i += 1;
+                if next == c {
+                    break;
+                }
+                // Only quotes and backslashes are escaped, so regexes keep their `\d`
+                if next == '\\' {
+                    if let Some(&(_, escaped)) = chars.get(i).filter(|(_, e)| *e == c || *e == '\\')
+                    {
+                        value.push(escaped);
+                        i += 1;
+                        continue;
+                    }
+                }
+                value.push(next);
+            }
+            tokens.push((Token::Str(value), start));
+        } else if c.is_ascii_digit() {
+            let mut end = i;
+            while chars.get(end).is_some_and(|(_, d)| d.is_ascii_digit()) {
+                end += 1;
+            }
+            // A fraction needs digits after the dot, so `items.0.name` keeps its dots
+            if chars.get(end).is_some_and(|(_, d)| *d == '.')
+                && chars.get(end + 1).is_some_and(|(_, d)| d.is_ascii_digit())
+                && !matches!(tokens.last(), Some((Token::Op("."), _)))
+            {
+                end += 1;
+                while chars.get(end).is_some_and(|(_, d)| d.is_ascii_digit()) {
+                    end += 1;
+                }
+            }
+            let text: String = chars[i..end].iter().map(|(_, d)| d).collect();
+            let number = text.parse().map_err(|_| ExprError::Syntax {
+                message: format!("invalid number {text}"),
+                position: start,
+            })?;
+            tokens.push((Token::Number(number), start));
+            i = end;
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = i;
+            loop {
+                match chars.get(end) {
+                    Some((_, d)) if d.is_alphanumeric() || *d == '_' => end += 1,
+                    // Step ids may contain dashes: `login-step_status` is one name,
+                    // while `count-1` (or `a - b`) is a subtraction
+                    Some((_, '-'))
+                        if chars
+                            .get(end + 1)
+                            .is_some_and(|(_, d)| d.is_alphabetic() || *d == '_') =>
+                    {
+                        end += 1
+                    }
+                    _ => break,
+                }
+            }
+            let name: String = chars[i..end].iter().map(|(_, d)| d).collect();
+            tokens.push((Token::Ident(name), start));
+            i = end;
+        } else {
+            let rest = &input[start..];
+            let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) else {
+                return Err(ExprError::Syntax {
+                    message: format!("unexpected character '{c}'"),
+                    position: start,
+                });
+            };
+            tokens.push((Token::Op(op), start));
+            i += op.len();
+        }
     }
+    tokens.push((Token::End, input.len()));
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
 
-    // Try parsing function-based expressions first, e.g., contains(vars, 'value')
-    if let Some(result) = parse_and_evaluate_function(expr, variables) {
-        return result;
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
     }
 
-    // Fallback to simple binary expressions, e.g., vars == 'value'
-    if let Some(result) = parse_and_evaluate_binary_expression(expr, variables) {
-        return result;
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].0.clone();
+        if token != Token::End {
+            self.pos += 1;
+        }
+        token
     }
 
-    // Handle literal boolean values
-    if expr == "true" {
-        return true;
+    fn eat(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Token::Op(o) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
     }
-    if expr == "false" {
-        return false;
+
+    fn expect(&mut self, op: &str) -> Result<(), ExprError> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{op}`, found {}", self.peek().describe())))
+        }
     }
 
-    // Handle simple variable references (evaluate to their boolean truthiness)
-    // This allows expressions like "env.troubleshooting" or "!env.troubleshooting"
-    // where troubleshooting is a boolean
-    if let Some(value) = get_value(expr, variables) {
-        return match value {
-            Value::Bool(b) => *b,
-            Value::String(s) => !s.is_empty() && s != "false" && s != "0",
-            Value::Number(n) => n.as_i64().unwrap_or(0) != 0,
-            Value::Null => false,
-            Value::Array(arr) => !arr.is_empty(),
-            Value::Object(obj) => !obj.is_empty(),
-        };
+    fn error(&self, message: String) -> ExprError {
+        ExprError::Syntax {
+            message,
+            position: self.tokens[self.pos].1,
+        }
     }
 
-    warn!(
-        "Could not parse expression: '{}'. Defaulting to false.",
-        expression
-    );
-    false
-}
+    fn or(&mut self) -> Result<Expr, ExprError> {
+        let mut left = self.and()?;
+        while self.eat("||") {
+            left = Expr::Binary(BinaryOp::Or, Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
 
-// Parses expressions like "contains(policy.product_types, 'FEX')"
-fn parse_and_evaluate_function(expr: &str, variables: &Value) -> Option<bool> {
-    let (func_name, args_str) = expr.split_once('(')?;
-    if !args_str.ends_with(')') {
-        return None;
+    fn and(&mut self) -> Result<Expr, ExprError> {
+        let mut left = self.not()?;
+        while self.eat("&&") {
+            left = Expr::Binary(BinaryOp::And, Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
     }
-    let args_str = &args_str[..args_str.len() - 1]; // Remove trailing ')'
 
-    match func_name.trim() {
-        "always" => {
-            // always() function takes no arguments and always returns true
-            if args_str.trim().is_empty() {
-                Some(true)
+    fn not(&mut self) -> Result<Expr, ExprError> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ExprError> {
+        let left = self.additive()?;
+        let op = match self.peek() {
+            Token::Op("==") => BinaryOp::Eq,
+            Token::Op("!=") => BinaryOp::Ne,
+            Token::Op("<") => BinaryOp::Lt,
+            Token::Op("<=") => BinaryOp::Le,
+            Token::Op(">") => BinaryOp::Gt,
+            Token::Op(">=") => BinaryOp::Ge,
+            Token::Ident(name) if name == "in" => BinaryOp::In,
+            _ => return Ok(left),
+        };
+        self.next();
+        let right = self.additive()?;
+        Ok(Expr::Binary(op, Box::new(left), Box::new(right)))
+    }
+
+    fn additive(&mut self) -> Result<Expr, ExprError> {
+        let mut left = self.multiplicative()?;
+        loop {
+            let op = if self.eat("+") {
+                BinaryOp::Add
+            } else if self.eat("-") {
+                BinaryOp::Sub
             } else {
-                None // always() should not have arguments
-            }
+                return Ok(left);
+            };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.multiplicative()?));
         }
-        _ => {
-            // For other functions, we need exactly 2 arguments
-            let args: Vec<&str> = args_str.split(',').map(|s| s.trim()).collect();
-            if args.len() != 2 {
-                return None;
-            }
+    }
 
-            let val1 = get_value(args[0], variables)?;
-            let val2_str = args[1].trim_matches('\''); // Remove single quotes
+    fn multiplicative(&mut self) -> Result<Expr, ExprError> {
+        let mut left = self.unary()?;
+        loop {
+            let op = if self.eat("*") {
+                BinaryOp::Mul
+            } else if self.eat("/") {
+                BinaryOp::Div
+            } else if self.eat("%") {
+                BinaryOp::Rem
+            } else {
+                return Ok(left);
+            };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.unary()?));
+        }
+    }
 
-            match func_name.trim() {
-                "contains" => Some(evaluate_contains(val1, val2_str)),
-                "startsWith" => Some(val1.as_str()?.starts_with(val2_str)),
-                "endsWith" => Some(val1.as_str()?.ends_with(val2_str)),
-                _ => None,
+    fn unary(&mut self) -> Result<Expr, ExprError> {
+        if self.eat("-") {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Expr, ExprError> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.eat(".") {
+                let key = match self.next() {
+                    Token::Ident(name) => Value::String(name),
+                    Token::Number(n) if n.fract() == 0.0 => Value::from(n as u64),
+                    token => {
+                        self.pos -= usize::from(token != Token::End);
+                        return Err(self.error(format!(
+                            "expected a property name after `.`, found {}",
+                            token.describe()
+                        )));
+                    }
+                };
+                expr = Expr::Index(Box::new(expr), Box::new(Expr::Literal(key)));
+            } else if self.eat("[") {
+                let index = self.or()?;
+                self.expect("]")?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else {
+                return Ok(expr);
             }
         }
     }
-}
 
-// Evaluates the 'contains' function for different types.
-fn evaluate_contains(collection: &Value, item: &str) -> bool {
-    match collection {
-        Value::Array(arr) => arr.iter().any(|v| v.as_str() == Some(item)),
-        Value::String(s) => s.contains(item),
-        _ => false,
+    fn primary(&mut self) -> Result<Expr, ExprError> {
+        let position = self.pos;
+        match self.next() {
+            Token::Number(n) => Ok(Expr::Literal(number_value(n))),
+            Token::Str(s) => Ok(Expr::Literal(Value::String(s))),
+            Token::Ident(name) => match name.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                _ if self.eat("(") => {
+                    let mut args = Vec::new();
+                    if !self.eat(")") {
+                        loop {
+                            args.push(self.or()?);
+                            if self.eat(")") {
+                                break;
+                            }
+                            self.expect(",")?;
+                        }
+                    }
+                    check_call(&name, &args)?;
+                    Ok(Expr::Call(name, args))
+                }
+                _ => Ok(Expr::Variable(name)),
+            },
+            Token::Op("(") => {
+                let expr = self.or()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Op("[") => {
+                let mut items = Vec::new();
+                if !self.eat("]") {
+                    loop {
+                        items.push(self.or()?);
+                        if self.eat("]") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+                Ok(Expr::Array(items))
+            }
+            token => {
+                self.pos = position;
+                Err(self.error(format!("expected a value, found {}", token.describe())))
+            }
+        }
     }
 }
 
-// Parses simple expressions like "variable == 'value'" or "variable == true"
-fn parse_and_evaluate_binary_expression(expr: &str, variables: &Value) -> Option<bool> {
-    // Try to parse comparison operators in order of longest first to avoid partial matches
-    let (var_path, op, raw_rhs) = if let Some(pos) = expr.find(">=") {
-        (&expr[..pos], ">=", &expr[pos + 2..])
-    } else if let Some(pos) = expr.find("<=") {
-        (&expr[..pos], "<=", &expr[pos + 2..])
-    } else if let Some(pos) = expr.find("==") {
-        (&expr[..pos], "==", &expr[pos + 2..])
-    } else if let Some(pos) = expr.find("!=") {
-        (&expr[..pos], "!=", &expr[pos + 2..])
-    } else if let Some(pos) = expr.find('>') {
-        (&expr[..pos], ">", &expr[pos + 1..])
-    } else if let Some(pos) = expr.find('<') {
-        (&expr[..pos], "<", &expr[pos + 1..])
-    } else {
-        return None;
-    };
-
-    let var_path = var_path.trim();
-    let raw_rhs = raw_rhs.trim();
-
-    // Try to get the left-hand side value
-    // Check if LHS is a coalesce() function call
-    let lhs = if var_path.contains("coalesce(") {
-        evaluate_coalesce_to_value(var_path, variables)
-    } else {
-        get_value(var_path, variables).cloned()
+/// Check a function call when it is parsed rather than when it runs
+fn check_call(name: &str, args: &[Expr]) -> Result<(), ExprError> {
+    let arity_ok = match name {
+        "always" | "now" => args.is_empty(),
+        "len" => args.len() == 1,
+        "contains" | "startsWith" | "endsWith" | "matches" => args.len() == 2,
+        "coalesce" => args.len() >= 2,
+        _ => {
+            return Err(ExprError::Function {
+                name: name.to_string(),
+                message: "unknown function".to_string(),
+            })
+        }
     };
-
-    // Handle undefined variables gracefully
-    if lhs.is_none() {
-        // For equality operators, undefined is never equal to anything
-        // For inequality operators, undefined is always not equal to anything
-        // For numeric comparisons, undefined is treated as less than any value
-        return Some(match op {
-            "==" => false, // undefined == anything → false
-            "!=" => true,  // undefined != anything → true
-            ">" => false,  // undefined > anything → false
-            "<" => true,   // undefined < anything → true (treat as 0 or null)
-            ">=" => false, // undefined >= anything → false
-            "<=" => true,  // undefined <= anything → true
-            _ => false,
+    if !arity_ok {
+        let expected = match name {
+            "always" | "now" => "no arguments",
+            "len" => "1 argument",
+            "coalesce" => "at least 2 arguments",
+            _ => "2 arguments",
+        };
+        return Err(ExprError::Function {
+            name: name.to_string(),
+            message: format!("expected {expected}, got {}", args.len()),
         });
     }
+    if let ("matches", Some(Expr::Literal(Value::String(pattern)))) = (name, args.get(1)) {
+        Regex::new(pattern).map_err(|e| ExprError::Regex(e.to_string()))?;
+    }
+    Ok(())
+}
 
-    let lhs = &lhs.unwrap();
+fn number_value(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+        Value::from(n as i64)
+    } else {
+        serde_json::Number::from_f64(n).map_or(Value::Null, Value::Number)
+    }
+}
 
-    // For equality/inequality operators, use smart comparison
-    if op == "==" || op == "!=" {
-        let are_equal = match raw_rhs {
-            "true" => lhs.as_bool() == Some(true),
-            "false" => lhs.as_bool() == Some(false),
-            _ if raw_rhs.starts_with('\'') && raw_rhs.ends_with('\'') => {
-                let rhs_str = raw_rhs.trim_matches('\'');
-                compare_values_smart(lhs, rhs_str)
+/// Evaluation result; `None` is an undefined variable
+type Eval = Result<Option<Value>, ExprError>;
+
+impl Expr {
+    /// Evaluate against the variables; `None` means the value is undefined
+    pub fn eval(&self, variables: &Value) -> Eval {
+        match self {
+            Expr::Literal(value) => Ok(Some(value.clone())),
+            Expr::Variable(name) => Ok(variables.get(name).cloned()),
+            Expr::Array(items) => items
+                .iter()
+                .map(|item| Ok(item.eval(variables)?.unwrap_or(Value::Null)))
+                .collect::<Result<Vec<_>, _>>()
+                .map(|items| Some(Value::Array(items))),
+            Expr::Index(target, key) => {
+                let (Some(target), Some(key)) = (target.eval(variables)?, key.eval(variables)?)
+                else {
+                    return Ok(None);
+                };
+                Ok(index(&target, &key))
             }
-            _ if raw_rhs.starts_with('"') && raw_rhs.ends_with('"') => {
-                let rhs_str = raw_rhs.trim_matches('"');
-                compare_values_smart(lhs, rhs_str)
+            Expr::Not(inner) => Ok(Some(Value::Bool(!is_truthy(
+                inner.eval(variables)?.as_ref(),
+            )))),
+            Expr::Neg(inner) => {
+                let value = inner.eval(variables)?;
+                let n = to_number(value.as_ref()).ok_or_else(|| {
+                    ExprError::Type(format!("cannot negate {}", describe(value.as_ref())))
+                })?;
+                Ok(Some(number_value(-n)))
             }
-            _ => {
-                // Try as bare number or literal
-                compare_values_smart(lhs, raw_rhs)
+            Expr::Binary(BinaryOp::And, left, right) => {
+                if !is_truthy(left.eval(variables)?.as_ref()) {
+                    return Ok(Some(Value::Bool(false)));
+                }
+                Ok(Some(Value::Bool(is_truthy(
+                    right.eval(variables)?.as_ref(),
+                ))))
             }
-        };
-
-        return match op {
-            "==" => Some(are_equal),
-            "!=" => Some(!are_equal),
-            _ => None,
-        };
+            Expr::Binary(BinaryOp::Or, left, right) => {
+                if is_truthy(left.eval(variables)?.as_ref()) {
+                    return Ok(Some(Value::Bool(true)));
+                }
+                Ok(Some(Value::Bool(is_truthy(
+                    right.eval(variables)?.as_ref(),
+                ))))
+            }
+            Expr::Binary(op, left, right) => {
+                binary(*op, left.eval(variables)?, right.eval(variables)?)
+            }
+            Expr::Call(name, args) => call(name, args, variables),
+        }
     }
+}
 
-    // For numeric comparison operators (>, <, >=, <=)
-    // Only numeric operators reach here, equality operators already returned
-
-    // Try to extract numeric value from LHS
-    let lhs_num = match lhs {
-        Value::Number(n) => n.as_f64(),
-        Value::String(s) => s.parse::<f64>().ok(),
-        Value::Bool(true) => Some(1.0),
-        Value::Bool(false) => Some(0.0),
-        Value::Null => Some(0.0),
+fn index(target: &Value, key: &Value) -> Option<Value> {
+    match (target, key) {
+        (Value::Object(map), Value::String(k)) => map.get(k).cloned(),
+        (Value::Object(map), Value::Number(n)) => map.get(&n.to_string()).cloned(),
+        (Value::Array(items), Value::Number(n)) => {
+            let i = n.as_i64()?;
+            // Negative indexes count from the end
+            let i = if i < 0 { items.len() as i64 + i } else { i };
+            items.get(usize::try_from(i).ok()?).cloned()
+        }
+        (Value::String(s), Value::Number(n)) => {
+            let i = usize::try_from(n.as_i64()?).ok()?;
+            s.chars().nth(i).map(|c| Value::String(c.to_string()))
+        }
         _ => None,
-    };
-
-    // Try to extract numeric value from RHS
-    let rhs_num = if raw_rhs == "true" {
-        Some(1.0)
-    } else if raw_rhs == "false" || raw_rhs == "null" {
-        Some(0.0)
-    } else if raw_rhs.starts_with('\'') && raw_rhs.ends_with('\'') {
-        raw_rhs.trim_matches('\'').parse::<f64>().ok()
-    } else if raw_rhs.starts_with('"') && raw_rhs.ends_with('"') {
-        raw_rhs.trim_matches('"').parse::<f64>().ok()
-    } else {
-        // Try parsing as bare number
-        raw_rhs.parse::<f64>().ok()
-    };
+    }
+}
 
-    // Both sides must be numeric for comparison
-    if let (Some(l), Some(r)) = (lhs_num, rhs_num) {
-        return Some(match op {
-            ">" => l > r,
-            "<" => l < r,
-            ">=" => l >= r,
-            "<=" => l <= r,
-            _ => false,
-        });
+fn binary(op: BinaryOp, left: Option<Value>, right: Option<Value>) -> Eval {
+    let bool_result = |b: bool| Ok(Some(Value::Bool(b)));
+    match op {
+        BinaryOp::Eq | BinaryOp::Ne => {
+            // Undefined is never equal to anything
+            let equal = match (&left, &right) {
+                (Some(l), Some(r)) => loose_eq(l, r),
+                _ => false,
+            };
+            bool_result(equal == (op == BinaryOp::Eq))
+        }
+        BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => {
+            // Undefined sorts before any value
+            let ordering = match (&left, &right) {
+                (None, _) => Ordering::Less,
+                (_, None) => Ordering::Greater,
+                (Some(l), Some(r)) => compare(l, r)?,
+            };
+            bool_result(match op {
+                BinaryOp::Lt => ordering == Ordering::Less,
+                BinaryOp::Le => ordering != Ordering::Greater,
+                BinaryOp::Gt => ordering == Ordering::Greater,
+                _ => ordering != Ordering::Less,
+            })
+        }
+        BinaryOp::In => {
+            let (Some(item), Some(collection)) = (&left, &right) else {
+                return bool_result(false);
+            };
+            match collection {
+                Value::Array(items) => bool_result(items.iter().any(|v| loose_eq(v, item))),
+                Value::String(s) => bool_result(s.contains(&*display(item))),
+                Value::Object(map) => bool_result(map.contains_key(&*display(item))),
+                other => Err(ExprError::Type(format!(
+                    "`in` needs an array, string or object, got {}",
+                    describe(Some(other))
+                ))),
+            }
+        }
+        BinaryOp::Add => match (&left, &right) {
+            (Some(Value::String(l)), r) => Ok(Some(Value::String(format!(
+                "{l}{}",
+                r.as_ref().map(display).unwrap_or_default()
+            )))),
+            (l, Some(Value::String(r))) => Ok(Some(Value::String(format!(
+                "{}{r}",
+                l.as_ref().map(display).unwrap_or_default()
+            )))),
+            _ => arithmetic(op, left.as_ref(), right.as_ref()),
+        },
+        _ => arithmetic(op, left.as_ref(), right.as_ref()),
     }
+}
 
-    // If we can't parse as numbers, the comparison fails
-    None
+fn arithmetic(op: BinaryOp, left: Option<&Value>, right: Option<&Value>) -> Eval {
+    let symbol = match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Sub => "-",
+        BinaryOp::Mul => "*",
+        BinaryOp::Div => "/",
+        _ => "%",
+    };
+    let (Some(l), Some(r)) = (to_number(left), to_number(right)) else {
+        return Err(ExprError::Type(format!(
+            "cannot apply `{symbol}` to {} and {}",
+            describe(left),
+            describe(right)
+        )));
+    };
+    if matches!(op, BinaryOp::Div | BinaryOp::Rem) && r == 0.0 {
+        return Err(ExprError::Type(format!("`{symbol}` by zero")));
+    }
+    Ok(Some(number_value(match op {
+        BinaryOp::Add => l + r,
+        BinaryOp::Sub => l - r,
+        BinaryOp::Mul => l * r,
+        BinaryOp::Div => l / r,
+        _ => l % r,
+    })))
 }
 
-// Smart comparison that handles type coercion between strings and booleans
-fn compare_values_smart(lhs: &Value, rhs_str: &str) -> bool {
-    match lhs {
-        Value::String(s) => s == rhs_str,
-        Value::Bool(true) => rhs_str == "true" || rhs_str == "1",
-        Value::Bool(false) => rhs_str == "false" || rhs_str == "0",
-        Value::Number(n) => rhs_str.parse::<f64>().ok() == Some(n.as_f64().unwrap_or(0.0)),
-        _ => false,
+fn call(name: &str, args: &[Expr], variables: &Value) -> Eval {
+    let values = |variables: &Value| -> Result<Vec<Option<Value>>, ExprError> {
+        args.iter().map(|arg| arg.eval(variables)).collect()
+    };
+    let bool_result = |b: bool| Ok(Some(Value::Bool(b)));
+    match name {
+        "always" => bool_result(true),
+        "now" => Ok(Some(Value::String(chrono::Local::now().to_rfc3339()))),
+        "coalesce" => {
+            // The first truthy argument, or else the last one
+            for arg in args {
+                let value = arg.eval(variables)?;
+                if is_truthy(value.as_ref()) {
+                    return Ok(value);
+                }
+            }
+            match args.last() {
+                Some(last) => last.eval(variables),
+                None => Ok(None),
+            }
+        }
+        "len" => match &values(variables)?[0] {
+            None | Some(Value::Null) => Ok(Some(Value::from(0))),
+            Some(Value::String(s)) => Ok(Some(Value::from(s.chars().count()))),
+            Some(Value::Array(items)) => Ok(Some(Value::from(items.len()))),
+            Some(Value::Object(map)) => Ok(Some(Value::from(map.len()))),
+            Some(other) => Err(ExprError::Type(format!(
+                "len() needs a string, array or object, got {}",
+                describe(Some(other))
+            ))),
+        },
+        "contains" | "startsWith" | "endsWith" => {
+            let values = values(variables)?;
+            let (Some(subject), Some(item)) = (&values[0], &values[1]) else {
+                return bool_result(false);
+            };
+            let item = display(item);
+            bool_result(match (name, subject) {
+                ("contains", Value::Array(items)) => {
+                    items.iter().any(|v| v.as_str() == Some(item.as_str()))
+                }
+                ("contains", Value::String(s)) => s.contains(&*item),
+                ("startsWith", Value::String(s)) => s.starts_with(&*item),
+                ("endsWith", Value::String(s)) => s.ends_with(&*item),
+                _ => false,
+            })
+        }
+        "matches" => {
+            let values = values(variables)?;
+            let Some(subject) = &values[0] else {
+                return bool_result(false);
+            };
+            let pattern = values[1].as_ref().map(display).unwrap_or_default();
+            let regex = Regex::new(&pattern).map_err(|e| ExprError::Regex(e.to_string()))?;
+            bool_result(regex.is_match(&display(subject)))
+        }
+        _ => Err(ExprError::Function {
+            name: name.to_string(),
+            message: "unknown function".to_string(),
+        }),
     }
 }
 
-/// Helper to check if a value is truthy
-fn is_truthy(val: &Value) -> bool {
-    match val {
-        Value::Bool(b) => *b,
-        Value::String(s) => !s.is_empty() && s != "false" && s != "0",
-        Value::Number(n) => n.as_f64().unwrap_or(0.0) != 0.0,
-        Value::Null => false,
-        Value::Array(arr) => !arr.is_empty(),
-        Value::Object(obj) => !obj.is_empty(),
+/// Equality with the coercions workflow authors expect: `'1' == 1`, `true == 'true'`
+fn loose_eq(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(l), Value::Number(r)) => l.as_f64() == r.as_f64(),
+        (Value::Bool(b), Value::String(s)) | (Value::String(s), Value::Bool(b)) => {
+            if *b {
+                s == "true" || s == "1"
+            } else {
+                s == "false" || s == "0"
+            }
+        }
+        (Value::Number(n), Value::String(s)) | (Value::String(s), Value::Number(n)) => {
+            s.trim().parse::<f64>().ok() == n.as_f64()
+        }
+        (Value::Number(n), Value::Bool(b)) | (Value::Bool(b), Value::Number(n)) => {
+            n.as_f64() == Some(if *b { 1.0 } else { 0.0 })
+        }
+        _ => left == right,
     }
 }
 
-/// Helper to parse a literal string as a Value
-fn parse_literal_value(literal: &str) -> Value {
-    // Try parsing as number
-    if let Ok(n) = literal.parse::<i64>() {
-        return Value::Number(n.into());
-    }
-    if let Ok(n) = literal.parse::<f64>() {
-        if let Some(num) = serde_json::Number::from_f64(n) {
-            return Value::Number(num);
+fn compare(left: &Value, right: &Value) -> Result<Ordering, ExprError> {
+    if let (Value::String(l), Value::String(r)) = (left, right) {
+        if let (Ok(l), Ok(r)) = (l.trim().parse::<f64>(), r.trim().parse::<f64>()) {
+            return Ok(l.partial_cmp(&r).unwrap_or(Ordering::Equal));
         }
+        return Ok(l.cmp(r));
     }
-
-    // Parse as boolean
-    match literal {
-        "true" => Value::Bool(true),
-        "false" => Value::Bool(false),
-        "null" => Value::Null,
-        _ => Value::String(literal.to_string()),
+    match (to_number(Some(left)), to_number(Some(right))) {
+        (Some(l), Some(r)) => Ok(l.partial_cmp(&r).unwrap_or(Ordering::Equal)),
+        _ => Err(ExprError::Type(format!(
+            "cannot compare {} with {}",
+            describe(Some(left)),
+            describe(Some(right))
+        ))),
     }
 }
 
-/// Evaluates coalesce() and returns the actual Value
-/// Returns the first truthy value from variables, or the last argument as default
-fn evaluate_coalesce_to_value(expr: &str, variables: &Value) -> Option<Value> {
-    // Extract function call: "coalesce(x, y, z, default)"
-    let func_start = expr.find("coalesce(")?;
-    let args_start = func_start + "coalesce(".len();
-    let args_end = expr[args_start..].find(')')?;
-    let args_str = &expr[args_start..args_start + args_end];
-
-    let args: Vec<&str> = args_str.split(',').map(|s| s.trim()).collect();
-
-    if args.len() < 2 {
-        return None;
+/// Numeric value of numbers, numeric strings, booleans and null
+fn to_number(value: Option<&Value>) -> Option<f64> {
+    match value? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        Value::Null => Some(0.0),
+        _ => None,
     }
+}
 
-    // coalesce(a, b, c, default) semantics:
-    // - Check ALL arguments (including last) as potential variables
-    // - Return the first one that exists AND is truthy
-    // - If none are truthy, return the last argument (as variable or literal)
-
-    for arg in &args {
-        if let Some(val) = get_value(arg, variables) {
-            // Variable exists - check if it's truthy
-            if is_truthy(val) {
-                return Some(val.clone());
-            }
-            // Variable exists but is falsy - continue to next argument
-        }
-        // Variable doesn't exist - continue to next argument
+/// Text of a value, without quotes for strings
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
     }
+}
 
-    // No truthy variables found, use last argument as default
-    let default_arg = args.last()?.trim_matches(|c| c == '\'' || c == '"');
-
-    // Try as variable first (might be a falsy variable that we'll return anyway)
-    if let Some(val) = get_value(default_arg, variables) {
-        return Some(val.clone());
+fn describe(value: Option<&Value>) -> &'static str {
+    match value {
+        None => "undefined",
+        Some(Value::Null) => "null",
+        Some(Value::Bool(_)) => "a boolean",
+        Some(Value::Number(_)) => "a number",
+        Some(Value::String(_)) => "a string",
+        Some(Value::Array(_)) => "an array",
+        Some(Value::Object(_)) => "an object",
     }
+}
 
-    // Not a variable, parse as literal
-    Some(parse_literal_value(default_arg))
+/// Helper to check if a value is truthy
+pub fn is_truthy(val: Option<&Value>) -> bool {
+    match val {
+        None | Some(Value::Null) => false,
+        Some(Value::Bool(b)) => *b,
+        Some(Value::String(s)) => !s.is_empty() && s != "false" && s != "0",
+        Some(Value::Number(n)) => n.as_f64().unwrap_or(0.0) != 0.0,
+        Some(Value::Array(arr)) => !arr.is_empty(),
+        Some(Value::Object(obj)) => !obj.is_empty(),
+    }
 }
 
 #[cfg(test)]
@@ -1,24 +1,40 @@
-use crate::expression_eval;
 use crate::mcp_types::TreeOutputFormat;
 use crate::tree_formatter::{format_tree_as_compact_yaml, format_ui_node_as_compact_yaml};
 use crate::utils::ToolCall;
 use regex::Regex;
 use rmcp::ErrorData as McpError;
-use serde_json::{json, Value};
+use serde_json::{json, Map, Value};
 use std::collections::HashMap;
 use std::time::Duration;
-use terminator::{AutomationError, Desktop, Selector, UIElement};
-
-/// Normalize key format to ensure curly brace syntax for special keys.
-/// If key already contains `{`, assume it's correctly formatted.
-/// Otherwise, wrap the entire key in `{}` to ensure it's treated as a special key press.
-/// Examples: "Enter" -> "{Enter}", "{Ctrl}c" -> "{Ctrl}c" (unchanged)
-pub fn normalize_key(key: &str) -> String {
-    if key.contains('{') {
-        key.to_string()
+use terminator::keys::{Key, KeyItem};
+use terminator::{
+    format_tree_with_budget, format_ui_node_with_budget, AutomationError, BudgetedTreeResult,
+    Desktop, KeyParseError, KeySequence, Selector, TreeBudget, UIElement,
+};
+
+/// Normalize a key to the shared key sequence syntax (see `terminator::keys`).
+/// Keys containing `{` are parsed as key sequences; anything else is read as a
+/// single key or `+` chord, so bare key names keep working.
+/// Examples: "Enter" -> "{Enter}", "ctrl+c" -> "{Ctrl}c", "{esc}" -> "{Escape}"
+pub fn normalize_key(key: &str) -> Result<String, KeyParseError> {
+    let sequence = if key.contains('{') {
+        KeySequence::parse(key)?
     } else {
-        format!("{{{}}}", key)
-    }
+        KeySequence::from_chord(key)?
+    };
+    Ok(sequence.to_string())
+}
+
+/// `invalid_params` error for a key that doesn't parse, with suggested spellings
+pub fn invalid_key_error(error: KeyParseError) -> McpError {
+    McpError::invalid_params(
+        format!("Invalid key: {error}"),
+        Some(json!({
+            "key": error.input,
+            "span": [error.span.start, error.span.end],
+            "suggestions": error.suggestions,
+        })),
+    )
 }
 
 /// Extract trailing special keys from text for type_into_element.
@@ -26,7 +42,7 @@ pub fn normalize_key(key: &str) -> String {
 /// This function splits the text into the actual text to type and the trailing keys to press.
 ///
 /// Only detects keys at the END of the string. Keys in the middle are typed literally.
-/// Whitelist: {Enter}, {Return}, {Tab}, {Escape}, {Esc} (case-insensitive)
+/// Whitelist: {Enter}, {Tab}, {Escape} and their aliases, optionally repeated ({Tab 2})
 ///
 /// Examples:
 /// - "mediar.ai{Enter}" -> ("mediar.ai", vec!["{Enter}"])
@@ -35,49 +51,33 @@ pub fn normalize_key(key: &str) -> String {
 /// - "{Enter}" -> ("", vec!["{Enter}"])
 /// - "plain text" -> ("plain text", vec![])
 pub fn extract_trailing_keys(text: &str) -> (String, Vec<String>) {
-    // Pattern: one or more special keys at the END of string
-    // Case-insensitive match for: Enter, Return, Tab, Escape, Esc
-    let pattern = r"(?i)(\{(?:Enter|Return|Tab|Escape|Esc)\})+$";
-    let re = match Regex::new(pattern) {
-        Ok(r) => r,
-        Err(_) => return (text.to_string(), vec![]),
-    };
-
-    if let Some(m) = re.find(text) {
-        let keys_part = m.as_str();
-        let text_part = &text[..m.start()];
-
-        // Extract individual keys from the matched portion
-        let key_re = match Regex::new(r"(?i)\{([^}]+)\}") {
-            Ok(r) => r,
-            Err(_) => return (text.to_string(), vec![]),
+    let mut rest = text;
+    let mut keys = Vec::new();
+    while let Some(open) = rest.strip_suffix('}').and_then(|r| r.rfind('{')) {
+        let Ok(sequence) = KeySequence::parse(&rest[open..]) else {
+            break;
         };
+        match sequence.items() {
+            [KeyItem::Key {
+                key: Key::Enter | Key::Tab | Key::Escape,
+                ..
+            }] => {
+                keys.insert(0, sequence.to_string());
+                rest = &rest[..open];
+            }
+            _ => break,
+        }
+    }
 
-        let keys: Vec<String> = key_re
-            .captures_iter(keys_part)
-            .map(|c| {
-                // Normalize to proper casing: {Enter}, {Tab}, {Escape}
-                let key_name = &c[1];
-                match key_name.to_lowercase().as_str() {
-                    "enter" | "return" => "{Enter}".to_string(),
-                    "tab" => "{Tab}".to_string(),
-                    "escape" | "esc" => "{Escape}".to_string(),
-                    _ => format!("{{{}}}", key_name),
-                }
-            })
-            .collect();
-
+    if !keys.is_empty() {
         tracing::info!(
             "[extract_trailing_keys] Extracted from '{}': text='{}', keys={:?}",
             text,
-            text_part,
+            rest,
             keys
         );
-
-        (text_part.to_string(), keys)
-    } else {
-        (text.to_string(), vec![])
     }
+    (rest.to_string(), keys)
 }
 
 /// Helper function to parse comma-separated alternative selectors into a Vec<String>
@@ -150,13 +150,21 @@ pub fn build_element_not_found_error(
     fallback: Option<&str>,
     original_error: anyhow::Error,
 ) -> McpError {
+    let automation_error = original_error
+        .chain()
+        .find_map(|e| e.downcast_ref::<AutomationError>());
+    let tag = |error: McpError| match automation_error {
+        Some(source) => with_error_kind(error, source),
+        None => error,
+    };
+
     // Check if the underlying error is UIAutomationAPIError
     if let Some(AutomationError::UIAutomationAPIError {
         message,
         com_error,
         operation,
         is_retryable,
-    }) = original_error.downcast_ref::<AutomationError>()
+    }) = automation_error
     {
         let error_details = json!({
             "error_type": "ui_automation_api_failure",
@@ -172,23 +180,72 @@ pub fn build_element_not_found_error(
             }
         });
 
-        return McpError::invalid_params("Windows UI Automation API failure", Some(error_details));
+        return tag(McpError::invalid_params(
+            "Windows UI Automation API failure",
+            Some(error_details),
+        ));
     }
 
     let selectors_tried = get_selectors_tried_all(primary_selector, alternatives, fallback);
-    let error_payload = json!({
+    let selector_errors = selector_parse_errors(&selectors_tried);
+    let mut suggestions = vec![
+        "Call `get_window_tree` again to get a fresh view of the UI; it might have changed.",
+        "Verify the element's 'name' and 'role' in the new UI tree. The 'name' attribute might be empty or different from the visible text.",
+        "If the element has no 'name', use its numeric ID selector (e.g., '#12345'). This is required for many clickable 'Group' elements.",
+        "Use `validate_element` (which never throws errors) to debug existence issues, or check if the element is conditionally rendered and may not always be present."
+    ];
+    if !selector_errors.is_empty() {
+        suggestions.insert(
+            0,
+            "Fix the selector syntax reported in `selector_errors` first; malformed selectors are guessed at and rarely match.",
+        );
+    }
+    let mut error_payload = json!({
         "error_type": "ElementNotFound",
         "message": format!("The specified element could not be found after trying all selectors. Original error: {}", original_error),
         "selectors_tried": selectors_tried,
-        "suggestions": [
-            "Call `get_window_tree` again to get a fresh view of the UI; it might have changed.",
-            "Verify the element's 'name' and 'role' in the new UI tree. The 'name' attribute might be empty or different from the visible text.",
-            "If the element has no 'name', use its numeric ID selector (e.g., '#12345'). This is required for many clickable 'Group' elements.",
-            "Use `validate_element` (which never throws errors) to debug existence issues, or check if the element is conditionally rendered and may not always be present."
-        ]
+        "suggestions": suggestions
     });
+    if !selector_errors.is_empty() {
+        error_payload["selector_errors"] = json!(selector_errors);
+    }
+
+    tag(McpError::invalid_params(
+        "Element not found",
+        Some(error_payload),
+    ))
+}
 
-    McpError::invalid_params("Element not found", Some(error_payload))
+/// Records the [`AutomationError`] variant behind `error` in its data as
+/// `error_kind`, where `metrics::error_kind` reads it back
+pub fn with_error_kind(mut error: McpError, source: &AutomationError) -> McpError {
+    let mut data = match error.data.take() {
+        Some(Value::Object(map)) => map,
+        Some(other) => Map::from_iter([("details".to_string(), other)]),
+        None => Map::new(),
+    };
+    data.insert("error_kind".to_string(), json!(source.kind()));
+    error.data = Some(Value::Object(data));
+    error
+}
+
+/// Syntax errors for the selectors that do not parse, each with the offending
+/// byte span and a rendered caret line pointing at it.
+pub fn selector_parse_errors(selectors: &[String]) -> Vec<Value> {
+    selectors
+        .iter()
+        .filter_map(|selector| {
+            let error = Selector::parse(selector).err()?;
+            Some(json!({
+                "selector": selector,
+                "message": error.message,
+                "span": { "start": error.span.start, "end": error.span.end },
+                "expected": error.expected,
+                "suggestion": error.suggestion,
+                "rendered": error.render(selector),
+            }))
+        })
+        .collect()
 }
 
 /// Converts a variable path to a JSON pointer.
@@ -199,7 +256,7 @@ pub fn build_element_not_found_error(
 ///   "keywords[0]" -> "/keywords/0"
 ///   "data.items[2].name" -> "/data/items/2/name"
 ///   "arr[0][1]" -> "/arr/0/1"
-fn variable_path_to_pointer(path: &str) -> String {
+pub(crate) fn variable_path_to_pointer(path: &str) -> String {
     // First replace dots with slashes
     let result = path.replace('.', "/");
     // Then replace [N] with /N using regex
@@ -209,152 +266,13 @@ fn variable_path_to_pointer(path: &str) -> String {
 }
 
 /// Substitutes `{{variable}}` placeholders in a JSON value.
+///
+/// Renders every string with the [`crate::template`] engine, leaving
+/// placeholders that cannot be resolved as they are. Use
+/// [`crate::template::render_value`] to find out which ones those were.
 pub fn substitute_variables(args: &mut Value, variables: &Value) {
-    use tracing::debug;
-
-    match args {
-        Value::Object(map) => {
-            for (key, value) in map {
-                debug!("Processing object key: {}", key);
-                substitute_variables(value, variables);
-            }
-        }
-        Value::Array(arr) => {
-            for (i, value) in arr.iter_mut().enumerate() {
-                debug!("Processing array index: {}", i);
-                substitute_variables(value, variables);
-            }
-        }
-        Value::String(s) => {
-            debug!("Processing string: '{}'", s);
-            // This regex finds all occurrences of {{...}} and ${{...}} non-greedily.
-            // It supports the traditional `{{variable}}` style as well as the GitHub Actions
-            // style `${{ variable }}` by making the leading `$` optional.
-            // Examples matched:
-            //   "{{my_var}}"
-            //   "${{my_var}}"
-            //   "role:Button|name:${{button_name}}"
-            let re = Regex::new(r"\$?\{\{(.*?)\}\}").unwrap();
-
-            // Handle full string replacement first, e.g., args is "{{my_var}}" or an expression.
-            if let Some(caps) = re.captures(s) {
-                if caps.get(0).unwrap().as_str() == s {
-                    let inner_str = caps.get(1).unwrap().as_str().trim();
-                    debug!(
-                        "Found full string placeholder: '{}' with inner: '{}'",
-                        s, inner_str
-                    );
-
-                    // Check if it's a simple variable path (supports array indexing like [0]).
-                    let is_simple_var = inner_str.chars().all(|c| {
-                        c.is_ascii_alphanumeric()
-                            || c == '_'
-                            || c == '-'
-                            || c == '.'
-                            || c == '['
-                            || c == ']'
-                    });
-
-                    if is_simple_var {
-                        let pointer = variable_path_to_pointer(inner_str);
-                        debug!("Looking up simple variable with pointer: '{}'", pointer);
-                        if let Some(replacement_val) = variables.pointer(&pointer) {
-                            debug!("Found replacement value: {}", replacement_val);
-                            *args = replacement_val.clone();
-                        } else {
-                            debug!("Variable '{}' not found in context", inner_str);
-                        }
-                        // If variable is not found, leave the placeholder as is.
-                        return;
-                    }
-
-                    // Check if it looks like an expression we should evaluate.
-                    let is_expression = inner_str.contains('(')
-                        || inner_str.contains("==")
-                        || inner_str.contains("!=")
-                        || inner_str.contains("contains")
-                        || inner_str.contains("startsWith")
-                        || inner_str.contains("endsWith");
-
-                    if is_expression {
-                        debug!("Evaluating expression: '{}'", inner_str);
-                        let eval_result = expression_eval::evaluate(inner_str, variables);
-                        debug!("Expression result: {}", eval_result);
-                        *args = Value::Bool(eval_result);
-                    }
-                    // If it's not a simple variable and not a recognized expression, leave it as is.
-                    return;
-                }
-            }
-
-            // Handle partial replacement within a larger string.
-            let original_s = s.clone();
-            let new_s = re
-                .replace_all(s, |caps: &regex::Captures| {
-                    // Because the regex allows an optional leading `$`, the capture group index
-                    // for the inner contents remains at 1 regardless of whether the `$` is
-                    // present. We therefore consistently pull out capture 1 here.
-                    let inner_str = caps.get(1).unwrap().as_str().trim();
-                    debug!(
-                        "Found partial placeholder: '{}' with inner: '{}'",
-                        caps.get(0).unwrap().as_str(),
-                        inner_str
-                    );
-
-                    let is_simple_var = inner_str.chars().all(|c| {
-                        c.is_ascii_alphanumeric()
-                            || c == '_'
-                            || c == '-'
-                            || c == '.'
-                            || c == '['
-                            || c == ']'
-                    });
-
-                    if is_simple_var {
-                        let pointer = variable_path_to_pointer(inner_str);
-                        debug!("Looking up simple variable with pointer: '{}'", pointer);
-                        if let Some(val) = variables.pointer(&pointer) {
-                            if val.is_string() {
-                                debug!("Found string replacement: '{}'", val.as_str().unwrap());
-                                val.as_str().unwrap().to_string()
-                            } else {
-                                debug!("Found non-string replacement: '{}'", val);
-                                val.to_string()
-                            }
-                        } else {
-                            debug!("Variable '{}' not found in context", inner_str);
-                            // Variable not found, keep original placeholder.
-                            caps.get(0).unwrap().as_str().to_string()
-                        }
-                    } else {
-                        // Not a simple variable, assume it's either an expression or text to be ignored.
-                        let is_expression = inner_str.contains('(')
-                            || inner_str.contains("==")
-                            || inner_str.contains("!=")
-                            || inner_str.contains("contains")
-                            || inner_str.contains("startsWith")
-                            || inner_str.contains("endsWith");
-
-                        if is_expression {
-                            debug!("Evaluating partial expression: '{}'", inner_str);
-                            let bool_val = expression_eval::evaluate(inner_str, variables);
-                            debug!("Expression result: {}", bool_val);
-                            bool_val.to_string()
-                        } else {
-                            debug!("Unknown placeholder type: '{}'", inner_str);
-                            // Not a known expression type, keep original placeholder.
-                            caps.get(0).unwrap().as_str().to_string()
-                        }
-                    }
-                })
-                .to_string();
-
-            if original_s != new_s {
-                debug!("String substitution: '{}' -> '{}'", original_s, new_s);
-            }
-            *s = new_s;
-        }
-        _ => {} // Other types are left as is
+    for issue in crate::template::render_value(args, variables) {
+        tracing::debug!("Template placeholder not substituted: {}", issue);
     }
 }
 
@@ -482,6 +400,7 @@ pub type UiaBoundsCache = HashMap<u32, (String, String, (f64, f64, f64, f64), Op
 
 // Helper to optionally attach UI tree to response
 // Returns the UIA bounds cache if tree was formatted in CompactYaml mode
+// With a budget, the compact output is trimmed and collapsed groups are listed in "tree_collapsed"
 #[allow(clippy::too_many_arguments)]
 pub async fn maybe_attach_tree(
     desktop: &Desktop,
@@ -494,6 +413,7 @@ pub async fn maybe_attach_tree(
     result_json: &mut Value,
     found_element: Option<&terminator::UIElement>,
     include_all_bounds: bool,
+    tree_budget: Option<&TreeBudget>,
 ) -> Option<UiaBoundsCache> {
     // Check if tree should be included
     if !include_tree_after_action {
@@ -519,13 +439,23 @@ pub async fn maybe_attach_tree(
 
     // Helper function to format SerializableUIElement based on output format
     // Returns (json_value, Option<bounds_cache>)
-    let format_serializable_tree = |tree: terminator::element::SerializableUIElement| -> (Result<Value, String>, Option<UiaBoundsCache>) {
+    let format_serializable_tree = |tree: terminator::element::SerializableUIElement,
+                                    result_json: &mut Value|
+     -> (Result<Value, String>, Option<UiaBoundsCache>) {
         match format {
             TreeOutputFormat::CompactYaml | TreeOutputFormat::ClusteredYaml => {
+                if let Some(budget) = tree_budget {
+                    return match format_tree_with_budget(&tree, budget) {
+                        Ok(result) => attach_budgeted_tree(result, result_json),
+                        Err(e) => (Err(e.to_string()), None),
+                    };
+                }
                 let result = format_tree_as_compact_yaml(&tree, 0);
                 (Ok(json!(result.formatted)), Some(result.index_to_bounds))
             }
-            TreeOutputFormat::VerboseJson => (serde_json::to_value(tree).map_err(|e| e.to_string()), None),
+            TreeOutputFormat::VerboseJson => {
+                (serde_json::to_value(tree).map_err(|e| e.to_string()), None)
+            }
         }
     };
 
@@ -535,13 +465,20 @@ pub async fn maybe_attach_tree(
             if let Some(element) = found_element {
                 let max_depth = tree_max_depth.unwrap_or(30);
                 let subtree = element.to_serializable_tree(max_depth);
-                let (tree_result, cache) = format_serializable_tree(subtree);
-                if let Ok(tree_val) = tree_result {
-                    if let Some(obj) = result_json.as_object_mut() {
-                        obj.insert("ui_tree".to_string(), tree_val);
-                        obj.insert("tree_type".to_string(), json!("subtree"));
+                let (tree_result, cache) = format_serializable_tree(subtree, result_json);
+                match tree_result {
+                    Ok(tree_val) => {
+                        if let Some(obj) = result_json.as_object_mut() {
+                            obj.insert("ui_tree".to_string(), tree_val);
+                            obj.insert("tree_type".to_string(), json!("subtree"));
+                        }
+                        bounds_cache = cache;
+                    }
+                    Err(e) => {
+                        if let Some(obj) = result_json.as_object_mut() {
+                            obj.insert("tree_error".to_string(), json!(e));
+                        }
                     }
-                    bounds_cache = cache;
                 }
                 return bounds_cache;
             }
@@ -566,7 +503,7 @@ pub async fn maybe_attach_tree(
         max_depth: tree_max_depth,
         include_all_bounds,
         ui_settle_delay_ms: None,
-        format_output: true, // Let SDK handle formatting
+        format_output: tree_budget.is_none(), // Let SDK handle formatting unless budgeted
         show_overlay: false,
         overlay_display_mode: None,
         from_selector: from_selector_opt.clone(),
@@ -587,8 +524,13 @@ pub async fn maybe_attach_tree(
             // Format based on output format
             let (tree_val_result, cache) = match format {
                 TreeOutputFormat::CompactYaml | TreeOutputFormat::ClusteredYaml => {
-                    // SDK already formatted, use that
-                    if let Some(formatted) = result.formatted {
+                    if let Some(budget) = tree_budget {
+                        match format_ui_node_with_budget(&result.tree, budget) {
+                            Ok(budgeted) => attach_budgeted_tree(budgeted, result_json),
+                            Err(e) => (Err(e.to_string()), None),
+                        }
+                    } else if let Some(formatted) = result.formatted {
+                        // SDK already formatted, use that
                         (Ok(json!(formatted)), Some(result.index_to_bounds))
                     } else {
                         // Fallback: format UINode
@@ -599,23 +541,33 @@ pub async fn maybe_attach_tree(
                         )
                     }
                 }
-                TreeOutputFormat::VerboseJson => (serde_json::to_value(&result.tree), None),
+                TreeOutputFormat::VerboseJson => (
+                    serde_json::to_value(&result.tree).map_err(|e| e.to_string()),
+                    None,
+                ),
             };
 
-            if let Ok(tree_val) = tree_val_result {
-                if let Some(obj) = result_json.as_object_mut() {
-                    obj.insert("ui_tree".to_string(), tree_val);
-                    let tree_type = if from_selector_opt.is_some() {
-                        "subtree"
-                    } else {
-                        "full_window"
-                    };
-                    obj.insert("tree_type".to_string(), json!(tree_type));
-                    if let Some(sel) = &from_selector_opt {
-                        obj.insert("from_selector_used".to_string(), json!(sel));
+            match tree_val_result {
+                Ok(tree_val) => {
+                    if let Some(obj) = result_json.as_object_mut() {
+                        obj.insert("ui_tree".to_string(), tree_val);
+                        let tree_type = if from_selector_opt.is_some() {
+                            "subtree"
+                        } else {
+                            "full_window"
+                        };
+                        obj.insert("tree_type".to_string(), json!(tree_type));
+                        if let Some(sel) = &from_selector_opt {
+                            obj.insert("from_selector_used".to_string(), json!(sel));
+                        }
+                    }
+                    bounds_cache = cache;
+                }
+                Err(e) => {
+                    if let Some(obj) = result_json.as_object_mut() {
+                        obj.insert("tree_error".to_string(), json!(e));
                     }
                 }
-                bounds_cache = cache;
             }
         }
         Err(e) => {
@@ -634,11 +586,39 @@ pub async fn maybe_attach_tree(
     bounds_cache
 }
 
+/// Attach the metadata of a budgeted tree to the response and return its formatted output and bounds
+fn attach_budgeted_tree(
+    result: BudgetedTreeResult,
+    result_json: &mut Value,
+) -> (Result<Value, String>, Option<UiaBoundsCache>) {
+    if let Some(obj) = result_json.as_object_mut() {
+        let collapsed: Vec<Value> = result
+            .collapsed
+            .iter()
+            .map(|group| {
+                json!({
+                    "handle": group.handle,
+                    "siblings": group.siblings,
+                    "elements": group.elements,
+                    "role": group.role,
+                })
+            })
+            .collect();
+        obj.insert("tree_collapsed".to_string(), json!(collapsed));
+        obj.insert(
+            "tree_estimated_tokens".to_string(),
+            json!(result.estimated_tokens),
+        );
+    }
+    (Ok(json!(result.formatted)), Some(result.index_to_bounds))
+}
+
 /// Result structure for UI tree diff computation
 #[derive(Debug, Clone)]
 pub struct UiDiffResult {
     pub diff: String,
     pub has_changes: bool,
+    pub changes: Vec<terminator::ui_tree_diff::UiChange>,
 }
 
 /// Find element and execute action with optional UI diff capture
@@ -722,6 +702,7 @@ where
                         let mcp_diff = ui_diff.map(|d| UiDiffResult {
                             diff: d.diff,
                             has_changes: d.has_changes,
+                            changes: d.changes,
                         });
                         return Ok(((result, returned_element), successful_selector, mcp_diff));
                     }
@@ -933,8 +914,61 @@ pub async fn verify_post_action(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::expression_eval;
     use serde_json::json;
 
+    #[test]
+    fn test_element_not_found_reports_selector_errors() {
+        let error = build_element_not_found_error(
+            "role:Button && && name:OK",
+            Some("role:Button|name:OK"),
+            None,
+            anyhow::anyhow!("timed out"),
+        );
+        let data = error.data.expect("error payload");
+        let selector_errors = data["selector_errors"].as_array().unwrap();
+        assert_eq!(selector_errors.len(), 1);
+        assert_eq!(selector_errors[0]["selector"], "role:Button && && name:OK");
+        assert_eq!(selector_errors[0]["span"], json!({"start": 15, "end": 17}));
+        assert!(selector_errors[0]["rendered"]
+            .as_str()
+            .unwrap()
+            .contains("\n               ^^ "));
+
+        let error = build_element_not_found_error(
+            "role:Button|name:OK",
+            None,
+            None,
+            anyhow::anyhow!("timed out"),
+        );
+        assert!(error.data.unwrap().get("selector_errors").is_none());
+    }
+
+    #[test]
+    fn test_normalize_key_and_trailing_keys() {
+        assert_eq!(normalize_key("Enter").unwrap(), "{Enter}");
+        assert_eq!(normalize_key("ctrl+shift+T").unwrap(), "{Ctrl}{Shift}t");
+        assert_eq!(normalize_key("{esc}").unwrap(), "{Escape}");
+        let error = normalize_key("{Entr}").unwrap_err();
+        assert_eq!(error.suggestions, ["{Enter}"]);
+
+        assert_eq!(
+            extract_trailing_keys("mediar.ai{return}{Tab 2}"),
+            (
+                "mediar.ai".to_string(),
+                vec!["{Enter}".to_string(), "{Tab 2}".to_string()]
+            )
+        );
+        assert_eq!(
+            extract_trailing_keys("Use {Enter} to submit"),
+            ("Use {Enter} to submit".to_string(), vec![])
+        );
+        assert_eq!(
+            extract_trailing_keys("a{F5}{Enter}"),
+            ("a{F5}".to_string(), vec!["{Enter}".to_string()])
+        );
+    }
+
     #[test]
     fn test_substitute_simple_string_variable() {
         let mut args = json!({"url": "{{url}}"});
@@ -8,27 +8,36 @@ pub mod expression_eval;
 pub mod helpers;
 pub mod log_pipe;
 pub mod mcp_types;
+pub mod metrics;
 pub mod omniparser;
 pub mod output_parser;
 pub mod posthog;
 pub mod prompt;
+pub mod replay;
+pub mod resources;
+pub mod scheduler;
 pub mod scripting_engine;
 pub mod sentry;
 pub mod server;
 pub mod server_sequence;
+pub mod step_loop;
 pub mod telemetry;
+pub mod template;
 pub mod tool_logging;
 pub mod tools;
 pub mod transpiler;
 pub mod tree_formatter;
+pub mod ui_assertions;
 pub mod utils;
 pub mod vision;
+pub mod workflow_state;
 pub mod workflow_typescript;
 
 // Re-export ui_tree_diff from terminator crate (single source of truth)
 pub use terminator::ui_tree_diff;
 
 // Re-export window_manager from terminator crate (single source of truth)
+#[cfg(target_os = "windows")]
 pub use terminator::{WindowCache, WindowInfo, WindowManager, WindowPlacement};
 
 // Re-export the extract_content_json function for testing
@@ -19,6 +19,7 @@
 //! ```
 
 use serde::Deserialize;
+#[cfg(windows)]
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
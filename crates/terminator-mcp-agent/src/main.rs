@@ -20,6 +20,7 @@ use rmcp::{
 };
 use std::{
     net::SocketAddr,
+    path::PathBuf,
     sync::{
         atomic::{AtomicUsize, Ordering},
         Arc, Mutex,
@@ -29,6 +30,9 @@ use std::{
 use sysinfo::{ProcessesToUpdate, System};
 use terminator_mcp_agent::cancellation::RequestManager;
 use terminator_mcp_agent::child_process;
+use terminator_mcp_agent::scheduler::{
+    self, McpWorkflowRunner, RequestGate, RunHistory, ScheduledWorkflow, Scheduler,
+};
 use terminator_mcp_agent::server::{self, check_terminator_source};
 use terminator_mcp_agent::utils::init_logging;
 use tower_http::cors::CorsLayer;
@@ -73,6 +77,11 @@ struct Args {
     /// Default: false (allows multiple instances via smart parent checking)
     #[arg(long)]
     enforce_single_instance: bool,
+
+    /// Folder of workflows to run on their triggers (can also use MCP_WORKFLOWS_DIR env var)
+    /// Cron triggers run in every transport mode; webhook triggers need the HTTP transport
+    #[arg(long, env = "MCP_WORKFLOWS_DIR")]
+    workflows_dir: Option<PathBuf>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -372,6 +381,41 @@ async fn main() -> Result<()> {
         tracing::info!("CORS enabled for web transports");
     }
 
+    // Discover triggered workflows up front; the scheduler starts once a DesktopWrapper exists
+    let scheduled_workflows = match &args.workflows_dir {
+        Some(dir) => {
+            let workflows = scheduler::discover_workflows(dir).await;
+            tracing::info!(
+                "Found {} triggered workflow(s) in {}",
+                workflows.len(),
+                dir.display()
+            );
+            if args.transport != TransportMode::Http
+                && workflows.iter().any(|w| w.webhook_path().is_some())
+            {
+                tracing::warn!("Webhook triggers are only served with the HTTP transport");
+            }
+            Some(workflows)
+        }
+        None => None,
+    };
+    let start_scheduler = |desktop: &server::DesktopWrapper, gate: Option<RequestGate>| {
+        scheduled_workflows
+            .clone()
+            .map(|workflows: Vec<ScheduledWorkflow>| {
+                let mut scheduler = Scheduler::new(
+                    workflows,
+                    Arc::new(McpWorkflowRunner::new(desktop.clone())),
+                    RunHistory::load(scheduler::default_history_path()),
+                );
+                if let Some(gate) = gate {
+                    scheduler = scheduler.with_gate(gate);
+                }
+                scheduler.start();
+                scheduler
+            })
+    };
+
     match args.transport {
         TransportMode::Stdio => {
             tracing::info!("Starting stdio transport...");
@@ -389,6 +433,14 @@ async fn main() -> Result<()> {
 
             // Background window polling service removed - we capture explicitly on step 0 instead
 
+            // Scheduled runs and interactive tool calls share one concurrency limit
+            let gate = RequestGate::from_env(desktop.request_manager.clone());
+            let scheduler = start_scheduler(&desktop, Some(gate.clone()));
+            let desktop = server::DesktopWrapper {
+                tool_gate: scheduler.is_some().then_some(gate),
+                ..desktop
+            };
+
             // Serve with better error handling
             let service = desktop.serve(stdio()).await.inspect_err(|e| {
                 tracing::error!("Serving error: {:?}", e);
@@ -437,9 +489,17 @@ async fn main() -> Result<()> {
 
             // Background window polling service removed - we capture explicitly on step 0 instead
 
+            // Scheduled runs and interactive tool calls share one concurrency limit
+            let gate = RequestGate::from_env(desktop.request_manager.clone());
+            let scheduler = start_scheduler(&desktop, Some(gate.clone()));
+            let desktop = server::DesktopWrapper {
+                tool_gate: scheduler.is_some().then_some(gate),
+                ..desktop
+            };
+
             let ct = SseServer::serve(addr)
                 .await?
-                .with_service(move || desktop.clone());
+                .with_service(move || desktop.for_session());
 
             info!("SSE server running on http://{addr}");
             info!("Connect your MCP client to:");
@@ -449,6 +509,9 @@ async fn main() -> Result<()> {
 
             tokio::signal::ctrl_c().await?;
             ct.cancel();
+            if let Some(scheduler) = scheduler {
+                scheduler.shutdown();
+            }
             // Kill any running child processes (bun/node workflow executors)
             child_process::kill_all();
             tracing::info!("Shutting down SSE server");
@@ -462,6 +525,29 @@ async fn main() -> Result<()> {
             let desktop_wrapper = Arc::new(tokio::sync::RwLock::new(None));
             let desktop_wrapper_for_service = desktop_wrapper.clone();
 
+            let max_concurrent = std::env::var("MCP_MAX_CONCURRENT")
+                .ok()
+                .and_then(|s| s.parse::<usize>().ok())
+                .unwrap_or(1);
+            let active_requests = Arc::new(AtomicUsize::new(0));
+            let request_manager = RequestManager::new();
+
+            // Scheduled workflows need the DesktopWrapper right away instead of on first /mcp use
+            let scheduler = if scheduled_workflows.is_some() {
+                let desktop = server::DesktopWrapper::new_with_log_capture(log_capture.clone())?;
+                *desktop_wrapper.write().await = Some(desktop.clone());
+                start_scheduler(
+                    &desktop,
+                    Some(RequestGate {
+                        active_requests: active_requests.clone(),
+                        max_concurrent,
+                        request_manager: request_manager.clone(),
+                    }),
+                )
+            } else {
+                None
+            };
+
             // Lazy-initialize DesktopWrapper on first /mcp use so that /health can succeed on CI
             let service = StreamableHttpService::new(
                 {
@@ -486,13 +572,14 @@ async fn main() -> Result<()> {
                                     match server::DesktopWrapper::new_with_log_capture(log_capture)
                                     {
                                         Ok(wrapper) => {
-                                            *wrapper_guard = Some(wrapper.clone());
-                                            Ok(wrapper)
+                                            let session = wrapper.for_session();
+                                            *wrapper_guard = Some(wrapper);
+                                            Ok(session)
                                         }
                                         Err(e) => Err(std::io::Error::other(e.to_string())),
                                     }
                                 } else {
-                                    Ok(wrapper_guard.as_ref().unwrap().clone())
+                                    Ok(wrapper_guard.as_ref().unwrap().for_session())
                                 }
                             })
                         })
@@ -513,16 +600,11 @@ async fn main() -> Result<()> {
                 desktop_wrapper: Arc<tokio::sync::RwLock<Option<server::DesktopWrapper>>>,
             }
 
-            let max_concurrent = std::env::var("MCP_MAX_CONCURRENT")
-                .ok()
-                .and_then(|s| s.parse::<usize>().ok())
-                .unwrap_or(1);
-
             let app_state = AppState {
-                active_requests: Arc::new(AtomicUsize::new(0)),
+                active_requests,
                 last_activity: Arc::new(Mutex::new(chrono::Utc::now().to_rfc3339())),
                 max_concurrent,
-                request_manager: RequestManager::new(),
+                request_manager,
                 auth_token: args.auth_token.clone(),
                 desktop_wrapper: desktop_wrapper.clone(),
             };
@@ -556,6 +638,21 @@ async fn main() -> Result<()> {
                 (code, Json(body))
             }
 
+            // Prometheus scrape endpoint
+            async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
+                let body = terminator_mcp_agent::metrics::render(
+                    state.request_manager.active_count().await,
+                    child_process::active_count(),
+                );
+                (
+                    [(
+                        axum::http::header::CONTENT_TYPE,
+                        terminator_mcp_agent::metrics::CONTENT_TYPE,
+                    )],
+                    body,
+                )
+            }
+
             // Request body for POST /mode endpoint
             #[derive(serde::Deserialize)]
             struct SetModeRequest {
@@ -792,10 +889,18 @@ async fn main() -> Result<()> {
                 .route("/health", get(health_check))
                 .route("/ready", get(readiness_check))
                 .route("/status", get(status_handler))
+                .route("/metrics", get(metrics_handler))
                 .route("/mode", post(set_mode_handler))
                 .nest("/mcp", mcp_router)
                 .with_state(app_state.clone());
 
+            // Webhook triggers and run history, behind the same auth as /mcp
+            if let Some(scheduler) = &scheduler {
+                router = router.merge(scheduler.router().layer(
+                    axum::middleware::from_fn_with_state(app_state.clone(), auth_middleware),
+                ));
+            }
+
             if args.cors {
                 router = router.layer(CorsLayer::permissive());
             }
@@ -811,13 +916,27 @@ async fn main() -> Result<()> {
             info!("  MCP client endpoint: http://{addr}/mcp");
             info!("  Status endpoint: http://{addr}/status");
             info!("  Health check: http://{addr}/health");
+            info!("  Prometheus metrics: http://{addr}/metrics");
             info!("  Mode control: POST http://{addr}/mode");
+            if let Some(scheduler) = &scheduler {
+                for path in scheduler
+                    .workflows()
+                    .iter()
+                    .filter_map(|w| w.webhook_path())
+                {
+                    info!("  Webhook trigger: POST http://{addr}{path}");
+                }
+                info!("  Scheduler runs: http://{addr}/scheduler/runs");
+            }
             info!("Press Ctrl+C to stop");
 
             axum::serve(tcp_listener, router)
                 .with_graceful_shutdown(async move {
                     tokio::signal::ctrl_c().await.ok();
                     info!("Received shutdown signal, cancelling active requests...");
+                    if let Some(scheduler) = scheduler {
+                        scheduler.shutdown();
+                    }
                     app_state.request_manager.cancel_all().await;
                     // Kill any running child processes (bun/node workflow executors)
                     child_process::kill_all();
@@ -851,7 +970,8 @@ async fn root_handler() -> impl axum::response::IntoResponse {
                 "/": "This endpoint - lists available endpoints",
                 "/mcp": "MCP protocol endpoint - connect your MCP client here",
                 "/health": "Health check endpoint - returns server status",
-                "/status": "Status endpoint - shows active requests and concurrency info"
+                "/status": "Status endpoint - shows active requests and concurrency info",
+                "/metrics": "Prometheus metrics - tool calls, latencies, errors and workflow outcomes"
             },
             "usage": {
                 "mcp_client": "Connect your MCP client to: /mcp",
@@ -882,7 +1002,8 @@ async fn health_check() -> impl axum::response::IntoResponse {
         "endpoints": {
             "/health": "Liveness check (this endpoint)",
             "/ready": "Readiness check with full UIAutomation validation",
-            "/status": "Concurrency and load status"
+            "/status": "Concurrency and load status",
+            "/metrics": "Prometheus metrics"
         }
     });
 
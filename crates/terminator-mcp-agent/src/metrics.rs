@@ -0,0 +1,279 @@
+//! Prometheus metrics served on the HTTP transport's `/metrics` endpoint
+//!
+//! Tool calls and workflow runs are counted in process memory and rendered in
+//! the Prometheus text exposition format on every scrape, so no collector has
+//! to run next to the agent.
+
+use rmcp::ErrorData as McpError;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use terminator::AutomationError;
+
+/// Content type of the rendered metrics
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Upper bounds, in seconds, of the latency histogram buckets
+const LATENCY_BUCKETS: &[f64] = &[0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0];
+
+/// Cumulative latency histogram
+#[derive(Debug, Clone)]
+struct Histogram {
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: vec![0; LATENCY_BUCKETS.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= *bound {
+                *bucket += 1;
+            }
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let (bucket_prefix, series_labels) = if labels.is_empty() {
+            (String::new(), String::new())
+        } else {
+            (format!("{labels},"), format!("{{{labels}}}"))
+        };
+        for (bucket, bound) in self.buckets.iter().zip(LATENCY_BUCKETS) {
+            let _ = writeln!(
+                out,
+                "{name}_bucket{{{bucket_prefix}le=\"{bound}\"}} {bucket}"
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{name}_bucket{{{bucket_prefix}le=\"+Inf\"}} {}",
+            self.count
+        );
+        let _ = writeln!(out, "{name}_sum{series_labels} {}", self.sum);
+        let _ = writeln!(out, "{name}_count{series_labels} {}", self.count);
+    }
+}
+
+#[derive(Debug, Default)]
+struct ToolStats {
+    success: u64,
+    error: u64,
+    latency: Histogram,
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    tools: BTreeMap<String, ToolStats>,
+    /// Error counts keyed by (tool, error kind)
+    errors: BTreeMap<(String, &'static str), u64>,
+    workflow_success: u64,
+    workflow_failure: u64,
+    workflow_latency: Histogram,
+}
+
+fn registry() -> &'static Mutex<Registry> {
+    static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(Registry::default()))
+}
+
+/// Record a finished tool call
+pub fn record_tool_call(tool: &str, duration: Duration, error: Option<&McpError>) {
+    let mut registry = registry().lock().unwrap_or_else(|e| e.into_inner());
+    let stats = registry.tools.entry(tool.to_string()).or_default();
+    stats.latency.observe(duration.as_secs_f64());
+    match error {
+        None => stats.success += 1,
+        Some(e) => {
+            stats.error += 1;
+            *registry
+                .errors
+                .entry((tool.to_string(), error_kind(e)))
+                .or_default() += 1;
+        }
+    }
+}
+
+/// Record a finished workflow run (execute_sequence)
+pub fn record_workflow(success: bool, duration: Duration) {
+    let mut registry = registry().lock().unwrap_or_else(|e| e.into_inner());
+    if success {
+        registry.workflow_success += 1;
+    } else {
+        registry.workflow_failure += 1;
+    }
+    registry.workflow_latency.observe(duration.as_secs_f64());
+}
+
+/// AutomationError variant behind a tool error, or "Other" if it did not come from one.
+/// Read from the `error_kind` that `helpers::with_error_kind` puts in the error data.
+pub fn error_kind(error: &McpError) -> &'static str {
+    error
+        .data
+        .as_ref()
+        .and_then(|data| data.get("error_kind"))
+        .and_then(|kind| kind.as_str())
+        .and_then(AutomationError::kind_from_name)
+        .unwrap_or("Other")
+}
+
+/// Escape a label value for the text exposition format
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Render all metrics in the Prometheus text exposition format
+pub fn render(active_requests: usize, child_processes: usize) -> String {
+    let registry = registry().lock().unwrap_or_else(|e| e.into_inner());
+    let mut out = String::new();
+
+    out.push_str("# HELP terminator_tool_calls_total Tool calls by tool and outcome.\n");
+    out.push_str("# TYPE terminator_tool_calls_total counter\n");
+    for (tool, stats) in &registry.tools {
+        let tool = escape_label(tool);
+        let _ = writeln!(
+            out,
+            "terminator_tool_calls_total{{tool=\"{tool}\",status=\"success\"}} {}",
+            stats.success
+        );
+        let _ = writeln!(
+            out,
+            "terminator_tool_calls_total{{tool=\"{tool}\",status=\"error\"}} {}",
+            stats.error
+        );
+    }
+
+    out.push_str("# HELP terminator_tool_duration_seconds Tool call latency.\n");
+    out.push_str("# TYPE terminator_tool_duration_seconds histogram\n");
+    for (tool, stats) in &registry.tools {
+        let labels = format!("tool=\"{}\"", escape_label(tool));
+        stats
+            .latency
+            .render(&mut out, "terminator_tool_duration_seconds", &labels);
+    }
+
+    out.push_str("# HELP terminator_tool_errors_total Tool errors by AutomationError variant.\n");
+    out.push_str("# TYPE terminator_tool_errors_total counter\n");
+    for ((tool, kind), count) in &registry.errors {
+        let _ = writeln!(
+            out,
+            "terminator_tool_errors_total{{tool=\"{}\",kind=\"{kind}\"}} {count}",
+            escape_label(tool)
+        );
+    }
+
+    out.push_str("# HELP terminator_workflow_runs_total Workflow runs by outcome.\n");
+    out.push_str("# TYPE terminator_workflow_runs_total counter\n");
+    let _ = writeln!(
+        out,
+        "terminator_workflow_runs_total{{status=\"success\"}} {}",
+        registry.workflow_success
+    );
+    let _ = writeln!(
+        out,
+        "terminator_workflow_runs_total{{status=\"failure\"}} {}",
+        registry.workflow_failure
+    );
+
+    out.push_str("# HELP terminator_workflow_duration_seconds Workflow run latency.\n");
+    out.push_str("# TYPE terminator_workflow_duration_seconds histogram\n");
+    registry
+        .workflow_latency
+        .render(&mut out, "terminator_workflow_duration_seconds", "");
+
+    out.push_str("# HELP terminator_active_requests MCP requests currently in flight.\n");
+    out.push_str("# TYPE terminator_active_requests gauge\n");
+    let _ = writeln!(out, "terminator_active_requests {active_requests}");
+
+    out.push_str(
+        "# HELP terminator_child_processes Workflow executor child processes currently running.\n",
+    );
+    out.push_str("# TYPE terminator_child_processes gauge\n");
+    let _ = writeln!(out, "terminator_child_processes {child_processes}");
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kind_from_error_data() {
+        let source = AutomationError::ElementNotFound("role:Button".into());
+        let err = crate::helpers::with_error_kind(
+            McpError::internal_error(format!("Failed to click: {source}"), None),
+            &source,
+        );
+        assert_eq!(error_kind(&err), "ElementNotFound");
+
+        let source = AutomationError::Timeout("5000ms".into());
+        let err = crate::helpers::with_error_kind(
+            McpError::invalid_params(
+                "Selector lookup failed",
+                Some(serde_json::json!({"reason": source.to_string()})),
+            ),
+            &source,
+        );
+        assert_eq!(error_kind(&err), "Timeout");
+        assert_eq!(err.data.as_ref().unwrap()["reason"], source.to_string());
+
+        // Text that merely mentions an error is not classified
+        let err = McpError::invalid_params("Element not found: in the message only", None);
+        assert_eq!(error_kind(&err), "Other");
+    }
+
+    #[test]
+    fn test_render_exposition_format() {
+        let tool = "metrics_test_tool";
+        record_tool_call(tool, Duration::from_millis(30), None);
+        let source = AutomationError::ElementNotVisible("x".into());
+        record_tool_call(
+            tool,
+            Duration::from_millis(700),
+            Some(&crate::helpers::with_error_kind(
+                McpError::internal_error(source.to_string(), None),
+                &source,
+            )),
+        );
+
+        let text = render(2, 1);
+        assert!(text.contains(
+            "terminator_tool_calls_total{tool=\"metrics_test_tool\",status=\"success\"} 1"
+        ));
+        assert!(text.contains(
+            "terminator_tool_calls_total{tool=\"metrics_test_tool\",status=\"error\"} 1"
+        ));
+        assert!(text.contains(
+            "terminator_tool_duration_seconds_bucket{tool=\"metrics_test_tool\",le=\"0.05\"} 1"
+        ));
+        assert!(text.contains(
+            "terminator_tool_duration_seconds_bucket{tool=\"metrics_test_tool\",le=\"1\"} 2"
+        ));
+        assert!(
+            text.contains("terminator_tool_duration_seconds_count{tool=\"metrics_test_tool\"} 2")
+        );
+        assert!(text.contains(
+            "terminator_tool_errors_total{tool=\"metrics_test_tool\",kind=\"ElementNotVisible\"} 1"
+        ));
+        assert!(text.contains("terminator_active_requests 2"));
+        assert!(text.contains("terminator_child_processes 1"));
+        assert_eq!(escape_label("a\"b\\c"), "a\\\"b\\\\c");
+    }
+}
@@ -1,4 +1,5 @@
 use chrono::Local;
+use rmcp::model::{GetPromptResult, Prompt, PromptMessage, PromptMessageRole};
 use std::env;
 
 pub fn get_server_instructions() -> String {
@@ -15,7 +16,7 @@ You are an AI assistant designed to control a computer desktop. Your primary goa
 
 **Tool Behavior & Metadata**
 - Do NOT call get_window_tree after action tools. Action tools have built-in tree/diff capture:
-  - `ui_diff_before_after: true` - Returns `ui_diff` (what changed) and `has_ui_changes` (boolean). Use to verify actions worked.
+  - `ui_diff_before_after: true` - Returns `ui_diff` (what changed), `has_ui_changes` (boolean) and `ui_changes` (typed operations such as `added`, `renamed`, `state_changed`). Use to verify actions worked.
   - `include_tree_after_action: true` - Returns full UI tree in response. Use when you need the tree for next action (e.g., index-based clicking).
 - Only call get_window_tree at the START of a task to understand the UI, or when you need special options (OCR, DOM, Omniparser, vision). Start with just the basic tree - only add include_ocr/include_omniparser/include_browser_dom if the basic tree doesn't show the element you need. Default tree_max_depth is 30; use 50+ for complex browser pages, 15 for simple apps.
 - Always derive selectors strictly from the provided UI tree or DOM data; never guess or predict element attributes based on assumptions.
@@ -72,7 +73,9 @@ Both do **substring matching** by default. Wildcards (`*`, `?`) are NOT supporte
 
 **Common Pitfalls & Solutions**
 *   **ElementNotVisible error on click:** Element has zero-size bounds, is offscreen, or not in viewport. Use `invoke_element` instead (doesn't require viewport visibility), or ensure element is scrolled into view first.
-*   **ElementNotStable error on click:** Element bounds are still animating after 800ms. Wait longer before clicking, or use `invoke_element` which doesn't require stable bounds.
+*   **ElementNotStable error on click:** Element bounds kept changing (animation, layout shift) until `timeout_ms`. Wait longer before clicking, or use `invoke_element` which doesn't require stable bounds.
+*   **ElementObscured error on click:** Another element (usually a modal dialog or popup) covers the click point; the error names it. Dismiss it first instead of retrying the click. Pass `force: true` only if the covering element is known to let clicks through.
+*   **ElementNotEditable error on typing:** The element is read-only or not a text field. Target the actual input (e.g. `role:Edit`) rather than its container.
 *   **ElementNotEnabled error:** Element is disabled/grayed out. Investigate why (missing required fields, unchecked dependencies, etc.) before attempting to click.
 *   **Radio button clicks don't register:** Use `set_selected` with `state: true` instead of `click_element`.
 *   **Form validation errors:** Verify all fields AND radio buttons/checkboxes before submitting.
@@ -172,3 +175,55 @@ Focus on:
 
 Be thorough - detect ALL UI elements visible in the screenshot. Be precise with bounding boxes."#
 }
+
+/// (name, description, text) of every prompt template exposed over MCP
+fn prompt_templates() -> [(&'static str, &'static str, String); 2] {
+    [
+        (
+            "server_instructions",
+            "Guidelines for automating desktop apps with the Terminator tools",
+            get_server_instructions(),
+        ),
+        (
+            "vision_ui_detection",
+            "Instructions for detecting UI elements in a screenshot with a vision model",
+            get_vision_prompt().to_string(),
+        ),
+    ]
+}
+
+/// Prompt templates for MCP `prompts/list`
+pub fn list_prompts() -> Vec<Prompt> {
+    prompt_templates()
+        .into_iter()
+        .map(|(name, description, _)| Prompt::new(name, Some(description), None))
+        .collect()
+}
+
+/// Render a prompt template for MCP `prompts/get`
+pub fn get_prompt(name: &str) -> Option<GetPromptResult> {
+    prompt_templates()
+        .into_iter()
+        .find(|(n, _, _)| *n == name)
+        .map(|(_, description, text)| GetPromptResult {
+            description: Some(description.to_string()),
+            messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_listed_prompt_can_be_fetched() {
+        let prompts = list_prompts();
+        assert_eq!(prompts.len(), 2);
+        for prompt in prompts {
+            let result = get_prompt(&prompt.name).unwrap();
+            assert_eq!(result.description, prompt.description);
+            assert_eq!(result.messages.len(), 1);
+        }
+        assert!(get_prompt("missing").is_none());
+    }
+}
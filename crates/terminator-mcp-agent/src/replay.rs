@@ -0,0 +1,414 @@
+//! Deterministic replay of recorded execution logs
+//!
+//! A [`Replay`] serves every tool call from the `ExecutionLog` files of a
+//! previous run instead of touching the desktop, so `execute_sequence` control
+//! flow (`if`, `jumps`, `fallback_id`, retries, output parsers) can be
+//! regression-tested offline. Calls are expected in the recorded order; any
+//! deviation is collected as a [`Divergence`] and reported in the sequence
+//! result under `replay`.
+//!
+//! Set `TERMINATOR_REPLAY_DIR` to a folder of execution logs (and optionally
+//! `TERMINATOR_REPLAY_RUN` to a run's timestamp prefix) to start the server in
+//! replay mode. Combine it with the `mock` feature to run without a desktop.
+
+use crate::execution_logger::{self, ExecutionResponse, LoggedExecution};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use rmcp::model::{CallToolResult, Content};
+use rmcp::ErrorData as McpError;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+/// Folder of execution logs to replay
+pub const REPLAY_DIR_ENV_VAR: &str = "TERMINATOR_REPLAY_DIR";
+/// Timestamp prefix of the run to replay (defaults to the latest run)
+pub const REPLAY_RUN_ENV_VAR: &str = "TERMINATOR_REPLAY_RUN";
+
+/// Identifies a tool call within a run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReplayStep {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step_id: Option<String>,
+    pub tool_name: String,
+}
+
+impl fmt::Display for ReplayStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.step_id {
+            Some(id) => write!(f, "{} (step '{}')", self.tool_name, id),
+            None => write!(f, "{}", self.tool_name),
+        }
+    }
+}
+
+/// A difference between the replayed and the recorded call order.
+/// `position` is the index of the recorded call where it was noticed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Divergence {
+    /// The call does not match the next recorded call, but was recorded later on
+    OutOfOrder {
+        position: usize,
+        expected: ReplayStep,
+        actual: ReplayStep,
+    },
+    /// No recorded call is left to serve this call
+    Unrecorded { position: usize, actual: ReplayStep },
+    /// A recorded call that was never replayed
+    Skipped {
+        position: usize,
+        expected: ReplayStep,
+    },
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Divergence::OutOfOrder {
+                position,
+                expected,
+                actual,
+            } => write!(f, "#{position}: expected {expected}, got {actual}"),
+            Divergence::Unrecorded { position, actual } => {
+                write!(f, "#{position}: {actual} was not recorded")
+            }
+            Divergence::Skipped { position, expected } => {
+                write!(f, "#{position}: {expected} was never replayed")
+            }
+        }
+    }
+}
+
+/// Progress of a replay, included in the `execute_sequence` result
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayReport {
+    pub recorded: usize,
+    pub replayed: usize,
+    pub divergences: Vec<Divergence>,
+}
+
+struct RecordedCall {
+    step: ReplayStep,
+    response: ExecutionResponse,
+    screenshots: Vec<String>,
+}
+
+#[derive(Default)]
+struct ReplayState {
+    cursor: usize,
+    consumed: Vec<bool>,
+    divergences: Vec<Divergence>,
+}
+
+/// Serves tool calls from a recorded run
+pub struct Replay {
+    dir: PathBuf,
+    calls: Vec<RecordedCall>,
+    sequence_request: Option<Value>,
+    state: Mutex<ReplayState>,
+}
+
+impl Replay {
+    /// Load a run from a folder of execution logs, see [`execution_logger::load_run`]
+    pub fn from_dir(dir: &Path, run: Option<&str>) -> Result<Self, String> {
+        Ok(Self::from_logs(dir, execution_logger::load_run(dir, run)?))
+    }
+
+    /// Build a replay from logs of one run, oldest first. `dir` is where
+    /// their screenshots are stored.
+    pub fn from_logs(dir: &Path, logs: Vec<LoggedExecution>) -> Self {
+        let mut sequence_request = None;
+        let mut calls = Vec::new();
+        for entry in logs {
+            // The sequence itself is re-executed, only its tool calls are served
+            if entry.is_sequence() {
+                sequence_request.get_or_insert(entry.log.request);
+                continue;
+            }
+            let screenshots = entry.log.screenshots.map(|s| s.after).unwrap_or_default();
+            calls.push(RecordedCall {
+                step: ReplayStep {
+                    step_id: entry.log.step_id,
+                    tool_name: entry.log.tool_name,
+                },
+                response: entry.log.response,
+                screenshots,
+            });
+        }
+        let state = ReplayState {
+            consumed: vec![false; calls.len()],
+            ..Default::default()
+        };
+        Self {
+            dir: dir.to_path_buf(),
+            calls,
+            sequence_request,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Replay configured through `TERMINATOR_REPLAY_DIR`, if any
+    pub fn from_env() -> Option<Self> {
+        let dir = std::env::var(REPLAY_DIR_ENV_VAR)
+            .ok()
+            .filter(|d| !d.is_empty())?;
+        let run = std::env::var(REPLAY_RUN_ENV_VAR)
+            .ok()
+            .filter(|r| !r.is_empty());
+        match Self::from_dir(Path::new(&dir), run.as_deref()) {
+            Ok(replay) => {
+                info!(
+                    "[replay] Serving {} recorded tool calls from {}",
+                    replay.calls.len(),
+                    dir
+                );
+                Some(replay)
+            }
+            Err(e) => {
+                warn!("[replay] Replay disabled: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Start over from the first recorded call, forgetting earlier divergences.
+    /// Called when a sequence run starts, so each run is compared on its own.
+    pub fn reset(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        *state = ReplayState {
+            consumed: vec![false; self.calls.len()],
+            ..Default::default()
+        };
+    }
+
+    /// Arguments of the recorded `execute_sequence` call, to run the same workflow again
+    pub fn sequence_request(&self) -> Option<&Value> {
+        self.sequence_request.as_ref()
+    }
+
+    /// Serve the next recorded response for a tool call.
+    ///
+    /// When the call does not match the next recorded one, the replay looks
+    /// further ahead for it so a single divergence does not fail the rest of
+    /// the run.
+    pub fn respond(
+        &self,
+        tool_name: &str,
+        step_id: Option<&str>,
+    ) -> Result<CallToolResult, McpError> {
+        let actual = ReplayStep {
+            step_id: step_id.map(String::from),
+            tool_name: tool_name
+                .strip_prefix("mcp_terminator-mcp-agent_")
+                .unwrap_or(tool_name)
+                .to_string(),
+        };
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let position = state.cursor;
+        let Some(index) = (position..self.calls.len()).find(|&i| self.calls[i].step == actual)
+        else {
+            warn!("[replay] #{}: {} was not recorded", position, actual);
+            let message = format!("Replay has no recorded response for {actual}");
+            state
+                .divergences
+                .push(Divergence::Unrecorded { position, actual });
+            return Err(McpError::internal_error(message, None));
+        };
+        if index != position {
+            let divergence = Divergence::OutOfOrder {
+                position,
+                expected: self.calls[position].step.clone(),
+                actual,
+            };
+            warn!("[replay] {}", divergence);
+            state.divergences.push(divergence);
+        }
+        state.consumed[index] = true;
+        state.cursor = index + 1;
+        drop(state);
+
+        self.calls[index].to_result(&self.dir)
+    }
+
+    /// Divergences so far, including recorded calls that were skipped or not reached yet
+    pub fn report(&self) -> ReplayReport {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut divergences = state.divergences.clone();
+        divergences.extend(
+            state
+                .consumed
+                .iter()
+                .enumerate()
+                .filter(|(_, consumed)| !**consumed)
+                .map(|(position, _)| Divergence::Skipped {
+                    position,
+                    expected: self.calls[position].step.clone(),
+                }),
+        );
+        ReplayReport {
+            recorded: self.calls.len(),
+            replayed: state.consumed.iter().filter(|c| **c).count(),
+            divergences,
+        }
+    }
+}
+
+impl RecordedCall {
+    fn to_result(&self, dir: &Path) -> Result<CallToolResult, McpError> {
+        if let Some(error) = &self.response.error {
+            return Err(serde_json::from_str::<McpError>(error)
+                .unwrap_or_else(|_| McpError::internal_error(error.clone(), None)));
+        }
+        let Some(result) = &self.response.result else {
+            return Ok(CallToolResult::success(Vec::new()));
+        };
+
+        // Sequence steps log `{"content": [...]}` with the extracted JSON of each
+        // content item, direct tool calls log the serialized content list
+        if let Ok(content) = serde_json::from_value::<Vec<Content>>(result.clone()) {
+            return Ok(CallToolResult::success(content));
+        }
+        let items = result
+            .get("content")
+            .and_then(|c| c.as_array())
+            .cloned()
+            .unwrap_or_else(|| vec![result.clone()]);
+        let mut screenshots = self.screenshots.iter();
+        let mut content = Vec::new();
+        for item in items {
+            match item.get("type").and_then(|t| t.as_str()) {
+                // Image data was moved to the screenshot files when logging
+                Some("image") => {
+                    let data = screenshots
+                        .next()
+                        .and_then(|file| std::fs::read(dir.join(file)).ok());
+                    if let Some(data) = data {
+                        let mime_type = item
+                            .get("mime_type")
+                            .and_then(|m| m.as_str())
+                            .unwrap_or("image/png");
+                        content.push(Content::image(BASE64.encode(data), mime_type));
+                    }
+                }
+                Some("text") if item.get("text").is_some_and(|t| t.is_string()) => {
+                    content.push(Content::text(item["text"].as_str().unwrap_or_default()));
+                }
+                _ => content.push(Content::json(item)?),
+            }
+        }
+        Ok(CallToolResult::success(content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution_logger::ExecutionLog;
+    use serde_json::json;
+
+    fn logged(step_id: &str, tool_name: &str, response: Value) -> LoggedExecution {
+        let log: ExecutionLog = serde_json::from_value(json!({
+            "timestamp": "2025-01-01T10:00:00+00:00",
+            "step_id": step_id,
+            "tool_name": tool_name,
+            "request": {},
+            "response": response,
+        }))
+        .unwrap();
+        LoggedExecution {
+            stem: format!("20250101_100000_wf_{step_id}_{tool_name}"),
+            started: chrono::DateTime::parse_from_rfc3339(&log.timestamp).unwrap(),
+            log,
+        }
+    }
+
+    fn replay() -> Replay {
+        Replay::from_logs(
+            Path::new("."),
+            vec![
+                logged(
+                    "open",
+                    "open_application",
+                    json!({"status": "executed_without_error", "duration_ms": 10,
+                           "result": {"content": [{"action": "open_application", "status": "success"}]}}),
+                ),
+                logged(
+                    "save",
+                    "click_element",
+                    json!({"status": "executed_with_error", "duration_ms": 10,
+                           "error": "{\"code\":-32603,\"message\":\"Element not found\"}"}),
+                ),
+                logged(
+                    "recover",
+                    "press_key",
+                    json!({"status": "executed_without_error", "duration_ms": 10,
+                           "result": [{"type": "text", "text": "pressed"}]}),
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_replay_serves_recorded_responses_in_order() {
+        let replay = replay();
+        let opened = replay.respond("open_application", Some("open")).unwrap();
+        let text = opened.content[0].as_text().unwrap().text.clone();
+        assert_eq!(
+            serde_json::from_str::<Value>(&text).unwrap(),
+            json!({"action": "open_application", "status": "success"})
+        );
+
+        let error = replay.respond("click_element", Some("save")).unwrap_err();
+        assert_eq!(error.message, "Element not found");
+
+        let pressed = replay
+            .respond("mcp_terminator-mcp-agent_press_key", Some("recover"))
+            .unwrap();
+        assert_eq!(pressed.content[0].as_text().unwrap().text, "pressed");
+
+        let report = replay.report();
+        assert_eq!((report.recorded, report.replayed), (3, 3));
+        assert!(report.divergences.is_empty());
+    }
+
+    #[test]
+    fn test_replay_flags_divergences() {
+        let replay = replay();
+        // Skipping the first step is reported, and the replay resyncs on "save"
+        assert!(replay.respond("click_element", Some("save")).is_err());
+        assert!(replay.respond("type_into_element", Some("extra")).is_err());
+
+        let report = replay.report();
+        assert_eq!(report.replayed, 1);
+        let messages: Vec<String> = report.divergences.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "#0: expected open_application (step 'open'), got click_element (step 'save')",
+                "#2: type_into_element (step 'extra') was not recorded",
+                "#0: open_application (step 'open') was never replayed",
+                "#2: press_key (step 'recover') was never replayed",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replay_reset_starts_a_new_run() {
+        let replay = replay();
+        assert!(replay.respond("click_element", Some("save")).is_err());
+        replay.reset();
+
+        assert!(replay.respond("open_application", Some("open")).is_ok());
+        let report = replay.report();
+        assert_eq!(report.replayed, 1);
+        // Only the calls the new run has not reached yet are reported
+        assert_eq!(report.divergences.len(), 2);
+        assert!(report
+            .divergences
+            .iter()
+            .all(|d| matches!(d, Divergence::Skipped { .. })));
+    }
+}
@@ -0,0 +1,421 @@
+//! MCP resources: live window trees, execution logs, workflow state files and screenshots
+//!
+//! | URI | Content |
+//! |-----|---------|
+//! | `terminator://tree/{pid}` | compact YAML tree of the application's window |
+//! | `terminator://executions/{file}` | execution log or generated snippet |
+//! | `terminator://screenshots/{file}` | screenshot saved by `screenshot_logger` |
+//! | `terminator://workflows/{folder}/state` | persisted workflow state (`state.json`) |
+//!
+//! Tree resources can be subscribed to; the tree is polled and a
+//! `notifications/resources/updated` is sent whenever it changes.
+
+use crate::execution_logger;
+use base64::{engine::general_purpose, Engine as _};
+use rmcp::model::{
+    AnnotateAble, RawResource, RawResourceTemplate, ReadResourceResult, Resource, ResourceContents,
+    ResourceTemplate, ResourceUpdatedNotificationParam,
+};
+use rmcp::service::{Peer, RoleServer};
+use rmcp::ErrorData as McpError;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use terminator::Desktop;
+use tracing::{debug, warn};
+
+const SCHEME: &str = "terminator://";
+/// Most recent files listed per directory; older ones can still be read by URI
+const MAX_LISTED_FILES: usize = 50;
+const TREE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceUri {
+    Tree(u32),
+    Execution(String),
+    Screenshot(String),
+    WorkflowState(String),
+}
+
+impl ResourceUri {
+    pub fn parse(uri: &str) -> Option<Self> {
+        let rest = uri.strip_prefix(SCHEME)?;
+        let (kind, name) = rest.split_once('/')?;
+        match kind {
+            "tree" => name.parse().ok().map(ResourceUri::Tree),
+            "executions" if is_plain_file_name(name) => {
+                Some(ResourceUri::Execution(name.to_string()))
+            }
+            "screenshots" if is_plain_file_name(name) => {
+                Some(ResourceUri::Screenshot(name.to_string()))
+            }
+            "workflows" => name
+                .strip_suffix("/state")
+                .filter(|folder| is_plain_file_name(folder))
+                .map(|folder| ResourceUri::WorkflowState(folder.to_string())),
+            _ => None,
+        }
+    }
+
+    pub fn to_uri(&self) -> String {
+        match self {
+            ResourceUri::Tree(pid) => format!("{SCHEME}tree/{pid}"),
+            ResourceUri::Execution(file) => format!("{SCHEME}executions/{file}"),
+            ResourceUri::Screenshot(file) => format!("{SCHEME}screenshots/{file}"),
+            ResourceUri::WorkflowState(folder) => format!("{SCHEME}workflows/{folder}/state"),
+        }
+    }
+}
+
+/// Reject anything that could escape the directory it is resolved against: the name
+/// must be exactly one normal path component (no root, prefix, `.` or `..`)
+fn is_plain_file_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(components.next(), Some(Component::Normal(_)))
+        && components.next().is_none()
+        && !name.contains(['/', '\\'])
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+fn mime_type(path: &Path) -> &'static str {
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("json") => "application/json",
+        Some("ts") => "text/typescript",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("yaml" | "yml") => "text/yaml",
+        _ => "text/plain",
+    }
+}
+
+/// The workflow state root, honouring `TERMINATOR_STATE_DIR` like the state store does
+fn workflows_dir() -> PathBuf {
+    crate::workflow_state::state_root()
+        .unwrap_or_else(|| std::env::temp_dir().join("mediar").join("workflows"))
+}
+
+pub fn resource_templates() -> Vec<ResourceTemplate> {
+    let template = |uri_template: &str, name: &str, description: &str, mime_type: &str| {
+        RawResourceTemplate {
+            uri_template: uri_template.to_string(),
+            name: name.to_string(),
+            title: None,
+            description: Some(description.to_string()),
+            mime_type: Some(mime_type.to_string()),
+        }
+        .no_annotation()
+    };
+    vec![
+        template(
+            "terminator://tree/{pid}",
+            "window_tree",
+            "UI tree of the application window owned by a process, as compact YAML. Subscribe to be notified when it changes.",
+            "text/yaml",
+        ),
+        template(
+            "terminator://executions/{file}",
+            "execution",
+            "Execution log (JSON) or generated TypeScript snippet of a past tool call",
+            "application/json",
+        ),
+        template(
+            "terminator://screenshots/{file}",
+            "screenshot",
+            "Screenshot saved during a tool call",
+            "image/png",
+        ),
+        template(
+            "terminator://workflows/{folder}/state",
+            "workflow_state",
+            "Persisted env state of a workflow, used to resume from a step",
+            "application/json",
+        ),
+    ]
+}
+
+/// Files in `dir` accepted by `filter`, newest first
+fn recent_files(dir: &Path, filter: impl Fn(&Path) -> bool) -> Vec<(PathBuf, std::fs::Metadata)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let metadata = entry.metadata().ok()?;
+            (metadata.is_file() && filter(&path)).then_some((path, metadata))
+        })
+        .collect();
+    files.sort_by_key(|(_, metadata)| {
+        std::cmp::Reverse(metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH))
+    });
+    files.truncate(MAX_LISTED_FILES);
+    files
+}
+
+fn file_resource(uri: ResourceUri, path: &Path, metadata: &std::fs::Metadata) -> Resource {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut resource = RawResource::new(uri.to_uri(), name);
+    resource.mime_type = Some(mime_type(path).to_string());
+    resource.size = u32::try_from(metadata.len()).ok();
+    resource.no_annotation()
+}
+
+pub fn list_file_resources() -> Vec<Resource> {
+    let mut resources = Vec::new();
+
+    for (path, metadata) in recent_files(&execution_logger::get_executions_dir(), |p| !is_image(p))
+    {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        resources.push(file_resource(
+            ResourceUri::Execution(name.to_string()),
+            &path,
+            &metadata,
+        ));
+    }
+
+    for (path, metadata) in recent_files(
+        &terminator::screenshot_logger::get_executions_dir(),
+        is_image,
+    ) {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        resources.push(file_resource(
+            ResourceUri::Screenshot(name.to_string()),
+            &path,
+            &metadata,
+        ));
+    }
+
+    if let Ok(entries) = std::fs::read_dir(workflows_dir()) {
+        for entry in entries.flatten() {
+            let state_file = entry.path().join("state.json");
+            let Ok(metadata) = std::fs::metadata(&state_file) else {
+                continue;
+            };
+            let folder = entry.file_name().to_string_lossy().to_string();
+            let uri = ResourceUri::WorkflowState(folder.clone());
+            let mut resource = RawResource::new(uri.to_uri(), format!("{folder} state"));
+            resource.mime_type = Some("application/json".to_string());
+            resource.size = u32::try_from(metadata.len()).ok();
+            resources.push(resource.no_annotation());
+        }
+    }
+
+    resources
+}
+
+/// One tree resource per application window that is currently open
+pub fn list_tree_resources(desktop: &Desktop) -> Vec<Resource> {
+    let applications = match desktop.applications() {
+        Ok(applications) => applications,
+        Err(e) => {
+            warn!("[resources] Failed to list applications: {}", e);
+            return Vec::new();
+        }
+    };
+    let mut seen = std::collections::HashSet::new();
+    applications
+        .iter()
+        .filter_map(|app| {
+            let pid = app.process_id().ok()?;
+            if !seen.insert(pid) {
+                return None;
+            }
+            let name = app
+                .name()
+                .filter(|n| !n.is_empty())
+                .unwrap_or_else(|| pid.to_string());
+            let mut resource = RawResource::new(
+                ResourceUri::Tree(pid).to_uri(),
+                format!("{name} window tree"),
+            );
+            resource.mime_type = Some("text/yaml".to_string());
+            Some(resource.no_annotation())
+        })
+        .collect()
+}
+
+fn read_file(uri: &str, path: &Path) -> Result<ReadResourceResult, McpError> {
+    let bytes = std::fs::read(path).map_err(|e| {
+        McpError::resource_not_found(
+            format!("Failed to read {uri}: {e}"),
+            Some(serde_json::json!({ "uri": uri })),
+        )
+    })?;
+    let mime_type = Some(mime_type(path).to_string());
+    let contents = if is_image(path) {
+        ResourceContents::BlobResourceContents {
+            uri: uri.to_string(),
+            mime_type,
+            blob: general_purpose::STANDARD.encode(bytes),
+            meta: None,
+        }
+    } else {
+        ResourceContents::TextResourceContents {
+            uri: uri.to_string(),
+            mime_type,
+            text: String::from_utf8_lossy(&bytes).into_owned(),
+            meta: None,
+        }
+    };
+    Ok(ReadResourceResult {
+        contents: vec![contents],
+    })
+}
+
+/// The window tree of `pid` as compact YAML
+pub fn window_tree_yaml(desktop: &Desktop, pid: u32) -> Result<String, McpError> {
+    let tree = desktop.get_window_tree(pid, None, None).map_err(|e| {
+        McpError::resource_not_found(
+            format!("Failed to get window tree for PID {pid}: {e}"),
+            Some(serde_json::json!({ "pid": pid })),
+        )
+    })?;
+    Ok(terminator::format_ui_node_as_compact_yaml(&tree, 0).formatted)
+}
+
+pub async fn read_resource(
+    desktop: Arc<Desktop>,
+    uri: &str,
+) -> Result<ReadResourceResult, McpError> {
+    let Some(resource) = ResourceUri::parse(uri) else {
+        return Err(McpError::resource_not_found(
+            format!("Unknown resource URI: {uri}"),
+            Some(serde_json::json!({ "uri": uri })),
+        ));
+    };
+    match resource {
+        ResourceUri::Tree(pid) => {
+            let yaml = tokio::task::spawn_blocking(move || window_tree_yaml(&desktop, pid))
+                .await
+                .map_err(|e| McpError::internal_error(e.to_string(), None))??;
+            Ok(ReadResourceResult {
+                contents: vec![ResourceContents::TextResourceContents {
+                    uri: uri.to_string(),
+                    mime_type: Some("text/yaml".to_string()),
+                    text: yaml,
+                    meta: None,
+                }],
+            })
+        }
+        ResourceUri::Execution(file) => {
+            read_file(uri, &execution_logger::get_executions_dir().join(file))
+        }
+        ResourceUri::Screenshot(file) => read_file(
+            uri,
+            &terminator::screenshot_logger::get_executions_dir().join(file),
+        ),
+        ResourceUri::WorkflowState(folder) => {
+            read_file(uri, &workflows_dir().join(folder).join("state.json"))
+        }
+    }
+}
+
+/// Poll the window tree of `pid` and notify `peer` whenever it changes, until the
+/// window or the connection goes away.
+/// Ids and bounds are ignored so that layout jitter does not count as a change.
+pub async fn watch_tree(desktop: Arc<Desktop>, peer: Peer<RoleServer>, uri: String, pid: u32) {
+    let fingerprint = |desktop: Arc<Desktop>| {
+        tokio::task::spawn_blocking(move || {
+            window_tree_yaml(&desktop, pid)
+                .map(|yaml| crate::ui_tree_diff::remove_ids_and_bounds_from_compact_yaml(&yaml))
+                .ok()
+        })
+    };
+    let mut last = fingerprint(desktop.clone()).await.ok().flatten();
+    loop {
+        tokio::time::sleep(TREE_POLL_INTERVAL).await;
+        if peer.is_transport_closed() {
+            break;
+        }
+        let current = fingerprint(desktop.clone()).await.ok().flatten();
+        if current == last {
+            continue;
+        }
+        debug!("[resources] Window tree of PID {} changed", pid);
+        let closed = current.is_none();
+        last = current;
+        if let Err(e) = peer
+            .notify_resource_updated(ResourceUpdatedNotificationParam { uri: uri.clone() })
+            .await
+        {
+            debug!("[resources] Stopping watch of {}: {}", uri, e);
+            break;
+        }
+        // The window is gone; the final notification lets the client re-read and see the error
+        if closed {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_uri_round_trip() {
+        for uri in [
+            ResourceUri::Tree(4242),
+            ResourceUri::Execution("20250101_120000_click_element.json".to_string()),
+            ResourceUri::Screenshot("20250101_120000_window.png".to_string()),
+            ResourceUri::WorkflowState("github-demo".to_string()),
+        ] {
+            assert_eq!(ResourceUri::parse(&uri.to_uri()), Some(uri));
+        }
+    }
+
+    #[test]
+    fn test_resource_uri_rejects_traversal_and_unknown_kinds() {
+        assert_eq!(ResourceUri::parse("terminator://tree/notapid"), None);
+        assert_eq!(
+            ResourceUri::parse("terminator://executions/../secrets"),
+            None
+        );
+        assert_eq!(ResourceUri::parse("terminator://executions/.."), None);
+        assert_eq!(
+            ResourceUri::parse("terminator://screenshots/a\\b.png"),
+            None
+        );
+        assert_eq!(ResourceUri::parse("terminator://workflows/../state"), None);
+        assert_eq!(ResourceUri::parse("terminator://workflows/demo"), None);
+        assert_eq!(ResourceUri::parse("terminator://executions/./a.json"), None);
+        assert_eq!(ResourceUri::parse("terminator://workflows/./state"), None);
+        // A drive-relative path
+        #[cfg(windows)]
+        assert_eq!(ResourceUri::parse("terminator://screenshots/C:a.png"), None);
+        assert_eq!(ResourceUri::parse("terminator://logs/today.log"), None);
+        assert_eq!(ResourceUri::parse("file:///etc/passwd"), None);
+    }
+
+    #[test]
+    fn test_templates_cover_every_resource_kind() {
+        let templates: Vec<String> = resource_templates()
+            .into_iter()
+            .map(|t| t.raw.uri_template)
+            .collect();
+        assert_eq!(
+            templates,
+            [
+                "terminator://tree/{pid}",
+                "terminator://executions/{file}",
+                "terminator://screenshots/{file}",
+                "terminator://workflows/{folder}/state",
+            ]
+        );
+    }
+}
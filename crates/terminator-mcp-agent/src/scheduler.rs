@@ -0,0 +1,1258 @@
+//! Workflow Scheduler
+//!
+//! Runs the workflows of a folder on their `trigger`: cron schedules (with IANA timezones)
+//! in every transport mode, and webhooks mounted on the HTTP router under `/webhooks/`.
+//! A workflow never runs twice at once - a trigger firing while it is still running is
+//! recorded as skipped. Runs also count against `MCP_MAX_CONCURRENT` - shared with the HTTP
+//! `/mcp` gate, or with interactive tool calls under stdio and SSE - and are registered with
+//! the [`RequestManager`] (see [`RequestGate`]).
+//! Run history is appended to %LOCALAPPDATA%\mediar\scheduler\runs.jsonl.
+
+use crate::cancellation::{RequestContext, RequestManager};
+use crate::server::DesktopWrapper;
+use crate::workflow_typescript::{TriggerConfig, TypeScriptWorkflow};
+use axum::{
+    body::Bytes,
+    extract::Query,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use croner::Cron;
+use futures::future::BoxFuture;
+use rmcp::model::CallToolRequestParam;
+use rmcp::ServiceExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+/// Number of runs kept in memory (the history file keeps everything)
+const MAX_HISTORY: usize = 500;
+
+/// Get the default run history path
+/// Path: %LOCALAPPDATA%/mediar/scheduler/runs.jsonl
+pub fn default_history_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("mediar")
+        .join("scheduler")
+        .join("runs.jsonl")
+}
+
+/// Where a scheduled workflow comes from
+#[derive(Debug, Clone)]
+pub enum WorkflowSource {
+    /// TypeScript workflow folder, run through `execute_sequence` by url
+    TypeScript(PathBuf),
+    /// JSON file holding `execute_sequence` arguments (steps, variables, ...)
+    Sequence { path: PathBuf, arguments: Value },
+}
+
+/// A workflow with a cron or webhook trigger
+#[derive(Debug, Clone)]
+pub struct ScheduledWorkflow {
+    /// Folder name or file stem, used in webhook paths and run history
+    pub id: String,
+    pub name: String,
+    pub source: WorkflowSource,
+    pub trigger: TriggerConfig,
+}
+
+impl ScheduledWorkflow {
+    /// The webhook path this workflow is mounted on, if it has an enabled webhook trigger
+    pub fn webhook_path(&self) -> Option<String> {
+        match &self.trigger {
+            TriggerConfig::Webhook {
+                path,
+                enabled: true,
+            } => {
+                let suffix = path
+                    .as_deref()
+                    .map(|p| p.trim_matches('/'))
+                    .filter(|p| !p.is_empty())
+                    .unwrap_or(&self.id);
+                Some(format!("/webhooks/{suffix}"))
+            }
+            _ => None,
+        }
+    }
+
+    /// The parsed cron schedule, if the workflow has an enabled cron trigger
+    pub fn cron_schedule(&self) -> Option<Result<CronSchedule, String>> {
+        match &self.trigger {
+            TriggerConfig::Cron {
+                schedule,
+                timezone,
+                enabled: true,
+            } => Some(CronSchedule::parse(schedule, timezone.as_deref())),
+            _ => None,
+        }
+    }
+
+    /// `execute_sequence` arguments for one run, with `inputs` merged over the workflow's own
+    pub fn execute_arguments(&self, run_id: &str, inputs: Value) -> Result<Value, String> {
+        let mut arguments = match &self.source {
+            WorkflowSource::TypeScript(path) => json!({
+                "url": format!("file://{}", path.display()),
+            }),
+            WorkflowSource::Sequence { arguments, .. } => arguments.clone(),
+        };
+        let Some(object) = arguments.as_object_mut() else {
+            return Err(format!(
+                "Workflow '{}' arguments must be a JSON object",
+                self.id
+            ));
+        };
+        if let WorkflowSource::Sequence { path, .. } = &self.source {
+            if !object.contains_key("scripts_base_path") {
+                if let Some(dir) = path.parent() {
+                    object.insert(
+                        "scripts_base_path".to_string(),
+                        json!(dir.display().to_string()),
+                    );
+                }
+            }
+        }
+        if let Value::Object(inputs) = inputs {
+            let merged = object.entry("inputs").or_insert_with(|| json!({}));
+            if let Some(merged) = merged.as_object_mut() {
+                merged.extend(inputs);
+            }
+        }
+        object.insert("execution_id".to_string(), json!(run_id));
+        Ok(arguments)
+    }
+}
+
+/// A cron expression (5 or 6 fields) evaluated in a timezone
+#[derive(Debug, Clone)]
+pub struct CronSchedule {
+    cron: Cron,
+    timezone: Tz,
+}
+
+impl CronSchedule {
+    /// Parse a cron expression; the timezone defaults to UTC
+    pub fn parse(schedule: &str, timezone: Option<&str>) -> Result<Self, String> {
+        let cron = Cron::new(schedule)
+            .with_seconds_optional()
+            .parse()
+            .map_err(|e| format!("Invalid cron expression '{schedule}': {e}"))?;
+        let timezone = match timezone {
+            Some(name) => name
+                .parse::<Tz>()
+                .map_err(|_| format!("Unknown timezone '{name}' (expected an IANA name)"))?,
+            None => Tz::UTC,
+        };
+        Ok(Self { cron, timezone })
+    }
+
+    /// The first fire time strictly after `after`
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.cron
+            .find_next_occurrence(&after.with_timezone(&self.timezone), false)
+            .ok()
+            .map(|next| next.with_timezone(&Utc))
+    }
+}
+
+/// Find the triggered workflows in `dir`: TypeScript workflow folders (trigger from their
+/// metadata) and JSON workflow files (top-level `trigger`). Manual and disabled triggers are skipped.
+pub async fn discover_workflows(dir: &Path) -> Vec<ScheduledWorkflow> {
+    let mut entries: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries.flatten().map(|e| e.path()).collect(),
+        Err(e) => {
+            error!("Cannot read workflows directory {}: {}", dir.display(), e);
+            return Vec::new();
+        }
+    };
+    entries.sort();
+
+    let mut workflows = Vec::new();
+    for path in entries {
+        let Some(id) = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        let workflow = if path.is_dir() {
+            if !path.join("terminator.ts").exists() && !path.join("src/terminator.ts").exists() {
+                continue;
+            }
+            load_typescript_workflow(id, &path).await
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            load_sequence_workflow(id, &path)
+        } else {
+            continue;
+        };
+        match workflow {
+            Ok(Some(workflow)) => {
+                info!(
+                    "Scheduled workflow '{}' ({:?}) from {}",
+                    workflow.id,
+                    workflow.trigger,
+                    path.display()
+                );
+                workflows.push(workflow);
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Skipping workflow {}: {}", path.display(), e),
+        }
+    }
+    workflows
+}
+
+fn is_scheduled(trigger: &TriggerConfig) -> bool {
+    matches!(
+        trigger,
+        TriggerConfig::Cron { enabled: true, .. } | TriggerConfig::Webhook { enabled: true, .. }
+    )
+}
+
+async fn load_typescript_workflow(
+    id: String,
+    path: &Path,
+) -> Result<Option<ScheduledWorkflow>, String> {
+    let workflow = TypeScriptWorkflow::new(&format!("file://{}", path.display()))
+        .map_err(|e| e.message.to_string())?;
+    let metadata = workflow
+        .metadata()
+        .await
+        .map_err(|e| e.message.to_string())?;
+    Ok(metadata
+        .trigger
+        .filter(is_scheduled)
+        .map(|trigger| ScheduledWorkflow {
+            id,
+            name: metadata.name,
+            source: WorkflowSource::TypeScript(path.to_path_buf()),
+            trigger,
+        }))
+}
+
+fn load_sequence_workflow(id: String, path: &Path) -> Result<Option<ScheduledWorkflow>, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut workflow: Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    // Accept both bare arguments and the `{tool_name: execute_sequence, arguments}` wrapper
+    if workflow.get("tool_name").and_then(|v| v.as_str()) == Some("execute_sequence") {
+        workflow = workflow["arguments"].take();
+    }
+    let Some(arguments) = workflow.as_object_mut() else {
+        return Ok(None);
+    };
+    if !arguments.contains_key("steps") {
+        return Ok(None);
+    }
+    let Some(trigger) = arguments.remove("trigger") else {
+        return Ok(None);
+    };
+    let trigger: TriggerConfig =
+        serde_json::from_value(trigger).map_err(|e| format!("Invalid trigger: {e}"))?;
+    if !is_scheduled(&trigger) {
+        return Ok(None);
+    }
+    let name = arguments
+        .get("name")
+        .and_then(|v| v.as_str())
+        .unwrap_or(&id)
+        .to_string();
+    Ok(Some(ScheduledWorkflow {
+        id,
+        name,
+        source: WorkflowSource::Sequence {
+            path: path.to_path_buf(),
+            arguments: workflow,
+        },
+        trigger,
+    }))
+}
+
+/// Executes one workflow run; the output is the `execute_sequence` result
+pub trait WorkflowRunner: Send + Sync {
+    fn run(
+        &self,
+        workflow: &ScheduledWorkflow,
+        run_id: &str,
+        inputs: Value,
+    ) -> BoxFuture<'static, Result<Value, String>>;
+}
+
+/// Runs workflows through the agent's own `execute_sequence` tool, over an in-process MCP connection
+pub struct McpWorkflowRunner {
+    desktop: DesktopWrapper,
+}
+
+impl McpWorkflowRunner {
+    pub fn new(desktop: DesktopWrapper) -> Self {
+        Self { desktop }
+    }
+}
+
+impl WorkflowRunner for McpWorkflowRunner {
+    fn run(
+        &self,
+        workflow: &ScheduledWorkflow,
+        run_id: &str,
+        inputs: Value,
+    ) -> BoxFuture<'static, Result<Value, String>> {
+        let desktop = self.desktop.clone();
+        let arguments = workflow.execute_arguments(run_id, inputs);
+        Box::pin(async move {
+            let arguments = arguments?;
+            let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+            let server = tokio::spawn(async move {
+                match desktop.serve(server_io).await {
+                    Ok(service) => {
+                        let _ = service.waiting().await;
+                    }
+                    Err(e) => warn!("Scheduler MCP session failed: {}", e),
+                }
+            });
+            let client = ()
+                .serve(client_io)
+                .await
+                .map_err(|e| format!("Failed to connect to the MCP server: {e}"))?;
+            let result = client
+                .call_tool(CallToolRequestParam {
+                    name: "execute_sequence".into(),
+                    arguments: arguments.as_object().cloned(),
+                })
+                .await;
+            let _ = client.cancel().await;
+            let _ = server.await;
+
+            let result = result.map_err(|e| e.to_string())?;
+            let output = result
+                .content
+                .first()
+                .and_then(|content| crate::server::extract_content_json(content).ok())
+                .unwrap_or(Value::Null);
+            let status = output.get("status").and_then(|s| s.as_str());
+            if result.is_error == Some(true)
+                || !matches!(status, Some("executed_without_error" | "success"))
+            {
+                let message = ["error", "message", "text"]
+                    .iter()
+                    .find_map(|key| output.get(key).and_then(|v| v.as_str()))
+                    .unwrap_or(status.unwrap_or("workflow failed"));
+                return Err(message.to_string());
+            }
+            Ok(output)
+        })
+    }
+}
+
+/// What started a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunTrigger {
+    Cron,
+    Webhook,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunStatus {
+    Succeeded,
+    Failed,
+    /// The workflow was still running when the trigger fired
+    Skipped,
+}
+
+/// One entry of the run history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub run_id: String,
+    pub workflow_id: String,
+    pub trigger: RunTrigger,
+    pub status: RunStatus,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Run history, appended to a JSONL file and kept (capped) in memory
+pub struct RunHistory {
+    path: Option<PathBuf>,
+    records: Mutex<VecDeque<RunRecord>>,
+    /// Serializes appends so lines land in the order they were recorded
+    file_lock: tokio::sync::Mutex<()>,
+}
+
+impl RunHistory {
+    /// Load the most recent runs from `path` (a missing file is an empty history)
+    pub fn load(path: PathBuf) -> Self {
+        let mut records = VecDeque::new();
+        if let Ok(content) = fs::read_to_string(&path) {
+            for line in content.lines() {
+                match serde_json::from_str::<RunRecord>(line) {
+                    Ok(record) => {
+                        if records.len() == MAX_HISTORY {
+                            records.pop_front();
+                        }
+                        records.push_back(record);
+                    }
+                    Err(e) => warn!("Ignoring malformed run history line: {}", e),
+                }
+            }
+        }
+        Self {
+            path: Some(path),
+            records: Mutex::new(records),
+            file_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// A history that is not persisted
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            records: Mutex::new(VecDeque::new()),
+            file_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    fn records(&self) -> MutexGuard<'_, VecDeque<RunRecord>> {
+        self.records.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Add a run; the file append happens on the blocking pool
+    pub async fn record(&self, record: RunRecord) {
+        {
+            let mut records = self.records();
+            if records.len() == MAX_HISTORY {
+                records.pop_front();
+            }
+            records.push_back(record.clone());
+        }
+        let Some(path) = self.path.clone() else {
+            return;
+        };
+        let _guard = self.file_lock.lock().await;
+        let result = tokio::task::spawn_blocking({
+            let path = path.clone();
+            move || append_line(&path, &record)
+        })
+        .await
+        .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+        if let Err(e) = result {
+            warn!("Failed to write run history to {}: {}", path.display(), e);
+        }
+    }
+
+    /// The latest runs, newest first, optionally for one workflow
+    pub fn recent(&self, workflow_id: Option<&str>, limit: usize) -> Vec<RunRecord> {
+        self.records()
+            .iter()
+            .rev()
+            .filter(|r| workflow_id.is_none_or(|id| r.workflow_id == id))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+fn append_line(path: &Path, record: &RunRecord) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)
+}
+
+/// Why a trigger did not start a run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TriggerError {
+    UnknownWorkflow(String),
+    AlreadyRunning {
+        workflow_id: String,
+        run_id: String,
+    },
+    /// The server already handles `MCP_MAX_CONCURRENT` requests
+    Busy {
+        active: usize,
+        max_concurrent: usize,
+    },
+}
+
+impl std::fmt::Display for TriggerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TriggerError::UnknownWorkflow(id) => write!(f, "Unknown workflow '{id}'"),
+            TriggerError::AlreadyRunning {
+                workflow_id,
+                run_id,
+            } => write!(
+                f,
+                "Workflow '{workflow_id}' is already running (run {run_id})"
+            ),
+            TriggerError::Busy {
+                active,
+                max_concurrent,
+            } => write!(
+                f,
+                "Server is busy ({active} of {max_concurrent} concurrent requests active)"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TriggerError {}
+
+/// The server's concurrency limit, shared with the HTTP `/mcp` gate or the interactive
+/// tool calls of stdio and SSE so scheduled runs and client requests draw from the same
+/// `MCP_MAX_CONCURRENT` budget
+#[derive(Clone)]
+pub struct RequestGate {
+    pub active_requests: Arc<AtomicUsize>,
+    pub max_concurrent: usize,
+    pub request_manager: RequestManager,
+}
+
+impl RequestGate {
+    /// A gate limited by `MCP_MAX_CONCURRENT` (default 1)
+    pub fn from_env(request_manager: RequestManager) -> Self {
+        Self {
+            active_requests: Arc::new(AtomicUsize::new(0)),
+            max_concurrent: std::env::var("MCP_MAX_CONCURRENT")
+                .ok()
+                .and_then(|s| s.parse::<usize>().ok())
+                .unwrap_or(1),
+            request_manager,
+        }
+    }
+
+    /// Hold a slot for an interactive tool call until the returned guard drops
+    pub fn enter(&self) -> Result<GateSlot<'_>, TriggerError> {
+        self.acquire().map(|()| GateSlot { gate: self })
+    }
+
+    /// Take a slot, or report how busy the server is
+    fn acquire(&self) -> Result<(), TriggerError> {
+        self.active_requests
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |active| {
+                (active < self.max_concurrent).then_some(active + 1)
+            })
+            .map(|_| ())
+            .map_err(|active| TriggerError::Busy {
+                active,
+                max_concurrent: self.max_concurrent,
+            })
+    }
+}
+
+/// A slot taken with [`RequestGate::enter`]
+pub struct GateSlot<'a> {
+    gate: &'a RequestGate,
+}
+
+impl Drop for GateSlot<'_> {
+    fn drop(&mut self) {
+        self.gate.active_requests.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+struct SchedulerInner {
+    workflows: Vec<ScheduledWorkflow>,
+    runner: Arc<dyn WorkflowRunner>,
+    history: RunHistory,
+    /// Workflow id -> id of its active run
+    running: Mutex<HashMap<String, String>>,
+    gate: Option<RequestGate>,
+    cancel: CancellationToken,
+}
+
+impl SchedulerInner {
+    fn running(&self) -> MutexGuard<'_, HashMap<String, String>> {
+        self.running.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Releases a run's reservation (running entry, gate slot, request registration)
+/// however the run ends, including a panicking runner or an aborted task
+struct RunGuard {
+    inner: Arc<SchedulerInner>,
+    workflow_id: String,
+    run_id: String,
+}
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        self.inner.running().remove(&self.workflow_id);
+        if let Some(gate) = &self.inner.gate {
+            gate.active_requests.fetch_sub(1, Ordering::SeqCst);
+            if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                let manager = gate.request_manager.clone();
+                let run_id = self.run_id.clone();
+                handle.spawn(async move { manager.unregister(&run_id).await });
+            }
+        }
+    }
+}
+
+/// Fires workflow triggers; cheap to clone
+#[derive(Clone)]
+pub struct Scheduler {
+    inner: Arc<SchedulerInner>,
+}
+
+impl Scheduler {
+    pub fn new(
+        workflows: Vec<ScheduledWorkflow>,
+        runner: Arc<dyn WorkflowRunner>,
+        history: RunHistory,
+    ) -> Self {
+        Self {
+            inner: Arc::new(SchedulerInner {
+                workflows,
+                runner,
+                history,
+                running: Mutex::new(HashMap::new()),
+                gate: None,
+                cancel: CancellationToken::new(),
+            }),
+        }
+    }
+
+    /// Count runs against the server's concurrency limit; call before [`Scheduler::start`]
+    pub fn with_gate(mut self, gate: RequestGate) -> Self {
+        Arc::get_mut(&mut self.inner)
+            .expect("with_gate must be called before the scheduler is shared")
+            .gate = Some(gate);
+        self
+    }
+
+    pub fn workflows(&self) -> &[ScheduledWorkflow] {
+        &self.inner.workflows
+    }
+
+    pub fn history(&self) -> &RunHistory {
+        &self.inner.history
+    }
+
+    /// Start the cron loops; they stop on [`Scheduler::shutdown`]
+    pub fn start(&self) {
+        for workflow in &self.inner.workflows {
+            match workflow.cron_schedule() {
+                Some(Ok(schedule)) => {
+                    let scheduler = self.clone();
+                    let workflow_id = workflow.id.clone();
+                    tokio::spawn(async move { scheduler.cron_loop(workflow_id, schedule).await });
+                }
+                Some(Err(e)) => error!("Not scheduling workflow '{}': {}", workflow.id, e),
+                None => {}
+            }
+        }
+    }
+
+    pub fn shutdown(&self) {
+        self.inner.cancel.cancel();
+    }
+
+    async fn cron_loop(self, workflow_id: String, schedule: CronSchedule) {
+        let mut after = Utc::now();
+        loop {
+            let Some(next) = schedule.next_after(after) else {
+                warn!(
+                    "Cron schedule of workflow '{}' never fires again",
+                    workflow_id
+                );
+                return;
+            };
+            info!("Workflow '{}' next runs at {}", workflow_id, next);
+            let wait = (next - Utc::now()).to_std().unwrap_or_default();
+            tokio::select! {
+                _ = self.inner.cancel.cancelled() => return,
+                _ = tokio::time::sleep(wait) => {}
+            }
+            if let Err(e) = self
+                .trigger(&workflow_id, RunTrigger::Cron, Value::Null)
+                .await
+            {
+                warn!("Cron trigger skipped: {}", e);
+            }
+            // Missed fire times (e.g. after the machine slept) are not replayed
+            after = next.max(Utc::now());
+        }
+    }
+
+    /// Start a run in the background and return its run id
+    pub async fn trigger(
+        &self,
+        workflow_id: &str,
+        trigger: RunTrigger,
+        inputs: Value,
+    ) -> Result<String, TriggerError> {
+        let (workflow, guard) = self.reserve(workflow_id, trigger).await?;
+        let run_id = guard.run_id.clone();
+        let scheduler = self.clone();
+        tokio::spawn(async move { scheduler.execute(workflow, guard, trigger, inputs).await });
+        Ok(run_id)
+    }
+
+    /// Run a workflow to completion
+    pub async fn run(
+        &self,
+        workflow_id: &str,
+        trigger: RunTrigger,
+        inputs: Value,
+    ) -> Result<RunRecord, TriggerError> {
+        let (workflow, guard) = self.reserve(workflow_id, trigger).await?;
+        Ok(self.execute(workflow, guard, trigger, inputs).await)
+    }
+
+    /// Mark the workflow as running, or record the trigger as skipped if it already is
+    /// (or the server has no free request slot)
+    async fn reserve(
+        &self,
+        workflow_id: &str,
+        trigger: RunTrigger,
+    ) -> Result<(ScheduledWorkflow, RunGuard), TriggerError> {
+        let workflow = self
+            .inner
+            .workflows
+            .iter()
+            .find(|w| w.id == workflow_id)
+            .ok_or_else(|| TriggerError::UnknownWorkflow(workflow_id.to_string()))?;
+        let run_id = uuid::Uuid::new_v4().to_string();
+
+        let reserved = {
+            let mut running = self.inner.running();
+            match running.get(workflow_id) {
+                Some(active) => Err(TriggerError::AlreadyRunning {
+                    workflow_id: workflow_id.to_string(),
+                    run_id: active.clone(),
+                }),
+                None => match self.inner.gate.as_ref().map(RequestGate::acquire) {
+                    Some(Err(busy)) => Err(busy),
+                    _ => {
+                        running.insert(workflow_id.to_string(), run_id.clone());
+                        Ok(())
+                    }
+                },
+            }
+        };
+        if let Err(error) = reserved {
+            let now = Utc::now();
+            self.inner
+                .history
+                .record(RunRecord {
+                    run_id,
+                    workflow_id: workflow_id.to_string(),
+                    trigger,
+                    status: RunStatus::Skipped,
+                    started_at: now,
+                    finished_at: now,
+                    error: Some(error.to_string()),
+                })
+                .await;
+            return Err(error);
+        }
+        let guard = RunGuard {
+            inner: self.inner.clone(),
+            workflow_id: workflow_id.to_string(),
+            run_id,
+        };
+        Ok((workflow.clone(), guard))
+    }
+
+    async fn execute(
+        &self,
+        workflow: ScheduledWorkflow,
+        guard: RunGuard,
+        trigger: RunTrigger,
+        inputs: Value,
+    ) -> RunRecord {
+        let run_id = guard.run_id.clone();
+        info!(
+            "Running workflow '{}' ({:?} trigger, run {})",
+            workflow.id, trigger, run_id
+        );
+        let started_at = Utc::now();
+        // Registered under the run id so cancel_all and /metrics see scheduled runs
+        let context: Option<RequestContext> = match &self.inner.gate {
+            Some(gate) => Some(gate.request_manager.register(run_id.clone(), None).await),
+            None => None,
+        };
+        let run = self.inner.runner.run(&workflow, &run_id, inputs);
+        let result = match &context {
+            Some(context) => tokio::select! {
+                result = run => result,
+                _ = context.cancellation_token.cancelled() => Err("Run was cancelled".to_string()),
+            },
+            None => run.await,
+        };
+        drop(guard);
+
+        let record = RunRecord {
+            run_id,
+            workflow_id: workflow.id,
+            trigger,
+            status: if result.is_ok() {
+                RunStatus::Succeeded
+            } else {
+                RunStatus::Failed
+            },
+            started_at,
+            finished_at: Utc::now(),
+            error: result.err(),
+        };
+        match &record.error {
+            Some(e) => warn!("Workflow '{}' failed: {}", record.workflow_id, e),
+            None => info!("Workflow '{}' succeeded", record.workflow_id),
+        }
+        self.inner.history.record(record.clone()).await;
+        record
+    }
+
+    /// Routes for the webhook triggers (`POST /webhooks/...`) and `GET /scheduler/runs`
+    pub fn router(&self) -> Router {
+        let mut router = Router::new();
+        let mut paths = HashSet::new();
+        for workflow in &self.inner.workflows {
+            let Some(path) = workflow.webhook_path() else {
+                continue;
+            };
+            if !paths.insert(path.clone()) {
+                warn!(
+                    "Webhook path {} of workflow '{}' is already in use, skipping",
+                    path, workflow.id
+                );
+                continue;
+            }
+            info!("Webhook for workflow '{}': POST {}", workflow.id, path);
+            let scheduler = self.clone();
+            let workflow_id = workflow.id.clone();
+            router = router.route(
+                &path,
+                post(
+                    move |body: Bytes| async move { scheduler.webhook(&workflow_id, &body).await },
+                ),
+            );
+        }
+        let scheduler = self.clone();
+        router.route(
+            "/scheduler/runs",
+            get(move |Query(query): Query<RunsQuery>| async move {
+                let runs = scheduler
+                    .inner
+                    .history
+                    .recent(query.workflow.as_deref(), query.limit.unwrap_or(50));
+                Json(json!({ "runs": runs }))
+            }),
+        )
+    }
+
+    async fn webhook(&self, workflow_id: &str, body: &[u8]) -> Response {
+        let inputs = if body.iter().all(u8::is_ascii_whitespace) {
+            Value::Null
+        } else {
+            match serde_json::from_slice(body) {
+                Ok(inputs) => inputs,
+                Err(e) => {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        Json(json!({"error": format!("Invalid JSON body: {e}")})),
+                    )
+                        .into_response()
+                }
+            }
+        };
+        match self.trigger(workflow_id, RunTrigger::Webhook, inputs).await {
+            Ok(run_id) => (
+                StatusCode::ACCEPTED,
+                Json(json!({"status": "accepted", "workflow_id": workflow_id, "run_id": run_id})),
+            )
+                .into_response(),
+            Err(e @ TriggerError::AlreadyRunning { .. }) => (
+                StatusCode::CONFLICT,
+                Json(json!({"status": "skipped", "workflow_id": workflow_id, "error": e.to_string()})),
+            )
+                .into_response(),
+            Err(e @ TriggerError::Busy { .. }) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(json!({"status": "skipped", "workflow_id": workflow_id, "error": e.to_string()})),
+            )
+                .into_response(),
+            Err(e) => (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RunsQuery {
+    workflow: Option<String>,
+    limit: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::Notify;
+
+    /// Runner that blocks until released and fails when the inputs ask it to
+    struct FakeRunner {
+        release: Arc<Notify>,
+    }
+
+    impl WorkflowRunner for FakeRunner {
+        fn run(
+            &self,
+            _workflow: &ScheduledWorkflow,
+            _run_id: &str,
+            inputs: Value,
+        ) -> BoxFuture<'static, Result<Value, String>> {
+            let release = self.release.clone();
+            let fail = inputs.get("fail").is_some();
+            Box::pin(async move {
+                release.notified().await;
+                if fail {
+                    Err("step failed".to_string())
+                } else {
+                    Ok(json!({"status": "executed_without_error"}))
+                }
+            })
+        }
+    }
+
+    fn sequence_workflow(id: &str, trigger: TriggerConfig) -> ScheduledWorkflow {
+        ScheduledWorkflow {
+            id: id.to_string(),
+            name: id.to_string(),
+            source: WorkflowSource::Sequence {
+                path: PathBuf::from("/workflows").join(format!("{id}.json")),
+                arguments: json!({"steps": [], "inputs": {"a": 1, "b": 2}}),
+            },
+            trigger,
+        }
+    }
+
+    fn scheduler(workflows: Vec<ScheduledWorkflow>) -> (Scheduler, Arc<Notify>) {
+        let release = Arc::new(Notify::new());
+        let runner = FakeRunner {
+            release: release.clone(),
+        };
+        let scheduler = Scheduler::new(workflows, Arc::new(runner), RunHistory::in_memory());
+        (scheduler, release)
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_cron_schedule_uses_timezone() {
+        let schedule = CronSchedule::parse("0 9 * * *", Some("America/New_York")).unwrap();
+        // 09:00 in New York is 13:00 UTC in summer and 14:00 UTC in winter
+        assert_eq!(
+            schedule.next_after(at("2025-07-01T12:00:00Z")),
+            Some(at("2025-07-01T13:00:00Z"))
+        );
+        assert_eq!(
+            schedule.next_after(at("2025-12-01T14:00:00Z")),
+            Some(at("2025-12-02T14:00:00Z"))
+        );
+
+        let schedule = CronSchedule::parse("*/30 * * * * *", None).unwrap();
+        assert_eq!(
+            schedule.next_after(at("2025-07-01T12:00:00Z")),
+            Some(at("2025-07-01T12:00:30Z"))
+        );
+
+        assert!(CronSchedule::parse("0 9 * *", None).is_err());
+        assert!(CronSchedule::parse("0 9 * * *", Some("Mars/Base")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_discovers_json_workflows_with_triggers() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("nightly.json"),
+            r#"{"name": "Nightly", "steps": [{"tool_name": "delay", "arguments": {"delay_ms": 1}}],
+                "trigger": {"type": "cron", "schedule": "0 2 * * *", "timezone": "Europe/Paris"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("hook.json"),
+            r#"{"tool_name": "execute_sequence", "arguments": {"steps": [],
+                "trigger": {"type": "webhook", "path": "/orders/new"}}}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("manual.json"),
+            r#"{"steps": [], "trigger": {"type": "manual"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("disabled.json"),
+            r#"{"steps": [], "trigger": {"type": "cron", "schedule": "* * * * *", "enabled": false}}"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("state.json"), r#"{"context": {}}"#).unwrap();
+
+        let workflows = discover_workflows(dir.path()).await;
+        let ids: Vec<_> = workflows.iter().map(|w| w.id.as_str()).collect();
+        assert_eq!(ids, ["hook", "nightly"]);
+
+        assert_eq!(
+            workflows[0].webhook_path().as_deref(),
+            Some("/webhooks/orders/new")
+        );
+        assert!(workflows[0].cron_schedule().is_none());
+        let WorkflowSource::Sequence { arguments, .. } = &workflows[0].source else {
+            panic!("expected a sequence workflow");
+        };
+        assert!(arguments.get("trigger").is_none());
+
+        assert_eq!(workflows[1].name, "Nightly");
+        assert!(workflows[1].cron_schedule().unwrap().is_ok());
+        assert_eq!(workflows[1].webhook_path(), None);
+    }
+
+    #[test]
+    fn test_execute_arguments_merge_inputs() {
+        let workflow = sequence_workflow("job", TriggerConfig::Manual { enabled: true });
+        let arguments = workflow
+            .execute_arguments("run-1", json!({"b": 3}))
+            .unwrap();
+        assert_eq!(arguments["inputs"], json!({"a": 1, "b": 3}));
+        assert_eq!(arguments["execution_id"], "run-1");
+        assert!(arguments["scripts_base_path"].is_string());
+
+        let workflow = ScheduledWorkflow {
+            source: WorkflowSource::TypeScript(PathBuf::from("/workflows/invoice")),
+            ..workflow
+        };
+        let arguments = workflow.execute_arguments("run-2", Value::Null).unwrap();
+        assert_eq!(arguments["url"], "file:///workflows/invoice");
+        assert!(arguments.get("inputs").is_none());
+
+        let workflow = ScheduledWorkflow {
+            source: WorkflowSource::Sequence {
+                path: PathBuf::from("/workflows/list.json"),
+                arguments: json!(["not", "an", "object"]),
+            },
+            ..workflow
+        };
+        let error = workflow
+            .execute_arguments("run-3", Value::Null)
+            .unwrap_err();
+        assert!(error.contains("must be a JSON object"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn test_overlapping_triggers_are_skipped() {
+        let workflow = sequence_workflow(
+            "job",
+            TriggerConfig::Webhook {
+                path: None,
+                enabled: true,
+            },
+        );
+        let (scheduler, release) = scheduler(vec![workflow]);
+
+        let first = tokio::spawn({
+            let scheduler = scheduler.clone();
+            async move { scheduler.run("job", RunTrigger::Webhook, json!({})).await }
+        });
+        while scheduler.inner.running().is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        let Err(TriggerError::AlreadyRunning { run_id, .. }) = scheduler
+            .trigger("job", RunTrigger::Cron, Value::Null)
+            .await
+        else {
+            panic!("expected the second trigger to be skipped");
+        };
+        assert_eq!(
+            scheduler
+                .trigger("other", RunTrigger::Cron, Value::Null)
+                .await,
+            Err(TriggerError::UnknownWorkflow("other".to_string()))
+        );
+
+        release.notify_one();
+        let record = first.await.unwrap().unwrap();
+        assert_eq!(record.run_id, run_id);
+        assert_eq!(record.status, RunStatus::Succeeded);
+
+        // Runs again once the first run finished
+        let second = tokio::spawn({
+            let scheduler = scheduler.clone();
+            async move {
+                scheduler
+                    .run("job", RunTrigger::Webhook, json!({"fail": true}))
+                    .await
+            }
+        });
+        release.notify_one();
+        let record = second.await.unwrap().unwrap();
+        assert_eq!(record.status, RunStatus::Failed);
+        assert_eq!(record.error.as_deref(), Some("step failed"));
+
+        let statuses: Vec<_> = scheduler
+            .history()
+            .recent(Some("job"), 10)
+            .iter()
+            .map(|r| (r.trigger, r.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                (RunTrigger::Webhook, RunStatus::Failed),
+                (RunTrigger::Webhook, RunStatus::Succeeded),
+                (RunTrigger::Cron, RunStatus::Skipped),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_runs_share_the_request_gate() {
+        let workflow = sequence_workflow("job", TriggerConfig::Manual { enabled: true });
+        let gate = RequestGate {
+            active_requests: Arc::new(AtomicUsize::new(1)),
+            max_concurrent: 1,
+            request_manager: RequestManager::new(),
+        };
+        let (scheduler, release) = scheduler(vec![workflow]);
+        let scheduler = scheduler.with_gate(gate.clone());
+
+        // An in-flight MCP request holds the only slot
+        assert!(matches!(
+            scheduler.run("job", RunTrigger::Cron, Value::Null).await,
+            Err(TriggerError::Busy { active: 1, .. })
+        ));
+        assert!(scheduler.inner.running().is_empty());
+
+        gate.active_requests.store(0, Ordering::SeqCst);
+        let run_id = scheduler
+            .trigger("job", RunTrigger::Cron, Value::Null)
+            .await
+            .unwrap();
+        assert_eq!(gate.active_requests.load(Ordering::SeqCst), 1);
+        // Interactive tool calls wait their turn behind the scheduled run
+        assert!(matches!(
+            gate.enter(),
+            Err(TriggerError::Busy { active: 1, .. })
+        ));
+        while gate.request_manager.get(&run_id).await.is_none() {
+            tokio::task::yield_now().await;
+        }
+        gate.request_manager.cancel_request(&run_id).await;
+        while scheduler.history().recent(None, 1)[0].run_id != run_id {
+            tokio::task::yield_now().await;
+        }
+        let record = &scheduler.history().recent(None, 1)[0];
+        assert_eq!(record.status, RunStatus::Failed);
+        assert_eq!(record.error.as_deref(), Some("Run was cancelled"));
+        assert_eq!(gate.active_requests.load(Ordering::SeqCst), 0);
+        assert!(scheduler.inner.running().is_empty());
+
+        let slot = gate.enter().unwrap();
+        assert_eq!(gate.active_requests.load(Ordering::SeqCst), 1);
+        drop(slot);
+        assert_eq!(gate.active_requests.load(Ordering::SeqCst), 0);
+        drop(release);
+    }
+
+    #[tokio::test]
+    async fn test_history_persists_across_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scheduler").join("runs.jsonl");
+        let history = RunHistory::load(path.clone());
+        for (i, status) in [RunStatus::Succeeded, RunStatus::Failed]
+            .into_iter()
+            .enumerate()
+        {
+            history
+                .record(RunRecord {
+                    run_id: format!("run-{i}"),
+                    workflow_id: "job".to_string(),
+                    trigger: RunTrigger::Cron,
+                    status,
+                    started_at: at("2025-07-01T12:00:00Z"),
+                    finished_at: at("2025-07-01T12:01:00Z"),
+                    error: None,
+                })
+                .await;
+        }
+
+        let reloaded = RunHistory::load(path);
+        let runs: Vec<_> = reloaded
+            .recent(None, 10)
+            .into_iter()
+            .map(|r| (r.run_id, r.status))
+            .collect();
+        assert_eq!(
+            runs,
+            [
+                ("run-1".to_string(), RunStatus::Failed),
+                ("run-0".to_string(), RunStatus::Succeeded)
+            ]
+        );
+        assert!(reloaded.recent(Some("other"), 10).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_webhook_routes() {
+        let workflow = sequence_workflow(
+            "job",
+            TriggerConfig::Webhook {
+                path: Some("orders".to_string()),
+                enabled: true,
+            },
+        );
+        let (scheduler, release) = scheduler(vec![workflow]);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let router = scheduler.router();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let client = reqwest::Client::new();
+        let url = format!("http://{addr}/webhooks/orders");
+        let response = client.post(&url).body("not json").send().await.unwrap();
+        assert_eq!(response.status(), 400);
+
+        let response = client
+            .post(&url)
+            .json(&json!({"id": 7}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 202);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["workflow_id"], "job");
+
+        let response = client.post(&url).send().await.unwrap();
+        assert_eq!(response.status(), 409);
+
+        release.notify_one();
+        while scheduler.history().recent(None, 1)[0].status == RunStatus::Skipped {
+            tokio::task::yield_now().await;
+        }
+        let runs: Value = client
+            .get(format!("http://{addr}/scheduler/runs?workflow=job&limit=1"))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(runs["runs"][0]["run_id"], body["run_id"]);
+        assert_eq!(runs["runs"][0]["status"], "succeeded");
+    }
+}
@@ -1,4 +1,8 @@
-use crate::event_pipe::{create_event_channel, EventPipeServer, EventSender, WorkflowEvent};
+use crate::event_pipe::EventSender;
+#[cfg(windows)]
+use crate::event_pipe::WorkflowEvent;
+#[cfg(windows)]
+use crate::event_pipe::{create_event_channel, EventPipeServer};
 use rmcp::ErrorData as McpError;
 use serde_json::json;
 use std::path::PathBuf;
@@ -1298,8 +1302,9 @@ try {{
         None
     };
 
+    // Events are streamed over a named pipe, which only exists on Windows
     #[cfg(not(windows))]
-    let pipe_server_handle: Option<((), String)> = None;
+    let _ = (&event_sender, execution_id);
 
     // Build command based on runtime type
     let mut cmd = if runtime == "bun" && !is_batch_file {
@@ -1839,8 +1844,9 @@ console.log('[TypeScript] Current working directory:', process.cwd());
         None
     };
 
+    // Events are streamed over a named pipe, which only exists on Windows
     #[cfg(not(windows))]
-    let pipe_server_handle: Option<((), String)> = None;
+    let _ = (&event_sender, execution_id);
 
     let mut cmd = if runtime == "bun" {
         // Bun can run TypeScript directly
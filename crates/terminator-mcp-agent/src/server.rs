@@ -33,8 +33,11 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use sysinfo::{ProcessesToUpdate, System};
+#[cfg(target_os = "windows")]
 use terminator::element::UIElementImpl;
-use terminator::{AutomationError, Browser, Desktop, Selector, UIElement};
+use terminator::{
+    ActionabilityOptions, AutomationError, Browser, Desktop, Selector, UIElement, WaitCondition,
+};
 use tokio::sync::Mutex;
 use tracing::{info, warn, Instrument};
 
@@ -263,6 +266,7 @@ impl DesktopWrapper {
 
     /// Prepare window management before tool execution
     /// Handles window cache update, state capture, minimize all, and maximize target
+    #[cfg(target_os = "windows")]
     async fn prepare_window_management(
         &self,
         process: &str,
@@ -641,9 +645,23 @@ impl DesktopWrapper {
         Ok(())
     }
 
+    /// Window management is Windows-only; elsewhere there is nothing to prepare
+    #[cfg(not(target_os = "windows"))]
+    async fn prepare_window_management(
+        &self,
+        _process: &str,
+        _execution_context: Option<&crate::utils::ToolExecutionContext>,
+        _process_id: Option<u32>,
+        _ui_element: Option<&terminator::UIElement>,
+        _window_mgmt_opts: &crate::utils::WindowManagementOptions,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
     /// Restore windows after tool execution
     /// Only restores for non-sequence calls or when explicitly needed
     async fn restore_window_management(&self, should_restore: bool) {
+        #[cfg(target_os = "windows")]
         if should_restore {
             if let Err(e) = self.window_manager.restore_all_windows().await {
                 tracing::warn!("Failed to restore windows: {}", e);
@@ -652,6 +670,8 @@ impl DesktopWrapper {
             }
             self.window_manager.clear_captured_state().await;
         }
+        #[cfg(not(target_os = "windows"))]
+        let _ = should_restore;
     }
 
     // Minimal, conservative parser to extract `{ set_env: {...} }` from simple scripts
@@ -799,6 +819,7 @@ impl DesktopWrapper {
             captured_stderr_logs: Arc::new(std::sync::Mutex::new(Vec::new())),
             current_workflow_dir: Arc::new(Mutex::new(None)),
             current_scripts_base_path: Arc::new(Mutex::new(None)),
+            #[cfg(target_os = "windows")]
             window_manager: Arc::new(terminator::WindowManager::new()),
             in_sequence: Arc::new(std::sync::Mutex::new(false)),
             ocr_bounds: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
@@ -812,9 +833,23 @@ impl DesktopWrapper {
             client_modes: Arc::new(Mutex::new(std::collections::HashMap::new())),
             elicitation_peer: Arc::new(Mutex::new(None)),
             broadcast_peers: Arc::new(Mutex::new(Vec::new())),
+            resource_subscriptions: Arc::new(Mutex::new(HashMap::new())),
+            session_id: uuid::Uuid::new_v4().to_string(),
+            replay: crate::replay::Replay::from_env().map(Arc::new),
+            state_store: crate::workflow_state::from_env(),
+            tool_gate: None,
         })
     }
 
+    /// A clone for a new client connection: shares all state, but keeps its own
+    /// resource subscriptions
+    pub fn for_session(&self) -> Self {
+        Self {
+            session_id: uuid::Uuid::new_v4().to_string(),
+            ..self.clone()
+        }
+    }
+
     /// Detect if a PID belongs to a browser process
     /// Delegates to terminator::is_browser_process for consistent browser detection
     fn detect_browser_by_pid(pid: u32) -> bool {
@@ -1325,7 +1360,7 @@ impl DesktopWrapper {
     }
 
     #[tool(
-        description = "Get UI tree for a process. Use ONLY at task start or for special modes (OCR, DOM, Omniparser, Gemini vision). Do NOT call after action tools - use their ui_diff_before_after/include_tree_after_action params instead. Options: include_browser_dom for DOM, include_ocr for text, include_omniparser for icons, include_gemini_vision for AI detection. tree_max_depth limits depth, tree_from_selector focuses on subtree. Read-only."
+        description = "Get UI tree for a process. Use ONLY at task start or for special modes (OCR, DOM, Omniparser, Gemini vision). Do NOT call after action tools - use their ui_diff_before_after/include_tree_after_action params instead. Options: include_browser_dom for DOM, include_ocr for text, include_omniparser for icons, include_gemini_vision for AI detection. tree_max_depth limits depth, tree_from_selector focuses on subtree. For large windows set tree_max_tokens/tree_max_elements and expand collapsed groups later with tree_expand. Read-only."
     )]
     pub async fn get_window_tree(
         &self,
@@ -1364,12 +1399,15 @@ impl DesktopWrapper {
 
         // Find PID for the process name using shared function
         let pid = terminator::find_pid_for_process(&self.desktop, &args.process).map_err(|e| {
-            McpError::resource_not_found(
-                format!(
-                    "Process '{}' not found. Use open_application to start it first.",
-                    args.process
+            with_error_kind(
+                McpError::resource_not_found(
+                    format!(
+                        "Process '{}' not found. Use open_application to start it first.",
+                        args.process
+                    ),
+                    Some(json!({"process": args.process, "error": e.to_string()})),
                 ),
-                Some(json!({"process": args.process, "error": e.to_string()})),
+                &e,
             )
         })?;
 
@@ -1491,6 +1529,14 @@ impl DesktopWrapper {
             .as_ref()
             .map(|s| s == "ui_tree")
             .unwrap_or(false);
+        let tree_budget = (args.tree_max_tokens.is_some()
+            || args.tree_max_elements.is_some()
+            || args.tree_expand.is_some())
+        .then(|| terminator::TreeBudget {
+            max_tokens: args.tree_max_tokens,
+            max_elements: args.tree_max_elements,
+            expand: args.tree_expand.clone(),
+        });
         if let Some(bounds_cache) = crate::helpers::maybe_attach_tree(
             &self.desktop,
             args.tree.include_tree_after_action,
@@ -1502,11 +1548,18 @@ impl DesktopWrapper {
             &mut result_json,
             None, // No found element for window tree
             include_all_bounds,
+            tree_budget.as_ref(),
         )
         .await
         {
             if let Ok(mut cache) = self.uia_bounds.lock() {
-                *cache = bounds_cache;
+                // Expanded groups keep their original indices, and their handles are
+                // refused once the tree changes, so they extend the cache
+                if args.tree_expand.is_some() {
+                    cache.extend(bounds_cache);
+                } else {
+                    *cache = bounds_cache;
+                }
             }
         }
 
@@ -1692,12 +1745,20 @@ impl DesktopWrapper {
                 .unwrap_or_default();
 
             // Cluster and format all elements from all sources
-            let clustered_result = crate::tree_formatter::format_clustered_tree_from_caches(
+            let mut cluster_config = crate::tree_formatter::ClusterConfig {
+                radius: args.cluster_radius,
+                ..Default::default()
+            };
+            if let Some(fuse) = args.cluster_fuse_sources {
+                cluster_config.fuse_sources = fuse;
+            }
+            let clustered_result = crate::tree_formatter::format_clustered_tree_with_config(
                 &uia_bounds_snapshot,
                 &dom_bounds_snapshot,
                 &ocr_bounds_snapshot,
                 &omniparser_snapshot,
                 &vision_snapshot,
+                &cluster_config,
             );
 
             result_json["clustered_tree"] = json!(clustered_result.formatted);
@@ -1709,6 +1770,9 @@ impl DesktopWrapper {
             }
 
             info!("Clustered tree generated with {} elements", element_count);
+        } else if let Ok(mut cache) = self.clustered_bounds.lock() {
+            // Fused aliases from an older clustered tree would point at stale indices
+            cache.clear();
         }
 
         // Handle show_overlay request
@@ -2077,9 +2141,12 @@ impl DesktopWrapper {
         let mut span = StepSpan::new("get_applications_and_windows_list", None);
 
         let apps = self.desktop.applications().map_err(|e| {
-            McpError::resource_not_found(
-                "Failed to get applications",
-                Some(json!({"reason": e.to_string()})),
+            with_error_kind(
+                McpError::resource_not_found(
+                    "Failed to get applications",
+                    Some(json!({"reason": e.to_string()})),
+                ),
+                &e,
             )
         })?;
 
@@ -2209,13 +2276,20 @@ impl DesktopWrapper {
         let try_click_before = args.try_click_before;
         let restore_focus = args.restore_focus;
         let highlight_before = args.highlight.highlight_before_action;
+        let actionability = crate::utils::actionability_options(
+            ActionabilityOptions::for_typing(),
+            args.timeout_ms,
+            args.force,
+        );
 
         // CRITICAL: Save focus state HERE at MCP level BEFORE any window activation
         // Both prepare_window_management() and activate_window() steal focus,
         // so we must save BEFORE either of them runs
         //
         // FocusState is Send + Sync (COM objects in MTA mode support cross-thread access)
+        #[cfg(target_os = "windows")]
         use terminator::platforms::windows::{restore_focus_state, save_focus_state};
+        #[cfg(target_os = "windows")]
         let saved_focus_state = if restore_focus {
             tracing::info!(
                 "[type_into_element] Saving focus state BEFORE window management (MCP level)"
@@ -2224,6 +2298,11 @@ impl DesktopWrapper {
         } else {
             None
         };
+        #[cfg(not(target_os = "windows"))]
+        let saved_focus_state: Option<()> = {
+            let _ = restore_focus;
+            None
+        };
 
         if should_restore {
             tracing::info!(
@@ -2246,7 +2325,11 @@ impl DesktopWrapper {
             move |element: UIElement| {
                 let text_to_type = text_to_type.clone();
                 let trailing_keys = trailing_keys_for_closure.clone();
+                let actionability = actionability.clone();
                 async move {
+                    // Wait until the element is visible, enabled and editable
+                    element.ensure_actionable_async(&actionability).await?;
+
                     // Activate window to ensure it has keyboard focus before typing
                     if let Err(e) = element.activate_window() {
                         tracing::warn!("Failed to activate window before typing: {}", e);
@@ -2261,7 +2344,9 @@ impl DesktopWrapper {
                     // NOTE: restore_focus=false - MCP handles restoration after find_and_execute
                     // NOTE: Overlay is handled by element.type_text_with_state_and_focus_restore
                     if should_clear {
-                        if let Err(clear_error) = element.set_value("") {
+                        if let Err(clear_error) =
+                            element.set_value_with_options("", &ActionabilityOptions::none())
+                        {
                             warn!(
                                 "Warning: Failed to clear element before typing: {}",
                                 clear_error
@@ -2270,12 +2355,13 @@ impl DesktopWrapper {
                     }
 
                     // Type the text (without trailing keys)
-                    let type_result = element.type_text_with_state_and_focus_restore(
+                    let type_result = element.type_text_with_options(
                         &text_to_type,
                         true,
                         try_focus_before,
                         try_click_before,
                         false, // Don't restore at core level - MCP handles it
+                        &ActionabilityOptions::none(), // Checked above
                     );
 
                     // Press any trailing keys after typing (e.g., {Enter})
@@ -2360,10 +2446,13 @@ impl DesktopWrapper {
 
         // CRITICAL: Restore focus state AFTER typing is complete
         // This must happen after find_and_execute but before returning results
+        #[cfg(target_os = "windows")]
         if let Some(state) = saved_focus_state {
             tracing::info!("[type_into_element] Restoring focus state (MCP level)");
             restore_focus_state(state);
         }
+        #[cfg(not(target_os = "windows"))]
+        let _ = saved_focus_state;
 
         let mut result_json = json!({
             "action": "type_into_element",
@@ -2439,6 +2528,7 @@ impl DesktopWrapper {
 
             result_json["ui_diff"] = json!(diff_result.diff);
             result_json["has_ui_changes"] = json!(diff_result.has_changes);
+            result_json["ui_changes"] = json!(diff_result.changes);
         }
 
         // Restore windows after typing into element
@@ -2521,6 +2611,7 @@ Click types: 'left' (default), 'double', 'right'. Use ui_diff_before_after:true
                 tracing::info!("[click_element] Coordinate mode: ({}, {})", x, y);
 
                 // Highlight before click if enabled (coordinate mode - small box around click point)
+                #[cfg(target_os = "windows")]
                 if args.highlight.highlight_before_action {
                     const HIGHLIGHT_SIZE: i32 = 24;
                     let highlight_x = (x as i32) - HIGHLIGHT_SIZE / 2;
@@ -2586,17 +2677,43 @@ Click types: 'left' (default), 'double', 'right'. Use ui_diff_before_after:true
                     Err(e) => {
                         span.set_status(false, Some(&e.to_string()));
                         span.end();
-                        return Err(McpError::internal_error(
-                            format!("Failed to click at ({}, {}): {}", x, y, e),
-                            Some(json!({ "x": x, "y": y })),
+                        return Err(with_error_kind(
+                            McpError::internal_error(
+                                format!("Failed to click at ({}, {}): {}", x, y, e),
+                                Some(json!({ "x": x, "y": y })),
+                            ),
+                            &e,
                         ));
                     }
                 }
             }
 
             ClickMode::Index => {
-                let index = args.index.unwrap();
-                let vision_type = args.get_vision_type();
+                let mut index = args.index.unwrap();
+                let mut vision_type = args.get_vision_type();
+
+                // A handle fused into another source in clustered output resolves to that
+                // element's most reliable source
+                let requested_handle =
+                    format!("{}{}", vision_type.element_source().prefix(), index);
+                let fused_target = self
+                    .clustered_bounds
+                    .lock()
+                    .ok()
+                    .and_then(|cache| cache.get(&requested_handle).copied())
+                    .filter(|(source, idx, _)| {
+                        *source != vision_type.element_source() || *idx != index
+                    });
+                if let Some((source, idx, _)) = fused_target {
+                    tracing::info!(
+                        "[click_element] #{} is fused into #{}{}, using that source",
+                        requested_handle,
+                        source.prefix(),
+                        idx
+                    );
+                    vision_type = crate::utils::VisionType::from_element_source(source);
+                    index = idx;
+                }
                 span.set_attribute("index", index.to_string());
                 span.set_attribute("vision_type", format!("{:?}", vision_type));
                 tracing::info!("[click_element] Index mode: {}, {:?}", index, vision_type);
@@ -2732,6 +2849,7 @@ Click types: 'left' (default), 'double', 'right'. Use ui_diff_before_after:true
                 span.set_attribute("label", item_label.clone());
 
                 // Highlight before click if enabled (index mode)
+                #[cfg(target_os = "windows")]
                 if args.highlight.highlight_before_action {
                     tracing::info!(
                         "HIGHLIGHT_BEFORE_CLICK (index mode) bounds=({}, {}, {}, {}) label={}",
@@ -2782,6 +2900,9 @@ Click types: 'left' (default), 'double', 'right'. Use ui_diff_before_after:true
                         if let Some(ref sel) = uia_selector {
                             result_json["selector"] = json!(sel);
                         }
+                        if fused_target.is_some() {
+                            result_json["fused_from"] = json!(requested_handle);
+                        }
                         span.set_status(true, None);
                         span.end();
                         append_window_screenshot_to_json(
@@ -2803,9 +2924,12 @@ Click types: 'left' (default), 'double', 'right'. Use ui_diff_before_after:true
                     Err(e) => {
                         span.set_status(false, Some(&e.to_string()));
                         span.end();
-                        return Err(McpError::internal_error(
-                            format!("Failed to click index {}: {e}", index),
-                            Some(json!({ "index": index, "label": item_label })),
+                        return Err(with_error_kind(
+                            McpError::internal_error(
+                                format!("Failed to click index {}: {e}", index),
+                                Some(json!({ "index": index, "label": item_label })),
+                            ),
+                            &e,
                         ));
                     }
                 }
@@ -2844,25 +2968,40 @@ Click types: 'left' (default), 'double', 'right'. Use ui_diff_before_after:true
                 let highlight_before = args.highlight.highlight_before_action;
                 let click_type = args.click_type;
                 let restore_cursor = args.restore_cursor;
+                let actionability = crate::utils::actionability_options(
+                    ActionabilityOptions::for_click().with_position(
+                        click_position.x_percentage.min(100) as u8,
+                        click_position.y_percentage.min(100) as u8,
+                    ),
+                    args.action.timeout_ms,
+                    args.force,
+                );
                 let action = {
                     let click_position = click_position.clone();
                     move |element: UIElement| {
                         let click_position = click_position.clone();
+                        let actionability = actionability.clone();
                         async move {
+                            // Fail with ElementObscured/NotVisible/... instead of clicking
+                            // whatever is on top (e.g. a modal dialog)
+                            element.ensure_actionable_async(&actionability).await?;
                             if highlight_before {
                                 let _ = element.highlight_before_action("click");
                             }
                             // Show action overlay
-                            let element_desc = element.name().unwrap_or_default();
-                            let element_role = element.role();
-                            let overlay_info = if element_desc.is_empty() {
-                                element_role.clone()
-                            } else if element_desc.len() > 50 {
-                                format!("'{}...' {}", &element_desc[..47], element_role)
-                            } else {
-                                format!("'{}' {}", element_desc, element_role)
-                            };
-                            terminator::show_action_overlay("Clicking", Some(overlay_info));
+                            #[cfg(target_os = "windows")]
+                            {
+                                let element_desc = element.name().unwrap_or_default();
+                                let element_role = element.role();
+                                let overlay_info = if element_desc.is_empty() {
+                                    element_role.clone()
+                                } else if element_desc.len() > 50 {
+                                    format!("'{}...' {}", &element_desc[..47], element_role)
+                                } else {
+                                    format!("'{}' {}", element_desc, element_role)
+                                };
+                                terminator::show_action_overlay("Clicking", Some(overlay_info));
+                            }
 
                             let result = match element.bounds() {
                                 Ok(bounds) => {
@@ -2889,12 +3028,19 @@ Click types: 'left' (default), 'double', 'right'. Use ui_diff_before_after:true
                                             terminator::ClickType::Right
                                         }
                                     };
+                                    #[cfg(target_os = "windows")]
                                     terminator::platforms::windows::send_mouse_click(
                                         x,
                                         y,
                                         terminator_click_type,
                                         restore_cursor,
                                     )?;
+                                    #[cfg(not(target_os = "windows"))]
+                                    element.click_at_position(
+                                        click_position.x_percentage.min(100) as u8,
+                                        click_position.y_percentage.min(100) as u8,
+                                        terminator_click_type,
+                                    )?;
 
                                     use terminator::ClickResult;
                                     Ok(ClickResult {
@@ -2912,9 +3058,11 @@ Click types: 'left' (default), 'double', 'right'. Use ui_diff_before_after:true
                                         "[click_element] Failed to get bounds: {}. Falling back.",
                                         e
                                     );
-                                    element.click()
+                                    // Checks already ran above
+                                    element.click_with_options(&ActionabilityOptions::none())
                                 }
                             };
+                            #[cfg(target_os = "windows")]
                             terminator::hide_action_overlay();
                             result
                         }
@@ -3037,6 +3185,7 @@ Click types: 'left' (default), 'double', 'right'. Use ui_diff_before_after:true
                     span.set_attribute("ui_diff.has_changes", diff_result.has_changes.to_string());
                     result_json["ui_diff"] = json!(diff_result.diff);
                     result_json["has_ui_changes"] = json!(diff_result.has_changes);
+                    result_json["ui_changes"] = json!(diff_result.changes);
                 }
 
                 self.restore_window_management(should_restore).await;
@@ -3062,7 +3211,7 @@ Click types: 'left' (default), 'double', 'right'. Use ui_diff_before_after:true
         }
     }
     #[tool(
-        description = "Sends a key press to a UI element. Keys are auto-normalized to curly brace format (e.g., 'Enter' becomes '{Enter}'). Examples: 'Enter', 'Tab', 'Ctrl+A', '{Ctrl}c', '{Alt}{F4}'. Use ui_diff_before_after:true to see changes (no need to call get_window_tree after)."
+        description = "Sends a key press to a UI element. Keys are auto-normalized to curly brace format (e.g., 'Enter' becomes '{Enter}'). Examples: 'Enter', 'Tab', 'Ctrl+A', '{Ctrl}c', '{Alt}{F4}', '{Tab 3}' (repeat). Unknown key names are rejected with suggestions. Use ui_diff_before_after:true to see changes (no need to call get_window_tree after)."
     )]
     async fn press_key(
         &self,
@@ -3116,8 +3265,8 @@ Click types: 'left' (default), 'double', 'right'. Use ui_diff_before_after:true
             );
         }
 
-        // Normalize key to ensure curly brace format (e.g., "Enter" -> "{Enter}")
-        let key_to_press = normalize_key(&args.key);
+        // Normalize key to the shared key syntax (e.g., "Enter" -> "{Enter}")
+        let key_to_press = normalize_key(&args.key).map_err(invalid_key_error)?;
         tracing::debug!(
             "[press_key] normalized key: {} -> {}",
             args.key,
@@ -3298,6 +3447,7 @@ Click types: 'left' (default), 'double', 'right'. Use ui_diff_before_after:true
 
             result_json["ui_diff"] = json!(diff_result.diff);
             result_json["has_ui_changes"] = json!(diff_result.has_changes);
+            result_json["ui_changes"] = json!(diff_result.changes);
         }
 
         // Restore windows after pressing key
@@ -3348,13 +3498,16 @@ Click types: 'left' (default), 'double', 'right'. Use ui_diff_before_after:true
             .first(None)
             .await
             .map_err(|e| {
-                McpError::resource_not_found(
-                    "Failed to find window for process",
-                    Some(json!({
-                        "reason": e.to_string(),
-                        "process": args.process,
-                        "selector": window_selector
-                    })),
+                with_error_kind(
+                    McpError::resource_not_found(
+                        "Failed to find window for process",
+                        Some(json!({
+                            "reason": e.to_string(),
+                            "process": args.process,
+                            "selector": window_selector
+                        })),
+                    ),
+                    &e,
                 )
             })?;
 
@@ -3363,21 +3516,27 @@ Click types: 'left' (default), 'double', 'right'. Use ui_diff_before_after:true
 
         // Activate the window to bring it to foreground
         window.activate_window().map_err(|e| {
-            McpError::internal_error(
-                "Failed to activate window",
-                Some(json!({
-                    "reason": e.to_string(),
-                    "process": args.process
-                })),
+            with_error_kind(
+                McpError::internal_error(
+                    "Failed to activate window",
+                    Some(json!({
+                        "reason": e.to_string(),
+                        "process": args.process
+                    })),
+                ),
+                &e,
             )
         })?;
         span.set_attribute("window.activated", "true".to_string());
 
         // Get the focused element after activation
         let element = self.desktop.focused_element().map_err(|e| {
-            McpError::internal_error(
-                "Failed to get focused element after window activation",
-                Some(json!({"reason": e.to_string()})),
+            with_error_kind(
+                McpError::internal_error(
+                    "Failed to get focused element after window activation",
+                    Some(json!({"reason": e.to_string()})),
+                ),
+                &e,
             )
         })?;
 
@@ -3394,8 +3553,8 @@ Click types: 'left' (default), 'double', 'right'. Use ui_diff_before_after:true
         let element_info = build_element_info(&element);
         let window_info = build_element_info(&window);
 
-        // Normalize key to ensure curly brace format (e.g., "Enter" -> "{Enter}")
-        let normalized_key = normalize_key(&args.key);
+        // Normalize key to the shared key syntax (e.g., "Enter" -> "{Enter}")
+        let normalized_key = normalize_key(&args.key).map_err(invalid_key_error)?;
         tracing::debug!(
             "[press_key_global] normalized key: {} -> {}",
             args.key,
@@ -3404,13 +3563,16 @@ Click types: 'left' (default), 'double', 'right'. Use ui_diff_before_after:true
 
         // Perform the key press on the focused element
         element.press_key(&normalized_key).map_err(|e| {
-            McpError::resource_not_found(
-                "Failed to press key on focused element",
-                Some(json!({
-                    "reason": e.to_string(),
-                    "key_pressed": normalized_key,
-                    "element_info": element_info
-                })),
+            with_error_kind(
+                McpError::resource_not_found(
+                    "Failed to press key on focused element",
+                    Some(json!({
+                        "reason": e.to_string(),
+                        "key_pressed": normalized_key,
+                        "element_info": element_info
+                    })),
+                ),
+                &e,
             )
         })?;
 
@@ -5087,6 +5249,7 @@ DATA PASSING:
             &mut result_json,
             Some(&element),
             false,
+            None,
         )
         .await;
 
@@ -5517,6 +5680,7 @@ DATA PASSING:
                     &mut result_json,
                     Some(&element),
                     false,
+                    None,
                 )
                 .await;
 
@@ -5779,7 +5943,7 @@ DATA PASSING:
     }
 
     #[tool(
-        description = "Waits for an element to meet a specific condition (visible, enabled, focused, exists)."
+        description = "Waits for an element to meet a specific condition (visible, enabled, focused, exists). The SDK's other wait conditions (stable, value/text, hidden, detached) are not exposed here. Waits scoped to a process wake on UI Automation events and re-check every 500ms for changes that raise none, so such a change can take up to half a second to be seen; unscoped waits poll every 100ms."
     )]
     async fn wait_for_element(
         &self,
@@ -5860,6 +6024,7 @@ DATA PASSING:
                         &mut result_json,
                         Some(&element),
                         false,
+                        None,
                     )
                     .await;
 
@@ -5885,7 +6050,12 @@ DATA PASSING:
                     return Ok(CallToolResult::success(contents));
                 }
                 Err(e) => {
-                    let error_msg = format!("Element not found within timeout: {e}");
+                    let error_msg = match e {
+                        AutomationError::ElementNotFound(_) | AutomationError::Timeout(_) => {
+                            format!("Element not found within timeout: {e}")
+                        }
+                        _ => format!("Failed waiting for element: {e}"),
+                    };
                     info!(
                         "[wait_for_element] Element NOT found for selector='{}' within timeout. Error: {}",
                         args.selector.selector, e
@@ -5893,176 +6063,143 @@ DATA PASSING:
 
                     self.restore_window_management(should_restore).await;
 
-                    return Err(McpError::internal_error(
-                        error_msg,
-                        Some(json!({
-                            "selector": args.selector.selector,
-                            "condition": args.condition,
-                            "timeout_ms": timeout.unwrap_or(std::time::Duration::from_millis(5000)).as_millis(),
-                            "error": e.to_string()
-                        })),
+                    return Err(with_error_kind(
+                        McpError::internal_error(
+                            error_msg,
+                            Some(json!({
+                                "selector": args.selector.selector,
+                                "condition": args.condition,
+                                "timeout_ms": timeout.unwrap_or(std::time::Duration::from_millis(5000)).as_millis(),
+                                "error": e.to_string()
+                            })),
+                        ),
+                        &e,
                     ));
                 }
             }
         }
 
-        // For other conditions (visible, enabled, focused), we need to poll
+        // For other conditions (visible, enabled, focused), wait on the locator; it wakes
+        // on UI tree events where the platform provides them
+        let condition = match condition_lower.as_str() {
+            "visible" => WaitCondition::Visible,
+            "enabled" => WaitCondition::Enabled,
+            "focused" => WaitCondition::Focused,
+            _ => {
+                info!(
+                    "[wait_for_element] Invalid condition provided: '{}'",
+                    args.condition
+                );
+
+                self.restore_window_management(should_restore).await;
+
+                return Err(McpError::invalid_params(
+                    "Invalid condition. Valid: exists, visible, enabled, focused",
+                    Some(json!({"provided_condition": args.condition})),
+                ));
+            }
+        };
+
         let start_time = std::time::Instant::now();
         let timeout_duration = timeout.unwrap_or(std::time::Duration::from_millis(5000));
         info!(
-            "[wait_for_element] Polling for condition '{}' on selector='{}' with timeout {:?}",
+            "[wait_for_element] Waiting for condition '{}' on selector='{}' with timeout {:?}",
             args.condition, args.selector.selector, timeout_duration
         );
 
-        loop {
-            // Check if we've exceeded the timeout
-            if start_time.elapsed() > timeout_duration {
-                let timeout_msg = format!(
-                    "Timeout waiting for element to be {} within {}ms",
-                    args.condition,
-                    timeout_duration.as_millis()
-                );
+        match locator.wait_for(condition, Some(timeout_duration)).await {
+            Ok(element) => {
+                // Only hidden/detached waits come back without an element
+                let element = element.as_ref();
                 info!(
-                    "[wait_for_element] Timeout exceeded for selector='{}', condition='{}', waited {}ms",
-                    args.selector.selector, args.condition, start_time.elapsed().as_millis()
+                    "[wait_for_element] Condition '{}' met for selector='{}' after {}ms",
+                    args.condition,
+                    args.selector.selector,
+                    start_time.elapsed().as_millis()
                 );
+                // Condition is met, return success
+                let mut result_json = json!({
+                    "action": "wait_for_element",
+                    "status": "executed_without_error",
+                    "condition": args.condition,
+                    "condition_met": true,
+                    "selector": args.selector.selector,
+                    "timeout_ms": timeout_duration.as_millis(),
+                    "elapsed_ms": start_time.elapsed().as_millis(),
+                    "timestamp": chrono::Utc::now().to_rfc3339()
+                });
 
-                self.restore_window_management(should_restore).await;
+                maybe_attach_tree(
+                    &self.desktop,
+                    args.tree.include_tree_after_action,
+                    args.tree.tree_max_depth,
+                    args.tree.tree_from_selector.as_deref(),
+                    None, // include_detailed_attributes - use default
+                    None, // tree_output_format - use default
+                    element.and_then(|el| el.process_id().ok()),
+                    &mut result_json,
+                    element,
+                    false,
+                    None,
+                )
+                .await;
 
-                return Err(McpError::internal_error(
-                    timeout_msg,
-                    Some(json!({
-                        "selector": args.selector.selector,
-                        "condition": args.condition,
-                        "timeout_ms": timeout_duration.as_millis(),
-                        "elapsed_ms": start_time.elapsed().as_millis()
-                    })),
-                ));
-            }
+                self.restore_window_management(should_restore).await;
 
-            // Try to find the element with a short timeout
-            match locator
-                .wait(Some(std::time::Duration::from_millis(100)))
-                .await
-            {
-                Ok(element) => {
-                    info!(
-                        "[wait_for_element] Element found for selector='{}', checking condition '{}'",
-                        args.selector.selector, args.condition
-                    );
-                    // Element exists, now check the specific condition
-                    let condition_met = match condition_lower.as_str() {
-                        "visible" => {
-                            let v = element.is_visible().unwrap_or(false);
-                            info!(
-                                "[wait_for_element] is_visible() for selector='{}': {}",
-                                args.selector.selector, v
-                            );
-                            v
-                        }
-                        "enabled" => {
-                            let v = element.is_enabled().unwrap_or(false);
-                            info!(
-                                "[wait_for_element] is_enabled() for selector='{}': {}",
-                                args.selector.selector, v
-                            );
-                            v
-                        }
-                        "focused" => {
-                            let v = element.is_focused().unwrap_or(false);
-                            info!(
-                                "[wait_for_element] is_focused() for selector='{}': {}",
-                                args.selector.selector, v
-                            );
-                            v
-                        }
-                        _ => {
-                            info!(
-                                "[wait_for_element] Invalid condition provided: '{}'",
-                                args.condition
-                            );
+                span.set_status(true, None);
+                span.end();
 
-                            self.restore_window_management(should_restore).await;
+                append_window_screenshot_to_json(
+                    &self.desktop,
+                    &args.selector.process,
+                    &mut result_json,
+                    args.window_screenshot.include_window_screenshot,
+                )
+                .await;
+                let contents = vec![Content::json(result_json)?];
+                let contents = append_monitor_screenshots_if_enabled(
+                    &self.desktop,
+                    contents,
+                    args.monitor.include_monitor_screenshots,
+                )
+                .await;
+                Ok(CallToolResult::success(contents))
+            }
+            Err(e) => {
+                let error = e.to_string();
+                let message = if matches!(e, AutomationError::Timeout(_)) {
+                    format!(
+                        "Timeout waiting for element to be {} within {}ms",
+                        args.condition,
+                        timeout_duration.as_millis()
+                    )
+                } else {
+                    format!(
+                        "Failed waiting for element to be {}: {error}",
+                        args.condition
+                    )
+                };
+                info!(
+                    "[wait_for_element] Wait failed for selector='{}', condition='{}', waited {}ms: {}",
+                    args.selector.selector, args.condition, start_time.elapsed().as_millis(), error
+                );
 
-                            return Err(McpError::invalid_params(
-                                "Invalid condition. Valid: exists, visible, enabled, focused",
-                                Some(json!({"provided_condition": args.condition})),
-                            ));
-                        }
-                    };
+                self.restore_window_management(should_restore).await;
 
-                    if condition_met {
-                        info!(
-                            "[wait_for_element] Condition '{}' met for selector='{}' after {}ms",
-                            args.condition,
-                            args.selector.selector,
-                            start_time.elapsed().as_millis()
-                        );
-                        // Condition is met, return success
-                        let mut result_json = json!({
-                            "action": "wait_for_element",
-                            "status": "executed_without_error",
-                            "condition": args.condition,
-                            "condition_met": true,
+                Err(with_error_kind(
+                    McpError::internal_error(
+                        message,
+                        Some(json!({
                             "selector": args.selector.selector,
+                            "condition": args.condition,
                             "timeout_ms": timeout_duration.as_millis(),
                             "elapsed_ms": start_time.elapsed().as_millis(),
-                            "timestamp": chrono::Utc::now().to_rfc3339()
-                        });
-
-                        maybe_attach_tree(
-                            &self.desktop,
-                            args.tree.include_tree_after_action,
-                            args.tree.tree_max_depth,
-                            args.tree.tree_from_selector.as_deref(),
-                            None, // include_detailed_attributes - use default
-                            None, // tree_output_format - use default
-                            element.process_id().ok(),
-                            &mut result_json,
-                            Some(&element),
-                            false,
-                        )
-                        .await;
-
-                        self.restore_window_management(should_restore).await;
-
-                        span.set_status(true, None);
-                        span.end();
-
-                        append_window_screenshot_to_json(
-                            &self.desktop,
-                            &args.selector.process,
-                            &mut result_json,
-                            args.window_screenshot.include_window_screenshot,
-                        )
-                        .await;
-                        let contents = vec![Content::json(result_json)?];
-                        let contents = append_monitor_screenshots_if_enabled(
-                            &self.desktop,
-                            contents,
-                            args.monitor.include_monitor_screenshots,
-                        )
-                        .await;
-                        return Ok(CallToolResult::success(contents));
-                    } else {
-                        info!(
-                            "[wait_for_element] Condition '{}' NOT met for selector='{}', continuing to poll...",
-                            args.condition, args.selector.selector
-                        );
-                    }
-                    // Condition not met yet, continue polling
-                }
-                Err(_) => {
-                    info!(
-                        "[wait_for_element] Element not found for selector='{}', will retry...",
-                        args.selector.selector
-                    );
-                    // Element doesn't exist yet, continue polling
-                }
+                            "error": error
+                        })),
+                    ),
+                    &e,
+                ))
             }
-
-            // Wait a bit before the next poll
-            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
         }
     }
 
@@ -6100,9 +6237,14 @@ DATA PASSING:
 
         let browser = Some(Browser::Custom(args.process.clone()));
         let ui_element = self.desktop.open_url(&args.url, browser).map_err(|e| {
-            McpError::internal_error(
-                "Failed to open URL",
-                Some(json!({"reason": e.to_string(), "url": args.url, "process": args.process})),
+            with_error_kind(
+                McpError::internal_error(
+                    "Failed to open URL",
+                    Some(
+                        json!({"reason": e.to_string(), "url": args.url, "process": args.process}),
+                    ),
+                ),
+                &e,
             )
         })?;
 
@@ -6190,6 +6332,7 @@ DATA PASSING:
             &mut result_json,
             Some(&ui_element),
             false,
+            None,
         )
         .await;
 
@@ -6234,9 +6377,12 @@ DATA PASSING:
 
         // Open the application
         let ui_element = self.desktop.open_application(&args.app_name).map_err(|e| {
-            McpError::internal_error(
-                "Failed to open application",
-                Some(json!({"reason": e.to_string(), "app_name": args.app_name})),
+            with_error_kind(
+                McpError::internal_error(
+                    "Failed to open application",
+                    Some(json!({"reason": e.to_string(), "app_name": args.app_name})),
+                ),
+                &e,
             )
         })?;
 
@@ -6296,6 +6442,7 @@ DATA PASSING:
             &mut result_json,
             Some(&ui_element),
             false,
+            None,
         )
         .await;
 
@@ -6586,6 +6733,7 @@ DATA PASSING:
 
             result_json["ui_diff"] = json!(diff_result.diff);
             result_json["has_ui_changes"] = json!(diff_result.has_changes);
+            result_json["ui_changes"] = json!(diff_result.changes);
         }
 
         self.restore_window_management(should_restore).await;
@@ -6728,6 +6876,7 @@ DATA PASSING:
 
             result_json["ui_diff"] = json!(diff_result.diff);
             result_json["has_ui_changes"] = json!(diff_result.has_changes);
+            result_json["ui_changes"] = json!(diff_result.changes);
         }
 
         self.restore_window_management(should_restore).await;
@@ -6975,6 +7124,7 @@ DATA PASSING:
 
             result_json["ui_diff"] = json!(diff_result.diff);
             result_json["has_ui_changes"] = json!(diff_result.has_changes);
+            result_json["ui_changes"] = json!(diff_result.changes);
         }
 
         self.restore_window_management(should_restore).await;
@@ -7073,17 +7223,23 @@ DATA PASSING:
 
             // Get the monitor containing this window
             let monitor = element.monitor().map_err(|e| {
-                McpError::internal_error(
-                    "Failed to get monitor for window",
-                    Some(json!({ "reason": e.to_string() })),
+                with_error_kind(
+                    McpError::internal_error(
+                        "Failed to get monitor for window",
+                        Some(json!({ "reason": e.to_string() })),
+                    ),
+                    &e,
                 )
             })?;
 
             // Capture the monitor
             let screenshot = monitor.capture(&self.desktop).await.map_err(|e| {
-                McpError::internal_error(
-                    "Failed to capture monitor screenshot",
-                    Some(json!({ "reason": e.to_string() })),
+                with_error_kind(
+                    McpError::internal_error(
+                        "Failed to capture monitor screenshot",
+                        Some(json!({ "reason": e.to_string() })),
+                    ),
+                    &e,
                 )
             })?;
 
@@ -7425,6 +7581,7 @@ DATA PASSING:
 
             result_json["ui_diff"] = json!(diff_result.diff);
             result_json["has_ui_changes"] = json!(diff_result.has_changes);
+            result_json["ui_changes"] = json!(diff_result.changes);
         }
 
         // Restore windows after invoking element
@@ -7517,6 +7674,7 @@ DATA PASSING:
 **KEY PARAMETERS:**
 - `url`: File path to workflow (file:// URL). Preferred over inline steps.
 - `start_from_step`: Step ID to start from. Loads saved state from previous runs.
+- `from_run_id`: execution_id of the past run whose saved state start_from_step resumes from (default: the latest run).
 - `end_at_step`: Step ID to stop at (inclusive). Same as start_from_step for single step.
 - `inputs`: Variables to pass to workflow (e.g., {\"username\": \"test\"}).
 - `workflow_id`: Optional identifier for state persistence when using inline steps.
@@ -7526,6 +7684,8 @@ DATA PASSING:
 - `follow_fallback`: Follow fallback_id beyond end_at_step on failures (default: false for bounded execution).
 - `skip_preflight_check`: Skip browser extension connectivity check.
 
+**LOOPS:** On a step, `for_each` (array expression, item bound as `item` or `as`) or `while` (condition, guarded by `max_iterations`) repeats it; results collect in {id}_results. A group with `parallel: true` runs its non-UI steps (run_command, file tools) concurrently.
+
 **DATA PASSING:** Use run_command with engine mode. Return {set_env: {key: value}} to pass data between steps. Access via {{key}} substitution or direct variable names in conditions.
 
 **LOCATOR TIMEOUTS:** .first(0) = immediate, .first(5000) = retry 5s. Default is 0ms (no polling).
@@ -7586,18 +7746,26 @@ DATA PASSING:
 
         let value_to_set = args.value.clone();
         let highlight_before = args.highlight.highlight_before_action;
+        let actionability = crate::utils::actionability_options(
+            ActionabilityOptions::for_set_value(),
+            args.action.timeout_ms,
+            args.force,
+        );
         let action = move |element: UIElement| {
             let value_to_set = value_to_set.clone();
+            let actionability = actionability.clone();
             async move {
                 // Apply highlighting before action if enabled
                 if highlight_before {
                     let _ = element.highlight_before_action("set_value");
                 }
+                // Wait off the runtime until the element is visible, enabled and editable
+                element.ensure_actionable_async(&actionability).await?;
                 // Activate window to ensure it has keyboard focus before setting value
                 if let Err(e) = element.activate_window() {
                     tracing::warn!("Failed to activate window before setting value: {}", e);
                 }
-                element.set_value(&value_to_set)
+                element.set_value_with_options(&value_to_set, &ActionabilityOptions::none())
             }
         };
 
@@ -7784,6 +7952,7 @@ DATA PASSING:
 
             result_json["ui_diff"] = json!(diff_result.diff);
             result_json["has_ui_changes"] = json!(diff_result.has_changes);
+            result_json["ui_changes"] = json!(diff_result.changes);
         }
 
         self.restore_window_management(should_restore).await;
@@ -8631,11 +8800,16 @@ console.info = function(...args) {
             .desktop
             .gemini_computer_use(&args.process, &args.goal, args.max_steps, None)
             .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            .map_err(|e| {
+                let error = McpError::internal_error(e.to_string(), None);
+                match e.downcast_ref::<terminator::AutomationError>() {
+                    Some(source) => with_error_kind(error, source),
+                    None => error,
+                }
+            })?;
 
         // Restore windows
-        let _ = self.window_manager.restore_all_windows().await;
-        self.window_manager.clear_captured_state().await;
+        self.restore_window_management(true).await;
 
         span.set_status(result.status == "executed_without_error", None);
         span.end();
@@ -9791,6 +9965,13 @@ impl DesktopWrapper {
             ));
         }
 
+        if let Some(replay) = &self.replay {
+            let step_id = execution_context
+                .as_ref()
+                .and_then(|c| c.step_id.as_deref());
+            return replay.respond(tool_name, step_id);
+        }
+
         // Window management for UI interaction tools
         // Check if tool has a 'process' argument - if so, it needs window management
         // No whitelist - any tool with a process argument gets window management
@@ -9830,8 +10011,6 @@ impl DesktopWrapper {
             );
             None
         };
-        #[cfg(not(target_os = "windows"))]
-        let saved_focus: Option<()> = None;
 
         // Perform window management if needed
         if let Some(ref process) = process_name {
@@ -9877,7 +10056,12 @@ impl DesktopWrapper {
         );
 
         // Start capturing tracing logs for this tool execution
-        if let Some(ref log_capture) = self.log_capture {
+        let log_capture = self.log_capture.as_ref().filter(|_| {
+            execution_context
+                .as_ref()
+                .is_none_or(|ctx| ctx.capture_logs)
+        });
+        if let Some(log_capture) = log_capture {
             log_capture.start_capture();
         }
 
@@ -10267,7 +10451,7 @@ impl DesktopWrapper {
         let mut all_logs: Vec<execution_logger::CapturedLogEntry> = Vec::new();
 
         // Get tracing logs
-        if let Some(ref log_capture) = self.log_capture {
+        if let Some(log_capture) = log_capture {
             let tracing_logs = log_capture.stop_capture();
             for log in tracing_logs {
                 all_logs.push(execution_logger::CapturedLogEntry {
@@ -10335,7 +10519,7 @@ impl DesktopWrapper {
         }
 
         // Sort logs by timestamp
-        all_logs.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        all_logs.sort_by_key(|a| a.timestamp);
 
         // Extract logs from error data (for TypeScript workflow errors)
         // The logs are embedded in error_data["logs"] when workflow fails
@@ -10414,6 +10598,8 @@ impl DesktopWrapper {
             }
         }
 
+        crate::metrics::record_tool_call(tool_name, start_time.elapsed(), result.as_ref().err());
+
         // Reset in_sequence flag after tool execution
         {
             let mut in_seq = self.in_sequence.lock().unwrap_or_else(|e| e.into_inner());
@@ -10421,6 +10607,7 @@ impl DesktopWrapper {
         }
 
         // Restore windows appropriately based on execution context
+        #[cfg(target_os = "windows")]
         match execution_context {
             None => {
                 // Single tool execution: always restore if window management was performed
@@ -10464,7 +10651,12 @@ impl ServerHandler for DesktopWrapper {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::LATEST,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .enable_resources_subscribe()
+                .enable_prompts()
+                .build(),
             server_info: Implementation::from_build_env(),
             instructions: Some(crate::prompt::get_server_instructions().to_string()),
         }
@@ -10521,12 +10713,38 @@ impl ServerHandler for DesktopWrapper {
             // If no mode is set for this client (e.g., "mediar-app"), allow all tools
         }
 
+        // Under stdio and SSE, interactive calls and scheduled runs share MCP_MAX_CONCURRENT
+        let _slot = match &self.tool_gate {
+            Some(gate) if tool_name != "stop_execution" => match gate.enter() {
+                Ok(slot) => Some(slot),
+                Err(busy) => {
+                    return Err(McpError::invalid_request(
+                        busy.to_string(),
+                        Some(json!({
+                            "busy": true,
+                            "tool": tool_name,
+                            "maxConcurrent": gate.max_concurrent,
+                        })),
+                    ));
+                }
+            },
+            _ => None,
+        };
+
         // Reset cancellation state before starting a new tool call (except for stop_execution itself)
         // This clears any previous stop_execution() so new operations can run
         if tool_name != "stop_execution" {
             self.desktop.reset_cancellation();
         }
 
+        // In replay mode direct tool calls are served from the recording, while
+        // execute_sequence runs for real and replays its steps in dispatch_tool
+        if let Some(replay) = &self.replay {
+            if tool_name != "execute_sequence" {
+                return replay.respond(&tool_name, None);
+            }
+        }
+
         // Log request before execution - extract workflow context from execute_sequence args
         let (wf_id, step_id) = if tool_name == "execute_sequence" {
             (
@@ -10541,12 +10759,18 @@ impl ServerHandler for DesktopWrapper {
             wf_id,
             step_id
         );
-        let log_ctx = execution_logger::log_request(&tool_name, &arguments, wf_id, step_id, None);
+        // Replays must not add to the logs they are replaying
+        let log_ctx = if self.replay.is_some() {
+            None
+        } else {
+            execution_logger::log_request(&tool_name, &arguments, wf_id, step_id, None)
+        };
         let start_time = std::time::Instant::now();
 
         // FOCUS RESTORATION: Extract restore_focus from arguments and save focus state BEFORE tool execution
         // Each tool's window management (bring_to_front, activate_window) steals focus
         // Default: false only for click-like tools (user wants focus on clicked element), true for everything else
+        #[cfg(target_os = "windows")]
         let window_mgmt_opts: crate::utils::WindowManagementOptions =
             serde_json::from_value(arguments.clone()).unwrap_or_default();
 
@@ -10574,8 +10798,6 @@ impl ServerHandler for DesktopWrapper {
             );
             None
         };
-        #[cfg(not(target_os = "windows"))]
-        let saved_focus: Option<()> = None;
 
         // Execute the tool via router
         let tcc = ToolCallContext::new(self, request, context);
@@ -10633,6 +10855,8 @@ impl ServerHandler for DesktopWrapper {
             }
         }
 
+        crate::metrics::record_tool_call(&tool_name, start_time.elapsed(), result.as_ref().err());
+
         // Track tool execution (PostHog analytics)
         match &result {
             Ok(_) => {
@@ -10690,6 +10914,115 @@ impl ServerHandler for DesktopWrapper {
         Ok(rmcp::model::ListToolsResult::with_all_items(all_tools))
     }
 
+    async fn list_resources(
+        &self,
+        _request: Option<rmcp::model::PaginatedRequestParam>,
+        _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+    ) -> Result<rmcp::model::ListResourcesResult, McpError> {
+        let desktop = self.desktop.clone();
+        let mut resources =
+            tokio::task::spawn_blocking(move || crate::resources::list_tree_resources(&desktop))
+                .await
+                .unwrap_or_default();
+        resources.extend(crate::resources::list_file_resources());
+        Ok(rmcp::model::ListResourcesResult::with_all_items(resources))
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<rmcp::model::PaginatedRequestParam>,
+        _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+    ) -> Result<rmcp::model::ListResourceTemplatesResult, McpError> {
+        Ok(rmcp::model::ListResourceTemplatesResult::with_all_items(
+            crate::resources::resource_templates(),
+        ))
+    }
+
+    async fn read_resource(
+        &self,
+        request: rmcp::model::ReadResourceRequestParam,
+        _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+    ) -> Result<rmcp::model::ReadResourceResult, McpError> {
+        crate::resources::read_resource(self.desktop.clone(), &request.uri).await
+    }
+
+    async fn subscribe(
+        &self,
+        request: rmcp::model::SubscribeRequestParam,
+        context: rmcp::service::RequestContext<rmcp::RoleServer>,
+    ) -> Result<(), McpError> {
+        // Only window trees change while the server runs; files are written once
+        let Some(crate::resources::ResourceUri::Tree(pid)) =
+            crate::resources::ResourceUri::parse(&request.uri)
+        else {
+            return Err(McpError::invalid_params(
+                format!(
+                    "Only terminator://tree/{{pid}} resources support subscriptions, got {}",
+                    request.uri
+                ),
+                None,
+            ));
+        };
+        let key = (self.session_id.clone(), request.uri.clone());
+        let subscriptions = self.resource_subscriptions.clone();
+        // Held until the watcher is stored, so a watcher that ends at once finds its entry
+        let mut guard = self.resource_subscriptions.lock().await;
+        let watcher = tokio::spawn({
+            let desktop = self.desktop.clone();
+            let key = key.clone();
+            async move {
+                crate::resources::watch_tree(desktop, context.peer, request.uri, pid).await;
+                let mut subscriptions = subscriptions.lock().await;
+                if subscriptions
+                    .get(&key)
+                    .is_some_and(|w| w.id() == tokio::task::id())
+                {
+                    subscriptions.remove(&key);
+                }
+            }
+        });
+        if let Some(previous) = guard.insert(key, watcher) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    async fn unsubscribe(
+        &self,
+        request: rmcp::model::UnsubscribeRequestParam,
+        _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+    ) -> Result<(), McpError> {
+        if let Some(watcher) = self
+            .resource_subscriptions
+            .lock()
+            .await
+            .remove(&(self.session_id.clone(), request.uri))
+        {
+            watcher.abort();
+        }
+        Ok(())
+    }
+
+    async fn list_prompts(
+        &self,
+        _request: Option<rmcp::model::PaginatedRequestParam>,
+        _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+    ) -> Result<rmcp::model::ListPromptsResult, McpError> {
+        Ok(rmcp::model::ListPromptsResult::with_all_items(
+            crate::prompt::list_prompts(),
+        ))
+    }
+
+    async fn get_prompt(
+        &self,
+        request: rmcp::model::GetPromptRequestParam,
+        _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+    ) -> Result<rmcp::model::GetPromptResult, McpError> {
+        crate::prompt::get_prompt(&request.name).ok_or_else(|| {
+            McpError::invalid_params(format!("Unknown prompt: {}", request.name), None)
+        })
+    }
+
     /// Called after a client completes initialization
     /// We check if this client supports elicitation and store the peer if so
     async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
@@ -2,10 +2,13 @@ use crate::execution_logger;
 use crate::helpers::substitute_variables;
 use crate::output_parser;
 use crate::server::extract_content_json;
+use crate::step_loop::StepLoop;
 use crate::telemetry::{StepSpan, WorkflowSpan};
 use crate::utils::{
-    DesktopWrapper, ExecuteSequenceArgs, SequenceItem, ToolCall, ToolGroup, VariableDefinition,
+    DesktopWrapper, ExecuteSequenceArgs, SequenceItem, SequenceStep, ToolCall, ToolGroup,
+    VariableDefinition,
 };
+use crate::workflow_state::{RunLock, StateSnapshot};
 use crate::workflow_typescript::{TypeScriptWorkflow, WorkflowEvent};
 use rmcp::model::{
     CallToolResult, Content, LoggingLevel, LoggingMessageNotificationParam, NumberOrString,
@@ -14,7 +17,8 @@ use rmcp::model::{
 use rmcp::service::{Peer, RequestContext, RoleServer};
 use rmcp::ErrorData as McpError;
 use serde_json::{json, Value};
-use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::sync::mpsc;
@@ -45,6 +49,18 @@ impl SequenceGuard {
     }
 }
 
+/// Tools that don't touch the UI, and so may run concurrently in a parallel group
+const PARALLEL_SAFE_TOOLS: &[&str] = &[
+    "run_command",
+    "delay",
+    "read_file",
+    "write_file",
+    "edit_file",
+    "copy_content",
+    "glob_files",
+    "grep_files",
+];
+
 /// Extract workflow folder name from URL
 /// e.g., file:///C:/Users/matt/.../workflows/github-demo/src/terminator.ts -> "github-demo"
 fn extract_workflow_folder_from_url(url: &str) -> Option<String> {
@@ -73,6 +89,104 @@ fn extract_workflow_folder_from_url(url: &str) -> Option<String> {
     None
 }
 
+/// Where execution continues after a failed step
+enum FailureRoute {
+    /// Jump to the step at this index
+    Fallback(usize),
+    Next,
+    /// Stop the sequence
+    Stop,
+}
+
+/// A malformed condition, loop or assertion found before a workflow runs
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StepIssue {
+    /// What is malformed: `condition`, `loop` or `assertion`
+    pub kind: &'static str,
+    /// Where it is, e.g. `steps[2].jumps[0].if`
+    pub location: String,
+    /// The condition or assertion as written
+    pub source: Value,
+    pub error: String,
+}
+
+impl std::fmt::Display for StepIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid {} at {}: {}",
+            self.kind, self.location, self.error
+        )
+    }
+}
+
+/// Parse every `if`, jump, `for_each` and `while` expression and check every
+/// `expect` assertion, so a typo fails the workflow up front instead of
+/// silently skipping steps. Shared by `execute_sequence` and `terminator mcp run`.
+pub fn validate_steps(
+    steps: Option<&[SequenceStep]>,
+    troubleshooting: Option<&[SequenceStep]>,
+) -> Vec<StepIssue> {
+    let mut issues = Vec::new();
+    for (section, steps) in [("steps", steps), ("troubleshooting", troubleshooting)] {
+        for (i, step) in steps.into_iter().flatten().enumerate() {
+            let conditions =
+                step.r#if
+                    .iter()
+                    .map(|c| (format!("{section}[{i}].if"), c))
+                    .chain(step.jumps.iter().flatten().enumerate().map(|(j, jump)| {
+                        (format!("{section}[{i}].jumps[{j}].if"), &jump.condition)
+                    }));
+            for (location, condition) in conditions {
+                if let Err(e) = crate::expression_eval::parse(condition) {
+                    issues.push(StepIssue {
+                        kind: "condition",
+                        location,
+                        source: json!(condition),
+                        error: e.to_string(),
+                    });
+                }
+            }
+            let loops = [("for_each", &step.for_each), ("while", &step.r#while)];
+            for (field, expression) in loops {
+                let Some(expression) = expression else {
+                    continue;
+                };
+                if let Err(e) = crate::expression_eval::parse(expression) {
+                    issues.push(StepIssue {
+                        kind: "loop",
+                        location: format!("{section}[{i}].{field}"),
+                        source: json!(expression),
+                        error: e.to_string(),
+                    });
+                }
+            }
+            for (j, assertion) in step.expect.iter().flatten().enumerate() {
+                if let Err(e) = assertion.validate() {
+                    issues.push(StepIssue {
+                        kind: "assertion",
+                        location: format!("{section}[{i}].expect[{j}]"),
+                        source: json!(assertion),
+                        error: e,
+                    });
+                }
+            }
+        }
+    }
+    issues
+}
+
+fn check_steps(args: &ExecuteSequenceArgs) -> Result<(), McpError> {
+    let issues = validate_steps(args.steps.as_deref(), args.troubleshooting.as_deref());
+    match issues.first() {
+        None => Ok(()),
+        Some(first) => Err(McpError::invalid_params(
+            first.to_string(),
+            Some(json!({ "errors": issues })),
+        )),
+    }
+}
+
 /// Helper function to recursively validate a value against a variable definition
 fn validate_variable_value(
     variable_name: &str,
@@ -184,106 +298,216 @@ fn validate_variable_value(
 }
 
 impl DesktopWrapper {
-    // Get the state file path for a workflow
-    // Uses OS-standard data directories:
-    //   Windows: %LOCALAPPDATA%\mediar\workflows\<workflow_id>\state.json
-    //   macOS: ~/Library/Application Support/mediar/workflows/<folder>/state.json
-    //   Linux: ~/.local/share/mediar/workflows/<folder>/state.json
-    // Priority: Extract folder from URL (matches actual workflow folder)
-    async fn get_state_file_path(
-        _workflow_id: Option<&str>, // Kept for backward compat, but unused
-        workflow_url: Option<&str>,
-    ) -> Option<PathBuf> {
-        let data_dir = dirs::data_local_dir()?;
-
-        // Extract folder name from URL (e.g., "github-demo" from file:///...workflows/github-demo/src/...)
-        if let Some(url) = workflow_url {
-            if let Some(folder) = extract_workflow_folder_from_url(url) {
-                debug!("Using folder '{}' for state file", folder);
-                let state_file = data_dir
-                    .join("mediar")
-                    .join("workflows")
-                    .join(&folder)
-                    .join("state.json");
-                return Some(state_file);
-            }
-        }
+    /// Key under which a workflow's state is stored: the explicit `workflow_id`,
+    /// else the workflow folder from the URL (e.g. "github-demo" from
+    /// file:///...workflows/github-demo/src/...)
+    fn state_workflow_key(workflow_id: Option<&str>, workflow_url: Option<&str>) -> Option<String> {
+        workflow_id
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .or_else(|| workflow_url.and_then(extract_workflow_folder_from_url))
+    }
 
-        None
+    /// Claims the run in the state store so a second sequence with the same
+    /// execution id cannot interleave its snapshots
+    async fn lock_workflow_run(
+        &self,
+        workflow_key: Option<&str>,
+        run_id: &str,
+    ) -> Result<Option<RunLock>, McpError> {
+        let (Some(store), Some(workflow_key)) = (self.state_store.clone(), workflow_key) else {
+            return Ok(None);
+        };
+        let (workflow_key, run_id) = (workflow_key.to_string(), run_id.to_string());
+        tokio::task::spawn_blocking(move || {
+            let lock = store.lock_run(&workflow_key, &run_id)?;
+            match store.prune_runs(&workflow_key, crate::workflow_state::RUNS_KEPT_PER_WORKFLOW) {
+                Ok(0) => {}
+                Ok(pruned) => debug!("Pruned {} old runs of '{}'", pruned, workflow_key),
+                Err(e) => warn!("Failed to prune old runs of '{}': {}", workflow_key, e),
+            }
+            Ok(lock)
+        })
+        .await
+        .map_err(|e| McpError::internal_error(format!("State store task failed: {e}"), None))?
+        .map(Some)
+        .map_err(|e: String| McpError::invalid_request(e, None))
     }
 
     // Save env state after any step that modifies it
     async fn save_workflow_state(
-        workflow_id: Option<&str>,
+        &self,
+        workflow_key: Option<&str>,
+        run_id: &str,
         workflow_url: Option<&str>,
         step_id: Option<&str>,
         step_index: usize,
         env: &serde_json::Value,
     ) -> Result<(), McpError> {
-        if let Some(state_file) = Self::get_state_file_path(workflow_id, workflow_url).await {
-            if let Some(state_dir) = state_file.parent() {
-                tokio::fs::create_dir_all(state_dir).await.map_err(|e| {
-                    McpError::internal_error(format!("Failed to create state directory: {e}"), None)
-                })?;
-            }
-
-            let state = json!({
-                "last_updated": chrono::Utc::now().to_rfc3339(),
-                "last_step_id": step_id,
-                "last_step_index": step_index,
-                "workflow_id": workflow_id,
-                "workflow_file": workflow_url.and_then(|url| {
-                    Path::new(url.strip_prefix("file://").unwrap_or(url))
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .map(|s| s.to_string())
-                }),
-                "env": env,
-            });
+        let (Some(store), Some(workflow_key)) = (self.state_store.clone(), workflow_key) else {
+            return Ok(());
+        };
+        let mut snapshot =
+            StateSnapshot::new(workflow_key, run_id, step_id, step_index, env.clone());
+        snapshot.workflow_file = workflow_url.and_then(|url| {
+            Path::new(url.strip_prefix("file://").unwrap_or(url))
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(|s| s.to_string())
+        });
 
-            tokio::fs::write(
-                &state_file,
-                serde_json::to_string_pretty(&state).map_err(|e| {
-                    McpError::internal_error(format!("Failed to serialize state: {e}"), None)
-                })?,
-            )
+        let saved = tokio::task::spawn_blocking(move || store.save_snapshot(snapshot))
             .await
-            .map_err(|e| {
-                McpError::internal_error(format!("Failed to write state file: {e}"), None)
-            })?;
-
-            debug!("Saved workflow state to: {:?}", state_file);
-        }
+            .map_err(|e| McpError::internal_error(format!("State store task failed: {e}"), None))?
+            .map_err(|e| McpError::internal_error(e, None))?;
+        debug!(
+            "Saved workflow state snapshot {} of run '{}' for '{}'",
+            saved.sequence, saved.run_id, saved.workflow_id
+        );
         Ok(())
     }
 
-    // Load env state when starting from a specific step
+    // Load env state when starting from a specific step, from `from_run_id` or the latest run.
+    // With `before_step` the state saved before that step ran is used.
     async fn load_workflow_state(
-        workflow_id: Option<&str>,
-        workflow_url: Option<&str>,
-    ) -> Result<Option<serde_json::Value>, McpError> {
-        if let Some(state_file) = Self::get_state_file_path(workflow_id, workflow_url).await {
-            if state_file.exists() {
-                let content = tokio::fs::read_to_string(&state_file).await.map_err(|e| {
-                    McpError::internal_error(format!("Failed to read state file: {e}"), None)
-                })?;
-                let state: serde_json::Value = serde_json::from_str(&content).map_err(|e| {
-                    McpError::internal_error(format!("Failed to parse state file: {e}"), None)
-                })?;
-
-                if let Some(env) = state.get("env") {
-                    debug!(
-                        "Loaded workflow state from step {} ({})",
-                        state["last_step_index"],
-                        state["last_step_id"].as_str().unwrap_or("unknown")
-                    );
-                    return Ok(Some(env.clone()));
-                }
-            } else {
-                debug!("No saved workflow state found at: {:?}", state_file);
+        &self,
+        workflow_key: Option<&str>,
+        from_run_id: Option<&str>,
+        before_step: Option<usize>,
+    ) -> Result<Option<StateSnapshot>, McpError> {
+        let (Some(store), Some(workflow_key)) = (self.state_store.clone(), workflow_key) else {
+            return Ok(None);
+        };
+        let (key, run_id) = (workflow_key.to_string(), from_run_id.map(str::to_string));
+        let snapshot = tokio::task::spawn_blocking(move || {
+            store.latest_snapshot(&key, run_id.as_deref(), before_step)
+        })
+        .await
+        .map_err(|e| McpError::internal_error(format!("State store task failed: {e}"), None))?
+        .map_err(|e| McpError::internal_error(e, None))?;
+
+        match &snapshot {
+            Some(snapshot) => debug!(
+                "Loaded workflow state of run '{}' from step {} ({})",
+                snapshot.run_id,
+                snapshot.step_index,
+                snapshot.step_id.as_deref().unwrap_or("unknown")
+            ),
+            None => debug!("No saved workflow state found"),
+        }
+        if let (Some(run_id), None) = (from_run_id, &snapshot) {
+            return Err(McpError::invalid_params(
+                format!("No saved state for run '{run_id}' of workflow '{workflow_key}'"),
+                None,
+            ));
+        }
+        Ok(snapshot)
+    }
+
+    /// Ends a loop step: unbinds its variables and returns its result, with the
+    /// overall status stored as `{id}_status`
+    fn finish_step_loop(
+        state: StepLoop,
+        execution_context_map: &mut serde_json::Map<String, Value>,
+        index: usize,
+        step: &crate::utils::SequenceStep,
+    ) -> Value {
+        let mut summary = state.exit(execution_context_map);
+        summary["index"] = json!(index);
+        if let Some(tool_name) = &step.tool_name {
+            summary["tool_name"] = json!(tool_name);
+        } else if let Some(group_name) = &step.group_name {
+            summary["group_name"] = json!(group_name);
+        }
+        if let Some(step_id) = step.id.as_deref() {
+            summary["step_id"] = json!(step_id);
+            Self::store_step_value(
+                execution_context_map,
+                &format!("{step_id}_status"),
+                summary["status"].clone(),
+            );
+        }
+        summary
+    }
+
+    /// Stores a value at the top level of the execution context and in env
+    fn store_step_value(
+        execution_context_map: &mut serde_json::Map<String, Value>,
+        key: &str,
+        value: Value,
+    ) {
+        if let Some(env_map) = execution_context_map
+            .get_mut("env")
+            .and_then(|env| env.as_object_mut())
+        {
+            env_map.insert(key.to_string(), value.clone());
+        }
+        execution_context_map.insert(key.to_string(), value);
+    }
+
+    /// Where a failed step hands over to: its `fallback_id` step, unless that is
+    /// missing or the run stops at `end_at` without `follow_fallback`
+    fn route_failure(
+        fallback_id: Option<&str>,
+        id_to_index: &HashMap<String, usize>,
+        current_index: usize,
+        end_at: Option<usize>,
+        follow_fallback: bool,
+    ) -> FailureRoute {
+        let Some(fb_id) = fallback_id else {
+            return FailureRoute::Next;
+        };
+        let Some(&fb_idx) = id_to_index.get(fb_id) else {
+            warn!(
+                "fallback_id '{}' for step {} not found. Continuing to next step.",
+                fb_id, current_index
+            );
+            return FailureRoute::Next;
+        };
+        if end_at.is_some_and(|end_at| current_index >= end_at) {
+            if !follow_fallback {
+                info!(
+                    "Step {} failed at end_at_step boundary. NOT following fallback '{}' (follow_fallback=false).",
+                    current_index, fb_id
+                );
+                return FailureRoute::Stop;
+            }
+            info!(
+                "Step {} failed at end_at_step boundary. Following fallback to '{}' (follow_fallback=true).",
+                current_index, fb_id
+            );
+        }
+        info!(
+            "Step {} failed. Jumping to fallback step with id '{}' (index {}).",
+            current_index, fb_id, fb_idx
+        );
+        FailureRoute::Fallback(fb_idx)
+    }
+
+    /// Checks the loop and parallel settings of a step before the sequence starts
+    fn validate_control_flow(step: &crate::utils::SequenceStep) -> Result<(), String> {
+        if step.for_each.is_some() && step.r#while.is_some() {
+            return Err("a step can't have both 'for_each' and 'while'".to_string());
+        }
+        if step.parallel != Some(true) {
+            return Ok(());
+        }
+        if step.group_name.is_none() {
+            return Err("'parallel' only applies to groups".to_string());
+        }
+        for call in step.steps.iter().flatten() {
+            let tool_name = call
+                .tool_name
+                .strip_prefix("mcp_terminator-mcp-agent_")
+                .unwrap_or(&call.tool_name);
+            if !PARALLEL_SAFE_TOOLS.contains(&tool_name) {
+                return Err(format!(
+                    "'{tool_name}' can't run in a parallel group, only non-UI tools can: {}",
+                    PARALLEL_SAFE_TOOLS.join(", ")
+                ));
             }
         }
-        Ok(None)
+        Ok(())
     }
 
     /// Helper function to create a flattened execution context where env properties
@@ -370,7 +594,8 @@ impl DesktopWrapper {
             log_source = "agent",
         );
 
-        tokio::select! {
+        let workflow_start = std::time::Instant::now();
+        let result = tokio::select! {
             result = self.execute_sequence_inner(peer, client_progress_token.clone(), request_context, args, request_id.clone()).instrument(tracing_span) => {
                 // Unregister when done
                 self.request_manager.unregister(&request_id).await;
@@ -384,7 +609,24 @@ impl DesktopWrapper {
                     Some(json!({"code": -32001, "request_id": request_id}))
                 ))
             }
-        }
+        };
+
+        // Same success criteria as scheduled runs: no tool error and a successful final status
+        let success = result.as_ref().is_ok_and(|r| {
+            let status = r
+                .content
+                .first()
+                .and_then(|c| crate::server::extract_content_json(c).ok())
+                .and_then(|v| v.get("status").and_then(|s| s.as_str()).map(str::to_string));
+            r.is_error != Some(true)
+                && matches!(
+                    status.as_deref(),
+                    Some("executed_without_error" | "success")
+                )
+        });
+        crate::metrics::record_workflow(success, workflow_start.elapsed());
+
+        result
     }
 
     async fn execute_sequence_inner(
@@ -456,6 +698,13 @@ impl DesktopWrapper {
             _ => args.include_detailed_results.unwrap_or(false), // Changed default to false
         };
 
+        check_steps(&args)?;
+
+        // Each run is compared against the recording from its first call
+        if let Some(replay) = &self.replay {
+            replay.reset();
+        }
+
         // Re-enabling validation logic
         if let Some(variable_schema) = &args.variables {
             let inputs_map = args
@@ -557,7 +806,6 @@ impl DesktopWrapper {
         execution_context_map.insert("env".to_string(), serde_json::Value::Object(env_map));
 
         // Build a map from step ID to its index for quick lookup (includes both main and troubleshooting steps)
-        use std::collections::HashMap;
         let mut id_to_index: HashMap<String, usize> = HashMap::new();
 
         // Map main workflow steps
@@ -628,15 +876,41 @@ impl DesktopWrapper {
             main_steps_len.saturating_sub(1)
         };
 
+        // State snapshots of this run are keyed by its execution id
+        let run_id = args
+            .execution_id
+            .clone()
+            .unwrap_or_else(|| execution_id.clone());
+        let state_key = Self::state_workflow_key(args.workflow_id.as_deref(), args.url.as_deref());
+        let _run_lock = self
+            .lock_workflow_run(state_key.as_deref(), &run_id)
+            .await?;
+
         // NEW: Load saved state if starting from a specific step
-        if start_from_index > 0 {
-            if let Some(saved_env) =
-                Self::load_workflow_state(args.workflow_id.as_deref(), args.url.as_deref()).await?
+        if start_from_index > 0 || args.from_run_id.is_some() {
+            if let Some(snapshot) = self
+                .load_workflow_state(
+                    state_key.as_deref(),
+                    args.from_run_id.as_deref(),
+                    (start_from_index > 0).then_some(start_from_index),
+                )
+                .await?
             {
-                execution_context_map.insert("env".to_string(), saved_env);
+                // Seed this run's history with the state it resumes from
+                self.save_workflow_state(
+                    state_key.as_deref(),
+                    &run_id,
+                    args.url.as_deref(),
+                    snapshot.step_id.as_deref(),
+                    snapshot.step_index,
+                    &snapshot.env,
+                )
+                .await
+                .unwrap_or_else(|e| warn!("Failed to seed workflow state: {}", e.message));
+                execution_context_map.insert("env".to_string(), snapshot.env);
                 debug!(
-                    "Loaded saved env state for resuming from step {}",
-                    start_from_index
+                    "Loaded saved env state of run '{}' for resuming from step {}",
+                    snapshot.run_id, start_from_index
                 );
             }
         }
@@ -719,6 +993,16 @@ impl DesktopWrapper {
         let empty_steps = Vec::new();
         let steps = args.steps.as_ref().unwrap_or(&empty_steps);
         for (step_idx, step) in steps.iter().enumerate() {
+            if let Err(message) = Self::validate_control_flow(step) {
+                return Err(McpError::invalid_params(
+                    format!("Step {} is invalid: {message}", step_idx + 1),
+                    Some(json!({
+                        "error_type": "invalid_step",
+                        "step_index": step_idx + 1,
+                        "step_id": step.id,
+                    })),
+                ));
+            }
             let item = if let Some(tool_name) = &step.tool_name {
                 // Parse delay from either delay_ms or human-readable delay field
                 let delay_ms = if let Some(delay_str) = &step.delay {
@@ -758,6 +1042,7 @@ impl DesktopWrapper {
                         })
                         .collect(),
                     skippable: step.skippable,
+                    parallel: step.parallel,
                 };
                 SequenceItem::Group { tool_group }
             } else {
@@ -796,6 +1081,21 @@ impl DesktopWrapper {
             );
             for (local_idx, step) in troubleshooting.iter().enumerate() {
                 let global_step_idx = main_steps_len + local_idx;
+                if let Err(message) = Self::validate_control_flow(step) {
+                    return Err(McpError::invalid_params(
+                        format!(
+                            "Troubleshooting step {} (global index {}) is invalid: {message}",
+                            local_idx + 1,
+                            global_step_idx + 1
+                        ),
+                        Some(json!({
+                            "error_type": "invalid_step",
+                            "step_index": global_step_idx + 1,
+                            "troubleshooting_index": local_idx + 1,
+                            "step_id": step.id,
+                        })),
+                    ));
+                }
                 let item = if let Some(tool_name) = &step.tool_name {
                     // Parse delay from either delay_ms or human-readable delay field
                     let delay_ms = if let Some(delay_str) = &step.delay {
@@ -835,6 +1135,7 @@ impl DesktopWrapper {
                             })
                             .collect(),
                         skippable: step.skippable,
+                        parallel: step.parallel,
                     };
                     SequenceItem::Group { tool_group }
                 } else {
@@ -1020,6 +1321,8 @@ impl DesktopWrapper {
         let mut current_index: usize = start_from_index;
         let max_iterations = sequence_items.len() * 10; // Prevent infinite fallback loops
         let mut iterations = 0usize;
+        // The for_each/while loop of the step at current_index, while it runs
+        let mut step_loop: Option<StepLoop> = None;
 
         // Track whether we've jumped to troubleshooting
         let mut jumped_to_troubleshooting = false;
@@ -1076,7 +1379,9 @@ impl DesktopWrapper {
         // Capture initial window state before executing any steps
         // This captures state before step 0 (which might open new windows)
         // Check if window management is enabled (defaults to true for backward compatibility)
+        #[cfg(target_os = "windows")]
         let window_mgmt_enabled = args.window_mgmt.enable_window_management.unwrap_or(true);
+        #[cfg(target_os = "windows")]
         if window_mgmt_enabled {
             if let Err(e) = self.window_manager.capture_initial_state().await {
                 tracing::warn!(
@@ -1094,7 +1399,10 @@ impl DesktopWrapper {
             && (current_index <= end_at_index || (follow_fallback && jumped_to_troubleshooting))
             && iterations < max_iterations
         {
-            iterations += 1;
+            // Loop iterations re-enter the same step and don't count towards the guard
+            if step_loop.is_none() {
+                iterations += 1;
+            }
 
             // Check if the request has been cancelled
             if request_context.ct.is_cancelled() {
@@ -1126,7 +1434,7 @@ impl DesktopWrapper {
             let is_always_step = if_expr.as_deref().is_some_and(|s| s.trim() == "always()");
 
             // If a critical error occurred and this step is NOT an 'always' step, skip it.
-            if critical_error_occurred && !is_always_step {
+            if critical_error_occurred && !is_always_step && step_loop.is_none() {
                 results.push(json!({
                     "index": current_index,
                     "status": "skipped",
@@ -1137,25 +1445,67 @@ impl DesktopWrapper {
                 continue;
             }
 
-            // 1. Evaluate condition, unless it's an 'always' step.
-            if let Some(cond_str) = &if_expr {
+            // 1. Evaluate condition, unless it's an 'always' step or a loop iteration.
+            if let (Some(cond_str), None) = (&if_expr, &step_loop) {
                 let execution_context =
                     Self::create_flattened_execution_context(&execution_context_map);
-                if !is_always_step
-                    && !crate::expression_eval::evaluate(cond_str, &execution_context)
-                {
-                    info!(
-                        "Skipping step {} due to if expression not met: `{}`",
-                        current_index, cond_str
-                    );
-                    results.push(json!({
-                        "index": current_index,
-                        "status": "skipped",
-                        "executed": false,
-                        "reason": format!("if_expr not met: {}", cond_str)
-                    }));
-                    current_index += 1;
-                    continue;
+                let condition = if is_always_step {
+                    Ok(true)
+                } else {
+                    crate::expression_eval::try_evaluate(cond_str, &execution_context)
+                };
+                match condition {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        info!(
+                            "Skipping step {} due to if expression not met: `{}`",
+                            current_index, cond_str
+                        );
+                        results.push(json!({
+                            "index": current_index,
+                            "status": "skipped",
+                            "executed": false,
+                            "reason": format!("if_expr not met: {}", cond_str)
+                        }));
+                        current_index += 1;
+                        continue;
+                    }
+                    Err(e) => {
+                        warn!(
+                            "Step {} if expression `{}` failed: {}",
+                            current_index, cond_str, e
+                        );
+                        results.push(json!({
+                            "index": current_index,
+                            "status": "error",
+                            "executed": false,
+                            "error": format!("if expression `{cond_str}` failed: {e}")
+                        }));
+                        sequence_had_errors = true;
+                        // Routed like a failed step: fallback_id, then continue_on_error
+                        let continue_on_error = original_step
+                            .and_then(|s| s.continue_on_error)
+                            .unwrap_or(false);
+                        if fallback_id_opt.is_none() && !continue_on_error {
+                            critical_error_occurred = true;
+                        }
+                        match Self::route_failure(
+                            fallback_id_opt.as_deref(),
+                            &id_to_index,
+                            current_index,
+                            args.end_at_step.is_some().then_some(end_at_index),
+                            follow_fallback,
+                        ) {
+                            FailureRoute::Fallback(fb_idx) => {
+                                used_fallback = true;
+                                jumped_to_troubleshooting |= fb_idx >= main_steps_len;
+                                current_index = fb_idx;
+                            }
+                            FailureRoute::Next => current_index += 1,
+                            FailureRoute::Stop => break,
+                        }
+                        continue;
+                    }
                 }
             }
 
@@ -1183,6 +1533,86 @@ impl DesktopWrapper {
                 }
             }
 
+            // for_each/while steps come back here for each iteration
+            if let (None, Some(step)) = (&step_loop, original_step) {
+                let context = Self::create_flattened_execution_context(&execution_context_map);
+                let started = StepLoop::start(step, &context).and_then(|state| match state {
+                    Some(state) => state.next(&context).map(|more| Some((state, more))),
+                    None => Ok(None),
+                });
+                match started {
+                    Ok(None) => {}
+                    Ok(Some((state, more))) => {
+                        if let Some(step_id) = step.id.as_deref() {
+                            Self::store_step_value(
+                                &mut execution_context_map,
+                                &format!("{step_id}_results"),
+                                json!([]),
+                            );
+                        }
+                        if more {
+                            step_loop = Some(state);
+                        } else {
+                            info!("Step {} has nothing to iterate over", current_index);
+                            let mut summary = Self::finish_step_loop(
+                                state,
+                                &mut execution_context_map,
+                                current_index,
+                                step,
+                            );
+                            summary["executed"] = json!(false);
+                            results.push(summary);
+                            current_index += 1;
+                            continue;
+                        }
+                    }
+                    Err(e) => {
+                        warn!("Step {} loop could not start: {}", current_index, e);
+                        results.push(json!({
+                            "index": current_index,
+                            "status": "error",
+                            "executed": false,
+                            "error": e,
+                        }));
+                        sequence_had_errors = true;
+                        // Routed like a failed step: fallback_id, then continue_on_error
+                        if fallback_id_opt.is_none() && !step.continue_on_error.unwrap_or(false) {
+                            critical_error_occurred = true;
+                        }
+                        match Self::route_failure(
+                            fallback_id_opt.as_deref(),
+                            &id_to_index,
+                            current_index,
+                            args.end_at_step.is_some().then_some(end_at_index),
+                            follow_fallback,
+                        ) {
+                            FailureRoute::Fallback(fb_idx) => {
+                                used_fallback = true;
+                                jumped_to_troubleshooting |= fb_idx >= main_steps_len;
+                                current_index = fb_idx;
+                            }
+                            FailureRoute::Next => current_index += 1,
+                            FailureRoute::Stop => break,
+                        }
+                        continue;
+                    }
+                }
+            }
+            if let Some(state) = step_loop.as_mut() {
+                state.enter(&mut execution_context_map);
+            }
+
+            // Snapshot what the step's UI assertions are compared against
+            let ui_assertions = original_step
+                .and_then(|s| s.expect.as_deref())
+                .filter(|a| !a.is_empty());
+            let assertions_before = match ui_assertions {
+                Some(assertions) => {
+                    Some(crate::ui_assertions::capture_before(&self.desktop, assertions).await)
+                }
+                None => None,
+            };
+
             // 2. Execute with retries
             let mut final_result = json!(null);
             let mut step_error_occurred = false;
@@ -1203,7 +1633,22 @@ impl DesktopWrapper {
                         let execution_context =
                             Self::create_flattened_execution_context(&execution_context_map);
                         let mut substituted_args = tool_call.arguments.clone();
-                        substitute_variables(&mut substituted_args, &execution_context);
+                        let template_issues = crate::template::render_value(
+                            &mut substituted_args,
+                            &execution_context,
+                        );
+                        let strict_templates = original_step
+                            .and_then(|s| s.strict_templates)
+                            .or(args.strict_templates)
+                            .unwrap_or(false);
+                        if !template_issues.is_empty() {
+                            warn!(
+                                "Step {} ('{}') has unresolved template placeholders: {}",
+                                current_index,
+                                tool_call.tool_name,
+                                Self::describe_template_issues(&template_issues)
+                            );
+                        }
 
                         // Inject workflow variables and accumulated env for run_command and execute_browser_script
                         if matches!(
@@ -1353,19 +1798,37 @@ impl DesktopWrapper {
                             ),
                         );
 
-                        let (result, error_occurred) = self
-                            .execute_single_tool(
-                                peer.clone(),
-                                request_context.clone(),
-                                &tool_call.tool_name,
-                                &substituted_args,
-                                tool_call.continue_on_error.unwrap_or(false),
-                                current_index,
-                                include_detailed,
-                                step_id.as_deref(),
-                                execution_context,
-                            )
-                            .await;
+                        let (mut result, error_occurred) =
+                            if strict_templates && !template_issues.is_empty() {
+                                Self::unresolved_template_result(
+                                    &tool_call.tool_name,
+                                    current_index,
+                                    step_id.as_deref(),
+                                    tool_call.continue_on_error.unwrap_or(false),
+                                    &template_issues,
+                                )
+                            } else {
+                                self.execute_single_tool(
+                                    peer.clone(),
+                                    request_context.clone(),
+                                    &tool_call.tool_name,
+                                    &substituted_args,
+                                    tool_call.continue_on_error.unwrap_or(false),
+                                    current_index,
+                                    include_detailed,
+                                    step_id.as_deref(),
+                                    execution_context,
+                                )
+                                .await
+                            };
+                        if !template_issues.is_empty() {
+                            if let Some(obj) = result.as_object_mut() {
+                                obj.insert(
+                                    "unresolved_variables".to_string(),
+                                    json!(template_issues),
+                                );
+                            }
+                        }
 
                         final_result = result.clone();
 
@@ -1428,15 +1891,19 @@ impl DesktopWrapper {
                                     );
 
                                     // Save state after storing tool result
-                                    Self::save_workflow_state(
-                                        args.workflow_id.as_deref(),
+                                    self.save_workflow_state(
+                                        state_key.as_deref(),
+                                        &run_id,
                                         args.url.as_deref(),
                                         Some(step_id),
                                         current_index,
                                         env_value,
                                     )
                                     .await
-                                    .ok(); // Don't fail the workflow if state save fails
+                                    // Don't fail the workflow if state save fails
+                                    .unwrap_or_else(|e| {
+                                        warn!("Failed to save workflow state: {}", e.message)
+                                    });
                                 }
                             }
                         }
@@ -1695,15 +2162,19 @@ impl DesktopWrapper {
 
                             // NEW: Save state after env update
                             if let Some(env_value) = execution_context_map.get("env") {
-                                Self::save_workflow_state(
-                                    args.workflow_id.as_deref(),
+                                self.save_workflow_state(
+                                    state_key.as_deref(),
+                                    &run_id,
                                     args.url.as_deref(),
                                     original_step.and_then(|s| s.id.as_deref()),
                                     current_index,
                                     env_value,
                                 )
                                 .await
-                                .ok(); // Don't fail the workflow if state save fails
+                                // Don't fail the workflow if state save fails
+                                .unwrap_or_else(|e| {
+                                    warn!("Failed to save workflow state: {}", e.message)
+                                });
                             }
                         }
                         // Check for executed_without_error status or legacy success boolean
@@ -1774,87 +2245,187 @@ impl DesktopWrapper {
                         let mut group_results = Vec::new();
                         let is_skippable = tool_group.skippable.unwrap_or(false);
 
-                        for (step_index, step_tool_call) in tool_group.steps.iter_mut().enumerate()
-                        {
-                            // Substitute variables in arguments before execution
+                        if tool_group.parallel.unwrap_or(false) {
                             let execution_context =
                                 Self::create_flattened_execution_context(&execution_context_map);
-                            let mut substituted_args = step_tool_call.arguments.clone();
-                            substitute_variables(&mut substituted_args, &execution_context);
-
-                            // Extract current process from arguments
-                            let current_process = substituted_args
-                                .get("process")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-
-                            // Create execution context for window management + logging
-                            let step_id_for_ctx = step_tool_call.id.clone();
-                            let tool_execution_context = Some(
-                                crate::utils::ToolExecutionContext::sequence_step(
-                                    args.url.clone().unwrap_or_default(),
-                                    current_index + 1, // 1-based for user display
-                                    total_steps,
-                                    last_executed_process.clone(),
-                                )
-                                .with_workflow_context(
-                                    args.url
-                                        .as_deref()
-                                        .and_then(extract_workflow_folder_from_url),
-                                    step_id_for_ctx.clone(),
-                                ),
-                            );
-
-                            let (result, error_occurred) = self
-                                .execute_single_tool(
+                            let strict_templates = original_step
+                                .and_then(|s| s.strict_templates)
+                                .or(args.strict_templates)
+                                .unwrap_or(false);
+                            let outcomes = self
+                                .execute_parallel_branches(
                                     peer.clone(),
                                     request_context.clone(),
-                                    &step_tool_call.tool_name,
-                                    &substituted_args,
-                                    step_tool_call.continue_on_error.unwrap_or(false),
-                                    step_index,
+                                    &tool_group.steps,
+                                    &execution_context,
+                                    strict_templates,
                                     include_detailed,
-                                    step_id_for_ctx.as_deref(), // Use step ID if available
-                                    tool_execution_context,
+                                    args.url.as_deref(),
+                                    current_index + 1,
+                                    total_steps,
                                 )
                                 .await;
 
-                            group_results.push(result.clone());
+                            for (step_tool_call, (result, error_occurred)) in
+                                tool_group.steps.iter().zip(outcomes)
+                            {
+                                // Branches can't see each other's env updates, only their results
+                                if let Some(step_id) = step_tool_call.id.as_deref() {
+                                    let mut content = result
+                                        .get("result")
+                                        .map(|r| r.get("content").unwrap_or(r))
+                                        .unwrap_or(&result)
+                                        .clone();
+                                    if let Some(obj) = content.as_object_mut() {
+                                        obj.remove("server_logs");
+                                    }
+                                    Self::store_step_value(
+                                        &mut execution_context_map,
+                                        &format!("{step_id}_result"),
+                                        content,
+                                    );
+                                    Self::store_step_value(
+                                        &mut execution_context_map,
+                                        &format!("{step_id}_status"),
+                                        result["status"].clone(),
+                                    );
+                                }
 
-                            // Update last_executed_process for window management
-                            if let Some(ref proc) = current_process {
-                                last_executed_process = Some(proc.clone());
+                                let tool_failed = !(result["status"] == "executed_without_error"
+                                    || result["success"] == true
+                                    || (result["status"].is_null() && result["success"] != false));
+                                if tool_failed {
+                                    group_had_errors = true;
+                                    if error_occurred && !is_skippable && fallback_id_opt.is_none()
+                                    {
+                                        critical_error_occurred = true;
+                                    }
+                                    warn!(
+                                        "Parallel group '{}' branch '{}' failed",
+                                        tool_group.group_name, step_tool_call.tool_name
+                                    );
+                                }
+                                group_results.push(result);
                             }
+                        } else {
+                            for (step_index, step_tool_call) in
+                                tool_group.steps.iter_mut().enumerate()
+                            {
+                                // Substitute variables in arguments before execution
+                                let execution_context = Self::create_flattened_execution_context(
+                                    &execution_context_map,
+                                );
+                                let mut substituted_args = step_tool_call.arguments.clone();
+                                let template_issues = crate::template::render_value(
+                                    &mut substituted_args,
+                                    &execution_context,
+                                );
+                                let strict_templates = original_step
+                                    .and_then(|s| s.strict_templates)
+                                    .or(args.strict_templates)
+                                    .unwrap_or(false);
+                                if !template_issues.is_empty() {
+                                    warn!(
+                                    "Group '{}' step {} ('{}') has unresolved template placeholders: {}",
+                                    tool_group.group_name,
+                                    step_index,
+                                    step_tool_call.tool_name,
+                                    Self::describe_template_issues(&template_issues)
+                                );
+                                }
 
-                            if let Some(delay_ms) = step_tool_call.delay_ms {
-                                if delay_ms > 0 {
-                                    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                                // Extract current process from arguments
+                                let current_process = substituted_args
+                                    .get("process")
+                                    .and_then(|v| v.as_str())
+                                    .map(|s| s.to_string());
+
+                                // Create execution context for window management + logging
+                                let step_id_for_ctx = step_tool_call.id.clone();
+                                let tool_execution_context = Some(
+                                    crate::utils::ToolExecutionContext::sequence_step(
+                                        args.url.clone().unwrap_or_default(),
+                                        current_index + 1, // 1-based for user display
+                                        total_steps,
+                                        last_executed_process.clone(),
+                                    )
+                                    .with_workflow_context(
+                                        args.url
+                                            .as_deref()
+                                            .and_then(extract_workflow_folder_from_url),
+                                        step_id_for_ctx.clone(),
+                                    ),
+                                );
+
+                                let (mut result, error_occurred) =
+                                    if strict_templates && !template_issues.is_empty() {
+                                        Self::unresolved_template_result(
+                                            &step_tool_call.tool_name,
+                                            step_index,
+                                            step_id_for_ctx.as_deref(),
+                                            step_tool_call.continue_on_error.unwrap_or(false),
+                                            &template_issues,
+                                        )
+                                    } else {
+                                        self.execute_single_tool(
+                                            peer.clone(),
+                                            request_context.clone(),
+                                            &step_tool_call.tool_name,
+                                            &substituted_args,
+                                            step_tool_call.continue_on_error.unwrap_or(false),
+                                            step_index,
+                                            include_detailed,
+                                            step_id_for_ctx.as_deref(), // Use step ID if available
+                                            tool_execution_context,
+                                        )
+                                        .await
+                                    };
+                                if !template_issues.is_empty() {
+                                    if let Some(obj) = result.as_object_mut() {
+                                        obj.insert(
+                                            "unresolved_variables".to_string(),
+                                            json!(template_issues),
+                                        );
+                                    }
+                                }
+
+                                group_results.push(result.clone());
+
+                                // Update last_executed_process for window management
+                                if let Some(ref proc) = current_process {
+                                    last_executed_process = Some(proc.clone());
+                                }
+
+                                if let Some(delay_ms) = step_tool_call.delay_ms {
+                                    if delay_ms > 0 {
+                                        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                                    }
                                 }
-                            }
 
-                            // Check for failure - not executed_without_error and not legacy success boolean
-                            let tool_failed = !(result["status"] == "executed_without_error"
-                                || result["success"] == true
-                                || (result["status"].is_null() && result["success"] != false));
-                            if tool_failed {
-                                group_had_errors = true;
-                                if error_occurred || is_skippable {
-                                    if error_occurred && !is_skippable {
-                                        // Only mark as critical if there's no fallback to handle it
-                                        if fallback_id_opt.is_none() {
-                                            critical_error_occurred = true;
+                                // Check for failure - not executed_without_error and not legacy success boolean
+                                let tool_failed = !(result["status"] == "executed_without_error"
+                                    || result["success"] == true
+                                    || (result["status"].is_null() && result["success"] != false));
+                                if tool_failed {
+                                    group_had_errors = true;
+                                    if error_occurred || is_skippable {
+                                        if error_occurred && !is_skippable {
+                                            // Only mark as critical if there's no fallback to handle it
+                                            if fallback_id_opt.is_none() {
+                                                critical_error_occurred = true;
+                                            }
                                         }
+                                        tracing::warn!(
+                                            group = %tool_group.group_name,
+                                            tool = %step_tool_call.tool_name,
+                                            step_index = step_index,
+                                            step_id = %step_tool_call.id.clone().unwrap_or_default(),
+                                            skippable = %is_skippable,
+                                            has_fallback = fallback_id_opt.is_some(),
+                                            "Group step failed; breaking out of group"
+                                        );
+                                        break;
                                     }
-                                    tracing::warn!(
-                                        group = %tool_group.group_name,
-                                        tool = %step_tool_call.tool_name,
-                                        step_index = step_index,
-                                        step_id = %step_tool_call.id.clone().unwrap_or_default(),
-                                        skippable = %is_skippable,
-                                        has_fallback = fallback_id_opt.is_some(),
-                                        "Group step failed; breaking out of group"
-                                    );
-                                    break;
                                 }
                             }
                         }
@@ -1899,6 +2470,108 @@ impl DesktopWrapper {
                 }
             }
 
+            // A step that ran cleanly still fails when its UI assertions don't hold
+            if let (Some(assertions), Some(before), false) =
+                (ui_assertions, &assertions_before, step_error_occurred)
+            {
+                let outcomes =
+                    crate::ui_assertions::verify(&self.desktop, assertions, before).await;
+                let failures: Vec<String> = outcomes
+                    .iter()
+                    .filter_map(|o| o.error.as_ref().map(|e| format!("{}: {e}", o.assertion)))
+                    .collect();
+                final_result["ui_assertions"] = json!(outcomes);
+                if !failures.is_empty() {
+                    warn!(
+                        "Step {} UI assertions failed: {}",
+                        current_index,
+                        failures.join("; ")
+                    );
+                    final_result["status"] = json!("executed_with_error");
+                    final_result["error"] =
+                        json!(format!("UI assertions failed: {}", failures.join("; ")));
+                    step_error_occurred = true;
+                    sequence_had_errors = true;
+                    let continue_on_error = original_step
+                        .and_then(|s| s.continue_on_error)
+                        .unwrap_or(false);
+                    if fallback_id_opt.is_none() && !continue_on_error {
+                        critical_error_occurred = true;
+                    }
+                    if let Some(step_id) = original_step.and_then(|s| s.id.as_deref()) {
+                        let status_key = format!("{step_id}_status");
+                        if let Some(env_map) = execution_context_map
+                            .get_mut("env")
+                            .and_then(|env| env.as_object_mut())
+                        {
+                            env_map.insert(status_key.clone(), final_result["status"].clone());
+                        }
+                        execution_context_map.insert(status_key, final_result["status"].clone());
+                    }
+                }
+            }
+
+            // Loop steps run again until their iterations are done
+            if let (Some(mut state), Some(step)) = (step_loop.take(), original_step) {
+                let step_id = step.id.as_deref();
+                let continue_on_error = step.continue_on_error.unwrap_or(false);
+
+                // Collect the iteration's result in env, as stored for single steps
+                if let Some(step_id) = step_id {
+                    let stored = match &sequence_items[current_index] {
+                        SequenceItem::Tool { .. } => {
+                            execution_context_map.get(&format!("{step_id}_result"))
+                        }
+                        SequenceItem::Group { .. } => None,
+                    };
+                    let item = stored.cloned().unwrap_or_else(|| final_result.clone());
+                    let results_key = format!("{step_id}_results");
+                    let mut collected = execution_context_map
+                        .get(&results_key)
+                        .and_then(|v| v.as_array())
+                        .cloned()
+                        .unwrap_or_default();
+                    collected.push(item);
+                    Self::store_step_value(
+                        &mut execution_context_map,
+                        &results_key,
+                        json!(collected),
+                    );
+                }
+                state.record(final_result.clone(), step_error_occurred);
+
+                if (!step_error_occurred || continue_on_error) && !request_context.ct.is_cancelled()
+                {
+                    let context = Self::create_flattened_execution_context(&execution_context_map);
+                    match state.next(&context) {
+                        Ok(true) => {
+                            step_loop = Some(state);
+                            continue;
+                        }
+                        Ok(false) => {}
+                        Err(e) => {
+                            warn!("Step {} loop stopped: {}", current_index, e);
+                            state.fail(e);
+                            if fallback_id_opt.is_none() && !continue_on_error {
+                                critical_error_occurred = true;
+                            }
+                        }
+                    }
+                }
+
+                if state.failed() {
+                    step_error_occurred = true;
+                    sequence_had_errors = true;
+                }
+                let summary =
+                    Self::finish_step_loop(state, &mut execution_context_map, current_index, step);
+                info!(
+                    "Step {} loop finished after {} iteration(s)",
+                    current_index, summary["iterations"]
+                );
+                final_result = summary;
+            }
+
             // Mark this step as executed (not skipped) and add to results
             if let Some(obj) = final_result.as_object_mut() {
                 obj.insert("executed".to_string(), json!(true));
@@ -1931,6 +2604,8 @@ impl DesktopWrapper {
                 );
             }
 
+            // A jump condition that fails to evaluate is routed like a failed step
+            let mut route_as_failure = !step_succeeded;
             if step_succeeded {
                 // Check for conditional jumps on success
                 let mut performed_jump = false;
@@ -1966,8 +2641,38 @@ impl DesktopWrapper {
                                 jump.condition
                             );
 
-                            if crate::expression_eval::evaluate(&jump.condition, &execution_context)
-                            {
+                            let matched = match crate::expression_eval::try_evaluate(
+                                &jump.condition,
+                                &execution_context,
+                            ) {
+                                Ok(matched) => matched,
+                                Err(e) => {
+                                    warn!(
+                                        "Jump condition {}/{} for step {} failed: {}",
+                                        idx + 1,
+                                        jumps.len(),
+                                        current_index,
+                                        e
+                                    );
+                                    results.push(json!({
+                                        "index": current_index,
+                                        "jump_index": idx,
+                                        "status": "error",
+                                        "executed": false,
+                                        "error": format!("jump condition `{}` failed: {e}", jump.condition)
+                                    }));
+                                    sequence_had_errors = true;
+                                    let continue_on_error = original_step
+                                        .and_then(|s| s.continue_on_error)
+                                        .unwrap_or(false);
+                                    if fallback_id_opt.is_none() && !continue_on_error {
+                                        critical_error_occurred = true;
+                                    }
+                                    route_as_failure = true;
+                                    break;
+                                }
+                            };
+                            if matched {
                                 // This condition matched - perform the jump
                                 if let Some(&target_idx) = id_to_index.get(&jump.to_id) {
                                     let reason = jump
@@ -2003,14 +2708,14 @@ impl DesktopWrapper {
                             }
                         }
 
-                        if !performed_jump {
+                        if !performed_jump && !route_as_failure {
                             debug!("No jump conditions matched for step {}", current_index);
                         }
                     }
                 }
 
                 // Only increment if we didn't jump
-                if !performed_jump {
+                if !performed_jump && !route_as_failure {
                     // For successful steps, check if we're about to enter troubleshooting section
                     if !jumped_to_troubleshooting && current_index >= main_steps_len - 1 {
                         // We're at or past the last main step and haven't jumped to troubleshooting
@@ -2020,65 +2725,26 @@ impl DesktopWrapper {
                     }
                     current_index += 1;
                 }
-            } else if let Some(fb_id) = fallback_id_opt {
-                if let Some(&fb_idx) = id_to_index.get(&fb_id) {
-                    // Check if we should follow this fallback based on end_at_step and follow_fallback setting
-                    let should_follow_fallback = if args.end_at_step.is_some()
-                        && current_index >= end_at_index
-                    {
-                        // We're at or past end_at_step boundary
-                        if follow_fallback {
-                            info!(
-                                "Step {} failed at end_at_step boundary. Following fallback to '{}' (follow_fallback=true).",
-                                current_index, fb_id
-                            );
-                            true
-                        } else {
-                            info!(
-                                "Step {} failed at end_at_step boundary. NOT following fallback '{}' (follow_fallback=false).",
-                                current_index, fb_id
-                            );
-                            false
-                        }
-                    } else {
-                        // Normal execution, always follow fallback
-                        true
-                    };
-
-                    if should_follow_fallback {
-                        info!(
-                            "Step {} failed. Jumping to fallback step with id '{}' (index {}).",
-                            current_index, fb_id, fb_idx
-                        );
-
-                        // Mark that we used a fallback
+            }
+            if route_as_failure {
+                match Self::route_failure(
+                    fallback_id_opt.as_deref(),
+                    &id_to_index,
+                    current_index,
+                    args.end_at_step.is_some().then_some(end_at_index),
+                    follow_fallback,
+                ) {
+                    FailureRoute::Fallback(fb_idx) => {
                         used_fallback = true;
-
-                        // Check if we're jumping into the troubleshooting section
                         if fb_idx >= main_steps_len {
                             jumped_to_troubleshooting = true;
                             info!("Entered troubleshooting section via fallback");
                         }
-
                         current_index = fb_idx;
-                    } else {
-                        // Don't follow fallback, treat as normal failure
-                        // Break the loop since we're at end_at_step and not following fallback
-                        info!(
-                            "Stopping execution at end_at_step boundary without following fallback"
-                        );
-                        break;
                     }
-                } else {
-                    warn!(
-                        "fallback_id '{}' for step {} not found. Continuing to next step.",
-                        fb_id, current_index
-                    );
-                    current_index += 1;
+                    FailureRoute::Next => current_index += 1,
+                    FailureRoute::Stop => break,
                 }
-            } else {
-                // Step failed with no fallback
-                current_index += 1;
             }
         }
 
@@ -2135,6 +2801,17 @@ impl DesktopWrapper {
             "typescript_snippet_path": log_paths.ts_path,
         });
 
+        if let Some(replay) = &self.replay {
+            let report = replay.report();
+            if !report.divergences.is_empty() {
+                warn!(
+                    "[replay] Run diverged from the recording in {} places",
+                    report.divergences.len()
+                );
+            }
+            summary["replay"] = json!(report);
+        }
+
         // Support both 'output_parser' (legacy) and 'output' (simplified)
         let parser_def = args.output_parser.as_ref().or(args.output.as_ref());
 
@@ -2232,6 +2909,7 @@ impl DesktopWrapper {
 
         // Restore windows after sequence completion (success or failure)
         // This ensures windows are restored even if sequence fails mid-execution
+        #[cfg(target_os = "windows")]
         if window_mgmt_enabled {
             if let Err(e) = self.window_manager.restore_all_windows().await {
                 tracing::warn!("Failed to restore windows after sequence: {}", e);
@@ -2246,6 +2924,75 @@ impl DesktopWrapper {
         Ok(CallToolResult::success(contents))
     }
 
+    /// Runs the steps of a parallel group concurrently against the same
+    /// context, returning each result and whether it failed unrecoverably.
+    /// Branches skip log capture, since the capture buffer is process-wide.
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_parallel_branches(
+        &self,
+        peer: Peer<RoleServer>,
+        request_context: RequestContext<RoleServer>,
+        steps: &[ToolCall],
+        execution_context: &Value,
+        strict_templates: bool,
+        include_detailed: bool,
+        url: Option<&str>,
+        step_number: usize,
+        total_steps: usize,
+    ) -> Vec<(Value, bool)> {
+        info!("Running {} parallel branches", steps.len());
+        let branches = steps.iter().enumerate().map(|(index, call)| {
+            let peer = peer.clone();
+            let request_context = request_context.clone();
+            async move {
+                let mut arguments = call.arguments.clone();
+                let issues = crate::template::render_value(&mut arguments, execution_context);
+                let is_skippable = call.continue_on_error.unwrap_or(false);
+                let (mut result, error_occurred) = if strict_templates && !issues.is_empty() {
+                    Self::unresolved_template_result(
+                        &call.tool_name,
+                        index,
+                        call.id.as_deref(),
+                        is_skippable,
+                        &issues,
+                    )
+                } else {
+                    let tool_context = crate::utils::ToolExecutionContext::sequence_step(
+                        url.unwrap_or_default().to_string(),
+                        step_number,
+                        total_steps,
+                        None,
+                    )
+                    .with_workflow_context(
+                        url.and_then(extract_workflow_folder_from_url),
+                        call.id.clone(),
+                    )
+                    .without_log_capture();
+                    self.execute_single_tool(
+                        peer,
+                        request_context,
+                        &call.tool_name,
+                        &arguments,
+                        is_skippable,
+                        index,
+                        include_detailed,
+                        call.id.as_deref(),
+                        Some(tool_context),
+                    )
+                    .await
+                };
+                if !issues.is_empty() {
+                    result["unresolved_variables"] = json!(issues);
+                }
+                if let Some(delay_ms) = call.delay_ms.filter(|ms| *ms > 0) {
+                    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                }
+                (result, error_occurred)
+            }
+        });
+        futures::future::join_all(branches).await
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub async fn execute_single_tool(
         &self,
@@ -2265,10 +3012,14 @@ impl DesktopWrapper {
             .unwrap_or(tool_name);
 
         // Start log capture if in verbose mode
-        if include_detailed {
-            if let Some(ref log_capture) = self.log_capture {
-                log_capture.start_capture();
-            }
+        let log_capture = self.log_capture.as_ref().filter(|_| {
+            include_detailed
+                && execution_context
+                    .as_ref()
+                    .is_none_or(|ctx| ctx.capture_logs)
+        });
+        if let Some(log_capture) = log_capture {
+            log_capture.start_capture();
         }
 
         // The substitution is handled in `execute_sequence_impl`.
@@ -2328,13 +3079,11 @@ impl DesktopWrapper {
                 }
 
                 // Capture server logs if in verbose mode
-                if include_detailed {
-                    if let Some(ref log_capture) = self.log_capture {
-                        let captured_logs = log_capture.stop_capture();
-                        if !captured_logs.is_empty() {
-                            if let Some(obj) = result_json.as_object_mut() {
-                                obj.insert("server_logs".to_string(), json!(captured_logs));
-                            }
+                if let Some(log_capture) = log_capture {
+                    let captured_logs = log_capture.stop_capture();
+                    if !captured_logs.is_empty() {
+                        if let Some(obj) = result_json.as_object_mut() {
+                            obj.insert("server_logs".to_string(), json!(captured_logs));
                         }
                     }
                 }
@@ -2372,13 +3121,7 @@ impl DesktopWrapper {
             }
             Err(e) => {
                 // Stop log capture on error and collect logs
-                let captured_logs = if include_detailed {
-                    self.log_capture
-                        .as_ref()
-                        .map(|log_capture| log_capture.stop_capture())
-                } else {
-                    None
-                };
+                let captured_logs = log_capture.map(|log_capture| log_capture.stop_capture());
 
                 let duration_ms = (chrono::Utc::now() - tool_start_time).num_milliseconds();
                 let mut error_result = json!({
@@ -2421,6 +3164,47 @@ impl DesktopWrapper {
         (processed_result, error_occurred)
     }
 
+    /// Joins template issues into a single line for logs and error messages
+    fn describe_template_issues(issues: &[crate::template::TemplateIssue]) -> String {
+        issues
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// Result for a step that was not run because `strict_templates` is on and
+    /// some of its placeholders could not be resolved
+    fn unresolved_template_result(
+        tool_name: &str,
+        index: usize,
+        step_id: Option<&str>,
+        is_skippable: bool,
+        issues: &[crate::template::TemplateIssue],
+    ) -> (serde_json::Value, bool) {
+        let error = format!(
+            "Unresolved template placeholders: {}",
+            Self::describe_template_issues(issues)
+        );
+        if !is_skippable {
+            warn!(
+                "Tool '{}' at index {} not executed. Reason: {}",
+                tool_name, index, error
+            );
+        }
+        let mut result = json!({
+            "tool_name": tool_name,
+            "index": index,
+            "status": if is_skippable { "skipped" } else { "error" },
+            "duration_ms": 0,
+            "error": error,
+        });
+        if let Some(id) = step_id {
+            result["step_id"] = json!(id);
+        }
+        (result, !is_skippable)
+    }
+
     /// Execute TypeScript workflow with MCP notification streaming
     async fn execute_typescript_workflow(
         &self,
@@ -2459,9 +3243,16 @@ impl DesktopWrapper {
 
         // Execute within the span context so all nested logs inherit execution_id/trace_id
         async move {
+            let state_key = Self::state_workflow_key(args.workflow_id.as_deref(), Some(url));
+            let _run_lock = self
+                .lock_workflow_run(state_key.as_deref(), &execution_id_val)
+                .await?;
+
             // Load saved state if resuming
-            let restored_state = if args.start_from_step.is_some() {
-                Self::load_workflow_state(args.workflow_id.as_deref(), Some(url)).await?
+            let restored_state = if args.start_from_step.is_some() || args.from_run_id.is_some() {
+                self.load_workflow_state(state_key.as_deref(), args.from_run_id.as_deref(), None)
+                    .await?
+                    .map(|snapshot| snapshot.env)
             } else {
                 None
             };
@@ -2724,8 +3515,9 @@ impl DesktopWrapper {
                 &result.result.result.last_step_id,
                 result.result.result.last_step_index,
             ) {
-                Self::save_workflow_state(
-                    args.workflow_id.as_deref(),
+                self.save_workflow_state(
+                    state_key.as_deref(),
+                    &execution_id_val,
                     Some(url),
                     Some(last_step_id),
                     last_step_index,
@@ -2771,8 +3563,8 @@ impl DesktopWrapper {
             }
 
             // Restore windows after TypeScript workflow completion (success or failure)
-            let window_mgmt_enabled = args.window_mgmt.enable_window_management.unwrap_or(true);
-            if window_mgmt_enabled {
+            #[cfg(target_os = "windows")]
+            if args.window_mgmt.enable_window_management.unwrap_or(true) {
                 if let Err(e) = self.window_manager.restore_all_windows().await {
                     tracing::warn!("Failed to restore windows after TypeScript workflow: {}", e);
                 } else {
@@ -0,0 +1,316 @@
+//! `for_each` and `while` steps in `execute_sequence`
+//!
+//! A loop step runs its tool or group once per iteration:
+//!
+//! ```yaml
+//! - id: enter_invoice
+//!   for_each: invoices
+//!   as: invoice
+//!   tool_name: type_into_element
+//!   arguments:
+//!     selector: "role:Edit|name:Number"
+//!     text_to_type: "{{ invoice.number }}"
+//!
+//! - id: poll_export
+//!   while: "export_state != 'done'"
+//!   max_iterations: 20
+//!   tool_name: run_command
+//!   arguments:
+//!     run: "..."
+//! ```
+//!
+//! The sequence runner keeps a [`StepLoop`] while the step runs and re-enters
+//! the step for each iteration, so retries, env updates and `{id}_result`
+//! behave as they do for any other step. The current item is bound to `item`
+//! (or the name given in `as`) and `loop` holds `index` (from 1), `index0`,
+//! `first`, plus `last` and `length` for `for_each`.
+
+use crate::expression_eval;
+use crate::utils::SequenceStep;
+use serde_json::{json, Map, Value};
+
+/// Iteration cap for `while` steps without `max_iterations`
+pub const DEFAULT_MAX_WHILE_ITERATIONS: usize = 100;
+
+const DEFAULT_ITEM_NAME: &str = "item";
+const LOOP_VAR: &str = "loop";
+
+enum Kind {
+    ForEach(Vec<Value>),
+    While(String),
+}
+
+/// Progress of a loop step across its iterations
+pub struct StepLoop {
+    kind: Kind,
+    item_name: String,
+    max_iterations: usize,
+    /// Results of the iterations run so far
+    results: Vec<Value>,
+    failed: bool,
+    error: Option<String>,
+    /// Values the loop variables replaced, put back by [`StepLoop::exit`]
+    shadowed: Option<Vec<(String, Option<Value>)>>,
+}
+
+impl StepLoop {
+    /// The loop of `step`, or `None` when it is not a loop step
+    pub fn start(step: &SequenceStep, context: &Value) -> Result<Option<Self>, String> {
+        let kind = match (&step.for_each, &step.r#while) {
+            (None, None) => return Ok(None),
+            (Some(_), Some(_)) => {
+                return Err("a step can't have both 'for_each' and 'while'".to_string())
+            }
+            (Some(expression), None) => Kind::ForEach(Self::items(expression, context)?),
+            (None, Some(condition)) => {
+                expression_eval::parse(condition)
+                    .map_err(|e| format!("invalid 'while' expression `{condition}`: {e}"))?;
+                Kind::While(condition.clone())
+            }
+        };
+
+        let item_name = step
+            .r#as
+            .clone()
+            .unwrap_or_else(|| DEFAULT_ITEM_NAME.to_string());
+        if item_name.is_empty()
+            || item_name == LOOP_VAR
+            || !item_name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        {
+            return Err(format!("invalid loop variable name '{item_name}'"));
+        }
+
+        let max_iterations = match (step.max_iterations, &kind) {
+            (Some(max), _) => max as usize,
+            (None, Kind::ForEach(items)) => items.len(),
+            (None, Kind::While(_)) => DEFAULT_MAX_WHILE_ITERATIONS,
+        };
+        if let Kind::ForEach(items) = &kind {
+            if items.len() > max_iterations {
+                return Err(format!(
+                    "'for_each' has {} items, more than max_iterations ({max_iterations})",
+                    items.len()
+                ));
+            }
+        }
+
+        Ok(Some(Self {
+            kind,
+            item_name,
+            max_iterations,
+            results: Vec::new(),
+            failed: false,
+            error: None,
+            shadowed: None,
+        }))
+    }
+
+    /// The array a `for_each` expression evaluates to
+    fn items(expression: &str, context: &Value) -> Result<Vec<Value>, String> {
+        let value = expression_eval::parse(expression)
+            .and_then(|expr| expr.eval(context))
+            .map_err(|e| format!("invalid 'for_each' expression `{expression}`: {e}"))?;
+        match value {
+            Some(Value::Array(items)) => Ok(items),
+            Some(Value::Null) => Ok(Vec::new()),
+            // Script outputs often carry arrays as JSON text
+            Some(Value::String(text)) => match serde_json::from_str(&text) {
+                Ok(Value::Array(items)) => Ok(items),
+                _ => Err(format!(
+                    "'for_each' expression `{expression}` is not an array"
+                )),
+            },
+            Some(_) => Err(format!(
+                "'for_each' expression `{expression}` is not an array"
+            )),
+            None => Err(format!("'for_each' expression `{expression}` is undefined")),
+        }
+    }
+
+    /// Whether another iteration should run, evaluating a `while` condition
+    /// against `context` with `loop` set for that iteration
+    pub fn next(&self, context: &Value) -> Result<bool, String> {
+        let index = self.results.len();
+        match &self.kind {
+            Kind::ForEach(items) => Ok(index < items.len()),
+            Kind::While(condition) => {
+                let mut scope = match context {
+                    Value::Object(map) => map.clone(),
+                    _ => Map::new(),
+                };
+                scope.insert(LOOP_VAR.to_string(), self.loop_info(index));
+                let holds = expression_eval::try_evaluate(condition, &Value::Object(scope))
+                    .map_err(|e| format!("'while' expression `{condition}` failed: {e}"))?;
+                if holds && index >= self.max_iterations {
+                    return Err(format!(
+                        "'while' condition still holds after {} iterations (max_iterations)",
+                        self.max_iterations
+                    ));
+                }
+                Ok(holds)
+            }
+        }
+    }
+
+    /// Binds the loop variables for the next iteration in `context`
+    pub fn enter(&mut self, context: &mut Map<String, Value>) {
+        let index = self.results.len();
+        let mut bindings = vec![(LOOP_VAR.to_string(), self.loop_info(index))];
+        if let Kind::ForEach(items) = &self.kind {
+            bindings.push((self.item_name.clone(), items[index].clone()));
+        }
+        let shadowed = self.shadowed.get_or_insert_with(Vec::new);
+        for (name, value) in bindings {
+            let previous = context.insert(name.clone(), value);
+            if !shadowed.iter().any(|(n, _)| *n == name) {
+                shadowed.push((name, previous));
+            }
+        }
+    }
+
+    /// Records the result of the iteration that just ran
+    pub fn record(&mut self, result: Value, failed: bool) {
+        self.results.push(result);
+        self.failed |= failed;
+    }
+
+    /// Ends the loop early with an error
+    pub fn fail(&mut self, error: String) {
+        self.failed = true;
+        self.error = Some(error);
+    }
+
+    pub fn results(&self) -> &[Value] {
+        &self.results
+    }
+
+    pub fn failed(&self) -> bool {
+        self.failed
+    }
+
+    /// Removes the loop variables from `context`, restoring what they replaced,
+    /// and returns the loop's summary
+    pub fn exit(self, context: &mut Map<String, Value>) -> Value {
+        for (name, previous) in self.shadowed.unwrap_or_default() {
+            match previous {
+                Some(value) => context.insert(name, value),
+                None => context.remove(&name),
+            };
+        }
+        let mut summary = json!({
+            "status": if self.failed { "executed_with_error" } else { "executed_without_error" },
+            "iterations": self.results.len(),
+            "results": self.results,
+        });
+        if let Some(error) = self.error {
+            summary["error"] = json!(error);
+        }
+        summary
+    }
+
+    fn loop_info(&self, index: usize) -> Value {
+        match &self.kind {
+            Kind::ForEach(items) => json!({
+                "index": index + 1,
+                "index0": index,
+                "first": index == 0,
+                "last": index + 1 == items.len(),
+                "length": items.len(),
+            }),
+            Kind::While(_) => json!({
+                "index": index + 1,
+                "index0": index,
+                "first": index == 0,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(value: Value) -> SequenceStep {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_for_each_binds_items_and_restores_context() {
+        let step = step(json!({"tool_name": "delay", "for_each": "rows", "as": "row"}));
+        let mut context = Map::new();
+        context.insert("rows".to_string(), json!([{"id": 1}, {"id": 2}]));
+        context.insert("row".to_string(), json!("outer"));
+
+        let mut state = StepLoop::start(&step, &Value::Object(context.clone()))
+            .unwrap()
+            .unwrap();
+        let mut seen = Vec::new();
+        while state.next(&Value::Object(context.clone())).unwrap() {
+            state.enter(&mut context);
+            seen.push((
+                context["row"]["id"].clone(),
+                context["loop"]["last"].clone(),
+            ));
+            state.record(json!({"ok": true}), false);
+        }
+        assert_eq!(
+            seen,
+            vec![(json!(1), json!(false)), (json!(2), json!(true))]
+        );
+
+        let summary = state.exit(&mut context);
+        assert_eq!(summary["iterations"], json!(2));
+        assert_eq!(summary["status"], json!("executed_without_error"));
+        assert_eq!(context["row"], json!("outer"));
+        assert!(!context.contains_key("loop"));
+    }
+
+    #[test]
+    fn test_for_each_rejects_non_arrays() {
+        let context = json!({"count": 3, "json_rows": "[1, 2]"});
+        let error = StepLoop::start(&step(json!({"for_each": "count"})), &context)
+            .err()
+            .unwrap();
+        assert!(error.contains("is not an array"), "{error}");
+        let error = StepLoop::start(&step(json!({"for_each": "missing"})), &context)
+            .err()
+            .unwrap();
+        assert!(error.contains("is undefined"), "{error}");
+
+        let state = StepLoop::start(&step(json!({"for_each": "json_rows"})), &context)
+            .unwrap()
+            .unwrap();
+        assert!(state.next(&context).unwrap());
+    }
+
+    #[test]
+    fn test_while_stops_at_max_iterations() {
+        let step = step(json!({"while": "loop.index0 < limit", "max_iterations": 3}));
+        let mut context = Map::new();
+        context.insert("limit".to_string(), json!(2));
+        let mut state = StepLoop::start(&step, &Value::Object(context.clone()))
+            .unwrap()
+            .unwrap();
+        let mut runs = 0;
+        while state.next(&Value::Object(context.clone())).unwrap() {
+            state.enter(&mut context);
+            state.record(Value::Null, false);
+            runs += 1;
+        }
+        assert_eq!(runs, 2);
+
+        context.insert("limit".to_string(), json!(10));
+        let mut state = StepLoop::start(&step, &Value::Object(context.clone()))
+            .unwrap()
+            .unwrap();
+        let error = loop {
+            match state.next(&Value::Object(context.clone())) {
+                Ok(true) => state.record(Value::Null, false),
+                Ok(false) => panic!("condition should hold"),
+                Err(e) => break e,
+            }
+        };
+        assert_eq!(state.results().len(), 3);
+        assert!(error.contains("after 3 iterations"), "{error}");
+    }
+}
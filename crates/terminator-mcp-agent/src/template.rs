@@ -0,0 +1,875 @@
+//! Template engine for `{{ }}` placeholders in step arguments and selectors
+//!
+//! A placeholder holds an [`expression_eval`] expression followed by filters:
+//!
+//! - `{{ user.name }}`, `${{ user.name }}`, `{{ retries * 2 + 1 }}`
+//! - `{{ name | lower }}`, `{{ title | default('Untitled') }}`, `{{ now() | date('%Y') }}`
+//!
+//! Filters: `lower`, `upper`, `trim`, `default(value)`, `json`, `date(format)`,
+//! `join(separator)`, `length`, `first`, `last` and `replace(from, to)`.
+//!
+//! Blocks repeat or select text, e.g. to build a selector list:
+//!
+//! ```text
+//! {% for name in buttons %}role:Button|name:{{ name }}{% if !loop.last %} || {% endif %}{% endfor %}
+//! ```
+//!
+//! Inside a loop, `loop.index` (from 1), `loop.index0`, `loop.first`,
+//! `loop.last` and `loop.length` describe the current item.
+//!
+//! A string that is exactly one placeholder takes the placeholder's value and
+//! type; otherwise values are spliced into the text. Placeholders that cannot
+//! be resolved stay in the output as written and are reported as
+//! [`TemplateIssue`]s.
+
+use crate::expression_eval::{self, Expr};
+use crate::helpers::variable_path_to_pointer;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::fmt;
+
+/// How deep a selector that refers to other selectors is expanded
+const MAX_NESTING: usize = 4;
+
+/// A placeholder or block that could not be rendered
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TemplateIssue {
+    /// The placeholder or block tag as written
+    pub placeholder: String,
+    pub message: String,
+}
+
+impl fmt::Display for TemplateIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.placeholder, self.message)
+    }
+}
+
+/// Renders every string inside `value` in place and returns the problems found
+pub fn render_value(value: &mut Value, variables: &Value) -> Vec<TemplateIssue> {
+    let mut issues = Vec::new();
+    render_value_into(value, variables, &mut issues);
+    issues
+}
+
+fn render_value_into(value: &mut Value, variables: &Value, issues: &mut Vec<TemplateIssue>) {
+    match value {
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                render_value_into(item, variables, issues);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                render_value_into(item, variables, issues);
+            }
+        }
+        Value::String(s) => {
+            if let Some(rendered) = render_template(s, variables, issues, 0) {
+                *value = rendered;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Renders one template string; `None` when it has nothing to render
+fn render_template(
+    template: &str,
+    variables: &Value,
+    issues: &mut Vec<TemplateIssue>,
+    depth: usize,
+) -> Option<Value> {
+    if !template.contains("{{") && !template.contains("{%") {
+        return None;
+    }
+    let nodes = match parse(template) {
+        Ok(nodes) => nodes,
+        Err(issue) => {
+            push_issue(issues, issue);
+            return None;
+        }
+    };
+
+    // A lone placeholder keeps the type of its value
+    if let [Node::Output(placeholder)] = nodes.as_slice() {
+        return Some(match placeholder.resolve(variables, issues)? {
+            Value::String(s) if placeholder.names_selector() => expand_nested(s, variables, depth),
+            other => other,
+        });
+    }
+
+    let mut out = String::with_capacity(template.len());
+    render_nodes(&nodes, variables, issues, depth, &mut out);
+    Some(Value::String(out))
+}
+
+/// Renders an entry of the authored `selectors` map, which may itself be a
+/// template built from inputs. Other variables are data and are never
+/// rendered again. Text that does not fully render is kept as it is.
+fn expand_nested(text: String, variables: &Value, depth: usize) -> Value {
+    if depth >= MAX_NESTING {
+        return Value::String(text);
+    }
+    let mut issues = Vec::new();
+    match render_template(&text, variables, &mut issues, depth + 1) {
+        Some(rendered) if issues.is_empty() => rendered,
+        _ => Value::String(text),
+    }
+}
+
+fn render_nodes(
+    nodes: &[Node],
+    variables: &Value,
+    issues: &mut Vec<TemplateIssue>,
+    depth: usize,
+    out: &mut String,
+) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Output(placeholder) => match placeholder.resolve(variables, issues) {
+                Some(Value::String(s)) if placeholder.names_selector() => {
+                    out.push_str(&display(&expand_nested(s, variables, depth)))
+                }
+                Some(value) => out.push_str(&display(&value)),
+                None => out.push_str(&placeholder.source),
+            },
+            Node::If {
+                source,
+                condition,
+                then,
+                otherwise,
+            } => match condition.eval(variables) {
+                Ok(value) => {
+                    let branch = if expression_eval::is_truthy(value.as_ref()) {
+                        then
+                    } else {
+                        otherwise
+                    };
+                    render_nodes(branch, variables, issues, depth, out);
+                }
+                Err(e) => push_issue(issues, issue(source, e.to_string())),
+            },
+            Node::For {
+                source,
+                var,
+                iterable,
+                body,
+            } => {
+                let items = match iterable.eval(variables) {
+                    Ok(Some(Value::Array(items))) => items,
+                    Ok(Some(Value::Null)) => Vec::new(),
+                    Ok(Some(other)) => {
+                        let message = format!("cannot loop over {}", describe(&other));
+                        push_issue(issues, issue(source, message));
+                        continue;
+                    }
+                    Ok(None) => {
+                        push_issue(issues, undefined_issue(source, iterable, variables));
+                        continue;
+                    }
+                    Err(e) => {
+                        push_issue(issues, issue(source, e.to_string()));
+                        continue;
+                    }
+                };
+
+                let mut scope = match variables {
+                    Value::Object(map) => map.clone(),
+                    _ => Map::new(),
+                };
+                let length = items.len();
+                for (i, item) in items.into_iter().enumerate() {
+                    scope.insert(var.clone(), item);
+                    scope.insert(
+                        "loop".to_string(),
+                        json!({
+                            "index": i + 1,
+                            "index0": i,
+                            "first": i == 0,
+                            "last": i + 1 == length,
+                            "length": length,
+                        }),
+                    );
+                    // Hand the scope out as a value and take it back for the next item
+                    let scope_value = Value::Object(std::mem::take(&mut scope));
+                    render_nodes(body, &scope_value, issues, depth, out);
+                    if let Value::Object(map) = scope_value {
+                        scope = map;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Output(Placeholder),
+    If {
+        source: String,
+        condition: Expr,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    For {
+        source: String,
+        var: String,
+        iterable: Expr,
+        body: Vec<Node>,
+    },
+}
+
+/// `{{ expression | filter | filter(args) }}`
+#[derive(Debug)]
+struct Placeholder {
+    /// As written, including the braces
+    source: String,
+    /// The expression text between the braces
+    inner: String,
+    /// The parsed pipeline, or why it does not parse
+    pipeline: Result<(Expr, Vec<Filter>), String>,
+}
+
+#[derive(Debug)]
+struct Filter {
+    name: String,
+    args: Vec<Expr>,
+}
+
+impl Placeholder {
+    fn new(source: &str, inner: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            inner: inner.trim().to_string(),
+            pipeline: parse_pipeline(inner.trim()),
+        }
+    }
+
+    /// Whether this is a plain lookup into the `selectors` map
+    fn names_selector(&self) -> bool {
+        is_variable_path(&self.inner)
+            && (self.inner.starts_with("selectors.") || self.inner.starts_with("selectors["))
+    }
+
+    /// The placeholder's value, or `None` (with an issue) when it has none
+    fn resolve(&self, variables: &Value, issues: &mut Vec<TemplateIssue>) -> Option<Value> {
+        // Plain variable paths are looked up literally first, so names that
+        // read as arithmetic (`step-1`) keep working
+        if is_variable_path(&self.inner) {
+            if let Some(value) = variables.pointer(&variable_path_to_pointer(&self.inner)) {
+                return Some(value.clone());
+            }
+        }
+
+        let (expr, filters) = match &self.pipeline {
+            Ok(pipeline) => pipeline,
+            Err(message) => {
+                push_issue(issues, issue(&self.source, message.clone()));
+                return None;
+            }
+        };
+        let mut value = match expr.eval(variables) {
+            Ok(value) => value,
+            Err(e) => {
+                push_issue(issues, issue(&self.source, e.to_string()));
+                return None;
+            }
+        };
+        for filter in filters {
+            let args = match filter
+                .args
+                .iter()
+                .map(|arg| arg.eval(variables).map(|v| v.unwrap_or(Value::Null)))
+                .collect::<Result<Vec<_>, _>>()
+            {
+                Ok(args) => args,
+                Err(e) => {
+                    push_issue(issues, issue(&self.source, e.to_string()));
+                    return None;
+                }
+            };
+            value = match apply_filter(&filter.name, value, &args) {
+                Ok(value) => value,
+                Err(message) => {
+                    let message = format!("{}: {message}", filter.name);
+                    push_issue(issues, issue(&self.source, message));
+                    return None;
+                }
+            };
+        }
+
+        if value.is_none() {
+            push_issue(issues, undefined_issue(&self.source, expr, variables));
+        }
+        value
+    }
+}
+
+fn issue(placeholder: &str, message: impl Into<String>) -> TemplateIssue {
+    TemplateIssue {
+        placeholder: placeholder.to_string(),
+        message: message.into(),
+    }
+}
+
+fn push_issue(issues: &mut Vec<TemplateIssue>, issue: TemplateIssue) {
+    if !issues.contains(&issue) {
+        issues.push(issue);
+    }
+}
+
+/// Names the first undefined variable of an expression that evaluated to nothing
+fn undefined_issue(source: &str, expr: &Expr, variables: &Value) -> TemplateIssue {
+    fn first_undefined<'a>(expr: &'a Expr, variables: &Value) -> Option<&'a str> {
+        match expr {
+            Expr::Variable(name) => variables.get(name).is_none().then_some(name.as_str()),
+            Expr::Index(target, key) => {
+                first_undefined(target, variables).or_else(|| first_undefined(key, variables))
+            }
+            Expr::Not(inner) | Expr::Neg(inner) => first_undefined(inner, variables),
+            Expr::Binary(_, left, right) => {
+                first_undefined(left, variables).or_else(|| first_undefined(right, variables))
+            }
+            Expr::Array(items) | Expr::Call(_, items) => items
+                .iter()
+                .find_map(|item| first_undefined(item, variables)),
+            Expr::Literal(_) => None,
+        }
+    }
+    match first_undefined(expr, variables) {
+        Some(name) => issue(source, format!("undefined variable `{name}`")),
+        None => issue(source, "value is undefined"),
+    }
+}
+
+/// A block being parsed, with the nodes collected so far
+struct Frame {
+    source: String,
+    kind: FrameKind,
+    nodes: Vec<Node>,
+    /// The `then` branch once an `{% else %}` is seen
+    then: Option<Vec<Node>>,
+}
+
+enum FrameKind {
+    If(Expr),
+    For(String, Expr),
+}
+
+/// Splits a template into text, placeholders and blocks
+fn parse(template: &str) -> Result<Vec<Node>, TemplateIssue> {
+    // Open blocks, innermost last
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut rest = template;
+
+    while !rest.is_empty() {
+        let Some((start, open_len, is_block)) = next_tag(rest) else {
+            push_text(current(&mut stack, &mut nodes), rest);
+            break;
+        };
+        let close = if is_block { "%}" } else { "}}" };
+        let body_start = start + open_len;
+        let Some(body_len) = find_close(&rest[body_start..], close) else {
+            // An unterminated tag is plain text
+            push_text(current(&mut stack, &mut nodes), rest);
+            break;
+        };
+        let end = body_start + body_len + close.len();
+        push_text(current(&mut stack, &mut nodes), &rest[..start]);
+        let source = &rest[start..end];
+        let inner = rest[body_start..body_start + body_len].trim();
+        rest = &rest[end..];
+
+        if !is_block {
+            let placeholder = Placeholder::new(source, inner);
+            current(&mut stack, &mut nodes).push(Node::Output(placeholder));
+            continue;
+        }
+
+        let (keyword, args) = inner
+            .split_once(char::is_whitespace)
+            .map_or((inner, ""), |(k, a)| (k, a.trim()));
+        let parse_expr =
+            |text: &str| expression_eval::parse(text).map_err(|e| issue(source, e.to_string()));
+        match keyword {
+            "if" => stack.push(Frame {
+                source: source.to_string(),
+                kind: FrameKind::If(parse_expr(args)?),
+                nodes: Vec::new(),
+                then: None,
+            }),
+            "for" => {
+                let Some((var, iterable)) = args.split_once(" in ") else {
+                    return Err(issue(source, "expected `for <name> in <expression>`"));
+                };
+                let var = var.trim();
+                if var.is_empty() || !var.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    return Err(issue(source, format!("invalid loop variable `{var}`")));
+                }
+                stack.push(Frame {
+                    source: source.to_string(),
+                    kind: FrameKind::For(var.to_string(), parse_expr(iterable)?),
+                    nodes: Vec::new(),
+                    then: None,
+                });
+            }
+            "else" => match stack.last_mut() {
+                Some(frame) if matches!(frame.kind, FrameKind::If(_)) && frame.then.is_none() => {
+                    frame.then = Some(std::mem::take(&mut frame.nodes));
+                }
+                _ => return Err(issue(source, "`else` outside of an `if` block")),
+            },
+            "endif" | "endfor" => {
+                let frame = match stack.pop() {
+                    Some(frame)
+                        if matches!(
+                            (&frame.kind, keyword),
+                            (FrameKind::If(_), "endif") | (FrameKind::For(..), "endfor")
+                        ) =>
+                    {
+                        frame
+                    }
+                    _ => return Err(issue(source, format!("unexpected `{keyword}`"))),
+                };
+                let node = match frame.kind {
+                    FrameKind::If(condition) => {
+                        let (then, otherwise) = match frame.then {
+                            Some(then) => (then, frame.nodes),
+                            None => (frame.nodes, Vec::new()),
+                        };
+                        Node::If {
+                            source: frame.source,
+                            condition,
+                            then,
+                            otherwise,
+                        }
+                    }
+                    FrameKind::For(var, iterable) => Node::For {
+                        source: frame.source,
+                        var,
+                        iterable,
+                        body: frame.nodes,
+                    },
+                };
+                current(&mut stack, &mut nodes).push(node);
+            }
+            _ => return Err(issue(source, format!("unknown block `{keyword}`"))),
+        }
+    }
+
+    match stack.pop() {
+        Some(frame) => Err(issue(&frame.source, "block is never closed")),
+        None => Ok(nodes),
+    }
+}
+
+/// Where the next node goes: the innermost open block, else the top level
+fn current<'a>(stack: &'a mut [Frame], nodes: &'a mut Vec<Node>) -> &'a mut Vec<Node> {
+    match stack.last_mut() {
+        Some(frame) => &mut frame.nodes,
+        None => nodes,
+    }
+}
+
+fn push_text(nodes: &mut Vec<Node>, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    match nodes.last_mut() {
+        Some(Node::Text(previous)) => previous.push_str(text),
+        _ => nodes.push(Node::Text(text.to_string())),
+    }
+}
+
+/// Start, opening length and kind of the next `{{`, `${{` or `{%`
+fn next_tag(text: &str) -> Option<(usize, usize, bool)> {
+    let start = text.find(['{', '$'].as_slice()).and_then(|i| {
+        text[i..]
+            .match_indices(['{', '$'].as_slice())
+            .map(|(j, _)| i + j)
+            .find(|&j| {
+                let rest = &text[j..];
+                rest.starts_with("{{") || rest.starts_with("${{") || rest.starts_with("{%")
+            })
+    })?;
+    let rest = &text[start..];
+    Some(if rest.starts_with("${{") {
+        (start, 3, false)
+    } else if rest.starts_with("{%") {
+        (start, 2, true)
+    } else {
+        (start, 2, false)
+    })
+}
+
+/// Offset of `close`, skipping over quoted strings
+fn find_close(text: &str, close: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if text[i..].starts_with(close) => return Some(i),
+            None => {}
+        }
+    }
+    // Unbalanced quotes: fall back to the first closing marker
+    text.find(close)
+}
+
+/// Splits `text` at top-level occurrences of `separator`, outside quotes and brackets
+fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut quote = None;
+    let mut start = 0;
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    for (n, &(i, c)) in chars.iter().enumerate() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '\'' | '"' => quote = Some(c),
+                '(' | '[' => depth += 1,
+                ')' | ']' => depth -= 1,
+                // `||` is the or operator, not a filter
+                '|' if separator == '|'
+                    && (chars.get(n + 1).is_some_and(|&(_, d)| d == '|')
+                        || n > 0 && chars[n - 1].1 == '|') => {}
+                c if c == separator && depth == 0 => {
+                    parts.push(&text[start..i]);
+                    start = i + c.len_utf8();
+                }
+                _ => {}
+            },
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+fn parse_pipeline(inner: &str) -> Result<(Expr, Vec<Filter>), String> {
+    let mut parts = split_top_level(inner, '|').into_iter();
+    let expr = expression_eval::parse(parts.next().unwrap_or_default())
+        .map_err(|e| format!("invalid expression: {e}"))?;
+    let filters = parts.map(parse_filter).collect::<Result<_, _>>()?;
+    Ok((expr, filters))
+}
+
+fn parse_filter(text: &str) -> Result<Filter, String> {
+    let text = text.trim();
+    let (name, args) = match text.split_once('(') {
+        Some((name, rest)) => {
+            let args = rest
+                .trim_end()
+                .strip_suffix(')')
+                .ok_or_else(|| format!("expected `)` after the arguments of `{}`", name.trim()))?;
+            let args = if args.trim().is_empty() {
+                Vec::new()
+            } else {
+                split_top_level(args, ',')
+                    .into_iter()
+                    .map(|arg| {
+                        expression_eval::parse(arg)
+                            .map_err(|e| format!("invalid argument of `{}`: {e}", name.trim()))
+                    })
+                    .collect::<Result<_, _>>()?
+            };
+            (name.trim(), args)
+        }
+        None => (text, Vec::new()),
+    };
+
+    let (min, max) = match name {
+        "lower" | "upper" | "trim" | "json" | "length" | "first" | "last" => (0, 0),
+        "default" => (1, 1),
+        "date" | "join" => (0, 1),
+        "replace" => (2, 2),
+        "" => return Err("missing filter name".to_string()),
+        _ => return Err(format!("unknown filter `{name}`")),
+    };
+    if args.len() < min || args.len() > max {
+        return Err(format!(
+            "`{name}` takes {} argument(s), got {}",
+            if min == max {
+                min.to_string()
+            } else {
+                format!("{min} to {max}")
+            },
+            args.len()
+        ));
+    }
+    Ok(Filter {
+        name: name.to_string(),
+        args,
+    })
+}
+
+/// Applies a filter; an undefined value passes through all but `default`
+fn apply_filter(name: &str, value: Option<Value>, args: &[Value]) -> Result<Option<Value>, String> {
+    let value = match (name, value) {
+        ("default", None | Some(Value::Null)) => return Ok(Some(args[0].clone())),
+        (_, None) => return Ok(None),
+        (_, Some(value)) => value,
+    };
+    Ok(Some(match name {
+        "default" => value,
+        "lower" => Value::String(display(&value).to_lowercase()),
+        "upper" => Value::String(display(&value).to_uppercase()),
+        "trim" => Value::String(display(&value).trim().to_string()),
+        "json" => Value::String(value.to_string()),
+        "replace" => Value::String(display(&value).replace(&display(&args[0]), &display(&args[1]))),
+        "length" => Value::from(match &value {
+            Value::Array(items) => items.len(),
+            Value::Object(map) => map.len(),
+            Value::Null => 0,
+            other => display(other).chars().count(),
+        }),
+        "first" | "last" => match value {
+            Value::Array(items) => {
+                let item = if name == "first" {
+                    items.into_iter().next()
+                } else {
+                    items.into_iter().last()
+                };
+                return Ok(item);
+            }
+            Value::String(s) => {
+                let c = if name == "first" {
+                    s.chars().next()
+                } else {
+                    s.chars().last()
+                };
+                return Ok(c.map(|c| Value::String(c.to_string())));
+            }
+            other => {
+                return Err(format!(
+                    "needs an array or string, got {}",
+                    describe(&other)
+                ))
+            }
+        },
+        "join" => match value {
+            Value::Array(items) => {
+                let separator = args.first().map(display).unwrap_or_else(|| ",".to_string());
+                Value::String(
+                    items
+                        .iter()
+                        .map(display)
+                        .collect::<Vec<_>>()
+                        .join(&separator),
+                )
+            }
+            other => Value::String(display(&other)),
+        },
+        "date" => {
+            let format = args
+                .first()
+                .map(display)
+                .unwrap_or_else(|| "%Y-%m-%d".to_string());
+            // Checked item by item: the parser keeps yielding errors after a bad spec
+            let mut items = Vec::new();
+            for item in StrftimeItems::new(&format) {
+                if item == Item::Error {
+                    return Err(format!("invalid format '{format}'"));
+                }
+                items.push(item);
+            }
+            let date = parse_date(&value)
+                .ok_or_else(|| format!("cannot read a date from {}", display(&value)))?;
+            Value::String(date.format_with_items(items.into_iter()).to_string())
+        }
+        _ => return Err("unknown filter".to_string()),
+    }))
+}
+
+/// Dates as RFC 3339, `YYYY-MM-DD[ HH:MM:SS]` or Unix seconds
+fn parse_date(value: &Value) -> Option<DateTime<FixedOffset>> {
+    match value {
+        Value::Number(n) => {
+            DateTime::from_timestamp(n.as_f64()? as i64, 0).map(|d| d.fixed_offset())
+        }
+        Value::String(s) => {
+            let s = s.trim();
+            DateTime::parse_from_rfc3339(s).ok().or_else(|| {
+                ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"]
+                    .iter()
+                    .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+                    .or_else(|| {
+                        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                            .ok()
+                            .and_then(|d| d.and_hms_opt(0, 0, 0))
+                    })
+                    .map(|d| d.and_utc().fixed_offset())
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Text of a value, without quotes for strings
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn describe(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Whether `inner` is a plain variable path like `data.items[2].name`
+fn is_variable_path(inner: &str) -> bool {
+    !inner.is_empty()
+        && inner
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '[' | ']'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str, variables: Value) -> (Value, Vec<TemplateIssue>) {
+        let mut value = json!(template);
+        let issues = render_value(&mut value, &variables);
+        (value, issues)
+    }
+
+    #[test]
+    fn test_filters() {
+        let vars = json!({
+            "name": "  Ada LOVELACE ",
+            "tags": ["a", "b", "c"],
+            "empty": null,
+            "when": "2024-03-05T10:20:30Z",
+        });
+        let cases = [
+            ("{{ name | trim | lower }}", json!("ada lovelace")),
+            ("{{ name | trim | upper }}", json!("ADA LOVELACE")),
+            ("{{ missing | default('x') }}", json!("x")),
+            ("{{ empty | default(3) }}", json!(3)),
+            ("{{ tags | json }}", json!("[\"a\",\"b\",\"c\"]")),
+            ("{{ tags | join(' / ') }}", json!("a / b / c")),
+            ("{{ tags | length }}", json!(3)),
+            ("{{ tags | last }}", json!("c")),
+            ("{{ when | date('%Y') }}", json!("2024")),
+            ("{{ when | date('%d.%m.%Y') }}", json!("05.03.2024")),
+            ("{{ 0 | date('%Y-%m-%d') }}", json!("1970-01-01")),
+            (
+                "{{ name | replace('LOVELACE', 'L.') | trim }}",
+                json!("Ada L."),
+            ),
+        ];
+        for (template, expected) in cases {
+            let (value, issues) = render(template, vars.clone());
+            assert_eq!(value, expected, "{template}");
+            assert!(issues.is_empty(), "{template}: {issues:?}");
+        }
+
+        let (value, _) = render("{{ now() | date('%Y') }}", json!({}));
+        assert_eq!(value.as_str().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_arithmetic_keeps_types() {
+        let vars = json!({"retries": 2, "page": {"size": 25}});
+        assert_eq!(render("{{ retries * 2 + 1 }}", vars.clone()).0, json!(5));
+        assert_eq!(
+            render("offset={{ (retries - 1) * page.size }}", vars.clone()).0,
+            json!("offset=25")
+        );
+        assert_eq!(
+            render("${{ retries > 1 && page.size == 25 }}", vars).0,
+            json!(true)
+        );
+    }
+
+    #[test]
+    fn test_loop_builds_selector_list() {
+        let vars = json!({"buttons": ["OK", "Apply", "Close"]});
+        let template = "{% for b in buttons %}role:Button|name:{{ b }}{% if !loop.last %} || {% endif %}{% endfor %}";
+        let (value, issues) = render(template, vars);
+        assert!(issues.is_empty(), "{issues:?}");
+        assert_eq!(
+            value,
+            json!("role:Button|name:OK || role:Button|name:Apply || role:Button|name:Close")
+        );
+
+        let (value, _) = render(
+            "{% for b in items %}{{ loop.index }}.{{ b }}{% else %}{% endfor %}",
+            json!({"items": ["x"]}),
+        );
+        assert_eq!(
+            value,
+            json!("{% for b in items %}{{ loop.index }}.{{ b }}{% else %}{% endfor %}")
+        );
+
+        let (value, issues) = render(
+            "{% if retry %}again{% else %}first{% endif %}",
+            json!({"retry": false}),
+        );
+        assert_eq!(value, json!("first"));
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn test_unresolved_placeholders_are_reported_and_kept() {
+        let (value, issues) = render("name:{{ user.name | lower }} id:{{ id }}", json!({"id": 7}));
+        assert_eq!(value, json!("name:{{ user.name | lower }} id:7"));
+        assert_eq!(
+            issues,
+            vec![TemplateIssue {
+                placeholder: "{{ user.name | lower }}".to_string(),
+                message: "undefined variable `user`".to_string(),
+            }]
+        );
+
+        let (_, issues) = render("{{ x | shout }}", json!({"x": 1}));
+        assert_eq!(issues[0].message, "unknown filter `shout`");
+        let (_, issues) = render("{{ x | date('%Q') }}", json!({"x": 1}));
+        assert!(issues[0].message.contains("invalid format"), "{issues:?}");
+        let (value, issues) = render("{% for x in items %}{{ x }}", json!({"items": []}));
+        assert_eq!(value, json!("{% for x in items %}{{ x }}"));
+        assert_eq!(issues[0].message, "block is never closed");
+    }
+
+    #[test]
+    fn test_nested_templates_expand() {
+        let vars = json!({
+            "selectors": {"submit": "role:Button|name:{{ label }}"},
+            "label": "Send",
+            "raw": "{{ not a template }}",
+            "payload": "{{ label }}",
+        });
+        let (value, issues) = render("{{ selectors.submit }}", vars.clone());
+        assert_eq!(value, json!("role:Button|name:Send"));
+        assert!(issues.is_empty());
+
+        // Data that only looks like a template is left alone
+        let (value, issues) = render("{{ raw }}", vars.clone());
+        assert_eq!(value, json!("{{ not a template }}"));
+        assert!(issues.is_empty());
+
+        // Values outside `selectors` are data and are never rendered again
+        let (value, _) = render("{{ payload }}", vars.clone());
+        assert_eq!(value, json!("{{ label }}"));
+        let (value, _) = render("text: {{ payload }}", vars);
+        assert_eq!(value, json!("text: {{ label }}"));
+    }
+}
@@ -9,7 +9,8 @@ use terminator::UINode;
 
 // Re-export clustering types and functions from core terminator crate
 pub use terminator::{
-    format_clustered_tree_from_caches, ClusteredFormattingResult, ElementSource, UnifiedElement,
+    format_clustered_tree_from_caches, format_clustered_tree_with_config, ClusterConfig,
+    ClusteredFormattingResult, ElementSource, UnifiedElement,
 };
 
 /// Convert UINode to SerializableUIElement for unified formatting
@@ -0,0 +1,351 @@
+//! Declarative UI assertions attached to a workflow step via `expect`
+//!
+//! Each assertion names one check and the selector it applies to:
+//!
+//! ```yaml
+//! expect:
+//!   - appears: "role:Dialog && name:Save As"
+//!   - value_changes: "nativeid:total"
+//!     to: "/\\d+\\.\\d\\d/"
+//!   - no_new: "role:Dialog && name:Error"
+//! ```
+//!
+//! Matching elements are counted before the step runs and again after it,
+//! polling until every assertion holds or the timeout runs out.
+
+use regex::Regex;
+use rmcp::{schemars, schemars::JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{Duration, Instant};
+use terminator::{Desktop, Selector};
+
+const DEFAULT_TIMEOUT_MS: u64 = 3000;
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const FIND_TIMEOUT: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq)]
+pub struct UiAssertion {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Selector of an element that must newly appear after the step")]
+    pub appears: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Selector of an element that must be gone after the step")]
+    pub disappears: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        description = "Selector of an element whose value must change during the step (see 'to')"
+    )]
+    pub value_changes: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        description = "Selector that must not match more elements after the step than before, e.g. error dialogs"
+    )]
+    pub no_new: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        description = "For value_changes: the expected new value, exact or a /regex/ such as \"/\\\\d+\\\\.\\\\d\\\\d/\""
+    )]
+    pub to: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "How long to wait for the assertion to hold (default 3000ms)")]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssertionKind {
+    Appears,
+    Disappears,
+    ValueChanges,
+    NoNew,
+}
+
+impl UiAssertion {
+    /// The single check this assertion names, with its selector
+    pub fn kind(&self) -> Result<(AssertionKind, &str), String> {
+        let set: Vec<(AssertionKind, &str)> = [
+            (AssertionKind::Appears, &self.appears),
+            (AssertionKind::Disappears, &self.disappears),
+            (AssertionKind::ValueChanges, &self.value_changes),
+            (AssertionKind::NoNew, &self.no_new),
+        ]
+        .into_iter()
+        .filter_map(|(kind, selector)| selector.as_deref().map(|s| (kind, s)))
+        .collect();
+        match set.as_slice() {
+            [one] => Ok(*one),
+            [] => Err(
+                "expected one of 'appears', 'disappears', 'value_changes' or 'no_new'".to_string(),
+            ),
+            _ => Err(
+                "only one of 'appears', 'disappears', 'value_changes' or 'no_new' may be set"
+                    .to_string(),
+            ),
+        }
+    }
+
+    /// Check the assertion is well formed without touching the UI
+    pub fn validate(&self) -> Result<(), String> {
+        let (kind, selector) = self.kind()?;
+        if let Selector::Invalid(reason) = Selector::from(selector) {
+            return Err(format!("invalid selector `{selector}`: {reason}"));
+        }
+        match (&self.to, kind) {
+            (Some(_), kind) if kind != AssertionKind::ValueChanges => {
+                Err("'to' only applies to 'value_changes'".to_string())
+            }
+            (Some(pattern), _) => value_matches(pattern, "").map(|_| ()),
+            (None, _) => Ok(()),
+        }
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS))
+    }
+
+    /// Decide whether the assertion holds given the state before and after the step
+    pub fn check(&self, before: &Snapshot, after: &Snapshot) -> Result<(), String> {
+        let (kind, selector) = self.kind()?;
+        match kind {
+            AssertionKind::Appears if after.count > before.count => Ok(()),
+            AssertionKind::Appears => Err(format!(
+                "no new element matching `{selector}` appeared ({} before, {} after)",
+                before.count, after.count
+            )),
+            AssertionKind::Disappears if after.count == 0 || after.count < before.count => Ok(()),
+            AssertionKind::Disappears => Err(format!(
+                "element matching `{selector}` is still present ({} after)",
+                after.count
+            )),
+            AssertionKind::NoNew if after.count <= before.count => Ok(()),
+            AssertionKind::NoNew => Err(format!(
+                "{} new element(s) matching `{selector}` appeared",
+                after.count - before.count
+            )),
+            AssertionKind::ValueChanges => {
+                if after.count == 0 {
+                    return Err(format!("no element matches `{selector}`"));
+                }
+                let value = after.value.as_deref().unwrap_or("");
+                if after.value == before.value {
+                    return Err(format!("value of `{selector}` stayed {value:?}"));
+                }
+                match &self.to {
+                    Some(pattern) if !value_matches(pattern, value)? => Err(format!(
+                        "value of `{selector}` changed to {value:?}, expected {pattern}"
+                    )),
+                    _ => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for UiAssertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind() {
+            Ok((AssertionKind::Appears, s)) => write!(f, "`{s}` appears"),
+            Ok((AssertionKind::Disappears, s)) => write!(f, "`{s}` disappears"),
+            Ok((AssertionKind::ValueChanges, s)) => match &self.to {
+                Some(to) => write!(f, "value of `{s}` changes to {to}"),
+                None => write!(f, "value of `{s}` changes"),
+            },
+            Ok((AssertionKind::NoNew, s)) => write!(f, "no new `{s}`"),
+            Err(e) => write!(f, "invalid assertion: {e}"),
+        }
+    }
+}
+
+/// Match `value` against an exact string or a `/regex/`
+fn value_matches(pattern: &str, value: &str) -> Result<bool, String> {
+    match pattern
+        .strip_prefix('/')
+        .and_then(|p| p.strip_suffix('/'))
+        .filter(|p| !p.is_empty())
+    {
+        Some(regex) => Regex::new(regex)
+            .map(|re| re.is_match(value))
+            .map_err(|e| format!("invalid regex {pattern}: {e}")),
+        None => Ok(pattern == value),
+    }
+}
+
+/// What a selector matched at one point in time
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    pub count: usize,
+    /// Value of the first match
+    pub value: Option<String>,
+}
+
+async fn snapshot(desktop: &Desktop, assertion: &UiAssertion) -> Snapshot {
+    let Ok((kind, selector)) = assertion.kind() else {
+        return Snapshot::default();
+    };
+    // Nothing matching is a normal outcome here, not an error
+    let elements = desktop
+        .locator(selector)
+        .all(Some(FIND_TIMEOUT), None)
+        .await
+        .unwrap_or_default();
+    let value = match (kind, elements.first()) {
+        (AssertionKind::ValueChanges, Some(element)) => element.get_value().ok().flatten(),
+        _ => None,
+    };
+    Snapshot {
+        count: elements.len(),
+        value,
+    }
+}
+
+/// Capture the state every assertion is compared against, before the step runs
+pub async fn capture_before(desktop: &Desktop, assertions: &[UiAssertion]) -> Vec<Snapshot> {
+    let mut snapshots = Vec::with_capacity(assertions.len());
+    for assertion in assertions {
+        snapshots.push(snapshot(desktop, assertion).await);
+    }
+    snapshots
+}
+
+/// Outcome of one assertion after the step
+#[derive(Debug, Clone, Serialize)]
+pub struct AssertionOutcome {
+    pub assertion: String,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Re-check the assertions after the step until they all hold or time out
+pub async fn verify(
+    desktop: &Desktop,
+    assertions: &[UiAssertion],
+    before: &[Snapshot],
+) -> Vec<AssertionOutcome> {
+    let start = Instant::now();
+    let mut results: Vec<Option<Result<(), String>>> = vec![None; assertions.len()];
+    loop {
+        for (i, assertion) in assertions.iter().enumerate() {
+            if matches!(results[i], Some(Ok(()))) {
+                continue;
+            }
+            let after = snapshot(desktop, assertion).await;
+            let before = before.get(i).cloned().unwrap_or_default();
+            results[i] = Some(assertion.check(&before, &after));
+        }
+        let pending = assertions
+            .iter()
+            .zip(&results)
+            .any(|(a, r)| !matches!(r, Some(Ok(()))) && start.elapsed() < a.timeout());
+        if !pending {
+            break;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+
+    assertions
+        .iter()
+        .zip(results)
+        .map(|(assertion, result)| {
+            let error = result.and_then(|r| r.err());
+            AssertionOutcome {
+                assertion: assertion.to_string(),
+                passed: error.is_none(),
+                error,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(json: serde_json::Value) -> UiAssertion {
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn snap(count: usize, value: Option<&str>) -> Snapshot {
+        Snapshot {
+            count,
+            value: value.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_kind_requires_exactly_one_check() {
+        let appears = parse(json!({"appears": "role:Dialog && name:Save As"}));
+        assert_eq!(
+            appears.kind(),
+            Ok((AssertionKind::Appears, "role:Dialog && name:Save As"))
+        );
+        assert!(UiAssertion::default().validate().is_err());
+        assert!(
+            parse(json!({"appears": "role:Dialog", "no_new": "role:Dialog"}))
+                .validate()
+                .is_err()
+        );
+        assert!(parse(json!({"appears": "role:Dialog", "to": "x"}))
+            .validate()
+            .is_err());
+        assert!(
+            parse(json!({"value_changes": "nativeid:total", "to": "/(/"}))
+                .validate()
+                .is_err()
+        );
+        assert!(
+            parse(json!({"value_changes": "nativeid:total", "to": r"/\d+\.\d\d/"}))
+                .validate()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_appears_disappears_and_no_new() {
+        let appears = parse(json!({"appears": "role:Dialog"}));
+        assert!(appears.check(&snap(0, None), &snap(1, None)).is_ok());
+        assert!(appears.check(&snap(1, None), &snap(1, None)).is_err());
+
+        let disappears = parse(json!({"disappears": "role:Dialog"}));
+        assert!(disappears.check(&snap(1, None), &snap(0, None)).is_ok());
+        assert!(disappears.check(&snap(2, None), &snap(1, None)).is_ok());
+        assert!(disappears.check(&snap(1, None), &snap(1, None)).is_err());
+
+        let no_new = parse(json!({"no_new": "role:Dialog && name:Error"}));
+        assert!(no_new.check(&snap(1, None), &snap(1, None)).is_ok());
+        assert_eq!(
+            no_new.check(&snap(0, None), &snap(1, None)),
+            Err("1 new element(s) matching `role:Dialog && name:Error` appeared".to_string())
+        );
+    }
+
+    #[test]
+    fn test_value_changes_with_pattern() {
+        let total = parse(json!({"value_changes": "nativeid:total", "to": r"/^\d+\.\d\d$/"}));
+        assert!(total
+            .check(&snap(1, Some("0.00")), &snap(1, Some("12.50")))
+            .is_ok());
+        assert!(total
+            .check(&snap(1, Some("0.00")), &snap(1, Some("12.5")))
+            .is_err());
+        assert!(total
+            .check(&snap(1, Some("12.50")), &snap(1, Some("12.50")))
+            .is_err());
+        assert!(total.check(&snap(1, Some("0.00")), &snap(0, None)).is_err());
+
+        let exact = parse(json!({"value_changes": "nativeid:status", "to": "Done"}));
+        assert!(exact
+            .check(&snap(1, Some("Working")), &snap(1, Some("Done")))
+            .is_ok());
+        assert_eq!(
+            exact.to_string(),
+            "value of `nativeid:status` changes to Done"
+        );
+    }
+}
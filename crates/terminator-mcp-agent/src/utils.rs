@@ -10,8 +10,9 @@ use std::env;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Duration;
+#[cfg(target_os = "windows")]
 use terminator::WindowManager;
-use terminator::{AutomationError, Desktop, UIElement};
+use terminator::{ActionabilityOptions, AutomationError, Desktop, UIElement};
 use tokio::sync::Mutex as TokioMutex;
 use tracing::{warn, Instrument, Level};
 use tracing_subscriber::{util::SubscriberInitExt, EnvFilter, Layer};
@@ -88,7 +89,7 @@ pub struct WindowManagementOptions {
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
 pub struct DiffTreeOptions {
     #[schemars(
-        description = "REQUIRED: Capture UI tree before and after action execution, then compute and return the diff. Returns ui_diff and has_ui_changes fields."
+        description = "REQUIRED: Capture UI tree before and after action execution, then compute and return the diff. Returns ui_diff, has_ui_changes and ui_changes (typed added/removed/moved/renamed/value_changed/state_changed operations) fields."
     )]
     pub ui_diff_before_after: bool,
 
@@ -288,6 +289,7 @@ pub struct ToolExecutionContext {
     pub previous_process: Option<String>, // Track previous process for detecting switches
     pub workflow_id: Option<String>,      // For execution logging
     pub step_id: Option<String>,          // For execution logging
+    pub capture_logs: bool,               // Off where tools run concurrently
 }
 
 impl ToolExecutionContext {
@@ -301,6 +303,7 @@ impl ToolExecutionContext {
             previous_process: None,
             workflow_id: None,
             step_id: None,
+            capture_logs: true,
         }
     }
 
@@ -319,6 +322,7 @@ impl ToolExecutionContext {
             previous_process,
             workflow_id: None,
             step_id: None,
+            capture_logs: true,
         }
     }
 
@@ -332,6 +336,12 @@ impl ToolExecutionContext {
         self.step_id = step_id;
         self
     }
+
+    /// Skip the process-wide log capture, which concurrent tools would share
+    pub fn without_log_capture(mut self) -> Self {
+        self.capture_logs = false;
+        self
+    }
 }
 
 fn default_desktop() -> Arc<Desktop> {
@@ -371,6 +381,7 @@ pub struct DesktopWrapper {
     pub current_workflow_dir: Arc<TokioMutex<Option<std::path::PathBuf>>>,
     #[serde(skip)]
     pub current_scripts_base_path: Arc<TokioMutex<Option<String>>>,
+    #[cfg(target_os = "windows")]
     #[serde(skip)]
     pub window_manager: Arc<WindowManager>,
     /// Tracks whether we're currently executing a workflow sequence
@@ -435,6 +446,23 @@ pub struct DesktopWrapper {
     /// When emit.progress() is called, notifications are sent to ALL connected clients
     #[serde(skip)]
     pub broadcast_peers: Arc<TokioMutex<Vec<Peer<RoleServer>>>>,
+    /// Tree watchers for subscribed `terminator://tree/{pid}` resources, keyed by
+    /// (session id, URI) since every client connection shares this map
+    #[serde(skip)]
+    pub resource_subscriptions:
+        Arc<TokioMutex<HashMap<(String, String), tokio::task::JoinHandle<()>>>>,
+    /// Identifies the client connection this clone serves, see [`DesktopWrapper::for_session`]
+    #[serde(skip)]
+    pub session_id: String,
+    /// When set, tool calls are served from a recorded run instead of the desktop
+    #[serde(skip)]
+    pub replay: Option<Arc<crate::replay::Replay>>,
+    /// Persists workflow `env` snapshots per run; `None` disables persistence
+    #[serde(skip)]
+    pub state_store: Option<Arc<dyn crate::workflow_state::WorkflowStateStore>>,
+    /// Concurrency limit shared with scheduled runs, for transports without the HTTP `/mcp` gate
+    #[serde(skip)]
+    pub tool_gate: Option<crate::scheduler::RequestGate>,
 }
 
 impl Default for DesktopWrapper {
@@ -484,6 +512,31 @@ pub struct GetWindowTreeArgs {
     #[serde(flatten)]
     pub window_mgmt: WindowManagementOptions,
 
+    #[schemars(
+        description = "Approximate token budget for the compact UI tree. Interactive and visible elements are kept first; the rest is collapsed into '… N more' lines with an expand handle. Listed in 'tree_collapsed'."
+    )]
+    pub tree_max_tokens: Option<usize>,
+
+    #[schemars(
+        description = "Maximum number of elements in the compact UI tree. Works like tree_max_tokens and can be combined with it."
+    )]
+    pub tree_max_elements: Option<usize>,
+
+    #[schemars(
+        description = "Handle of a collapsed group from a previous budgeted tree (e.g. '@0.2:3-242~5f1c09ab'). Returns only that group, with the same indices as before, and adds them to the click_element index cache. Pass the same tree_from_selector as the original call. A handle is rejected once its group has changed; get the tree again for new handles."
+    )]
+    pub tree_expand: Option<String>,
+
+    #[schemars(
+        description = "Cluster radius in pixels for tree_output_format 'clustered_yaml'. Defaults to 1.5x the smaller element dimension."
+    )]
+    pub cluster_radius: Option<f64>,
+
+    #[schemars(
+        description = "Whether 'clustered_yaml' fuses the same element seen by several sources (UIA, DOM, OCR, ...) into one line with all its handles. Clicking any fused handle uses the most reliable source. Defaults to true."
+    )]
+    pub cluster_fuse_sources: Option<bool>,
+
     #[schemars(
         description = "Whether to perform OCR on the window and include recognized text with bounding boxes. OCR results are returned as a separate 'ocr_tree' field with word-level positioning for click targeting. Defaults to false."
     )]
@@ -706,6 +759,12 @@ pub struct ClickElementArgs {
     #[serde(default = "default_true")]
     pub restore_cursor: bool,
 
+    #[schemars(
+        description = "Skip actionability checks (visible, enabled, stable, not covered by another element such as a modal dialog). Defaults to false: failed checks are retried until timeout_ms. Selector mode only."
+    )]
+    #[serde(default)]
+    pub force: bool,
+
     #[serde(flatten)]
     pub action: ActionOptions,
 
@@ -859,6 +918,11 @@ pub struct TypeIntoElementArgs {
     )]
     #[serde(default)]
     pub restore_focus: bool,
+    #[schemars(
+        description = "Skip actionability checks (visible, enabled, editable). Defaults to false: failed checks are retried until timeout_ms."
+    )]
+    #[serde(default)]
+    pub force: bool,
     #[serde(flatten)]
     pub selector: SelectorOptions,
 
@@ -1072,6 +1136,32 @@ pub enum VisionType {
     Gemini,
 }
 
+impl VisionType {
+    /// Source of the indexed items in clustered output
+    pub fn element_source(self) -> crate::tree_formatter::ElementSource {
+        use crate::tree_formatter::ElementSource;
+        match self {
+            VisionType::Ocr => ElementSource::Ocr,
+            VisionType::Omniparser => ElementSource::Omniparser,
+            VisionType::UiTree => ElementSource::Uia,
+            VisionType::Dom => ElementSource::Dom,
+            VisionType::Gemini => ElementSource::Gemini,
+        }
+    }
+
+    /// Vision type that holds the items of a clustered output source
+    pub fn from_element_source(source: crate::tree_formatter::ElementSource) -> Self {
+        use crate::tree_formatter::ElementSource;
+        match source {
+            ElementSource::Ocr => VisionType::Ocr,
+            ElementSource::Omniparser => VisionType::Omniparser,
+            ElementSource::Uia => VisionType::UiTree,
+            ElementSource::Dom => VisionType::Dom,
+            ElementSource::Gemini => VisionType::Gemini,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ValidateElementArgs {
     #[serde(flatten)]
@@ -1312,6 +1402,11 @@ pub struct SelectOptionArgs {
 pub struct SetValueArgs {
     #[schemars(description = "The text value to set.")]
     pub value: String,
+    #[schemars(
+        description = "Skip the enabled check before setting the value. Defaults to false: the check is retried until timeout_ms."
+    )]
+    #[serde(default)]
+    pub force: bool,
     #[serde(flatten)]
     pub selector: SelectorOptions,
 
@@ -1466,7 +1561,7 @@ pub struct SequenceStep {
     pub skippable: Option<bool>,
     #[serde(rename = "if", skip_serializing_if = "Option::is_none")]
     #[schemars(
-        description = "An optional expression to determine if this step should run. e.g., \"policy.use_max_budget == true\" or \"contains(policy.product_types, 'FEX')\""
+        description = "An optional expression to determine if this step should run. Supports &&, ||, !, comparisons, arithmetic, grouping and indexing, e.g., \"policy.use_max_budget == true\", \"contains(policy.product_types, 'FEX')\" or \"(retries < 3 && steps[0].status == 'success') || always()\""
     )]
     pub r#if: Option<String>,
     #[schemars(description = "Number of times to retry this step or group on failure.")]
@@ -1493,9 +1588,49 @@ pub struct SequenceStep {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     #[schemars(
-        description = "Expected UI changes after this action (diff between before/after UI trees). Used for validation during workflow playback to ensure actions had the expected effect."
+        description = "Expected UI changes after this action (diff between before/after UI trees), kept as a human-readable note. Use 'expect' for assertions that are checked during playback."
     )]
     pub expected_ui_changes: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        description = "UI assertions checked after the step succeeds, e.g. [{\"appears\": \"role:Dialog && name:Save As\"}, {\"value_changes\": \"nativeid:total\", \"to\": \"/\\\\d+\\\\.\\\\d\\\\d/\"}, {\"no_new\": \"role:Dialog && name:Error\"}]. A failed assertion fails the step, so fallback_id applies."
+    )]
+    pub expect: Option<Vec<crate::ui_assertions::UiAssertion>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        description = "Overrides the sequence-level 'strict_templates' for this step. When true, the step fails instead of running if any {{ }} placeholder in its arguments cannot be resolved."
+    )]
+    pub strict_templates: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        description = "Run this step once per item of an array, given as an expression such as \"invoices\" or \"env.rows\". The item is available as 'item' (or the name in 'as') and loop.index, loop.first, loop.last and loop.length describe the iteration. Each iteration's result is collected in {step_id}_results."
+    )]
+    pub for_each: Option<String>,
+
+    #[serde(rename = "as", skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Name of the loop variable for 'for_each' (default: 'item')")]
+    pub r#as: Option<String>,
+
+    #[serde(rename = "while", skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        description = "Run this step repeatedly while the expression holds, checked before each iteration, e.g. \"export_status != 'done'\". Stops with an error after max_iterations."
+    )]
+    pub r#while: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        description = "Iteration guard for 'while' (default: 100) and 'for_each' (default: no limit)"
+    )]
+    pub max_iterations: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        description = "Run the steps of a group concurrently (for grouped steps). Only non-UI tools are allowed: run_command, delay and the file tools. Each branch's result is stored as {id}_result and {id}_status in env."
+    )]
+    pub parallel: Option<bool>,
 }
 
 #[derive(Debug, serde::Deserialize, serde::Serialize, Clone, Default, JsonSchema)]
@@ -1530,6 +1665,10 @@ pub struct ExecuteSequenceArgs {
     pub selectors: Option<serde_json::Value>,
     #[schemars(description = "Whether to stop the entire sequence on first error (default: true)")]
     pub stop_on_error: Option<bool>,
+    #[schemars(
+        description = "Fail a step instead of running it when a {{ }} placeholder in its arguments cannot be resolved (default: false, unresolved placeholders are left as-is and listed in the step's 'unresolved_variables')"
+    )]
+    pub strict_templates: Option<bool>,
     #[schemars(
         description = "Whether to include detailed results from each tool execution (default: true)"
     )]
@@ -1558,6 +1697,10 @@ pub struct ExecuteSequenceArgs {
     pub verbosity: Option<String>,
     #[schemars(description = "Start execution from a specific step ID (will load saved state)")]
     pub start_from_step: Option<String>,
+    #[schemars(
+        description = "Execution ID of a past run whose latest saved state to resume from (default: the most recently updated run of this workflow)"
+    )]
+    pub from_run_id: Option<String>,
     #[schemars(description = "Stop execution after a specific step ID (inclusive)")]
     pub end_at_step: Option<String>,
     #[schemars(
@@ -1647,6 +1790,7 @@ pub struct ToolGroup {
     pub group_name: String,
     pub steps: Vec<ToolCall>,
     pub skippable: Option<bool>,
+    pub parallel: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -2297,6 +2441,19 @@ pub fn get_timeout(timeout_ms: Option<u64>) -> Option<Duration> {
     Some(Duration::from_millis(timeout))
 }
 
+/// Actionability checks for a tool's action, retried until the tool's timeout.
+/// `force` skips them, like Playwright's `force: true`.
+pub fn actionability_options(
+    checks: ActionabilityOptions,
+    timeout_ms: Option<u64>,
+    force: bool,
+) -> ActionabilityOptions {
+    if force {
+        return ActionabilityOptions::none();
+    }
+    checks.with_timeout(get_timeout(timeout_ms).unwrap_or(Duration::from_millis(3000)))
+}
+
 /// Try multiple selectors with primary selector priority
 /// The primary selector is always preferred if it succeeds, even if alternatives also succeed
 pub async fn find_element_with_fallbacks(
@@ -0,0 +1,827 @@
+//! Versioned workflow state persistence
+//!
+//! `execute_sequence` saves the workflow `env` after every step that changes
+//! it. Each save is kept as a [`StateSnapshot`] keyed by workflow id and run id
+//! (the sequence's execution id), so parallel runs of one workflow keep
+//! separate histories and `start_from_step` can resume from any past run.
+//!
+//! Backends implement [`WorkflowStateStore`]:
+//! - [`FileStateStore`] (default) writes one JSON file per snapshot under
+//!   `<root>/<workflow>/runs/<run>/`
+//! - `SqliteStateStore` (feature `sqlite-state`) keeps all snapshots in
+//!   `<root>/state.sqlite`
+//!
+//! Both also refresh `<root>/<workflow>/state.json` with the latest snapshot,
+//! the file the CLI and the `terminator://workflows/{folder}/state` resource
+//! read. Select the backend with `TERMINATOR_STATE_BACKEND` (`file` or
+//! `sqlite`) and move the root with `TERMINATOR_STATE_DIR`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::warn;
+
+/// State backend to use: `file` (default) or `sqlite`
+pub const STATE_BACKEND_ENV_VAR: &str = "TERMINATOR_STATE_BACKEND";
+/// Root folder for workflow state (defaults to `<data dir>/mediar/workflows`)
+pub const STATE_DIR_ENV_VAR: &str = "TERMINATOR_STATE_DIR";
+/// Runs whose snapshots are kept per workflow; older ones are pruned when a run starts
+pub const RUNS_KEPT_PER_WORKFLOW: usize = 20;
+
+/// The workflow `env` as saved after one step of a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub workflow_id: String,
+    pub run_id: String,
+    /// Position of the snapshot within its run, assigned by the store
+    pub sequence: u64,
+    pub step_id: Option<String>,
+    pub step_index: usize,
+    pub saved_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workflow_file: Option<String>,
+    pub env: Value,
+}
+
+impl StateSnapshot {
+    pub fn new(
+        workflow_id: &str,
+        run_id: &str,
+        step_id: Option<&str>,
+        step_index: usize,
+        env: Value,
+    ) -> Self {
+        Self {
+            workflow_id: workflow_id.to_string(),
+            run_id: run_id.to_string(),
+            sequence: 0,
+            step_id: step_id.map(str::to_string),
+            step_index,
+            saved_at: Utc::now(),
+            workflow_file: None,
+            env,
+        }
+    }
+
+    /// The legacy `state.json` layout
+    fn to_legacy_state(&self) -> Value {
+        json!({
+            "last_updated": self.saved_at.to_rfc3339(),
+            "last_step_id": self.step_id,
+            "last_step_index": self.step_index,
+            "workflow_id": self.workflow_id,
+            "workflow_file": self.workflow_file,
+            "run_id": self.run_id,
+            "env": self.env,
+        })
+    }
+}
+
+/// Overview of one run's snapshot history
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunSummary {
+    pub run_id: String,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub snapshots: usize,
+    pub last_step_id: Option<String>,
+    pub last_step_index: usize,
+}
+
+impl RunSummary {
+    fn from_history(first: &StateSnapshot, last: &StateSnapshot, snapshots: usize) -> Self {
+        Self {
+            run_id: last.run_id.clone(),
+            started_at: first.saved_at,
+            updated_at: last.saved_at,
+            snapshots,
+            last_step_id: last.step_id.clone(),
+            last_step_index: last.step_index,
+        }
+    }
+}
+
+/// Exclusive claim on a run, released when dropped
+pub struct RunLock {
+    release: Option<Box<dyn FnOnce() + Send + Sync>>,
+}
+
+impl RunLock {
+    fn new(release: impl FnOnce() + Send + Sync + 'static) -> Self {
+        Self {
+            release: Some(Box::new(release)),
+        }
+    }
+}
+
+impl Drop for RunLock {
+    fn drop(&mut self) {
+        if let Some(release) = self.release.take() {
+            release();
+        }
+    }
+}
+
+/// Storage for per-run workflow state snapshots
+pub trait WorkflowStateStore: Send + Sync {
+    /// Claims `run_id` for this process until the returned lock is dropped.
+    /// Fails while another live process or sequence holds the same run.
+    fn lock_run(&self, workflow_id: &str, run_id: &str) -> Result<RunLock, String>;
+
+    /// Appends a snapshot to its run and returns it with its `sequence` set
+    fn save_snapshot(&self, snapshot: StateSnapshot) -> Result<StateSnapshot, String>;
+
+    /// All snapshots of a run, oldest first
+    fn history(&self, workflow_id: &str, run_id: &str) -> Result<Vec<StateSnapshot>, String>;
+
+    /// Runs with at least one snapshot, most recently updated first
+    fn list_runs(&self, workflow_id: &str) -> Result<Vec<RunSummary>, String>;
+
+    /// Deletes the snapshots of all but the `keep` most recently updated runs,
+    /// sparing runs that are in progress. Returns how many runs were removed.
+    fn prune_runs(&self, workflow_id: &str, keep: usize) -> Result<usize, String>;
+
+    /// The snapshot to resume from in `run_id`, or in the most recently updated run:
+    /// the last one saved before step `before_step` ran, or the last one overall
+    fn latest_snapshot(
+        &self,
+        workflow_id: &str,
+        run_id: Option<&str>,
+        before_step: Option<usize>,
+    ) -> Result<Option<StateSnapshot>, String> {
+        let run_id = match run_id {
+            Some(run_id) => run_id.to_string(),
+            None => match self.list_runs(workflow_id)?.into_iter().next() {
+                Some(run) => run.run_id,
+                None => return Ok(None),
+            },
+        };
+        let mut history = self.history(workflow_id, &run_id)?;
+        Ok(match before_step {
+            Some(step) => history.into_iter().rev().find(|s| s.step_index < step),
+            None => history.pop(),
+        })
+    }
+}
+
+/// Root folder for workflow state: `TERMINATOR_STATE_DIR`, else `<data dir>/mediar/workflows`
+pub fn state_root() -> Option<PathBuf> {
+    std::env::var(STATE_DIR_ENV_VAR)
+        .ok()
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::data_local_dir().map(|d| d.join("mediar").join("workflows")))
+}
+
+/// Builds the store selected by `TERMINATOR_STATE_BACKEND`, or `None` when no
+/// state folder is available
+pub fn from_env() -> Option<Arc<dyn WorkflowStateStore>> {
+    let root = state_root()?;
+    let backend = std::env::var(STATE_BACKEND_ENV_VAR).unwrap_or_default();
+    match backend.as_str() {
+        "" | "file" => Some(Arc::new(FileStateStore::new(root))),
+        #[cfg(feature = "sqlite-state")]
+        "sqlite" => match SqliteStateStore::open(&root) {
+            Ok(store) => {
+                tracing::info!("[state] Using SQLite state store in {}", root.display());
+                Some(Arc::new(store))
+            }
+            Err(e) => {
+                warn!("[state] {}; falling back to the file state store", e);
+                Some(Arc::new(FileStateStore::new(root)))
+            }
+        },
+        other => {
+            warn!(
+                "[state] Unknown or disabled state backend '{}'; using the file state store",
+                other
+            );
+            Some(Arc::new(FileStateStore::new(root)))
+        }
+    }
+}
+
+/// Makes an id safe to use as a single path component
+fn path_component(id: &str) -> String {
+    let component: String = id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if component.is_empty() || component.chars().all(|c| c == '.') {
+        component.replace('.', "_") + "_"
+    } else {
+        component
+    }
+}
+
+/// Writes through a uniquely named temporary file and a rename, so readers
+/// never see a partially written file and concurrent writers never share one
+fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    use std::io::Write;
+
+    let dir = path.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    let write_error = |e: std::io::Error| format!("Failed to write {}: {e}", path.display());
+    // Removed on drop if anything below fails
+    let mut tmp = tempfile::NamedTempFile::new_in(dir).map_err(write_error)?;
+    tmp.write_all(contents)
+        .and_then(|_| tmp.as_file().sync_all())
+        .map_err(write_error)?;
+    tmp.persist(path).map_err(|e| write_error(e.error))?;
+    Ok(())
+}
+
+/// Refreshes `<root>/<workflow>/state.json` for readers of the legacy layout
+fn write_latest_state(root: &Path, snapshot: &StateSnapshot) -> Result<(), String> {
+    let path = root
+        .join(path_component(&snapshot.workflow_id))
+        .join("state.json");
+    let state = serde_json::to_vec_pretty(&snapshot.to_legacy_state())
+        .map_err(|e| format!("Failed to serialize state: {e}"))?;
+    write_atomic(&path, &state)
+}
+
+/// One JSON file per snapshot under `<root>/<workflow>/runs/<run>/`
+pub struct FileStateStore {
+    root: PathBuf,
+}
+
+impl FileStateStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn runs_dir(&self, workflow_id: &str) -> PathBuf {
+        self.root.join(path_component(workflow_id)).join("runs")
+    }
+
+    fn run_dir(&self, workflow_id: &str, run_id: &str) -> PathBuf {
+        self.runs_dir(workflow_id).join(path_component(run_id))
+    }
+
+    /// Snapshot files of a run, oldest first
+    fn snapshot_files(&self, workflow_id: &str, run_id: &str) -> Result<Vec<PathBuf>, String> {
+        let dir = self.run_dir(workflow_id, run_id);
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to read {}: {e}", dir.display())),
+        };
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.extension().is_some_and(|ext| ext == "json")
+                    && path
+                        .file_stem()
+                        .and_then(|s| s.to_str())
+                        .is_some_and(|s| s.bytes().all(|b| b.is_ascii_digit()))
+            })
+            .collect();
+        // Names are zero-padded sequence numbers
+        files.sort();
+        Ok(files)
+    }
+
+    /// Opens a run's lock file and tries to lock it. The OS releases the lock when
+    /// the holder exits, so a crashed run never leaves a stale lock behind.
+    fn try_lock_run_dir(dir: &Path) -> Result<Option<fs::File>, String> {
+        let lock_path = dir.join("run.lock");
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_path)
+            .map_err(|e| format!("Failed to open {}: {e}", lock_path.display()))?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(file)),
+            Err(fs::TryLockError::WouldBlock) => Ok(None),
+            Err(fs::TryLockError::Error(e)) => {
+                Err(format!("Failed to lock {}: {e}", lock_path.display()))
+            }
+        }
+    }
+
+    fn read_snapshot(path: &Path) -> Result<StateSnapshot, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {e}", path.display()))
+    }
+}
+
+impl WorkflowStateStore for FileStateStore {
+    fn lock_run(&self, workflow_id: &str, run_id: &str) -> Result<RunLock, String> {
+        use std::io::Write;
+
+        let dir = self.run_dir(workflow_id, run_id);
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+        let Some(mut file) = Self::try_lock_run_dir(&dir)? else {
+            // Unreadable on Windows while locked, so the pid is best effort
+            let holder = fs::read_to_string(dir.join("run.lock"))
+                .ok()
+                .and_then(|s| s.trim().parse::<u32>().ok())
+                .map(|pid| format!(" (pid {pid})"))
+                .unwrap_or_default();
+            return Err(format!(
+                "Run '{run_id}' of workflow '{workflow_id}' is already in progress{holder}"
+            ));
+        };
+        // The pid is informational only; the file stays in place so no other
+        // process can lock a fresh file while this one is held
+        file.set_len(0)
+            .and_then(|_| file.write_all(std::process::id().to_string().as_bytes()))
+            .map_err(|e| format!("Failed to write {}: {e}", dir.join("run.lock").display()))?;
+        Ok(RunLock::new(move || {
+            let _ = file.unlock();
+        }))
+    }
+
+    fn save_snapshot(&self, mut snapshot: StateSnapshot) -> Result<StateSnapshot, String> {
+        let files = self.snapshot_files(&snapshot.workflow_id, &snapshot.run_id)?;
+        snapshot.sequence = files
+            .last()
+            .and_then(|path| path.file_stem()?.to_str()?.parse::<u64>().ok())
+            .map_or(0, |last| last + 1);
+
+        let path = self
+            .run_dir(&snapshot.workflow_id, &snapshot.run_id)
+            .join(format!("{:06}.json", snapshot.sequence));
+        let contents = serde_json::to_vec_pretty(&snapshot)
+            .map_err(|e| format!("Failed to serialize state: {e}"))?;
+        write_atomic(&path, &contents)?;
+        write_latest_state(&self.root, &snapshot)?;
+        Ok(snapshot)
+    }
+
+    fn history(&self, workflow_id: &str, run_id: &str) -> Result<Vec<StateSnapshot>, String> {
+        self.snapshot_files(workflow_id, run_id)?
+            .iter()
+            .map(|path| Self::read_snapshot(path))
+            .collect()
+    }
+
+    fn list_runs(&self, workflow_id: &str) -> Result<Vec<RunSummary>, String> {
+        let dir = self.runs_dir(workflow_id);
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to read {}: {e}", dir.display())),
+        };
+
+        let mut runs = Vec::new();
+        for entry in entries.filter_map(Result::ok) {
+            let Some(run) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            let files = self.snapshot_files(workflow_id, &run)?;
+            if let (Some(first), Some(last)) = (files.first(), files.last()) {
+                runs.push(RunSummary::from_history(
+                    &Self::read_snapshot(first)?,
+                    &Self::read_snapshot(last)?,
+                    files.len(),
+                ));
+            }
+        }
+        runs.sort_by_key(|run| std::cmp::Reverse(run.updated_at));
+        Ok(runs)
+    }
+
+    fn prune_runs(&self, workflow_id: &str, keep: usize) -> Result<usize, String> {
+        let mut removed = 0;
+        for run in self.list_runs(workflow_id)?.into_iter().skip(keep) {
+            let dir = self.run_dir(workflow_id, &run.run_id);
+            // Held while the directory is removed, and skipped if the run is in progress
+            let Some(lock) = Self::try_lock_run_dir(&dir)? else {
+                continue;
+            };
+            let removed_files = fs::read_dir(&dir)
+                .map_err(|e| format!("Failed to read {}: {e}", dir.display()))?
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_name() != "run.lock")
+                .try_for_each(|entry| fs::remove_file(entry.path()));
+            drop(lock);
+            removed_files
+                .and_then(|_| fs::remove_dir_all(&dir))
+                .map_err(|e| format!("Failed to prune {}: {e}", dir.display()))?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(feature = "sqlite-state")]
+pub use sqlite::SqliteStateStore;
+
+#[cfg(feature = "sqlite-state")]
+mod sqlite {
+    use super::*;
+    use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+    use std::sync::Mutex;
+    use sysinfo::{ProcessesToUpdate, System};
+
+    const SCHEMA: &str = "
+        CREATE TABLE IF NOT EXISTS snapshots (
+            workflow_id TEXT NOT NULL,
+            run_id TEXT NOT NULL,
+            sequence INTEGER NOT NULL,
+            step_id TEXT,
+            step_index INTEGER NOT NULL,
+            saved_at TEXT NOT NULL,
+            workflow_file TEXT,
+            env TEXT NOT NULL,
+            PRIMARY KEY (workflow_id, run_id, sequence)
+        );
+        CREATE TABLE IF NOT EXISTS run_locks (
+            workflow_id TEXT NOT NULL,
+            run_id TEXT NOT NULL,
+            pid INTEGER NOT NULL,
+            PRIMARY KEY (workflow_id, run_id)
+        );";
+
+    /// All snapshots in `<root>/state.sqlite`, shared safely between processes
+    pub struct SqliteStateStore {
+        root: PathBuf,
+        conn: Arc<Mutex<Connection>>,
+    }
+
+    impl SqliteStateStore {
+        pub fn open(root: &Path) -> Result<Self, String> {
+            fs::create_dir_all(root)
+                .map_err(|e| format!("Failed to create {}: {e}", root.display()))?;
+            let path = root.join("state.sqlite");
+            let conn = Connection::open(&path)
+                .map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+            conn.busy_timeout(std::time::Duration::from_secs(5))
+                .and_then(|_| conn.pragma_update(None, "journal_mode", "WAL"))
+                .and_then(|_| conn.execute_batch(SCHEMA))
+                .map_err(|e| format!("Failed to initialize {}: {e}", path.display()))?;
+            Ok(Self {
+                root: root.to_path_buf(),
+                conn: Arc::new(Mutex::new(conn)),
+            })
+        }
+
+        fn connection(&self) -> std::sync::MutexGuard<'_, Connection> {
+            self.conn.lock().unwrap_or_else(|e| e.into_inner())
+        }
+    }
+
+    fn process_alive(pid: u32) -> bool {
+        let pid = sysinfo::Pid::from_u32(pid);
+        let mut system = System::new();
+        system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+        system.process(pid).is_some()
+    }
+
+    fn sql_error(e: rusqlite::Error) -> String {
+        format!("State database error: {e}")
+    }
+
+    fn snapshot_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<StateSnapshot> {
+        let saved_at: String = row.get("saved_at")?;
+        let env: String = row.get("env")?;
+        Ok(StateSnapshot {
+            workflow_id: row.get("workflow_id")?,
+            run_id: row.get("run_id")?,
+            sequence: row.get::<_, i64>("sequence")? as u64,
+            step_id: row.get("step_id")?,
+            step_index: row.get::<_, i64>("step_index")? as usize,
+            saved_at: DateTime::parse_from_rfc3339(&saved_at)
+                .map(|t| t.with_timezone(&Utc))
+                .unwrap_or_default(),
+            workflow_file: row.get("workflow_file")?,
+            env: serde_json::from_str(&env).unwrap_or(Value::Null),
+        })
+    }
+
+    impl WorkflowStateStore for SqliteStateStore {
+        fn lock_run(&self, workflow_id: &str, run_id: &str) -> Result<RunLock, String> {
+            let pid = std::process::id();
+            let mut conn = self.connection();
+            let tx = conn
+                .transaction_with_behavior(TransactionBehavior::Immediate)
+                .map_err(sql_error)?;
+            let holder: Option<i64> = tx
+                .query_row(
+                    "SELECT pid FROM run_locks WHERE workflow_id = ?1 AND run_id = ?2",
+                    params![workflow_id, run_id],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(sql_error)?;
+            if let Some(holder) = holder.filter(|&h| process_alive(h as u32)) {
+                return Err(format!(
+                    "Run '{run_id}' of workflow '{workflow_id}' is already in progress (pid {holder})"
+                ));
+            }
+            tx.execute(
+                "INSERT OR REPLACE INTO run_locks (workflow_id, run_id, pid) VALUES (?1, ?2, ?3)",
+                params![workflow_id, run_id, pid],
+            )
+            .map_err(sql_error)?;
+            tx.commit().map_err(sql_error)?;
+
+            let conn = self.conn.clone();
+            let (workflow_id, run_id) = (workflow_id.to_string(), run_id.to_string());
+            Ok(RunLock::new(move || {
+                let conn = conn.lock().unwrap_or_else(|e| e.into_inner());
+                let _ = conn.execute(
+                    "DELETE FROM run_locks WHERE workflow_id = ?1 AND run_id = ?2 AND pid = ?3",
+                    params![workflow_id, run_id, pid],
+                );
+            }))
+        }
+
+        fn save_snapshot(&self, mut snapshot: StateSnapshot) -> Result<StateSnapshot, String> {
+            let env = serde_json::to_string(&snapshot.env)
+                .map_err(|e| format!("Failed to serialize state: {e}"))?;
+            let mut conn = self.connection();
+            let tx = conn
+                .transaction_with_behavior(TransactionBehavior::Immediate)
+                .map_err(sql_error)?;
+            let next: i64 = tx
+                .query_row(
+                    "SELECT COALESCE(MAX(sequence) + 1, 0) FROM snapshots
+                     WHERE workflow_id = ?1 AND run_id = ?2",
+                    params![snapshot.workflow_id, snapshot.run_id],
+                    |row| row.get(0),
+                )
+                .map_err(sql_error)?;
+            snapshot.sequence = next as u64;
+            tx.execute(
+                "INSERT INTO snapshots
+                 (workflow_id, run_id, sequence, step_id, step_index, saved_at, workflow_file, env)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    snapshot.workflow_id,
+                    snapshot.run_id,
+                    next,
+                    snapshot.step_id,
+                    snapshot.step_index as i64,
+                    snapshot.saved_at.to_rfc3339(),
+                    snapshot.workflow_file,
+                    env,
+                ],
+            )
+            .map_err(sql_error)?;
+            tx.commit().map_err(sql_error)?;
+            drop(conn);
+
+            write_latest_state(&self.root, &snapshot)?;
+            Ok(snapshot)
+        }
+
+        fn history(&self, workflow_id: &str, run_id: &str) -> Result<Vec<StateSnapshot>, String> {
+            let conn = self.connection();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT * FROM snapshots WHERE workflow_id = ?1 AND run_id = ?2
+                     ORDER BY sequence",
+                )
+                .map_err(sql_error)?;
+            let rows = stmt
+                .query_map(params![workflow_id, run_id], snapshot_from_row)
+                .map_err(sql_error)?;
+            rows.collect::<Result<_, _>>().map_err(sql_error)
+        }
+
+        fn list_runs(&self, workflow_id: &str) -> Result<Vec<RunSummary>, String> {
+            let conn = self.connection();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT run_id, MIN(sequence), MAX(sequence), COUNT(*) FROM snapshots
+                     WHERE workflow_id = ?1 GROUP BY run_id",
+                )
+                .map_err(sql_error)?;
+            let bounds = stmt
+                .query_map(params![workflow_id], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, i64>(1)?,
+                        row.get::<_, i64>(2)?,
+                        row.get::<_, i64>(3)? as usize,
+                    ))
+                })
+                .map_err(sql_error)?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(sql_error)?;
+
+            let mut runs = Vec::new();
+            for (run_id, first, last, count) in bounds {
+                let snapshot_at = |sequence: i64| {
+                    conn.query_row(
+                        "SELECT * FROM snapshots
+                         WHERE workflow_id = ?1 AND run_id = ?2 AND sequence = ?3",
+                        params![workflow_id, run_id, sequence],
+                        snapshot_from_row,
+                    )
+                    .map_err(sql_error)
+                };
+                runs.push(RunSummary::from_history(
+                    &snapshot_at(first)?,
+                    &snapshot_at(last)?,
+                    count,
+                ));
+            }
+            runs.sort_by_key(|run| std::cmp::Reverse(run.updated_at));
+            Ok(runs)
+        }
+
+        fn prune_runs(&self, workflow_id: &str, keep: usize) -> Result<usize, String> {
+            let stale: Vec<String> = self
+                .list_runs(workflow_id)?
+                .into_iter()
+                .skip(keep)
+                .map(|run| run.run_id)
+                .collect();
+            let mut conn = self.connection();
+            let tx = conn
+                .transaction_with_behavior(TransactionBehavior::Immediate)
+                .map_err(sql_error)?;
+            let mut removed = 0;
+            for run_id in stale {
+                let holder: Option<i64> = tx
+                    .query_row(
+                        "SELECT pid FROM run_locks WHERE workflow_id = ?1 AND run_id = ?2",
+                        params![workflow_id, run_id],
+                        |row| row.get(0),
+                    )
+                    .optional()
+                    .map_err(sql_error)?;
+                if holder.is_some_and(|h| process_alive(h as u32)) {
+                    continue;
+                }
+                tx.execute(
+                    "DELETE FROM snapshots WHERE workflow_id = ?1 AND run_id = ?2",
+                    params![workflow_id, run_id],
+                )
+                .map_err(sql_error)?;
+                removed += 1;
+            }
+            tx.commit().map_err(sql_error)?;
+            Ok(removed)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("terminator_state_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        root
+    }
+
+    fn exercise_store(store: &dyn WorkflowStateStore, root: &Path) {
+        // Two interleaved runs of the same workflow keep separate histories
+        store
+            .save_snapshot(StateSnapshot::new(
+                "wf",
+                "run-a",
+                Some("open"),
+                0,
+                json!({"n": 1}),
+            ))
+            .unwrap();
+        store
+            .save_snapshot(StateSnapshot::new(
+                "wf",
+                "run-b",
+                Some("open"),
+                0,
+                json!({"n": 10}),
+            ))
+            .unwrap();
+        let saved = store
+            .save_snapshot(StateSnapshot::new(
+                "wf",
+                "run-a",
+                Some("save"),
+                2,
+                json!({"n": 2}),
+            ))
+            .unwrap();
+        assert_eq!(saved.sequence, 1);
+
+        let history = store.history("wf", "run-a").unwrap();
+        assert_eq!(
+            history
+                .iter()
+                .map(|s| s.env["n"].clone())
+                .collect::<Vec<_>>(),
+            vec![json!(1), json!(2)]
+        );
+        assert_eq!(
+            store
+                .latest_snapshot("wf", Some("run-b"), None)
+                .unwrap()
+                .unwrap()
+                .env,
+            json!({"n": 10})
+        );
+        // Without a run id the most recently updated run wins
+        assert_eq!(
+            store
+                .latest_snapshot("wf", None, None)
+                .unwrap()
+                .unwrap()
+                .run_id,
+            "run-a"
+        );
+        // Resuming at a step starts from the state saved before it ran
+        let before_save = store.latest_snapshot("wf", Some("run-a"), Some(2)).unwrap();
+        assert_eq!(before_save.unwrap().step_id.as_deref(), Some("open"));
+        assert!(store
+            .latest_snapshot("wf", Some("run-a"), Some(0))
+            .unwrap()
+            .is_none());
+        let runs = store.list_runs("wf").unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].snapshots, 2);
+        assert_eq!(runs[0].last_step_id.as_deref(), Some("save"));
+        assert!(store
+            .latest_snapshot("other", None, None)
+            .unwrap()
+            .is_none());
+
+        // The legacy state.json mirrors the latest save
+        let legacy: Value =
+            serde_json::from_str(&fs::read_to_string(root.join("wf").join("state.json")).unwrap())
+                .unwrap();
+        assert_eq!(legacy["last_step_id"], json!("save"));
+        assert_eq!(legacy["env"], json!({"n": 2}));
+
+        // A run can only be claimed once at a time
+        let lock = store.lock_run("wf", "run-a").unwrap();
+        assert!(store.lock_run("wf", "run-a").is_err());
+        assert!(store.lock_run("wf", "run-b").is_ok());
+        drop(lock);
+        assert!(store.lock_run("wf", "run-a").is_ok());
+
+        // Only the most recent runs are kept, and a run in progress is never pruned
+        let _lock = store.lock_run("wf", "run-b").unwrap();
+        assert_eq!(store.prune_runs("wf", 0).unwrap(), 1);
+        let runs: Vec<String> = store
+            .list_runs("wf")
+            .unwrap()
+            .into_iter()
+            .map(|run| run.run_id)
+            .collect();
+        assert_eq!(runs, ["run-b"]);
+        assert!(store.history("wf", "run-a").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_file_store_keeps_runs_apart() {
+        let root = temp_root("file");
+        exercise_store(&FileStateStore::new(&root), &root);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_file_store_ignores_lock_files_left_by_dead_processes() {
+        let root = temp_root("stale");
+        let store = FileStateStore::new(&root);
+        let lock_path = store.run_dir("wf", "run").join("run.lock");
+        fs::create_dir_all(lock_path.parent().unwrap()).unwrap();
+        fs::write(&lock_path, u32::MAX.to_string()).unwrap();
+
+        let lock = store.lock_run("wf", "run").unwrap();
+        drop(lock);
+        assert_eq!(
+            fs::read_to_string(&lock_path).unwrap(),
+            std::process::id().to_string()
+        );
+        assert!(store.lock_run("wf", "run").is_ok());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_ids_stay_inside_the_state_folder() {
+        assert_eq!(path_component("../../etc"), ".._.._etc");
+        assert_eq!(path_component(".."), "___");
+        assert_eq!(path_component(""), "_");
+        assert_eq!(path_component("github-demo"), "github-demo");
+    }
+
+    #[cfg(feature = "sqlite-state")]
+    #[test]
+    fn test_sqlite_store_keeps_runs_apart() {
+        let root = temp_root("sqlite");
+        exercise_store(&SqliteStateStore::open(&root).unwrap(), &root);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
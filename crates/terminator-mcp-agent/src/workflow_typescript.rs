@@ -8,8 +8,11 @@ use tokio::io::{AsyncBufReadExt, BufReader};
 use tracing::{debug, error, info, warn, Instrument};
 
 use crate::child_process;
-use crate::event_pipe::{create_event_channel, try_parse_event, EventPipeServer};
+use crate::event_pipe::try_parse_event;
+#[cfg(windows)]
+use crate::event_pipe::{create_event_channel, EventPipeServer};
 use crate::execution_logger::CapturedLogEntry;
+#[cfg(windows)]
 use crate::log_pipe::{create_log_channel, forward_log_to_tracing, LogPipeServer};
 use chrono::Utc;
 use rmcp::ErrorData as McpError;
@@ -43,6 +46,27 @@ pub fn detect_js_runtime() -> JsRuntime {
     JsRuntime::Node
 }
 
+/// Command evaluating `script` with the given runtime from `dir`, with piped stdout/stderr
+fn runtime_command(runtime: &JsRuntime, dir: &Path, script: &str) -> tokio::process::Command {
+    use std::process::Stdio;
+    let mut cmd = match runtime {
+        JsRuntime::Bun(bun_path) => {
+            let mut cmd = tokio::process::Command::new(bun_path);
+            cmd.arg("--eval").arg(script);
+            cmd
+        }
+        JsRuntime::Node => {
+            let mut cmd = tokio::process::Command::new("node");
+            cmd.arg("--import").arg("tsx/esm").arg("--eval").arg(script);
+            cmd
+        }
+    };
+    cmd.current_dir(dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    cmd
+}
+
 /// Log level parsed from TypeScript console output
 #[derive(Debug, Clone, PartialEq)]
 pub enum LogLevel {
@@ -299,6 +323,46 @@ impl TypeScriptWorkflow {
         })
     }
 
+    /// Load the workflow's metadata (name, steps, trigger) without running it
+    pub async fn metadata(&self) -> Result<WorkflowMetadata, McpError> {
+        self.ensure_dependencies_in(&self.workflow_path).await?;
+        let script =
+            self.create_execution_script(&self.workflow_path, json!({}), None, None, None, None)?;
+        let output = runtime_command(&detect_js_runtime(), &self.workflow_path, &script)
+            .env("TERMINATOR_GET_METADATA", "1")
+            .env("MCP_PARENT_PID", std::process::id().to_string())
+            .output()
+            .await
+            .map_err(|e| {
+                McpError::internal_error(
+                    format!("Failed to load workflow metadata: {e}"),
+                    Some(json!({"error": e.to_string()})),
+                )
+            })?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let parsed = stdout
+            .find('{')
+            .and_then(|start| serde_json::from_str::<Value>(&stdout[start..]).ok())
+            .and_then(|value| value.get("metadata").cloned())
+            .filter(|_| output.status.success());
+        let Some(metadata) = parsed else {
+            return Err(McpError::internal_error(
+                "Failed to load workflow metadata".to_string(),
+                Some(json!({
+                    "path": self.workflow_path.display().to_string(),
+                    "stderr": String::from_utf8_lossy(&output.stderr),
+                })),
+            ));
+        };
+        serde_json::from_value(metadata).map_err(|e| {
+            McpError::internal_error(
+                format!("Invalid workflow metadata: {e}"),
+                Some(json!({"error": e.to_string()})),
+            )
+        })
+    }
+
     /// Execute the entire TypeScript workflow with state management
     pub async fn execute(
         &self,
@@ -511,9 +575,6 @@ impl TypeScriptWorkflow {
             None
         };
 
-        #[cfg(not(windows))]
-        let pipe_server_handle: Option<((), String)> = None;
-
         // Set up Windows named pipe for log streaming
         #[cfg(windows)]
         let log_pipe_handle = {
@@ -557,47 +618,19 @@ impl TypeScriptWorkflow {
             Some((handle, log_pipe_name, captured_logs_for_pipe, receiver_task))
         };
 
-        #[cfg(not(windows))]
-        let log_pipe_handle: Option<(
-            (),
-            String,
-            Arc<Mutex<Vec<CapturedLogEntry>>>,
-            tokio::task::JoinHandle<()>,
-        )> = None;
-
-        use std::process::Stdio;
-        let mut cmd = match runtime {
-            JsRuntime::Bun(ref bun_path) => {
-                info!(
-                    "Executing workflow with bun: {}/{}",
-                    execution_dir.display(),
-                    self.entry_file
-                );
-                let mut cmd = tokio::process::Command::new(bun_path);
-                cmd.current_dir(&execution_dir)
-                    .arg("--eval")
-                    .arg(&exec_script)
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::piped());
-                cmd
-            }
-            JsRuntime::Node => {
-                info!(
-                    "Executing workflow with node: {}/{}",
-                    execution_dir.display(),
-                    self.entry_file
-                );
-                let mut cmd = tokio::process::Command::new("node");
-                cmd.current_dir(&execution_dir)
-                    .arg("--import")
-                    .arg("tsx/esm")
-                    .arg("--eval")
-                    .arg(&exec_script)
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::piped());
-                cmd
-            }
-        };
+        match runtime {
+            JsRuntime::Bun(_) => info!(
+                "Executing workflow with bun: {}/{}",
+                execution_dir.display(),
+                self.entry_file
+            ),
+            JsRuntime::Node => info!(
+                "Executing workflow with node: {}/{}",
+                execution_dir.display(),
+                self.entry_file
+            ),
+        }
+        let mut cmd = runtime_command(&runtime, &execution_dir, &exec_script);
 
         // Set the pipe path environment variables if we have pipe servers
         #[cfg(windows)]
@@ -887,6 +920,7 @@ impl TypeScriptWorkflow {
                 None
             };
             // Suppress unused variable warning
+            #[cfg(windows)]
             let _ = &log_pipe_handle;
 
             // Sort logs by timestamp
@@ -1205,12 +1239,13 @@ try {{
     const workflow = workflowModule.default || workflowModule.bestPlanProWorkflow || workflowModule;
 
     // Check if we're just getting metadata
-    if (process.argv.includes('--get-metadata')) {{
+    if (process.argv.includes('--get-metadata') || process.env.TERMINATOR_GET_METADATA === '1') {{
         const metadata = workflow.getMetadata ? workflow.getMetadata() : {{
             name: workflow.config?.name || 'Unknown',
             version: workflow.config?.version || '1.0.0',
             description: workflow.config?.description || '',
-            steps: workflow.steps || []
+            steps: workflow.steps || [],
+            trigger: workflow.config?.trigger
         }};
         originalLog(JSON.stringify({{ metadata }}, null, 2));
         if (parentCheckInterval) clearInterval(parentCheckInterval);
@@ -1434,7 +1469,9 @@ pub struct WorkflowMetadata {
     pub name: String,
     pub description: Option<String>,
     pub version: Option<String>,
+    #[serde(default)]
     pub input: Value,
+    #[serde(default)]
     pub steps: Vec<StepMetadata>,
     /// Trigger configuration for the workflow
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -0,0 +1,35 @@
+//! Runs `execute_sequence` on an in-process server over an in-memory pipe,
+//! shared by the integration tests that drive the mock desktop.
+#![allow(dead_code)]
+
+use rmcp::model::CallToolRequestParam;
+use rmcp::ServiceExt;
+use serde_json::Value;
+use terminator_mcp_agent::server::DesktopWrapper;
+
+/// Calls `execute_sequence` and returns the summary, or the error message
+pub async fn execute(arguments: Value) -> Result<Value, String> {
+    execute_with(DesktopWrapper::new().unwrap(), arguments).await
+}
+
+/// Like [`execute`], on a server the test has configured
+pub async fn execute_with(server: DesktopWrapper, arguments: Value) -> Result<Value, String> {
+    let (server_io, client_io) = tokio::io::duplex(1 << 20);
+    tokio::spawn(async move {
+        let running = server.serve(tokio::io::split(server_io)).await.unwrap();
+        let _ = running.waiting().await;
+    });
+    let client = ().serve(tokio::io::split(client_io)).await.unwrap();
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "execute_sequence".into(),
+            arguments: arguments.as_object().cloned(),
+        })
+        .await;
+    let _ = client.cancel().await;
+
+    let result = result.map_err(|e| e.to_string())?;
+    let text = &result.content[0].as_text().unwrap().text;
+    Ok(serde_json::from_str(text).unwrap())
+}
@@ -0,0 +1,266 @@
+//! `for_each`, `while` and parallel group steps in `execute_sequence`,
+//! against the in-memory mock desktop.
+//!
+//! Run with: cargo test -p terminator-mcp-agent --features mock --test control_flow_tests
+#![cfg(feature = "mock")]
+
+mod common;
+
+use common::execute;
+use serde_json::{json, Value};
+
+fn step<'a>(summary: &'a Value, id: &str) -> &'a Value {
+    summary["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["step_id"] == id)
+        .unwrap_or_else(|| panic!("no result for {id}: {summary}"))
+}
+
+#[tokio::test]
+async fn test_for_each_runs_once_per_item() {
+    let summary = execute(json!({
+        "inputs": {"rows": [{"wait": 1}, {"wait": 2}, {"wait": 3}], "none": []},
+        "steps": [
+            {
+                "id": "wait",
+                "for_each": "rows",
+                "as": "row",
+                "tool_name": "delay",
+                "arguments": {"delay_ms": "{{ row.wait }}"},
+            },
+            {"id": "empty", "for_each": "none", "tool_name": "delay", "arguments": {"delay_ms": 1}},
+            {"id": "after", "tool_name": "delay", "arguments": {"delay_ms": "{{ wait_results | length }}"}},
+        ],
+    }))
+    .await
+    .unwrap();
+
+    let wait = step(&summary, "wait");
+    assert_eq!(wait["status"], "executed_without_error");
+    assert_eq!(wait["iterations"], 3);
+    assert_eq!(wait["results"].as_array().unwrap().len(), 3);
+    assert_eq!(summary["env"]["wait_results"].as_array().unwrap().len(), 3);
+    assert_eq!(summary["env"]["wait_status"], "executed_without_error");
+    // The loop variables are gone once the loop ends
+    assert!(summary["env"].get("row").is_none());
+
+    let empty = step(&summary, "empty");
+    assert_eq!(empty["iterations"], 0);
+    assert_eq!(empty["executed"], false);
+    assert_eq!(step(&summary, "after")["status"], "executed_without_error");
+}
+
+#[tokio::test]
+async fn test_while_loop_is_guarded() {
+    let summary = execute(json!({
+        "steps": [
+            {"id": "twice", "while": "loop.index0 < 2", "tool_name": "delay", "arguments": {"delay_ms": 1}},
+            {"id": "forever", "while": "true", "max_iterations": 3, "tool_name": "delay", "arguments": {"delay_ms": 1}},
+            {"id": "after", "tool_name": "delay", "arguments": {"delay_ms": 1}},
+        ],
+    }))
+    .await
+    .unwrap();
+
+    assert_eq!(step(&summary, "twice")["iterations"], 2);
+    let forever = step(&summary, "forever");
+    assert_eq!(forever["status"], "executed_with_error");
+    assert_eq!(forever["iterations"], 3);
+    assert!(
+        forever["error"]
+            .as_str()
+            .unwrap()
+            .contains("after 3 iterations"),
+        "{forever}"
+    );
+    let results = summary["results"].as_array().unwrap();
+    assert_eq!(results.last().unwrap()["status"], "skipped");
+}
+
+#[tokio::test]
+async fn test_parallel_group_runs_file_tools() {
+    let dir = std::env::temp_dir().join(format!("terminator_parallel_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let dir_str = dir.to_string_lossy().to_string();
+
+    let summary = execute(json!({
+        "inputs": {"dir": dir_str, "names": ["a", "b"]},
+        "steps": [
+            {
+                "group_name": "write both",
+                "parallel": true,
+                "steps": [
+                    {"id": "write_a", "tool_name": "write_file", "arguments": {"path": "{{ dir }}/a.txt", "content": "A"}},
+                    {"id": "write_b", "tool_name": "write_file", "arguments": {"path": "{{ dir }}/b.txt", "content": "B"}},
+                ],
+            },
+            {
+                "id": "read_each",
+                "for_each": "names",
+                "as": "name",
+                "group_name": "read",
+                "steps": [
+                    {"tool_name": "read_file", "arguments": {"path": "{{ dir }}/{{ name }}.txt"}},
+                ],
+            },
+        ],
+    }))
+    .await
+    .unwrap();
+
+    let group = &summary["results"][0];
+    assert_eq!(group["status"], "executed_without_error", "{group}");
+    assert_eq!(group["results"].as_array().unwrap().len(), 2);
+    assert_eq!(summary["env"]["write_a_status"], "executed_without_error");
+    assert_eq!(summary["env"]["write_b_status"], "executed_without_error");
+    assert_eq!(std::fs::read_to_string(dir.join("b.txt")).unwrap(), "B");
+
+    let read_each = step(&summary, "read_each");
+    assert_eq!(read_each["iterations"], 2, "{read_each}");
+    assert_eq!(read_each["status"], "executed_without_error");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_parallel_group_rejects_ui_tools() {
+    let error = execute(json!({
+        "steps": [{
+            "group_name": "ui",
+            "parallel": true,
+            "steps": [{"tool_name": "click_element", "arguments": {"selector": "role:Button"}}],
+        }],
+    }))
+    .await
+    .unwrap_err();
+    assert!(
+        error.contains("'click_element' can't run in a parallel group"),
+        "{error}"
+    );
+}
+
+#[tokio::test]
+async fn test_failing_if_follows_fallback_and_continue_on_error() {
+    let summary = execute(json!({
+        "inputs": {"n": 0},
+        "steps": [
+            {"id": "lenient", "if": "1 / n > 0", "continue_on_error": true, "tool_name": "delay", "arguments": {"delay_ms": 1}},
+            {"id": "next", "tool_name": "delay", "arguments": {"delay_ms": 1}},
+            {"id": "guarded", "if": "1 / n > 0", "fallback_id": "recover", "tool_name": "delay", "arguments": {"delay_ms": 1}},
+        ],
+        "troubleshooting": [
+            {"id": "recover", "tool_name": "delay", "arguments": {"delay_ms": 1}},
+        ],
+    }))
+    .await
+    .unwrap();
+
+    let results = summary["results"].as_array().unwrap();
+    let statuses: Vec<_> = results.iter().map(|r| r["status"].clone()).collect();
+    assert_eq!(
+        statuses,
+        [
+            "error",
+            "executed_without_error",
+            "error",
+            "executed_without_error"
+        ],
+        "{summary}"
+    );
+    assert!(results[0]["error"].as_str().unwrap().contains("by zero"));
+    assert_eq!(
+        step(&summary, "recover")["status"],
+        "executed_without_error"
+    );
+}
+
+#[tokio::test]
+async fn test_failing_jump_condition_is_reported_and_routed() {
+    let summary = execute(json!({
+        "inputs": {"n": 0},
+        "steps": [
+            {
+                "id": "lenient",
+                "continue_on_error": true,
+                "jumps": [{"if": "1 / n > 0", "to_id": "guarded"}],
+                "tool_name": "delay",
+                "arguments": {"delay_ms": 1},
+            },
+            {"id": "next", "tool_name": "delay", "arguments": {"delay_ms": 1}},
+            {
+                "id": "guarded",
+                "fallback_id": "recover",
+                "jumps": [{"if": "false", "to_id": "next"}, {"if": "1 / n > 0", "to_id": "next"}],
+                "tool_name": "delay",
+                "arguments": {"delay_ms": 1},
+            },
+            {"id": "unreached", "tool_name": "delay", "arguments": {"delay_ms": 1}},
+        ],
+        "troubleshooting": [
+            {"id": "recover", "tool_name": "delay", "arguments": {"delay_ms": 1}},
+        ],
+    }))
+    .await
+    .unwrap();
+
+    let results = summary["results"].as_array().unwrap();
+    let statuses: Vec<_> = results.iter().map(|r| r["status"].clone()).collect();
+    assert_eq!(
+        statuses,
+        [
+            "executed_without_error",
+            "error",
+            "executed_without_error",
+            "executed_without_error",
+            "error",
+            "executed_without_error"
+        ],
+        "{summary}"
+    );
+    assert_eq!(results[1]["index"], 0);
+    assert_eq!(results[1]["jump_index"], 0);
+    assert!(results[1]["error"].as_str().unwrap().contains("by zero"));
+    assert_eq!(results[4]["index"], 2);
+    assert_eq!(results[4]["jump_index"], 1);
+    assert_eq!(
+        step(&summary, "recover")["status"],
+        "executed_without_error"
+    );
+    assert_eq!(summary["status"], "executed_with_error");
+}
+
+#[tokio::test]
+async fn test_loops_are_validated_and_routed_on_failure() {
+    let error = execute(json!({
+        "steps": [
+            {"id": "typo", "while": "loop.index0 <", "tool_name": "delay", "arguments": {"delay_ms": 1}},
+        ],
+    }))
+    .await
+    .unwrap_err();
+    assert!(error.contains("Invalid loop at steps[0].while"), "{error}");
+
+    // `for_each` over a number can't start; the step is routed like a failure
+    let summary = execute(json!({
+        "inputs": {"n": 3},
+        "steps": [
+            {"id": "lenient", "for_each": "n", "continue_on_error": true, "tool_name": "delay", "arguments": {"delay_ms": 1}},
+            {"id": "next", "tool_name": "delay", "arguments": {"delay_ms": 1}},
+            {"id": "guarded", "for_each": "n", "fallback_id": "recover", "tool_name": "delay", "arguments": {"delay_ms": 1}},
+        ],
+        "troubleshooting": [
+            {"id": "recover", "tool_name": "delay", "arguments": {"delay_ms": 1}},
+        ],
+    }))
+    .await
+    .unwrap();
+
+    let results = summary["results"].as_array().unwrap();
+    assert_eq!(results[0]["status"], "error", "{summary}");
+    assert_eq!(step(&summary, "next")["status"], "executed_without_error");
+    assert_eq!(
+        step(&summary, "recover")["status"],
+        "executed_without_error"
+    );
+}
@@ -1,5 +1,7 @@
 use serde_json::json;
-use terminator_mcp_agent::expression_eval::evaluate;
+use terminator_mcp_agent::expression_eval::{
+    evaluate, evaluate_value, parse, try_evaluate, ExprError,
+};
 
 #[test]
 fn test_evaluate_binary_expressions() {
@@ -579,3 +581,124 @@ fn test_null_in_numeric_comparisons() {
     assert!(evaluate("null_value <= 0", &vars));
     assert!(evaluate("null_value < 1", &vars));
 }
+
+#[test]
+fn test_grouping_and_precedence() {
+    let vars = json!({ "a": true, "b": false, "c": false });
+
+    // `&&` binds tighter than `||`, wherever the operators appear
+    assert!(evaluate("b && c || a", &vars));
+    assert!(evaluate("a || b && c", &vars));
+    assert!(!evaluate("(a || b) && c", &vars));
+    assert!(evaluate("!(b || c) && a", &vars));
+    assert!(evaluate("!b && !c", &vars));
+}
+
+#[test]
+fn test_arithmetic() {
+    let vars = json!({ "count": 7, "price": "2.5", "name": "report" });
+
+    assert_eq!(evaluate_value("count + 3 * 2", &vars), Ok(json!(13)));
+    assert_eq!(evaluate_value("(count + 3) * 2", &vars), Ok(json!(20)));
+    assert_eq!(evaluate_value("count % 4 - -1", &vars), Ok(json!(4)));
+    assert_eq!(evaluate_value("price * 2", &vars), Ok(json!(5)));
+    assert_eq!(evaluate_value("count / 2", &vars), Ok(json!(3.5)));
+    assert_eq!(
+        evaluate_value("name + '_' + count", &vars),
+        Ok(json!("report_7"))
+    );
+    assert!(evaluate("count - 1 >= 6", &vars));
+    assert!(matches!(
+        evaluate_value("count / 0", &vars),
+        Err(ExprError::Type(_))
+    ));
+}
+
+#[test]
+fn test_indexing_and_member_access() {
+    let vars = json!({
+        "steps": [{ "status": "success" }, { "status": "error" }],
+        "rows": [[1, 2], [3, 4]],
+        "data": { "first name": "Ada", "items": ["x", "y"] },
+        "login-step_status": "success",
+        "i": 1
+    });
+
+    assert!(evaluate("steps[0].status == 'success'", &vars));
+    assert!(evaluate("steps[i].status == 'error'", &vars));
+    assert!(evaluate("steps[-1].status == 'error'", &vars));
+    assert_eq!(evaluate_value("rows[1][0]", &vars), Ok(json!(3)));
+    assert_eq!(
+        evaluate_value("data['first name']", &vars),
+        Ok(json!("Ada"))
+    );
+    assert_eq!(evaluate_value("data.items.1", &vars), Ok(json!("y")));
+    assert!(evaluate("login-step_status == 'success'", &vars));
+
+    // Missing elements are undefined rather than errors
+    assert!(!evaluate("steps[5].status == 'success'", &vars));
+    assert!(evaluate("steps[5].status != 'success'", &vars));
+    assert_eq!(evaluate_value("missing.deeply[0]", &vars), Ok(json!(null)));
+}
+
+#[test]
+fn test_len_matches_and_in() {
+    let vars = json!({
+        "items": ["FEX", "Term"],
+        "invoice": "INV-2024-0042",
+        "config": { "mode": "fast" },
+        "empty": ""
+    });
+
+    assert!(evaluate("len(items) == 2", &vars));
+    assert!(evaluate("len(invoice) > 10 && len(empty) == 0", &vars));
+    assert!(evaluate("len(missing) == 0", &vars));
+    assert!(evaluate(r"matches(invoice, '^INV-\d{4}-\d+$')", &vars));
+    assert!(!evaluate("matches(invoice, '^PO-')", &vars));
+    assert!(evaluate(r#"'it\'s' == "it's""#, &vars));
+    assert!(evaluate("'FEX' in items", &vars));
+    assert!(!evaluate("'MedSup' in items", &vars));
+    assert!(evaluate("'2024' in invoice", &vars));
+    assert!(evaluate("'mode' in config", &vars));
+    assert!(evaluate("config.mode in ['fast', 'slow']", &vars));
+    assert!(!evaluate("missing in items", &vars));
+}
+
+#[test]
+fn test_malformed_expressions_are_errors() {
+    let vars = json!({ "a": 1 });
+
+    for expression in [
+        "a ==",
+        "(a == 1",
+        "a == 1)",
+        "a == 'unterminated",
+        "invalid expression",
+        "a = 1",
+        "steps[0",
+    ] {
+        assert!(
+            matches!(parse(expression), Err(ExprError::Syntax { .. })),
+            "{expression} should not parse"
+        );
+        assert!(try_evaluate(expression, &vars).is_err());
+    }
+
+    assert_eq!(
+        parse("a ==").unwrap_err().to_string(),
+        "expected a value, found end of expression at position 4"
+    );
+    assert!(matches!(
+        parse("unsupported(a, b)"),
+        Err(ExprError::Function { name, .. }) if name == "unsupported"
+    ));
+    assert!(matches!(
+        parse("len(a, a)"),
+        Err(ExprError::Function { .. })
+    ));
+    assert!(matches!(parse("matches(a, '(')"), Err(ExprError::Regex(_))));
+    assert!(matches!(
+        try_evaluate("len(a)", &vars),
+        Err(ExprError::Type(_))
+    ));
+}
@@ -0,0 +1,162 @@
+//! Replays a recorded workflow run through `execute_sequence` against the
+//! in-memory mock desktop.
+//!
+//! Run with: cargo test -p terminator-mcp-agent --features mock --test replay_tests
+#![cfg(feature = "mock")]
+
+mod common;
+
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use terminator_mcp_agent::replay::Replay;
+use terminator_mcp_agent::server::DesktopWrapper;
+
+fn write_log(dir: &Path, second: u32, step_id: Option<&str>, tool_name: &str, response: Value) {
+    let mut log = json!({
+        "timestamp": format!("2025-01-01T10:00:{second:02}+00:00"),
+        "workflow_id": "replay",
+        "tool_name": tool_name,
+        "request": {},
+        "response": response,
+    });
+    if let Some(step_id) = step_id {
+        log["step_id"] = json!(step_id);
+    }
+    let stem = format!(
+        "20250101_1000{second:02}_replay_{}_{tool_name}",
+        step_id.unwrap_or("full")
+    );
+    std::fs::write(dir.join(format!("{stem}.json")), log.to_string()).unwrap();
+}
+
+fn ok(result: Value) -> Value {
+    json!({"status": "executed_without_error", "duration_ms": 5, "result": {"content": [result]}})
+}
+
+fn failed(message: &str) -> Value {
+    json!({
+        "status": "executed_with_error",
+        "duration_ms": 5,
+        "error": json!({"code": -32603, "message": message}).to_string(),
+    })
+}
+
+/// A run that jumped over `unused`, skipped `greet` through its `if`, retried
+/// `save` and recovered from it through its fallback
+fn recorded_run(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("terminator_replay_{name}_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let workflow = json!({
+        "inputs": {"user": "alice"},
+        "steps": [
+            {"id": "open", "tool_name": "open_application", "arguments": {"app_name": "notepad"},
+             "jumps": [{"if": "open_status == 'executed_without_error'", "to_id": "login"}]},
+            {"id": "unused", "tool_name": "press_key", "arguments": {"key": "{Esc}"}},
+            {"id": "login", "tool_name": "click_element",
+             "arguments": {"selector": "role:Button|name:Login"}},
+            {"id": "greet", "if": "user == 'bob'", "tool_name": "click_element",
+             "arguments": {"selector": "role:Button|name:Hi"}},
+            {"id": "save", "retries": 1, "fallback_id": "recover", "tool_name": "click_element",
+             "arguments": {"selector": "role:Button|name:Save"}},
+        ],
+        "troubleshooting": [
+            {"id": "recover", "tool_name": "press_key", "arguments": {"key": "{Ctrl}s"}},
+        ],
+    });
+    write_log(
+        &dir,
+        0,
+        None,
+        "execute_sequence",
+        json!({"status": "executed_with_error", "duration_ms": 9000}),
+    );
+    // The recorded request is the workflow being replayed
+    let sequence_log = dir.join("20250101_100000_replay_full_execute_sequence.json");
+    let mut log: Value =
+        serde_json::from_str(&std::fs::read_to_string(&sequence_log).unwrap()).unwrap();
+    log["request"] = workflow;
+    std::fs::write(&sequence_log, log.to_string()).unwrap();
+
+    write_log(
+        &dir,
+        1,
+        Some("open"),
+        "open_application",
+        ok(json!({"status": "success"})),
+    );
+    write_log(
+        &dir,
+        2,
+        Some("login"),
+        "click_element",
+        ok(json!({"action": "click"})),
+    );
+    write_log(
+        &dir,
+        3,
+        Some("save"),
+        "click_element",
+        failed("Save is disabled"),
+    );
+    write_log(
+        &dir,
+        4,
+        Some("save"),
+        "click_element",
+        failed("Save is disabled"),
+    );
+    write_log(
+        &dir,
+        5,
+        Some("recover"),
+        "press_key",
+        ok(json!({"action": "press_key"})),
+    );
+    dir
+}
+
+async fn replay_sequence(replay: Arc<Replay>, arguments: Value) -> Value {
+    let mut server = DesktopWrapper::new().unwrap();
+    server.replay = Some(replay);
+    common::execute_with(server, arguments).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_replay_reproduces_recorded_control_flow() {
+    let dir = recorded_run("same");
+    let replay = Arc::new(Replay::from_dir(&dir, None).unwrap());
+    let workflow = replay.sequence_request().unwrap().clone();
+
+    let summary = replay_sequence(replay.clone(), workflow).await;
+
+    assert_eq!(summary["used_fallback"], json!(true));
+    assert_eq!(summary["replay"]["recorded"], json!(5));
+    assert_eq!(summary["replay"]["replayed"], json!(5));
+    assert_eq!(summary["replay"]["divergences"], json!([]));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_replay_flags_changed_step_order() {
+    let dir = recorded_run("changed");
+    let replay = Arc::new(Replay::from_dir(&dir, None).unwrap());
+    let mut workflow = replay.sequence_request().unwrap().clone();
+    // Without its condition `greet` now runs, which the recording never saw
+    workflow["steps"][3].as_object_mut().unwrap().remove("if");
+
+    let summary = replay_sequence(replay.clone(), workflow).await;
+
+    let divergences = summary["replay"]["divergences"].as_array().unwrap();
+    assert_eq!(
+        divergences[0],
+        json!({
+            "kind": "unrecorded",
+            "position": 2,
+            "actual": {"step_id": "greet", "tool_name": "click_element"},
+        })
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,81 @@
+//! `{{ }}` templates in `execute_sequence` step arguments, against the
+//! in-memory mock desktop.
+//!
+//! Run with: cargo test -p terminator-mcp-agent --features mock --test template_tests
+#![cfg(feature = "mock")]
+
+mod common;
+
+use common::execute;
+use serde_json::{json, Value};
+
+fn workflow(strict: Option<bool>) -> Value {
+    let mut workflow = json!({
+        "inputs": {"waits": [1, 2]},
+        "steps": [
+            {"id": "typed", "tool_name": "delay", "arguments": {"delay_ms": "{{ waits | first }}"}},
+            {"id": "unresolved", "tool_name": "delay", "arguments": {"delay_ms": 1, "note": "{{ missing | lower }}"}},
+            {"id": "after", "tool_name": "delay", "arguments": {"delay_ms": "{{ waits[1] * 2 }}"}},
+        ],
+    });
+    if let Some(strict) = strict {
+        workflow["strict_templates"] = json!(strict);
+    }
+    workflow
+}
+
+fn step<'a>(summary: &'a Value, id: &str) -> Option<&'a Value> {
+    summary["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["step_id"] == id)
+}
+
+#[tokio::test]
+async fn test_unresolved_placeholders_are_reported_per_step() {
+    let summary = execute(workflow(None)).await.unwrap();
+
+    assert_eq!(
+        step(&summary, "typed").unwrap()["status"],
+        "executed_without_error"
+    );
+    let unresolved = step(&summary, "unresolved").unwrap();
+    assert_eq!(unresolved["status"], "executed_without_error");
+    assert_eq!(
+        unresolved["unresolved_variables"],
+        json!([{"placeholder": "{{ missing | lower }}", "message": "undefined variable `missing`"}])
+    );
+    let after = step(&summary, "after").unwrap();
+    assert_eq!(after["status"], "executed_without_error");
+    assert!(after.get("unresolved_variables").is_none());
+}
+
+#[tokio::test]
+async fn test_strict_templates_fail_the_step() {
+    let summary = execute(workflow(Some(true))).await.unwrap();
+
+    let unresolved = step(&summary, "unresolved").unwrap();
+    assert_eq!(unresolved["status"], "error");
+    assert!(
+        unresolved["error"]
+            .as_str()
+            .unwrap()
+            .contains("{{ missing | lower }}: undefined variable `missing`"),
+        "{unresolved}"
+    );
+    assert!(
+        step(&summary, "after").is_none(),
+        "sequence stops on the failed step"
+    );
+
+    // A step can opt out of the sequence-level setting
+    let mut lenient = workflow(Some(true));
+    lenient["steps"][1]["strict_templates"] = json!(false);
+    let summary = execute(lenient).await.unwrap();
+    assert_eq!(
+        step(&summary, "unresolved").unwrap()["status"],
+        "executed_without_error"
+    );
+    assert!(step(&summary, "after").is_some());
+}
@@ -110,6 +110,8 @@ mod scripts_base_path_tests {
             r#continue: Some(false),
             verbosity: Some("normal".to_string()),
             start_from_step: Some("step1".to_string()),
+            from_run_id: None,
+            strict_templates: None,
             end_at_step: Some("step5".to_string()),
             follow_fallback: Some(false),
             scripts_base_path: Some("/custom/path".to_string()),
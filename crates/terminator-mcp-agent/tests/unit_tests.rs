@@ -27,6 +27,8 @@ fn test_execute_sequence_args_serialization() {
         inputs: None,
         selectors: None,
         start_from_step: None,
+        from_run_id: None,
+        strict_templates: None,
         follow_fallback: None,
         end_at_step: None,
         troubleshooting: None,
@@ -43,6 +45,28 @@ fn test_execute_sequence_args_serialization() {
     assert!(json.contains("click_element"));
 }
 
+#[test]
+fn test_sequence_step_expect_deserialization() {
+    let step: SequenceStep = serde_json::from_value(json!({
+        "tool_name": "click_element",
+        "arguments": {"selector": "role:Button && name:Save"},
+        "fallback_id": "retry_save",
+        "expect": [
+            {"appears": "role:Dialog && name:Save As", "timeout_ms": 5000},
+            {"value_changes": "nativeid:total", "to": "/\\d+\\.\\d\\d/"},
+            {"no_new": "role:Dialog && name:Error"}
+        ]
+    }))
+    .unwrap();
+
+    let expect = step.expect.unwrap();
+    assert_eq!(expect.len(), 3);
+    assert!(expect.iter().all(|a| a.validate().is_ok()));
+    assert_eq!(expect[0].timeout_ms, Some(5000));
+    assert_eq!(expect[1].to.as_deref(), Some(r"/\d+\.\d\d/"));
+    assert_eq!(expect[2].to_string(), "no new `role:Dialog && name:Error`");
+}
+
 #[test]
 fn test_execute_sequence_args_deserialization() {
     let json = r#"{
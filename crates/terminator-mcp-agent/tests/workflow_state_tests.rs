@@ -0,0 +1,111 @@
+//! Per-run workflow state persisted by `execute_sequence`, against the
+//! in-memory mock desktop.
+//!
+//! Run with: cargo test -p terminator-mcp-agent --features mock --test workflow_state_tests
+#![cfg(feature = "mock")]
+
+mod common;
+
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+use terminator_mcp_agent::server::DesktopWrapper;
+use terminator_mcp_agent::workflow_state::{FileStateStore, WorkflowStateStore};
+
+fn state_root(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!(
+        "terminator_state_seq_{name}_{}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&root);
+    root
+}
+
+fn workflow(run_id: &str, user: &str) -> Value {
+    json!({
+        "workflow_id": "checkout",
+        "execution_id": run_id,
+        "inputs": {"user": user},
+        "steps": [
+            {"id": "first", "tool_name": "delay", "arguments": {"delay_ms": 1}},
+            {"id": "second", "tool_name": "delay", "arguments": {"delay_ms": 1}},
+        ],
+    })
+}
+
+/// Calls `execute_sequence` on a server that saves state to `store`
+async fn execute(store: Arc<FileStateStore>, arguments: Value) -> Result<Value, String> {
+    let mut server = DesktopWrapper::new().unwrap();
+    server.state_store = Some(store);
+    common::execute_with(server, arguments).await
+}
+
+#[tokio::test]
+async fn test_parallel_runs_keep_separate_state() {
+    let root = state_root("parallel");
+    let store = Arc::new(FileStateStore::new(&root));
+
+    let (a, b) = tokio::join!(
+        execute(store.clone(), workflow("run-a", "alice")),
+        execute(store.clone(), workflow("run-b", "bob")),
+    );
+    a.unwrap();
+    b.unwrap();
+
+    for (run, user) in [("run-a", "alice"), ("run-b", "bob")] {
+        let history = store.history("checkout", run).unwrap();
+        assert_eq!(history.len(), 2, "one snapshot per step of {run}");
+        assert!(history.iter().all(|s| s.env["user"] == json!(user)));
+        assert_eq!(history[1].step_id.as_deref(), Some("second"));
+    }
+    assert_eq!(store.list_runs("checkout").unwrap().len(), 2);
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn test_start_from_step_resumes_a_chosen_run() {
+    let root = state_root("resume");
+    let store = Arc::new(FileStateStore::new(&root));
+    execute(store.clone(), workflow("run-a", "alice"))
+        .await
+        .unwrap();
+    execute(store.clone(), workflow("run-b", "bob"))
+        .await
+        .unwrap();
+
+    let mut resumed = workflow("run-c", "carol");
+    resumed["start_from_step"] = json!("second");
+    resumed["from_run_id"] = json!("run-a");
+    execute(store.clone(), resumed).await.unwrap();
+
+    // The new run starts from run-a's saved env rather than the latest run's,
+    // as it was before "second" ran
+    let history = store.history("checkout", "run-c").unwrap();
+    assert_eq!(history[0].env["user"], json!("alice"));
+    assert_eq!(history[0].step_id.as_deref(), Some("first"));
+    assert_eq!(history.last().unwrap().env["user"], json!("alice"));
+
+    let mut unknown = workflow("run-d", "dave");
+    unknown["start_from_step"] = json!("second");
+    unknown["from_run_id"] = json!("missing");
+    let error = execute(store.clone(), unknown).await.unwrap_err();
+    assert!(
+        error.contains("No saved state for run 'missing'"),
+        "{error}"
+    );
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn test_run_in_progress_is_locked() {
+    let root = state_root("locked");
+    let store = Arc::new(FileStateStore::new(&root));
+    let _lock = store.lock_run("checkout", "run-a").unwrap();
+
+    let error = execute(store.clone(), workflow("run-a", "alice"))
+        .await
+        .unwrap_err();
+    assert!(error.contains("already in progress"), "{error}");
+    assert!(store.history("checkout", "run-a").unwrap().is_empty());
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,1243 @@
+//! Conversion of recorded workflows into `execute_sequence` steps
+//!
+//! The recorder captures low-level input alongside high-level events. The
+//! converter keeps the high-level events, folds the keystroke noise into
+//! `type_into_element` calls and picks the most robust selector for every
+//! action, so a recording can be replayed by the MCP agent.
+
+use crate::events::{
+    ApplicationSwitchMethod, BrowserClickEvent, BrowserTextInputEvent, McpToolStep, MouseButton,
+    RecordedWorkflow, SelectorCandidate, SerializableApplicationSwitchEvent,
+    SerializableBrowserTabNavigationEvent, SerializableClickEvent, SerializableHotkeyEvent,
+    SerializableKeyboardEvent, SerializableRecordedEvent, SerializableRecordedWorkflow,
+    SerializableTextInputCompletedEvent, SerializableWorkflowEvent, TabAction, TabNavigationMethod,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use terminator::{KeySequence, Selector};
+
+/// Pauses shorter than this are not worth replaying
+const MIN_DELAY_MS: u64 = 1000;
+/// A navigation this soon after a click is treated as the click's effect
+const NAVIGATION_AFTER_CLICK_MS: u64 = 2000;
+/// A hotkey this soon before a switch or tab navigation caused it
+const HOTKEY_EFFECT_MS: u64 = 1500;
+/// Window in which a DOM text input duplicates a UI Automation one
+const DUPLICATE_INPUT_MS: u64 = 2000;
+/// A `BrowserClick` is merged with a `Click` recorded this soon after it
+const BROWSER_CLICK_MERGE_MS: u64 = 1000;
+/// DOM selectors scoring at least this are preferred over UI Automation ones
+const ROBUST_DOM_SCORE: i64 = 80;
+
+// Windows virtual-key codes of the keys replayed individually
+const VK_BACK: u32 = 0x08;
+const VK_TAB: u32 = 0x09;
+const VK_RETURN: u32 = 0x0D;
+const VK_ESCAPE: u32 = 0x1B;
+const VK_SPACE: u32 = 0x20;
+
+/// Options controlling how a recording is converted
+#[derive(Debug, Clone)]
+pub struct ConversionOptions {
+    /// Replay the pauses the user made between actions as `delay_ms`
+    pub preserve_timing: bool,
+    /// Upper bound for a replayed pause
+    pub max_delay_ms: u64,
+    /// Attach `expected_ui_changes` derived from the recording
+    pub expected_ui_changes: bool,
+}
+
+impl Default for ConversionOptions {
+    fn default() -> Self {
+        Self {
+            preserve_timing: true,
+            max_delay_ms: 3000,
+            expected_ui_changes: true,
+        }
+    }
+}
+
+/// A recording converted into MCP tool steps
+#[derive(Debug, Clone, Serialize)]
+pub struct ConvertedWorkflow {
+    pub name: String,
+    pub steps: Vec<McpToolStep>,
+    /// Events that could not be converted faithfully
+    pub warnings: Vec<String>,
+}
+
+impl ConvertedWorkflow {
+    /// The arguments of an `execute_sequence` call running the steps
+    pub fn to_execute_sequence(&self) -> Value {
+        let steps: Vec<Value> = self
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| {
+                let mut value = json!({
+                    "id": format!("step_{}", i + 1),
+                    "tool_name": step.tool_name,
+                    "arguments": step.arguments,
+                });
+                if let Some(delay_ms) = step.delay_ms {
+                    value["delay_ms"] = json!(delay_ms);
+                }
+                if let Some(continue_on_error) = step.continue_on_error {
+                    value["continue_on_error"] = json!(continue_on_error);
+                }
+                if let Some(changes) = &step.expected_ui_changes {
+                    value["expected_ui_changes"] = json!(changes);
+                }
+                value
+            })
+            .collect();
+        json!({ "steps": steps })
+    }
+}
+
+impl RecordedWorkflow {
+    /// Convert the recording into `execute_sequence` steps
+    pub fn to_sequence(&self, options: &ConversionOptions) -> ConvertedWorkflow {
+        convert_workflow(&self.into(), options)
+    }
+}
+
+/// Convert a recording into `execute_sequence` steps
+pub fn convert_workflow(
+    workflow: &SerializableRecordedWorkflow,
+    options: &ConversionOptions,
+) -> ConvertedWorkflow {
+    let mut converter = Converter::new(workflow);
+    let events = &workflow.events;
+    for (i, recorded) in events.iter().enumerate() {
+        let timestamp = recorded.timestamp;
+        match &recorded.event {
+            SerializableWorkflowEvent::BrowserClick(click) => {
+                converter.flush_browser_click();
+                converter.browser_click = Some((timestamp, click.clone()));
+            }
+            SerializableWorkflowEvent::Click(click) => converter.click(timestamp, click),
+            SerializableWorkflowEvent::TextInputCompleted(input) => {
+                converter.flush_browser_click();
+                converter.text_input(timestamp, input);
+            }
+            SerializableWorkflowEvent::BrowserTextInput(input) => {
+                converter.flush_browser_click();
+                converter.browser_text_input(timestamp, input);
+            }
+            SerializableWorkflowEvent::Keyboard(key) if key.is_key_down => {
+                converter.flush_browser_click();
+                converter.key(timestamp, key);
+            }
+            SerializableWorkflowEvent::Hotkey(hotkey) => {
+                converter.flush_browser_click();
+                if !hotkey_has_recorded_effect(&events[i + 1..], timestamp) {
+                    converter.hotkey(timestamp, hotkey);
+                }
+            }
+            SerializableWorkflowEvent::ApplicationSwitch(switch) => {
+                converter.flush_browser_click();
+                converter.application_switch(timestamp, switch);
+            }
+            SerializableWorkflowEvent::BrowserTabNavigation(navigation) => {
+                converter.flush_browser_click();
+                converter.tab_navigation(timestamp, navigation);
+            }
+            SerializableWorkflowEvent::DragDrop(_) => {
+                converter.flush_browser_click();
+                converter.warn(timestamp, "drag and drop cannot be replayed, skipped");
+            }
+            // Raw mouse input is covered by `Click`, the rest has no replayable effect
+            _ => {}
+        }
+    }
+    converter.finish(options)
+}
+
+/// Whether the events right after a hotkey already replay its effect
+fn hotkey_has_recorded_effect(rest: &[SerializableRecordedEvent], timestamp: u64) -> bool {
+    rest.iter()
+        .take_while(|e| e.timestamp.saturating_sub(timestamp) <= HOTKEY_EFFECT_MS)
+        .any(|e| match &e.event {
+            SerializableWorkflowEvent::ApplicationSwitch(switch) => matches!(
+                switch.switch_method,
+                ApplicationSwitchMethod::AltTab | ApplicationSwitchMethod::WindowsKeyShortcut
+            ),
+            SerializableWorkflowEvent::BrowserTabNavigation(navigation) => {
+                navigation.method == TabNavigationMethod::KeyboardShortcut
+            }
+            _ => false,
+        })
+}
+
+/// A step with the recording context needed to finish it
+struct PendingStep {
+    step: McpToolStep,
+    /// When the action started and ended
+    start: u64,
+    end: u64,
+    window_title: Option<String>,
+    /// Browser process whose navigations this step may cause
+    browser: Option<String>,
+    changes: Vec<String>,
+}
+
+/// Printable keys typed outside of a recognised text field
+struct Typing {
+    text: String,
+    process: Option<String>,
+    selector: String,
+    window_title: Option<String>,
+    start: u64,
+    end: u64,
+}
+
+struct Converter {
+    name: String,
+    steps: Vec<PendingStep>,
+    warnings: Vec<String>,
+    start_time: u64,
+    /// Process of the last action, used by events that do not record one
+    process: Option<String>,
+    /// Selector of the last element clicked, where unattributed typing goes
+    last_target: Option<String>,
+    browser_click: Option<(u64, BrowserClickEvent)>,
+    typing: Option<Typing>,
+    /// Time spans covered by `TextInputCompleted` events
+    text_input_spans: Vec<(u64, u64)>,
+    /// Text and time of the last `TextInputCompleted`
+    last_text_input: Option<(u64, String)>,
+}
+
+impl Converter {
+    fn new(workflow: &SerializableRecordedWorkflow) -> Self {
+        let text_input_spans = workflow
+            .events
+            .iter()
+            .filter_map(|e| match &e.event {
+                SerializableWorkflowEvent::TextInputCompleted(input) => Some((
+                    e.timestamp
+                        .saturating_sub(input.typing_duration_ms)
+                        .saturating_sub(500),
+                    e.timestamp,
+                )),
+                _ => None,
+            })
+            .collect();
+        Self {
+            name: workflow.name.clone(),
+            steps: Vec::new(),
+            warnings: Vec::new(),
+            start_time: workflow.start_time,
+            process: None,
+            last_target: None,
+            browser_click: None,
+            typing: None,
+            text_input_spans,
+            last_text_input: None,
+        }
+    }
+
+    fn warn(&mut self, timestamp: u64, message: &str) {
+        let offset = timestamp.saturating_sub(self.start_time);
+        self.warnings.push(format!("+{offset}ms: {message}"));
+    }
+
+    fn push(&mut self, step: PendingStep) {
+        self.flush_typing();
+        self.steps.push(step);
+    }
+
+    fn step(
+        &self,
+        timestamp: u64,
+        tool_name: &str,
+        arguments: Value,
+        description: String,
+    ) -> PendingStep {
+        PendingStep {
+            step: McpToolStep {
+                tool_name: tool_name.to_string(),
+                arguments,
+                description,
+                timeout_ms: None,
+                continue_on_error: None,
+                delay_ms: None,
+                expected_ui_changes: None,
+                expected_dom_changes: None,
+            },
+            start: timestamp,
+            end: timestamp,
+            window_title: None,
+            browser: None,
+            changes: Vec::new(),
+        }
+    }
+
+    fn resolve_process(&mut self, process_name: Option<&str>) -> Option<String> {
+        if let Some(process) = process_name.and_then(process_from_executable) {
+            self.process = Some(process);
+        }
+        self.process.clone()
+    }
+
+    /// Emit a browser click that was not followed by a UI Automation click
+    fn flush_browser_click(&mut self) {
+        if let Some((timestamp, click)) = self.browser_click.take() {
+            self.click_with(timestamp, None, Some(&click));
+        }
+    }
+
+    fn click(&mut self, timestamp: u64, click: &SerializableClickEvent) {
+        let browser_click = self
+            .browser_click
+            .take()
+            .filter(|(at, _)| timestamp.saturating_sub(*at) <= BROWSER_CLICK_MERGE_MS);
+        self.click_with(
+            timestamp,
+            Some(click),
+            browser_click.as_ref().map(|(_, click)| click),
+        );
+    }
+
+    fn click_with(
+        &mut self,
+        timestamp: u64,
+        click: Option<&SerializableClickEvent>,
+        browser_click: Option<&BrowserClickEvent>,
+    ) {
+        let element = click.and_then(|c| c.metadata.ui_element.as_ref());
+        let process = self.resolve_process(click.and_then(|c| c.process_name.as_deref()));
+        let click_type = match browser_click {
+            Some(b) if b.is_double_click => "double",
+            Some(b) if b.button == MouseButton::Right => "right",
+            _ => "left",
+        };
+
+        let candidates = browser_click.map(dom_candidates).unwrap_or_default();
+        let best_dom = candidates
+            .first()
+            .filter(|c| candidate_score(c) >= ROBUST_DOM_SCORE);
+        let ui_selector = click.and_then(|c| {
+            let name = element
+                .and_then(|e| e.name.clone())
+                .unwrap_or_else(|| c.element_text.clone());
+            let role = element.map_or(c.element_role.as_str(), |e| e.role.as_str());
+            named_selector(role, &name)
+        });
+
+        // Any DOM selector beats coordinates
+        let dom_choice = best_dom.or_else(|| ui_selector.is_none().then(|| candidates.first())?);
+        let mut step = if let Some(candidate) = dom_choice {
+            let mut arguments = json!({
+                "script": dom_click_script(candidate, click_type),
+                "verify_element_exists": "",
+                "verify_element_not_exists": "",
+            });
+            if let Some(process) = &process {
+                arguments["process"] = json!(process);
+            }
+            self.step(
+                timestamp,
+                "execute_browser_script",
+                arguments,
+                format!("Click `{}` in the page", candidate.selector),
+            )
+        } else if let Some((selector, alternative)) = ui_selector {
+            let mut arguments = json!({
+                "selector": selector,
+                "alternative_selectors": alternative,
+                "click_type": click_type,
+                "verify_element_exists": "",
+                "verify_element_not_exists": "",
+            });
+            if let Some(process) = &process {
+                arguments["process"] = json!(process);
+            }
+            if let Some((x, y)) = click.and_then(|c| c.relative_position) {
+                // Clicks near the centre need no explicit position
+                if (x - 0.5).abs() > 0.1 || (y - 0.5).abs() > 0.1 {
+                    arguments["click_position"] = json!({
+                        "x_percentage": (x * 100.0).round() as u32,
+                        "y_percentage": (y * 100.0).round() as u32,
+                    });
+                }
+            }
+            self.last_target = Some(selector.clone());
+            self.step(
+                timestamp,
+                "click_element",
+                arguments,
+                format!("Click `{selector}`"),
+            )
+        } else if let Some(position) = click
+            .and_then(|c| c.click_position)
+            .or(browser_click.map(|b| b.position))
+        {
+            self.warn(
+                timestamp,
+                "click target has no usable selector, replaying screen coordinates",
+            );
+            let mut arguments = json!({
+                "x": position.x,
+                "y": position.y,
+                "click_type": click_type,
+                "verify_element_exists": "",
+                "verify_element_not_exists": "",
+            });
+            if let Some(process) = &process {
+                arguments["process"] = json!(process);
+            }
+            self.step(
+                timestamp,
+                "click_element",
+                arguments,
+                format!("Click at ({}, {})", position.x, position.y),
+            )
+        } else {
+            self.warn(timestamp, "click without a target or position, skipped");
+            return;
+        };
+
+        step.window_title = element.and_then(|e| e.window_title.clone());
+        if browser_click.is_some() || click.is_some_and(|c| c.page_url.is_some()) {
+            step.browser = process;
+        }
+        self.push(step);
+    }
+
+    fn text_input(&mut self, timestamp: u64, input: &SerializableTextInputCompletedEvent) {
+        let process = self.resolve_process(input.process_name.as_deref());
+        let element = input.metadata.ui_element.as_ref();
+        let role = element.map_or(input.field_type.as_str(), |e| e.role.as_str());
+        let name = input
+            .field_name
+            .clone()
+            .or_else(|| element.and_then(|e| e.name.clone()))
+            .unwrap_or_default();
+        let selector = match named_selector(role, &name) {
+            Some((selector, _)) => selector,
+            None => {
+                self.warn(
+                    timestamp,
+                    &format!("text field `{role}` has no name, the selector may be ambiguous"),
+                );
+                Selector::Role {
+                    role: role.to_string(),
+                    name: None,
+                }
+                .to_string()
+            }
+        };
+
+        let mut arguments = json!({
+            "selector": selector,
+            "text_to_type": input.text_value,
+            "clear_before_typing": true,
+        });
+        if let Some(process) = &process {
+            arguments["process"] = json!(process);
+        }
+        let mut step = self.step(
+            timestamp.saturating_sub(input.typing_duration_ms),
+            "type_into_element",
+            arguments,
+            format!("Type into `{selector}`"),
+        );
+        step.end = timestamp;
+        step.window_title = element.and_then(|e| e.window_title.clone());
+        step.changes
+            .push(value_change(role, &name, &input.text_value));
+        self.last_text_input = Some((timestamp, input.text_value.clone()));
+        self.push(step);
+    }
+
+    fn browser_text_input(&mut self, timestamp: u64, input: &BrowserTextInputEvent) {
+        let duplicate = self.last_text_input.as_ref().is_some_and(|(at, text)| {
+            timestamp.abs_diff(*at) <= DUPLICATE_INPUT_MS && *text == input.text
+        });
+        if duplicate {
+            return;
+        }
+        let selector = input
+            .dom_element
+            .as_ref()
+            .and_then(|dom| best_candidate(&dom.selector_candidates))
+            .map(|c| c.selector.clone())
+            .unwrap_or_else(|| input.selector.clone());
+        if selector.is_empty() {
+            self.warn(timestamp, "page text input without a selector, skipped");
+            return;
+        }
+        let candidate = SelectorCandidate {
+            selector: selector.clone(),
+            selector_type: String::new(),
+            specificity: 0,
+            requires_jquery: false,
+        };
+        let mut arguments = json!({
+            "script": dom_input_script(&candidate, &input.text),
+            "verify_element_exists": "",
+            "verify_element_not_exists": "",
+        });
+        if let Some(process) = &self.process {
+            arguments["process"] = json!(process);
+        }
+        let step = self.step(
+            timestamp,
+            "execute_browser_script",
+            arguments,
+            format!("Fill `{selector}` in the page"),
+        );
+        self.push(step);
+    }
+
+    fn key(&mut self, timestamp: u64, key: &SerializableKeyboardEvent) {
+        // Shortcuts arrive as `Hotkey` events and field input as `TextInputCompleted`
+        if key.ctrl_pressed || key.alt_pressed || key.win_pressed {
+            return;
+        }
+        if self
+            .text_input_spans
+            .iter()
+            .any(|(start, end)| (*start..=*end).contains(&timestamp))
+        {
+            return;
+        }
+
+        let special = match key.key_code {
+            VK_RETURN => Some("Enter"),
+            VK_TAB => Some("Tab"),
+            VK_ESCAPE => Some("Escape"),
+            _ => None,
+        };
+        if let Some(name) = special {
+            let Some(process) = self.process.clone() else {
+                self.warn(
+                    timestamp,
+                    &format!("{name} pressed before any window was known, skipped"),
+                );
+                return;
+            };
+            let window_title = self.typing.as_ref().and_then(|t| t.window_title.clone());
+            let mut step = self.step(
+                timestamp,
+                "press_key_global",
+                json!({
+                    "process": process,
+                    "key": format!("{{{name}}}"),
+                    "verify_element_exists": "",
+                    "verify_element_not_exists": "",
+                }),
+                format!("Press {name}"),
+            );
+            step.window_title = window_title;
+            self.push(step);
+            return;
+        }
+
+        if key.key_code == VK_BACK {
+            if let Some(typing) = &mut self.typing {
+                typing.text.pop();
+                typing.end = timestamp;
+            }
+            return;
+        }
+
+        let Some(character) = typed_character(key) else {
+            return;
+        };
+        let element = key.metadata.ui_element.as_ref();
+        let selector = element
+            .and_then(|e| named_selector(&e.role, e.name.as_deref().unwrap_or_default()))
+            .map(|(selector, _)| selector)
+            .or_else(|| self.last_target.clone());
+        let Some(selector) = selector else {
+            self.warn(timestamp, "typed text has no target element, skipped");
+            return;
+        };
+        if self
+            .typing
+            .as_ref()
+            .is_some_and(|typing| typing.selector != selector)
+        {
+            self.flush_typing();
+        }
+        let process = self.process.clone();
+        let typing = self.typing.get_or_insert_with(|| Typing {
+            text: String::new(),
+            process,
+            selector,
+            window_title: element.and_then(|e| e.window_title.clone()),
+            start: timestamp,
+            end: timestamp,
+        });
+        typing.text.push(character);
+        typing.end = timestamp;
+    }
+
+    fn flush_typing(&mut self) {
+        let Some(typing) = self.typing.take() else {
+            return;
+        };
+        if typing.text.is_empty() {
+            return;
+        }
+        let mut arguments = json!({
+            "selector": typing.selector,
+            "text_to_type": typing.text,
+            "clear_before_typing": false,
+        });
+        if let Some(process) = &typing.process {
+            arguments["process"] = json!(process);
+        }
+        let mut step = self.step(
+            typing.start,
+            "type_into_element",
+            arguments,
+            format!("Type into `{}`", typing.selector),
+        );
+        step.end = typing.end;
+        step.window_title = typing.window_title;
+        self.steps.push(step);
+    }
+
+    fn hotkey(&mut self, timestamp: u64, hotkey: &SerializableHotkeyEvent) {
+        let Some(process) = self.resolve_process(hotkey.process_name.as_deref()) else {
+            self.warn(
+                timestamp,
+                &format!(
+                    "{} pressed before any window was known, skipped",
+                    hotkey.combination
+                ),
+            );
+            return;
+        };
+        let keys = match KeySequence::from_chord(&hotkey.combination) {
+            Ok(keys) => keys,
+            Err(e) => {
+                self.warn(timestamp, &format!("hotkey skipped: {e}"));
+                return;
+            }
+        };
+        let mut step = self.step(
+            timestamp,
+            "press_key_global",
+            json!({
+                "process": process,
+                "key": keys.to_string(),
+                "verify_element_exists": "",
+                "verify_element_not_exists": "",
+            }),
+            hotkey
+                .action
+                .clone()
+                .unwrap_or_else(|| format!("Press {}", hotkey.combination)),
+        );
+        step.window_title = hotkey
+            .metadata
+            .ui_element
+            .as_ref()
+            .and_then(|e| e.window_title.clone());
+        self.push(step);
+    }
+
+    fn application_switch(&mut self, timestamp: u64, switch: &SerializableApplicationSwitchEvent) {
+        let Some(process) = switch
+            .to_process_name
+            .as_deref()
+            .and_then(process_from_executable)
+        else {
+            self.warn(
+                timestamp,
+                &format!(
+                    "switch to `{}` has no process name, skipped",
+                    switch.to_window_and_application_name
+                ),
+            );
+            return;
+        };
+        self.process = Some(process.clone());
+        let already_active = self.steps.last().is_some_and(|last| {
+            last.step.tool_name == "activate_element" && last.step.arguments["process"] == process
+        });
+        if already_active && self.typing.is_none() {
+            return;
+        }
+        let mut step = self.step(
+            timestamp,
+            "activate_element",
+            json!({
+                "process": process,
+                "selector": "",
+                "verify_element_exists": "",
+                "verify_element_not_exists": "",
+            }),
+            format!("Switch to {}", switch.to_window_and_application_name),
+        );
+        step.window_title = Some(switch.to_window_and_application_name.clone());
+        self.push(step);
+    }
+
+    fn tab_navigation(
+        &mut self,
+        timestamp: u64,
+        navigation: &SerializableBrowserTabNavigationEvent,
+    ) {
+        let process = browser_process(&navigation.browser);
+        self.process = Some(process.clone());
+        match navigation.action {
+            TabAction::Created | TabAction::Switched => {
+                let Some(url) = &navigation.to_url else {
+                    return;
+                };
+                if self.typing.is_none() {
+                    if let Some(last) = self.steps.last_mut() {
+                        let caused_by_click = last.browser.as_deref() == Some(process.as_str())
+                            && timestamp.saturating_sub(last.end) <= NAVIGATION_AFTER_CLICK_MS;
+                        let same_url = last.step.tool_name == "navigate_browser"
+                            && last.step.arguments["url"] == url.as_str();
+                        if caused_by_click {
+                            last.changes.push(format!(
+                                "+ - [Document] {} (url: {url})",
+                                navigation.to_title.as_deref().unwrap_or_default()
+                            ));
+                            last.window_title = None;
+                            return;
+                        }
+                        if same_url {
+                            return;
+                        }
+                    }
+                }
+                let mut step = self.step(
+                    timestamp,
+                    "navigate_browser",
+                    json!({
+                        "url": url,
+                        "process": process,
+                        "verify_element_exists": "",
+                        "verify_element_not_exists": "",
+                    }),
+                    format!("Open {url}"),
+                );
+                step.browser = Some(process);
+                self.push(step);
+            }
+            TabAction::Closed | TabAction::Refreshed => {
+                let (key, description) = if navigation.action == TabAction::Closed {
+                    ("{Ctrl}{W}", "Close the tab")
+                } else {
+                    ("{F5}", "Reload the page")
+                };
+                let step = self.step(
+                    timestamp,
+                    "press_key_global",
+                    json!({
+                        "process": process,
+                        "key": key,
+                        "verify_element_exists": "",
+                        "verify_element_not_exists": "",
+                    }),
+                    description.to_string(),
+                );
+                self.push(step);
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(mut self, options: &ConversionOptions) -> ConvertedWorkflow {
+        self.flush_browser_click();
+        self.flush_typing();
+
+        let next: Vec<(u64, Option<String>)> = self
+            .steps
+            .iter()
+            .skip(1)
+            .map(|s| (s.start, s.window_title.clone()))
+            .collect();
+        let steps = self
+            .steps
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut pending)| {
+                if let Some((start, title)) = next.get(i) {
+                    let gap = start.saturating_sub(pending.end);
+                    if options.preserve_timing && gap >= MIN_DELAY_MS {
+                        pending.step.delay_ms = Some(gap.min(options.max_delay_ms));
+                    }
+                    if let (Some(before), Some(after)) = (&pending.window_title, title) {
+                        if before != after {
+                            pending.changes.push(format!("- - [Window] {before}"));
+                            pending.changes.push(format!("+ - [Window] {after}"));
+                        }
+                    }
+                }
+                if options.expected_ui_changes && !pending.changes.is_empty() {
+                    pending.step.expected_ui_changes = Some(pending.changes.join("\n"));
+                }
+                pending.step
+            })
+            .collect();
+
+        ConvertedWorkflow {
+            name: self.name,
+            steps,
+            warnings: self.warnings,
+        }
+    }
+}
+
+/// `chrome.exe` -> `chrome`, the form the MCP tools expect
+fn process_from_executable(executable: &str) -> Option<String> {
+    let name = executable.trim();
+    let name = name
+        .strip_suffix(".exe")
+        .or_else(|| name.strip_suffix(".EXE"))
+        .unwrap_or(name);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+fn browser_process(browser: &str) -> String {
+    let lower = browser.to_lowercase();
+    if lower.contains("edge") {
+        "msedge".to_string()
+    } else if lower.contains("chrome") {
+        "chrome".to_string()
+    } else if lower.contains("firefox") {
+        "firefox".to_string()
+    } else {
+        process_from_executable(&lower).unwrap_or(lower)
+    }
+}
+
+/// `role|name` selector for an element, with a `text:` alternative
+fn named_selector(role: &str, name: &str) -> Option<(String, String)> {
+    let name = name.trim();
+    if role.is_empty() || name.is_empty() {
+        return None;
+    }
+    let selector = Selector::Role {
+        role: role.to_string(),
+        name: Some(name.to_string()),
+    };
+    // Quotes only where needed, as the selector also ends up in generated code
+    let text = Selector::Text(name.to_string());
+    let plain = format!("text:{name}");
+    let alternative = if Selector::parse(&plain).as_ref() == Ok(&text) {
+        plain
+    } else {
+        text.to_string()
+    };
+    Some((selector.to_string(), alternative))
+}
+
+/// `ui_tree_diff` line for a field now holding `value`
+fn value_change(role: &str, name: &str, value: &str) -> String {
+    let mut line = format!("+ - [{role}]");
+    if !name.is_empty() {
+        line.push(' ');
+        line.push_str(name);
+    }
+    line.push_str(&format!(" (value: {value})"));
+    line
+}
+
+/// Character typed by a key press, from its virtual-key code
+fn typed_character(key: &SerializableKeyboardEvent) -> Option<char> {
+    let code = u8::try_from(key.key_code).ok()?;
+    match code {
+        b'A'..=b'Z' if key.shift_pressed => Some(code as char),
+        b'A'..=b'Z' => Some(code.to_ascii_lowercase() as char),
+        b'0'..=b'9' if !key.shift_pressed => Some(code as char),
+        _ if key.key_code == VK_SPACE => Some(' '),
+        _ => None,
+    }
+}
+
+/// Selector candidates of a browser click, most robust first
+fn dom_candidates(click: &BrowserClickEvent) -> Vec<SelectorCandidate> {
+    let mut candidates: Vec<SelectorCandidate> = Vec::new();
+    let from_dom = click
+        .dom_element
+        .iter()
+        .flat_map(|dom| dom.selector_candidates.iter());
+    for candidate in click.selectors.iter().chain(from_dom) {
+        if !candidate.selector.is_empty()
+            && !candidates.iter().any(|c| c.selector == candidate.selector)
+        {
+            candidates.push(candidate.clone());
+        }
+    }
+    // Stable, so equal scores keep the recorder's order
+    candidates.sort_by_key(|c| std::cmp::Reverse(candidate_score(c)));
+    candidates
+}
+
+fn best_candidate(candidates: &[SelectorCandidate]) -> Option<&SelectorCandidate> {
+    candidates
+        .iter()
+        .filter(|c| !c.selector.is_empty())
+        .max_by_key(|c| candidate_score(c))
+}
+
+/// The recorder's specificity, lowered for selectors likely to change
+fn candidate_score(candidate: &SelectorCandidate) -> i64 {
+    let mut score = i64::from(candidate.specificity);
+    if candidate.requires_jquery {
+        score -= 40;
+    }
+    match candidate.selector_type.as_str() {
+        // Framework-generated ids such as `#input-1234` differ between page loads
+        "Id" if has_digit_run(&candidate.selector, 4) => score -= 50,
+        "Class" if candidate.selector.matches('.').count() > 2 => score -= 20,
+        _ => {}
+    }
+    score
+}
+
+fn has_digit_run(text: &str, length: usize) -> bool {
+    let mut run = 0;
+    for c in text.chars() {
+        run = if c.is_ascii_digit() { run + 1 } else { 0 };
+        if run >= length {
+            return true;
+        }
+    }
+    false
+}
+
+/// JavaScript expression finding the candidate's element
+fn dom_lookup(candidate: &SelectorCandidate) -> String {
+    let selector = serde_json::to_string(&candidate.selector).unwrap_or_default();
+    if candidate.selector_type == "XPath" {
+        format!(
+            "document.evaluate({selector}, document, null, XPathResult.FIRST_ORDERED_NODE_TYPE, null).singleNodeValue"
+        )
+    } else {
+        format!("document.querySelector({selector})")
+    }
+}
+
+fn dom_click_script(candidate: &SelectorCandidate, click_type: &str) -> String {
+    let action = match click_type {
+        "double" => "el.dispatchEvent(new MouseEvent('dblclick', { bubbles: true }));",
+        "right" => "el.dispatchEvent(new MouseEvent('contextmenu', { bubbles: true }));",
+        _ => "el.click();",
+    };
+    format!(
+        "(function() {{\n  const el = {};\n  if (!el) throw new Error({});\n  el.scrollIntoView({{ block: 'center' }});\n  {action}\n  return JSON.stringify({{ clicked: true }});\n}})()",
+        dom_lookup(candidate),
+        serde_json::to_string(&format!("Element not found: {}", candidate.selector))
+            .unwrap_or_default(),
+    )
+}
+
+fn dom_input_script(candidate: &SelectorCandidate, text: &str) -> String {
+    format!(
+        "(function() {{\n  const el = {};\n  if (!el) throw new Error({});\n  el.focus();\n  el.value = {};\n  el.dispatchEvent(new Event('input', {{ bubbles: true }}));\n  el.dispatchEvent(new Event('change', {{ bubbles: true }}));\n  return JSON.stringify({{ filled: true }});\n}})()",
+        dom_lookup(candidate),
+        serde_json::to_string(&format!("Element not found: {}", candidate.selector))
+            .unwrap_or_default(),
+        serde_json::to_string(text).unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(role: &str, name: &str, window: &str) -> Value {
+        json!({ "role": role, "name": name, "window_title": window })
+    }
+
+    fn recording(events: Vec<(u64, &str, Value)>) -> SerializableRecordedWorkflow {
+        let events: Vec<Value> = events
+            .into_iter()
+            .map(|(timestamp, kind, mut event)| {
+                event["metadata"]["timestamp"] = json!(timestamp);
+                json!({ "timestamp": timestamp, "event": { kind: event } })
+            })
+            .collect();
+        serde_json::from_value(json!({
+            "name": "recording",
+            "start_time": 0,
+            "events": events,
+        }))
+        .unwrap()
+    }
+
+    fn click(name: &str, window: &str) -> Value {
+        json!({
+            "element_text": name,
+            "interaction_type": "Click",
+            "element_role": "Button",
+            "was_enabled": true,
+            "process_name": "notepad.exe",
+            "relative_position": [0.5, 0.5],
+            "metadata": { "ui_element": element("Button", name, window) },
+        })
+    }
+
+    fn key(key_code: u32) -> Value {
+        json!({
+            "key_code": key_code,
+            "is_key_down": true,
+            "ctrl_pressed": false,
+            "alt_pressed": false,
+            "shift_pressed": false,
+            "win_pressed": false,
+            "metadata": { "ui_element": element("Edit", "Search", "App") },
+        })
+    }
+
+    fn browser_click(selectors: Value) -> Value {
+        json!({
+            "dom_element": null,
+            "position": { "x": 10, "y": 20 },
+            "selectors": selectors,
+            "page_url": "https://example.com",
+            "page_title": "Example",
+            "timestamp": 0,
+            "button": "Left",
+            "is_double_click": false,
+            "metadata": {},
+        })
+    }
+
+    fn candidate(selector: &str, selector_type: &str, specificity: u32) -> Value {
+        json!({
+            "selector": selector,
+            "selector_type": selector_type,
+            "specificity": specificity,
+            "requires_jquery": false,
+        })
+    }
+
+    #[test]
+    fn test_clicks_use_role_and_name_selectors() {
+        let workflow = recording(vec![
+            (1000, "Click", click("Save", "Untitled - Notepad")),
+            (4000, "Click", click("OK", "Save As")),
+        ]);
+        let converted = convert_workflow(&workflow, &ConversionOptions::default());
+
+        assert!(converted.warnings.is_empty());
+        let first = &converted.steps[0];
+        assert_eq!(first.tool_name, "click_element");
+        assert_eq!(first.arguments["process"], "notepad");
+        assert_eq!(first.arguments["selector"], "role:Button|name:Save");
+        assert_eq!(first.arguments["alternative_selectors"], "text:Save");
+        assert_eq!(first.delay_ms, Some(3000));
+        assert_eq!(
+            first.expected_ui_changes.as_deref(),
+            Some("- - [Window] Untitled - Notepad\n+ - [Window] Save As")
+        );
+        assert_eq!(converted.steps[1].delay_ms, None);
+
+        let options = ConversionOptions {
+            preserve_timing: false,
+            expected_ui_changes: false,
+            ..Default::default()
+        };
+        let converted = convert_workflow(&workflow, &options);
+        assert_eq!(converted.steps[0].delay_ms, None);
+        assert_eq!(converted.steps[0].expected_ui_changes, None);
+    }
+
+    #[test]
+    fn test_keystrokes_merge_into_type_into_element() {
+        let mut shifted = key(u32::from(b'H'));
+        shifted["shift_pressed"] = json!(true);
+        let workflow = recording(vec![
+            (0, "Click", click("Search", "App")),
+            (100, "Keyboard", shifted),
+            (150, "Keyboard", key(u32::from(b'I'))),
+            (200, "Keyboard", key(u32::from(b'X'))),
+            (250, "Keyboard", key(VK_BACK)),
+            (300, "Keyboard", key(u32::from(b'1'))),
+            (350, "Keyboard", key(VK_RETURN)),
+        ]);
+        let converted = convert_workflow(&workflow, &ConversionOptions::default());
+
+        let tools: Vec<&str> = converted
+            .steps
+            .iter()
+            .map(|s| s.tool_name.as_str())
+            .collect();
+        assert_eq!(
+            tools,
+            ["click_element", "type_into_element", "press_key_global"]
+        );
+        let typed = &converted.steps[1].arguments;
+        assert_eq!(typed["text_to_type"], "Hi1");
+        assert_eq!(typed["selector"], "role:Edit|name:Search");
+        assert_eq!(typed["clear_before_typing"], false);
+        assert_eq!(converted.steps[2].arguments["key"], "{Enter}");
+    }
+
+    #[test]
+    fn test_completed_text_input_replaces_keystrokes() {
+        let workflow = recording(vec![
+            (100, "Keyboard", key(u32::from(b'A'))),
+            (200, "Keyboard", key(u32::from(b'B'))),
+            (
+                400,
+                "TextInputCompleted",
+                json!({
+                    "text_value": "ab",
+                    "field_name": "Search",
+                    "field_type": "Edit",
+                    "input_method": "Typed",
+                    "focus_method": "MouseClick",
+                    "typing_duration_ms": 300,
+                    "keystroke_count": 2,
+                    "process_name": "chrome.exe",
+                    "metadata": {},
+                }),
+            ),
+            (
+                500,
+                "BrowserTextInput",
+                json!({
+                    "dom_element": null,
+                    "text": "ab",
+                    "selector": "#q",
+                    "was_pasted": false,
+                    "page_url": "https://example.com",
+                    "page_title": "Example",
+                    "timestamp": 500,
+                    "metadata": {},
+                }),
+            ),
+        ]);
+        let converted = convert_workflow(&workflow, &ConversionOptions::default());
+
+        assert_eq!(converted.steps.len(), 1);
+        let step = &converted.steps[0];
+        assert_eq!(step.tool_name, "type_into_element");
+        assert_eq!(step.arguments["process"], "chrome");
+        assert_eq!(step.arguments["text_to_type"], "ab");
+        assert_eq!(step.arguments["clear_before_typing"], true);
+        assert_eq!(
+            step.expected_ui_changes.as_deref(),
+            Some("+ - [Edit] Search (value: ab)")
+        );
+    }
+
+    #[test]
+    fn test_browser_clicks_prefer_robust_dom_selectors() {
+        let mut chrome_click = click("Submit", "Example - Google Chrome");
+        chrome_click["process_name"] = json!("chrome.exe");
+        let workflow = recording(vec![
+            (
+                0,
+                "BrowserClick",
+                browser_click(json!([
+                    candidate("#input-48213", "Id", 100),
+                    candidate("[data-testid=\"submit\"]", "DataAttribute", 90),
+                ])),
+            ),
+            (50, "Click", chrome_click),
+            (
+                900,
+                "BrowserTabNavigation",
+                json!({
+                    "action": "Switched",
+                    "method": "LinkNewTab",
+                    "to_url": "https://example.com/done",
+                    "to_title": "Done",
+                    "browser": "Google Chrome",
+                    "is_back_forward": false,
+                    "metadata": {},
+                }),
+            ),
+        ]);
+        let converted = convert_workflow(&workflow, &ConversionOptions::default());
+
+        assert_eq!(converted.steps.len(), 1);
+        let step = &converted.steps[0];
+        assert_eq!(step.tool_name, "execute_browser_script");
+        let script = step.arguments["script"].as_str().unwrap();
+        assert!(script.contains(r#"document.querySelector("[data-testid=\"submit\"]")"#));
+        assert_eq!(
+            step.expected_ui_changes.as_deref(),
+            Some("+ - [Document] Done (url: https://example.com/done)")
+        );
+
+        // Weak DOM selectors lose to the UI Automation name
+        let workflow = recording(vec![
+            (
+                0,
+                "BrowserClick",
+                browser_click(json!([candidate("div > span", "CssPath", 30)])),
+            ),
+            (50, "Click", click("Submit", "Example")),
+        ]);
+        let converted = convert_workflow(&workflow, &ConversionOptions::default());
+        assert_eq!(converted.steps[0].tool_name, "click_element");
+    }
+
+    #[test]
+    fn test_hotkeys_switches_and_sequence_output() {
+        let workflow = recording(vec![
+            (0, "Click", click("Copy", "App")),
+            (
+                100,
+                "Hotkey",
+                json!({
+                    "combination": "Ctrl+Shift+T",
+                    "is_global": false,
+                    "metadata": {},
+                }),
+            ),
+            (
+                2000,
+                "Hotkey",
+                json!({ "combination": "Alt+Tab", "is_global": true, "metadata": {} }),
+            ),
+            (
+                2200,
+                "ApplicationSwitch",
+                json!({
+                    "to_window_and_application_name": "Calculator",
+                    "to_process_name": "CalculatorApp.exe",
+                    "to_process_id": 42,
+                    "switch_method": "AltTab",
+                    "metadata": {},
+                }),
+            ),
+            (
+                2300,
+                "DragDrop",
+                json!({
+                    "start_position": { "x": 0, "y": 0 },
+                    "end_position": { "x": 5, "y": 5 },
+                    "success": true,
+                    "metadata": {},
+                }),
+            ),
+        ]);
+        let converted = convert_workflow(&workflow, &ConversionOptions::default());
+
+        let tools: Vec<&str> = converted
+            .steps
+            .iter()
+            .map(|s| s.tool_name.as_str())
+            .collect();
+        assert_eq!(
+            tools,
+            ["click_element", "press_key_global", "activate_element"]
+        );
+        assert_eq!(converted.steps[1].arguments["key"], "{Ctrl}{Shift}t");
+        assert_eq!(converted.steps[1].arguments["process"], "notepad");
+        assert_eq!(converted.steps[2].arguments["process"], "CalculatorApp");
+        assert_eq!(converted.warnings.len(), 1);
+
+        let sequence = converted.to_execute_sequence();
+        assert_eq!(sequence["steps"][0]["id"], "step_1");
+        assert_eq!(sequence["steps"][1]["delay_ms"], 2100);
+        assert_eq!(sequence["steps"][2]["tool_name"], "activate_element");
+    }
+}
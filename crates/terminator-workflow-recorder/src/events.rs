@@ -2,7 +2,7 @@ use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashSet;
 use std::sync::LazyLock;
 use std::time::SystemTime;
-use terminator::UIElement;
+use terminator::{KeyParseError, KeySequence, UIElement};
 
 // Precomputed set of null-like values for efficient O(1) lookups
 static NULL_LIKE_VALUES: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
@@ -295,6 +295,13 @@ pub struct HotkeyEvent {
     pub metadata: EventMetadata,
 }
 
+impl HotkeyEvent {
+    /// The combination as a key sequence, e.g. `{Ctrl}c` for "Ctrl+C"
+    pub fn key_sequence(&self) -> Result<KeySequence, KeyParseError> {
+        KeySequence::from_chord(&self.combination)
+    }
+}
+
 /// Represents the type of button interaction
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ButtonInteractionType {
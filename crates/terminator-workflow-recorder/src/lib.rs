@@ -6,10 +6,12 @@
 
 #![cfg_attr(not(target_os = "windows"), allow(unused))]
 
+mod converter;
 mod error;
 mod events;
 mod recorder;
 
+pub use converter::{convert_workflow, ConversionOptions, ConvertedWorkflow};
 pub use error::*;
 pub use events::{
     build_chained_selector, build_parent_hierarchy, ApplicationSwitchEvent,
@@ -18,7 +20,8 @@ pub use events::{
     DragDropEvent, EnhancedUIElement, EventMetadata, FieldFocusMethod, FileOpenedEvent,
     HotkeyEvent, InteractionContext, KeyboardEvent, McpToolStep, MouseButton, MouseEvent,
     MouseEventType, PendingActionEvent, PendingActionType, Position, RecordedEvent,
-    RecordedWorkflow, Rect, SelectionMethod, SelectorCandidate, TabAction, TabNavigationMethod,
+    RecordedWorkflow, Rect, SelectionMethod, SelectorCandidate, SerializableRecordedEvent,
+    SerializableRecordedWorkflow, SerializableWorkflowEvent, TabAction, TabNavigationMethod,
     TextInputCompletedEvent, TextInputMethod, TextSelectionEvent, UIElementInfo, WorkflowEvent,
 };
 pub use recorder::*;
@@ -1445,6 +1445,39 @@ impl WindowsRecorder {
         }
     }
 
+    /// `Ctrl+Shift+Esc` style name of virtual key codes, as read by
+    /// `KeySequence::from_chord`
+    fn format_hotkey(keys: &[u32]) -> String {
+        keys.iter()
+            .map(|&vk| match vk {
+                160 | 161 => "Shift".to_string(),
+                162 | 163 => "Ctrl".to_string(),
+                164 | 165 => "Alt".to_string(),
+                91 | 92 => "Win".to_string(),
+                8 => "Backspace".to_string(),
+                9 => "Tab".to_string(),
+                13 => "Enter".to_string(),
+                27 => "Esc".to_string(),
+                32 => "Space".to_string(),
+                33 => "PageUp".to_string(),
+                34 => "PageDown".to_string(),
+                35 => "End".to_string(),
+                36 => "Home".to_string(),
+                37 => "Left".to_string(),
+                38 => "Up".to_string(),
+                39 => "Right".to_string(),
+                40 => "Down".to_string(),
+                45 => "Insert".to_string(),
+                46 => "Delete".to_string(),
+                112..=135 => format!("F{}", vk - 111),
+                // Digits and letters share their ASCII codes
+                48..=57 | 65..=90 => char::from(vk as u8).to_string(),
+                other => format!("VK{other}"),
+            })
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+
     /// Detect hotkey combinations
     fn detect_hotkey(
         patterns: &[HotkeyPattern],
@@ -1457,7 +1490,7 @@ impl WindowsRecorder {
                 .all(|&key| active_keys.get(&key).copied().unwrap_or(false))
             {
                 return Some(HotkeyEvent {
-                    combination: format!("{:?}", pattern.keys), // TODO: Format properly
+                    combination: Self::format_hotkey(&pattern.keys),
                     action: Some(pattern.action.clone()),
                     is_global: true,
                     process_name: None, // TODO: Pass process context from caller
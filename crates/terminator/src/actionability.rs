@@ -0,0 +1,350 @@
+//! Actionability checks run before element actions
+//!
+//! Before clicking or typing, [`ensure_actionable`] verifies that the element is in a
+//! state where the action will land where the caller expects: visible, enabled, not
+//! moving, not covered by another window (e.g. a modal dialog) and, for typing,
+//! editable. Failed checks are retried until [`ActionabilityOptions::timeout`]
+//! elapses; the last failure is returned as the matching [`AutomationError`].
+//!
+//! `UIElement::click`, `type_text` and `set_value` run the checks once with the
+//! defaults below. Callers that located the element through a [`crate::Locator`]
+//! (such as the MCP tools) pass the locator timeout so transient states like an
+//! animation or a closing dialog are waited out.
+
+use crate::{AutomationError, UIElement};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Bounds samples compared by the stability check
+pub const DEFAULT_STABLE_SAMPLES: usize = 2;
+
+/// Delay between bounds samples
+pub const DEFAULT_SAMPLE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Delay between attempts while retrying failed checks
+pub const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How far up the tree the hit-test looks when relating the hit element to the target
+const MAX_ANCESTOR_DEPTH: usize = 64;
+
+/// A single precondition checked before an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActionabilityCheck {
+    /// The element is visible and has a non-empty on-screen area
+    Visible,
+    /// The element is enabled
+    Enabled,
+    /// The element accepts text input
+    Editable,
+    /// The element's bounds are unchanged across consecutive samples
+    Stable,
+    /// The topmost element at the action point is the target or one of its descendants
+    ReceivesEvents,
+}
+
+impl ActionabilityCheck {
+    /// All checks, in the order they are evaluated
+    pub const ALL: [ActionabilityCheck; 5] = [
+        Self::Visible,
+        Self::Enabled,
+        Self::Editable,
+        Self::Stable,
+        Self::ReceivesEvents,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Visible => "visible",
+            Self::Enabled => "enabled",
+            Self::Editable => "editable",
+            Self::Stable => "stable",
+            Self::ReceivesEvents => "receivesEvents",
+        }
+    }
+}
+
+/// Which checks to run before an action and how long to wait for them to pass
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionabilityOptions {
+    pub checks: Vec<ActionabilityCheck>,
+    /// Number of bounds samples that must match for [`ActionabilityCheck::Stable`]
+    pub stable_samples: usize,
+    /// Delay between bounds samples
+    pub sample_interval: Duration,
+    /// Point used by [`ActionabilityCheck::ReceivesEvents`], as percentages (0-100)
+    /// of the element's width and height
+    pub position: (u8, u8),
+    /// How long failed checks are retried. `Duration::ZERO` checks once.
+    pub timeout: Duration,
+    /// Delay between attempts
+    pub retry_interval: Duration,
+}
+
+impl ActionabilityOptions {
+    /// Run the given checks once at the element's center
+    pub fn with_checks(checks: impl IntoIterator<Item = ActionabilityCheck>) -> Self {
+        Self {
+            checks: checks.into_iter().collect(),
+            stable_samples: DEFAULT_STABLE_SAMPLES,
+            sample_interval: DEFAULT_SAMPLE_INTERVAL,
+            position: (50, 50),
+            timeout: Duration::ZERO,
+            retry_interval: DEFAULT_RETRY_INTERVAL,
+        }
+    }
+
+    /// Checks for clicks: visible, enabled, stable and receives events
+    pub fn for_click() -> Self {
+        Self::with_checks([
+            ActionabilityCheck::Visible,
+            ActionabilityCheck::Enabled,
+            ActionabilityCheck::Stable,
+            ActionabilityCheck::ReceivesEvents,
+        ])
+    }
+
+    /// Checks for typing: visible, enabled and editable
+    pub fn for_typing() -> Self {
+        Self::with_checks([
+            ActionabilityCheck::Visible,
+            ActionabilityCheck::Enabled,
+            ActionabilityCheck::Editable,
+        ])
+    }
+
+    /// Checks for setting a value through the accessibility API: enabled.
+    /// Value patterns also work on scrolled-out and non-text controls (sliders,
+    /// spinners), so visibility and editability are not required.
+    pub fn for_set_value() -> Self {
+        Self::with_checks([ActionabilityCheck::Enabled])
+    }
+
+    /// Run no checks (the equivalent of Playwright's `force: true`)
+    pub fn none() -> Self {
+        Self::with_checks([])
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Hit-test at a position within the element instead of its center
+    pub fn with_position(mut self, x_pct: u8, y_pct: u8) -> Self {
+        self.position = (x_pct.min(100), y_pct.min(100));
+        self
+    }
+
+    pub fn without(mut self, check: ActionabilityCheck) -> Self {
+        self.checks.retain(|c| *c != check);
+        self
+    }
+
+    fn has(&self, check: ActionabilityCheck) -> bool {
+        self.checks.contains(&check)
+    }
+}
+
+/// Run the configured checks against `element`, retrying until they all pass or
+/// the timeout elapses
+pub fn ensure_actionable(
+    element: &UIElement,
+    options: &ActionabilityOptions,
+) -> Result<(), AutomationError> {
+    if options.checks.is_empty() {
+        return Ok(());
+    }
+    let start = Instant::now();
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        match check_once(element, options) {
+            Ok(()) => return Ok(()),
+            Err(e)
+                if is_retryable(&e)
+                    && start.elapsed() + options.retry_interval < options.timeout =>
+            {
+                debug!(
+                    "Actionability check failed for {} (attempt {attempts}), retrying: {e}",
+                    describe(element)
+                );
+                thread::sleep(options.retry_interval);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Whether a failure may resolve by itself (an animation finishing, a dialog closing)
+pub fn is_retryable(error: &AutomationError) -> bool {
+    matches!(
+        error,
+        AutomationError::ElementNotVisible(_)
+            | AutomationError::ElementNotEnabled(_)
+            | AutomationError::ElementNotEditable(_)
+            | AutomationError::ElementNotStable(_)
+            | AutomationError::ElementObscured(_)
+    )
+}
+
+fn check_once(element: &UIElement, options: &ActionabilityOptions) -> Result<(), AutomationError> {
+    for check in ActionabilityCheck::ALL {
+        if !options.has(check) {
+            continue;
+        }
+        match check {
+            ActionabilityCheck::Visible => check_visible(element)?,
+            ActionabilityCheck::Enabled => {
+                if !element.is_enabled()? {
+                    return Err(AutomationError::ElementNotEnabled(format!(
+                        "{} is disabled",
+                        describe(element)
+                    )));
+                }
+            }
+            ActionabilityCheck::Editable => {
+                if !element.is_editable()? {
+                    return Err(AutomationError::ElementNotEditable(format!(
+                        "{} does not accept text input (read-only or not a text field)",
+                        describe(element)
+                    )));
+                }
+            }
+            ActionabilityCheck::Stable => check_stable(element, options)?,
+            ActionabilityCheck::ReceivesEvents => check_receives_events(element, options)?,
+        }
+    }
+    Ok(())
+}
+
+fn check_visible(element: &UIElement) -> Result<(), AutomationError> {
+    if !element.is_visible()? {
+        return Err(AutomationError::ElementNotVisible(format!(
+            "{} is not visible",
+            describe(element)
+        )));
+    }
+    let (_, _, width, height) = element.bounds()?;
+    if width <= 0.0 || height <= 0.0 {
+        return Err(AutomationError::ElementNotVisible(format!(
+            "{} has no on-screen area ({width}x{height})",
+            describe(element)
+        )));
+    }
+    Ok(())
+}
+
+fn check_stable(
+    element: &UIElement,
+    options: &ActionabilityOptions,
+) -> Result<(), AutomationError> {
+    let mut previous = element.bounds()?;
+    for _ in 1..options.stable_samples {
+        thread::sleep(options.sample_interval);
+        let current = element.bounds()?;
+        if !same_bounds(previous, current) {
+            return Err(AutomationError::ElementNotStable(format!(
+                "{} moved from {previous:?} to {current:?} within {:?}",
+                describe(element),
+                options.sample_interval
+            )));
+        }
+        previous = current;
+    }
+    Ok(())
+}
+
+fn same_bounds(a: (f64, f64, f64, f64), b: (f64, f64, f64, f64)) -> bool {
+    const TOLERANCE: f64 = 0.5;
+    (a.0 - b.0).abs() <= TOLERANCE
+        && (a.1 - b.1).abs() <= TOLERANCE
+        && (a.2 - b.2).abs() <= TOLERANCE
+        && (a.3 - b.3).abs() <= TOLERANCE
+}
+
+fn check_receives_events(
+    element: &UIElement,
+    options: &ActionabilityOptions,
+) -> Result<(), AutomationError> {
+    let (x, y, width, height) = element.bounds()?;
+    let point_x = x + width * f64::from(options.position.0) / 100.0;
+    let point_y = y + height * f64::from(options.position.1) / 100.0;
+
+    let hit = match element.element_at_point(point_x, point_y) {
+        Ok(hit) => hit,
+        // Platforms without hit testing skip the check rather than fail every click
+        Err(AutomationError::UnsupportedOperation(reason)) => {
+            debug!("Skipping hit-test check: {reason}");
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    };
+    let Some(hit) = hit else {
+        return Err(AutomationError::ElementObscured(format!(
+            "no element receives input at ({point_x:.0}, {point_y:.0}), the center of {}",
+            describe(element)
+        )));
+    };
+    if is_related(&hit, element) {
+        return Ok(());
+    }
+    let window = hit
+        .window()
+        .ok()
+        .flatten()
+        .and_then(|w| w.name())
+        .filter(|name| !name.is_empty())
+        .map(|name| format!(" in window '{name}'"))
+        .unwrap_or_default();
+    Err(AutomationError::ElementObscured(format!(
+        "{}{window} would receive the click at ({point_x:.0}, {point_y:.0}) instead of {}",
+        describe(&hit),
+        describe(element)
+    )))
+}
+
+/// Whether the hit element is the target or one of its descendants. Some providers
+/// report the containing control for points inside children that do not hit-test
+/// themselves, so the target's direct parent is accepted too - unless it is a
+/// top-level window or the desktop root, which is what a click through an
+/// overlapping window would land on.
+pub(crate) fn is_related(hit: &UIElement, target: &UIElement) -> bool {
+    is_ancestor_or_self(target, hit) || is_inner_parent(hit, target)
+}
+
+/// Whether `parent` is `element`'s parent and sits below a top-level window
+fn is_inner_parent(parent: &UIElement, element: &UIElement) -> bool {
+    element.parent().ok().flatten().as_ref() == Some(parent)
+        && parent
+            .parent()
+            .ok()
+            .flatten()
+            .and_then(|grandparent| grandparent.parent().ok().flatten())
+            .is_some()
+}
+
+/// Whether `ancestor` is `element` or one of its ancestors
+fn is_ancestor_or_self(ancestor: &UIElement, element: &UIElement) -> bool {
+    let mut current = Some(element.clone());
+    for _ in 0..MAX_ANCESTOR_DEPTH {
+        match current {
+            Some(el) if el == *ancestor => return true,
+            Some(el) => current = el.parent().ok().flatten(),
+            None => return false,
+        }
+    }
+    false
+}
+
+/// Short description used in error messages, e.g. `'Save' Button`
+fn describe(element: &UIElement) -> String {
+    let role = element.role();
+    match element.name().filter(|name| !name.is_empty()) {
+        Some(name) if name.chars().count() > 50 => {
+            format!("'{}...' {role}", name.chars().take(47).collect::<String>())
+        }
+        Some(name) => format!("'{name}' {role}"),
+        None => role,
+    }
+}
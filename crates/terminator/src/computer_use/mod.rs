@@ -7,7 +7,7 @@
 //!
 //! Types and backend API are in the `terminator-computer-use` crate.
 
-use crate::Desktop;
+use crate::{Desktop, KeySequence};
 use anyhow::Result;
 use base64::{engine::general_purpose, Engine as _};
 use chrono::Local;
@@ -20,9 +20,8 @@ use std::path::PathBuf;
 use std::time::Duration;
 use sysinfo::{ProcessesToUpdate, System};
 use terminator_computer_use::{
-    call_computer_use_backend, convert_normalized_to_screen, translate_gemini_keys,
-    ComputerUseActionResponse, ComputerUsePreviousAction, ComputerUseResult, ComputerUseStep,
-    ProgressCallback,
+    call_computer_use_backend, convert_normalized_to_screen, ComputerUseActionResponse,
+    ComputerUsePreviousAction, ComputerUseResult, ComputerUseStep, ProgressCallback,
 };
 use tracing::{info, warn};
 
@@ -230,6 +229,19 @@ fn capture_window_for_computer_use(
     })
 }
 
+// ===== Key Translation =====
+
+/// Translate Gemini Computer Use key format to uiautomation format.
+///
+/// Gemini format: "enter", "control+a", "Meta+Shift+T"
+/// uiautomation format: "{Enter}", "{Ctrl}a", "{Win}{Shift}t"
+#[deprecated(note = "use `KeySequence::from_chord(keys)?.to_send_keys()`")]
+pub fn translate_gemini_keys(gemini_keys: &str) -> Result<String, String> {
+    KeySequence::from_chord(gemini_keys)
+        .map(|sequence| sequence.to_send_keys())
+        .map_err(|e| e.to_string())
+}
+
 // ===== Action Execution =====
 
 /// Execute a computer use action
@@ -312,7 +324,9 @@ async fn execute_action(
         }
         "key_combination" => {
             let keys = get_str("keys").ok_or("key_combination requires keys")?;
-            let translated = translate_gemini_keys(keys)?;
+            let translated = KeySequence::from_chord(keys)
+                .map_err(|e| e.to_string())?
+                .to_string();
             info!("[computer_use] key_combination: {} -> {}", keys, translated);
             desktop
                 .press_key(&translated)
@@ -708,3 +722,33 @@ impl Desktop {
         Ok(result)
     }
 }
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate_gemini_keys_simple() {
+        assert_eq!(translate_gemini_keys("enter").unwrap(), "{Enter}");
+        assert_eq!(translate_gemini_keys("tab").unwrap(), "{Tab}");
+        assert_eq!(translate_gemini_keys("escape").unwrap(), "{Escape}");
+    }
+
+    #[test]
+    fn test_translate_gemini_keys_modifiers() {
+        assert_eq!(translate_gemini_keys("control+a").unwrap(), "{Ctrl}a");
+        assert_eq!(translate_gemini_keys("ctrl+c").unwrap(), "{Ctrl}c");
+        assert_eq!(
+            translate_gemini_keys("Meta+Shift+T").unwrap(),
+            "{Win}{Shift}t"
+        );
+    }
+
+    #[test]
+    fn test_translate_gemini_keys_function() {
+        assert_eq!(translate_gemini_keys("f1").unwrap(), "{F1}");
+        assert_eq!(translate_gemini_keys("f12").unwrap(), "{F12}");
+        assert_eq!(translate_gemini_keys("alt+f4").unwrap(), "{Alt}{F4}");
+    }
+}
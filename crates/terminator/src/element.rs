@@ -1,3 +1,4 @@
+use crate::actionability::ActionabilityOptions;
 use crate::errors::AutomationError;
 use crate::selector::Selector;
 use crate::ScreenshotResult;
@@ -132,8 +133,8 @@ impl From<&UIElement> for SerializableUIElement {
 
         // Get process_id and derive process_name
         let process_id = element.process_id().ok();
-        let process_name = process_id
-            .and_then(|pid| crate::platforms::windows::get_process_name_by_pid(pid as i32).ok());
+        let process_name =
+            process_id.and_then(|pid| crate::get_process_name_by_pid(pid as i32).ok());
 
         Self {
             id: element.id(),
@@ -614,6 +615,22 @@ pub trait UIElementImpl: Send + Sync + Debug {
     // New method for keyboard focusable
     fn is_keyboard_focusable(&self) -> Result<bool, AutomationError>;
 
+    /// Whether the element accepts typed text. Platforms should override this with
+    /// their read-only / editable state; the default assumes it does, so typing is
+    /// not refused on platforms that cannot tell.
+    fn is_editable(&self) -> Result<bool, AutomationError> {
+        Ok(true)
+    }
+
+    /// Topmost element that receives pointer input at a screen point, used by the
+    /// actionability hit-test. `Ok(None)` means nothing accepts input there.
+    fn element_at_point(&self, x: f64, y: f64) -> Result<Option<UIElement>, AutomationError> {
+        let _ = (x, y);
+        Err(AutomationError::UnsupportedOperation(
+            "element_at_point not implemented for this platform".into(),
+        ))
+    }
+
     // New method for mouse drag
     fn mouse_drag(
         &self,
@@ -765,6 +782,7 @@ pub trait UIElementImpl: Send + Sync + Debug {
                 })? as f64;
 
                 // Get work area for this monitor if it's Windows and primary
+                #[cfg(target_os = "windows")]
                 let work_area = if is_primary {
                     use crate::platforms::windows::element::WorkArea;
                     if let Ok(work_area) = WorkArea::get_primary() {
@@ -785,6 +803,13 @@ pub trait UIElementImpl: Send + Sync + Debug {
                         height: mon_h as u32,
                     })
                 };
+                #[cfg(not(target_os = "windows"))]
+                let work_area = Some(crate::WorkAreaBounds {
+                    x: mon_x,
+                    y: mon_y,
+                    width: mon_w as u32,
+                    height: mon_h as u32,
+                });
 
                 return Ok(crate::Monitor {
                     id: format!("monitor_{idx}"),
@@ -842,6 +867,7 @@ pub trait UIElementImpl: Send + Sync + Debug {
                 })? as f64;
 
                 // Get work area for primary monitor
+                #[cfg(target_os = "windows")]
                 let work_area = {
                     use crate::platforms::windows::element::WorkArea;
                     if let Ok(work_area) = WorkArea::get_primary() {
@@ -855,6 +881,8 @@ pub trait UIElementImpl: Send + Sync + Debug {
                         None
                     }
                 };
+                #[cfg(not(target_os = "windows"))]
+                let work_area = None;
 
                 return Ok(crate::Monitor {
                     id: format!("monitor_{idx}"),
@@ -927,9 +955,19 @@ impl UIElement {
         }
     }
 
-    /// Click on this element
-    #[instrument(level = "debug", skip(self))]
+    /// Click on this element after checking it is visible, enabled, stable and
+    /// not covered by another element
     pub fn click(&self) -> Result<ClickResult, AutomationError> {
+        self.click_with_options(&ActionabilityOptions::for_click())
+    }
+
+    /// Click on this element, running the given actionability checks first
+    #[instrument(level = "debug", skip(self))]
+    pub fn click_with_options(
+        &self,
+        options: &ActionabilityOptions,
+    ) -> Result<ClickResult, AutomationError> {
+        self.ensure_actionable(options)?;
         #[cfg(target_os = "windows")]
         show_action_overlay("Clicking", Some(self.overlay_info()));
         let result = self.inner.click();
@@ -939,8 +977,17 @@ impl UIElement {
     }
 
     /// Double-click on this element
-    #[instrument(level = "debug", skip(self))]
     pub fn double_click(&self) -> Result<ClickResult, AutomationError> {
+        self.double_click_with_options(&ActionabilityOptions::for_click())
+    }
+
+    /// Double-click on this element, running the given actionability checks first
+    #[instrument(level = "debug", skip(self))]
+    pub fn double_click_with_options(
+        &self,
+        options: &ActionabilityOptions,
+    ) -> Result<ClickResult, AutomationError> {
+        self.ensure_actionable(options)?;
         #[cfg(target_os = "windows")]
         show_action_overlay("Double-clicking", Some(self.overlay_info()));
         let result = self.inner.double_click();
@@ -950,8 +997,17 @@ impl UIElement {
     }
 
     /// Right-click on this element
-    #[instrument(level = "debug", skip(self))]
     pub fn right_click(&self) -> Result<(), AutomationError> {
+        self.right_click_with_options(&ActionabilityOptions::for_click())
+    }
+
+    /// Right-click on this element, running the given actionability checks first
+    #[instrument(level = "debug", skip(self))]
+    pub fn right_click_with_options(
+        &self,
+        options: &ActionabilityOptions,
+    ) -> Result<(), AutomationError> {
+        self.ensure_actionable(options)?;
         #[cfg(target_os = "windows")]
         show_action_overlay("Right-clicking", Some(self.overlay_info()));
         let result = self.inner.right_click();
@@ -961,7 +1017,9 @@ impl UIElement {
     }
 
     /// Click at a specific position within the element bounds
-    /// x_pct and y_pct are percentages (0-100) from top-left corner
+    /// x_pct and y_pct are percentages (0-100) from top-left corner. No actionability
+    /// checks are run; call [`UIElement::ensure_actionable`] with
+    /// [`ActionabilityOptions::with_position`] first to verify the point is not covered.
     #[instrument(level = "debug", skip(self))]
     pub fn click_at_position(
         &self,
@@ -1009,8 +1067,9 @@ impl UIElement {
         result
     }
 
-    /// Type text into this element
+    /// Type text into this element after checking it is visible, enabled and editable
     pub fn type_text(&self, text: &str, use_clipboard: bool) -> Result<(), AutomationError> {
+        self.ensure_actionable(&ActionabilityOptions::for_typing())?;
         #[cfg(target_os = "windows")]
         show_action_overlay("Typing", Some(self.overlay_info()));
         // Default: try both focus and click, no focus restore
@@ -1027,6 +1086,7 @@ impl UIElement {
         text: &str,
         use_clipboard: bool,
     ) -> Result<crate::ActionResult, AutomationError> {
+        self.ensure_actionable(&ActionabilityOptions::for_typing())?;
         #[cfg(target_os = "windows")]
         show_action_overlay("Typing", Some(self.overlay_info()));
         // Default: try both focus and click
@@ -1066,6 +1126,28 @@ impl UIElement {
         try_click_before: bool,
         restore_focus: bool,
     ) -> Result<crate::ActionResult, AutomationError> {
+        self.type_text_with_options(
+            text,
+            use_clipboard,
+            try_focus_before,
+            try_click_before,
+            restore_focus,
+            &ActionabilityOptions::for_typing(),
+        )
+    }
+
+    /// Type text with state tracking, running the given actionability checks first
+    #[instrument(level = "debug", skip(self, options))]
+    pub fn type_text_with_options(
+        &self,
+        text: &str,
+        use_clipboard: bool,
+        try_focus_before: bool,
+        try_click_before: bool,
+        restore_focus: bool,
+        options: &ActionabilityOptions,
+    ) -> Result<crate::ActionResult, AutomationError> {
+        self.ensure_actionable(options)?;
         #[cfg(target_os = "windows")]
         show_action_overlay("Typing", Some(self.overlay_info()));
 
@@ -1122,6 +1204,7 @@ impl UIElement {
 
     /// Press a key while this element is focused
     pub fn press_key(&self, key: &str) -> Result<(), AutomationError> {
+        let key = &crate::KeySequence::parse(key)?.to_send_keys();
         #[cfg(target_os = "windows")]
         show_action_overlay(
             "Pressing key",
@@ -1137,6 +1220,7 @@ impl UIElement {
     /// Press a key with state tracking
     #[instrument(level = "debug", skip(self))]
     pub fn press_key_with_state(&self, key: &str) -> Result<crate::ActionResult, AutomationError> {
+        let key = &crate::KeySequence::parse(key)?.to_send_keys();
         #[cfg(target_os = "windows")]
         show_action_overlay(
             "Pressing key",
@@ -1157,6 +1241,7 @@ impl UIElement {
         try_focus_before: bool,
         try_click_before: bool,
     ) -> Result<crate::ActionResult, AutomationError> {
+        let key = &crate::KeySequence::parse(key)?.to_send_keys();
         #[cfg(target_os = "windows")]
         show_action_overlay(
             "Pressing key",
@@ -1181,8 +1266,18 @@ impl UIElement {
         self.inner.get_value()
     }
 
-    /// Set value of this element
+    /// Set value of this element after checking it is enabled
     pub fn set_value(&self, value: &str) -> Result<(), AutomationError> {
+        self.set_value_with_options(value, &ActionabilityOptions::for_set_value())
+    }
+
+    /// Set value of this element, running the given actionability checks first
+    pub fn set_value_with_options(
+        &self,
+        value: &str,
+        options: &ActionabilityOptions,
+    ) -> Result<(), AutomationError> {
+        self.ensure_actionable(options)?;
         self.inner.set_value(value)
     }
 
@@ -1274,6 +1369,35 @@ impl UIElement {
         self.inner.is_keyboard_focusable()
     }
 
+    /// Check if element accepts typed text (not read-only)
+    pub fn is_editable(&self) -> Result<bool, AutomationError> {
+        self.inner.is_editable()
+    }
+
+    /// Topmost element that would receive a click at the given screen point
+    pub fn element_at_point(&self, x: f64, y: f64) -> Result<Option<UIElement>, AutomationError> {
+        self.inner.element_at_point(x, y)
+    }
+
+    /// Run actionability checks (visible, enabled, stable, ...) against this element,
+    /// retrying until `options.timeout`. See [`crate::actionability`].
+    pub fn ensure_actionable(&self, options: &ActionabilityOptions) -> Result<(), AutomationError> {
+        crate::actionability::ensure_actionable(self, options)
+    }
+
+    /// [`UIElement::ensure_actionable`] on a blocking thread, for async callers
+    /// (the retries sleep between attempts)
+    pub async fn ensure_actionable_async(
+        &self,
+        options: &ActionabilityOptions,
+    ) -> Result<(), AutomationError> {
+        let element = self.clone();
+        let options = options.clone();
+        tokio::task::spawn_blocking(move || element.ensure_actionable(&options))
+            .await
+            .map_err(|e| AutomationError::PlatformError(format!("Task join error: {e}")))?
+    }
+
     /// Drag mouse from start to end coordinates
     pub fn mouse_drag(
         &self,
@@ -52,6 +52,9 @@ pub enum AutomationError {
     #[error("Element is obscured by another element: {0}")]
     ElementObscured(String),
 
+    #[error("Element is not editable: {0}")]
+    ElementNotEditable(String),
+
     #[error("Failed to scroll element into view: {0}")]
     ScrollFailed(String),
 
@@ -61,3 +64,58 @@ pub enum AutomationError {
     #[error("Verification failed: {0}")]
     VerificationFailed(String),
 }
+
+impl AutomationError {
+    /// Name of the error variant, e.g. "ElementNotFound"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AutomationError::ElementNotFound(_) => "ElementNotFound",
+            AutomationError::Timeout(_) => "Timeout",
+            AutomationError::PermissionDenied(_) => "PermissionDenied",
+            AutomationError::PlatformError(_) => "PlatformError",
+            AutomationError::UnsupportedOperation(_) => "UnsupportedOperation",
+            AutomationError::UnsupportedPlatform(_) => "UnsupportedPlatform",
+            AutomationError::InvalidArgument(_) => "InvalidArgument",
+            AutomationError::Internal(_) => "Internal",
+            AutomationError::InvalidSelector(_) => "InvalidSelector",
+            AutomationError::UIAutomationAPIError { .. } => "UIAutomationAPIError",
+            AutomationError::ElementDetached(_) => "ElementDetached",
+            AutomationError::ElementNotVisible(_) => "ElementNotVisible",
+            AutomationError::ElementNotEnabled(_) => "ElementNotEnabled",
+            AutomationError::ElementNotStable(_) => "ElementNotStable",
+            AutomationError::ElementObscured(_) => "ElementObscured",
+            AutomationError::ElementNotEditable(_) => "ElementNotEditable",
+            AutomationError::ScrollFailed(_) => "ScrollFailed",
+            AutomationError::OperationCancelled(_) => "OperationCancelled",
+            AutomationError::VerificationFailed(_) => "VerificationFailed",
+        }
+    }
+
+    /// Every name `kind()` can return
+    pub const KINDS: &'static [&'static str] = &[
+        "ElementNotFound",
+        "Timeout",
+        "PermissionDenied",
+        "PlatformError",
+        "UnsupportedOperation",
+        "UnsupportedPlatform",
+        "InvalidArgument",
+        "Internal",
+        "InvalidSelector",
+        "UIAutomationAPIError",
+        "ElementDetached",
+        "ElementNotVisible",
+        "ElementNotEnabled",
+        "ElementNotStable",
+        "ElementObscured",
+        "ElementNotEditable",
+        "ScrollFailed",
+        "OperationCancelled",
+        "VerificationFailed",
+    ];
+
+    /// The `kind()` name matching `name`, if it is one
+    pub fn kind_from_name(name: &str) -> Option<&'static str> {
+        Self::KINDS.iter().copied().find(|kind| *kind == name)
+    }
+}
@@ -0,0 +1,165 @@
+//! Accessibility change notifications used to wake waits
+//!
+//! [`crate::Locator::wait_for`] re-checks its condition whenever something in the
+//! accessibility tree changes instead of polling on a fixed interval. Engines that
+//! can observe the tree implement `AccessibilityEngine::subscribe` and push
+//! [`TreeEvent`]s into an [`EventSubscription`]; [`ChangeWaiter`] turns those into
+//! wake-ups and falls back to polling when the engine has no events.
+//!
+//! Events are only hints: providers drop notifications (UIA does not raise
+//! structure events for every control), so waits still re-check on
+//! [`EVENT_FALLBACK_INTERVAL`] while subscribed.
+
+use crate::platforms::AccessibilityEngine;
+use crate::{AutomationError, UIElement};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tracing::debug;
+
+/// Delay between checks when the engine offers no events. Event-driven waits
+/// never re-check more often than this either, so a busy window costs no more
+/// than polling did.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Safety-net re-check interval while subscribed, for changes that raise no event.
+/// Such a change is noticed up to this late; a shorter interval re-reads the tree
+/// more often while nothing happens.
+pub const EVENT_FALLBACK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A change in the accessibility tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeEvent {
+    /// Elements were added, removed or reordered
+    StructureChanged,
+    /// A property (name, value, enabled, bounds, ...) of an element changed
+    PropertyChanged,
+    /// Keyboard focus moved to another element
+    FocusChanged,
+}
+
+/// Sending half of an [`EventSubscription`], held by the engine's event source
+pub type EventSender = UnboundedSender<TreeEvent>;
+
+/// Stream of [`TreeEvent`]s from an engine. Dropping it unregisters the
+/// underlying platform handlers.
+pub struct EventSubscription {
+    receiver: UnboundedReceiver<TreeEvent>,
+    on_drop: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl std::fmt::Debug for EventSubscription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventSubscription").finish_non_exhaustive()
+    }
+}
+
+impl EventSubscription {
+    /// A subscription fed through the returned sender
+    pub fn channel() -> (EventSender, Self) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (
+            sender,
+            Self {
+                receiver,
+                on_drop: None,
+            },
+        )
+    }
+
+    /// Run `f` when the subscription is dropped, e.g. to remove platform handlers
+    pub fn on_drop(mut self, f: impl FnOnce() + Send + 'static) -> Self {
+        self.on_drop = Some(Box::new(f));
+        self
+    }
+
+    /// Next event, or `None` once the event source has gone away
+    pub async fn next(&mut self) -> Option<TreeEvent> {
+        self.receiver.recv().await
+    }
+
+    /// Discard queued events, returning how many there were
+    pub fn drain(&mut self) -> usize {
+        let mut count = 0;
+        while self.receiver.try_recv().is_ok() {
+            count += 1;
+        }
+        count
+    }
+}
+
+impl Drop for EventSubscription {
+    fn drop(&mut self) {
+        if let Some(f) = self.on_drop.take() {
+            f();
+        }
+    }
+}
+
+/// Sleeps between condition checks of a wait, waking early on tree changes
+#[derive(Debug)]
+pub struct ChangeWaiter {
+    subscription: Option<EventSubscription>,
+    last_wake: Instant,
+}
+
+impl ChangeWaiter {
+    /// Subscribe to changes below `scope` (the whole desktop when `None`),
+    /// falling back to polling if the engine has no events
+    pub fn new(engine: &dyn AccessibilityEngine, scope: Option<&UIElement>) -> Self {
+        let subscription = match engine.subscribe(scope) {
+            Ok(subscription) => Some(subscription),
+            Err(AutomationError::UnsupportedOperation(reason)) => {
+                debug!("Tree events unavailable, polling every {POLL_INTERVAL:?}: {reason}");
+                None
+            }
+            Err(e) => {
+                debug!("Failed to subscribe to tree events, polling every {POLL_INTERVAL:?}: {e}");
+                None
+            }
+        };
+        Self {
+            subscription,
+            ..Self::polling()
+        }
+    }
+
+    /// A waiter that always polls
+    pub fn polling() -> Self {
+        Self {
+            subscription: None,
+            last_wake: Instant::now(),
+        }
+    }
+
+    /// Whether waits wake on events rather than polling
+    pub fn is_event_driven(&self) -> bool {
+        self.subscription.is_some()
+    }
+
+    /// Wait until the tree may have changed, for at most `max`
+    pub async fn wait(&mut self, max: Duration) {
+        let deadline = Instant::now() + max;
+        match self.subscription.as_mut() {
+            None => tokio::time::sleep(max.min(POLL_INTERVAL)).await,
+            Some(subscription) => {
+                match tokio::time::timeout(max.min(EVENT_FALLBACK_INTERVAL), subscription.next())
+                    .await
+                {
+                    Ok(Some(event)) => {
+                        // Rate-limit re-checks and fold the rest of a burst into this one
+                        let earliest = (self.last_wake + POLL_INTERVAL).min(deadline);
+                        tokio::time::sleep_until(earliest.into()).await;
+                        let coalesced = subscription.drain();
+                        debug!("Woke on {event:?} (+{coalesced} more)");
+                    }
+                    Ok(None) => {
+                        debug!("Tree event source closed, falling back to polling");
+                        self.subscription = None;
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+        self.last_wake = Instant::now();
+    }
+}
@@ -0,0 +1,356 @@
+//! Element predicates referenced by [`Selector::Filter`]
+//!
+//! A `filter:` selector only carries an id; the predicate lives in a process-wide
+//! registry. Ids below [`BuiltinFilter::ALL`]`.len()` are the built-in predicates
+//! (`filter:hasValue`, `filter:enabled`, `filter:inViewport`); custom predicates are
+//! added with [`register_filter`] / [`register_named_filter`] or [`crate::Locator::filter`].
+//!
+//! Filters narrow the results of another selector, either later in a chain
+//! (`role:ListItem >> filter:enabled`) or in a conjunction
+//! (`role:Edit && filter:hasValue`).
+
+use crate::{AutomationError, Selector, UIElement, UIElementAttributes};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Predicate evaluated against each candidate element
+pub type FilterPredicate = Arc<dyn Fn(&UIElement) -> Result<bool, AutomationError> + Send + Sync>;
+
+/// Predicates available in selector strings without registration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinFilter {
+    /// `filter:hasValue` - the element has a non-empty value
+    HasValue,
+    /// `filter:enabled` - the element accepts input
+    Enabled,
+    /// `filter:inViewport` - the element is visible and overlaps its window
+    InViewport,
+}
+
+impl BuiltinFilter {
+    pub const ALL: [BuiltinFilter; 3] = [Self::HasValue, Self::Enabled, Self::InViewport];
+
+    /// Id used in [`Selector::Filter`]
+    pub fn id(self) -> usize {
+        self as usize
+    }
+
+    pub fn from_id(id: usize) -> Option<Self> {
+        Self::ALL.get(id).copied()
+    }
+
+    /// Name used in selector strings (`filter:<name>`)
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::HasValue => "hasValue",
+            Self::Enabled => "enabled",
+            Self::InViewport => "inViewport",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|f| f.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Evaluate the predicate against a live element
+    pub fn matches(self, element: &UIElement) -> Result<bool, AutomationError> {
+        match self {
+            Self::HasValue => Ok(element
+                .get_value()?
+                .is_some_and(|value| !value.trim().is_empty())),
+            Self::Enabled => element.is_enabled(),
+            Self::InViewport => {
+                if !element.is_visible()? {
+                    return Ok(false);
+                }
+                let bounds = element.bounds()?;
+                match element.window()? {
+                    Some(window) => Ok(overlaps(bounds, window.bounds()?)),
+                    None => Ok(true),
+                }
+            }
+        }
+    }
+
+    /// Evaluate the predicate against captured attributes. `viewport` is the bounds
+    /// of the element's top-level window, if known.
+    pub(crate) fn matches_attributes(
+        self,
+        attributes: &UIElementAttributes,
+        visible: bool,
+        viewport: Option<(f64, f64, f64, f64)>,
+    ) -> bool {
+        match self {
+            Self::HasValue => attributes
+                .value
+                .as_deref()
+                .is_some_and(|value| !value.trim().is_empty()),
+            Self::Enabled => attributes.enabled.unwrap_or(true),
+            Self::InViewport => {
+                visible
+                    && match (attributes.bounds, viewport) {
+                        (Some(bounds), Some(viewport)) => overlaps(bounds, viewport),
+                        _ => true,
+                    }
+            }
+        }
+    }
+}
+
+fn overlaps(a: (f64, f64, f64, f64), b: (f64, f64, f64, f64)) -> bool {
+    a.0 < b.0 + b.2 && b.0 < a.0 + a.2 && a.1 < b.1 + b.3 && b.1 < a.1 + a.3
+}
+
+struct RegisteredFilter {
+    name: Option<String>,
+    predicate: FilterPredicate,
+}
+
+fn registry() -> &'static RwLock<HashMap<usize, RegisteredFilter>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<usize, RegisteredFilter>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(BuiltinFilter::ALL.len());
+
+/// Register an anonymous predicate and return its id for [`Selector::Filter`].
+/// The predicate stays registered until [`unregister_filter`] is called.
+pub fn register_filter<F>(predicate: F) -> usize
+where
+    F: Fn(&UIElement) -> Result<bool, AutomationError> + Send + Sync + 'static,
+{
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    registry()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(
+            id,
+            RegisteredFilter {
+                name: None,
+                predicate: Arc::new(predicate),
+            },
+        );
+    id
+}
+
+/// Register a predicate usable as `filter:<name>` in selector strings.
+/// Registering an existing name replaces its predicate and keeps its id.
+pub fn register_named_filter<F>(name: &str, predicate: F) -> Result<usize, AutomationError>
+where
+    F: Fn(&UIElement) -> Result<bool, AutomationError> + Send + Sync + 'static,
+{
+    if name.is_empty() || name.parse::<usize>().is_ok() {
+        return Err(AutomationError::InvalidArgument(format!(
+            "Invalid filter name '{name}': names must be non-empty and not numeric"
+        )));
+    }
+    if BuiltinFilter::from_name(name).is_some() {
+        return Err(AutomationError::InvalidArgument(format!(
+            "'{name}' is a built-in filter and cannot be replaced"
+        )));
+    }
+    let mut registry = registry().write().unwrap_or_else(|e| e.into_inner());
+    let id = registry
+        .iter()
+        .find(|(_, f)| f.name.as_deref() == Some(name))
+        .map(|(id, _)| *id)
+        .unwrap_or_else(|| NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    registry.insert(
+        id,
+        RegisteredFilter {
+            name: Some(name.to_string()),
+            predicate: Arc::new(predicate),
+        },
+    );
+    Ok(id)
+}
+
+/// Remove a registered predicate. Returns `false` for unknown and built-in ids.
+pub fn unregister_filter(id: usize) -> bool {
+    registry()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&id)
+        .is_some()
+}
+
+/// Id of a built-in or registered named filter
+pub fn filter_id(name: &str) -> Option<usize> {
+    if let Some(builtin) = BuiltinFilter::from_name(name) {
+        return Some(builtin.id());
+    }
+    registry()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find(|(_, f)| f.name.as_deref() == Some(name))
+        .map(|(id, _)| *id)
+}
+
+/// Name of a built-in or registered named filter
+pub fn filter_name(id: usize) -> Option<String> {
+    if let Some(builtin) = BuiltinFilter::from_id(id) {
+        return Some(builtin.name().to_string());
+    }
+    registry()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&id)
+        .and_then(|f| f.name.clone())
+}
+
+/// Whether `id` refers to a built-in or a currently registered predicate
+pub fn is_registered(id: usize) -> bool {
+    BuiltinFilter::from_id(id).is_some()
+        || registry()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains_key(&id)
+}
+
+/// Evaluate the predicate with the given id against an element
+pub fn evaluate_filter(id: usize, element: &UIElement) -> Result<bool, AutomationError> {
+    if let Some(builtin) = BuiltinFilter::from_id(id) {
+        return builtin.matches(element);
+    }
+    // Clone the predicate out so it can itself run selectors without holding the lock
+    let predicate = registry()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&id)
+        .map(|f| f.predicate.clone())
+        .ok_or_else(|| unknown_filter(id))?;
+    predicate(element)
+}
+
+pub(crate) fn unknown_filter(id: usize) -> AutomationError {
+    AutomationError::InvalidSelector(format!(
+        "Unknown filter id {id}: the predicate was never registered or has been dropped"
+    ))
+}
+
+/// Error for a `filter:` selector used on its own
+pub(crate) fn standalone_filter_error() -> AutomationError {
+    AutomationError::InvalidSelector(
+        "Filter selector must narrow another selector (e.g. 'role:Button && filter:enabled' or 'role:Button >> filter:enabled')"
+            .to_string(),
+    )
+}
+
+/// Keep the elements the predicate with the given id accepts
+pub fn retain_matching(
+    id: usize,
+    elements: Vec<UIElement>,
+) -> Result<Vec<UIElement>, AutomationError> {
+    let mut kept = Vec::with_capacity(elements.len());
+    for element in elements {
+        if evaluate_filter(id, &element)? {
+            kept.push(element);
+        }
+    }
+    Ok(kept)
+}
+
+/// Index of the selector a conjunction should be seeded from: the first one that is
+/// not a filter, since filters only narrow an existing candidate set
+pub(crate) fn and_seed(selectors: &[Selector]) -> usize {
+    selectors
+        .iter()
+        .position(|s| !matches!(s, Selector::Filter(_)))
+        .unwrap_or(0)
+}
+
+/// Keeps a predicate registered for as long as any holder is alive
+#[derive(Debug)]
+pub struct FilterHandle {
+    id: usize,
+}
+
+impl FilterHandle {
+    pub fn register<F>(predicate: F) -> Self
+    where
+        F: Fn(&UIElement) -> Result<bool, AutomationError> + Send + Sync + 'static,
+    {
+        Self {
+            id: register_filter(predicate),
+        }
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+}
+
+impl Drop for FilterHandle {
+    fn drop(&mut self) {
+        unregister_filter(self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_and_named_filters_round_trip() {
+        for builtin in BuiltinFilter::ALL {
+            assert_eq!(filter_id(builtin.name()), Some(builtin.id()));
+            let selector =
+                Selector::from(format!("role:Edit >> filter:{}", builtin.name()).as_str());
+            assert_eq!(
+                selector,
+                Selector::Chain(vec![
+                    Selector::Role {
+                        role: "Edit".to_string(),
+                        name: None
+                    },
+                    Selector::Filter(builtin.id())
+                ])
+            );
+            assert_eq!(
+                selector.to_string(),
+                format!("role:Edit >> filter:{}", builtin.name())
+            );
+        }
+
+        let id = register_named_filter("wideFilterTest", |el| Ok(el.bounds()?.2 > 100.0)).unwrap();
+        assert_eq!(
+            Selector::from("filter:wideFilterTest"),
+            Selector::Filter(id)
+        );
+        assert_eq!(Selector::Filter(id).to_string(), "filter:wideFilterTest");
+        let replaced = register_named_filter("wideFilterTest", |_| Ok(true)).unwrap();
+        assert_eq!(replaced, id);
+        assert!(register_named_filter("enabled", |_| Ok(true)).is_err());
+        assert!(unregister_filter(id));
+        assert!(matches!(
+            Selector::from("filter:wideFilterTest"),
+            Selector::Invalid(_)
+        ));
+
+        let handle = FilterHandle::register(|_| Ok(false));
+        let id = handle.id();
+        assert!(is_registered(id));
+        drop(handle);
+        assert!(!is_registered(id));
+    }
+
+    #[test]
+    fn test_builtin_attribute_evaluation() {
+        let attributes = UIElementAttributes {
+            value: Some("hello".to_string()),
+            enabled: Some(false),
+            bounds: Some((900.0, 10.0, 50.0, 20.0)),
+            ..Default::default()
+        };
+        let window = Some((0.0, 0.0, 800.0, 600.0));
+        assert!(BuiltinFilter::HasValue.matches_attributes(&attributes, true, window));
+        assert!(!BuiltinFilter::Enabled.matches_attributes(&attributes, true, window));
+        assert!(!BuiltinFilter::InViewport.matches_attributes(&attributes, true, window));
+        assert!(BuiltinFilter::InViewport.matches_attributes(&attributes, true, None));
+        assert!(!BuiltinFilter::InViewport.matches_attributes(&attributes, false, None));
+    }
+}
@@ -0,0 +1,727 @@
+//! Key sequence grammar shared by every platform
+//!
+//! Key strings use the SendKeys syntax on all platforms:
+//!
+//! - `{Name}` presses a named key: `{Enter}`, `{F4}`, `{PageDown}`. Names are
+//!   case-insensitive and accept common aliases (`{Esc}`, `{Return}`, `{Del}`).
+//! - `{Name N}` presses it N times: `{Tab 3}`.
+//! - `{Ctrl}`, `{Alt}`, `{Shift}` and `{Win}` are held for the next key, or for a
+//!   parenthesised group: `{Ctrl}a`, `{Alt}{F4}`, `{Ctrl}(ac)`. A modifier at the
+//!   end of the sequence is pressed on its own. `{RCtrl}`, `{RAlt}`, `{RShift}`
+//!   and `{RWin}` hold the right-hand key, `{AltGr}` the third-level shift.
+//! - `{{}`, `{}}`, `{(}` and `{)}` type the literal character; any other
+//!   character is typed as-is.
+//!
+//! [`KeySequence::parse`] checks a key string against this grammar, so unknown
+//! key names are rejected up front with suggestions instead of failing in the
+//! platform layer. [`KeySequence::from_chord`] reads the `Ctrl+Shift+T` style
+//! used by recorded hotkeys and computer-use models.
+
+use crate::utils::edit_distance;
+use crate::AutomationError;
+use serde::Serialize;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// Most times a single `{Name N}` may repeat a key
+pub const MAX_KEY_REPEAT: u32 = 1000;
+
+/// A modifier key, held down while other keys are pressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Modifier {
+    Ctrl,
+    Alt,
+    Shift,
+    /// The Windows key, Super on Linux, Command on macOS
+    Win,
+    RCtrl,
+    RAlt,
+    RShift,
+    RWin,
+    /// AltGr, for the third character on a key; right Alt on Windows
+    AltGr,
+}
+
+/// A key that is pressed by name rather than typed as a character
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    Enter,
+    Tab,
+    Escape,
+    Backspace,
+    Delete,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Left,
+    Up,
+    Right,
+    Down,
+    Space,
+    CapsLock,
+    NumLock,
+    ScrollLock,
+    Pause,
+    PrintScreen,
+    /// The context menu key
+    Apps,
+    /// Function keys F1 to F24, or F35 on Linux
+    F(u8),
+    /// A modifier pressed on its own, e.g. `{Win}` to open the start menu
+    Modifier(Modifier),
+}
+
+/// Highest function key the platform can press
+const MAX_FUNCTION_KEY: u8 = if cfg!(target_os = "linux") { 35 } else { 24 };
+
+/// Key names and aliases, canonical name first for each key
+const KEY_NAMES: &[(&str, Key)] = &[
+    ("Enter", Key::Enter),
+    ("Return", Key::Enter),
+    ("Tab", Key::Tab),
+    ("Escape", Key::Escape),
+    ("Esc", Key::Escape),
+    ("Backspace", Key::Backspace),
+    ("Back", Key::Backspace),
+    ("BS", Key::Backspace),
+    ("Delete", Key::Delete),
+    ("Del", Key::Delete),
+    ("Insert", Key::Insert),
+    ("Ins", Key::Insert),
+    ("Home", Key::Home),
+    ("End", Key::End),
+    ("PageUp", Key::PageUp),
+    ("PgUp", Key::PageUp),
+    ("PageDown", Key::PageDown),
+    ("PgDn", Key::PageDown),
+    ("PgDown", Key::PageDown),
+    ("Left", Key::Left),
+    ("ArrowLeft", Key::Left),
+    ("Up", Key::Up),
+    ("ArrowUp", Key::Up),
+    ("Right", Key::Right),
+    ("ArrowRight", Key::Right),
+    ("Down", Key::Down),
+    ("ArrowDown", Key::Down),
+    ("Space", Key::Space),
+    ("CapsLock", Key::CapsLock),
+    ("NumLock", Key::NumLock),
+    ("ScrollLock", Key::ScrollLock),
+    ("ScrLk", Key::ScrollLock),
+    ("Pause", Key::Pause),
+    ("Break", Key::Pause),
+    ("PrintScreen", Key::PrintScreen),
+    ("PrtSc", Key::PrintScreen),
+    ("Print", Key::PrintScreen),
+    ("Apps", Key::Apps),
+    ("Menu", Key::Apps),
+    ("ContextMenu", Key::Apps),
+    ("Ctrl", Key::Modifier(Modifier::Ctrl)),
+    ("Control", Key::Modifier(Modifier::Ctrl)),
+    ("LCtrl", Key::Modifier(Modifier::Ctrl)),
+    ("RCtrl", Key::Modifier(Modifier::RCtrl)),
+    ("Alt", Key::Modifier(Modifier::Alt)),
+    ("LAlt", Key::Modifier(Modifier::Alt)),
+    ("RAlt", Key::Modifier(Modifier::RAlt)),
+    ("AltGr", Key::Modifier(Modifier::AltGr)),
+    ("Shift", Key::Modifier(Modifier::Shift)),
+    ("LShift", Key::Modifier(Modifier::Shift)),
+    ("RShift", Key::Modifier(Modifier::RShift)),
+    ("Win", Key::Modifier(Modifier::Win)),
+    ("Windows", Key::Modifier(Modifier::Win)),
+    ("LWin", Key::Modifier(Modifier::Win)),
+    ("Super", Key::Modifier(Modifier::Win)),
+    ("Meta", Key::Modifier(Modifier::Win)),
+    ("Cmd", Key::Modifier(Modifier::Win)),
+    ("Command", Key::Modifier(Modifier::Win)),
+    ("RWin", Key::Modifier(Modifier::RWin)),
+];
+
+impl Key {
+    /// The key called `name` (case-insensitive, aliases included)
+    pub fn from_name(name: &str) -> Option<Key> {
+        if let Some((_, key)) = KEY_NAMES
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(name))
+        {
+            return Some(*key);
+        }
+        let number: u8 = name
+            .strip_prefix(['f', 'F'])?
+            .parse()
+            .ok()
+            .filter(|n| (1..=MAX_FUNCTION_KEY).contains(n))?;
+        Some(Key::F(number))
+    }
+
+    /// The canonical name, as written inside `{...}`
+    pub fn name(&self) -> String {
+        match self {
+            Key::F(number) => format!("F{number}"),
+            key => KEY_NAMES
+                .iter()
+                .find(|(_, known)| known == key)
+                .map(|(name, _)| name.to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The modifier this key holds, if it is one
+    pub fn modifier(&self) -> Option<Modifier> {
+        match self {
+            Key::Modifier(modifier) => Some(*modifier),
+            _ => None,
+        }
+    }
+}
+
+impl Modifier {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Modifier::Ctrl => "Ctrl",
+            Modifier::Alt => "Alt",
+            Modifier::Shift => "Shift",
+            Modifier::Win => "Win",
+            Modifier::RCtrl => "RCtrl",
+            Modifier::RAlt => "RAlt",
+            Modifier::RShift => "RShift",
+            Modifier::RWin => "RWin",
+            Modifier::AltGr => "AltGr",
+        }
+    }
+}
+
+/// One element of a [`KeySequence`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyItem {
+    /// Literal text, typed character by character
+    Text(String),
+    /// A named key pressed `repeat` times
+    Key { key: Key, repeat: u32 },
+    /// Modifiers held down while `items` are pressed, then released
+    Chord {
+        modifiers: Vec<Modifier>,
+        items: Vec<KeyItem>,
+    },
+}
+
+/// A parsed key string such as `{Ctrl}{Shift}t` or `user{Tab}secret{Enter}`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct KeySequence {
+    items: Vec<KeyItem>,
+}
+
+/// A key string that doesn't follow the grammar, located by byte offsets
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, thiserror::Error)]
+#[error("{message} in key sequence '{input}'")]
+pub struct KeyParseError {
+    pub message: String,
+    /// The key string that failed to parse
+    pub input: String,
+    /// Byte range of the offending input
+    pub span: Range<usize>,
+    /// Valid spellings close to the offending input, e.g. `{Enter}` for `{Entr}`
+    pub suggestions: Vec<String>,
+}
+
+impl From<KeyParseError> for AutomationError {
+    fn from(error: KeyParseError) -> Self {
+        AutomationError::InvalidArgument(error.to_string())
+    }
+}
+
+impl KeySequence {
+    /// Parse a key string in the SendKeys syntax described in the module docs
+    pub fn parse(input: &str) -> Result<Self, KeyParseError> {
+        let mut parser = Parser { input, pos: 0 };
+        let items = parser.items(None)?;
+        Ok(Self { items })
+    }
+
+    /// Parse a `+`-separated chord such as `Ctrl+Shift+T`, `alt+f4` or `Enter`.
+    ///
+    /// Every part but the last must be a modifier; the last is a key name or a
+    /// single character. Letters are pressed lowercase, since the modifiers are
+    /// spelled out.
+    pub fn from_chord(input: &str) -> Result<Self, KeyParseError> {
+        let error = |message: String, suggestions: Vec<String>| KeyParseError {
+            message,
+            input: input.to_string(),
+            span: 0..input.len(),
+            suggestions,
+        };
+        let parts: Vec<&str> = input.split('+').map(str::trim).collect();
+        if parts.iter().any(|part| part.is_empty()) {
+            return Err(error("empty key name".to_string(), Vec::new()));
+        }
+        let (last, held) = parts.split_last().expect("split yields at least one part");
+
+        let mut modifiers = Vec::new();
+        for part in held {
+            match Key::from_name(part).and_then(|key| key.modifier()) {
+                Some(modifier) => modifiers.push(modifier),
+                None => {
+                    return Err(error(
+                        format!("'{part}' is not a modifier (Ctrl, Alt, Shift or Win)"),
+                        Vec::new(),
+                    ))
+                }
+            }
+        }
+
+        let mut chars = last.chars();
+        let item = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyItem::Text(c.to_lowercase().collect()),
+            _ => match Key::from_name(last) {
+                Some(key) => KeyItem::Key { key, repeat: 1 },
+                None => {
+                    let suggestions = suggest_names(last);
+                    return Err(error(unknown_key_message(last, &suggestions), suggestions));
+                }
+            },
+        };
+        let item = if modifiers.is_empty() {
+            item
+        } else {
+            KeyItem::Chord {
+                modifiers,
+                items: vec![item],
+            }
+        };
+        Ok(Self { items: vec![item] })
+    }
+
+    pub fn items(&self) -> &[KeyItem] {
+        &self.items
+    }
+
+    /// The sequence with repeats written out, for backends that don't
+    /// understand `{Name N}`
+    pub fn to_send_keys(&self) -> String {
+        let mut out = String::new();
+        write_items(&self.items, true, &mut out);
+        out
+    }
+}
+
+impl fmt::Display for KeySequence {
+    /// The canonical spelling, which parses back to the same sequence
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        write_items(&self.items, false, &mut out);
+        f.write_str(&out)
+    }
+}
+
+impl FromStr for KeySequence {
+    type Err = KeyParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::parse(input)
+    }
+}
+
+fn write_items(items: &[KeyItem], expand: bool, out: &mut String) {
+    for item in items {
+        match item {
+            KeyItem::Text(text) => {
+                for c in text.chars() {
+                    match c {
+                        '{' | '}' | '(' | ')' => {
+                            out.push('{');
+                            out.push(c);
+                            out.push('}');
+                        }
+                        c => out.push(c),
+                    }
+                }
+            }
+            KeyItem::Key { key, repeat } => {
+                let name = key.name();
+                if expand {
+                    for _ in 0..*repeat {
+                        out.push_str(&format!("{{{name}}}"));
+                    }
+                } else if *repeat > 1 {
+                    out.push_str(&format!("{{{name} {repeat}}}"));
+                } else {
+                    out.push_str(&format!("{{{name}}}"));
+                }
+            }
+            KeyItem::Chord { modifiers, items } => {
+                for modifier in modifiers {
+                    out.push_str(&format!("{{{}}}", modifier.name()));
+                }
+                let single = match items.as_slice() {
+                    [KeyItem::Text(text)] => text.chars().count() == 1,
+                    [KeyItem::Key { repeat, .. }] => *repeat == 1 || !expand,
+                    _ => false,
+                };
+                if single {
+                    write_items(items, expand, out);
+                } else {
+                    out.push('(');
+                    write_items(items, expand, out);
+                    out.push(')');
+                }
+            }
+        }
+    }
+}
+
+/// Known key names close to `name`, most likely first, written as `{Name}`
+fn suggest_names(name: &str) -> Vec<String> {
+    // `{ctrl+c}` is a chord written the other way
+    if name.contains('+') {
+        if let Ok(sequence) = KeySequence::from_chord(name) {
+            return vec![sequence.to_string()];
+        }
+    }
+    let lower = name.to_lowercase();
+    let scored: Vec<(usize, String)> = KEY_NAMES
+        .iter()
+        .map(|(known, key)| (edit_distance(&lower, &known.to_lowercase()), key.name()))
+        .filter(|(distance, _)| *distance <= 2.max(lower.chars().count() / 3))
+        .collect();
+    let best = scored.iter().map(|(distance, _)| *distance).min();
+    let mut names: Vec<String> = Vec::new();
+    for (_, canonical) in scored.into_iter().filter(|(d, _)| Some(*d) == best) {
+        let suggestion = format!("{{{canonical}}}");
+        if !names.contains(&suggestion) {
+            names.push(suggestion);
+        }
+    }
+    names.truncate(3);
+    names
+}
+
+fn unknown_key_message(name: &str, suggestions: &[String]) -> String {
+    match suggestions {
+        [] => format!("unknown key '{name}'"),
+        [only] => format!("unknown key '{name}', did you mean {only}?"),
+        several => format!(
+            "unknown key '{name}', did you mean one of {}?",
+            several.join(", ")
+        ),
+    }
+}
+
+enum Token {
+    Char(char),
+    Key { key: Key, repeat: u32 },
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: String, span: Range<usize>) -> KeyParseError {
+        KeyParseError {
+            message,
+            input: self.input.to_string(),
+            span,
+            suggestions: Vec::new(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    /// Items up to the end of input, or up to the `)` closing a group opened at `group`
+    fn items(&mut self, group: Option<usize>) -> Result<Vec<KeyItem>, KeyParseError> {
+        let mut items: Vec<KeyItem> = Vec::new();
+        loop {
+            match self.peek() {
+                None => match group {
+                    Some(start) => {
+                        return Err(
+                            self.error("unclosed '(' after modifier".to_string(), start..start + 1)
+                        )
+                    }
+                    None => break,
+                },
+                Some(')') if group.is_some() => {
+                    self.bump();
+                    break;
+                }
+                Some(_) => {
+                    let item = self.item(group.is_some())?;
+                    match (items.last_mut(), item) {
+                        (Some(KeyItem::Text(text)), KeyItem::Text(more)) => text.push_str(&more),
+                        (_, item) => items.push(item),
+                    }
+                }
+            }
+        }
+        Ok(items)
+    }
+
+    fn item(&mut self, in_group: bool) -> Result<KeyItem, KeyParseError> {
+        let start = self.pos;
+        let modifier = match self.token()? {
+            Token::Char(c) => return Ok(KeyItem::Text(c.to_string())),
+            Token::Key { key, repeat } => match key.modifier() {
+                Some(modifier) if repeat > 1 => {
+                    return Err(self.error(
+                        format!("modifier key '{}' can't be repeated", modifier.name()),
+                        start..self.pos,
+                    ))
+                }
+                Some(modifier) => modifier,
+                None => return Ok(KeyItem::Key { key, repeat }),
+            },
+        };
+
+        let at_end = match self.peek() {
+            None => true,
+            Some(')') => in_group,
+            Some(_) => false,
+        };
+        if at_end {
+            return Ok(KeyItem::Key {
+                key: Key::Modifier(modifier),
+                repeat: 1,
+            });
+        }
+
+        if self.peek() == Some('(') {
+            let open = self.pos;
+            self.bump();
+            let items = self.items(Some(open))?;
+            if items.is_empty() {
+                return Err(self.error("empty group after modifier".to_string(), open..self.pos));
+            }
+            return Ok(KeyItem::Chord {
+                modifiers: vec![modifier],
+                items,
+            });
+        }
+
+        // `{Ctrl}{Shift}t` holds both modifiers for `t`
+        Ok(match self.item(in_group)? {
+            KeyItem::Chord {
+                mut modifiers,
+                items,
+            } => {
+                modifiers.insert(0, modifier);
+                KeyItem::Chord { modifiers, items }
+            }
+            item => KeyItem::Chord {
+                modifiers: vec![modifier],
+                items: vec![item],
+            },
+        })
+    }
+
+    fn token(&mut self) -> Result<Token, KeyParseError> {
+        let start = self.pos;
+        let Some(c) = self.bump() else {
+            return Err(self.error("unexpected end".to_string(), start..start));
+        };
+        if c != '{' {
+            return Ok(Token::Char(c));
+        }
+
+        // The first character always belongs to the name, so that `{}}` works
+        let name_start = self.pos;
+        if self.bump().is_none() {
+            return Err(self.error("unterminated '{'".to_string(), start..self.pos));
+        }
+        loop {
+            match self.bump() {
+                Some('}') => break,
+                Some(_) => {}
+                None => return Err(self.error("unterminated '{'".to_string(), start..self.pos)),
+            }
+        }
+        let span = start..self.pos;
+        let body = &self.input[name_start..self.pos - 1];
+        if let "{" | "}" | "(" | ")" = body {
+            return Ok(Token::Char(body.chars().next().unwrap_or_default()));
+        }
+
+        let mut words = body.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let repeat = match (words.next(), words.next()) {
+            (None, _) => 1,
+            (Some(count), None) => match count.parse::<u32>() {
+                Ok(n) if (1..=MAX_KEY_REPEAT).contains(&n) => n,
+                _ => {
+                    return Err(self.error(
+                        format!(
+                            "invalid repeat count '{count}' in '{{{body}}}', expected 1 to {MAX_KEY_REPEAT}"
+                        ),
+                        span,
+                    ))
+                }
+            },
+            (Some(_), Some(_)) => {
+                return Err(self.error(
+                    format!("expected '{{Name}}' or '{{Name count}}', found '{{{body}}}'"),
+                    span,
+                ))
+            }
+        };
+
+        match Key::from_name(name) {
+            Some(key) => Ok(Token::Key { key, repeat }),
+            None => {
+                let suggestions = suggest_names(body);
+                let mut error = self.error(unknown_key_message(name, &suggestions), span);
+                error.suggestions = suggestions;
+                Err(error)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> KeyItem {
+        KeyItem::Text(s.to_string())
+    }
+
+    fn key(key: Key, repeat: u32) -> KeyItem {
+        KeyItem::Key { key, repeat }
+    }
+
+    #[test]
+    fn test_parse_keys_text_and_chords() {
+        let sequence = KeySequence::parse("user{tab 2}p{{}w{ENTER}").unwrap();
+        assert_eq!(
+            sequence.items(),
+            [
+                text("user"),
+                key(Key::Tab, 2),
+                text("p{w"),
+                key(Key::Enter, 1)
+            ]
+        );
+
+        let sequence = KeySequence::parse("{Ctrl}{Shift}t{Alt}{F4}{Ctrl}(ac)").unwrap();
+        assert_eq!(
+            sequence.items(),
+            [
+                KeyItem::Chord {
+                    modifiers: vec![Modifier::Ctrl, Modifier::Shift],
+                    items: vec![text("t")],
+                },
+                KeyItem::Chord {
+                    modifiers: vec![Modifier::Alt],
+                    items: vec![key(Key::F(4), 1)],
+                },
+                KeyItem::Chord {
+                    modifiers: vec![Modifier::Ctrl],
+                    items: vec![text("ac")],
+                },
+            ]
+        );
+
+        // A trailing modifier is pressed on its own, a bare `(` is text
+        let sequence = KeySequence::parse("(x){Win}").unwrap();
+        assert_eq!(
+            sequence.items(),
+            [text("(x)"), key(Key::Modifier(Modifier::Win), 1)]
+        );
+    }
+
+    #[test]
+    fn test_display_round_trips_and_expands_repeats() {
+        for input in [
+            "{Ctrl}{Shift}t",
+            "a{Tab 3}b",
+            "{Ctrl}(ac)",
+            "{Alt}{F4}",
+            "{{}x{}}{(}{)}",
+        ] {
+            let sequence = KeySequence::parse(input).unwrap();
+            assert_eq!(sequence.to_string(), input);
+            assert_eq!(KeySequence::parse(&sequence.to_string()).unwrap(), sequence);
+        }
+        assert_eq!(
+            KeySequence::parse("{esc}{return}").unwrap().to_string(),
+            "{Escape}{Enter}"
+        );
+        assert_eq!(
+            KeySequence::parse("{Ctrl}{Tab 2}").unwrap().to_send_keys(),
+            "{Ctrl}({Tab}{Tab})"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_suggest_key_names() {
+        let error = KeySequence::parse("abc{Entr}").unwrap_err();
+        assert_eq!(error.span, 3..9);
+        assert_eq!(error.suggestions, ["{Enter}"]);
+        assert!(error.message.contains("did you mean {Enter}"), "{error}");
+
+        let error = KeySequence::parse("{ctrl+c}").unwrap_err();
+        assert_eq!(error.suggestions, ["{Ctrl}c"]);
+
+        for input in ["{Enter", "{Tab 0}", "{Ctrl 2}a", "{Ctrl}(ab", "{Ctrl}()"] {
+            assert!(KeySequence::parse(input).is_err(), "{input} should fail");
+        }
+    }
+
+    #[test]
+    fn test_lock_keys_and_right_hand_modifiers() {
+        let sequence = KeySequence::parse("{NumLock}{ScrLk}{Menu}{RCtrl}c{AltGr}e").unwrap();
+        assert_eq!(
+            sequence.items(),
+            [
+                key(Key::NumLock, 1),
+                key(Key::ScrollLock, 1),
+                key(Key::Apps, 1),
+                KeyItem::Chord {
+                    modifiers: vec![Modifier::RCtrl],
+                    items: vec![text("c")],
+                },
+                KeyItem::Chord {
+                    modifiers: vec![Modifier::AltGr],
+                    items: vec![text("e")],
+                },
+            ]
+        );
+        assert_eq!(
+            sequence.to_string(),
+            "{NumLock}{ScrollLock}{Apps}{RCtrl}c{AltGr}e"
+        );
+        assert_eq!(Key::from_name("LCtrl"), Some(Key::Modifier(Modifier::Ctrl)));
+        assert_eq!(
+            Key::from_name("rshift"),
+            Some(Key::Modifier(Modifier::RShift))
+        );
+        assert_eq!(Key::from_name("F35").is_some(), cfg!(target_os = "linux"));
+        assert_eq!(Key::from_name("F36"), None);
+    }
+
+    #[test]
+    fn test_from_chord() {
+        let chord = |s: &str| KeySequence::from_chord(s).unwrap().to_string();
+        assert_eq!(chord("Ctrl+Shift+T"), "{Ctrl}{Shift}t");
+        assert_eq!(chord("alt + f4"), "{Alt}{F4}");
+        assert_eq!(chord("control+a"), "{Ctrl}a");
+        assert_eq!(chord("Meta+ArrowUp"), "{Win}{Up}");
+        assert_eq!(chord("enter"), "{Enter}");
+        assert_eq!(chord("x"), "x");
+
+        assert!(KeySequence::from_chord("a+b").is_err());
+        assert!(KeySequence::from_chord("ctrl+").is_err());
+        let error = KeySequence::from_chord("ctrl+escpe").unwrap_err();
+        assert_eq!(error.suggestions, ["{Escape}"]);
+    }
+}
@@ -10,13 +10,18 @@ use std::sync::{Arc, Mutex, RwLock};
 use sysinfo::{ProcessesToUpdate, System};
 use tracing::{debug, error, info, instrument};
 
+pub mod actionability;
 pub mod browser_script;
 pub mod element;
 pub mod errors;
+pub mod events;
 pub mod extension_bridge;
+pub mod filters;
 pub mod health;
+pub mod keys;
 pub mod locator;
 pub mod platforms;
+pub mod query;
 pub mod screenshot;
 pub mod screenshot_logger;
 pub mod selector;
@@ -27,32 +32,37 @@ pub mod types;
 pub mod ui_tree_diff;
 pub mod utils;
 
-#[cfg(target_os = "windows")]
 pub mod computer_use;
 
+pub use actionability::{ActionabilityCheck, ActionabilityOptions};
 pub use element::{OcrElement, SerializableUIElement, UIElement, UIElementAttributes};
 pub use errors::AutomationError;
-pub use locator::Locator;
+pub use events::{EventSubscription, TreeEvent};
+pub use filters::{BuiltinFilter, FilterPredicate};
+pub use keys::{KeyParseError, KeySequence};
+pub use locator::{Locator, LocatorFilter, WaitCondition};
 pub use screenshot::{
     get_cursor_position, ScreenshotError, ScreenshotResult, DEFAULT_MAX_DIMENSION,
 };
-pub use selector::Selector;
+pub use selector::{Selector, SelectorParseError, SelectorParseMode};
 pub use tokio_util::sync::CancellationToken;
 pub use tree_formatter::{
-    format_clustered_tree_from_caches, format_ocr_tree_as_compact_yaml,
-    format_tree_as_compact_yaml, format_ui_node_as_compact_yaml, serializable_to_ui_node,
-    ClusteredFormattingResult, ElementSource, OcrFormattingResult, TreeFormattingResult,
-    UnifiedElement,
+    format_clustered_tree_from_caches, format_clustered_tree_with_config,
+    format_ocr_tree_as_compact_yaml, format_tree_as_compact_yaml, format_tree_with_budget,
+    format_ui_node_as_compact_yaml, format_ui_node_with_budget, serializable_to_ui_node,
+    BudgetedTreeResult, ClusterConfig, ClusteredFormattingResult, CollapsedGroup, ElementSource,
+    OcrFormattingResult, TreeBudget, TreeFormattingResult, UnifiedElement,
 };
 pub use types::{FontStyle, HighlightHandle, OmniparserItem, TextPosition, VisionElement};
 pub use utils::find_pid_for_process;
 
 // Re-export types from terminator-computer-use crate
-#[cfg(target_os = "windows")]
+#[allow(deprecated)]
+pub use computer_use::translate_gemini_keys;
 pub use terminator_computer_use::{
-    call_computer_use_backend, convert_normalized_to_screen, translate_gemini_keys,
-    ComputerUseActionResponse, ComputerUseFunctionCall, ComputerUsePreviousAction,
-    ComputerUseResponse, ComputerUseResult, ComputerUseStep, ProgressCallback,
+    call_computer_use_backend, convert_normalized_to_screen, ComputerUseActionResponse,
+    ComputerUseFunctionCall, ComputerUsePreviousAction, ComputerUseResponse, ComputerUseResult,
+    ComputerUseStep, ProgressCallback,
 };
 
 // Re-export cross-platform types from platforms
@@ -187,6 +197,13 @@ pub use platforms::windows::{
     InspectOverlayHandle, KNOWN_BROWSER_PROCESS_NAMES,
 };
 
+#[cfg(all(target_os = "linux", not(feature = "mock")))]
+pub use platforms::linux::{get_process_name_by_pid, is_browser_process};
+#[cfg(all(feature = "mock", not(target_os = "windows")))]
+pub use platforms::mock::{get_process_name_by_pid, is_browser_process};
+#[cfg(not(target_os = "windows"))]
+pub use platforms::KNOWN_BROWSER_PROCESS_NAMES;
+
 // Define a new struct to hold click result information - move to module level
 pub struct ClickResult {
     pub method: String,
@@ -267,6 +284,8 @@ pub struct UiDiffResult {
     pub diff: String,
     /// Whether any UI changes were detected
     pub has_changes: bool,
+    /// Typed structural changes between the two trees
+    pub changes: Vec<ui_tree_diff::UiChange>,
 }
 
 /// Represents a monitor/display device
@@ -445,7 +464,13 @@ impl Desktop {
     #[instrument(skip(use_background_apps, activate_app))]
     pub fn new(use_background_apps: bool, activate_app: bool) -> Result<Self, AutomationError> {
         let engine = platforms::create_engine(use_background_apps, activate_app)?;
-        Ok(Self {
+        Ok(Self::with_engine(engine))
+    }
+
+    /// Creates a desktop backed by an existing engine (for example a
+    /// `platforms::mock::MockEngine` built from a fixture).
+    pub fn with_engine(engine: Arc<dyn platforms::AccessibilityEngine>) -> Self {
+        Self {
             engine,
             cancellation_token: Arc::new(RwLock::new(CancellationToken::new())),
             uia_cache: Arc::new(Mutex::new(HashMap::new())),
@@ -453,7 +478,7 @@ impl Desktop {
             omniparser_cache: Arc::new(Mutex::new(HashMap::new())),
             vision_cache: Arc::new(Mutex::new(HashMap::new())),
             dom_cache: Arc::new(Mutex::new(HashMap::new())),
-        })
+        }
     }
 
     /// Initializet the desktop without arguments
@@ -496,6 +521,17 @@ impl Desktop {
         Locator::new(self.engine.clone(), selector)
     }
 
+    /// Register a predicate usable as `filter:<name>` in selector strings, e.g.
+    /// `role:Button && filter:wide` after registering `wide`. The predicate stays
+    /// registered for the lifetime of the process; registering the same name again
+    /// replaces it.
+    pub fn register_filter<F>(&self, name: &str, predicate: F) -> Result<usize, AutomationError>
+    where
+        F: Fn(&UIElement) -> Result<bool, AutomationError> + Send + Sync + 'static,
+    {
+        filters::register_named_filter(name, predicate)
+    }
+
     #[instrument(skip(self))]
     pub fn focused_element(&self) -> Result<UIElement, AutomationError> {
         self.engine.get_focused_element()
@@ -1588,8 +1624,10 @@ impl Desktop {
         Ok(windows)
     }
 
+    /// Press a key sequence such as `{Ctrl}s` on whatever has focus (see [`keys`])
     pub async fn press_key(&self, key: &str) -> Result<(), AutomationError> {
-        self.engine.press_key(key)
+        let key = KeySequence::parse(key)?.to_send_keys();
+        self.engine.press_key(&key)
     }
 
     /// Delay execution for a specified number of milliseconds.
@@ -1717,7 +1755,7 @@ impl Desktop {
     /// 3. Executes the action
     /// 4. Waits for UI to settle (configurable, default 1500ms)
     /// 5. Captures the UI tree after the action
-    /// 6. Computes and returns the diff
+    /// 6. Computes and returns the line diff and the typed [`ui_tree_diff::UiChange`]s
     ///
     /// # Arguments
     /// * `selector` - Selector string to find the element
@@ -1830,6 +1868,7 @@ impl Desktop {
         let after_str = format_ui_node_as_compact_yaml(&tree_after, 0).formatted;
 
         // Compute diff
+        let changes = ui_tree_diff::semantic_ui_tree_diff(&tree_before, &tree_after);
         let diff_result = match ui_tree_diff::simple_ui_tree_diff(&before_str, &after_str) {
             Ok(Some(diff)) => {
                 info!(
@@ -1839,6 +1878,7 @@ impl Desktop {
                 UiDiffResult {
                     diff,
                     has_changes: true,
+                    changes,
                 }
             }
             Ok(None) => {
@@ -1846,6 +1886,7 @@ impl Desktop {
                 UiDiffResult {
                     diff: "No UI changes detected".to_string(),
                     has_changes: false,
+                    changes,
                 }
             }
             Err(e) => {
@@ -1956,6 +1997,7 @@ impl Desktop {
         let after_str = format_ui_node_as_compact_yaml(&tree_after, 0).formatted;
 
         // Compute diff
+        let changes = ui_tree_diff::semantic_ui_tree_diff(&tree_before, &tree_after);
         let diff_result = match ui_tree_diff::simple_ui_tree_diff(&before_str, &after_str) {
             Ok(Some(diff)) => {
                 info!(
@@ -1965,6 +2007,7 @@ impl Desktop {
                 UiDiffResult {
                     diff,
                     has_changes: true,
+                    changes,
                 }
             }
             Ok(None) => {
@@ -1972,6 +2015,7 @@ impl Desktop {
                 UiDiffResult {
                     diff: "No UI changes detected".to_string(),
                     has_changes: false,
+                    changes,
                 }
             }
             Err(e) => {
@@ -1,7 +1,10 @@
 use tracing::{debug, instrument};
 
+use crate::actionability::{ActionabilityCheck, ActionabilityOptions};
 use crate::element::UIElement;
 use crate::errors::AutomationError;
+use crate::events::ChangeWaiter;
+use crate::filters::FilterHandle;
 use crate::platforms::AccessibilityEngine;
 use crate::selector::Selector;
 use std::sync::Arc;
@@ -9,7 +12,7 @@ use std::time::Duration;
 use tokio::task;
 
 /// Conditions that can be waited for on an element
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum WaitCondition {
     /// Wait for element to exist
     Exists,
@@ -19,6 +22,67 @@ pub enum WaitCondition {
     Enabled,
     /// Wait for element to be focused
     Focused,
+    /// Wait for the element's bounds to stop changing
+    Stable,
+    /// Wait for the element's value to equal the given string
+    ValueEquals(String),
+    /// Wait for the element's text content to contain the given string
+    TextContains(String),
+    /// Wait until no matching element is visible (it is hidden or gone)
+    Hidden,
+    /// Wait until no element matches
+    Detached,
+}
+
+/// Errors a wait retries instead of returning, because the tree may settle
+fn is_transient(error: &AutomationError) -> bool {
+    matches!(
+        error,
+        AutomationError::PlatformError(_)
+            | AutomationError::ElementDetached(_)
+            | AutomationError::UIAutomationAPIError {
+                is_retryable: true,
+                ..
+            }
+    )
+}
+
+/// Playwright-style narrowing options for [`Locator::filter_by`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LocatorFilter {
+    /// Keep elements whose text, or a descendant's text, contains this string
+    pub has_text: Option<String>,
+    /// Keep elements with a descendant matching this selector
+    pub has: Option<Selector>,
+    /// Keep elements without a descendant matching this selector
+    pub has_not: Option<Selector>,
+}
+
+/// How deep `WaitCondition::TextContains` reads text content
+const WAIT_TEXT_DEPTH: usize = 10;
+
+/// Whether `element` currently meets `condition`
+async fn condition_met(condition: &WaitCondition, element: &UIElement) -> bool {
+    match condition {
+        WaitCondition::Exists => true,
+        WaitCondition::Visible => element.is_visible().unwrap_or(false),
+        WaitCondition::Enabled => element.is_enabled().unwrap_or(false),
+        WaitCondition::Focused => element.is_focused().unwrap_or(false),
+        WaitCondition::Stable => element
+            .ensure_actionable_async(&ActionabilityOptions::with_checks([
+                ActionabilityCheck::Stable,
+            ]))
+            .await
+            .is_ok(),
+        WaitCondition::ValueEquals(expected) => {
+            element.get_value().ok().flatten().as_deref() == Some(expected.as_str())
+        }
+        WaitCondition::TextContains(expected) => element
+            .text(WAIT_TEXT_DEPTH)
+            .is_ok_and(|text| text.contains(expected.as_str())),
+        WaitCondition::Hidden => !element.is_visible().unwrap_or(true),
+        WaitCondition::Detached => false,
+    }
 }
 
 // Default timeout if none is specified on the locator itself
@@ -35,6 +99,9 @@ pub struct Locator {
     selector: Selector,
     timeout: Duration, // Default timeout for this locator instance
     root: Option<UIElement>,
+    /// Predicates referenced by `filter:` steps of the selector, unregistered
+    /// once the last locator using them is dropped
+    filters: Vec<Arc<FilterHandle>>,
 }
 
 impl Locator {
@@ -45,6 +112,7 @@ impl Locator {
             selector,
             timeout: DEFAULT_LOCATOR_TIMEOUT, // Use default
             root: None,
+            filters: Vec::new(),
         }
     }
 
@@ -157,78 +225,188 @@ impl Locator {
         )
     }
 
-    /// Wait for an element to meet a specific condition.
-    /// Polls the element until the condition is met or timeout is reached.
+    /// Wait for the locator to meet a specific condition.
+    /// Re-checks whenever the engine reports a tree change (polling when it can't)
+    /// until the condition is met or timeout is reached.
     ///
     /// # Arguments
-    /// * `condition` - The condition to wait for (exists, visible, enabled, focused)
+    /// * `condition` - The condition to wait for (exists, visible, enabled, stable, ...)
     /// * `timeout` - Maximum time to wait. Uses locator's default timeout if None.
     ///
     /// # Returns
-    /// The element when the condition is met, or an error on timeout.
+    /// The first matching element when the condition is met, or an error on timeout.
+    /// Only [`WaitCondition::Hidden`] and [`WaitCondition::Detached`] return `None`,
+    /// when no matching element is left.
     #[instrument(level = "debug", skip(self, timeout))]
     pub async fn wait_for(
         &self,
         condition: WaitCondition,
         timeout: Option<Duration>,
-    ) -> Result<UIElement, AutomationError> {
+    ) -> Result<Option<UIElement>, AutomationError> {
         debug!(
             "Waiting for element matching selector: {:?} with condition: {:?}",
             self.selector, condition
         );
+        let description = format!("{condition:?}");
+        let condition = &condition;
+        self.wait_until(&description, timeout, move |found| async move {
+            match found {
+                None => matches!(condition, WaitCondition::Hidden | WaitCondition::Detached)
+                    .then_some(None),
+                Some(element) => condition_met(condition, &element)
+                    .await
+                    .then_some(Some(element)),
+            }
+        })
+        .await
+    }
+
+    /// Wait until no element matching the locator is visible (it is hidden or gone)
+    #[instrument(level = "debug", skip(self, timeout))]
+    pub async fn wait_for_hidden(&self, timeout: Option<Duration>) -> Result<(), AutomationError> {
+        self.wait_for(WaitCondition::Hidden, timeout)
+            .await
+            .map(|_| ())
+    }
+
+    /// Wait until no element matches the locator
+    #[instrument(level = "debug", skip(self, timeout))]
+    pub async fn wait_for_detached(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<(), AutomationError> {
+        self.wait_for(WaitCondition::Detached, timeout)
+            .await
+            .map(|_| ())
+    }
 
+    /// Re-run `check` on the current first match (or `None`) whenever the tree changes,
+    /// until it returns a value or the timeout elapses
+    async fn wait_until<T, F, Fut>(
+        &self,
+        description: &str,
+        timeout: Option<Duration>,
+        mut check: F,
+    ) -> Result<T, AutomationError>
+    where
+        F: FnMut(Option<UIElement>) -> Fut,
+        Fut: std::future::Future<Output = Option<T>>,
+    {
         if let Selector::Invalid(reason) = &self.selector {
             return Err(AutomationError::InvalidSelector(reason.clone()));
         }
 
         let effective_timeout = timeout.unwrap_or(self.timeout);
         let start_time = std::time::Instant::now();
-        let poll_interval = Duration::from_millis(100);
+        let mut waiter = self.change_waiter().await;
+        let mut last_error: Option<AutomationError> = None;
 
         loop {
             // Check if we've exceeded the timeout
             if start_time.elapsed() > effective_timeout {
+                let last_error = last_error
+                    .map(|e| format!(" (last error: {e})"))
+                    .unwrap_or_default();
                 return Err(AutomationError::Timeout(format!(
-                    "Timed out after {:?} waiting for element {} to be {:?}",
+                    "Timed out after {:?} waiting for element {} to be {}{}",
                     effective_timeout,
                     self.selector_string(),
-                    condition
+                    description,
+                    last_error
                 )));
             }
 
-            // Try to find the element with a short timeout
-            match self.validate(Some(poll_interval)).await {
-                Ok(Some(element)) => {
-                    // Element exists, now check the specific condition
-                    let condition_met = match condition {
-                        WaitCondition::Exists => true,
-                        WaitCondition::Visible => element.is_visible().unwrap_or(false),
-                        WaitCondition::Enabled => element.is_enabled().unwrap_or(false),
-                        WaitCondition::Focused => element.is_focused().unwrap_or(false),
-                    };
-
-                    if condition_met {
+            match self.validate(Some(Duration::ZERO)).await {
+                Ok(found) => {
+                    last_error = None;
+                    if let Some(value) = check(found).await {
                         debug!(
-                            "Condition {:?} met for selector {} after {:?}",
-                            condition,
+                            "Condition {} met for selector {} after {:?}",
+                            description,
                             self.selector_string(),
                             start_time.elapsed()
                         );
-                        return Ok(element);
+                        return Ok(value);
                     }
-                    // Condition not met yet, continue polling
                 }
-                Ok(None) => {
-                    // Element doesn't exist yet, continue polling
-                }
-                Err(e) => {
-                    // Platform error or invalid selector
-                    return Err(e);
+                // The tree can be mid-update (an element going away while it is read)
+                Err(e) if is_transient(&e) => {
+                    debug!(
+                        "Transient error waiting for {}, retrying: {e}",
+                        self.selector_string()
+                    );
+                    last_error = Some(e);
                 }
+                Err(e) => return Err(e),
             }
 
-            // Wait before the next poll
-            tokio::time::sleep(poll_interval).await;
+            waiter
+                .wait(effective_timeout.saturating_sub(start_time.elapsed()))
+                .await;
+        }
+    }
+
+    /// Subscribe to tree changes below the locator's root, or below the window of
+    /// the process the selector starts with when there is no root. Registering
+    /// handlers can block on some platforms, so it runs off the async runtime.
+    async fn change_waiter(&self) -> ChangeWaiter {
+        let engine = self.engine.clone();
+        let root = self.root.clone();
+        let process = match &self.selector {
+            Selector::Chain(parts) => parts.first(),
+            selector => Some(selector),
+        }
+        .filter(|selector| matches!(selector, Selector::Process(_)))
+        .cloned();
+        task::spawn_blocking(move || {
+            let scope = root.or_else(|| {
+                process.and_then(|process| {
+                    engine
+                        .find_element(&process, None, Some(Duration::ZERO))
+                        .ok()
+                })
+            });
+            ChangeWaiter::new(engine.as_ref(), scope.as_ref())
+        })
+        .await
+        .unwrap_or_else(|_| ChangeWaiter::polling())
+    }
+
+    /// Number of elements currently matching this locator, without waiting
+    pub async fn count(&self) -> Result<usize, AutomationError> {
+        match self.all(Some(Duration::ZERO), None).await {
+            Ok(elements) => Ok(elements.len()),
+            Err(AutomationError::ElementNotFound(_) | AutomationError::Timeout(_)) => Ok(0),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Wait until exactly `expected` elements match this locator
+    #[instrument(level = "debug", skip(self, timeout))]
+    pub async fn wait_for_count(
+        &self,
+        expected: usize,
+        timeout: Option<Duration>,
+    ) -> Result<(), AutomationError> {
+        let effective_timeout = timeout.unwrap_or(self.timeout);
+        let start_time = std::time::Instant::now();
+        let mut waiter = self.change_waiter().await;
+
+        loop {
+            let count = self.count().await?;
+            if count == expected {
+                return Ok(());
+            }
+            if start_time.elapsed() > effective_timeout {
+                return Err(AutomationError::Timeout(format!(
+                    "Timed out after {:?} waiting for {} to match {expected} elements (last count: {count})",
+                    effective_timeout,
+                    self.selector_string(),
+                )));
+            }
+            waiter
+                .wait(effective_timeout.saturating_sub(start_time.elapsed()))
+                .await;
         }
     }
 
@@ -247,25 +425,142 @@ impl Locator {
             s => new_chain.push(s),
         }
 
+        self.with_selector(Selector::Chain(new_chain))
+    }
+
+    /// A locator sharing this one's engine, timeout, root and filters
+    fn with_selector(&self, selector: Selector) -> Locator {
         Locator {
             engine: self.engine.clone(),
-            selector: Selector::Chain(new_chain),
+            selector,
             timeout: self.timeout,
             root: self.root.clone(),
+            filters: self.filters.clone(),
         }
     }
 
+    /// Require the matched elements to also satisfy `condition`. In a chain the
+    /// condition joins the last step (`a >> b && c`); after `nth`/`filter`
+    /// steps it applies to the whole selector.
+    fn narrow(&self, condition: Selector) -> Locator {
+        let selector = match self.selector.clone() {
+            Selector::Chain(mut steps) if steps.last().is_some_and(is_element_step) => {
+                let last = steps.pop().expect("checked above");
+                steps.push(conjoin(last, condition));
+                Selector::Chain(steps)
+            }
+            s if is_element_step(&s) => conjoin(s, condition),
+            s => Selector::And(vec![s, condition]),
+        };
+        self.with_selector(selector)
+    }
+
     /// Adds a filter to find elements based on their visibility.
     pub fn visible(&self, is_visible: bool) -> Locator {
         self.append_selector(Selector::Visible(is_visible))
     }
 
+    /// Keep only the elements for which `predicate` returns true, e.g.
+    /// `locator.filter(|el| Ok(el.bounds()?.2 > 100.0))`.
+    pub fn filter<F>(&self, predicate: F) -> Locator
+    where
+        F: Fn(&UIElement) -> Result<bool, AutomationError> + Send + Sync + 'static,
+    {
+        let handle = Arc::new(FilterHandle::register(predicate));
+        let mut locator = self.append_selector(Selector::Filter(handle.id()));
+        locator.filters.push(handle);
+        locator
+    }
+
+    /// Narrow the matches Playwright-style, e.g.
+    /// `locator.filter_by(LocatorFilter { has_text: Some("Total".into()), ..Default::default() })`.
+    /// Maps onto `text:`, `has:` and `!` selectors.
+    pub fn filter_by(&self, filter: LocatorFilter) -> Locator {
+        let mut locator = self.clone();
+        if let Some(text) = filter.has_text {
+            let text = Selector::Text(text);
+            locator = locator.narrow(Selector::Or(vec![
+                text.clone(),
+                Selector::Has(Box::new(text)),
+            ]));
+        }
+        if let Some(has) = filter.has {
+            locator = locator.narrow(Selector::Has(Box::new(has)));
+        }
+        if let Some(has_not) = filter.has_not {
+            locator = locator.narrow(Selector::Not(Box::new(Selector::Has(Box::new(has_not)))));
+        }
+        locator
+    }
+
+    /// Select the n-th match (0-based); negative indices count from the end
+    pub fn nth(&self, index: i32) -> Locator {
+        self.append_selector(Selector::Nth(index))
+    }
+
+    /// Select the last match
+    pub fn last(&self) -> Locator {
+        self.nth(-1)
+    }
+
+    /// Match elements matched by this locator or by `other`
+    pub fn or(&self, other: &Locator) -> Locator {
+        let mut parts = match self.selector.clone() {
+            Selector::Or(parts) => parts,
+            s => vec![s],
+        };
+        match other.selector.clone() {
+            Selector::Or(mut more) => parts.append(&mut more),
+            s => parts.push(s),
+        }
+        self.combined_with(other, Selector::Or(parts))
+    }
+
+    /// Match elements matched by both this locator and `other`
+    pub fn and(&self, other: &Locator) -> Locator {
+        let selector = conjoin(self.selector.clone(), other.selector.clone());
+        self.combined_with(other, selector)
+    }
+
+    fn combined_with(&self, other: &Locator, selector: Selector) -> Locator {
+        let mut locator = self.with_selector(selector);
+        locator.filters.extend(other.filters.iter().cloned());
+        locator
+    }
+
     /// Get a nested locator
     pub fn locator(&self, selector: impl Into<Selector>) -> Locator {
         self.append_selector(selector.into())
     }
 
+    /// The locator's selector in canonical selector syntax
     pub fn selector_string(&self) -> String {
-        format!("{:?}", self.selector)
+        self.selector.to_string()
+    }
+}
+
+/// Whether a selector matches elements by their own properties, so another
+/// condition can be and-ed onto it (as opposed to `nth`, `filter:` or `..` steps)
+fn is_element_step(selector: &Selector) -> bool {
+    !matches!(
+        selector,
+        Selector::Nth(_)
+            | Selector::Filter(_)
+            | Selector::Parent
+            | Selector::Chain(_)
+            | Selector::Invalid(_)
+    )
+}
+
+/// `a && b`, flattening existing conjunctions
+fn conjoin(a: Selector, b: Selector) -> Selector {
+    let mut parts = match a {
+        Selector::And(parts) => parts,
+        s => vec![s],
+    };
+    match b {
+        Selector::And(mut more) => parts.append(&mut more),
+        s => parts.push(s),
     }
+    Selector::And(parts)
 }
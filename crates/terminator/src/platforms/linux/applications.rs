@@ -0,0 +1,154 @@
+//! Application management and process-related functions for Linux
+
+use super::engine::LinuxEngine;
+use crate::platforms::AccessibilityEngine;
+use crate::{AutomationError, Browser, UIElement};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+pub use crate::platforms::KNOWN_BROWSER_PROCESS_NAMES;
+
+/// How long to wait for a freshly launched application to show up on the accessibility bus
+const LAUNCH_TIMEOUT: Duration = Duration::from_millis(10000);
+const LAUNCH_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Get process name by PID
+pub fn get_process_name_by_pid(pid: i32) -> Result<String, AutomationError> {
+    let mut system = sysinfo::System::new();
+    let pid = sysinfo::Pid::from_u32(pid as u32);
+    system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid]), true);
+    system
+        .process(pid)
+        .map(|p| p.name().to_string_lossy().to_string())
+        .ok_or_else(|| AutomationError::PlatformError(format!("Process {pid} not found")))
+}
+
+/// Check if a PID belongs to a known browser (see [`KNOWN_BROWSER_PROCESS_NAMES`])
+pub fn is_browser_process(pid: u32) -> bool {
+    get_process_name_by_pid(pid as i32).is_ok_and(|name| {
+        let name = name.to_lowercase();
+        KNOWN_BROWSER_PROCESS_NAMES
+            .iter()
+            .any(|&browser| name.contains(browser))
+    })
+}
+
+/// Launch a detached process, returning its PID
+fn spawn(program: &str, args: &[&str]) -> Result<u32, AutomationError> {
+    Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|child| child.id())
+        .map_err(|e| AutomationError::PlatformError(format!("Failed to launch '{program}': {e}")))
+}
+
+/// Wait until `find` returns an element or the launch timeout expires
+fn wait_for<F>(what: &str, mut find: F) -> Result<UIElement, AutomationError>
+where
+    F: FnMut() -> Option<UIElement>,
+{
+    let deadline = Instant::now() + LAUNCH_TIMEOUT;
+    loop {
+        if let Some(element) = find() {
+            return Ok(element);
+        }
+        if Instant::now() >= deadline {
+            return Err(AutomationError::Timeout(format!(
+                "Timed out waiting for {what} to appear on the accessibility bus"
+            )));
+        }
+        std::thread::sleep(LAUNCH_POLL_INTERVAL);
+    }
+}
+
+/// Launch an application by command name (e.g. `gedit`, `gnome-calculator`) and return its window
+pub fn open_application(
+    engine: &LinuxEngine,
+    app_name: &str,
+) -> Result<UIElement, AutomationError> {
+    info!("Opening application on Linux: {}", app_name);
+    let mut parts = app_name.split_whitespace();
+    let program = parts.next().ok_or_else(|| {
+        AutomationError::InvalidArgument("Application name cannot be empty".to_string())
+    })?;
+    let args: Vec<&str> = parts.collect();
+    let pid = spawn(program, &args)?;
+    debug!("Launched '{}' with PID {}", program, pid);
+
+    let process_name = std::path::Path::new(program)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(program)
+        .to_string();
+
+    // Single-instance applications hand the request over to an existing process,
+    // so fall back to looking the application up by name
+    let app = wait_for(&format!("application '{app_name}'"), || {
+        engine
+            .get_application_by_pid(pid as i32, Some(Duration::ZERO))
+            .or_else(|_| engine.get_application_by_name(&process_name))
+            .ok()
+    })?;
+    let window = engine.first_window(&app).unwrap_or(app);
+    if let Err(e) = window.activate_window() {
+        debug!("Failed to activate window of '{}': {}", app_name, e);
+    }
+    Ok(window)
+}
+
+/// Command used to open URLs in the requested browser
+fn browser_command(browser: &Browser) -> &str {
+    match browser {
+        Browser::Default => "xdg-open",
+        Browser::Chrome => "google-chrome",
+        Browser::Firefox => "firefox",
+        Browser::Edge => "microsoft-edge",
+        Browser::Brave => "brave-browser",
+        Browser::Opera => "opera",
+        Browser::Vivaldi => "vivaldi",
+        Browser::Custom(command) => command.as_str(),
+    }
+}
+
+/// Open a URL and return the browser window that shows it
+pub fn open_url(
+    engine: &LinuxEngine,
+    url: &str,
+    browser: Option<Browser>,
+) -> Result<UIElement, AutomationError> {
+    let browser = browser.unwrap_or(Browser::Default);
+    info!("Opening URL on Linux: {} (browser: {:?})", url, browser);
+    spawn(browser_command(&browser), &[url])?;
+
+    wait_for(&format!("a browser window for '{url}'"), || {
+        engine
+            .get_applications()
+            .ok()?
+            .into_iter()
+            .find(|app| app.process_id().is_ok_and(is_browser_process))
+            .map(|app| engine.first_window(&app).unwrap_or(app))
+    })
+}
+
+/// Open a file with its default application (`xdg-open`)
+pub fn open_file(file_path: &str) -> Result<(), AutomationError> {
+    info!("Opening file on Linux: {}", file_path);
+    let status = Command::new("xdg-open")
+        .arg(file_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| AutomationError::PlatformError(format!("Failed to run xdg-open: {e}")))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(AutomationError::PlatformError(format!(
+            "xdg-open failed to open '{file_path}' ({status})"
+        )))
+    }
+}
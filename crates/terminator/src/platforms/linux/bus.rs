@@ -0,0 +1,181 @@
+//! Connection to the AT-SPI2 accessibility bus
+
+use crate::AutomationError;
+use atspi_common::object_ref::ObjectRefOwned;
+use atspi_proxies::accessible::AccessibleProxyBlocking;
+use atspi_proxies::bus::{BusProxyBlocking, StatusProxyBlocking};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+use zbus::blocking::Connection;
+use zbus::proxy::CacheProperties;
+
+/// Well-known name of the AT-SPI registry daemon, which owns the desktop root
+pub(crate) const REGISTRY_BUS_NAME: &str = "org.a11y.atspi.Registry";
+/// Object path of the desktop root (and of every application's root accessible)
+pub(crate) const ROOT_PATH: &str = "/org/a11y/atspi/accessible/root";
+
+/// D-Bus errors that mean the remote object (or its whole application) is gone
+const DETACHED_ERRORS: &[&str] = &[
+    "org.freedesktop.DBus.Error.UnknownObject",
+    "org.freedesktop.DBus.Error.ServiceUnknown",
+    "org.freedesktop.DBus.Error.NameHasNoOwner",
+];
+
+/// Address of a remote accessible: the owning application's unique bus name plus object path
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct AccessibleRef {
+    pub bus_name: String,
+    pub path: String,
+}
+
+impl AccessibleRef {
+    /// The desktop root exposed by the registry
+    pub(crate) fn desktop() -> Self {
+        Self {
+            bus_name: REGISTRY_BUS_NAME.to_string(),
+            path: ROOT_PATH.to_string(),
+        }
+    }
+
+    /// Convert a reference returned by AT-SPI, `None` for the null reference
+    pub(crate) fn from_object_ref(object: &ObjectRefOwned) -> Option<Self> {
+        let bus_name = object.name_as_str()?;
+        let path = object.path_as_str();
+        if object.is_null() || bus_name.is_empty() || path.ends_with("/null") {
+            return None;
+        }
+        Some(Self {
+            bus_name: bus_name.to_string(),
+            path: path.to_string(),
+        })
+    }
+
+    pub(crate) fn is_desktop(&self) -> bool {
+        self.bus_name == REGISTRY_BUS_NAME && self.path == ROOT_PATH
+    }
+}
+
+/// Shared connection to the accessibility bus
+#[derive(Debug, Clone)]
+pub(crate) struct AtspiBus {
+    connection: Connection,
+    /// Process IDs by application bus name, resolved once per application
+    pids: Arc<Mutex<HashMap<String, u32>>>,
+}
+
+impl AtspiBus {
+    /// Connect to the accessibility bus.
+    ///
+    /// Uses `AT_SPI_BUS_ADDRESS` when set, otherwise asks `org.a11y.Bus` on the session
+    /// bus for the address (starting `at-spi-bus-launcher` through D-Bus activation).
+    pub(crate) fn connect() -> Result<Self, AutomationError> {
+        let address = match std::env::var("AT_SPI_BUS_ADDRESS") {
+            Ok(address) if !address.is_empty() => address,
+            _ => Self::address_from_session_bus()?,
+        };
+        debug!("Connecting to AT-SPI bus at {}", address);
+
+        let connection = zbus::blocking::connection::Builder::address(address.as_str())
+            .and_then(|builder| builder.build())
+            .map_err(|e| {
+                AutomationError::PlatformError(format!(
+                    "Failed to connect to the accessibility bus at {address}: {e}"
+                ))
+            })?;
+
+        Ok(Self {
+            connection,
+            pids: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    fn address_from_session_bus() -> Result<String, AutomationError> {
+        let session = Connection::session().map_err(|e| {
+            AutomationError::PlatformError(format!(
+                "Failed to connect to the D-Bus session bus (is DBUS_SESSION_BUS_ADDRESS set?): {e}"
+            ))
+        })?;
+
+        // Toolkits (Qt in particular) only export their tree once accessibility is enabled
+        match StatusProxyBlocking::new(&session) {
+            Ok(status) => {
+                if !status.is_enabled().unwrap_or(false) {
+                    if let Err(e) = status.set_is_enabled(true) {
+                        warn!("Failed to enable accessibility on the session bus: {}", e);
+                    }
+                }
+            }
+            Err(e) => warn!("org.a11y.Status is not available: {}", e),
+        }
+
+        BusProxyBlocking::new(&session)
+            .and_then(|bus| bus.get_address())
+            .map_err(|e| {
+                AutomationError::PlatformError(format!(
+                    "Failed to get the accessibility bus address (is at-spi2-core installed?): {e}"
+                ))
+            })
+    }
+
+    /// Build a blocking proxy of interface `T` for the given accessible
+    pub(crate) fn proxy<'a, T>(&self, node: &'a AccessibleRef) -> Result<T, AutomationError>
+    where
+        T: From<zbus::Proxy<'a>> + zbus::proxy::Defaults,
+    {
+        zbus::blocking::proxy::Builder::<T>::new(&self.connection)
+            .destination(node.bus_name.as_str())
+            .and_then(|builder| builder.path(node.path.as_str()))
+            .map(|builder| builder.cache_properties(CacheProperties::No))
+            .and_then(|builder| builder.build())
+            .map_err(|e| dbus_error("Failed to create accessibility proxy", e))
+    }
+
+    pub(crate) fn accessible<'a>(
+        &self,
+        node: &'a AccessibleRef,
+    ) -> Result<AccessibleProxyBlocking<'a>, AutomationError> {
+        self.proxy(node)
+    }
+
+    /// Process ID of the application owning `bus_name`
+    pub(crate) fn process_id(&self, bus_name: &str) -> Result<u32, AutomationError> {
+        if let Some(pid) = self.pids.lock().ok().and_then(|p| p.get(bus_name).copied()) {
+            return Ok(pid);
+        }
+
+        let name = zbus::names::BusName::try_from(bus_name)
+            .map_err(|e| AutomationError::PlatformError(format!("Invalid bus name: {e}")))?;
+        let pid = zbus::blocking::fdo::DBusProxy::new(&self.connection)
+            .and_then(|dbus| {
+                dbus.get_connection_unix_process_id(name)
+                    .map_err(Into::into)
+            })
+            .map_err(|e| dbus_error("Failed to get process ID of application", e))?;
+
+        if let Ok(mut pids) = self.pids.lock() {
+            pids.insert(bus_name.to_string(), pid);
+        }
+        Ok(pid)
+    }
+}
+
+/// Map a D-Bus error onto `AutomationError`, reporting vanished objects as detached
+pub(crate) fn dbus_error(context: &str, error: zbus::Error) -> AutomationError {
+    let detached = match &error {
+        zbus::Error::MethodError(name, _, _) => DETACHED_ERRORS.contains(&name.as_str()),
+        zbus::Error::FDO(fdo) => matches!(
+            fdo.as_ref(),
+            zbus::fdo::Error::UnknownObject(_)
+                | zbus::fdo::Error::ServiceUnknown(_)
+                | zbus::fdo::Error::NameHasNoOwner(_)
+        ),
+        _ => false,
+    };
+
+    if detached {
+        AutomationError::ElementDetached(format!("{context}: {error}"))
+    } else {
+        AutomationError::PlatformError(format!("{context}: {error}"))
+    }
+}
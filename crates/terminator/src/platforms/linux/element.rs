@@ -0,0 +1,949 @@
+//! Linux implementation of `UIElementImpl` over AT-SPI2
+
+use super::bus::{dbus_error, AccessibleRef, AtspiBus};
+use super::engine::LinuxEngine;
+use super::input;
+use super::roles::{is_enabled, is_showing, is_toggled, role_name};
+use super::search::Search;
+use super::x11;
+use crate::element::UIElementImpl;
+use crate::{
+    AutomationError, ClickResult, ClickType, FontStyle, HighlightHandle, Locator, ScreenshotResult,
+    Selector, TextPosition, UIElement, UIElementAttributes,
+};
+use atspi_common::{CoordType, Interface, InterfaceSet, Role, State, StateSet};
+use atspi_proxies::action::ActionProxyBlocking;
+use atspi_proxies::component::ComponentProxyBlocking;
+use atspi_proxies::document::DocumentProxyBlocking;
+use atspi_proxies::editable_text::EditableTextProxyBlocking;
+use atspi_proxies::selection::SelectionProxyBlocking;
+use atspi_proxies::text::TextProxyBlocking;
+use atspi_proxies::value::ValueProxyBlocking;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use tracing::debug;
+
+/// Action names toolkits use for the default "activate this control" action
+const DEFAULT_ACTIONS: &[&str] = &["click", "press", "activate", "jump", "toggle", "open"];
+/// How far below a combo box or list its options are searched for
+const OPTION_SEARCH_DEPTH: usize = 4;
+/// How far below a browser window its document is searched for
+const DOCUMENT_SEARCH_DEPTH: usize = 12;
+
+/// Stable identity of an accessible, shared by every handle to it
+pub(crate) fn object_id(node: &AccessibleRef) -> usize {
+    let mut hasher = DefaultHasher::new();
+    node.hash(&mut hasher);
+    hasher.finish() as usize
+}
+
+/// Short element id, derived from the object id like on Windows
+pub(crate) fn element_id(node: &AccessibleRef) -> String {
+    object_id(node).to_string().chars().take(6).collect()
+}
+
+/// Screen bounds of an accessible as (x, y, width, height)
+pub(crate) fn read_bounds(
+    bus: &AtspiBus,
+    node: &AccessibleRef,
+) -> Result<(f64, f64, f64, f64), AutomationError> {
+    let (x, y, w, h) = bus
+        .proxy::<ComponentProxyBlocking>(node)?
+        .get_extents(CoordType::Screen)
+        .map_err(|e| dbus_error("Failed to get element bounds", e))?;
+    Ok((x as f64, y as f64, w as f64, h as f64))
+}
+
+/// Value of an element: the contents of editable text, or the number of a range control
+pub(crate) fn read_value(
+    bus: &AtspiBus,
+    node: &AccessibleRef,
+) -> Result<Option<String>, AutomationError> {
+    let interfaces = bus
+        .accessible(node)?
+        .get_interfaces()
+        .map_err(|e| dbus_error("Failed to get element interfaces", e))?;
+    if interfaces.contains(Interface::EditableText) && interfaces.contains(Interface::Text) {
+        let text = bus
+            .proxy::<TextProxyBlocking>(node)?
+            .get_text(0, -1)
+            .map_err(|e| dbus_error("Failed to read text", e))?;
+        return Ok(Some(text));
+    }
+    if interfaces.contains(Interface::Value) {
+        let value = bus
+            .proxy::<ValueProxyBlocking>(node)?
+            .current_value()
+            .map_err(|e| dbus_error("Failed to read value", e))?;
+        return Ok(Some(value.to_string()));
+    }
+    Ok(None)
+}
+
+/// A live handle to an accessible object of a running application
+#[derive(Debug, Clone)]
+pub struct LinuxUIElement {
+    bus: AtspiBus,
+    node: AccessibleRef,
+}
+
+impl LinuxUIElement {
+    pub(crate) fn new(bus: AtspiBus, node: AccessibleRef) -> Self {
+        Self { bus, node }
+    }
+
+    pub(crate) fn node(&self) -> &AccessibleRef {
+        &self.node
+    }
+
+    fn element(&self, node: AccessibleRef) -> UIElement {
+        UIElement::new(Box::new(Self::new(self.bus.clone(), node)))
+    }
+
+    fn states(&self) -> Result<StateSet, AutomationError> {
+        self.bus
+            .accessible(&self.node)?
+            .get_state()
+            .map_err(|e| dbus_error("Failed to get element state", e))
+    }
+
+    fn atspi_role(&self) -> Result<Role, AutomationError> {
+        self.bus
+            .accessible(&self.node)?
+            .get_role()
+            .map_err(|e| dbus_error("Failed to get element role", e))
+    }
+
+    fn interfaces(&self) -> InterfaceSet {
+        self.bus
+            .accessible(&self.node)
+            .ok()
+            .and_then(|a| a.get_interfaces().ok())
+            .unwrap_or_default()
+    }
+
+    fn parent_ref(&self) -> Option<AccessibleRef> {
+        if self.node.is_desktop() {
+            return None;
+        }
+        let parent = self.bus.accessible(&self.node).ok()?.parent().ok()?;
+        AccessibleRef::from_object_ref(&parent)
+    }
+
+    fn index_in_parent(&self) -> Result<i32, AutomationError> {
+        self.bus
+            .accessible(&self.node)?
+            .get_index_in_parent()
+            .map_err(|e| dbus_error("Failed to get index in parent", e))
+    }
+
+    /// Top-level window containing this element (the child of its application)
+    fn window_ref(&self) -> Option<AccessibleRef> {
+        if self.node.is_desktop() {
+            return None;
+        }
+        let mut current = self.node.clone();
+        loop {
+            let parent = Self::new(self.bus.clone(), current.clone()).parent_ref()?;
+            let parent_is_app = self
+                .bus
+                .accessible(&parent)
+                .ok()
+                .and_then(|a| a.get_role().ok())
+                .is_some_and(|r| r == Role::Application);
+            if parent_is_app {
+                return Some(current);
+            }
+            if parent.is_desktop() {
+                // `self` is an application: its first window stands in for it
+                let app = Self::new(self.bus.clone(), current);
+                return app.children_refs().ok()?.into_iter().next();
+            }
+            current = parent;
+        }
+    }
+
+    fn children_refs(&self) -> Result<Vec<AccessibleRef>, AutomationError> {
+        let children = self
+            .bus
+            .accessible(&self.node)?
+            .get_children()
+            .map_err(|e| dbus_error("Failed to get children", e))?;
+        Ok(children
+            .iter()
+            .filter_map(AccessibleRef::from_object_ref)
+            .collect())
+    }
+
+    /// X11 top-level window of the element's window, matched by PID and title
+    fn x11_window(&self) -> Result<(Arc<x11::X11Display>, u32), AutomationError> {
+        let window = self.window_ref().ok_or_else(|| {
+            AutomationError::ElementNotFound("Element has no containing window".to_string())
+        })?;
+        let title = self.bus.accessible(&window)?.name().ok();
+        let pid = self.process_id()?;
+        let display = x11::display()?;
+        let x_window = display.find_window(pid, title.as_deref())?.ok_or_else(|| {
+            AutomationError::ElementNotFound(format!(
+                "No X11 window found for PID {pid} and title {title:?}"
+            ))
+        })?;
+        Ok((display, x_window))
+    }
+
+    /// Ensure the element can receive pointer input, like the Windows `validate_clickable`
+    fn validate_clickable(&self) -> Result<(), AutomationError> {
+        let states = self.states()?;
+        if !is_enabled(states) {
+            return Err(AutomationError::ElementNotEnabled(format!(
+                "Element '{}' is disabled",
+                self.name().unwrap_or_default()
+            )));
+        }
+        if !is_showing(states) {
+            return Err(AutomationError::ElementNotVisible(format!(
+                "Element '{}' is not showing",
+                self.name().unwrap_or_default()
+            )));
+        }
+        Ok(())
+    }
+
+    fn center(&self) -> Result<(f64, f64), AutomationError> {
+        let (x, y, w, h) = self.bounds()?;
+        if w <= 0.0 || h <= 0.0 {
+            return Err(AutomationError::ElementNotVisible(
+                "Element has no on-screen area".to_string(),
+            ));
+        }
+        Ok((x + w / 2.0, y + h / 2.0))
+    }
+
+    fn mouse_click(
+        &self,
+        click_type: ClickType,
+        method: &str,
+    ) -> Result<ClickResult, AutomationError> {
+        self.validate_clickable()?;
+        let (x, y) = self.center()?;
+        input::send_mouse_click(x, y, click_type, false)?;
+        Ok(ClickResult {
+            method: method.to_string(),
+            coordinates: Some((x, y)),
+            details: "Clicked by Mouse".to_string(),
+        })
+    }
+
+    /// Index of the AT-SPI action called `name` (case-insensitive)
+    fn action_index(&self, names: &[&str]) -> Result<Option<i32>, AutomationError> {
+        let action = self.bus.proxy::<ActionProxyBlocking>(&self.node)?;
+        let count = action.nactions().unwrap_or(0);
+        for i in 0..count {
+            if let Ok(name) = action.get_name(i) {
+                if names.iter().any(|n| n.eq_ignore_ascii_case(&name)) {
+                    return Ok(Some(i));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn do_action(&self, index: i32) -> Result<(), AutomationError> {
+        let done = self
+            .bus
+            .proxy::<ActionProxyBlocking>(&self.node)?
+            .do_action(index)
+            .map_err(|e| dbus_error("Failed to perform action", e))?;
+        if done {
+            Ok(())
+        } else {
+            Err(AutomationError::PlatformError(
+                "Application refused to perform the action".to_string(),
+            ))
+        }
+    }
+
+    fn prepare_for_input(&self, try_focus_before: bool, try_click_before: bool) {
+        if try_focus_before && self.focus().is_ok() {
+            return;
+        }
+        if try_click_before {
+            if let Err(e) = self.click() {
+                debug!("Failed to click element before input: {}", e);
+            }
+        }
+    }
+
+    /// Selectable options below a combo box or list, in document order
+    fn option_refs(&self) -> Vec<(AccessibleRef, String)> {
+        let mut search = Search::new(&self.bus);
+        let scope = search.add(self.node.clone());
+        search
+            .descendants(scope, Some(OPTION_SEARCH_DEPTH))
+            .into_iter()
+            .filter_map(|id| {
+                let info = search.info(id)?;
+                matches!(
+                    info.role,
+                    Role::ListItem
+                        | Role::MenuItem
+                        | Role::CheckMenuItem
+                        | Role::RadioMenuItem
+                        | Role::TableCell
+                        | Role::TreeItem
+                )
+                .then(|| (search.node(id).clone(), info.name))
+            })
+            .filter(|(_, name)| !name.is_empty())
+            .collect()
+    }
+
+    /// Change selection through the parent's Selection interface
+    fn select_in_parent(&self, select: bool) -> Result<bool, AutomationError> {
+        let Some(parent) = self.parent_ref() else {
+            return Ok(false);
+        };
+        let parent_element = Self::new(self.bus.clone(), parent.clone());
+        if !parent_element.interfaces().contains(Interface::Selection) {
+            return Ok(false);
+        }
+        let index = self.index_in_parent()?;
+        let selection = self.bus.proxy::<SelectionProxyBlocking>(&parent)?;
+        let done = if select {
+            selection.select_child(index)
+        } else {
+            selection.deselect_child(index)
+        }
+        .map_err(|e| dbus_error("Failed to change selection", e))?;
+        Ok(done)
+    }
+
+    fn document_url(&self, node: &AccessibleRef) -> Option<String> {
+        self.bus
+            .proxy::<DocumentProxyBlocking>(node)
+            .ok()?
+            .get_attribute_value("DocURL")
+            .ok()
+            .filter(|url| !url.is_empty())
+    }
+
+    fn text_contents(&self, node: &AccessibleRef) -> Option<String> {
+        self.bus
+            .proxy::<TextProxyBlocking>(node)
+            .ok()?
+            .get_text(0, -1)
+            .ok()
+            .filter(|text| !text.trim().is_empty())
+    }
+}
+
+impl UIElementImpl for LinuxUIElement {
+    fn object_id(&self) -> usize {
+        object_id(&self.node)
+    }
+
+    fn id(&self) -> Option<String> {
+        Some(element_id(&self.node))
+    }
+
+    fn role(&self) -> String {
+        if self.node.is_desktop() {
+            return "Desktop".to_string();
+        }
+        match (self.atspi_role(), self.states()) {
+            (Ok(role), Ok(states)) => role_name(role, states).to_string(),
+            (Ok(role), Err(_)) => role_name(role, StateSet::empty()).to_string(),
+            _ => "Unknown".to_string(),
+        }
+    }
+
+    fn attributes(&self) -> UIElementAttributes {
+        let Ok(accessible) = self.bus.accessible(&self.node) else {
+            return UIElementAttributes::default();
+        };
+        let states = accessible.get_state().unwrap_or_default();
+        let role = self.role();
+        let interfaces = accessible.get_interfaces().unwrap_or_default();
+
+        let mut properties: HashMap<String, Option<serde_json::Value>> = HashMap::new();
+        if let Ok(id) = accessible.accessible_id() {
+            if !id.is_empty() {
+                properties.insert("AutomationId".to_string(), Some(id.into()));
+            }
+        }
+        if let Ok(localized) = accessible.get_localized_role_name() {
+            properties.insert("LocalizedControlType".to_string(), Some(localized.into()));
+        }
+        if let Ok(object_attributes) = accessible.get_attributes() {
+            for (key, value) in object_attributes {
+                properties.entry(key).or_insert(Some(value.into()));
+            }
+        }
+
+        let application_name = accessible
+            .get_application()
+            .ok()
+            .and_then(|app| AccessibleRef::from_object_ref(&app))
+            .and_then(|app| self.bus.accessible(&app).ok()?.name().ok());
+        let checkable = states.contains(State::Checkable)
+            || matches!(role.as_str(), "CheckBox" | "RadioButton");
+
+        UIElementAttributes {
+            role,
+            name: accessible.name().ok().filter(|n| !n.is_empty()),
+            label: None,
+            text: None,
+            value: read_value(&self.bus, &self.node).ok().flatten(),
+            description: accessible.description().ok().filter(|d| !d.is_empty()),
+            application_name,
+            properties,
+            is_keyboard_focusable: Some(states.contains(State::Focusable)),
+            is_focused: Some(states.contains(State::Focused)),
+            is_toggled: checkable.then(|| is_toggled(states)),
+            bounds: interfaces
+                .contains(Interface::Component)
+                .then(|| self.bounds().ok())
+                .flatten(),
+            enabled: Some(is_enabled(states)),
+            is_selected: states
+                .contains(State::Selectable)
+                .then(|| states.contains(State::Selected)),
+            child_count: accessible.child_count().ok().map(|c| c.max(0) as usize),
+            index_in_parent: accessible
+                .get_index_in_parent()
+                .ok()
+                .filter(|&i| i >= 0)
+                .map(|i| i as usize),
+        }
+    }
+
+    fn name(&self) -> Option<String> {
+        self.bus.accessible(&self.node).ok()?.name().ok()
+    }
+
+    fn children(&self) -> Result<Vec<UIElement>, AutomationError> {
+        Ok(self
+            .children_refs()?
+            .into_iter()
+            .map(|node| self.element(node))
+            .collect())
+    }
+
+    fn parent(&self) -> Result<Option<UIElement>, AutomationError> {
+        Ok(self.parent_ref().map(|node| self.element(node)))
+    }
+
+    fn bounds(&self) -> Result<(f64, f64, f64, f64), AutomationError> {
+        read_bounds(&self.bus, &self.node)
+    }
+
+    fn click(&self) -> Result<ClickResult, AutomationError> {
+        match self.mouse_click(ClickType::Left, "Single Click") {
+            Ok(result) => Ok(result),
+            // Off-screen elements can still be activated through their default action
+            Err(AutomationError::ElementNotVisible(reason)) => {
+                let index = self
+                    .action_index(DEFAULT_ACTIONS)?
+                    .ok_or_else(|| AutomationError::ElementNotVisible(reason.clone()))?;
+                self.do_action(index)?;
+                Ok(ClickResult {
+                    method: "AT-SPI Action".to_string(),
+                    coordinates: None,
+                    details: format!("Element not on screen ({reason}); performed default action"),
+                })
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn double_click(&self) -> Result<ClickResult, AutomationError> {
+        self.mouse_click(ClickType::Double, "Double Click")
+    }
+
+    fn right_click(&self) -> Result<(), AutomationError> {
+        self.mouse_click(ClickType::Right, "Right Click")
+            .map(|_| ())
+    }
+
+    fn click_at_position(
+        &self,
+        x_pct: u8,
+        y_pct: u8,
+        click_type: ClickType,
+    ) -> Result<ClickResult, AutomationError> {
+        self.validate_clickable()?;
+        let (x, y, w, h) = self.bounds()?;
+        let click_x = x + w * x_pct.min(100) as f64 / 100.0;
+        let click_y = y + h * y_pct.min(100) as f64 / 100.0;
+        input::send_mouse_click(click_x, click_y, click_type, false)?;
+        Ok(ClickResult {
+            method: format!("PositionClick({x_pct}%, {y_pct}%)"),
+            coordinates: Some((click_x, click_y)),
+            details: format!("{click_type:?} click"),
+        })
+    }
+
+    fn hover(&self) -> Result<(), AutomationError> {
+        let (x, y) = self.center()?;
+        input::mouse_move(x, y)
+    }
+
+    fn focus(&self) -> Result<(), AutomationError> {
+        let focused = self
+            .bus
+            .proxy::<ComponentProxyBlocking>(&self.node)?
+            .grab_focus()
+            .map_err(|e| dbus_error("Failed to focus element", e))?;
+        if focused {
+            Ok(())
+        } else {
+            Err(AutomationError::PlatformError(format!(
+                "Element '{}' did not accept focus",
+                self.name().unwrap_or_default()
+            )))
+        }
+    }
+
+    fn invoke(&self) -> Result<(), AutomationError> {
+        let index = self.action_index(DEFAULT_ACTIONS)?.ok_or_else(|| {
+            AutomationError::UnsupportedOperation(
+                "Element has no default action. Try using 'click' instead.".to_string(),
+            )
+        })?;
+        self.do_action(index)
+    }
+
+    fn type_text(
+        &self,
+        text: &str,
+        use_clipboard: bool,
+        try_focus_before: bool,
+        try_click_before: bool,
+        restore_focus: bool,
+    ) -> Result<(), AutomationError> {
+        if use_clipboard || restore_focus {
+            debug!(
+                "Clipboard typing and focus restore are not available on Linux; typing directly"
+            );
+        }
+        self.prepare_for_input(try_focus_before, try_click_before);
+        input::type_text(text)
+    }
+
+    fn press_key(
+        &self,
+        key: &str,
+        try_focus_before: bool,
+        try_click_before: bool,
+        _restore_focus: bool,
+    ) -> Result<(), AutomationError> {
+        self.prepare_for_input(try_focus_before, try_click_before);
+        input::send_keys(key)
+    }
+
+    fn get_text(&self, max_depth: usize) -> Result<String, AutomationError> {
+        let mut search = Search::new(&self.bus);
+        let scope = search.add(self.node.clone());
+        let mut texts = Vec::new();
+        let nodes = std::iter::once(scope).chain(search.descendants(scope, Some(max_depth)));
+        for id in nodes.collect::<Vec<_>>() {
+            let node = search.node(id).clone();
+            if let Some(text) = self.text_contents(&node) {
+                texts.push(text);
+            } else if search.children(id).is_empty() {
+                // Leaves without a Text interface (icons, buttons) still carry a name
+                if let Some(info) = search.info(id).filter(|i| !i.name.is_empty()) {
+                    texts.push(info.name);
+                }
+            }
+        }
+        Ok(texts.join("\n"))
+    }
+
+    fn set_value(&self, value: &str) -> Result<(), AutomationError> {
+        let interfaces = self.interfaces();
+        if interfaces.contains(Interface::EditableText) {
+            let done = self
+                .bus
+                .proxy::<EditableTextProxyBlocking>(&self.node)?
+                .set_text_contents(value)
+                .map_err(|e| dbus_error("Failed to set text", e))?;
+            return if done {
+                Ok(())
+            } else {
+                Err(AutomationError::PlatformError(
+                    "Application refused to change the text".to_string(),
+                ))
+            };
+        }
+        if interfaces.contains(Interface::Value) {
+            let number: f64 = value.trim().parse().map_err(|_| {
+                AutomationError::InvalidArgument(format!(
+                    "Element holds a numeric value; '{value}' is not a number"
+                ))
+            })?;
+            return self.set_range_value(number);
+        }
+        Err(AutomationError::UnsupportedOperation(
+            "Element supports neither editable text nor a value".to_string(),
+        ))
+    }
+
+    fn get_value(&self) -> Result<Option<String>, AutomationError> {
+        read_value(&self.bus, &self.node)
+    }
+
+    fn is_enabled(&self) -> Result<bool, AutomationError> {
+        Ok(is_enabled(self.states()?))
+    }
+
+    fn is_visible(&self) -> Result<bool, AutomationError> {
+        Ok(is_showing(self.states()?))
+    }
+
+    fn is_focused(&self) -> Result<bool, AutomationError> {
+        Ok(self.states()?.contains(State::Focused))
+    }
+
+    fn perform_action(&self, action: &str) -> Result<(), AutomationError> {
+        match action {
+            "focus" => self.focus(),
+            "invoke" => self.invoke(),
+            "click" => self.click().map(|_| ()),
+            "double_click" => self.double_click().map(|_| ()),
+            "right_click" => self.right_click(),
+            "toggle" => {
+                let index = self.action_index(&["toggle", "click", "press"])?;
+                match index {
+                    Some(index) => self.do_action(index),
+                    None => Err(AutomationError::UnsupportedOperation(
+                        "Element is not toggleable. Try using 'click' instead.".to_string(),
+                    )),
+                }
+            }
+            "expand_collapse" => {
+                let index = self.action_index(&["expand or contract", "expand", "collapse"])?;
+                match index {
+                    Some(index) => self.do_action(index),
+                    None => Err(AutomationError::UnsupportedOperation(
+                        "Element cannot be expanded or collapsed".to_string(),
+                    )),
+                }
+            }
+            "scroll_into_view" => {
+                self.bus
+                    .proxy::<ComponentProxyBlocking>(&self.node)?
+                    .scroll_to(atspi_common::ScrollType::Anywhere)
+                    .map_err(|e| dbus_error("Failed to scroll element into view", e))?;
+                Ok(())
+            }
+            // Anything else is looked up among the element's own AT-SPI actions
+            other => match self.action_index(&[other])? {
+                Some(index) => self.do_action(index),
+                None => Err(AutomationError::UnsupportedOperation(format!(
+                    "Action '{other}' is not supported by this element"
+                ))),
+            },
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn create_locator(&self, selector: Selector) -> Result<Locator, AutomationError> {
+        let engine = LinuxEngine::with_bus(self.bus.clone());
+        Ok(Locator::new(Arc::new(engine), selector).within(UIElement::new(self.clone_box())))
+    }
+
+    fn scroll(&self, direction: &str, amount: f64) -> Result<(), AutomationError> {
+        // The wheel scrolls whatever is under the pointer
+        if let Ok((x, y)) = self.center() {
+            input::mouse_move(x, y)?;
+        }
+        input::scroll_wheel(direction, amount)
+    }
+
+    fn activate_window(&self) -> Result<(), AutomationError> {
+        match self.x11_window() {
+            Ok((display, window)) => display.activate(window),
+            Err(e) => {
+                // Without a matching X11 window (e.g. Wayland), ask the toolkit for focus
+                debug!("Falling back to grab_focus for activation: {}", e);
+                let window = self.window_ref().ok_or(e)?;
+                Self::new(self.bus.clone(), window).focus()
+            }
+        }
+    }
+
+    fn minimize_window(&self) -> Result<(), AutomationError> {
+        let (display, window) = self.x11_window()?;
+        display.minimize(window)
+    }
+
+    fn maximize_window(&self) -> Result<(), AutomationError> {
+        let (display, window) = self.x11_window()?;
+        display.maximize(window)
+    }
+
+    /// Window-manager shortcuts differ between desktops, so this uses the EWMH request
+    fn maximize_window_keyboard(&self) -> Result<(), AutomationError> {
+        self.maximize_window()
+    }
+
+    /// Window-manager shortcuts differ between desktops, so this uses the EWMH request
+    fn minimize_window_keyboard(&self) -> Result<(), AutomationError> {
+        self.minimize_window()
+    }
+
+    fn get_native_window_handle(&self) -> Result<isize, AutomationError> {
+        let (_, window) = self.x11_window()?;
+        Ok(window as isize)
+    }
+
+    fn clone_box(&self) -> Box<dyn UIElementImpl> {
+        Box::new(self.clone())
+    }
+
+    fn is_keyboard_focusable(&self) -> Result<bool, AutomationError> {
+        Ok(self.states()?.contains(State::Focusable))
+    }
+
+    fn is_editable(&self) -> Result<bool, AutomationError> {
+        Ok(self.states()?.contains(State::Editable))
+    }
+
+    fn mouse_drag(
+        &self,
+        start_x: f64,
+        start_y: f64,
+        end_x: f64,
+        end_y: f64,
+    ) -> Result<(), AutomationError> {
+        input::mouse_drag(start_x, start_y, end_x, end_y)
+    }
+
+    fn mouse_click_and_hold(&self, x: f64, y: f64) -> Result<(), AutomationError> {
+        input::mouse_move(x, y)?;
+        input::mouse_button(input::BUTTON_LEFT, true)
+    }
+
+    fn mouse_move(&self, x: f64, y: f64) -> Result<(), AutomationError> {
+        input::mouse_move(x, y)
+    }
+
+    fn mouse_release(&self) -> Result<(), AutomationError> {
+        input::mouse_button(input::BUTTON_LEFT, false)
+    }
+
+    fn application(&self) -> Result<Option<UIElement>, AutomationError> {
+        if self.node.is_desktop() {
+            return Ok(None);
+        }
+        let app = self
+            .bus
+            .accessible(&self.node)?
+            .get_application()
+            .map_err(|e| dbus_error("Failed to get application", e))?;
+        Ok(AccessibleRef::from_object_ref(&app).map(|node| self.element(node)))
+    }
+
+    fn window(&self) -> Result<Option<UIElement>, AutomationError> {
+        Ok(self.window_ref().map(|node| self.element(node)))
+    }
+
+    fn highlight(
+        &self,
+        _color: Option<u32>,
+        _duration: Option<std::time::Duration>,
+        _text: Option<&str>,
+        _text_position: Option<TextPosition>,
+        _font_style: Option<FontStyle>,
+    ) -> Result<HighlightHandle, AutomationError> {
+        Err(AutomationError::UnsupportedOperation(
+            "Highlighting is not supported on Linux".to_string(),
+        ))
+    }
+
+    fn set_transparency(&self, percentage: u8) -> Result<(), AutomationError> {
+        let (display, window) = self.x11_window()?;
+        display.set_opacity(window, percentage)
+    }
+
+    fn process_id(&self) -> Result<u32, AutomationError> {
+        if self.node.is_desktop() {
+            return Err(AutomationError::UnsupportedOperation(
+                "The desktop root has no process".to_string(),
+            ));
+        }
+        self.bus.process_id(&self.node.bus_name)
+    }
+
+    fn capture(&self) -> Result<ScreenshotResult, AutomationError> {
+        let (x, y, w, h) = self.bounds()?;
+        let monitors = xcap::Monitor::all()
+            .map_err(|e| AutomationError::PlatformError(format!("Failed to get monitors: {e}")))?;
+
+        // Capture from the monitor containing the element's top-left corner
+        for monitor in monitors {
+            let (mx, my) = (
+                monitor.x().unwrap_or_default() as f64,
+                monitor.y().unwrap_or_default() as f64,
+            );
+            let (mw, mh) = (
+                monitor.width().unwrap_or_default() as f64,
+                monitor.height().unwrap_or_default() as f64,
+            );
+            if x < mx || y < my || x >= mx + mw || y >= my + mh {
+                continue;
+            }
+            let rel_x = (x - mx) as u32;
+            let rel_y = (y - my) as u32;
+            let width = (w.min(mx + mw - x).max(1.0)) as u32;
+            let height = (h.min(my + mh - y).max(1.0)) as u32;
+            let capture = monitor
+                .capture_region(rel_x, rel_y, width, height)
+                .map_err(|e| {
+                    AutomationError::PlatformError(format!("Failed to capture region: {e}"))
+                })?;
+            return Ok(ScreenshotResult {
+                image_data: capture.to_vec(),
+                width,
+                height,
+                monitor: None,
+            });
+        }
+
+        Err(AutomationError::PlatformError(
+            "Element is not visible on any monitor".to_string(),
+        ))
+    }
+
+    fn close(&self) -> Result<(), AutomationError> {
+        let is_window = self
+            .atspi_role()
+            .is_ok_and(|r| matches!(r, Role::Frame | Role::Window | Role::Dialog | Role::Alert));
+        if !is_window && self.atspi_role().ok() != Some(Role::Application) {
+            // Not closable: buttons, text and the like
+            return Ok(());
+        }
+        let (display, window) = self.x11_window()?;
+        display.close(window)
+    }
+
+    fn url(&self) -> Option<String> {
+        if let Some(url) = self.document_url(&self.node) {
+            return Some(url);
+        }
+        let window = self.window_ref()?;
+        let mut search = Search::new(&self.bus);
+        let scope = search.add(window);
+        search
+            .query(
+                &Selector::Role {
+                    role: "Document".to_string(),
+                    name: None,
+                },
+                scope,
+                Some(DOCUMENT_SEARCH_DEPTH),
+            )
+            .ok()?
+            .into_iter()
+            .find_map(|id| self.document_url(search.node(id)))
+    }
+
+    fn select_option(&self, option_name: &str) -> Result<(), AutomationError> {
+        let wanted = option_name.to_lowercase();
+        let options = self.option_refs();
+        let (node, _) = options
+            .iter()
+            .find(|(_, name)| name.to_lowercase() == wanted)
+            .or_else(|| {
+                options
+                    .iter()
+                    .find(|(_, name)| name.to_lowercase().contains(&wanted))
+            })
+            .ok_or_else(|| {
+                AutomationError::ElementNotFound(format!(
+                    "Option '{option_name}' not found. Available options: {:?}",
+                    options.iter().map(|(_, n)| n).collect::<Vec<_>>()
+                ))
+            })?;
+
+        let option = Self::new(self.bus.clone(), node.clone());
+        if option.select_in_parent(true)? {
+            return Ok(());
+        }
+        option.invoke().or_else(|_| option.click().map(|_| ()))
+    }
+
+    fn list_options(&self) -> Result<Vec<String>, AutomationError> {
+        Ok(self
+            .option_refs()
+            .into_iter()
+            .map(|(_, name)| name)
+            .collect())
+    }
+
+    fn is_toggled(&self) -> Result<bool, AutomationError> {
+        Ok(is_toggled(self.states()?))
+    }
+
+    fn set_toggled(&self, state: bool) -> Result<(), AutomationError> {
+        if self.is_toggled()? == state {
+            return Ok(());
+        }
+        self.perform_action("toggle")
+            .or_else(|_| self.click().map(|_| ()))
+    }
+
+    fn get_range_value(&self) -> Result<f64, AutomationError> {
+        self.bus
+            .proxy::<ValueProxyBlocking>(&self.node)?
+            .current_value()
+            .map_err(|e| dbus_error("Failed to read value", e))
+    }
+
+    fn set_range_value(&self, value: f64) -> Result<(), AutomationError> {
+        let proxy = self.bus.proxy::<ValueProxyBlocking>(&self.node)?;
+        let (min, max) = (
+            proxy.minimum_value().unwrap_or(f64::MIN),
+            proxy.maximum_value().unwrap_or(f64::MAX),
+        );
+        if value < min || value > max {
+            return Err(AutomationError::InvalidArgument(format!(
+                "Value {value} is outside the range {min}..={max}"
+            )));
+        }
+        proxy
+            .set_current_value(value)
+            .map_err(|e| dbus_error("Failed to set value", e))
+    }
+
+    fn is_selected(&self) -> Result<bool, AutomationError> {
+        let states = self.states()?;
+        Ok(states.contains(State::Selected)
+            || (self.atspi_role()? == Role::RadioButton && states.contains(State::Checked)))
+    }
+
+    fn set_selected(&self, state: bool) -> Result<(), AutomationError> {
+        if self.is_selected()? == state {
+            return Ok(());
+        }
+        if self.select_in_parent(state)? {
+            return Ok(());
+        }
+        // Radio buttons and check boxes are selected by activating them
+        self.invoke().or_else(|_| self.click().map(|_| ()))
+    }
+}
+
+/// Build an element handle for the desktop root of `bus`
+pub(crate) fn desktop_element(bus: &AtspiBus) -> UIElement {
+    UIElement::new(Box::new(LinuxUIElement::new(
+        bus.clone(),
+        AccessibleRef::desktop(),
+    )))
+}
@@ -0,0 +1,648 @@
+//! Linux implementation of `AccessibilityEngine` over AT-SPI2
+
+use super::applications;
+use super::bus::{AccessibleRef, AtspiBus};
+use super::element::{desktop_element, LinuxUIElement};
+use super::input;
+use super::search::{NodeId, Search, DESKTOP};
+use super::tree_builder;
+use super::x11;
+use crate::platforms::{selector_has_process_scope, AccessibilityEngine, TreeBuildConfig};
+use crate::{
+    AutomationError, Browser, ClickType, CommandOutput, Monitor, ScreenshotResult, Selector,
+    UIElement, UINode, WorkAreaBounds,
+};
+use atspi_common::{Role, State};
+use image::{DynamicImage, ImageBuffer, Rgba};
+use std::time::{Duration, Instant};
+use tracing::debug;
+use uni_ocr::{OcrEngine, OcrProvider};
+
+/// Default time `find_element` keeps re-evaluating the tree before giving up
+const DEFAULT_FIND_TIMEOUT: Duration = Duration::from_millis(5000);
+/// How often the tree is re-evaluated while waiting for an element
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How deep below the active window the focused element is searched for
+const FOCUS_SEARCH_DEPTH: usize = 50;
+
+/// Accessibility engine backed by the AT-SPI2 accessibility bus
+#[derive(Debug, Clone)]
+pub struct LinuxEngine {
+    bus: AtspiBus,
+    // Kept for parity with the Windows engine: AT-SPI lists every application
+    // registered on the bus, and launched applications are always activated
+    #[allow(unused)]
+    use_background_apps: bool,
+    #[allow(unused)]
+    activate_app: bool,
+}
+
+impl LinuxEngine {
+    /// Connect to the accessibility bus of the current session
+    pub fn new(use_background_apps: bool, activate_app: bool) -> Result<Self, AutomationError> {
+        Ok(Self {
+            bus: AtspiBus::connect()?,
+            use_background_apps,
+            activate_app,
+        })
+    }
+
+    /// Engine sharing an existing bus connection (used by element-scoped locators)
+    pub(crate) fn with_bus(bus: AtspiBus) -> Self {
+        Self {
+            bus,
+            use_background_apps: false,
+            activate_app: false,
+        }
+    }
+
+    pub(crate) fn element(&self, node: AccessibleRef) -> UIElement {
+        UIElement::new(Box::new(LinuxUIElement::new(self.bus.clone(), node)))
+    }
+
+    /// Resolve the search root, rejecting elements from other platforms
+    fn scope(&self, root: Option<&UIElement>) -> Result<AccessibleRef, AutomationError> {
+        match root {
+            None => Ok(AccessibleRef::desktop()),
+            Some(el) => el
+                .as_any()
+                .downcast_ref::<LinuxUIElement>()
+                .map(|linux| linux.node().clone())
+                .ok_or_else(|| {
+                    AutomationError::PlatformError(
+                        "Search root is not an AT-SPI element".to_string(),
+                    )
+                }),
+        }
+    }
+
+    /// Re-run `query` against a fresh view of the tree until it returns something
+    /// or the timeout elapses
+    fn poll<F>(
+        &self,
+        timeout: Option<Duration>,
+        mut query: F,
+    ) -> Result<Vec<AccessibleRef>, AutomationError>
+    where
+        F: FnMut(&mut Search) -> Result<Vec<NodeId>, AutomationError>,
+    {
+        let deadline = Instant::now() + timeout.unwrap_or(DEFAULT_FIND_TIMEOUT);
+        loop {
+            let mut search = Search::new(&self.bus).with_engine(self);
+            let found = query(&mut search)?;
+            if !found.is_empty() || Instant::now() >= deadline {
+                return Ok(found
+                    .into_iter()
+                    .map(|id| search.node(id).clone())
+                    .collect());
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Applications whose process ID is `pid`
+    fn applications_of(search: &mut Search, pid: u32, bus: &AtspiBus) -> Vec<NodeId> {
+        search
+            .children(DESKTOP)
+            .into_iter()
+            .filter(|&app| {
+                bus.process_id(&search.node(app).bus_name)
+                    .is_ok_and(|p| p == pid)
+            })
+            .collect()
+    }
+
+    /// The first top-level window of an application element
+    pub(crate) fn first_window(&self, app: &UIElement) -> Option<UIElement> {
+        let node = self.scope(Some(app)).ok()?;
+        let mut search = Search::new(&self.bus);
+        let app = search.add(node);
+        let window = search.children(app).into_iter().find(|&w| {
+            search.info(w).is_some_and(|info| {
+                is_window_role(info.role) && info.states.contains(State::Showing)
+            })
+        })?;
+        Some(self.element(search.node(window).clone()))
+    }
+
+    fn find_window(&self, pid: u32, title: Option<&str>) -> Result<AccessibleRef, AutomationError> {
+        let mut search = Search::new(&self.bus);
+        let windows: Vec<NodeId> = Self::applications_of(&mut search, pid, &self.bus)
+            .into_iter()
+            .flat_map(|app| search.children(app))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .filter(|&w| search.info(w).is_some_and(|info| is_window_role(info.role)))
+            .collect();
+
+        let title_match = title.and_then(|title| {
+            let title = title.to_lowercase();
+            windows.iter().copied().find(|&w| {
+                search
+                    .info(w)
+                    .is_some_and(|info| info.name.to_lowercase().contains(&title))
+            })
+        });
+        // Prefer the active window, then any window that is on screen
+        let active = || {
+            windows.iter().copied().find(|&w| {
+                search
+                    .info(w)
+                    .is_some_and(|info| info.states.contains(State::Active))
+            })
+        };
+        let window = title_match
+            .or_else(active)
+            .or_else(|| windows.first().copied())
+            .ok_or_else(|| {
+                AutomationError::ElementNotFound(format!(
+                    "No window found for PID {pid} and title {title:?}"
+                ))
+            })?;
+        Ok(search.node(window).clone())
+    }
+
+    /// The window that currently has the user's attention
+    fn active_window(&self) -> Result<AccessibleRef, AutomationError> {
+        let mut search = Search::new(&self.bus);
+        for app in search.children(DESKTOP) {
+            for window in search.children(app) {
+                if search
+                    .info(window)
+                    .is_some_and(|info| info.states.contains(State::Active))
+                {
+                    return Ok(search.node(window).clone());
+                }
+            }
+        }
+
+        // Some toolkits do not report `active`: ask the window manager instead
+        let display = x11::display()?;
+        let window = display.active_window().ok_or_else(|| {
+            AutomationError::ElementNotFound("No active window found".to_string())
+        })?;
+        let pid = display.window_pid(window).ok_or_else(|| {
+            AutomationError::ElementNotFound("Active window has no _NET_WM_PID".to_string())
+        })?;
+        self.find_window(pid, display.window_title(window).as_deref())
+    }
+
+    fn list_monitors_sync(&self) -> Result<Vec<Monitor>, AutomationError> {
+        let monitors = xcap::Monitor::all()
+            .map_err(|e| AutomationError::PlatformError(format!("Failed to get monitors: {e}")))?;
+        monitors
+            .iter()
+            .enumerate()
+            .map(|(index, monitor)| monitor_info(index, monitor))
+            .collect()
+    }
+}
+
+/// Convert an xcap monitor to our `Monitor`. X11 has no portable per-monitor work
+/// area, so the work area is the full monitor.
+fn monitor_info(index: usize, monitor: &xcap::Monitor) -> Result<Monitor, AutomationError> {
+    let platform_error =
+        |what: &str, e: xcap::XCapError| AutomationError::PlatformError(format!("{what}: {e}"));
+    let x = monitor
+        .x()
+        .map_err(|e| platform_error("Failed to get monitor x position", e))?;
+    let y = monitor
+        .y()
+        .map_err(|e| platform_error("Failed to get monitor y position", e))?;
+    let width = monitor
+        .width()
+        .map_err(|e| platform_error("Failed to get monitor width", e))?;
+    let height = monitor
+        .height()
+        .map_err(|e| platform_error("Failed to get monitor height", e))?;
+    Ok(Monitor {
+        id: format!("monitor_{index}"),
+        name: monitor
+            .name()
+            .map_err(|e| platform_error("Failed to get monitor name", e))?,
+        is_primary: monitor
+            .is_primary()
+            .map_err(|e| platform_error("Failed to check primary status", e))?,
+        width,
+        height,
+        x,
+        y,
+        scale_factor: monitor
+            .scale_factor()
+            .map_err(|e| platform_error("Failed to get monitor scale factor", e))?
+            as f64,
+        work_area: Some(WorkAreaBounds {
+            x,
+            y,
+            width,
+            height,
+        }),
+    })
+}
+
+#[async_trait::async_trait]
+impl AccessibilityEngine for LinuxEngine {
+    fn get_root_element(&self) -> UIElement {
+        desktop_element(&self.bus)
+    }
+
+    /// Like on Windows, the id is a process ID: returns that process's application
+    fn get_element_by_id(&self, id: i32) -> Result<UIElement, AutomationError> {
+        self.get_application_by_pid(id, Some(Duration::ZERO))
+    }
+
+    fn get_focused_element(&self) -> Result<UIElement, AutomationError> {
+        let window = self.active_window()?;
+        let mut search = Search::new(&self.bus);
+        let scope = search.add(window);
+        let focused = std::iter::once(scope)
+            .chain(search.descendants(scope, Some(FOCUS_SEARCH_DEPTH)))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .find(|&id| {
+                search
+                    .info(id)
+                    .is_some_and(|info| info.states.contains(State::Focused))
+            })
+            .ok_or_else(|| AutomationError::ElementNotFound("No element has focus".to_string()))?;
+        Ok(self.element(search.node(focused).clone()))
+    }
+
+    fn get_applications(&self) -> Result<Vec<UIElement>, AutomationError> {
+        let mut search = Search::new(&self.bus);
+        let apps = search.children(DESKTOP);
+        Ok(apps
+            .into_iter()
+            .map(|app| self.element(search.node(app).clone()))
+            .collect())
+    }
+
+    fn get_application_by_name(&self, name: &str) -> Result<UIElement, AutomationError> {
+        let wanted = name.to_lowercase();
+        let wanted = wanted.strip_suffix(".exe").unwrap_or(&wanted);
+        let mut search = Search::new(&self.bus);
+        for app in search.children(DESKTOP) {
+            let app_name = search.info(app).map(|i| i.name.to_lowercase());
+            let process_name = search.process_name(app).map(|p| p.to_lowercase());
+            if process_name.as_deref() == Some(wanted)
+                || app_name.is_some_and(|n| n.contains(wanted))
+            {
+                return Ok(self.element(search.node(app).clone()));
+            }
+        }
+        Err(AutomationError::ElementNotFound(format!(
+            "Application '{name}' not found"
+        )))
+    }
+
+    fn get_application_by_pid(
+        &self,
+        pid: i32,
+        timeout: Option<Duration>,
+    ) -> Result<UIElement, AutomationError> {
+        let bus = self.bus.clone();
+        let found = self.poll(timeout, |search| {
+            Ok(Self::applications_of(search, pid as u32, &bus)
+                .into_iter()
+                .take(1)
+                .collect())
+        })?;
+        found
+            .into_iter()
+            .next()
+            .map(|node| self.element(node))
+            .ok_or_else(|| {
+                AutomationError::ElementNotFound(format!("No application found for PID {pid}"))
+            })
+    }
+
+    fn find_element(
+        &self,
+        selector: &Selector,
+        root: Option<&UIElement>,
+        timeout: Option<Duration>,
+    ) -> Result<UIElement, AutomationError> {
+        let scope = self.scope(root)?;
+        debug!("linux find_element {:?} within {:?}", selector, scope);
+        let found = self.poll(timeout, |search| {
+            let scope = search.add(scope.clone());
+            search.query(selector, scope, None)
+        })?;
+        found
+            .into_iter()
+            .next()
+            .map(|n| self.element(n))
+            .ok_or_else(|| {
+                AutomationError::ElementNotFound(format!(
+                    "No element found for selector: {selector:?}"
+                ))
+            })
+    }
+
+    fn find_elements(
+        &self,
+        selector: &Selector,
+        root: Option<&UIElement>,
+        timeout: Option<Duration>,
+        depth: Option<usize>,
+    ) -> Result<Vec<UIElement>, AutomationError> {
+        // Same scoping rule as the Windows engine, so workflows behave identically
+        if root.is_none() && !selector_has_process_scope(selector) {
+            return Err(AutomationError::InvalidSelector(format!(
+                "Desktop-wide search not allowed. Selector must include 'process:' prefix to scope search to a specific application.\n\
+                 Current selector: {selector:?}"
+            )));
+        }
+        let scope = self.scope(root)?;
+        let found = self.poll(timeout, |search| {
+            let scope = search.add(scope.clone());
+            search.query(selector, scope, depth)
+        })?;
+        Ok(found.into_iter().map(|n| self.element(n)).collect())
+    }
+
+    fn open_application(&self, app_name: &str) -> Result<UIElement, AutomationError> {
+        applications::open_application(self, app_name)
+    }
+
+    fn activate_application(&self, app_name: &str) -> Result<(), AutomationError> {
+        let app = self.get_application_by_name(app_name)?;
+        self.first_window(&app).unwrap_or(app).activate_window()
+    }
+
+    fn open_url(&self, url: &str, browser: Option<Browser>) -> Result<UIElement, AutomationError> {
+        applications::open_url(self, url, browser)
+    }
+
+    fn open_file(&self, file_path: &str) -> Result<(), AutomationError> {
+        applications::open_file(file_path)
+    }
+
+    async fn run_command(
+        &self,
+        windows_command: Option<&str>,
+        unix_command: Option<&str>,
+    ) -> Result<CommandOutput, AutomationError> {
+        let command = unix_command.or(windows_command).ok_or_else(|| {
+            AutomationError::InvalidArgument("A command must be provided".to_string())
+        })?;
+        let output = tokio::process::Command::new("sh")
+            .args(["-c", command])
+            .output()
+            .await
+            .map_err(|e| AutomationError::PlatformError(e.to_string()))?;
+
+        Ok(CommandOutput {
+            exit_status: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        })
+    }
+
+    async fn list_monitors(&self) -> Result<Vec<Monitor>, AutomationError> {
+        self.list_monitors_sync()
+    }
+
+    async fn get_primary_monitor(&self) -> Result<Monitor, AutomationError> {
+        self.list_monitors_sync()?
+            .into_iter()
+            .find(|m| m.is_primary)
+            .ok_or_else(|| AutomationError::PlatformError("No primary monitor found".to_string()))
+    }
+
+    async fn get_active_monitor(&self) -> Result<Monitor, AutomationError> {
+        // The monitor containing the center of the active window
+        let window = self.element(self.active_window()?);
+        let (x, y, w, h) = window.bounds()?;
+        let (cx, cy) = ((x + w / 2.0) as i32, (y + h / 2.0) as i32);
+        let monitors = self.list_monitors_sync()?;
+        monitors
+            .iter()
+            .find(|m| {
+                cx >= m.x && cx < m.x + m.width as i32 && cy >= m.y && cy < m.y + m.height as i32
+            })
+            .or_else(|| monitors.iter().find(|m| m.is_primary))
+            .cloned()
+            .ok_or_else(|| AutomationError::PlatformError("No monitor found".to_string()))
+    }
+
+    async fn get_monitor_by_id(&self, id: &str) -> Result<Monitor, AutomationError> {
+        self.list_monitors_sync()?
+            .into_iter()
+            .find(|m| m.id == id)
+            .ok_or_else(|| {
+                AutomationError::ElementNotFound(format!("Monitor with ID '{id}' not found"))
+            })
+    }
+
+    async fn get_monitor_by_name(&self, name: &str) -> Result<Monitor, AutomationError> {
+        self.list_monitors_sync()?
+            .into_iter()
+            .find(|m| m.name == name)
+            .ok_or_else(|| AutomationError::ElementNotFound(format!("Monitor '{name}' not found")))
+    }
+
+    async fn capture_monitor_by_id(&self, id: &str) -> Result<ScreenshotResult, AutomationError> {
+        let monitors = xcap::Monitor::all()
+            .map_err(|e| AutomationError::PlatformError(format!("Failed to get monitors: {e}")))?;
+        for (index, monitor) in monitors.iter().enumerate() {
+            if format!("monitor_{index}") != id {
+                continue;
+            }
+            let image = monitor.capture_image().map_err(|e| {
+                AutomationError::PlatformError(format!("Failed to capture monitor: {e}"))
+            })?;
+            return Ok(ScreenshotResult {
+                width: image.width(),
+                height: image.height(),
+                image_data: image.into_raw(),
+                monitor: Some(monitor_info(index, monitor)?),
+            });
+        }
+        Err(AutomationError::ElementNotFound(format!(
+            "Monitor with ID '{id}' not found"
+        )))
+    }
+
+    async fn ocr_image_path(&self, image_path: &str) -> Result<String, AutomationError> {
+        let engine = OcrEngine::new(OcrProvider::Auto).map_err(|e| {
+            AutomationError::PlatformError(format!("Failed to create OCR engine: {e}"))
+        })?;
+        let (text, _language, _confidence) = engine
+            .recognize_file(image_path)
+            .await
+            .map_err(|e| AutomationError::PlatformError(format!("OCR recognition failed: {e}")))?;
+        Ok(text)
+    }
+
+    async fn ocr_screenshot(
+        &self,
+        screenshot: &ScreenshotResult,
+    ) -> Result<String, AutomationError> {
+        let img_buffer: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::from_raw(
+            screenshot.width,
+            screenshot.height,
+            screenshot.image_data.clone(),
+        )
+        .ok_or_else(|| {
+            AutomationError::InvalidArgument(
+                "Invalid screenshot data for buffer creation".to_string(),
+            )
+        })?;
+        let dynamic_image = DynamicImage::ImageRgba8(img_buffer);
+
+        let engine = OcrEngine::new(OcrProvider::Auto).map_err(|e| {
+            AutomationError::PlatformError(format!("Failed to create OCR engine: {e}"))
+        })?;
+        let (text, _language, _confidence) = engine
+            .recognize_image(&dynamic_image)
+            .await
+            .map_err(|e| AutomationError::PlatformError(format!("OCR recognition failed: {e}")))?;
+        Ok(text)
+    }
+
+    fn click_at_coordinates(
+        &self,
+        x: f64,
+        y: f64,
+        restore_cursor: bool,
+    ) -> Result<(), AutomationError> {
+        input::send_mouse_click(x, y, ClickType::Left, restore_cursor)
+    }
+
+    fn click_at_coordinates_with_type(
+        &self,
+        x: f64,
+        y: f64,
+        click_type: ClickType,
+        restore_cursor: bool,
+    ) -> Result<(), AutomationError> {
+        input::send_mouse_click(x, y, click_type, restore_cursor)
+    }
+
+    fn activate_browser_window_by_title(&self, title: &str) -> Result<(), AutomationError> {
+        let wanted = title.to_lowercase();
+        let mut search = Search::new(&self.bus);
+        for app in search.children(DESKTOP) {
+            let is_browser = self
+                .bus
+                .process_id(&search.node(app).bus_name)
+                .is_ok_and(applications::is_browser_process);
+            if !is_browser {
+                continue;
+            }
+            for window in search.children(app) {
+                if search
+                    .info(window)
+                    .is_some_and(|info| info.name.to_lowercase().contains(&wanted))
+                {
+                    return self.element(search.node(window).clone()).activate_window();
+                }
+            }
+        }
+        Err(AutomationError::ElementNotFound(format!(
+            "No browser window with title containing '{title}'"
+        )))
+    }
+
+    async fn get_current_browser_window(&self) -> Result<UIElement, AutomationError> {
+        let window = self.element(self.active_window()?);
+        if window
+            .process_id()
+            .is_ok_and(applications::is_browser_process)
+        {
+            Ok(window)
+        } else {
+            Err(AutomationError::ElementNotFound(
+                "The active window is not a browser window".to_string(),
+            ))
+        }
+    }
+
+    async fn get_current_window(&self) -> Result<UIElement, AutomationError> {
+        Ok(self.element(self.active_window()?))
+    }
+
+    async fn get_current_application(&self) -> Result<UIElement, AutomationError> {
+        self.element(self.active_window()?)
+            .application()?
+            .ok_or_else(|| {
+                AutomationError::ElementNotFound("Active window has no application".to_string())
+            })
+    }
+
+    fn press_key(&self, key: &str) -> Result<(), AutomationError> {
+        input::send_keys(key)
+    }
+
+    fn set_zoom(&self, percentage: u32) -> Result<(), AutomationError> {
+        // Same keyboard approach as on Windows: zoom all the way out to a known state,
+        // then zoom in step by step. Imprecise, since zoom levels are not linear.
+        const ZOOM_STEP: u32 = 10;
+        const MIN_ZOOM: u32 = 25;
+        const MAX_ZOOM_OUT_STEPS: u32 = 50;
+
+        for _ in 0..MAX_ZOOM_OUT_STEPS {
+            self.press_key("{Ctrl}-")?;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+
+        if percentage <= MIN_ZOOM {
+            return Ok(());
+        }
+        let steps_to_zoom_in = (percentage.saturating_sub(MIN_ZOOM) + ZOOM_STEP / 2) / ZOOM_STEP;
+        for _ in 0..steps_to_zoom_in {
+            self.press_key("{Ctrl}=")?;
+        }
+        Ok(())
+    }
+
+    fn get_window_tree(
+        &self,
+        pid: u32,
+        title: Option<&str>,
+        config: TreeBuildConfig,
+    ) -> Result<UINode, AutomationError> {
+        let window = self.find_window(pid, title)?;
+        self.get_tree_from_element(&self.element(window), config)
+    }
+
+    fn get_tree_from_element(
+        &self,
+        element: &UIElement,
+        config: TreeBuildConfig,
+    ) -> Result<UINode, AutomationError> {
+        if let Some(delay) = config.ui_settle_delay_ms.filter(|&d| d > 0) {
+            std::thread::sleep(Duration::from_millis(delay));
+        }
+
+        let mut start = self.scope(Some(element))?;
+        if let Some(from_selector) = config.from_selector.as_deref() {
+            let selector = Selector::from(from_selector);
+            let mut search = Search::new(&self.bus);
+            let scope = search.add(start);
+            let found = search.query(&selector, scope, None)?;
+            start = found
+                .first()
+                .map(|&id| search.node(id).clone())
+                .ok_or_else(|| {
+                    AutomationError::ElementNotFound(format!(
+                        "from_selector '{from_selector}' did not match any element"
+                    ))
+                })?;
+        }
+        tree_builder::build_tree(&self.bus, &start, &config)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// Role of a top-level accessible that represents a window
+pub(crate) fn is_window_role(role: Role) -> bool {
+    matches!(
+        role,
+        Role::Frame | Role::Window | Role::Dialog | Role::Alert | Role::FileChooser
+    )
+}
@@ -0,0 +1,540 @@
+//! Input operations (mouse, keyboard, scroll) for Linux
+//!
+//! Synthetic input is injected through the XTest extension, so it reaches whatever
+//! window is under the pointer or has keyboard focus, exactly like real input.
+//! Key sequences use the same syntax as on Windows: `{Ctrl}c`, `{Alt}{F4}`, `{Enter}`.
+
+use super::x11::{display, x11_error, X11Display};
+use crate::keys::{Key, KeyItem, KeySequence, Modifier};
+use crate::{AutomationError, ClickType};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tracing::debug;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    ConnectionExt as _, BUTTON_PRESS_EVENT, BUTTON_RELEASE_EVENT, KEY_PRESS_EVENT,
+    KEY_RELEASE_EVENT, MOTION_NOTIFY_EVENT,
+};
+use x11rb::protocol::xtest::ConnectionExt as _;
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::CURRENT_TIME;
+
+pub(crate) const BUTTON_LEFT: u8 = 1;
+pub(crate) const BUTTON_RIGHT: u8 = 3;
+const BUTTON_WHEEL_UP: u8 = 4;
+const BUTTON_WHEEL_DOWN: u8 = 5;
+const BUTTON_WHEEL_LEFT: u8 = 6;
+const BUTTON_WHEEL_RIGHT: u8 = 7;
+
+const KEYSYM_SHIFT: u32 = 0xffe1;
+const KEYSYM_CONTROL: u32 = 0xffe3;
+const KEYSYM_ALT: u32 = 0xffe9;
+const KEYSYM_SUPER: u32 = 0xffeb;
+const KEYSYM_RETURN: u32 = 0xff0d;
+const KEYSYM_TAB: u32 = 0xff09;
+
+/// Delay between synthetic key events, matching the Windows keyboard interval
+const KEY_INTERVAL: Duration = Duration::from_millis(10);
+/// Delay between the two clicks of a double click
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// One key press, with the modifiers held while it is pressed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct KeyStroke {
+    pub modifiers: Vec<u32>,
+    pub keysym: u32,
+}
+
+/// X keysym for a named key
+fn key_keysym(key: Key) -> u32 {
+    match key {
+        Key::Enter => KEYSYM_RETURN,
+        Key::Tab => KEYSYM_TAB,
+        Key::Escape => 0xff1b,
+        Key::Backspace => 0xff08,
+        Key::Delete => 0xffff,
+        Key::Insert => 0xff63,
+        Key::Home => 0xff50,
+        Key::End => 0xff57,
+        Key::PageUp => 0xff55,
+        Key::PageDown => 0xff56,
+        Key::Left => 0xff51,
+        Key::Up => 0xff52,
+        Key::Right => 0xff53,
+        Key::Down => 0xff54,
+        Key::Space => 0x0020,
+        Key::CapsLock => 0xffe5,
+        Key::NumLock => 0xff7f,
+        Key::ScrollLock => 0xff14,
+        Key::Pause => 0xff13,
+        Key::PrintScreen => 0xff61,
+        Key::Apps => 0xff67,
+        Key::F(number) => 0xffbe + u32::from(number) - 1,
+        Key::Modifier(modifier) => modifier_keysym(modifier),
+    }
+}
+
+fn modifier_keysym(modifier: Modifier) -> u32 {
+    match modifier {
+        Modifier::Shift => KEYSYM_SHIFT,
+        Modifier::Ctrl => KEYSYM_CONTROL,
+        Modifier::Alt => KEYSYM_ALT,
+        Modifier::Win => KEYSYM_SUPER,
+        Modifier::RCtrl => 0xffe4,
+        Modifier::RAlt => 0xffea,
+        Modifier::RShift => 0xffe2,
+        Modifier::RWin => 0xffec,
+        // ISO_Level3_Shift
+        Modifier::AltGr => 0xfe03,
+    }
+}
+
+/// X keysym that produces the character `c`
+pub(crate) fn char_keysym(c: char) -> u32 {
+    match c {
+        '\n' | '\r' => KEYSYM_RETURN,
+        '\t' => KEYSYM_TAB,
+        // Latin-1 keysyms are the code point itself, everything else uses the Unicode range
+        ' '..='~' | '\u{a0}'..='\u{ff}' => c as u32,
+        _ => 0x0100_0000 | c as u32,
+    }
+}
+
+/// Key strokes for a key sequence in the shared syntax (see [`crate::keys`])
+pub(crate) fn parse_key_sequence(keys: &str) -> Result<Vec<KeyStroke>, AutomationError> {
+    let sequence = KeySequence::parse(keys)?;
+    let mut strokes = Vec::new();
+    push_strokes(sequence.items(), &[], &mut strokes);
+    Ok(strokes)
+}
+
+fn push_strokes(items: &[KeyItem], held: &[u32], strokes: &mut Vec<KeyStroke>) {
+    for item in items {
+        match item {
+            KeyItem::Text(text) => strokes.extend(text.chars().map(|c| KeyStroke {
+                modifiers: held.to_vec(),
+                keysym: char_keysym(c),
+            })),
+            KeyItem::Key { key, repeat } => {
+                for _ in 0..*repeat {
+                    strokes.push(KeyStroke {
+                        modifiers: held.to_vec(),
+                        keysym: key_keysym(*key),
+                    });
+                }
+            }
+            KeyItem::Chord { modifiers, items } => {
+                let mut held = held.to_vec();
+                held.extend(modifiers.iter().map(|m| modifier_keysym(*m)));
+                push_strokes(items, &held, strokes);
+            }
+        }
+    }
+}
+
+// ============== KEYBOARD MAPPING ==============
+
+/// Snapshot of the server's keycode → keysym table
+struct Keymap {
+    min_keycode: u8,
+    keysyms_per_keycode: u8,
+    keysyms: Vec<u32>,
+}
+
+impl Keymap {
+    fn load(display: &X11Display) -> Result<Self, AutomationError> {
+        let setup = display.conn.setup();
+        let (min_keycode, max_keycode) = (setup.min_keycode, setup.max_keycode);
+        let reply = display
+            .conn
+            .get_keyboard_mapping(min_keycode, max_keycode - min_keycode + 1)
+            .map_err(|e| x11_error("Failed to read keyboard mapping", e))?
+            .reply()
+            .map_err(|e| x11_error("Failed to read keyboard mapping", e))?;
+        Ok(Self {
+            min_keycode,
+            keysyms_per_keycode: reply.keysyms_per_keycode,
+            keysyms: reply.keysyms,
+        })
+    }
+
+    fn per_keycode(&self) -> usize {
+        self.keysyms_per_keycode.max(1) as usize
+    }
+
+    /// Keycode and shift level (0 or 1) producing `keysym`
+    fn lookup(&self, keysym: u32) -> Option<(u8, usize)> {
+        self.keysyms
+            .chunks(self.per_keycode())
+            .enumerate()
+            .find_map(|(i, syms)| {
+                syms.iter()
+                    .take(2)
+                    .position(|&s| s == keysym)
+                    .map(|level| (self.min_keycode + i as u8, level))
+            })
+    }
+
+    /// Highest keycode with no keysyms bound, used to type characters the layout lacks
+    fn scratch_keycode(&self) -> Option<u8> {
+        self.keysyms
+            .chunks(self.per_keycode())
+            .enumerate()
+            .rev()
+            .find(|(_, syms)| syms.iter().all(|&s| s == 0))
+            .map(|(i, _)| self.min_keycode + i as u8)
+    }
+}
+
+static KEYMAP: Mutex<Option<Keymap>> = Mutex::new(None);
+
+/// Resolve `keysym` to a keycode, binding it to a spare keycode if the layout lacks it.
+/// Returns the keycode and whether Shift must be held.
+fn keycode_for(display: &X11Display, keysym: u32) -> Result<(u8, bool), AutomationError> {
+    let mut guard = KEYMAP
+        .lock()
+        .map_err(|_| AutomationError::Internal("Keyboard mapping lock poisoned".to_string()))?;
+    if guard.is_none() {
+        *guard = Some(Keymap::load(display)?);
+    }
+    let keymap = guard.as_mut().expect("keymap was just loaded");
+
+    if let Some((keycode, level)) = keymap.lookup(keysym) {
+        return Ok((keycode, level == 1));
+    }
+
+    let keycode = keymap.scratch_keycode().ok_or_else(|| {
+        AutomationError::PlatformError(format!(
+            "No free keycode to type keysym 0x{keysym:x} with the current keyboard layout"
+        ))
+    })?;
+    debug!("Binding keysym 0x{:x} to spare keycode {}", keysym, keycode);
+
+    let per_keycode = keymap.per_keycode();
+    let mut syms = vec![0u32; per_keycode];
+    syms[0] = keysym;
+    if per_keycode > 1 {
+        syms[1] = keysym;
+    }
+    display
+        .conn
+        .change_keyboard_mapping(1, keycode, per_keycode as u8, &syms)
+        .map_err(|e| x11_error("Failed to change keyboard mapping", e))?;
+    display
+        .conn
+        .sync()
+        .map_err(|e| x11_error("Failed to change keyboard mapping", e))?;
+
+    let offset = (keycode - keymap.min_keycode) as usize * per_keycode;
+    keymap.keysyms[offset..offset + per_keycode].copy_from_slice(&syms);
+    // Clients need a moment to process the MappingNotify before the key arrives
+    thread::sleep(Duration::from_millis(20));
+    Ok((keycode, false))
+}
+
+fn fake_key(display: &X11Display, keycode: u8, press: bool) -> Result<(), AutomationError> {
+    let event = if press {
+        KEY_PRESS_EVENT
+    } else {
+        KEY_RELEASE_EVENT
+    };
+    display
+        .conn
+        .xtest_fake_input(event, keycode, CURRENT_TIME, display.root, 0, 0, 0)
+        .map_err(|e| x11_error("Failed to send key event", e))?;
+    Ok(())
+}
+
+fn send_stroke(display: &X11Display, stroke: &KeyStroke) -> Result<(), AutomationError> {
+    let (keycode, needs_shift) = keycode_for(display, stroke.keysym)?;
+    let mut held = Vec::new();
+    for &modifier in &stroke.modifiers {
+        held.push(keycode_for(display, modifier)?.0);
+    }
+    if needs_shift && !stroke.modifiers.contains(&KEYSYM_SHIFT) {
+        held.push(keycode_for(display, KEYSYM_SHIFT)?.0);
+    }
+
+    for &modifier in &held {
+        fake_key(display, modifier, true)?;
+    }
+    fake_key(display, keycode, true)?;
+    fake_key(display, keycode, false)?;
+    for &modifier in held.iter().rev() {
+        fake_key(display, modifier, false)?;
+    }
+    display
+        .conn
+        .sync()
+        .map_err(|e| x11_error("Failed to flush key events", e))?;
+    thread::sleep(KEY_INTERVAL);
+    Ok(())
+}
+
+/// Press a key sequence such as `{Ctrl}s` or `{Alt}{F4}` on the focused window
+pub(crate) fn send_keys(keys: &str) -> Result<(), AutomationError> {
+    let strokes = parse_key_sequence(keys)?;
+    let display = display()?;
+    for stroke in &strokes {
+        send_stroke(&display, stroke)?;
+    }
+    Ok(())
+}
+
+/// Type literal text on the focused window, character by character
+pub(crate) fn type_text(text: &str) -> Result<(), AutomationError> {
+    let display = display()?;
+    for c in text.chars() {
+        let stroke = KeyStroke {
+            modifiers: Vec::new(),
+            keysym: char_keysym(c),
+        };
+        send_stroke(&display, &stroke)?;
+    }
+    Ok(())
+}
+
+// ============== MOUSE ==============
+
+fn fake_motion(display: &X11Display, x: f64, y: f64) -> Result<(), AutomationError> {
+    display
+        .conn
+        .xtest_fake_input(
+            MOTION_NOTIFY_EVENT,
+            0,
+            CURRENT_TIME,
+            display.root,
+            x.round() as i16,
+            y.round() as i16,
+            0,
+        )
+        .map_err(|e| x11_error("Failed to move mouse", e))?;
+    Ok(())
+}
+
+fn fake_button(display: &X11Display, button: u8, press: bool) -> Result<(), AutomationError> {
+    let event = if press {
+        BUTTON_PRESS_EVENT
+    } else {
+        BUTTON_RELEASE_EVENT
+    };
+    display
+        .conn
+        .xtest_fake_input(event, button, CURRENT_TIME, display.root, 0, 0, 0)
+        .map_err(|e| x11_error("Failed to send mouse button event", e))?;
+    Ok(())
+}
+
+fn sync(display: &X11Display) -> Result<(), AutomationError> {
+    display
+        .conn
+        .sync()
+        .map_err(|e| x11_error("Failed to flush mouse events", e))
+}
+
+/// Current pointer position in screen coordinates
+pub(crate) fn cursor_position() -> Result<(f64, f64), AutomationError> {
+    let display = display()?;
+    let pointer = display
+        .conn
+        .query_pointer(display.root)
+        .map_err(|e| x11_error("Failed to query pointer", e))?
+        .reply()
+        .map_err(|e| x11_error("Failed to query pointer", e))?;
+    Ok((pointer.root_x as f64, pointer.root_y as f64))
+}
+
+/// Move the pointer to absolute screen coordinates
+pub(crate) fn mouse_move(x: f64, y: f64) -> Result<(), AutomationError> {
+    let display = display()?;
+    fake_motion(&display, x, y)?;
+    sync(&display)
+}
+
+/// Press or release a mouse button at the current pointer position
+pub(crate) fn mouse_button(button: u8, press: bool) -> Result<(), AutomationError> {
+    let display = display()?;
+    fake_button(&display, button, press)?;
+    sync(&display)
+}
+
+/// Send a mouse click at absolute screen coordinates.
+///
+/// Shared by `Desktop::click_at_coordinates` and element clicks, like the Windows
+/// `send_mouse_click`. If `restore_cursor` is set the pointer returns to where it was.
+pub(crate) fn send_mouse_click(
+    x: f64,
+    y: f64,
+    click_type: ClickType,
+    restore_cursor: bool,
+) -> Result<(), AutomationError> {
+    let original = if restore_cursor {
+        cursor_position().ok()
+    } else {
+        None
+    };
+
+    let display = display()?;
+    fake_motion(&display, x, y)?;
+    let (button, clicks) = match click_type {
+        ClickType::Left => (BUTTON_LEFT, 1),
+        ClickType::Double => (BUTTON_LEFT, 2),
+        ClickType::Right => (BUTTON_RIGHT, 1),
+    };
+    for click in 0..clicks {
+        if click > 0 {
+            sync(&display)?;
+            thread::sleep(DOUBLE_CLICK_INTERVAL);
+        }
+        fake_button(&display, button, true)?;
+        fake_button(&display, button, false)?;
+    }
+
+    if let Some((x, y)) = original {
+        fake_motion(&display, x, y)?;
+    }
+    sync(&display)
+}
+
+/// Press the left button at the start point, move to the end point and release
+pub(crate) fn mouse_drag(
+    start_x: f64,
+    start_y: f64,
+    end_x: f64,
+    end_y: f64,
+) -> Result<(), AutomationError> {
+    const STEPS: u32 = 10;
+    let display = display()?;
+    fake_motion(&display, start_x, start_y)?;
+    fake_button(&display, BUTTON_LEFT, true)?;
+    sync(&display)?;
+    // Intermediate moves so toolkits recognise the drag threshold
+    for step in 1..=STEPS {
+        let t = step as f64 / STEPS as f64;
+        fake_motion(
+            &display,
+            start_x + (end_x - start_x) * t,
+            start_y + (end_y - start_y) * t,
+        )?;
+        sync(&display)?;
+        thread::sleep(KEY_INTERVAL);
+    }
+    fake_button(&display, BUTTON_LEFT, false)?;
+    sync(&display)
+}
+
+/// Turn the mouse wheel at the current pointer position.
+/// `amount` is the number of wheel notches (at least one).
+pub(crate) fn scroll_wheel(direction: &str, amount: f64) -> Result<(), AutomationError> {
+    let button = match direction.to_lowercase().as_str() {
+        "up" => BUTTON_WHEEL_UP,
+        "down" => BUTTON_WHEEL_DOWN,
+        "left" => BUTTON_WHEEL_LEFT,
+        "right" => BUTTON_WHEEL_RIGHT,
+        other => {
+            return Err(AutomationError::InvalidArgument(format!(
+                "Invalid scroll direction '{other}'. Use up, down, left or right"
+            )))
+        }
+    };
+    let display = display()?;
+    let notches = amount.round().max(1.0) as u32;
+    for _ in 0..notches {
+        fake_button(&display, button, true)?;
+        fake_button(&display, button, false)?;
+    }
+    sync(&display)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stroke(modifiers: &[u32], keysym: u32) -> KeyStroke {
+        KeyStroke {
+            modifiers: modifiers.to_vec(),
+            keysym,
+        }
+    }
+
+    #[test]
+    fn test_plain_text_and_named_keys() {
+        assert_eq!(
+            parse_key_sequence("a{Enter}").unwrap(),
+            vec![stroke(&[], 'a' as u32), stroke(&[], KEYSYM_RETURN)]
+        );
+        assert_eq!(
+            parse_key_sequence("{f5}").unwrap(),
+            vec![stroke(&[], 0xffc2)]
+        );
+        assert_eq!(
+            parse_key_sequence("{Tab 2}").unwrap(),
+            vec![stroke(&[], KEYSYM_TAB), stroke(&[], KEYSYM_TAB)]
+        );
+    }
+
+    #[test]
+    fn test_modifiers_apply_to_next_key_only() {
+        assert_eq!(
+            parse_key_sequence("{Ctrl}cv").unwrap(),
+            vec![
+                stroke(&[KEYSYM_CONTROL], 'c' as u32),
+                stroke(&[], 'v' as u32)
+            ]
+        );
+        assert_eq!(
+            parse_key_sequence("{Alt}{F4}").unwrap(),
+            vec![stroke(&[KEYSYM_ALT], 0xffc1)]
+        );
+        assert_eq!(
+            parse_key_sequence("{Ctrl}{Shift}s").unwrap(),
+            vec![stroke(&[KEYSYM_CONTROL, KEYSYM_SHIFT], 's' as u32)]
+        );
+        // A lone modifier is pressed on its own
+        assert_eq!(
+            parse_key_sequence("{Win}").unwrap(),
+            vec![stroke(&[], KEYSYM_SUPER)]
+        );
+        // Right-hand modifiers keep their own keysyms
+        assert_eq!(
+            parse_key_sequence("{RCtrl}c{AltGr}e{F35}").unwrap(),
+            vec![
+                stroke(&[0xffe4], 'c' as u32),
+                stroke(&[0xfe03], 'e' as u32),
+                stroke(&[], 0xffe0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_modifier_groups_and_literal_braces() {
+        assert_eq!(
+            parse_key_sequence("{Ctrl}(ac)x").unwrap(),
+            vec![
+                stroke(&[KEYSYM_CONTROL], 'a' as u32),
+                stroke(&[KEYSYM_CONTROL], 'c' as u32),
+                stroke(&[], 'x' as u32)
+            ]
+        );
+        assert_eq!(
+            parse_key_sequence("{{}{}}").unwrap(),
+            vec![stroke(&[], '{' as u32), stroke(&[], '}' as u32)]
+        );
+    }
+
+    #[test]
+    fn test_invalid_sequences() {
+        assert!(parse_key_sequence("{Enter").is_err());
+        assert!(parse_key_sequence("{NotAKey}").is_err());
+        assert!(parse_key_sequence("{Ctrl}(ab").is_err());
+    }
+
+    #[test]
+    fn test_char_keysyms() {
+        assert_eq!(char_keysym('A'), 0x41);
+        assert_eq!(char_keysym('é'), 0xe9);
+        assert_eq!(char_keysym('€'), 0x0100_20ac);
+        assert_eq!(char_keysym('\n'), KEYSYM_RETURN);
+    }
+}
@@ -0,0 +1,30 @@
+//! Linux accessibility backend built on AT-SPI2
+//!
+//! Elements are read from the AT-SPI2 accessibility bus over D-Bus and synthetic
+//! input is sent through the XTest extension, so an X11 (or XWayland) session is
+//! required for mouse and keyboard actions.
+//!
+//! The engine can run headless, e.g. in CI:
+//!
+//! ```text
+//! Xvfb :99 &
+//! export DISPLAY=:99
+//! dbus-run-session -- sh -c '/usr/libexec/at-spi-bus-launcher --launch-immediately & cargo test'
+//! ```
+//!
+//! `AT_SPI_BUS_ADDRESS` can point the engine at a specific accessibility bus; otherwise
+//! the address is requested from `org.a11y.Bus` on the session bus.
+
+pub mod applications;
+pub(crate) mod bus;
+pub mod element;
+pub mod engine;
+pub(crate) mod input;
+pub(crate) mod roles;
+pub(crate) mod search;
+pub(crate) mod tree_builder;
+pub(crate) mod x11;
+
+pub use applications::{get_process_name_by_pid, is_browser_process, KNOWN_BROWSER_PROCESS_NAMES};
+pub use element::LinuxUIElement;
+pub use engine::LinuxEngine;
@@ -0,0 +1,103 @@
+//! Mapping of AT-SPI2 roles and states onto terminator's role vocabulary
+//!
+//! Elements report the same role names as on Windows (`Button`, `Edit`, `Window`, ...)
+//! so that `role:` selectors and recorded workflows work unchanged across platforms.
+
+use atspi_common::{Role, State, StateSet};
+
+/// Role name reported for an element with the given AT-SPI role and state set
+pub(crate) fn role_name(role: Role, states: StateSet) -> &'static str {
+    match role {
+        Role::Frame | Role::Window | Role::Dialog | Role::Alert | Role::FileChooser => "Window",
+        Role::ColorChooser | Role::FontChooser => "Window",
+        Role::Application | Role::DesktopFrame => "Pane",
+        Role::Button => "Button",
+        Role::ToggleButton => "Button",
+        Role::PushButtonMenu => "SplitButton",
+        Role::CheckBox => "CheckBox",
+        Role::RadioButton => "RadioButton",
+        Role::CheckMenuItem | Role::RadioMenuItem | Role::MenuItem | Role::TearoffMenuItem => {
+            "MenuItem"
+        }
+        Role::Menu | Role::PopupMenu => "Menu",
+        Role::MenuBar => "MenuBar",
+        Role::ComboBox => "ComboBox",
+        Role::Entry | Role::PasswordText | Role::Autocomplete | Role::Editbar => "Edit",
+        Role::Terminal => "Edit",
+        // GtkTextView and friends report `text`; only the editable ones are edit fields
+        Role::Text if states.contains(State::Editable) => "Edit",
+        Role::Text | Role::Label | Role::Static | Role::Caption | Role::Heading => "Text",
+        Role::Paragraph | Role::AcceleratorLabel => "Text",
+        Role::DocumentFrame
+        | Role::DocumentWeb
+        | Role::DocumentText
+        | Role::DocumentEmail
+        | Role::DocumentSpreadsheet
+        | Role::DocumentPresentation
+        | Role::HTMLContainer => "Document",
+        Role::Link => "Hyperlink",
+        Role::Image | Role::Icon | Role::DesktopIcon | Role::Animation | Role::ImageMap => "Image",
+        Role::List | Role::ListBox => "List",
+        Role::ListItem => "ListItem",
+        Role::PageTabList => "Tab",
+        Role::PageTab => "TabItem",
+        Role::Tree | Role::TreeTable => "Tree",
+        Role::TreeItem => "TreeItem",
+        Role::Table => "Table",
+        Role::TableRow | Role::TableCell => "DataItem",
+        Role::ColumnHeader | Role::RowHeader | Role::TableColumnHeader | Role::TableRowHeader => {
+            "HeaderItem"
+        }
+        Role::Header => "Header",
+        Role::ProgressBar | Role::LevelBar => "ProgressBar",
+        Role::ScrollBar => "ScrollBar",
+        Role::Slider | Role::Dial => "Slider",
+        Role::SpinButton => "Spinner",
+        Role::StatusBar => "StatusBar",
+        Role::ToolBar => "ToolBar",
+        Role::ToolTip => "ToolTip",
+        Role::Separator => "Separator",
+        Role::Calendar | Role::DateEditor => "Calendar",
+        Role::TitleBar => "TitleBar",
+        Role::Grouping
+        | Role::Section
+        | Role::Form
+        | Role::Landmark
+        | Role::Article
+        | Role::BlockQuote
+        | Role::Footer
+        | Role::DescriptionList => "Group",
+        Role::Panel
+        | Role::Filler
+        | Role::ScrollPane
+        | Role::Viewport
+        | Role::SplitPane
+        | Role::LayeredPane
+        | Role::RootPane
+        | Role::GlassPane
+        | Role::InternalFrame
+        | Role::OptionPane
+        | Role::DirectoryPane
+        | Role::Canvas
+        | Role::DrawingArea
+        | Role::Embedded
+        | Role::InfoBar
+        | Role::Notification => "Pane",
+        _ => "Custom",
+    }
+}
+
+/// Whether the element accepts input (GTK reports `sensitive`, Qt reports `enabled`)
+pub(crate) fn is_enabled(states: StateSet) -> bool {
+    states.intersects(State::Enabled | State::Sensitive)
+}
+
+/// Whether the element is on screen: shown by its toolkit and not hidden by an ancestor
+pub(crate) fn is_showing(states: StateSet) -> bool {
+    states.contains(State::Showing | State::Visible)
+}
+
+/// Whether a checkbox, toggle button or checkable menu item is on
+pub(crate) fn is_toggled(states: StateSet) -> bool {
+    states.intersects(State::Checked | State::Pressed)
+}
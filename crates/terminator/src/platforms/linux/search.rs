@@ -0,0 +1,517 @@
+//! Selector evaluation against the live AT-SPI tree
+//!
+//! Follows the semantics of the Windows engine (and the mock engine): `process:`
+//! scopes start from the desktop, chains search below every match of the previous
+//! step, `nth=` picks from the previous step's results and spatial selectors order
+//! candidates by distance. Every D-Bus round trip is cached for the duration of one
+//! evaluation, so a node's role, name, states or bounds are fetched at most once.
+
+use super::bus::{AccessibleRef, AtspiBus};
+use super::element::{element_id, read_bounds, read_value};
+use super::engine::LinuxEngine;
+use super::roles::{is_enabled, is_showing, role_name};
+use crate::query::{apply_nth, canonical_role, distance, process_matches, spatially_related};
+use crate::{filters, AutomationError, Selector};
+use atspi_common::{Role, State, StateSet};
+use std::collections::{HashMap, HashSet};
+
+/// Index of a node within one [`Search`]
+pub(crate) type NodeId = usize;
+
+/// The desktop root, always the first node of a search
+pub(crate) const DESKTOP: NodeId = 0;
+
+type Bounds = (f64, f64, f64, f64);
+
+/// Role, name and states of a node, fetched together
+#[derive(Debug, Clone)]
+pub(crate) struct NodeInfo {
+    pub role: Role,
+    pub name: String,
+    pub states: StateSet,
+}
+
+impl NodeInfo {
+    pub(crate) fn role_name(&self) -> &'static str {
+        role_name(self.role, self.states)
+    }
+}
+
+struct Entry {
+    node: AccessibleRef,
+    children: Option<Vec<NodeId>>,
+    /// `None` until fetched, `Some(None)` when the node could not be read (e.g. it vanished)
+    info: Option<Option<NodeInfo>>,
+    bounds: Option<Option<Bounds>>,
+    attributes: Option<HashMap<String, String>>,
+}
+
+/// One evaluation of a selector over the accessibility tree
+pub(crate) struct Search<'a> {
+    bus: &'a AtspiBus,
+    entries: Vec<Entry>,
+    ids: HashMap<AccessibleRef, NodeId>,
+    process_names: HashMap<String, Option<String>>,
+    /// Engine handing out live elements to `filter:` predicates
+    engine: Option<&'a LinuxEngine>,
+}
+
+impl<'a> Search<'a> {
+    pub(crate) fn new(bus: &'a AtspiBus) -> Self {
+        let mut search = Self {
+            bus,
+            entries: Vec::new(),
+            ids: HashMap::new(),
+            process_names: HashMap::new(),
+            engine: None,
+        };
+        search.add(AccessibleRef::desktop());
+        search
+    }
+
+    /// Allow `filter:` selectors, whose predicates run against elements of `engine`
+    pub(crate) fn with_engine(mut self, engine: &'a LinuxEngine) -> Self {
+        self.engine = Some(engine);
+        self
+    }
+
+    /// Keep the nodes the `filter:` predicate accepts
+    fn retain_filtered(
+        &mut self,
+        ids: Vec<NodeId>,
+        filter: usize,
+    ) -> Result<Vec<NodeId>, AutomationError> {
+        let engine = self.engine.ok_or_else(|| {
+            AutomationError::UnsupportedOperation(
+                "`Filter` selector not supported in this search".to_string(),
+            )
+        })?;
+        let mut kept = Vec::with_capacity(ids.len());
+        for id in ids {
+            if filters::evaluate_filter(filter, &engine.element(self.node(id).clone()))? {
+                kept.push(id);
+            }
+        }
+        Ok(kept)
+    }
+
+    /// Register a node, returning its id (the same node always gets the same id)
+    pub(crate) fn add(&mut self, node: AccessibleRef) -> NodeId {
+        if let Some(&id) = self.ids.get(&node) {
+            return id;
+        }
+        let id = self.entries.len();
+        self.ids.insert(node.clone(), id);
+        self.entries.push(Entry {
+            node,
+            children: None,
+            info: None,
+            bounds: None,
+            attributes: None,
+        });
+        id
+    }
+
+    pub(crate) fn node(&self, id: NodeId) -> &AccessibleRef {
+        &self.entries[id].node
+    }
+
+    pub(crate) fn info(&mut self, id: NodeId) -> Option<NodeInfo> {
+        if let Some(info) = &self.entries[id].info {
+            return info.clone();
+        }
+        let info = self
+            .bus
+            .accessible(&self.entries[id].node)
+            .ok()
+            .and_then(|a| {
+                Some(NodeInfo {
+                    role: a.get_role().ok()?,
+                    name: a.name().unwrap_or_default(),
+                    states: a.get_state().ok()?,
+                })
+            });
+        self.entries[id].info = Some(info.clone());
+        info
+    }
+
+    pub(crate) fn children(&mut self, id: NodeId) -> Vec<NodeId> {
+        if let Some(children) = &self.entries[id].children {
+            return children.clone();
+        }
+        let refs = self
+            .bus
+            .accessible(&self.entries[id].node)
+            .and_then(|a| {
+                a.get_children()
+                    .map_err(|e| super::bus::dbus_error("Failed to get children", e))
+            })
+            .unwrap_or_default();
+        let children: Vec<NodeId> = refs
+            .iter()
+            .filter_map(AccessibleRef::from_object_ref)
+            .map(|node| self.add(node))
+            .collect();
+        self.entries[id].children = Some(children.clone());
+        children
+    }
+
+    /// Descendants of `scope` in document order, excluding `scope` itself.
+    /// `depth` limits how many levels below `scope` are visited.
+    pub(crate) fn descendants(&mut self, scope: NodeId, depth: Option<usize>) -> Vec<NodeId> {
+        let mut result = Vec::new();
+        let mut visited = HashSet::from([scope]);
+        let mut stack: Vec<(NodeId, usize)> = self
+            .children(scope)
+            .into_iter()
+            .rev()
+            .map(|c| (c, 1))
+            .collect();
+        while let Some((id, level)) = stack.pop() {
+            // Broken toolkits occasionally report cycles
+            if !visited.insert(id) {
+                continue;
+            }
+            result.push(id);
+            if depth.is_none_or(|max| level < max) {
+                for child in self.children(id).into_iter().rev() {
+                    stack.push((child, level + 1));
+                }
+            }
+        }
+        result
+    }
+
+    pub(crate) fn bounds(&mut self, id: NodeId) -> Option<Bounds> {
+        if let Some(bounds) = self.entries[id].bounds {
+            return bounds;
+        }
+        let bounds = read_bounds(self.bus, &self.entries[id].node).ok();
+        self.entries[id].bounds = Some(bounds);
+        bounds
+    }
+
+    fn attributes(&mut self, id: NodeId) -> &HashMap<String, String> {
+        if self.entries[id].attributes.is_none() {
+            let attributes = self
+                .bus
+                .accessible(&self.entries[id].node)
+                .ok()
+                .and_then(|a| a.get_attributes().ok())
+                .unwrap_or_default();
+            self.entries[id].attributes = Some(attributes);
+        }
+        self.entries[id].attributes.as_ref().expect("just loaded")
+    }
+
+    fn object_attribute(&mut self, id: NodeId, key: &str) -> Option<String> {
+        self.attributes(id)
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.clone())
+    }
+
+    fn accessible_id(&mut self, id: NodeId) -> Option<String> {
+        let accessible_id = self
+            .bus
+            .accessible(&self.entries[id].node)
+            .ok()
+            .and_then(|a| a.accessible_id().ok())
+            .filter(|s| !s.is_empty());
+        // Browsers expose the DOM id as an object attribute instead
+        accessible_id.or_else(|| self.object_attribute(id, "id"))
+    }
+
+    pub(crate) fn process_name(&mut self, id: NodeId) -> Option<String> {
+        let bus_name = self.entries[id].node.bus_name.clone();
+        if let Some(name) = self.process_names.get(&bus_name) {
+            return name.clone();
+        }
+        let name = self
+            .bus
+            .process_id(&bus_name)
+            .ok()
+            .and_then(|pid| super::get_process_name_by_pid(pid as i32).ok());
+        self.process_names.insert(bus_name, name.clone());
+        name
+    }
+
+    fn is_visible(&mut self, id: NodeId) -> bool {
+        self.info(id).is_some_and(|info| is_showing(info.states))
+    }
+
+    /// Resolve an `attr:` key, accepting the UIA property names used on Windows
+    fn attribute_value(&mut self, id: NodeId, key: &str) -> Option<String> {
+        let info = self.info(id)?;
+        match key.to_lowercase().as_str() {
+            "name" => Some(info.name),
+            "role" | "controltype" => Some(info.role_name().to_string()),
+            "value" => read_value(self.bus, self.node(id)).ok().flatten(),
+            "description" | "helptext" => self
+                .bus
+                .accessible(self.node(id))
+                .ok()
+                .and_then(|a| a.description().ok()),
+            "isenabled" | "enabled" => Some(is_enabled(info.states).to_string()),
+            "haskeyboardfocus" | "focused" => {
+                Some(info.states.contains(State::Focused).to_string())
+            }
+            "iskeyboardfocusable" => Some(info.states.contains(State::Focusable).to_string()),
+            "isoffscreen" => Some((!is_showing(info.states)).to_string()),
+            "automationid" => self.accessible_id(id),
+            "processid" => self
+                .bus
+                .process_id(&self.node(id).bus_name)
+                .ok()
+                .map(|pid| pid.to_string()),
+            _ => self.object_attribute(id, key),
+        }
+    }
+
+    /// Check a single node against a selector that can be evaluated without context.
+    /// Returns `None` for selectors that need a search (chains, spatial, `has:` ...).
+    fn matches_atomic(&mut self, id: NodeId, selector: &Selector) -> Option<bool> {
+        let Some(info) = self.info(id) else {
+            // Unreadable nodes never match, but still let compound selectors decide
+            return match selector {
+                Selector::Chain(_)
+                | Selector::Has(_)
+                | Selector::RightOf(_)
+                | Selector::LeftOf(_)
+                | Selector::Above(_)
+                | Selector::Below(_)
+                | Selector::Near(_) => None,
+                _ => Some(false),
+            };
+        };
+        let result = match selector {
+            Selector::Role {
+                role,
+                name: expected,
+            } => {
+                (role == "*" || canonical_role(role) == canonical_role(info.role_name()))
+                    && expected
+                        .as_ref()
+                        .is_none_or(|e| info.name.to_lowercase().contains(&e.to_lowercase()))
+            }
+            Selector::Id(expected) => {
+                element_id(self.node(id)) == expected.strip_prefix('#').unwrap_or(expected)
+            }
+            Selector::Name(expected) => info.name.to_lowercase().contains(&expected.to_lowercase()),
+            Selector::Text(expected) => info.name.contains(expected.as_str()),
+            Selector::NativeId(expected) => {
+                self.accessible_id(id).as_deref() == Some(expected.as_str())
+            }
+            Selector::ClassName(expected) => {
+                self.object_attribute(id, "class").as_deref() == Some(expected.as_str())
+            }
+            Selector::LocalizedRole(expected) => self
+                .bus
+                .accessible(self.node(id))
+                .ok()
+                .and_then(|a| a.get_localized_role_name().ok())
+                .is_some_and(|r| r.eq_ignore_ascii_case(expected)),
+            Selector::Visible(expected) => is_showing(info.states) == *expected,
+            Selector::Process(expected) => self
+                .process_name(id)
+                .is_some_and(|actual| process_matches(&actual, expected)),
+            Selector::Attributes(attributes) => attributes.iter().all(|(key, expected)| {
+                self.attribute_value(id, key)
+                    .is_some_and(|v| v.eq_ignore_ascii_case(expected))
+            }),
+            Selector::And(selectors) => {
+                let mut all = true;
+                for s in selectors {
+                    if !self.matches_atomic(id, s)? {
+                        all = false;
+                    }
+                }
+                all
+            }
+            Selector::Or(selectors) => {
+                let mut any = false;
+                for s in selectors {
+                    if self.matches_atomic(id, s)? {
+                        any = true;
+                    }
+                }
+                any
+            }
+            Selector::Not(inner) => !self.matches_atomic(id, inner)?,
+            _ => return None,
+        };
+        Some(result)
+    }
+
+    /// Evaluate `selector` against the descendants of `scope`, in document order
+    pub(crate) fn query(
+        &mut self,
+        selector: &Selector,
+        scope: NodeId,
+        depth: Option<usize>,
+    ) -> Result<Vec<NodeId>, AutomationError> {
+        match selector {
+            Selector::Invalid(reason) => Err(AutomationError::InvalidSelector(reason.clone())),
+            Selector::Path(_) => Err(AutomationError::UnsupportedOperation(
+                "`Path` selector not supported".to_string(),
+            )),
+            Selector::Filter(_) => Err(filters::standalone_filter_error()),
+            Selector::Nth(_) => Err(AutomationError::InvalidSelector(
+                "Nth selector must be used as part of a chain (e.g. 'list >> nth=0')".to_string(),
+            )),
+            Selector::Parent => {
+                if scope == DESKTOP {
+                    return Err(AutomationError::InvalidSelector(
+                        "Parent selector requires a starting element".to_string(),
+                    ));
+                }
+                let parent = self
+                    .bus
+                    .accessible(self.node(scope))
+                    .ok()
+                    .and_then(|a| a.parent().ok())
+                    .and_then(|p| AccessibleRef::from_object_ref(&p));
+                Ok(parent.map(|p| self.add(p)).into_iter().collect())
+            }
+            // Process scoping always starts from the desktop, like on Windows
+            Selector::Process(_) => {
+                let mut apps = Vec::new();
+                for app in self.children(DESKTOP) {
+                    if self.matches_atomic(app, selector).unwrap_or(false) {
+                        apps.push(app);
+                    }
+                }
+                Ok(apps)
+            }
+            Selector::Chain(selectors) => {
+                let (first, rest) = selectors.split_first().ok_or_else(|| {
+                    AutomationError::InvalidArgument("Selector chain cannot be empty".to_string())
+                })?;
+                let mut current = self.query(first, scope, depth)?;
+                for selector in rest {
+                    current = match selector {
+                        Selector::Nth(index) => apply_nth(current, *index),
+                        Selector::Filter(filter) => self.retain_filtered(current, *filter)?,
+                        _ => {
+                            let mut seen = HashSet::new();
+                            let mut next = Vec::new();
+                            for root in current {
+                                for found in self.query(selector, root, None)? {
+                                    if seen.insert(found) {
+                                        next.push(found);
+                                    }
+                                }
+                            }
+                            next
+                        }
+                    };
+                    if current.is_empty() {
+                        break;
+                    }
+                }
+                Ok(current)
+            }
+            Selector::And(selectors) if selectors.is_empty() => Ok(Vec::new()),
+            Selector::And(selectors) => {
+                let seed = filters::and_seed(selectors);
+                let mut results = self.query(&selectors[seed], scope, depth)?;
+                for (i, selector) in selectors.iter().enumerate() {
+                    if i == seed {
+                        continue;
+                    }
+                    if let Selector::Filter(filter) = selector {
+                        results = self.retain_filtered(results, *filter)?;
+                        continue;
+                    }
+                    let flags: Option<Vec<bool>> = results
+                        .iter()
+                        .map(|&id| self.matches_atomic(id, selector))
+                        .collect();
+                    results = match flags {
+                        Some(flags) => results
+                            .into_iter()
+                            .zip(flags)
+                            .filter_map(|(id, keep)| keep.then_some(id))
+                            .collect(),
+                        None => {
+                            let allowed: HashSet<NodeId> =
+                                self.query(selector, scope, depth)?.into_iter().collect();
+                            results
+                                .into_iter()
+                                .filter(|id| allowed.contains(id))
+                                .collect()
+                        }
+                    };
+                }
+                Ok(results)
+            }
+            Selector::Or(selectors) => {
+                let mut seen = HashSet::new();
+                let mut results = Vec::new();
+                for selector in selectors {
+                    for found in self.query(selector, scope, depth)? {
+                        if seen.insert(found) {
+                            results.push(found);
+                        }
+                    }
+                }
+                Ok(results)
+            }
+            Selector::Not(inner) => {
+                let excluded: HashSet<NodeId> =
+                    self.query(inner, scope, depth)?.into_iter().collect();
+                Ok(self
+                    .descendants(scope, depth)
+                    .into_iter()
+                    .filter(|id| !excluded.contains(id))
+                    .collect())
+            }
+            Selector::Has(inner) => {
+                let mut results = Vec::new();
+                for candidate in self.descendants(scope, depth) {
+                    if !self.query(inner, candidate, None)?.is_empty() {
+                        results.push(candidate);
+                    }
+                }
+                Ok(results)
+            }
+            Selector::RightOf(inner)
+            | Selector::LeftOf(inner)
+            | Selector::Above(inner)
+            | Selector::Below(inner)
+            | Selector::Near(inner) => {
+                let anchor = *self.query(inner, scope, depth)?.first().ok_or_else(|| {
+                    AutomationError::ElementNotFound(format!("Anchor element not found: {inner:?}"))
+                })?;
+                let anchor_bounds = self.bounds(anchor).ok_or_else(|| {
+                    AutomationError::ElementNotFound(format!(
+                        "Anchor element has no bounds: {inner:?}"
+                    ))
+                })?;
+                let mut results = Vec::new();
+                for id in self.descendants(scope, depth) {
+                    if id == anchor || !self.is_visible(id) {
+                        continue;
+                    }
+                    if let Some(bounds) = self.bounds(id) {
+                        if spatially_related(selector, anchor_bounds, bounds) {
+                            results.push((id, distance(anchor_bounds, bounds)));
+                        }
+                    }
+                }
+                // Closest first, like the Windows engine's find_element
+                results.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+                Ok(results.into_iter().map(|(id, _)| id).collect())
+            }
+            _ => {
+                let mut results = Vec::new();
+                for id in self.descendants(scope, depth) {
+                    if self.matches_atomic(id, selector).unwrap_or(false) {
+                        results.push(id);
+                    }
+                }
+                Ok(results)
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! UI tree building functionality for Linux
+
+use super::bus::{AccessibleRef, AtspiBus};
+use super::element::{element_id, read_value};
+use super::roles::{is_enabled, is_toggled};
+use super::search::{NodeId, Search};
+use crate::platforms::{PropertyLoadingMode, TreeBuildConfig};
+use crate::{AutomationError, UIElementAttributes, UINode};
+use atspi_common::State;
+use std::collections::{HashMap, HashSet};
+use tracing::debug;
+
+/// Build a selector segment for a single element (e.g., "role:Button && name:Submit")
+/// Only includes name if it's non-empty and meaningful
+fn build_selector_segment(role: &str, name: Option<&str>) -> String {
+    match name {
+        Some(n) if !n.is_empty() => format!("role:{} && name:{}", role, n),
+        _ => format!("role:{}", role),
+    }
+}
+
+struct TreeBuilder<'a> {
+    search: Search<'a>,
+    bus: &'a AtspiBus,
+    config: &'a TreeBuildConfig,
+    application_name: Option<String>,
+    visited: HashSet<NodeId>,
+    elements_processed: usize,
+}
+
+/// Build the `UINode` tree rooted at `root`, honouring depth, bounds and property settings
+pub(crate) fn build_tree(
+    bus: &AtspiBus,
+    root: &AccessibleRef,
+    config: &TreeBuildConfig,
+) -> Result<UINode, AutomationError> {
+    let application_name = bus
+        .accessible(root)
+        .ok()
+        .and_then(|a| a.get_application().ok())
+        .and_then(|app| AccessibleRef::from_object_ref(&app))
+        .and_then(|app| bus.accessible(&app).ok()?.name().ok())
+        .filter(|n| !n.is_empty());
+
+    let mut builder = TreeBuilder {
+        search: Search::new(bus),
+        bus,
+        config,
+        application_name,
+        visited: HashSet::new(),
+        elements_processed: 0,
+    };
+    let root_id = builder.search.add(root.clone());
+    let node = builder.build(root_id, 0, &[]).ok_or_else(|| {
+        AutomationError::ElementDetached("Tree root is no longer available".to_string())
+    })?;
+    debug!(
+        "Built Linux UI tree with {} elements",
+        builder.elements_processed
+    );
+    Ok(node)
+}
+
+impl TreeBuilder<'_> {
+    fn build(&mut self, id: NodeId, depth: usize, selector_path: &[String]) -> Option<UINode> {
+        // Broken toolkits occasionally report cycles
+        if !self.visited.insert(id) {
+            return None;
+        }
+        let info = self.search.info(id)?;
+        self.elements_processed += 1;
+
+        let role = info.role_name().to_string();
+        let name = Some(info.name.clone()).filter(|n| !n.is_empty());
+        let states = info.states;
+        let focusable = states.contains(State::Focusable);
+
+        let bounds = if self.config.include_all_bounds || focusable {
+            self.search.bounds(id)
+        } else {
+            None
+        };
+
+        let mut current_path = selector_path.to_vec();
+        current_path.push(build_selector_segment(&role, name.as_deref()));
+
+        let complete = matches!(self.config.property_mode, PropertyLoadingMode::Complete);
+        let smart = matches!(self.config.property_mode, PropertyLoadingMode::Smart);
+        let node_ref = self.search.node(id).clone();
+        let value = if complete || (smart && matches!(role.as_str(), "Edit" | "ComboBox")) {
+            read_value(self.bus, &node_ref).ok().flatten()
+        } else {
+            None
+        };
+        let description = if complete {
+            self.bus
+                .accessible(&node_ref)
+                .ok()
+                .and_then(|a| a.description().ok())
+                .filter(|d| !d.is_empty())
+        } else {
+            None
+        };
+
+        let mut node = UINode {
+            id: Some(element_id(&node_ref)),
+            attributes: UIElementAttributes {
+                role,
+                name,
+                value,
+                description,
+                application_name: self.application_name.clone(),
+                properties: HashMap::new(),
+                is_keyboard_focusable: Some(focusable).filter(|&f| f),
+                is_focused: Some(states.contains(State::Focused)).filter(|&f| f),
+                is_toggled: states
+                    .contains(State::Checkable)
+                    .then(|| is_toggled(states)),
+                bounds,
+                enabled: Some(is_enabled(states)),
+                is_selected: Some(states.contains(State::Selected)).filter(|&s| s),
+                ..Default::default()
+            },
+            children: Vec::new(),
+            selector: Some(current_path.join(" >> ")),
+        };
+
+        if self.config.max_depth.is_none_or(|max| depth < max) {
+            for child in self.search.children(id) {
+                if let Some(child_node) = self.build(child, depth + 1, &current_path) {
+                    node.children.push(child_node);
+                }
+            }
+        }
+        Some(node)
+    }
+}
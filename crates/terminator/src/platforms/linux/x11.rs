@@ -0,0 +1,285 @@
+//! Shared X11 connection and EWMH window operations
+//!
+//! AT-SPI has no notion of native windows, so window-level operations (activate,
+//! minimize, maximize, close, opacity) go through the window manager using the
+//! EWMH/ICCCM conventions. Top-level windows are matched to accessibles by
+//! `_NET_WM_PID` and title.
+
+use crate::AutomationError;
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    AtomEnum, ClientMessageEvent, ConfigureWindowAux, ConnectionExt as _, EventMask, InputFocus,
+    PropMode, StackMode, Window,
+};
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::CURRENT_TIME;
+
+/// ICCCM `IconicState`, requested through `WM_CHANGE_STATE` to minimize a window
+const ICONIC_STATE: u32 = 3;
+/// `_NET_WM_STATE_ADD` action of the `_NET_WM_STATE` client message
+const NET_WM_STATE_ADD: u32 = 1;
+/// Source indication for EWMH requests: 2 = pager/automation tool
+const SOURCE_PAGER: u32 = 2;
+
+/// An open connection to the X server
+pub(crate) struct X11Display {
+    pub(crate) conn: RustConnection,
+    pub(crate) root: Window,
+}
+
+static DISPLAY: Mutex<Option<Arc<X11Display>>> = Mutex::new(None);
+
+/// The process-wide X11 connection, opened on first use from `$DISPLAY`
+pub(crate) fn display() -> Result<Arc<X11Display>, AutomationError> {
+    let mut guard = DISPLAY
+        .lock()
+        .map_err(|_| AutomationError::Internal("X11 connection lock poisoned".to_string()))?;
+    if let Some(display) = guard.as_ref() {
+        return Ok(display.clone());
+    }
+
+    let (conn, screen) = x11rb::connect(None).map_err(|e| {
+        AutomationError::PlatformError(format!(
+            "Failed to connect to the X server (is DISPLAY set? Wayland sessions need XWayland): {e}"
+        ))
+    })?;
+    let root = conn.setup().roots[screen].root;
+    let display = Arc::new(X11Display { conn, root });
+    *guard = Some(display.clone());
+    Ok(display)
+}
+
+pub(crate) fn x11_error(context: &str, error: impl std::fmt::Display) -> AutomationError {
+    AutomationError::PlatformError(format!("{context}: {error}"))
+}
+
+impl X11Display {
+    pub(crate) fn atom(&self, name: &str) -> Result<u32, AutomationError> {
+        self.conn
+            .intern_atom(false, name.as_bytes())
+            .map_err(|e| x11_error("Failed to intern atom", e))?
+            .reply()
+            .map(|reply| reply.atom)
+            .map_err(|e| x11_error("Failed to intern atom", e))
+    }
+
+    fn property32(&self, window: Window, name: &str) -> Result<Vec<u32>, AutomationError> {
+        let atom = self.atom(name)?;
+        let reply = self
+            .conn
+            .get_property(false, window, atom, AtomEnum::ANY, 0, u32::MAX)
+            .map_err(|e| x11_error("Failed to read window property", e))?
+            .reply()
+            .map_err(|e| x11_error("Failed to read window property", e))?;
+        Ok(reply
+            .value32()
+            .map(|values| values.collect())
+            .unwrap_or_default())
+    }
+
+    fn property_string(&self, window: Window, name: &str) -> Option<String> {
+        let atom = self.atom(name).ok()?;
+        let reply = self
+            .conn
+            .get_property(false, window, atom, AtomEnum::ANY, 0, u32::MAX)
+            .ok()?
+            .reply()
+            .ok()?;
+        (!reply.value.is_empty()).then(|| String::from_utf8_lossy(&reply.value).into_owned())
+    }
+
+    /// Title of a top-level window (`_NET_WM_NAME`, falling back to `WM_NAME`)
+    pub(crate) fn window_title(&self, window: Window) -> Option<String> {
+        self.property_string(window, "_NET_WM_NAME")
+            .or_else(|| self.property_string(window, "WM_NAME"))
+    }
+
+    /// Process that owns a top-level window, from `_NET_WM_PID`
+    pub(crate) fn window_pid(&self, window: Window) -> Option<u32> {
+        self.property32(window, "_NET_WM_PID")
+            .ok()?
+            .first()
+            .copied()
+    }
+
+    /// Top-level windows managed by the window manager, in stacking order
+    pub(crate) fn client_windows(&self) -> Result<Vec<Window>, AutomationError> {
+        let clients = self.property32(self.root, "_NET_CLIENT_LIST_STACKING")?;
+        if !clients.is_empty() {
+            return Ok(clients);
+        }
+        let clients = self.property32(self.root, "_NET_CLIENT_LIST")?;
+        if !clients.is_empty() {
+            return Ok(clients);
+        }
+
+        // No EWMH window manager (e.g. bare Xvfb): fall back to mapped children of the root
+        let tree = self
+            .conn
+            .query_tree(self.root)
+            .map_err(|e| x11_error("Failed to query window tree", e))?
+            .reply()
+            .map_err(|e| x11_error("Failed to query window tree", e))?;
+        Ok(tree.children)
+    }
+
+    /// Find the top-level window of `pid`, preferring the one whose title matches `title`
+    pub(crate) fn find_window(
+        &self,
+        pid: u32,
+        title: Option<&str>,
+    ) -> Result<Option<Window>, AutomationError> {
+        let windows: Vec<Window> = self
+            .client_windows()?
+            .into_iter()
+            .filter(|&w| self.window_pid(w) == Some(pid))
+            .collect();
+
+        if let Some(title) = title.filter(|t| !t.is_empty()) {
+            let wanted = title.to_lowercase();
+            let titled = |exact: bool| {
+                windows.iter().copied().find(|&w| {
+                    self.window_title(w).is_some_and(|t| {
+                        let t = t.to_lowercase();
+                        if exact {
+                            t == wanted
+                        } else {
+                            t.contains(&wanted) || wanted.contains(&t)
+                        }
+                    })
+                })
+            };
+            if let Some(window) = titled(true).or_else(|| titled(false)) {
+                return Ok(Some(window));
+            }
+        }
+        // Topmost window of the process wins when the title does not help
+        Ok(windows.last().copied())
+    }
+
+    /// The window the window manager reports as active
+    pub(crate) fn active_window(&self) -> Option<Window> {
+        self.property32(self.root, "_NET_ACTIVE_WINDOW")
+            .ok()?
+            .first()
+            .copied()
+            .filter(|&w| w != 0)
+    }
+
+    fn supports(&self, hint: &str) -> bool {
+        let Ok(hint) = self.atom(hint) else {
+            return false;
+        };
+        self.property32(self.root, "_NET_SUPPORTED")
+            .is_ok_and(|supported| supported.contains(&hint))
+    }
+
+    fn send_client_message(
+        &self,
+        window: Window,
+        message: &str,
+        data: [u32; 5],
+    ) -> Result<(), AutomationError> {
+        let event = ClientMessageEvent::new(32, window, self.atom(message)?, data);
+        self.conn
+            .send_event(
+                false,
+                self.root,
+                EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+                event,
+            )
+            .map_err(|e| x11_error("Failed to send window manager request", e))?;
+        self.conn
+            .flush()
+            .map_err(|e| x11_error("Failed to flush X11 connection", e))
+    }
+
+    /// Bring a window to the front and give it keyboard focus
+    pub(crate) fn activate(&self, window: Window) -> Result<(), AutomationError> {
+        if self.supports("_NET_ACTIVE_WINDOW") {
+            debug!(
+                "Activating window 0x{:x} through _NET_ACTIVE_WINDOW",
+                window
+            );
+            return self.send_client_message(
+                window,
+                "_NET_ACTIVE_WINDOW",
+                [SOURCE_PAGER, CURRENT_TIME, 0, 0, 0],
+            );
+        }
+
+        // Without a window manager, raise and focus the window ourselves
+        debug!(
+            "Activating window 0x{:x} directly (no EWMH support)",
+            window
+        );
+        self.conn
+            .map_window(window)
+            .and_then(|_| {
+                self.conn.configure_window(
+                    window,
+                    &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
+                )
+            })
+            .and_then(|_| {
+                self.conn
+                    .set_input_focus(InputFocus::PARENT, window, CURRENT_TIME)
+            })
+            .map_err(|e| x11_error("Failed to activate window", e))?;
+        self.conn
+            .flush()
+            .map_err(|e| x11_error("Failed to flush X11 connection", e))
+    }
+
+    pub(crate) fn minimize(&self, window: Window) -> Result<(), AutomationError> {
+        self.send_client_message(window, "WM_CHANGE_STATE", [ICONIC_STATE, 0, 0, 0, 0])
+    }
+
+    pub(crate) fn maximize(&self, window: Window) -> Result<(), AutomationError> {
+        let vertical = self.atom("_NET_WM_STATE_MAXIMIZED_VERT")?;
+        let horizontal = self.atom("_NET_WM_STATE_MAXIMIZED_HORZ")?;
+        self.send_client_message(
+            window,
+            "_NET_WM_STATE",
+            [NET_WM_STATE_ADD, vertical, horizontal, SOURCE_PAGER, 0],
+        )
+    }
+
+    /// Ask the window to close itself (ICCCM `WM_DELETE_WINDOW`), like clicking its close button
+    pub(crate) fn close(&self, window: Window) -> Result<(), AutomationError> {
+        let protocols = self.atom("WM_PROTOCOLS")?;
+        let delete = self.atom("WM_DELETE_WINDOW")?;
+        let event = ClientMessageEvent::new(32, window, protocols, [delete, CURRENT_TIME, 0, 0, 0]);
+        self.conn
+            .send_event(false, window, EventMask::NO_EVENT, event)
+            .map_err(|e| x11_error("Failed to close window", e))?;
+        self.conn
+            .flush()
+            .map_err(|e| x11_error("Failed to flush X11 connection", e))
+    }
+
+    /// Set window opacity through `_NET_WM_WINDOW_OPACITY` (applied by the compositor)
+    pub(crate) fn set_opacity(
+        &self,
+        window: Window,
+        percentage: u8,
+    ) -> Result<(), AutomationError> {
+        let opacity = (u64::from(percentage.min(100)) * u64::from(u32::MAX) / 100) as u32;
+        let atom = self.atom("_NET_WM_WINDOW_OPACITY")?;
+        self.conn
+            .change_property32(
+                PropMode::REPLACE,
+                window,
+                atom,
+                AtomEnum::CARDINAL,
+                &[opacity],
+            )
+            .map_err(|e| x11_error("Failed to set window opacity", e))?;
+        self.conn
+            .flush()
+            .map_err(|e| x11_error("Failed to flush X11 connection", e))
+    }
+}
@@ -0,0 +1,113 @@
+use super::linux::*;
+use crate::platforms::{AccessibilityEngine, PropertyLoadingMode, TreeBuildConfig};
+use crate::{AutomationError, Selector};
+use std::process;
+use std::time::Duration;
+
+// These tests need an AT-SPI2 accessibility bus (see the `linux` module docs for a
+// headless Xvfb + dbus-run-session setup) and skip themselves when none is reachable.
+
+fn engine() -> Option<LinuxEngine> {
+    match LinuxEngine::new(false, false) {
+        Ok(engine) => Some(engine),
+        Err(e) => {
+            println!("Cannot create LinuxEngine ({e}), skipping test");
+            None
+        }
+    }
+}
+
+#[test]
+fn test_get_process_name_by_pid_current_process() {
+    let current_pid = process::id() as i32;
+    let process_name =
+        get_process_name_by_pid(current_pid).expect("Should be able to get current process name");
+
+    assert!(!process_name.is_empty(), "Process name should not be empty");
+    assert!(
+        !is_browser_process(process::id()),
+        "The test runner should not be detected as a browser"
+    );
+}
+
+#[test]
+fn test_root_element_is_desktop() {
+    let Some(engine) = engine() else { return };
+    let root = engine.get_root_element();
+    assert_eq!(root.role(), "Desktop");
+}
+
+#[test]
+fn test_find_elements_requires_process_scope() {
+    let Some(engine) = engine() else { return };
+    let result = engine.find_elements(
+        &Selector::Role {
+            role: "Button".to_string(),
+            name: None,
+        },
+        None,
+        Some(Duration::ZERO),
+        None,
+    );
+    assert!(
+        matches!(result, Err(AutomationError::InvalidSelector(_))),
+        "Desktop-wide searches should be rejected, got {result:?}"
+    );
+}
+
+#[test]
+fn test_applications_have_process_ids() {
+    let Some(engine) = engine() else { return };
+    let applications = match engine.get_applications() {
+        Ok(apps) => apps,
+        Err(e) => {
+            println!("Cannot get applications ({e}), skipping");
+            return;
+        }
+    };
+    println!("Found {} accessible applications", applications.len());
+    for app in applications {
+        if let Ok(pid) = app.process_id() {
+            assert!(pid > 0, "Application PIDs should be positive");
+        }
+    }
+}
+
+#[test]
+fn test_unknown_pid_is_not_found() {
+    let Some(engine) = engine() else { return };
+    let result = engine.get_application_by_pid(i32::MAX, Some(Duration::ZERO));
+    assert!(
+        matches!(result, Err(AutomationError::ElementNotFound(_))),
+        "Unexpected result for an unknown PID: {result:?}"
+    );
+}
+
+#[test]
+fn test_window_tree_respects_max_depth() {
+    let Some(engine) = engine() else { return };
+    let Some(app) = engine
+        .get_applications()
+        .ok()
+        .and_then(|a| a.into_iter().next())
+    else {
+        println!("No accessible applications running, skipping");
+        return;
+    };
+    let config = TreeBuildConfig {
+        property_mode: PropertyLoadingMode::Fast,
+        max_depth: Some(1),
+        ..Default::default()
+    };
+    let tree = match engine.get_tree_from_element(&app, config) {
+        Ok(tree) => tree,
+        Err(e) => {
+            println!("Cannot build tree ({e}), skipping");
+            return;
+        }
+    };
+    assert!(
+        tree.children.iter().all(|child| child.children.is_empty()),
+        "max_depth 1 should stop below the first level"
+    );
+}
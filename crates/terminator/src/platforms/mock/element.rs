@@ -0,0 +1,625 @@
+//! Mock implementation of `UIElementImpl` over the in-memory tree
+
+use super::engine::{element_at_point, mock_monitor, query, MockEngine};
+use super::tree::{property_as_string, MockTree, NodeIndex, ROOT};
+use crate::element::UIElementImpl;
+use crate::{
+    AutomationError, ClickResult, ClickType, FontStyle, HighlightHandle, Locator, Monitor,
+    ScreenshotResult, Selector, TextPosition, UIElement, UIElementAttributes,
+};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+/// A live handle to a node of a [`super::MockDesktop`].
+///
+/// Actions mutate the shared tree (focus, toggle/selection state, values) and are
+/// appended to the desktop's action log so tests can assert on them.
+#[derive(Debug, Clone)]
+pub struct MockUIElement {
+    engine: MockEngine,
+    index: NodeIndex,
+}
+
+impl MockUIElement {
+    pub(crate) fn new(engine: MockEngine, index: NodeIndex) -> Self {
+        Self { engine, index }
+    }
+
+    pub(crate) fn engine(&self) -> &MockEngine {
+        &self.engine
+    }
+
+    pub(crate) fn index(&self) -> NodeIndex {
+        self.index
+    }
+
+    fn element(&self, index: NodeIndex) -> UIElement {
+        self.engine.element(index)
+    }
+
+    fn ensure_attached(&self, tree: &MockTree) -> Result<(), AutomationError> {
+        if tree.nodes[self.index].removed {
+            return Err(AutomationError::ElementDetached(format!(
+                "Element '{}' is no longer in the tree",
+                tree.nodes[self.index].id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Run an action against this element: checks it is attached and enabled,
+    /// applies `apply` to the tree and records the action.
+    fn act<T>(
+        &self,
+        action: &str,
+        detail: Option<String>,
+        apply: impl FnOnce(&mut MockTree, NodeIndex) -> Result<T, AutomationError>,
+    ) -> Result<T, AutomationError> {
+        let mut tree = self.engine.desktop().write();
+        self.ensure_attached(&tree)?;
+        if tree.nodes[self.index].attributes.enabled == Some(false) {
+            return Err(AutomationError::ElementNotEnabled(format!(
+                "Cannot {action} disabled element '{}'",
+                tree.nodes[self.index].id
+            )));
+        }
+        let result = apply(&mut tree, self.index)?;
+        tree.record(self.index, action, detail);
+        Ok(result)
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&MockTree, NodeIndex) -> T) -> Result<T, AutomationError> {
+        let tree = self.engine.desktop().read();
+        self.ensure_attached(&tree)?;
+        Ok(f(&tree, self.index))
+    }
+
+    fn center(&self) -> Option<(f64, f64)> {
+        let (x, y, w, h) = self.bounds().ok()?;
+        Some((x + w / 2.0, y + h / 2.0))
+    }
+
+    fn click_result(&self, method: &str) -> ClickResult {
+        ClickResult {
+            method: method.to_string(),
+            coordinates: self.center(),
+            details: "Mock click".to_string(),
+        }
+    }
+
+    fn window_action(&self, action: &str) -> Result<(), AutomationError> {
+        let window = self
+            .read(|tree, index| tree.application_of(index))?
+            .unwrap_or(self.index);
+        let mut tree = self.engine.desktop().write();
+        if action == "activate_window" {
+            tree.set_focus(window);
+        }
+        tree.record(window, action, None);
+        Ok(())
+    }
+}
+
+/// Applies the state change a click causes for the role of the clicked node
+fn apply_click(tree: &mut MockTree, index: NodeIndex) {
+    tree.set_focus(index);
+    let role = tree.nodes[index].attributes.role.to_lowercase();
+    match role.as_str() {
+        "checkbox" | "togglebutton" => {
+            let attrs = &mut tree.nodes[index].attributes;
+            attrs.is_toggled = Some(!attrs.is_toggled.unwrap_or(false));
+        }
+        "radiobutton" => select(tree, index, true),
+        "listitem" | "tabitem" | "treeitem" | "dataitem" => {
+            tree.nodes[index].attributes.is_selected = Some(true)
+        }
+        _ => {}
+    }
+}
+
+/// Selects a node; radio buttons also clear their selected siblings
+fn select(tree: &mut MockTree, index: NodeIndex, state: bool) {
+    if state
+        && tree.nodes[index]
+            .attributes
+            .role
+            .eq_ignore_ascii_case("radiobutton")
+    {
+        if let Some(parent) = tree.nodes[index].parent {
+            for sibling in tree.nodes[parent].children.clone() {
+                if tree.nodes[sibling]
+                    .attributes
+                    .role
+                    .eq_ignore_ascii_case("radiobutton")
+                {
+                    tree.nodes[sibling].attributes.is_selected = None;
+                }
+            }
+        }
+    }
+    tree.nodes[index].attributes.is_selected = state.then_some(true);
+}
+
+fn collect_text(tree: &MockTree, index: NodeIndex, max_depth: usize, parts: &mut Vec<String>) {
+    let attrs = &tree.nodes[index].attributes;
+    for text in [&attrs.name, &attrs.value, &attrs.text]
+        .into_iter()
+        .flatten()
+    {
+        if !text.is_empty() && !parts.contains(text) {
+            parts.push(text.clone());
+        }
+    }
+    if max_depth > 0 {
+        for &child in &tree.nodes[index].children {
+            collect_text(tree, child, max_depth - 1, parts);
+        }
+    }
+}
+
+/// Roles the mock treats as text inputs
+fn is_text_input_role(role: &str) -> bool {
+    ["Edit", "Document", "ComboBox", "TextBox", "SearchBox"]
+        .iter()
+        .any(|r| r.eq_ignore_ascii_case(role))
+}
+
+impl UIElementImpl for MockUIElement {
+    fn object_id(&self) -> usize {
+        // Unique per desktop and node, stable across handles to the same node
+        self.engine
+            .desktop()
+            .tree_ptr()
+            .wrapping_mul(31)
+            .wrapping_add(self.index)
+    }
+
+    fn id(&self) -> Option<String> {
+        Some(self.engine.desktop().read().nodes[self.index].id.clone())
+    }
+
+    fn role(&self) -> String {
+        self.engine.desktop().read().nodes[self.index]
+            .attributes
+            .role
+            .clone()
+    }
+
+    fn attributes(&self) -> UIElementAttributes {
+        let tree = self.engine.desktop().read();
+        let node = &tree.nodes[self.index];
+        let mut attributes = node.attributes.clone();
+        attributes.is_focused = (tree.focused == Some(self.index)).then_some(true);
+        attributes.child_count = Some(node.children.len());
+        attributes
+    }
+
+    fn children(&self) -> Result<Vec<UIElement>, AutomationError> {
+        let children = self.read(|tree, index| tree.nodes[index].children.clone())?;
+        Ok(children.into_iter().map(|i| self.element(i)).collect())
+    }
+
+    fn parent(&self) -> Result<Option<UIElement>, AutomationError> {
+        let parent = self.read(|tree, index| tree.nodes[index].parent)?;
+        Ok(parent.map(|i| self.element(i)))
+    }
+
+    fn bounds(&self) -> Result<(f64, f64, f64, f64), AutomationError> {
+        self.read(|tree, index| tree.nodes[index].attributes.bounds)?
+            .ok_or_else(|| {
+                AutomationError::PlatformError(format!(
+                    "Element '{}' has no bounds",
+                    self.id().unwrap_or_default()
+                ))
+            })
+    }
+
+    fn click(&self) -> Result<ClickResult, AutomationError> {
+        self.act("click", None, |tree, index| {
+            apply_click(tree, index);
+            Ok(())
+        })?;
+        Ok(self.click_result("MockClick"))
+    }
+
+    fn double_click(&self) -> Result<ClickResult, AutomationError> {
+        self.act("double_click", None, |tree, index| {
+            tree.set_focus(index);
+            Ok(())
+        })?;
+        Ok(self.click_result("MockDoubleClick"))
+    }
+
+    fn right_click(&self) -> Result<(), AutomationError> {
+        self.act("right_click", None, |tree, index| {
+            tree.set_focus(index);
+            Ok(())
+        })
+    }
+
+    fn click_at_position(
+        &self,
+        x_pct: u8,
+        y_pct: u8,
+        click_type: ClickType,
+    ) -> Result<ClickResult, AutomationError> {
+        let (x, y, w, h) = self.bounds()?;
+        let point = (
+            x + w * f64::from(x_pct.min(100)) / 100.0,
+            y + h * f64::from(y_pct.min(100)) / 100.0,
+        );
+        self.act(
+            "click_at_position",
+            Some(format!("{x_pct}%,{y_pct}% {click_type:?}")),
+            |tree, index| {
+                if click_type == ClickType::Left {
+                    apply_click(tree, index);
+                } else {
+                    tree.set_focus(index);
+                }
+                Ok(())
+            },
+        )?;
+        Ok(ClickResult {
+            method: "MockClickAtPosition".to_string(),
+            coordinates: Some(point),
+            details: format!("Clicked at {x_pct}%,{y_pct}%"),
+        })
+    }
+
+    fn hover(&self) -> Result<(), AutomationError> {
+        self.act("hover", None, |_, _| Ok(()))
+    }
+
+    fn focus(&self) -> Result<(), AutomationError> {
+        self.act("focus", None, |tree, index| {
+            tree.set_focus(index);
+            Ok(())
+        })
+    }
+
+    fn invoke(&self) -> Result<(), AutomationError> {
+        self.act("invoke", None, |tree, index| {
+            apply_click(tree, index);
+            Ok(())
+        })
+    }
+
+    fn type_text(
+        &self,
+        text: &str,
+        _use_clipboard: bool,
+        _try_focus_before: bool,
+        _try_click_before: bool,
+        _restore_focus: bool,
+    ) -> Result<(), AutomationError> {
+        self.act("type_text", Some(text.to_string()), |tree, index| {
+            tree.set_focus(index);
+            let value = &mut tree.nodes[index].attributes.value;
+            value.get_or_insert_with(String::new).push_str(text);
+            Ok(())
+        })
+    }
+
+    fn press_key(
+        &self,
+        key: &str,
+        _try_focus_before: bool,
+        _try_click_before: bool,
+        _restore_focus: bool,
+    ) -> Result<(), AutomationError> {
+        self.act("press_key", Some(key.to_string()), |tree, index| {
+            tree.set_focus(index);
+            Ok(())
+        })
+    }
+
+    fn get_text(&self, max_depth: usize) -> Result<String, AutomationError> {
+        self.read(|tree, index| {
+            let mut parts = Vec::new();
+            collect_text(tree, index, max_depth, &mut parts);
+            parts.join("\n")
+        })
+    }
+
+    fn set_value(&self, value: &str) -> Result<(), AutomationError> {
+        self.act("set_value", Some(value.to_string()), |tree, index| {
+            tree.nodes[index].attributes.value = Some(value.to_string());
+            Ok(())
+        })
+    }
+
+    fn get_value(&self) -> Result<Option<String>, AutomationError> {
+        self.read(|tree, index| tree.nodes[index].attributes.value.clone())
+    }
+
+    fn is_enabled(&self) -> Result<bool, AutomationError> {
+        self.read(|tree, index| tree.nodes[index].attributes.enabled.unwrap_or(true))
+    }
+
+    fn is_visible(&self) -> Result<bool, AutomationError> {
+        let visible = Selector::Visible(true);
+        self.read(|tree, index| {
+            query(
+                tree,
+                &visible,
+                tree.nodes[index].parent.unwrap_or(ROOT),
+                Some(1),
+            )
+            .is_ok_and(|found| found.contains(&index))
+        })
+    }
+
+    fn is_focused(&self) -> Result<bool, AutomationError> {
+        self.read(|tree, index| tree.focused == Some(index))
+    }
+
+    fn perform_action(&self, action: &str) -> Result<(), AutomationError> {
+        match action {
+            "click" | "invoke" => self.click().map(|_| ()),
+            "double_click" => self.double_click().map(|_| ()),
+            "right_click" => self.right_click(),
+            "focus" => self.focus(),
+            "toggle" => self.set_toggled(!self.is_toggled()?),
+            "select" => self.set_selected(true),
+            "expand_collapse" | "scroll_into_view" => self.act(action, None, |_, _| Ok(())),
+            _ => Err(AutomationError::UnsupportedOperation(format!(
+                "Action '{action}' is not supported by the mock engine"
+            ))),
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn create_locator(&self, selector: Selector) -> Result<Locator, AutomationError> {
+        Ok(Locator::new(Arc::new(self.engine.clone()), selector)
+            .within(UIElement::new(self.clone_box())))
+    }
+
+    fn scroll(&self, direction: &str, amount: f64) -> Result<(), AutomationError> {
+        self.act("scroll", Some(format!("{direction} {amount}")), |_, _| {
+            Ok(())
+        })
+    }
+
+    fn activate_window(&self) -> Result<(), AutomationError> {
+        self.window_action("activate_window")
+    }
+
+    fn minimize_window(&self) -> Result<(), AutomationError> {
+        self.window_action("minimize_window")
+    }
+
+    fn maximize_window(&self) -> Result<(), AutomationError> {
+        self.window_action("maximize_window")
+    }
+
+    fn maximize_window_keyboard(&self) -> Result<(), AutomationError> {
+        self.window_action("maximize_window")
+    }
+
+    fn minimize_window_keyboard(&self) -> Result<(), AutomationError> {
+        self.window_action("minimize_window")
+    }
+
+    fn get_native_window_handle(&self) -> Result<isize, AutomationError> {
+        let window = self
+            .read(|tree, index| tree.application_of(index))?
+            .unwrap_or(self.index);
+        Ok(window as isize)
+    }
+
+    fn clone_box(&self) -> Box<dyn UIElementImpl> {
+        Box::new(self.clone())
+    }
+
+    fn is_keyboard_focusable(&self) -> Result<bool, AutomationError> {
+        self.read(|tree, index| {
+            tree.nodes[index]
+                .attributes
+                .is_keyboard_focusable
+                .unwrap_or(false)
+        })
+    }
+
+    fn is_editable(&self) -> Result<bool, AutomationError> {
+        self.read(|tree, index| {
+            let attributes = &tree.nodes[index].attributes;
+            is_text_input_role(&attributes.role)
+                && property_as_string(attributes, "IsReadOnly").as_deref() != Some("true")
+        })
+    }
+
+    fn element_at_point(&self, x: f64, y: f64) -> Result<Option<UIElement>, AutomationError> {
+        let hit = element_at_point(&self.engine.desktop().read(), x, y);
+        Ok(hit.map(|index| self.engine.element(index)))
+    }
+
+    fn mouse_drag(
+        &self,
+        start_x: f64,
+        start_y: f64,
+        end_x: f64,
+        end_y: f64,
+    ) -> Result<(), AutomationError> {
+        self.act(
+            "mouse_drag",
+            Some(format!("{start_x},{start_y} -> {end_x},{end_y}")),
+            |_, _| Ok(()),
+        )
+    }
+
+    fn mouse_click_and_hold(&self, x: f64, y: f64) -> Result<(), AutomationError> {
+        self.act("mouse_click_and_hold", Some(format!("{x},{y}")), |_, _| {
+            Ok(())
+        })
+    }
+
+    fn mouse_move(&self, x: f64, y: f64) -> Result<(), AutomationError> {
+        self.act("mouse_move", Some(format!("{x},{y}")), |_, _| Ok(()))
+    }
+
+    fn mouse_release(&self) -> Result<(), AutomationError> {
+        self.act("mouse_release", None, |_, _| Ok(()))
+    }
+
+    fn application(&self) -> Result<Option<UIElement>, AutomationError> {
+        self.window()
+    }
+
+    fn window(&self) -> Result<Option<UIElement>, AutomationError> {
+        let window = self.read(|tree, index| {
+            if tree.nodes[index].parent == Some(ROOT) {
+                Some(index)
+            } else {
+                tree.application_of(index)
+            }
+        })?;
+        Ok(window.map(|i| self.element(i)))
+    }
+
+    fn highlight(
+        &self,
+        _color: Option<u32>,
+        _duration: Option<std::time::Duration>,
+        text: Option<&str>,
+        _text_position: Option<TextPosition>,
+        _font_style: Option<FontStyle>,
+    ) -> Result<HighlightHandle, AutomationError> {
+        self.read(|_, _| ())?;
+        self.engine
+            .desktop()
+            .write()
+            .record(self.index, "highlight", text.map(str::to_string));
+        Ok(HighlightHandle {
+            should_close: Arc::new(AtomicBool::new(false)),
+            handle: None,
+        })
+    }
+
+    fn set_transparency(&self, percentage: u8) -> Result<(), AutomationError> {
+        self.act("set_transparency", Some(percentage.to_string()), |_, _| {
+            Ok(())
+        })
+    }
+
+    fn process_id(&self) -> Result<u32, AutomationError> {
+        self.read(|tree, index| tree.nodes[index].process_id)
+    }
+
+    fn capture(&self) -> Result<ScreenshotResult, AutomationError> {
+        let (_, _, w, h) = self.bounds()?;
+        let (width, height) = (w.max(1.0) as u32, h.max(1.0) as u32);
+        Ok(ScreenshotResult {
+            image_data: vec![0; (width * height * 4) as usize],
+            width,
+            height,
+            monitor: None,
+        })
+    }
+
+    fn close(&self) -> Result<(), AutomationError> {
+        let window = self.read(|tree, index| tree.nodes[index].parent == Some(ROOT))?;
+        if !window {
+            // Like the real engines, closing a non-window element is a no-op
+            return Ok(());
+        }
+        let id = self.id().unwrap_or_default();
+        self.engine
+            .desktop()
+            .write()
+            .record(self.index, "close", None);
+        self.engine.desktop().remove(&id)
+    }
+
+    fn url(&self) -> Option<String> {
+        let tree = self.engine.desktop().read();
+        let window = tree.application_of(self.index).unwrap_or(self.index);
+        property_as_string(&tree.nodes[window].attributes, "Url")
+            .or_else(|| property_as_string(&tree.nodes[self.index].attributes, "Url"))
+    }
+
+    fn select_option(&self, option_name: &str) -> Result<(), AutomationError> {
+        let option = self.read(|tree, index| {
+            tree.descendants(index, None).into_iter().find(|&i| {
+                tree.nodes[i]
+                    .attributes
+                    .name
+                    .as_deref()
+                    .is_some_and(|n| n.eq_ignore_ascii_case(option_name))
+            })
+        })?;
+        let option = option.ok_or_else(|| {
+            AutomationError::ElementNotFound(format!("Option '{option_name}' not found"))
+        })?;
+        self.act(
+            "select_option",
+            Some(option_name.to_string()),
+            |tree, index| {
+                for sibling in tree.descendants(index, None) {
+                    tree.nodes[sibling].attributes.is_selected = None;
+                }
+                tree.nodes[option].attributes.is_selected = Some(true);
+                tree.nodes[index].attributes.value = Some(option_name.to_string());
+                Ok(())
+            },
+        )
+    }
+
+    fn list_options(&self) -> Result<Vec<String>, AutomationError> {
+        self.read(|tree, index| {
+            tree.descendants(index, None)
+                .into_iter()
+                .filter(|&i| {
+                    let role = tree.nodes[i].attributes.role.to_lowercase();
+                    role == "listitem" || role == "menuitem"
+                })
+                .filter_map(|i| tree.nodes[i].attributes.name.clone())
+                .collect()
+        })
+    }
+
+    fn is_toggled(&self) -> Result<bool, AutomationError> {
+        self.read(|tree, index| tree.nodes[index].attributes.is_toggled.unwrap_or(false))
+    }
+
+    fn set_toggled(&self, state: bool) -> Result<(), AutomationError> {
+        self.act("set_toggled", Some(state.to_string()), |tree, index| {
+            tree.nodes[index].attributes.is_toggled = Some(state);
+            Ok(())
+        })
+    }
+
+    fn get_range_value(&self) -> Result<f64, AutomationError> {
+        let value = self.get_value()?.unwrap_or_default();
+        value.trim().parse().map_err(|_| {
+            AutomationError::UnsupportedOperation(format!(
+                "Element value '{value}' is not a range value"
+            ))
+        })
+    }
+
+    fn set_range_value(&self, value: f64) -> Result<(), AutomationError> {
+        self.act("set_range_value", Some(value.to_string()), |tree, index| {
+            tree.nodes[index].attributes.value = Some(value.to_string());
+            Ok(())
+        })
+    }
+
+    fn is_selected(&self) -> Result<bool, AutomationError> {
+        self.read(|tree, index| tree.nodes[index].attributes.is_selected.unwrap_or(false))
+    }
+
+    fn set_selected(&self, state: bool) -> Result<(), AutomationError> {
+        self.act("set_selected", Some(state.to_string()), |tree, index| {
+            select(tree, index, state);
+            Ok(())
+        })
+    }
+
+    fn monitor(&self) -> Result<Monitor, AutomationError> {
+        Ok(mock_monitor())
+    }
+}
@@ -0,0 +1,535 @@
+//! Mock implementation of `AccessibilityEngine` over the in-memory tree
+
+use super::element::MockUIElement;
+use super::tree::{MockDesktop, MockTree, NodeIndex, ROOT};
+use crate::platforms::{selector_has_process_scope, AccessibilityEngine, TreeBuildConfig};
+pub(crate) use crate::query::query;
+use crate::query::{node_is_visible, process_matches, QueryTree};
+use crate::{
+    AutomationError, Browser, ClickType, CommandOutput, Monitor, ScreenshotResult, Selector,
+    UIElement, UIElementAttributes, UINode, WorkAreaBounds,
+};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Default time `find_element` keeps re-evaluating the tree before giving up
+const DEFAULT_FIND_TIMEOUT: Duration = Duration::from_millis(5000);
+/// How often the tree is re-evaluated while waiting for an element
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Accessibility engine backed by a [`MockDesktop`]
+#[derive(Debug, Clone)]
+pub struct MockEngine {
+    desktop: MockDesktop,
+}
+
+impl MockEngine {
+    /// Create an engine over the given desktop
+    pub fn new(desktop: MockDesktop) -> Self {
+        Self { desktop }
+    }
+
+    /// Create an engine over the registered fixture (see [`super::set_fixture`])
+    pub fn from_default_fixture() -> Result<Self, AutomationError> {
+        Ok(Self::new(super::default_fixture()?))
+    }
+
+    /// The desktop this engine operates on
+    pub fn desktop(&self) -> &MockDesktop {
+        &self.desktop
+    }
+
+    pub(crate) fn element(&self, index: NodeIndex) -> UIElement {
+        UIElement::new(Box::new(MockUIElement::new(self.clone(), index)))
+    }
+
+    fn elements(&self, indices: Vec<NodeIndex>) -> Vec<UIElement> {
+        indices.into_iter().map(|i| self.element(i)).collect()
+    }
+
+    /// Resolve the search root to a node index, rejecting elements from other engines
+    fn root_index(&self, root: Option<&UIElement>) -> Result<NodeIndex, AutomationError> {
+        match root {
+            None => Ok(ROOT),
+            Some(el) => match el.as_any().downcast_ref::<MockUIElement>() {
+                Some(mock) if mock.engine().desktop.tree_ptr() == self.desktop.tree_ptr() => {
+                    let tree = self.desktop.read();
+                    if tree.nodes[mock.index()].removed {
+                        Err(AutomationError::ElementDetached(format!(
+                            "Search root '{}' is no longer in the tree",
+                            tree.nodes[mock.index()].id
+                        )))
+                    } else {
+                        Ok(mock.index())
+                    }
+                }
+                _ => Err(AutomationError::PlatformError(
+                    "Search root is not an element of this mock desktop".to_string(),
+                )),
+            },
+        }
+    }
+
+    /// Re-run `query` against the tree until it returns something or the timeout elapses.
+    /// Each run sees a copy of the tree, so custom `filter:` predicates can read the
+    /// live desktop without re-entering its lock.
+    fn poll<F>(
+        &self,
+        timeout: Option<Duration>,
+        mut query: F,
+    ) -> Result<Vec<NodeIndex>, AutomationError>
+    where
+        F: FnMut(&MockTree) -> Result<Vec<NodeIndex>, AutomationError>,
+    {
+        let deadline = Instant::now() + timeout.unwrap_or(DEFAULT_FIND_TIMEOUT);
+        loop {
+            let tree = self.desktop.read().clone();
+            let found = query(&tree)?;
+            if !found.is_empty() || Instant::now() >= deadline {
+                return Ok(found);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    fn find_window(&self, pid: u32, title: Option<&str>) -> Result<NodeIndex, AutomationError> {
+        let tree = self.desktop.read();
+        let windows: Vec<NodeIndex> = tree.nodes[ROOT]
+            .children
+            .iter()
+            .copied()
+            .filter(|&i| tree.nodes[i].process_id == pid)
+            .collect();
+
+        let title_match = title.and_then(|title| {
+            let title = title.to_lowercase();
+            windows.iter().copied().find(|&i| {
+                tree.nodes[i]
+                    .attributes
+                    .name
+                    .as_deref()
+                    .is_some_and(|n| n.to_lowercase().contains(&title))
+            })
+        });
+
+        title_match
+            .or_else(|| windows.first().copied())
+            .ok_or_else(|| {
+                AutomationError::ElementNotFound(format!(
+                    "No window found for PID {pid} and title {title:?}"
+                ))
+            })
+    }
+}
+
+/// Tree view whose `filter:` predicates receive live elements of the engine
+struct LiveTree<'a> {
+    tree: &'a MockTree,
+    engine: &'a MockEngine,
+}
+
+impl QueryTree for LiveTree<'_> {
+    fn attributes(&self, index: usize) -> &UIElementAttributes {
+        self.tree.attributes(index)
+    }
+
+    fn node_id(&self, index: usize) -> Option<&str> {
+        self.tree.node_id(index)
+    }
+
+    fn parent(&self, index: usize) -> Option<usize> {
+        QueryTree::parent(self.tree, index)
+    }
+
+    fn children(&self, index: usize) -> &[usize] {
+        QueryTree::children(self.tree, index)
+    }
+
+    fn process_name(&self, index: usize) -> Option<&str> {
+        QueryTree::process_name(self.tree, index)
+    }
+
+    fn process_id(&self, index: usize) -> Option<u32> {
+        QueryTree::process_id(self.tree, index)
+    }
+
+    fn is_focused(&self, index: usize) -> bool {
+        QueryTree::is_focused(self.tree, index)
+    }
+
+    fn descendants(&self, index: usize, max_depth: Option<usize>) -> Vec<usize> {
+        self.tree.descendants(index, max_depth)
+    }
+
+    fn element(&self, index: usize) -> Option<UIElement> {
+        Some(self.engine.element(index))
+    }
+}
+
+/// The single display reported by the mock desktop
+pub(crate) fn mock_monitor() -> Monitor {
+    Monitor {
+        id: "mock-0".to_string(),
+        name: "Mock Display".to_string(),
+        is_primary: true,
+        width: 1920,
+        height: 1080,
+        x: 0,
+        y: 0,
+        scale_factor: 1.0,
+        work_area: Some(WorkAreaBounds {
+            x: 0,
+            y: 0,
+            width: 1920,
+            height: 1040,
+        }),
+    }
+}
+
+// ============== HIT TESTING ==============
+
+/// Deepest visible element whose bounds contain the point (the "hit test" target)
+pub(crate) fn element_at_point(tree: &MockTree, x: f64, y: f64) -> Option<NodeIndex> {
+    // Later nodes in document order are drawn on top, so search from the end
+    tree.descendants(ROOT, None)
+        .into_iter()
+        .rev()
+        .filter(|&i| node_is_visible(tree, i))
+        .find(|&i| {
+            tree.nodes[i]
+                .attributes
+                .bounds
+                .is_some_and(|(bx, by, bw, bh)| x >= bx && x < bx + bw && y >= by && y < by + bh)
+        })
+}
+
+#[async_trait::async_trait]
+impl AccessibilityEngine for MockEngine {
+    fn get_root_element(&self) -> UIElement {
+        self.element(ROOT)
+    }
+
+    fn get_element_by_id(&self, id: i32) -> Result<UIElement, AutomationError> {
+        let index = self.desktop.read().index_of(&id.to_string())?;
+        Ok(self.element(index))
+    }
+
+    fn get_focused_element(&self) -> Result<UIElement, AutomationError> {
+        let focused = self.desktop.read().focused;
+        focused
+            .map(|i| self.element(i))
+            .ok_or_else(|| AutomationError::ElementNotFound("No element has focus".to_string()))
+    }
+
+    fn get_applications(&self) -> Result<Vec<UIElement>, AutomationError> {
+        let apps = self.desktop.read().nodes[ROOT].children.clone();
+        Ok(self.elements(apps))
+    }
+
+    fn get_application_by_name(&self, name: &str) -> Result<UIElement, AutomationError> {
+        let tree = self.desktop.read();
+        let wanted = name.to_lowercase();
+        let app = tree.nodes[ROOT].children.iter().copied().find(|&i| {
+            let node = &tree.nodes[i];
+            process_matches(&node.process_name, name)
+                || node
+                    .attributes
+                    .name
+                    .as_deref()
+                    .is_some_and(|n| n.to_lowercase().contains(&wanted))
+        });
+        drop(tree);
+        app.map(|i| self.element(i)).ok_or_else(|| {
+            AutomationError::ElementNotFound(format!("Application '{name}' not found"))
+        })
+    }
+
+    fn get_application_by_pid(
+        &self,
+        pid: i32,
+        timeout: Option<Duration>,
+    ) -> Result<UIElement, AutomationError> {
+        let found = self.poll(timeout, |tree| {
+            Ok(tree.nodes[ROOT]
+                .children
+                .iter()
+                .copied()
+                .filter(|&i| tree.nodes[i].process_id as i32 == pid)
+                .take(1)
+                .collect())
+        })?;
+        found.first().map(|&i| self.element(i)).ok_or_else(|| {
+            AutomationError::ElementNotFound(format!("No application found for PID {pid}"))
+        })
+    }
+
+    fn find_element(
+        &self,
+        selector: &Selector,
+        root: Option<&UIElement>,
+        timeout: Option<Duration>,
+    ) -> Result<UIElement, AutomationError> {
+        let scope = self.root_index(root)?;
+        debug!("mock find_element {:?} within #{}", selector, scope);
+        let found = self.poll(timeout, |tree| {
+            query(&LiveTree { tree, engine: self }, selector, scope, None)
+        })?;
+        found.first().map(|&i| self.element(i)).ok_or_else(|| {
+            AutomationError::ElementNotFound(format!("No element found for selector: {selector:?}"))
+        })
+    }
+
+    fn find_elements(
+        &self,
+        selector: &Selector,
+        root: Option<&UIElement>,
+        timeout: Option<Duration>,
+        depth: Option<usize>,
+    ) -> Result<Vec<UIElement>, AutomationError> {
+        // Same scoping rule as the Windows engine, so workflows behave identically
+        if root.is_none() && !selector_has_process_scope(selector) {
+            return Err(AutomationError::InvalidSelector(format!(
+                "Desktop-wide search not allowed. Selector must include 'process:' prefix to scope search to a specific application.\n\
+                 Current selector: {selector:?}"
+            )));
+        }
+        let scope = self.root_index(root)?;
+        let found = self.poll(timeout, |tree| {
+            query(&LiveTree { tree, engine: self }, selector, scope, depth)
+        })?;
+        Ok(self.elements(found))
+    }
+
+    fn open_application(&self, app_name: &str) -> Result<UIElement, AutomationError> {
+        let app = self.get_application_by_name(app_name)?;
+        app.activate_window()?;
+        Ok(app)
+    }
+
+    fn activate_application(&self, app_name: &str) -> Result<(), AutomationError> {
+        self.get_application_by_name(app_name)?.activate_window()
+    }
+
+    fn open_url(&self, url: &str, _browser: Option<Browser>) -> Result<UIElement, AutomationError> {
+        Err(AutomationError::UnsupportedOperation(format!(
+            "Cannot open '{url}' on the mock desktop"
+        )))
+    }
+
+    fn open_file(&self, file_path: &str) -> Result<(), AutomationError> {
+        Err(AutomationError::UnsupportedOperation(format!(
+            "Cannot open '{file_path}' on the mock desktop"
+        )))
+    }
+
+    async fn run_command(
+        &self,
+        windows_command: Option<&str>,
+        unix_command: Option<&str>,
+    ) -> Result<CommandOutput, AutomationError> {
+        // Commands are real: only the desktop is faked
+        #[cfg(target_os = "windows")]
+        let (shell, flag, command) = ("powershell", "-Command", windows_command.or(unix_command));
+        #[cfg(not(target_os = "windows"))]
+        let (shell, flag, command) = ("sh", "-c", unix_command.or(windows_command));
+
+        let command = command.ok_or_else(|| {
+            AutomationError::InvalidArgument("A command must be provided".to_string())
+        })?;
+        let output = tokio::process::Command::new(shell)
+            .args([flag, command])
+            .output()
+            .await
+            .map_err(|e| AutomationError::PlatformError(e.to_string()))?;
+
+        Ok(CommandOutput {
+            exit_status: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        })
+    }
+
+    async fn list_monitors(&self) -> Result<Vec<Monitor>, AutomationError> {
+        Ok(vec![mock_monitor()])
+    }
+
+    async fn get_primary_monitor(&self) -> Result<Monitor, AutomationError> {
+        Ok(mock_monitor())
+    }
+
+    async fn get_active_monitor(&self) -> Result<Monitor, AutomationError> {
+        Ok(mock_monitor())
+    }
+
+    async fn get_monitor_by_id(&self, id: &str) -> Result<Monitor, AutomationError> {
+        let monitor = mock_monitor();
+        if monitor.id == id {
+            Ok(monitor)
+        } else {
+            Err(AutomationError::ElementNotFound(format!(
+                "Monitor with ID '{id}' not found"
+            )))
+        }
+    }
+
+    async fn get_monitor_by_name(&self, name: &str) -> Result<Monitor, AutomationError> {
+        let monitor = mock_monitor();
+        if monitor.name == name {
+            Ok(monitor)
+        } else {
+            Err(AutomationError::ElementNotFound(format!(
+                "Monitor '{name}' not found"
+            )))
+        }
+    }
+
+    async fn capture_monitor_by_id(&self, id: &str) -> Result<ScreenshotResult, AutomationError> {
+        let monitor = self.get_monitor_by_id(id).await?;
+        Ok(ScreenshotResult {
+            image_data: vec![0; (monitor.width * monitor.height * 4) as usize],
+            width: monitor.width,
+            height: monitor.height,
+            monitor: Some(monitor),
+        })
+    }
+
+    async fn ocr_image_path(&self, _image_path: &str) -> Result<String, AutomationError> {
+        Err(AutomationError::UnsupportedOperation(
+            "OCR is not available on the mock desktop".to_string(),
+        ))
+    }
+
+    async fn ocr_screenshot(
+        &self,
+        _screenshot: &ScreenshotResult,
+    ) -> Result<String, AutomationError> {
+        Err(AutomationError::UnsupportedOperation(
+            "OCR is not available on the mock desktop".to_string(),
+        ))
+    }
+
+    fn click_at_coordinates(
+        &self,
+        x: f64,
+        y: f64,
+        restore_cursor: bool,
+    ) -> Result<(), AutomationError> {
+        self.click_at_coordinates_with_type(x, y, ClickType::Left, restore_cursor)
+    }
+
+    fn click_at_coordinates_with_type(
+        &self,
+        x: f64,
+        y: f64,
+        click_type: ClickType,
+        _restore_cursor: bool,
+    ) -> Result<(), AutomationError> {
+        let target = element_at_point(&self.desktop.read(), x, y);
+        match (target, click_type) {
+            (Some(index), ClickType::Left) => self.element(index).click().map(|_| ()),
+            (Some(index), ClickType::Double) => self.element(index).double_click().map(|_| ()),
+            (Some(index), ClickType::Right) => self.element(index).right_click(),
+            (None, _) => {
+                self.desktop
+                    .write()
+                    .record(ROOT, "click_at_coordinates", Some(format!("{x},{y}")));
+                Ok(())
+            }
+        }
+    }
+
+    fn activate_browser_window_by_title(&self, title: &str) -> Result<(), AutomationError> {
+        let window = {
+            let tree = self.desktop.read();
+            let wanted = title.to_lowercase();
+            tree.nodes[ROOT].children.iter().copied().find(|&i| {
+                tree.nodes[i]
+                    .attributes
+                    .name
+                    .as_deref()
+                    .is_some_and(|n| n.to_lowercase().contains(&wanted))
+            })
+        };
+        match window {
+            Some(index) => self.element(index).activate_window(),
+            None => Err(AutomationError::ElementNotFound(format!(
+                "No browser window with title containing '{title}'"
+            ))),
+        }
+    }
+
+    async fn get_current_browser_window(&self) -> Result<UIElement, AutomationError> {
+        self.get_current_window().await
+    }
+
+    async fn get_current_window(&self) -> Result<UIElement, AutomationError> {
+        let window = {
+            let tree = self.desktop.read();
+            tree.focused
+                .and_then(|i| tree.application_of(i))
+                .or_else(|| tree.nodes[ROOT].children.first().copied())
+        };
+        window
+            .map(|i| self.element(i))
+            .ok_or_else(|| AutomationError::ElementNotFound("No window is open".to_string()))
+    }
+
+    async fn get_current_application(&self) -> Result<UIElement, AutomationError> {
+        self.get_current_window().await
+    }
+
+    fn press_key(&self, key: &str) -> Result<(), AutomationError> {
+        let mut tree = self.desktop.write();
+        let target = tree.focused.unwrap_or(ROOT);
+        tree.record(target, "press_key", Some(key.to_string()));
+        Ok(())
+    }
+
+    fn set_zoom(&self, percentage: u32) -> Result<(), AutomationError> {
+        self.desktop
+            .write()
+            .record(ROOT, "set_zoom", Some(percentage.to_string()));
+        Ok(())
+    }
+
+    fn get_window_tree(
+        &self,
+        pid: u32,
+        title: Option<&str>,
+        config: TreeBuildConfig,
+    ) -> Result<UINode, AutomationError> {
+        let window = self.find_window(pid, title)?;
+        self.get_tree_from_element(&self.element(window), config)
+    }
+
+    fn get_tree_from_element(
+        &self,
+        element: &UIElement,
+        config: TreeBuildConfig,
+    ) -> Result<UINode, AutomationError> {
+        let mut start = self.root_index(Some(element))?;
+        if let Some(from_selector) = config.from_selector.as_deref() {
+            let selector = Selector::from(from_selector);
+            let found = query(&*self.desktop.read(), &selector, start, None)?;
+            start = *found.first().ok_or_else(|| {
+                AutomationError::ElementNotFound(format!(
+                    "from_selector '{from_selector}' did not match any element"
+                ))
+            })?;
+        }
+        let tree = self.desktop.read();
+        Ok(tree.to_ui_node(start, config.max_depth, &mut Vec::new()))
+    }
+
+    /// Events cover the whole mock desktop; `scope` is ignored
+    fn subscribe(
+        &self,
+        _scope: Option<&UIElement>,
+    ) -> Result<crate::events::EventSubscription, AutomationError> {
+        self.desktop.write().subscribe()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,107 @@
+//! In-memory fake platform implementation for UI automation
+//!
+//! This module provides an `AccessibilityEngine` that runs entirely against an
+//! in-memory UI tree built from `UINode` / `SerializableUIElement` JSON fixtures.
+//! It is enabled with the `mock` cargo feature and lets `Desktop`, `Locator`, the
+//! selector engine and everything built on top of them run without a Windows desktop
+//! (for example on Linux CI).
+//!
+//! ```ignore
+//! use terminator::platforms::mock::{self, MockDesktop};
+//!
+//! let fixture = MockDesktop::from_json(include_str!("fixtures/notepad.json"))?;
+//! mock::set_fixture(fixture.clone());
+//!
+//! let desktop = terminator::Desktop::new_default()?; // uses the fixture above
+//! desktop.locator("process:notepad >> role:Document").first(None).await?.type_text("hi", false)?;
+//! assert_eq!(fixture.actions().last().unwrap().action, "type_text");
+//! ```
+
+pub mod element;
+pub mod engine;
+pub mod tree;
+
+use crate::platforms::KNOWN_BROWSER_PROCESS_NAMES;
+use crate::AutomationError;
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+
+// Re-export the main types that external code needs
+pub use element::MockUIElement;
+pub use engine::MockEngine;
+pub use tree::{MockAction, MockDesktop, MockEvents};
+
+/// Environment variable pointing at a JSON fixture file loaded by `create_engine`
+/// when no fixture was registered with [`set_fixture`].
+pub const FIXTURE_ENV_VAR: &str = "TERMINATOR_MOCK_FIXTURE";
+
+static DEFAULT_FIXTURE: Lazy<Mutex<Option<MockDesktop>>> = Lazy::new(|| Mutex::new(None));
+
+/// Register the desktop that `create_engine` (and therefore `Desktop::new`) hands out.
+///
+/// All engines created afterwards share the same in-memory tree, so state changes made
+/// through one `Desktop` are visible through every other one.
+pub fn set_fixture(desktop: MockDesktop) {
+    *lock_fixture() = Some(desktop);
+}
+
+/// Remove the registered fixture. The next `create_engine` call falls back to
+/// `TERMINATOR_MOCK_FIXTURE` or an empty desktop.
+pub fn clear_fixture() {
+    *lock_fixture() = None;
+}
+
+/// A panic while holding the lock can only come from a test assertion; keep going.
+fn lock_fixture() -> std::sync::MutexGuard<'static, Option<MockDesktop>> {
+    DEFAULT_FIXTURE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Returns the registered fixture, loading it from `TERMINATOR_MOCK_FIXTURE` on first use.
+/// Falls back to an empty desktop when neither is available.
+pub(crate) fn default_fixture() -> Result<MockDesktop, AutomationError> {
+    let mut fixture = lock_fixture();
+
+    if let Some(desktop) = fixture.as_ref() {
+        return Ok(desktop.clone());
+    }
+
+    let desktop = match std::env::var(FIXTURE_ENV_VAR) {
+        Ok(path) if !path.is_empty() => MockDesktop::from_file(&path)?,
+        _ => MockDesktop::new(),
+    };
+    *fixture = Some(desktop.clone());
+    Ok(desktop)
+}
+
+/// Process name for a PID: fixture processes first, then the real process table.
+/// Stands in for the Windows implementation when only the mock engine is compiled.
+pub fn get_process_name_by_pid(pid: i32) -> Result<String, AutomationError> {
+    let fixture = lock_fixture().clone();
+    if let Some(name) = fixture.and_then(|desktop| {
+        let tree = desktop.read();
+        tree.nodes
+            .iter()
+            .find(|n| n.process_id as i32 == pid && !n.removed && !n.process_name.is_empty())
+            .map(|n| n.process_name.clone())
+    }) {
+        return Ok(name);
+    }
+
+    let mut system = sysinfo::System::new();
+    let pid = sysinfo::Pid::from_u32(pid as u32);
+    system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid]), true);
+    system
+        .process(pid)
+        .map(|p| p.name().to_string_lossy().to_string())
+        .ok_or_else(|| AutomationError::PlatformError(format!("Process {pid} not found")))
+}
+
+/// Check if a PID belongs to a known browser (see [`KNOWN_BROWSER_PROCESS_NAMES`])
+pub fn is_browser_process(pid: u32) -> bool {
+    get_process_name_by_pid(pid as i32).is_ok_and(|name| {
+        let name = name.to_lowercase();
+        KNOWN_BROWSER_PROCESS_NAMES
+            .iter()
+            .any(|&browser| name.contains(browser))
+    })
+}
@@ -0,0 +1,554 @@
+//! Shared in-memory UI tree backing the mock engine
+
+use crate::element::SerializableUIElement;
+use crate::events::{EventSender, EventSubscription, TreeEvent};
+use crate::query::QueryTree;
+use crate::{AutomationError, UIElementAttributes, UINode};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Index of a node inside the mock tree arena
+pub(crate) type NodeIndex = usize;
+
+/// Index of the desktop root node
+pub(crate) const ROOT: NodeIndex = 0;
+
+/// First process id handed out to applications that don't declare one
+const FIRST_MOCK_PID: u32 = 1000;
+
+/// A single element of the mock desktop
+#[derive(Debug, Clone)]
+pub(crate) struct MockNode {
+    pub id: String,
+    pub attributes: UIElementAttributes,
+    pub parent: Option<NodeIndex>,
+    pub children: Vec<NodeIndex>,
+    pub process_id: u32,
+    pub process_name: String,
+    /// Set when the element (or one of its ancestors) was closed/removed
+    pub removed: bool,
+}
+
+/// An action performed against the mock desktop, recorded for test assertions
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockAction {
+    /// Id of the element the action was performed on (empty for desktop-level actions)
+    pub element_id: String,
+    /// Role of the element the action was performed on
+    pub role: String,
+    /// Action name, e.g. "click", "type_text", "press_key", "set_value"
+    pub action: String,
+    /// Action argument, e.g. the typed text or the pressed key
+    pub detail: Option<String>,
+}
+
+/// How the mock desktop reports tree changes to `AccessibilityEngine::subscribe`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MockEvents {
+    /// Every mutation and action raises the matching [`TreeEvent`]
+    #[default]
+    Automatic,
+    /// Only events sent with [`MockDesktop::emit`] are delivered, so tests can
+    /// script exactly when waits wake up
+    Scripted,
+    /// `subscribe` fails like on a platform without change notifications
+    Unsupported,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct MockTree {
+    pub nodes: Vec<MockNode>,
+    pub focused: Option<NodeIndex>,
+    pub actions: Vec<MockAction>,
+    next_pid: u32,
+    events: MockEvents,
+    subscribers: Vec<EventSender>,
+}
+
+/// In-memory desktop: a tree of application windows shared by every mock engine and element.
+///
+/// Cloning a `MockDesktop` is cheap and returns a handle to the same tree.
+#[derive(Debug, Clone)]
+pub struct MockDesktop {
+    tree: Arc<RwLock<MockTree>>,
+}
+
+impl Default for MockDesktop {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockDesktop {
+    /// Create an empty desktop with no running applications
+    pub fn new() -> Self {
+        let root = MockNode {
+            id: "desktop".to_string(),
+            attributes: UIElementAttributes {
+                role: "Pane".to_string(),
+                name: Some("Desktop".to_string()),
+                enabled: Some(true),
+                ..Default::default()
+            },
+            parent: None,
+            children: Vec::new(),
+            process_id: 0,
+            process_name: String::new(),
+            removed: false,
+        };
+        Self {
+            tree: Arc::new(RwLock::new(MockTree {
+                nodes: vec![root],
+                focused: None,
+                actions: Vec::new(),
+                next_pid: FIRST_MOCK_PID,
+                events: MockEvents::default(),
+                subscribers: Vec::new(),
+            })),
+        }
+    }
+
+    /// Build a desktop from a `UINode`.
+    ///
+    /// A node with role `Desktop` is treated as the desktop root and each of its children
+    /// becomes an application window; any other node is added as a single application window.
+    pub fn from_ui_node(node: UINode) -> Self {
+        let desktop = Self::new();
+        if node.attributes.role.eq_ignore_ascii_case("desktop") {
+            for window in node.children {
+                desktop.add_window(window, None, None);
+            }
+        } else {
+            desktop.add_window(node, None, None);
+        }
+        desktop
+    }
+
+    /// Build a desktop from a `SerializableUIElement` tree (same rules as [`Self::from_ui_node`]).
+    pub fn from_serializable(element: &SerializableUIElement) -> Self {
+        let desktop = Self::new();
+        if element.role.eq_ignore_ascii_case("desktop") {
+            for window in element.children.iter().flatten() {
+                desktop.add_serializable_application(window);
+            }
+        } else {
+            desktop.add_serializable_application(element);
+        }
+        desktop
+    }
+
+    /// Parse a JSON fixture.
+    ///
+    /// Accepts a `UINode` (as returned by `get_window_tree`), a `SerializableUIElement`,
+    /// or an array of either where every entry is one application window.
+    pub fn from_json(json: &str) -> Result<Self, AutomationError> {
+        let value: serde_json::Value = serde_json::from_str(json)
+            .map_err(|e| AutomationError::InvalidArgument(format!("Invalid mock fixture: {e}")))?;
+
+        match value {
+            serde_json::Value::Array(items) => {
+                let desktop = Self::new();
+                for item in items {
+                    desktop.add_json_window(item)?;
+                }
+                Ok(desktop)
+            }
+            serde_json::Value::Object(ref map) if map.contains_key("attributes") => {
+                let node: UINode = serde_json::from_value(value).map_err(|e| {
+                    AutomationError::InvalidArgument(format!("Invalid UINode fixture: {e}"))
+                })?;
+                Ok(Self::from_ui_node(node))
+            }
+            serde_json::Value::Object(_) => {
+                let element: SerializableUIElement =
+                    serde_json::from_value(value).map_err(|e| {
+                        AutomationError::InvalidArgument(format!(
+                            "Invalid SerializableUIElement fixture: {e}"
+                        ))
+                    })?;
+                Ok(Self::from_serializable(&element))
+            }
+            other => Err(AutomationError::InvalidArgument(format!(
+                "Mock fixture must be a JSON object or array, got: {other}"
+            ))),
+        }
+    }
+
+    /// Load a JSON fixture from disk (see [`Self::from_json`])
+    pub fn from_file(path: &str) -> Result<Self, AutomationError> {
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            AutomationError::InvalidArgument(format!("Failed to read mock fixture '{path}': {e}"))
+        })?;
+        Self::from_json(&json)
+    }
+
+    fn add_json_window(&self, item: serde_json::Value) -> Result<(), AutomationError> {
+        if item.get("attributes").is_some() {
+            let node: UINode = serde_json::from_value(item).map_err(|e| {
+                AutomationError::InvalidArgument(format!("Invalid UINode fixture: {e}"))
+            })?;
+            self.add_window(node, None, None);
+        } else {
+            let element: SerializableUIElement = serde_json::from_value(item).map_err(|e| {
+                AutomationError::InvalidArgument(format!(
+                    "Invalid SerializableUIElement fixture: {e}"
+                ))
+            })?;
+            self.add_serializable_application(&element);
+        }
+        Ok(())
+    }
+
+    /// Add an application window owned by `process_name`. Returns the process id assigned to it.
+    pub fn add_application(&self, process_name: &str, window: UINode) -> u32 {
+        self.add_window(window, None, Some(process_name.to_string()))
+    }
+
+    /// Add an application window from a `SerializableUIElement`, keeping its
+    /// `process_id`/`process_name` when present. Returns the process id assigned to it.
+    pub fn add_serializable_application(&self, window: &SerializableUIElement) -> u32 {
+        let node = crate::tree_formatter::serializable_to_ui_node(window);
+        self.add_window(node, window.process_id, window.process_name.clone())
+    }
+
+    fn add_window(&self, window: UINode, pid: Option<u32>, process_name: Option<String>) -> u32 {
+        let mut tree = self.write();
+        let props = &window.attributes.properties;
+        let pid = pid
+            .or_else(|| property_u64(props, "ProcessId").map(|p| p as u32))
+            .unwrap_or_else(|| {
+                let pid = tree.next_pid;
+                tree.next_pid += 1;
+                pid
+            });
+        let process_name = process_name
+            .or_else(|| property_string(props, "ProcessName"))
+            .or_else(|| window.attributes.application_name.clone())
+            .or_else(|| window.attributes.name.clone())
+            .unwrap_or_default();
+
+        tree.insert(ROOT, window, pid, &process_name);
+        tree.notify(TreeEvent::StructureChanged);
+        pid
+    }
+
+    /// Insert `node` (and its subtree) as the last child of the element with id `parent_id`.
+    /// Returns the id of the inserted element.
+    pub fn insert(&self, parent_id: &str, node: UINode) -> Result<String, AutomationError> {
+        let mut tree = self.write();
+        let parent = tree.index_of(parent_id)?;
+        let (pid, process_name) = (
+            tree.nodes[parent].process_id,
+            tree.nodes[parent].process_name.clone(),
+        );
+        let index = tree.insert(parent, node, pid, &process_name);
+        tree.notify(TreeEvent::StructureChanged);
+        Ok(tree.nodes[index].id.clone())
+    }
+
+    /// Remove the element with id `id` and its subtree (e.g. a dialog being closed)
+    pub fn remove(&self, id: &str) -> Result<(), AutomationError> {
+        let mut tree = self.write();
+        let index = tree.index_of(id)?;
+        tree.detach(index);
+        tree.notify(TreeEvent::StructureChanged);
+        Ok(())
+    }
+
+    /// Mutate the attributes of the element with id `id` (e.g. to simulate the app changing state)
+    pub fn update<F>(&self, id: &str, f: F) -> Result<(), AutomationError>
+    where
+        F: FnOnce(&mut UIElementAttributes),
+    {
+        let mut tree = self.write();
+        let index = tree.index_of(id)?;
+        f(&mut tree.nodes[index].attributes);
+        tree.notify(TreeEvent::PropertyChanged);
+        Ok(())
+    }
+
+    /// Choose how tree changes are reported to subscribers
+    pub fn set_events(&self, events: MockEvents) {
+        self.write().events = events;
+    }
+
+    /// Deliver `event` to every subscriber, whatever the [`MockEvents`] mode
+    pub fn emit(&self, event: TreeEvent) {
+        self.write().send(event);
+    }
+
+    /// Number of live subscriptions (dropped ones are not counted)
+    pub fn subscriber_count(&self) -> usize {
+        let mut tree = self.write();
+        tree.subscribers.retain(|s| !s.is_closed());
+        tree.subscribers.len()
+    }
+
+    /// All actions performed against this desktop so far, oldest first
+    pub fn actions(&self) -> Vec<MockAction> {
+        self.read().actions.clone()
+    }
+
+    /// Forget the recorded actions
+    pub fn clear_actions(&self) {
+        self.write().actions.clear();
+    }
+
+    /// Snapshot of the whole desktop as a `UINode` (role `Desktop`, one child per window)
+    pub fn to_ui_node(&self) -> UINode {
+        let tree = self.read();
+        let mut node = tree.to_ui_node(ROOT, None, &mut Vec::new());
+        node.attributes.role = "Desktop".to_string();
+        node
+    }
+
+    pub(crate) fn read(&self) -> RwLockReadGuard<'_, MockTree> {
+        // A panic while holding the lock can only come from a test assertion; keep going.
+        self.tree.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn write(&self) -> RwLockWriteGuard<'_, MockTree> {
+        self.tree.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Stable identity of the underlying tree, used for element equality
+    pub(crate) fn tree_ptr(&self) -> usize {
+        Arc::as_ptr(&self.tree) as usize
+    }
+}
+
+impl MockTree {
+    fn insert(&mut self, parent: NodeIndex, node: UINode, pid: u32, process_name: &str) -> usize {
+        let index = self.nodes.len();
+        let id = match node.id {
+            Some(ref id) if !id.is_empty() && self.index_of(id).is_err() => id.clone(),
+            _ => format!("mock-{index}"),
+        };
+        let mut attributes = node.attributes;
+        if attributes.is_focused == Some(true) {
+            self.focused = Some(index);
+        }
+        attributes.child_count = None;
+        attributes.index_in_parent = None;
+
+        self.nodes.push(MockNode {
+            id,
+            attributes,
+            parent: Some(parent),
+            children: Vec::new(),
+            process_id: pid,
+            process_name: process_name.to_string(),
+            removed: false,
+        });
+        self.nodes[parent].children.push(index);
+
+        for child in node.children {
+            self.insert(index, child, pid, process_name);
+        }
+        index
+    }
+
+    fn detach(&mut self, index: NodeIndex) {
+        if let Some(parent) = self.nodes[index].parent {
+            self.nodes[parent].children.retain(|c| *c != index);
+        }
+        let mut stack = vec![index];
+        while let Some(current) = stack.pop() {
+            self.nodes[current].removed = true;
+            if self.focused == Some(current) {
+                self.focused = None;
+            }
+            stack.extend(self.nodes[current].children.iter().copied());
+        }
+    }
+
+    /// Find the live node with the given element id
+    pub(crate) fn index_of(&self, id: &str) -> Result<NodeIndex, AutomationError> {
+        self.nodes
+            .iter()
+            .position(|n| n.id == id && !n.removed)
+            .ok_or_else(|| {
+                AutomationError::ElementNotFound(format!("No mock element with id '{id}'"))
+            })
+    }
+
+    /// All live descendants of `index` in document order, limited to `max_depth` levels
+    pub(crate) fn descendants(&self, index: NodeIndex, max_depth: Option<usize>) -> Vec<NodeIndex> {
+        let mut result = Vec::new();
+        self.collect_descendants(index, 1, max_depth, &mut result);
+        result
+    }
+
+    fn collect_descendants(
+        &self,
+        index: NodeIndex,
+        depth: usize,
+        max_depth: Option<usize>,
+        result: &mut Vec<NodeIndex>,
+    ) {
+        if max_depth.is_some_and(|max| depth > max) {
+            return;
+        }
+        for &child in &self.nodes[index].children {
+            result.push(child);
+            self.collect_descendants(child, depth + 1, max_depth, result);
+        }
+    }
+
+    /// Top-level application window containing `index` (direct child of the desktop root)
+    pub(crate) fn application_of(&self, index: NodeIndex) -> Option<NodeIndex> {
+        let mut current = index;
+        while let Some(parent) = self.nodes[current].parent {
+            if parent == ROOT {
+                return Some(current);
+            }
+            current = parent;
+        }
+        None
+    }
+
+    pub(crate) fn set_focus(&mut self, index: NodeIndex) {
+        if let Some(previous) = self.focused {
+            self.nodes[previous].attributes.is_focused = None;
+        }
+        self.nodes[index].attributes.is_focused = Some(true);
+        self.focused = Some(index);
+        self.notify(TreeEvent::FocusChanged);
+    }
+
+    pub(crate) fn subscribe(&mut self) -> Result<EventSubscription, AutomationError> {
+        if self.events == MockEvents::Unsupported {
+            return Err(AutomationError::UnsupportedOperation(
+                "mock desktop events are disabled".to_string(),
+            ));
+        }
+        let (sender, subscription) = EventSubscription::channel();
+        self.subscribers.push(sender);
+        Ok(subscription)
+    }
+
+    /// Report a change made by a mutation or action ([`MockEvents::Automatic`] only)
+    pub(crate) fn notify(&mut self, event: TreeEvent) {
+        if self.events == MockEvents::Automatic {
+            self.send(event);
+        }
+    }
+
+    fn send(&mut self, event: TreeEvent) {
+        self.subscribers.retain(|s| s.send(event).is_ok());
+    }
+
+    pub(crate) fn record(&mut self, index: NodeIndex, action: &str, detail: Option<String>) {
+        let node = &self.nodes[index];
+        self.actions.push(MockAction {
+            element_id: node.id.clone(),
+            role: node.attributes.role.clone(),
+            action: action.to_string(),
+            detail,
+        });
+        self.notify(TreeEvent::PropertyChanged);
+    }
+
+    /// Convert the subtree at `index` back into a `UINode`, filling in chained selectors
+    pub(crate) fn to_ui_node(
+        &self,
+        index: NodeIndex,
+        max_depth: Option<usize>,
+        selector_path: &mut Vec<String>,
+    ) -> UINode {
+        let node = &self.nodes[index];
+        // The desktop root is implicit in selectors, like on the real platforms
+        if index != ROOT {
+            let mut segment = format!("role:{}", node.attributes.role);
+            if let Some(name) = node.attributes.name.as_deref().filter(|n| !n.is_empty()) {
+                segment.push_str(&format!(" && name:{name}"));
+            }
+            selector_path.push(segment);
+        }
+
+        let children = if max_depth == Some(0) {
+            Vec::new()
+        } else {
+            node.children
+                .iter()
+                .map(|&child| self.to_ui_node(child, max_depth.map(|d| d - 1), selector_path))
+                .collect()
+        };
+
+        let mut attributes = node.attributes.clone();
+        attributes.child_count = Some(node.children.len());
+        attributes.index_in_parent = node
+            .parent
+            .and_then(|p| self.nodes[p].children.iter().position(|c| *c == index));
+
+        let selector = if index == ROOT {
+            None
+        } else {
+            let selector = Some(selector_path.join(" >> "));
+            selector_path.pop();
+            selector
+        };
+
+        UINode {
+            id: Some(node.id.clone()),
+            attributes,
+            children,
+            selector,
+        }
+    }
+}
+
+fn property_string(
+    props: &HashMap<String, Option<serde_json::Value>>,
+    key: &str,
+) -> Option<String> {
+    match props.get(key)? {
+        Some(serde_json::Value::String(s)) => Some(s.clone()),
+        Some(other) => Some(other.to_string()),
+        None => None,
+    }
+}
+
+fn property_u64(props: &HashMap<String, Option<serde_json::Value>>, key: &str) -> Option<u64> {
+    match props.get(key)? {
+        Some(serde_json::Value::Number(n)) => n.as_u64(),
+        Some(serde_json::Value::String(s)) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+pub(crate) use crate::query::property_as_string;
+
+impl QueryTree for MockTree {
+    fn attributes(&self, index: usize) -> &UIElementAttributes {
+        &self.nodes[index].attributes
+    }
+
+    fn node_id(&self, index: usize) -> Option<&str> {
+        Some(&self.nodes[index].id)
+    }
+
+    fn parent(&self, index: usize) -> Option<usize> {
+        self.nodes[index].parent
+    }
+
+    fn children(&self, index: usize) -> &[usize] {
+        &self.nodes[index].children
+    }
+
+    fn process_name(&self, index: usize) -> Option<&str> {
+        Some(&self.nodes[index].process_name)
+    }
+
+    fn process_id(&self, index: usize) -> Option<u32> {
+        Some(self.nodes[index].process_id)
+    }
+
+    fn is_focused(&self, index: usize) -> bool {
+        self.focused == Some(index)
+    }
+
+    fn descendants(&self, index: usize, max_depth: Option<usize>) -> Vec<usize> {
+        MockTree::descendants(self, index, max_depth)
+    }
+}
@@ -311,14 +311,58 @@ pub trait AccessibilityEngine: Send + Sync {
         config: TreeBuildConfig,
     ) -> Result<UINode, AutomationError>;
 
+    /// Subscribe to structure, property and focus changes below `scope` (the whole
+    /// desktop when `None`). Waits use this to wake on changes instead of polling;
+    /// engines without change notifications, or that cannot watch the given scope
+    /// cheaply, return `UnsupportedOperation`.
+    fn subscribe(
+        &self,
+        _scope: Option<&UIElement>,
+    ) -> Result<crate::events::EventSubscription, AutomationError> {
+        Err(AutomationError::UnsupportedOperation(
+            "tree change events are not available on this platform".to_string(),
+        ))
+    }
+
     /// Enable downcasting to concrete engine types
     fn as_any(&self) -> &dyn std::any::Any;
 }
 
-// Terminator only supports Windows. Attempting to compile on other platforms will fail.
-#[cfg(not(target_os = "windows"))]
-compile_error!("Terminator only supports Windows. Linux and macOS are not supported.");
+/// Known browser process names for detection (without .exe)
+pub const KNOWN_BROWSER_PROCESS_NAMES: &[&str] = &[
+    "chrome", "firefox", "msedge", "edge", "iexplore", "opera", "brave", "vivaldi", "browser",
+    "arc",
+];
+
+/// Check if a selector has process scoping (required for desktop-wide `find_elements`)
+pub(crate) fn selector_has_process_scope(selector: &Selector) -> bool {
+    match selector {
+        Selector::Process(_) => true,
+        Selector::Chain(selectors) => selectors.iter().any(selector_has_process_scope),
+        Selector::And(selectors) => selectors.iter().any(selector_has_process_scope),
+        Selector::Or(selectors) => selectors.iter().any(selector_has_process_scope),
+        Selector::Not(inner) => selector_has_process_scope(inner),
+        Selector::Has(inner) => selector_has_process_scope(inner),
+        Selector::RightOf(inner)
+        | Selector::LeftOf(inner)
+        | Selector::Above(inner)
+        | Selector::Below(inner)
+        | Selector::Near(inner) => selector_has_process_scope(inner),
+        _ => false,
+    }
+}
 
+// Terminator supports Windows (UI Automation) and Linux (AT-SPI2). Attempting to compile
+// on other platforms will fail, unless the in-memory `mock` engine is enabled.
+#[cfg(not(any(target_os = "windows", target_os = "linux", feature = "mock")))]
+compile_error!("Terminator only supports Windows and Linux. macOS is not supported (enable the `mock` feature to build the in-memory test engine).");
+
+#[cfg(target_os = "linux")]
+pub mod linux;
+#[cfg(all(target_os = "linux", test))]
+pub mod linux_tests;
+#[cfg(feature = "mock")]
+pub mod mock;
 #[cfg(target_os = "windows")]
 pub mod windows;
 #[cfg(all(target_os = "windows", test))]
@@ -329,21 +373,37 @@ pub mod windows_tests;
 pub mod windows_benchmarks;
 
 /// Create the appropriate engine for the current platform
+///
+/// When the `mock` feature is enabled the in-memory [`mock::MockEngine`] is returned
+/// on every platform, loaded from the fixture registered with [`mock::set_fixture`]
+/// or the `TERMINATOR_MOCK_FIXTURE` file.
 pub fn create_engine(
     use_background_apps: bool,
     activate_app: bool,
 ) -> Result<Arc<dyn AccessibilityEngine>, AutomationError> {
-    #[cfg(target_os = "windows")]
+    #[cfg(feature = "mock")]
+    {
+        let _ = (use_background_apps, activate_app);
+        Ok(Arc::new(mock::MockEngine::from_default_fixture()?))
+    }
+    #[cfg(all(target_os = "windows", not(feature = "mock")))]
     {
         Ok(Arc::new(windows::WindowsEngine::new(
             use_background_apps,
             activate_app,
         )?))
     }
-    #[cfg(not(target_os = "windows"))]
+    #[cfg(all(target_os = "linux", not(feature = "mock")))]
+    {
+        Ok(Arc::new(linux::LinuxEngine::new(
+            use_background_apps,
+            activate_app,
+        )?))
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux", feature = "mock")))]
     {
         Err(AutomationError::UnsupportedPlatform(
-            "Terminator only supports Windows".to_string(),
+            "Terminator only supports Windows and Linux".to_string(),
         ))
     }
 }
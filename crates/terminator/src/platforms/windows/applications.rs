@@ -49,11 +49,7 @@ const DEFAULT_FIND_TIMEOUT: Duration = Duration::from_millis(5000);
 static ENUM_TARGET_PID: AtomicU32 = AtomicU32::new(0);
 static ENUM_FOUND_HWND: AtomicIsize = AtomicIsize::new(0);
 
-/// Known browser process names for detection (without .exe)
-pub const KNOWN_BROWSER_PROCESS_NAMES: &[&str] = &[
-    "chrome", "firefox", "msedge", "edge", "iexplore", "opera", "brave", "vivaldi", "browser",
-    "arc",
-];
+pub use crate::platforms::KNOWN_BROWSER_PROCESS_NAMES;
 
 /// Fast window finder using Win32 EnumWindows API
 /// This is 10-100x faster than UI Automation tree traversal
@@ -23,6 +23,11 @@ use uiautomation::types::{TreeScope, UIProperty};
 use uiautomation::variants::Variant;
 use uiautomation::UIAutomation;
 
+thread_local! {
+    /// UI Automation instance for hit tests of elements created without an engine
+    static HIT_TEST_AUTOMATION: std::cell::RefCell<Option<UIAutomation>> = const { std::cell::RefCell::new(None) };
+}
+
 trait ScrollFallback {
     fn scroll_with_fallback(&self, direction: &str, amount: f64) -> Result<(), AutomationError>;
 }
@@ -1241,11 +1246,12 @@ impl UIElementImpl for WindowsUIElement {
             let _ = self.element.0.send_keys("{END}", 10);
         }
 
-        let result = self
-            .element
-            .0
-            .send_keys(key, 10)
-            .map_err(|e| AutomationError::PlatformError(format!("Failed to press key: {e:?}")));
+        let result = super::input::send_unnamed_keys(key).unwrap_or_else(|| {
+            self.element
+                .0
+                .send_keys(key, 10)
+                .map_err(|e| AutomationError::PlatformError(format!("Failed to press key: {e:?}")))
+        });
 
         // Restore focus state after pressing key if we saved it
         if let Some(state) = saved_focus {
@@ -1655,6 +1661,53 @@ impl UIElementImpl for WindowsUIElement {
         })
     }
 
+    fn is_editable(&self) -> Result<bool, AutomationError> {
+        // Value-backed controls report read-only state; anything else that takes
+        // keyboard focus (rich text, contenteditable, terminals) accepts keystrokes
+        match self.element.0.get_pattern::<patterns::UIValuePattern>() {
+            Ok(value_pattern) => value_pattern
+                .is_readonly()
+                .map(|read_only| !read_only)
+                .map_err(|e| AutomationError::PlatformError(e.to_string())),
+            Err(_) => self.is_keyboard_focusable(),
+        }
+    }
+
+    fn element_at_point(&self, x: f64, y: f64) -> Result<Option<UIElement>, AutomationError> {
+        let point = uiautomation::types::Point::new(x.round() as i32, y.round() as i32);
+        // Reuse the engine's automation, or this thread's, rather than creating one per hit test
+        let hit = match &self.engine {
+            Some(engine) => engine.automation.0.element_from_point(point),
+            None => HIT_TEST_AUTOMATION.with(|cached| {
+                let automation = match cached.borrow_mut().take() {
+                    Some(automation) => automation,
+                    None => create_ui_automation_with_com_init().map_err(|e| {
+                        AutomationError::PlatformError(format!(
+                            "Failed to create UI automation for hit test: {e}"
+                        ))
+                    })?,
+                };
+                let hit = automation.element_from_point(point);
+                *cached.borrow_mut() = Some(automation);
+                Ok::<_, AutomationError>(hit)
+            })?,
+        };
+        match hit {
+            Ok(element) => {
+                #[allow(clippy::arc_with_non_send_sync)]
+                let hit = UIElement::new(Box::new(WindowsUIElement {
+                    element: ThreadSafeWinUIElement(Arc::new(element)),
+                    engine: self.engine.clone(),
+                }));
+                Ok(Some(hit))
+            }
+            Err(e) => {
+                debug!("ElementFromPoint({}, {}) failed: {}", x, y, e);
+                Ok(None)
+            }
+        }
+    }
+
     // New method for mouse drag
     fn mouse_drag(
         &self,
@@ -12,7 +12,7 @@ use crate::platforms::windows::virtual_display::{
     is_headless_environment, HeadlessConfig, VirtualDisplayConfig, VirtualDisplayManager,
 };
 use crate::platforms::windows::{applications, generate_element_id, WindowsUIElement};
-use crate::platforms::AccessibilityEngine;
+use crate::platforms::{selector_has_process_scope, AccessibilityEngine};
 use crate::ScreenshotResult;
 use crate::{AutomationError, Selector, UIElement};
 use image::DynamicImage;
@@ -180,24 +180,6 @@ pub fn get_process_name_by_pid(pid: i32) -> Result<String, AutomationError> {
     }
 }
 
-// Helper function to check if a selector has process scoping
-fn selector_has_process_scope(selector: &Selector) -> bool {
-    match selector {
-        Selector::Process(_) => true,
-        Selector::Chain(selectors) => selectors.iter().any(selector_has_process_scope),
-        Selector::And(selectors) => selectors.iter().any(selector_has_process_scope),
-        Selector::Or(selectors) => selectors.iter().any(selector_has_process_scope),
-        Selector::Not(inner) => selector_has_process_scope(inner),
-        Selector::Has(inner) => selector_has_process_scope(inner),
-        Selector::RightOf(inner)
-        | Selector::LeftOf(inner)
-        | Selector::Above(inner)
-        | Selector::Below(inner)
-        | Selector::Near(inner) => selector_has_process_scope(inner),
-        _ => false,
-    }
-}
-
 // RAII guard to ensure handle is closed
 struct HandleGuard(HANDLE);
 
@@ -574,6 +556,10 @@ impl WindowsEngine {
         element: &UIElement,
         selector: &Selector,
     ) -> Result<bool, AutomationError> {
+        if let Selector::Filter(id) = selector {
+            return crate::filters::evaluate_filter(*id, element);
+        }
+
         // Get the underlying Windows element
         let win_element = if let Some(ele) = element.as_any().downcast_ref::<WindowsUIElement>() {
             &ele.element.0
@@ -682,9 +668,20 @@ impl WindowsEngine {
                 // Negate the inner selector
                 Ok(!self.element_matches_selector(element, inner_selector)?)
             }
+            Selector::Has(inner_selector) => {
+                // Matches when any descendant matches the inner selector right now;
+                // the caller's search already does the waiting
+                match self.find_elements(inner_selector, Some(element), Some(Duration::ZERO), None)
+                {
+                    Ok(found) => Ok(!found.is_empty()),
+                    Err(AutomationError::ElementNotFound(_) | AutomationError::Timeout(_)) => {
+                        Ok(false)
+                    }
+                    Err(e) => Err(e),
+                }
+            }
             // Complex selectors that would need more context
             Selector::Chain(_)
-            | Selector::Has(_)
             | Selector::Parent
             | Selector::RightOf(_)
             | Selector::LeftOf(_)
@@ -1106,8 +1103,24 @@ impl AccessibilityEngine for WindowsEngine {
                 // and then filter. The challenge is determining what "all elements" means.
                 // We'll use the first selector to get candidates, then filter by the rest.
 
-                // Get initial candidates from the first selector
-                let candidates = self.find_elements(&selectors[0], root, timeout, depth)?;
+                // Get initial candidates from the first selector (`filter:` steps only narrow)
+                let seed = crate::filters::and_seed(selectors);
+                let candidates = self.find_elements(&selectors[seed], root, timeout, depth)?;
+
+                // Chains can't be evaluated on a single element; resolve them once
+                // and test candidates for membership
+                let mut chain_matches = std::collections::HashMap::new();
+                for (i, sel) in selectors.iter().enumerate() {
+                    if i != seed && matches!(sel, Selector::Chain(_)) {
+                        let found: std::collections::HashSet<UIElement> =
+                            match self.find_elements(sel, root, timeout, depth) {
+                                Ok(found) => found.into_iter().collect(),
+                                Err(AutomationError::ElementNotFound(_)) => Default::default(),
+                                Err(e) => return Err(e),
+                            };
+                        chain_matches.insert(i, found);
+                    }
+                }
 
                 // Filter candidates by checking if they match ALL remaining selectors
                 let mut results = Vec::new();
@@ -1115,8 +1128,12 @@ impl AccessibilityEngine for WindowsEngine {
                     let mut matches_all = true;
 
                     // Check if this candidate matches all other selectors
-                    for sel in &selectors[1..] {
-                        if !self.element_matches_selector(&candidate, sel)? {
+                    for (i, sel) in selectors.iter().enumerate().filter(|(i, _)| *i != seed) {
+                        let matches = match chain_matches.get(&i) {
+                            Some(found) => found.contains(&candidate),
+                            None => self.element_matches_selector(&candidate, sel)?,
+                        };
+                        if !matches {
                             matches_all = false;
                             break;
                         }
@@ -1594,9 +1611,7 @@ impl AccessibilityEngine for WindowsEngine {
                     })
                     .collect())
             }
-            Selector::Filter(_filter) => Err(AutomationError::UnsupportedOperation(
-                "`Filter` selector not supported".to_string(),
-            )),
+            Selector::Filter(_) => Err(crate::filters::standalone_filter_error()),
             Selector::Chain(selectors) => {
                 if selectors.is_empty() {
                     return Err(AutomationError::InvalidArgument(
@@ -1618,7 +1633,9 @@ impl AccessibilityEngine for WindowsEngine {
                         )));
                     }
 
-                    if let Selector::Nth(index) = selector {
+                    if let Selector::Filter(id) = selector {
+                        current_results = crate::filters::retain_matching(*id, current_results)?;
+                    } else if let Selector::Nth(index) = selector {
                         let mut i = *index;
                         let len = current_results.len();
 
@@ -1964,6 +1981,17 @@ impl AccessibilityEngine for WindowsEngine {
         let timeout_ms = timeout.unwrap_or(DEFAULT_FIND_TIMEOUT).as_millis() as u32;
 
         match selector {
+            // Chains with `filter:` steps narrow whole result sets - delegate to find_elements
+            Selector::Chain(selectors)
+                if selectors.iter().any(|s| matches!(s, Selector::Filter(_))) =>
+            {
+                let elements = self.find_elements(selector, root, timeout, None)?;
+                elements.into_iter().next().ok_or_else(|| {
+                    AutomationError::ElementNotFound(format!(
+                        "No element found for selector: {selector}"
+                    ))
+                })
+            }
             // Boolean operators - delegate to find_elements and take first result
             Selector::And(_) | Selector::Or(_) | Selector::Not(_) => {
                 let elements = self.find_elements(selector, root, timeout, None)?;
@@ -2278,9 +2306,7 @@ impl AccessibilityEngine for WindowsEngine {
                     engine: None,
                 })))
             }
-            Selector::Filter(_filter) => Err(AutomationError::UnsupportedOperation(
-                "`Filter` selector not supported".to_string(),
-            )),
+            Selector::Filter(_) => Err(crate::filters::standalone_filter_error()),
             Selector::Chain(selectors) => {
                 if selectors.is_empty() {
                     return Err(AutomationError::InvalidArgument(
@@ -4083,6 +4109,9 @@ impl AccessibilityEngine for WindowsEngine {
     }
 
     fn press_key(&self, key: &str) -> Result<(), AutomationError> {
+        if let Some(result) = super::input::send_unnamed_keys(key) {
+            return result;
+        }
         // Use global keyboard simulation directly (works without focused element)
         use uiautomation::inputs::Keyboard;
         Keyboard::new()
@@ -4129,6 +4158,26 @@ impl AccessibilityEngine for WindowsEngine {
         Ok(())
     }
 
+    fn subscribe(
+        &self,
+        scope: Option<&UIElement>,
+    ) -> Result<crate::events::EventSubscription, AutomationError> {
+        let scope = scope.ok_or_else(|| {
+            AutomationError::UnsupportedOperation(
+                "tree change events need a scope element; desktop-wide waits poll".to_string(),
+            )
+        })?;
+        let scope = scope
+            .as_any()
+            .downcast_ref::<WindowsUIElement>()
+            .ok_or_else(|| {
+                AutomationError::InvalidArgument("Event scope is not a Windows element".to_string())
+            })?
+            .element
+            .clone();
+        super::events::subscribe(scope)
+    }
+
     /// Enable downcasting to concrete engine types
     fn as_any(&self) -> &dyn std::any::Any {
         self
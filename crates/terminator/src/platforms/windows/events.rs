@@ -0,0 +1,259 @@
+//! UI Automation event handlers feeding `AccessibilityEngine::subscribe`
+//!
+//! Handlers are registered from one long-lived MTA thread that owns a single
+//! `UIAutomation` instance; each subscription asks it to add handlers below
+//! its scope and to remove them again when the subscription is dropped. UIA
+//! calls the handlers on its own threads, so they only forward a
+//! [`TreeEvent`] and never block.
+
+use super::types::ThreadSafeWinUIElement;
+use crate::events::{EventSender, EventSubscription, TreeEvent};
+use crate::AutomationError;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, OnceLock};
+use std::time::Duration;
+use tracing::{debug, warn};
+use uiautomation::events::{
+    CustomFocusChangedEventHandler, CustomPropertyChangedEventHandler,
+    CustomStructureChangedEventHandler, UIFocusChangedEventHandler, UIPropertyChangedEventHandler,
+    UIStructureChangeEventHandler,
+};
+use uiautomation::types::{StructureChangeType, TreeScope, UIProperty};
+use uiautomation::variants::Variant;
+use uiautomation::{UIAutomation, UIElement};
+use windows::Win32::System::Com::{CoInitializeEx, CoUninitialize, COINIT_MULTITHREADED};
+
+/// How long `subscribe` waits for the handlers to be registered
+const REGISTRATION_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Properties whose changes can satisfy a wait condition
+const WATCHED_PROPERTIES: [UIProperty; 8] = [
+    UIProperty::Name,
+    UIProperty::IsEnabled,
+    UIProperty::IsOffscreen,
+    UIProperty::BoundingRectangle,
+    UIProperty::HasKeyboardFocus,
+    UIProperty::ValueValue,
+    UIProperty::ToggleToggleState,
+    UIProperty::SelectionItemIsSelected,
+];
+
+struct StructureHandler(EventSender);
+
+impl CustomStructureChangedEventHandler for StructureHandler {
+    fn handle(
+        &self,
+        _sender: &UIElement,
+        _change_type: StructureChangeType,
+        _runtime_id: Option<&[i32]>,
+    ) -> uiautomation::Result<()> {
+        self.0.send(TreeEvent::StructureChanged).ok();
+        Ok(())
+    }
+}
+
+struct PropertyHandler(EventSender);
+
+impl CustomPropertyChangedEventHandler for PropertyHandler {
+    fn handle(
+        &self,
+        _sender: &UIElement,
+        _property: UIProperty,
+        _new_value: Variant,
+    ) -> uiautomation::Result<()> {
+        self.0.send(TreeEvent::PropertyChanged).ok();
+        Ok(())
+    }
+}
+
+/// Focus handlers are desktop-wide, so this one only forwards focus changes
+/// within the scope's process (all of them if its process id is unknown)
+struct FocusHandler {
+    sender: EventSender,
+    process_id: Option<u32>,
+}
+
+impl CustomFocusChangedEventHandler for FocusHandler {
+    fn handle(&self, sender: &UIElement) -> uiautomation::Result<()> {
+        let in_scope = match self.process_id {
+            Some(scope_pid) => sender.get_process_id().is_ok_and(|pid| pid == scope_pid),
+            None => true,
+        };
+        if in_scope {
+            self.sender.send(TreeEvent::FocusChanged).ok();
+        }
+        Ok(())
+    }
+}
+
+/// Handlers registered on one element, removed with [`Registration::remove`]
+struct Registration {
+    element: UIElement,
+    structure: UIStructureChangeEventHandler,
+    property: UIPropertyChangedEventHandler,
+    focus: UIFocusChangedEventHandler,
+}
+
+impl Registration {
+    fn new(
+        automation: &UIAutomation,
+        element: UIElement,
+        sender: EventSender,
+    ) -> Result<Self, AutomationError> {
+        let registration = Self {
+            structure: StructureHandler(sender.clone()).into(),
+            property: PropertyHandler(sender.clone()).into(),
+            focus: FocusHandler {
+                sender,
+                process_id: element.get_process_id().ok(),
+            }
+            .into(),
+            element,
+        };
+
+        let register_error =
+            |e: uiautomation::Error| AutomationError::PlatformError(format!("UIA events: {e}"));
+        automation
+            .add_structure_changed_event_handler(
+                &registration.element,
+                TreeScope::Subtree,
+                None,
+                &registration.structure,
+            )
+            .map_err(register_error)?;
+        if let Err(e) = automation.add_property_changed_event_handler(
+            &registration.element,
+            TreeScope::Subtree,
+            None,
+            &registration.property,
+            &WATCHED_PROPERTIES,
+        ) {
+            registration.remove(automation);
+            return Err(register_error(e));
+        }
+        if let Err(e) = automation.add_focus_changed_event_handler(None, &registration.focus) {
+            registration.remove(automation);
+            return Err(register_error(e));
+        }
+        Ok(registration)
+    }
+
+    /// Remove the handlers; handlers that were never added are ignored
+    fn remove(&self, automation: &UIAutomation) {
+        let results = [
+            automation.remove_structure_changed_event_handler(&self.element, &self.structure),
+            automation.remove_property_changed_event_handler(&self.element, &self.property),
+            automation.remove_focus_changed_event_handler(&self.focus),
+        ];
+        for e in results.into_iter().filter_map(Result::err) {
+            debug!("Removing UIA event handler: {e}");
+        }
+    }
+}
+
+/// Requests handled by the event thread
+enum Command {
+    Register {
+        id: u64,
+        scope: ThreadSafeWinUIElement,
+        sender: EventSender,
+        ready: mpsc::Sender<Result<(), AutomationError>>,
+    },
+    Remove(u64),
+}
+
+/// Channel to the shared event thread, started on first use
+fn event_thread() -> Result<&'static mpsc::Sender<Command>, AutomationError> {
+    static COMMANDS: OnceLock<Option<mpsc::Sender<Command>>> = OnceLock::new();
+    COMMANDS
+        .get_or_init(|| {
+            let (commands, receiver) = mpsc::channel();
+            std::thread::Builder::new()
+                .name("uia-events".to_string())
+                .spawn(move || run_event_thread(receiver))
+                .map_err(|e| warn!("Failed to spawn UIA event thread: {e}"))
+                .ok()
+                .map(|_| commands)
+        })
+        .as_ref()
+        .ok_or_else(|| AutomationError::PlatformError("UIA event thread is not running".into()))
+}
+
+fn run_event_thread(commands: mpsc::Receiver<Command>) {
+    let com_initialized = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED).is_ok() };
+    let automation = UIAutomation::new_direct()
+        .map_err(|e| AutomationError::PlatformError(format!("UIAutomation init: {e}")));
+    let mut registrations = HashMap::new();
+    for command in commands {
+        match command {
+            Command::Register {
+                id,
+                scope,
+                sender,
+                ready,
+            } => {
+                let result = match &automation {
+                    Ok(automation) => {
+                        Registration::new(automation, scope.0.as_ref().clone(), sender).map(
+                            |registration| {
+                                registrations.insert(id, registration);
+                            },
+                        )
+                    }
+                    Err(e) => Err(AutomationError::PlatformError(e.to_string())),
+                };
+                let _ = ready.send(result);
+            }
+            Command::Remove(id) => {
+                if let (Some(registration), Ok(automation)) =
+                    (registrations.remove(&id), automation.as_ref())
+                {
+                    registration.remove(automation);
+                }
+            }
+        }
+    }
+    if com_initialized {
+        unsafe { CoUninitialize() };
+    }
+}
+
+/// Register structure, property and focus handlers below `scope`, usually an
+/// application window. Watching the whole desktop is left to polling: it would
+/// wake on every change on screen.
+pub(crate) fn subscribe(
+    scope: ThreadSafeWinUIElement,
+) -> Result<EventSubscription, AutomationError> {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+    let commands = event_thread()?;
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let (sender, subscription) = EventSubscription::channel();
+    let (ready_tx, ready_rx) = mpsc::channel();
+    commands
+        .send(Command::Register {
+            id,
+            scope,
+            sender,
+            ready: ready_tx,
+        })
+        .map_err(|_| AutomationError::PlatformError("UIA event thread has stopped".into()))?;
+
+    match ready_rx.recv_timeout(REGISTRATION_TIMEOUT) {
+        Ok(Ok(())) => {
+            let commands = commands.clone();
+            Ok(subscription.on_drop(move || {
+                let _ = commands.send(Command::Remove(id));
+            }))
+        }
+        Ok(Err(e)) => Err(e),
+        Err(_) => {
+            warn!("Timed out registering UIA event handlers");
+            // Commands run in order, so this undoes the registration once it lands
+            let _ = commands.send(Command::Remove(id));
+            Err(AutomationError::Timeout(
+                "Registering UIA event handlers".to_string(),
+            ))
+        }
+    }
+}
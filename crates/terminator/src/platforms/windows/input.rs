@@ -3,6 +3,7 @@
 //! This module provides low-level input functions that are shared across
 //! engine.rs and element.rs to avoid code duplication.
 
+use crate::keys::{Key, KeyItem, KeySequence, Modifier};
 use crate::{AutomationError, ClickType};
 use std::thread;
 use std::time::Duration;
@@ -17,8 +18,14 @@ use windows::Win32::UI::Accessibility::{
     IUIAutomationTextRange, UIA_TextPattern2Id,
 };
 use windows::Win32::UI::Input::KeyboardAndMouse::{
-    SendInput, INPUT, INPUT_0, INPUT_MOUSE, MOUSEEVENTF_ABSOLUTE, MOUSEEVENTF_LEFTDOWN,
-    MOUSEEVENTF_LEFTUP, MOUSEEVENTF_MOVE, MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, MOUSEINPUT,
+    SendInput, VkKeyScanW, INPUT, INPUT_0, INPUT_KEYBOARD, INPUT_MOUSE, KEYBDINPUT,
+    KEYBD_EVENT_FLAGS, KEYEVENTF_EXTENDEDKEY, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE,
+    MOUSEEVENTF_ABSOLUTE, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP, MOUSEEVENTF_MOVE,
+    MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, MOUSEINPUT, VIRTUAL_KEY, VK_APPS, VK_BACK,
+    VK_CAPITAL, VK_CONTROL, VK_DELETE, VK_DOWN, VK_END, VK_ESCAPE, VK_F1, VK_HOME, VK_INSERT,
+    VK_LEFT, VK_LWIN, VK_MENU, VK_NEXT, VK_NUMLOCK, VK_PAUSE, VK_PRIOR, VK_RCONTROL, VK_RETURN,
+    VK_RIGHT, VK_RMENU, VK_RSHIFT, VK_RWIN, VK_SCROLL, VK_SHIFT, VK_SNAPSHOT, VK_SPACE, VK_TAB,
+    VK_UP,
 };
 use windows::Win32::UI::WindowsAndMessaging::{
     GetCursorPos, GetSystemMetrics, SetCursorPos, SM_CXSCREEN, SM_CYSCREEN,
@@ -141,6 +148,179 @@ pub fn send_left_click(x: f64, y: f64, restore_cursor: bool) -> Result<(), Autom
     send_mouse_click(x, y, ClickType::Left, restore_cursor)
 }
 
+/// Press a key sequence through SendInput if it uses keys that UI Automation's
+/// SendKeys has no name for: `{NumLock}`, `{ScrollLock}`, `{Apps}` and `{AltGr}`.
+/// Returns `None` for every other sequence, which SendKeys handles.
+pub fn send_unnamed_keys(keys: &str) -> Option<Result<(), AutomationError>> {
+    let sequence = KeySequence::parse(keys).ok()?;
+    if !sequence.items().iter().any(needs_send_input) {
+        return None;
+    }
+    let mut events = Vec::new();
+    push_key_events(sequence.items(), false, &mut events);
+    // One batch, so a blocked call cannot leave a modifier held down
+    let sent = unsafe { SendInput(&events, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != events.len() {
+        return Some(Err(AutomationError::PlatformError(format!(
+            "Failed to press keys '{keys}': {}",
+            windows::core::Error::from_win32()
+        ))));
+    }
+    Some(Ok(()))
+}
+
+fn needs_send_input(item: &KeyItem) -> bool {
+    match item {
+        KeyItem::Text(_) => false,
+        KeyItem::Key { key, .. } => matches!(
+            key,
+            Key::NumLock | Key::ScrollLock | Key::Apps | Key::Modifier(Modifier::AltGr)
+        ),
+        KeyItem::Chord { modifiers, items } => {
+            modifiers.contains(&Modifier::AltGr) || items.iter().any(needs_send_input)
+        }
+    }
+}
+
+/// Virtual keys a modifier holds; AltGr is Ctrl and right Alt together
+fn modifier_virtual_keys(modifier: Modifier) -> &'static [VIRTUAL_KEY] {
+    match modifier {
+        Modifier::Ctrl => &[VK_CONTROL],
+        Modifier::Alt => &[VK_MENU],
+        Modifier::Shift => &[VK_SHIFT],
+        Modifier::Win => &[VK_LWIN],
+        Modifier::RCtrl => &[VK_RCONTROL],
+        Modifier::RAlt => &[VK_RMENU],
+        Modifier::RShift => &[VK_RSHIFT],
+        Modifier::RWin => &[VK_RWIN],
+        Modifier::AltGr => &[VK_CONTROL, VK_RMENU],
+    }
+}
+
+fn key_virtual_key(key: Key) -> VIRTUAL_KEY {
+    match key {
+        Key::Enter => VK_RETURN,
+        Key::Tab => VK_TAB,
+        Key::Escape => VK_ESCAPE,
+        Key::Backspace => VK_BACK,
+        Key::Delete => VK_DELETE,
+        Key::Insert => VK_INSERT,
+        Key::Home => VK_HOME,
+        Key::End => VK_END,
+        Key::PageUp => VK_PRIOR,
+        Key::PageDown => VK_NEXT,
+        Key::Left => VK_LEFT,
+        Key::Up => VK_UP,
+        Key::Right => VK_RIGHT,
+        Key::Down => VK_DOWN,
+        Key::Space => VK_SPACE,
+        Key::CapsLock => VK_CAPITAL,
+        Key::NumLock => VK_NUMLOCK,
+        Key::ScrollLock => VK_SCROLL,
+        Key::Pause => VK_PAUSE,
+        Key::PrintScreen => VK_SNAPSHOT,
+        Key::Apps => VK_APPS,
+        Key::F(number) => VIRTUAL_KEY(VK_F1.0 + u16::from(number) - 1),
+        Key::Modifier(modifier) => modifier_virtual_keys(modifier)[0],
+    }
+}
+
+/// Keys that share a code with a numpad key unless flagged as extended
+fn is_extended(vk: VIRTUAL_KEY) -> bool {
+    [
+        VK_INSERT,
+        VK_DELETE,
+        VK_HOME,
+        VK_END,
+        VK_PRIOR,
+        VK_NEXT,
+        VK_LEFT,
+        VK_UP,
+        VK_RIGHT,
+        VK_DOWN,
+        VK_NUMLOCK,
+        VK_SNAPSHOT,
+        VK_APPS,
+        VK_LWIN,
+        VK_RWIN,
+        VK_RCONTROL,
+        VK_RMENU,
+    ]
+    .contains(&vk)
+}
+
+fn key_event(vk: VIRTUAL_KEY, scan: u16, mut flags: KEYBD_EVENT_FLAGS) -> INPUT {
+    if is_extended(vk) {
+        flags |= KEYEVENTF_EXTENDEDKEY;
+    }
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: vk,
+                wScan: scan,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    }
+}
+
+fn push_tap(vk: VIRTUAL_KEY, events: &mut Vec<INPUT>) {
+    events.push(key_event(vk, 0, KEYBD_EVENT_FLAGS(0)));
+    events.push(key_event(vk, 0, KEYEVENTF_KEYUP));
+}
+
+fn push_key_events(items: &[KeyItem], held: bool, events: &mut Vec<INPUT>) {
+    for item in items {
+        match item {
+            KeyItem::Text(text) => {
+                for c in text.chars() {
+                    // Under a modifier the key itself must go down, so that
+                    // `{AltGr}e` types the key's third character
+                    let scan = if held && (c as u32) < 256 {
+                        unsafe { VkKeyScanW(c as u16) }
+                    } else {
+                        -1
+                    };
+                    if scan != -1 {
+                        push_tap(VIRTUAL_KEY((scan & 0xff) as u16), events);
+                        continue;
+                    }
+                    let mut units = [0u16; 2];
+                    for unit in c.encode_utf16(&mut units).iter() {
+                        events.push(key_event(VIRTUAL_KEY(0), *unit, KEYEVENTF_UNICODE));
+                        events.push(key_event(
+                            VIRTUAL_KEY(0),
+                            *unit,
+                            KEYEVENTF_UNICODE | KEYEVENTF_KEYUP,
+                        ));
+                    }
+                }
+            }
+            KeyItem::Key { key, repeat } => {
+                for _ in 0..*repeat {
+                    push_tap(key_virtual_key(*key), events);
+                }
+            }
+            KeyItem::Chord { modifiers, items } => {
+                let keys: Vec<VIRTUAL_KEY> = modifiers
+                    .iter()
+                    .flat_map(|m| modifier_virtual_keys(*m).iter().copied())
+                    .collect();
+                for vk in &keys {
+                    events.push(key_event(*vk, 0, KEYBD_EVENT_FLAGS(0)));
+                }
+                push_key_events(items, true, events);
+                for vk in keys.iter().rev() {
+                    events.push(key_event(*vk, 0, KEYEVENTF_KEYUP));
+                }
+            }
+        }
+    }
+}
+
 /// Saved focus state for restoration after automation operations.
 ///
 /// Contains the previously focused element, optional caret position (for text fields),
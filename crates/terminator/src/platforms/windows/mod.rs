@@ -7,6 +7,7 @@ pub mod action_overlay;
 pub mod applications;
 pub mod element;
 pub mod engine;
+pub mod events;
 pub mod health;
 pub mod highlighting;
 pub mod input;
@@ -0,0 +1,878 @@
+//! Offline selector evaluation
+//!
+//! Evaluates [`Selector`]s against an in-memory tree instead of a live accessibility
+//! API. The same matcher backs the mock engine and [`UINode::query`] /
+//! [`SerializableUIElement::query`], so selectors can be unit-tested against saved
+//! `get_window_tree` output on any platform, with the semantics of `find_elements`.
+
+use crate::element::SerializableUIElement;
+use crate::filters::{self, BuiltinFilter};
+use crate::tree_formatter::serializable_attributes;
+use crate::{AutomationError, Selector, UIElement, UIElementAttributes, UINode};
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+/// Distance (between centers) under which an element counts as `near:` its anchor
+const NEAR_THRESHOLD: f64 = 50.0;
+
+/// Arena-style tree that selectors can be evaluated against.
+///
+/// Node `0` is the desktop: `process:` selectors start from its children and
+/// `..` cannot leave it.
+pub(crate) trait QueryTree {
+    fn attributes(&self, index: usize) -> &UIElementAttributes;
+    fn node_id(&self, index: usize) -> Option<&str>;
+    fn parent(&self, index: usize) -> Option<usize>;
+    fn children(&self, index: usize) -> &[usize];
+    fn process_name(&self, index: usize) -> Option<&str>;
+    fn process_id(&self, index: usize) -> Option<u32>;
+
+    fn is_focused(&self, index: usize) -> bool {
+        self.attributes(index).is_focused == Some(true)
+    }
+
+    /// Live element for a node, handed to custom `filter:` predicates.
+    /// Captured snapshots have none, so only built-in filters work on them.
+    fn element(&self, _index: usize) -> Option<UIElement> {
+        None
+    }
+
+    /// All descendants of `index` in document order, limited to `max_depth` levels
+    fn descendants(&self, index: usize, max_depth: Option<usize>) -> Vec<usize> {
+        let mut result = Vec::new();
+        let mut stack: Vec<(usize, usize)> =
+            self.children(index).iter().rev().map(|&c| (c, 1)).collect();
+        while let Some((current, depth)) = stack.pop() {
+            if max_depth.is_some_and(|max| depth > max) {
+                continue;
+            }
+            result.push(current);
+            stack.extend(self.children(current).iter().rev().map(|&c| (c, depth + 1)));
+        }
+        result
+    }
+}
+
+/// Desktop node that captured snapshots are attached to
+pub(crate) const DESKTOP: usize = 0;
+
+/// Map generic role aliases onto the canonical role names stored in the tree
+pub(crate) fn canonical_role(role: &str) -> String {
+    match role.to_lowercase().as_str() {
+        "app" | "application" => "pane".to_string(),
+        "dialog" => "window".to_string(),
+        "url" | "urlfield" | "textfield" | "input" => "edit".to_string(),
+        "data" => "dataitem".to_string(),
+        "title" => "titlebar".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Read a property as a plain string (JSON strings are unquoted)
+pub(crate) fn property_as_string(attrs: &UIElementAttributes, key: &str) -> Option<String> {
+    match attrs.properties.get(key)?.as_ref()? {
+        serde_json::Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+pub(crate) fn node_is_visible<T: QueryTree + ?Sized>(tree: &T, index: usize) -> bool {
+    let attrs = tree.attributes(index);
+    if property_as_string(attrs, "IsOffscreen").as_deref() == Some("true") {
+        return false;
+    }
+    attrs.bounds.is_none_or(|(_, _, w, h)| w > 0.0 && h > 0.0)
+}
+
+/// Whether a process name satisfies a `process:` selector value. Case-insensitive,
+/// ignores a `.exe` suffix on either side and accepts prefixes both ways
+/// (`firefox` matches `firefox-bin`).
+pub(crate) fn process_matches(actual: &str, expected: &str) -> bool {
+    let actual = actual.to_lowercase();
+    let actual = actual.strip_suffix(".exe").unwrap_or(&actual);
+    let expected = expected.to_lowercase();
+    let expected = expected.strip_suffix(".exe").unwrap_or(&expected);
+    actual == expected || actual.starts_with(expected) || expected.starts_with(actual)
+}
+
+/// Resolve an `attr:` key against the node, accepting UIA property names
+fn attribute_value<T: QueryTree + ?Sized>(tree: &T, index: usize, key: &str) -> Option<String> {
+    let attrs = tree.attributes(index);
+    match key.to_lowercase().as_str() {
+        "name" => attrs.name.clone(),
+        "role" | "controltype" => Some(attrs.role.clone()),
+        "value" => attrs.value.clone(),
+        "description" | "helptext" => attrs.description.clone(),
+        "isenabled" | "enabled" => Some(attrs.enabled.unwrap_or(true).to_string()),
+        "haskeyboardfocus" | "focused" => Some(tree.is_focused(index).to_string()),
+        "iskeyboardfocusable" => Some(attrs.is_keyboard_focusable.unwrap_or(false).to_string()),
+        "isoffscreen" => Some((!node_is_visible(tree, index)).to_string()),
+        "automationid" => property_as_string(attrs, "AutomationId"),
+        "processid" => tree.process_id(index).map(|pid| pid.to_string()),
+        _ => {
+            let key = attrs
+                .properties
+                .keys()
+                .find(|k| k.eq_ignore_ascii_case(key))?;
+            property_as_string(attrs, key)
+        }
+    }
+}
+
+/// Check a single node against a selector that can be evaluated without context.
+/// Returns `None` for selectors that need a search (chains, spatial, `has:` ...).
+fn matches_atomic<T: QueryTree + ?Sized>(
+    tree: &T,
+    index: usize,
+    selector: &Selector,
+) -> Option<bool> {
+    let attrs = tree.attributes(index);
+    let name = attrs.name.as_deref().unwrap_or_default();
+    let result = match selector {
+        Selector::Role {
+            role,
+            name: expected,
+        } => {
+            (role == "*" || canonical_role(role) == canonical_role(&attrs.role))
+                && expected
+                    .as_ref()
+                    .is_none_or(|e| name.to_lowercase().contains(&e.to_lowercase()))
+        }
+        Selector::Id(id) => tree.node_id(index) == Some(id.strip_prefix('#').unwrap_or(id)),
+        Selector::Name(expected) => name.to_lowercase().contains(&expected.to_lowercase()),
+        Selector::Text(expected) => name.contains(expected.as_str()),
+        Selector::NativeId(expected) => {
+            property_as_string(attrs, "AutomationId").as_deref() == Some(expected.as_str())
+        }
+        Selector::ClassName(expected) => {
+            property_as_string(attrs, "ClassName").as_deref() == Some(expected.as_str())
+        }
+        Selector::LocalizedRole(expected) => property_as_string(attrs, "LocalizedControlType")
+            .unwrap_or_else(|| attrs.role.to_lowercase())
+            .eq_ignore_ascii_case(expected),
+        Selector::Visible(expected) => node_is_visible(tree, index) == *expected,
+        // Snapshots without process information cannot rule a process out
+        Selector::Process(expected) => tree
+            .process_name(index)
+            .is_none_or(|actual| process_matches(actual, expected)),
+        Selector::Attributes(attributes) => attributes.iter().all(|(key, expected)| {
+            attribute_value(tree, index, key).is_some_and(|v| v.eq_ignore_ascii_case(expected))
+        }),
+        Selector::And(selectors) => {
+            let mut all = true;
+            for s in selectors {
+                if !matches_atomic(tree, index, s)? {
+                    all = false;
+                }
+            }
+            all
+        }
+        Selector::Or(selectors) => {
+            let mut any = false;
+            for s in selectors {
+                if matches_atomic(tree, index, s)? {
+                    any = true;
+                }
+            }
+            any
+        }
+        Selector::Not(inner) => !matches_atomic(tree, index, inner)?,
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn bounds_of<T: QueryTree + ?Sized>(tree: &T, index: usize) -> Option<(f64, f64, f64, f64)> {
+    tree.attributes(index).bounds
+}
+
+fn center(bounds: (f64, f64, f64, f64)) -> (f64, f64) {
+    (bounds.0 + bounds.2 / 2.0, bounds.1 + bounds.3 / 2.0)
+}
+
+pub(crate) fn distance(a: (f64, f64, f64, f64), b: (f64, f64, f64, f64)) -> f64 {
+    let (ax, ay) = center(a);
+    let (bx, by) = center(b);
+    ((ax - bx).powi(2) + (ay - by).powi(2)).sqrt()
+}
+
+/// Spatial relation used by `rightof:`, `leftof:`, `above:`, `below:` and `near:`
+pub(crate) fn spatially_related(
+    selector: &Selector,
+    anchor: (f64, f64, f64, f64),
+    candidate: (f64, f64, f64, f64),
+) -> bool {
+    let (anchor_left, anchor_top) = (anchor.0, anchor.1);
+    let (anchor_right, anchor_bottom) = (anchor.0 + anchor.2, anchor.1 + anchor.3);
+    let (candidate_left, candidate_top) = (candidate.0, candidate.1);
+    let (candidate_right, candidate_bottom) =
+        (candidate.0 + candidate.2, candidate.1 + candidate.3);
+
+    let vertical_overlap = candidate_top < anchor_bottom && candidate_bottom > anchor_top;
+    let horizontal_overlap = candidate_left < anchor_right && candidate_right > anchor_left;
+
+    match selector {
+        Selector::RightOf(_) => candidate_left >= anchor_right && vertical_overlap,
+        Selector::LeftOf(_) => candidate_right <= anchor_left && vertical_overlap,
+        Selector::Above(_) => candidate_bottom <= anchor_top && horizontal_overlap,
+        Selector::Below(_) => candidate_top >= anchor_bottom && horizontal_overlap,
+        Selector::Near(_) => distance(anchor, candidate) < NEAR_THRESHOLD,
+        _ => false,
+    }
+}
+
+/// Bounds of the top-level window containing `index`
+fn viewport_of<T: QueryTree + ?Sized>(tree: &T, index: usize) -> Option<(f64, f64, f64, f64)> {
+    let mut current = index;
+    while let Some(parent) = tree.parent(current) {
+        if parent == DESKTOP {
+            return bounds_of(tree, current);
+        }
+        current = parent;
+    }
+    None
+}
+
+/// Evaluate a `filter:` predicate. Built-in filters read the stored attributes;
+/// custom predicates need a live element.
+fn filter_matches<T: QueryTree + ?Sized>(
+    tree: &T,
+    index: usize,
+    id: usize,
+) -> Result<bool, AutomationError> {
+    if let Some(builtin) = BuiltinFilter::from_id(id) {
+        return Ok(builtin.matches_attributes(
+            tree.attributes(index),
+            node_is_visible(tree, index),
+            viewport_of(tree, index),
+        ));
+    }
+    if !filters::is_registered(id) {
+        return Err(filters::unknown_filter(id));
+    }
+    match tree.element(index) {
+        Some(element) => filters::evaluate_filter(id, &element),
+        None => Err(AutomationError::UnsupportedOperation(
+            "Custom filter predicates need live elements; captured trees only support hasValue, enabled and inViewport".to_string(),
+        )),
+    }
+}
+
+fn retain_filtered<T: QueryTree + ?Sized>(
+    tree: &T,
+    results: Vec<usize>,
+    id: usize,
+) -> Result<Vec<usize>, AutomationError> {
+    let mut kept = Vec::with_capacity(results.len());
+    for index in results {
+        if filter_matches(tree, index, id)? {
+            kept.push(index);
+        }
+    }
+    Ok(kept)
+}
+
+/// Keep the `index`-th result; negative indices count from the end
+pub(crate) fn apply_nth(mut results: Vec<usize>, index: i32) -> Vec<usize> {
+    let len = results.len() as i32;
+    let i = if index < 0 { index + len } else { index };
+    if i >= 0 && i < len {
+        vec![results.swap_remove(i as usize)]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Follow a `path:` selector (`Window[1]/Pane/Button[2]`) through the children of `scope`.
+/// Segments are control types with an optional 1-based index, as on Windows.
+fn resolve_path<T: QueryTree + ?Sized>(
+    tree: &T,
+    path: &str,
+    scope: usize,
+) -> Result<usize, AutomationError> {
+    if path.trim_matches('/').is_empty() {
+        return Err(AutomationError::InvalidArgument(
+            "Path cannot be empty".to_string(),
+        ));
+    }
+    let segment_re = regex::Regex::new(r"^([A-Za-z]+)(?:\[(\d+)\])?$").unwrap();
+    let mut current = scope;
+    for part in path.trim_matches('/').split('/').filter(|p| !p.is_empty()) {
+        let caps = segment_re.captures(part).ok_or_else(|| {
+            AutomationError::PlatformError(format!(
+                "Failed to parse path, make sure its is in correct format & latest updated with ui: '{path}'",
+            ))
+        })?;
+        let role = canonical_role(&caps[1]);
+        let index = caps
+            .get(2)
+            .map_or(1, |m| m.as_str().parse().unwrap_or(1))
+            .max(1);
+        let matching: Vec<usize> = tree
+            .children(current)
+            .iter()
+            .copied()
+            .filter(|&c| canonical_role(&tree.attributes(c).role) == role)
+            .collect();
+        current = *matching.get(index - 1).ok_or_else(|| {
+            AutomationError::ElementNotFound(format!(
+                "Failed to find {}[{index}], only {} elements matched",
+                &caps[1],
+                matching.len()
+            ))
+        })?;
+    }
+    Ok(current)
+}
+
+/// Evaluate `selector` against the descendants of `scope`, in document order.
+/// Mirrors the semantics of the Windows engine's `find_elements`.
+pub(crate) fn query<T: QueryTree + ?Sized>(
+    tree: &T,
+    selector: &Selector,
+    scope: usize,
+    depth: Option<usize>,
+) -> Result<Vec<usize>, AutomationError> {
+    match selector {
+        Selector::Invalid(reason) => Err(AutomationError::InvalidSelector(reason.clone())),
+        Selector::Path(path) => Ok(vec![resolve_path(tree, path, scope)?]),
+        Selector::Filter(_) => Err(filters::standalone_filter_error()),
+        Selector::Nth(_) => Err(AutomationError::InvalidSelector(
+            "Nth selector must be used as part of a chain (e.g. 'list >> nth=0')".to_string(),
+        )),
+        Selector::Parent => {
+            if scope == DESKTOP {
+                return Err(AutomationError::InvalidSelector(
+                    "Parent selector requires a starting element".to_string(),
+                ));
+            }
+            Ok(tree.parent(scope).into_iter().collect())
+        }
+        // Process scoping always starts from the desktop, like on Windows
+        Selector::Process(_) => Ok(tree
+            .children(DESKTOP)
+            .iter()
+            .copied()
+            .filter(|&i| matches_atomic(tree, i, selector).unwrap_or(false))
+            .collect()),
+        Selector::Chain(selectors) => {
+            let (first, rest) = selectors.split_first().ok_or_else(|| {
+                AutomationError::InvalidArgument("Selector chain cannot be empty".to_string())
+            })?;
+            let mut current = query(tree, first, scope, depth)?;
+            for selector in rest {
+                current = match selector {
+                    Selector::Nth(index) => apply_nth(current, *index),
+                    Selector::Filter(id) => retain_filtered(tree, current, *id)?,
+                    _ => {
+                        let mut seen = HashSet::new();
+                        let mut next = Vec::new();
+                        for &root in &current {
+                            for found in query(tree, selector, root, None)? {
+                                if seen.insert(found) {
+                                    next.push(found);
+                                }
+                            }
+                        }
+                        next
+                    }
+                };
+                if current.is_empty() {
+                    break;
+                }
+            }
+            Ok(current)
+        }
+        Selector::And(selectors) if selectors.is_empty() => Ok(Vec::new()),
+        Selector::And(selectors) => {
+            let seed = filters::and_seed(selectors);
+            let mut results = query(tree, &selectors[seed], scope, depth)?;
+            for (i, selector) in selectors.iter().enumerate() {
+                if i == seed {
+                    continue;
+                }
+                if let Selector::Filter(id) = selector {
+                    results = retain_filtered(tree, results, *id)?;
+                    continue;
+                }
+                results = match results
+                    .iter()
+                    .map(|&i| matches_atomic(tree, i, selector))
+                    .collect::<Option<Vec<bool>>>()
+                {
+                    Some(flags) => results
+                        .into_iter()
+                        .zip(flags)
+                        .filter_map(|(i, keep)| keep.then_some(i))
+                        .collect(),
+                    None => {
+                        let allowed: HashSet<usize> =
+                            query(tree, selector, scope, depth)?.into_iter().collect();
+                        results
+                            .into_iter()
+                            .filter(|i| allowed.contains(i))
+                            .collect()
+                    }
+                };
+            }
+            Ok(results)
+        }
+        Selector::Or(selectors) => {
+            let mut seen = HashSet::new();
+            let mut results = Vec::new();
+            for selector in selectors {
+                for found in query(tree, selector, scope, depth)? {
+                    if seen.insert(found) {
+                        results.push(found);
+                    }
+                }
+            }
+            results.sort_unstable();
+            Ok(results)
+        }
+        Selector::Not(inner) => {
+            let excluded: HashSet<usize> = query(tree, inner, scope, depth)?.into_iter().collect();
+            Ok(tree
+                .descendants(scope, depth)
+                .into_iter()
+                .filter(|i| !excluded.contains(i))
+                .collect())
+        }
+        Selector::Has(inner) => {
+            let mut results = Vec::new();
+            for candidate in tree.descendants(scope, depth) {
+                if !query(tree, inner, candidate, None)?.is_empty() {
+                    results.push(candidate);
+                }
+            }
+            Ok(results)
+        }
+        Selector::RightOf(inner)
+        | Selector::LeftOf(inner)
+        | Selector::Above(inner)
+        | Selector::Below(inner)
+        | Selector::Near(inner) => {
+            let anchor = *query(tree, inner, scope, depth)?.first().ok_or_else(|| {
+                AutomationError::ElementNotFound(format!("Anchor element not found: {inner:?}"))
+            })?;
+            let anchor_bounds = bounds_of(tree, anchor).ok_or_else(|| {
+                AutomationError::ElementNotFound(format!("Anchor element has no bounds: {inner:?}"))
+            })?;
+            let mut results: Vec<usize> = tree
+                .descendants(scope, depth)
+                .into_iter()
+                .filter(|&i| i != anchor && node_is_visible(tree, i))
+                .filter(|&i| {
+                    bounds_of(tree, i)
+                        .is_some_and(|b| spatially_related(selector, anchor_bounds, b))
+                })
+                .collect();
+            // Closest first, like the Windows engine's find_element
+            results.sort_by(|&a, &b| {
+                let da = bounds_of(tree, a).map_or(f64::MAX, |x| distance(anchor_bounds, x));
+                let db = bounds_of(tree, b).map_or(f64::MAX, |x| distance(anchor_bounds, x));
+                da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+            });
+            Ok(results)
+        }
+        _ => Ok(tree
+            .descendants(scope, depth)
+            .into_iter()
+            .filter(|&i| matches_atomic(tree, i, selector).unwrap_or(false))
+            .collect()),
+    }
+}
+
+// ============== CAPTURED SNAPSHOTS ==============
+
+struct SnapshotNode<'a, N> {
+    /// `None` for the synthetic desktop
+    source: Option<&'a N>,
+    attributes: Cow<'a, UIElementAttributes>,
+    id: Option<&'a str>,
+    process_name: Option<&'a str>,
+    process_id: Option<u32>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+/// A captured tree flattened into an arena, with the captured root attached
+/// to a synthetic desktop so that it can be matched itself
+struct Snapshot<'a, N> {
+    nodes: Vec<SnapshotNode<'a, N>>,
+}
+
+impl<'a, N> Snapshot<'a, N> {
+    fn new() -> Self {
+        Self {
+            nodes: vec![SnapshotNode {
+                source: None,
+                attributes: Cow::Owned(UIElementAttributes {
+                    role: "Desktop".to_string(),
+                    ..Default::default()
+                }),
+                id: None,
+                process_name: None,
+                process_id: None,
+                parent: None,
+                children: Vec::new(),
+            }],
+        }
+    }
+
+    fn push(&mut self, parent: usize, mut node: SnapshotNode<'a, N>) -> usize {
+        let index = self.nodes.len();
+        // Only the captured root usually carries process information
+        if parent != DESKTOP {
+            node.process_name = node.process_name.or(self.nodes[parent].process_name);
+            node.process_id = node.process_id.or(self.nodes[parent].process_id);
+        }
+        node.parent = Some(parent);
+        self.nodes.push(node);
+        self.nodes[parent].children.push(index);
+        index
+    }
+
+    /// Run the query from the desktop and map the results back to the captured nodes
+    fn run(&self, selector: &Selector) -> Result<Vec<&'a N>, AutomationError> {
+        Ok(query(self, selector, DESKTOP, None)?
+            .into_iter()
+            .filter_map(|i| self.nodes[i].source)
+            .collect())
+    }
+}
+
+impl<N> QueryTree for Snapshot<'_, N> {
+    fn attributes(&self, index: usize) -> &UIElementAttributes {
+        &self.nodes[index].attributes
+    }
+
+    fn node_id(&self, index: usize) -> Option<&str> {
+        self.nodes[index].id
+    }
+
+    fn parent(&self, index: usize) -> Option<usize> {
+        self.nodes[index].parent
+    }
+
+    fn children(&self, index: usize) -> &[usize] {
+        &self.nodes[index].children
+    }
+
+    fn process_name(&self, index: usize) -> Option<&str> {
+        self.nodes[index].process_name
+    }
+
+    fn process_id(&self, index: usize) -> Option<u32> {
+        self.nodes[index].process_id
+    }
+}
+
+fn add_ui_node<'a>(snapshot: &mut Snapshot<'a, UINode>, parent: usize, node: &'a UINode) {
+    let index = snapshot.push(
+        parent,
+        SnapshotNode {
+            source: Some(node),
+            attributes: Cow::Borrowed(&node.attributes),
+            id: node.id.as_deref(),
+            process_name: None,
+            process_id: None,
+            parent: None,
+            children: Vec::new(),
+        },
+    );
+    for child in &node.children {
+        add_ui_node(snapshot, index, child);
+    }
+}
+
+fn add_serializable<'a>(
+    snapshot: &mut Snapshot<'a, SerializableUIElement>,
+    parent: usize,
+    element: &'a SerializableUIElement,
+) {
+    let index = snapshot.push(
+        parent,
+        SnapshotNode {
+            source: Some(element),
+            attributes: Cow::Owned(serializable_attributes(element)),
+            id: element.id.as_deref(),
+            process_name: element.process_name.as_deref(),
+            process_id: element.process_id,
+            parent: None,
+            children: Vec::new(),
+        },
+    );
+    for child in element.children.iter().flatten() {
+        add_serializable(snapshot, index, child);
+    }
+}
+
+fn first_match<'a, N>(
+    found: Result<Vec<&'a N>, AutomationError>,
+    selector: &Selector,
+) -> Result<&'a N, AutomationError> {
+    found?.into_iter().next().ok_or_else(|| {
+        AutomationError::ElementNotFound(format!("No element found for selector: {selector:?}"))
+    })
+}
+
+impl UINode {
+    /// All nodes of this captured tree (including the root) matching `selector`,
+    /// in document order, evaluated like `find_elements` on a live desktop.
+    ///
+    /// `process:` selectors match the root, since a `UINode` does not record
+    /// which process it was captured from.
+    pub fn query(&self, selector: &Selector) -> Result<Vec<&UINode>, AutomationError> {
+        let mut snapshot = Snapshot::new();
+        add_ui_node(&mut snapshot, DESKTOP, self);
+        snapshot.run(selector)
+    }
+
+    /// First node matching `selector`, or `ElementNotFound`
+    pub fn query_first(&self, selector: &Selector) -> Result<&UINode, AutomationError> {
+        first_match(self.query(selector), selector)
+    }
+}
+
+impl SerializableUIElement {
+    /// All elements of this captured tree (including the root) matching `selector`,
+    /// in document order, evaluated like `find_elements` on a live desktop.
+    ///
+    /// Children inherit `process_name` / `process_id` from their nearest ancestor
+    /// that has them; without any, `process:` selectors match the root.
+    pub fn query(
+        &self,
+        selector: &Selector,
+    ) -> Result<Vec<&SerializableUIElement>, AutomationError> {
+        let mut snapshot = Snapshot::new();
+        add_serializable(&mut snapshot, DESKTOP, self);
+        snapshot.run(selector)
+    }
+
+    /// First element matching `selector`, or `ElementNotFound`
+    pub fn query_first(
+        &self,
+        selector: &Selector,
+    ) -> Result<&SerializableUIElement, AutomationError> {
+        first_match(self.query(selector), selector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(role: &str, name: &str, bounds: (f64, f64, f64, f64), children: Vec<UINode>) -> UINode {
+        UINode {
+            id: None,
+            attributes: UIElementAttributes {
+                role: role.to_string(),
+                name: Some(name.to_string()),
+                bounds: Some(bounds),
+                ..Default::default()
+            },
+            children,
+            selector: None,
+        }
+    }
+
+    fn form() -> UINode {
+        node(
+            "Window",
+            "Login",
+            (0.0, 0.0, 400.0, 300.0),
+            vec![
+                node(
+                    "Group",
+                    "Credentials",
+                    (10.0, 10.0, 380.0, 100.0),
+                    vec![
+                        node("Text", "Username", (20.0, 20.0, 80.0, 20.0), vec![]),
+                        node("Edit", "User field", (110.0, 20.0, 200.0, 20.0), vec![]),
+                        node("Text", "Password", (20.0, 60.0, 80.0, 20.0), vec![]),
+                        node("Edit", "Password field", (110.0, 60.0, 200.0, 20.0), vec![]),
+                    ],
+                ),
+                node("Button", "Sign in", (110.0, 120.0, 100.0, 30.0), vec![]),
+                node("Button", "Cancel", (220.0, 120.0, 100.0, 30.0), vec![]),
+            ],
+        )
+    }
+
+    fn names(found: Vec<&UINode>) -> Vec<&str> {
+        found
+            .into_iter()
+            .map(|n| n.attributes.name.as_deref().unwrap_or_default())
+            .collect()
+    }
+
+    #[test]
+    fn test_query_structural_selectors() {
+        let tree = form();
+        let q = |s: &str| tree.query(&Selector::from(s)).map(names);
+
+        assert_eq!(q("role:Window").unwrap(), vec!["Login"]);
+        assert_eq!(q("role:Button").unwrap(), vec!["Sign in", "Cancel"]);
+        assert_eq!(
+            q("process:notepad >> role:Button >> nth=-1").unwrap(),
+            vec!["Cancel"]
+        );
+        assert_eq!(
+            q("role:Group >> role:Edit >> nth=0").unwrap(),
+            vec!["User field"]
+        );
+        assert_eq!(
+            q("role:Edit && name:Password").unwrap(),
+            vec!["Password field"]
+        );
+        assert_eq!(q("role:Button && !name:Cancel").unwrap(), vec!["Sign in"]);
+        assert_eq!(
+            q("(role:Button && name:Cancel) || role:Group").unwrap(),
+            vec!["Credentials", "Cancel"]
+        );
+        assert_eq!(q("has:role:Edit").unwrap(), vec!["Login", "Credentials"]);
+        assert_eq!(q("name:Username >> ..").unwrap(), vec!["Credentials"]);
+        assert_eq!(
+            q("path:/Window/Group/Edit[2]").unwrap(),
+            vec!["Password field"]
+        );
+        assert!(q("nth=0").is_err());
+        assert!(matches!(
+            tree.query_first(&Selector::from("role:CheckBox")),
+            Err(AutomationError::ElementNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_query_spatial_selectors() {
+        let tree = form();
+        let q = |s: &str| tree.query(&Selector::from(s)).map(names);
+
+        assert_eq!(
+            q("role:Edit && rightof:name:Password").unwrap(),
+            vec!["Password field"]
+        );
+        assert_eq!(
+            q("role:Button && below:role:Group").unwrap(),
+            vec!["Sign in", "Cancel"]
+        );
+        assert_eq!(
+            q("role:Button && near:name:Sign in").unwrap(),
+            vec![] as Vec<&str>
+        );
+        assert_eq!(
+            q("role:Text && above:name:Password").unwrap(),
+            vec!["Username"]
+        );
+        assert!(matches!(
+            q("rightof:name:Missing"),
+            Err(AutomationError::ElementNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_query_serializable_snapshot_json() {
+        let json = r#"{
+            "role": "Window", "name": "Untitled - Notepad",
+            "process_name": "notepad.exe", "process_id": 42,
+            "bounds": [0.0, 0.0, 800.0, 600.0],
+            "children": [
+                {"role": "Document", "name": "Text Editor", "bounds": [0.0, 40.0, 800.0, 560.0]},
+                {"role": "Button", "name": "Close", "bounds": [760.0, 0.0, 40.0, 30.0]}
+            ]
+        }"#;
+        let tree: SerializableUIElement = serde_json::from_str(json).unwrap();
+
+        let found = tree
+            .query(&Selector::from("process:notepad >> role:Document"))
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name.as_deref(), Some("Text Editor"));
+        assert!(tree
+            .query(&Selector::from("process:chrome >> role:Document"))
+            .unwrap()
+            .is_empty());
+        let close = tree
+            .query_first(&Selector::from("role:Button && attr:processid=42"))
+            .unwrap();
+        assert_eq!(close.name.as_deref(), Some("Close"));
+    }
+
+    #[test]
+    fn test_query_builtin_filters_on_snapshots() {
+        let mut tree = form();
+        tree.children[0].children[1].attributes.value = Some("alice".to_string());
+        tree.children[2].attributes.enabled = Some(false);
+        tree.children[2].attributes.bounds = Some((500.0, 120.0, 100.0, 30.0));
+        let q = |s: &str| tree.query(&Selector::from(s)).map(names);
+
+        assert_eq!(
+            q("role:Edit && filter:hasValue").unwrap(),
+            vec!["User field"]
+        );
+        assert_eq!(q("role:Button >> filter:enabled").unwrap(), vec!["Sign in"]);
+        assert_eq!(
+            q("filter:inViewport && role:Button").unwrap(),
+            vec!["Sign in"]
+        );
+        assert!(matches!(
+            q("filter:enabled"),
+            Err(AutomationError::InvalidSelector(_))
+        ));
+
+        let custom = crate::filters::FilterHandle::register(|_| Ok(true));
+        assert!(matches!(
+            tree.query(&Selector::Chain(vec![
+                Selector::from("role:Button"),
+                Selector::Filter(custom.id())
+            ])),
+            Err(AutomationError::UnsupportedOperation(_))
+        ));
+    }
+
+    #[test]
+    fn test_process_matching() {
+        assert!(process_matches("gnome-calculator", "gnome-calculator"));
+        assert!(process_matches("gnome-calculator", "gnome-calc"));
+        assert!(process_matches("firefox", "firefox.exe"));
+        assert!(process_matches("Notepad.exe", "notepad"));
+        assert!(process_matches("firefox-bin", "Firefox"));
+        assert!(!process_matches("gedit", "firefox"));
+    }
+
+    #[test]
+    fn test_role_aliases() {
+        for alias in ["textfield", "input", "url", "Edit"] {
+            assert_eq!(canonical_role(alias), "edit", "{alias}");
+        }
+        assert_eq!(canonical_role("dialog"), canonical_role("Window"));
+    }
+
+    #[test]
+    fn test_spatial_relations() {
+        let anchor = (100.0, 100.0, 50.0, 20.0);
+        let right = (160.0, 105.0, 40.0, 20.0);
+        let below = (100.0, 130.0, 50.0, 20.0);
+        let parent = || Box::new(Selector::Parent);
+        assert!(spatially_related(
+            &Selector::RightOf(parent()),
+            anchor,
+            right
+        ));
+        assert!(!spatially_related(
+            &Selector::LeftOf(parent()),
+            anchor,
+            right
+        ));
+        assert!(spatially_related(&Selector::Below(parent()), anchor, below));
+        assert!(spatially_related(&Selector::Near(parent()), anchor, below));
+    }
+
+    #[test]
+    fn test_apply_nth() {
+        assert_eq!(apply_nth(vec![4, 5, 6], 1), vec![5]);
+        assert_eq!(apply_nth(vec![4, 5, 6], -1), vec![6]);
+        assert!(apply_nth(vec![4, 5, 6], 3).is_empty());
+    }
+}
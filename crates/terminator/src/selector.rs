@@ -17,8 +17,9 @@ pub enum Selector {
     NativeId(String),
     /// Select by multiple attributes (key-value pairs)
     Attributes(BTreeMap<String, String>),
-    /// Filter current elements by a predicate
-    Filter(usize), // Uses an ID to reference a filter predicate stored separately
+    /// Filter current elements by a predicate, referenced by its id in the
+    /// [`crate::filters`] registry
+    Filter(usize),
     /// Chain multiple selectors
     Chain(Vec<Selector>),
     /// Select by class name
@@ -55,9 +56,207 @@ pub enum Selector {
     Invalid(String),
 }
 
+/// Prefixes of selectors that wrap another selector, e.g. `has:(role:Button && name:OK)`
+const RELATIONAL_PREFIXES: [&str; 6] = ["rightof:", "leftof:", "above:", "below:", "near:", "has:"];
+
+impl Selector {
+    /// Binding strength of the selector's top-level operator, used by the printer to
+    /// decide when a nested selector needs parentheses (higher binds tighter)
+    fn precedence(&self) -> u8 {
+        match self {
+            Selector::Chain(parts) | Selector::And(parts) | Selector::Or(parts)
+                if parts.len() == 1 =>
+            {
+                parts[0].precedence()
+            }
+            Selector::Chain(parts) if !parts.is_empty() => 0,
+            Selector::Or(parts) if !parts.is_empty() => 1,
+            Selector::And(parts) if !parts.is_empty() => 2,
+            Selector::Not(_) => 3,
+            _ => 4,
+        }
+    }
+}
+
+/// Whether a value must be quoted to survive parsing unchanged
+fn needs_quotes(value: &str, extra_reserved: &[char]) -> bool {
+    value.is_empty()
+        || value.starts_with(char::is_whitespace)
+        || value.ends_with(char::is_whitespace)
+        || value.chars().any(|c| {
+            c.is_control()
+                || matches!(c, '&' | '|' | ',' | '!' | '(' | ')' | '>' | '"' | '\\')
+                || extra_reserved.contains(&c)
+        })
+}
+
+/// Write a selector value, quoting and escaping it when it contains selector syntax
+fn write_value(
+    f: &mut std::fmt::Formatter<'_>,
+    value: &str,
+    extra_reserved: &[char],
+) -> std::fmt::Result {
+    if needs_quotes(value, extra_reserved) {
+        write_quoted(f, value)
+    } else {
+        f.write_str(value)
+    }
+}
+
+fn write_quoted(f: &mut std::fmt::Formatter<'_>, value: &str) -> std::fmt::Result {
+    f.write_str("\"")?;
+    for c in value.chars() {
+        if matches!(c, '"' | '\\') {
+            f.write_str("\\")?;
+        }
+        write!(f, "{c}")?;
+    }
+    f.write_str("\"")
+}
+
+/// Write `selector`, parenthesized if its operator binds looser than `min_precedence`
+fn write_operand(
+    f: &mut std::fmt::Formatter<'_>,
+    selector: &Selector,
+    min_precedence: u8,
+) -> std::fmt::Result {
+    if selector.precedence() < min_precedence {
+        write!(f, "({selector})")
+    } else {
+        write!(f, "{selector}")
+    }
+}
+
+fn write_joined(
+    f: &mut std::fmt::Formatter<'_>,
+    parts: &[Selector],
+    separator: &str,
+    min_precedence: u8,
+) -> std::fmt::Result {
+    if parts.is_empty() {
+        return f.write_str("()");
+    }
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 {
+            f.write_str(separator)?;
+        }
+        write_operand(f, part, min_precedence)?;
+    }
+    Ok(())
+}
+
+/// Prints the canonical selector string, which parses back to an equal `Selector`.
+///
+/// Values containing selector syntax are double-quoted (`name:"Save & Close"`), and
+/// nested selectors are parenthesized only where operator precedence requires it.
+/// `Invalid` prints its reason, and compounds holding one or no selectors print their
+/// only element or `()`; these are the only forms that do not round-trip.
 impl std::fmt::Display for Selector {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
+        match self {
+            Selector::Role { role, name } => {
+                f.write_str("role:")?;
+                write_value(f, role, &[])?;
+                if let Some(name) = name {
+                    // The legacy `role|name` form is the only syntax for a named Role
+                    f.write_str("|name:")?;
+                    if name.starts_with("contains:") {
+                        write_quoted(f, name)?;
+                    } else {
+                        write_value(f, name, &[])?;
+                    }
+                }
+                Ok(())
+            }
+            Selector::Id(id) => {
+                f.write_str("id:")?;
+                write_value(f, id, &[])
+            }
+            Selector::Name(name) => {
+                f.write_str("name:")?;
+                write_value(f, name, &[])
+            }
+            Selector::Text(text) => {
+                // Unquoted text: values run over `||`, `,` and `)`, so always quote them
+                f.write_str("text:")?;
+                write_quoted(f, text)
+            }
+            Selector::Path(path) => {
+                if path.starts_with('/') && !path.contains(':') && !needs_quotes(path, &[]) {
+                    f.write_str(path)
+                } else {
+                    f.write_str("path:")?;
+                    write_value(f, path, &[])
+                }
+            }
+            Selector::NativeId(id) => {
+                f.write_str("nativeid:")?;
+                write_value(f, id, &[])
+            }
+            Selector::Attributes(attributes) => {
+                f.write_str("attr:")?;
+                for (i, (key, value)) in attributes.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(";")?;
+                    }
+                    write_value(f, key, &['=', ';'])?;
+                    f.write_str("=")?;
+                    write_value(f, value, &['=', ';'])?;
+                }
+                Ok(())
+            }
+            Selector::Filter(id) => match crate::filters::filter_name(*id) {
+                Some(name) => write!(f, "filter:{name}"),
+                None => write!(f, "filter:{id}"),
+            },
+            Selector::Chain(parts) => write_joined(f, parts, " >> ", 1),
+            Selector::ClassName(class_name) => {
+                f.write_str("classname:")?;
+                write_value(f, class_name, &[])
+            }
+            Selector::Visible(visible) => write!(f, "visible:{visible}"),
+            Selector::LocalizedRole(role) => {
+                f.write_str("localizedrole:")?;
+                write_value(f, role, &[])
+            }
+            Selector::Process(process) => {
+                f.write_str("process:")?;
+                write_value(f, process, &[])
+            }
+            Selector::RightOf(inner) => {
+                f.write_str("rightof:")?;
+                write_operand(f, inner, 4)
+            }
+            Selector::LeftOf(inner) => {
+                f.write_str("leftof:")?;
+                write_operand(f, inner, 4)
+            }
+            Selector::Above(inner) => {
+                f.write_str("above:")?;
+                write_operand(f, inner, 4)
+            }
+            Selector::Below(inner) => {
+                f.write_str("below:")?;
+                write_operand(f, inner, 4)
+            }
+            Selector::Near(inner) => {
+                f.write_str("near:")?;
+                write_operand(f, inner, 4)
+            }
+            Selector::Nth(index) => write!(f, "nth:{index}"),
+            Selector::Has(inner) => {
+                f.write_str("has:")?;
+                write_operand(f, inner, 4)
+            }
+            Selector::Parent => f.write_str(".."),
+            Selector::And(parts) => write_joined(f, parts, " && ", 3),
+            Selector::Or(parts) => write_joined(f, parts, " || ", 2),
+            Selector::Not(inner) => {
+                f.write_str("!")?;
+                write_operand(f, inner, 4)
+            }
+            Selector::Invalid(reason) => f.write_str(reason),
+        }
     }
 }
 
@@ -72,6 +271,91 @@ enum Token {
     RParen, // )
 }
 
+/// Whether a `"` following `prev` starts a quoted value. Quotes are only recognised
+/// at the start of a value (`name:"a && b"`, `attr:"k"="v"`), so existing selectors
+/// that merely contain a quote character keep their meaning.
+fn opens_quote(prev: Option<char>) -> bool {
+    matches!(prev, None | Some(':' | '=' | ';'))
+}
+
+/// Each character of `s` with its byte offset and whether it belongs to a quoted
+/// value (including the quotes themselves)
+fn scan_quoted(s: &str) -> Vec<(usize, char, bool)> {
+    let mut out = Vec::with_capacity(s.len());
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut prev = None;
+    for (i, ch) in s.char_indices() {
+        if in_quotes {
+            out.push((i, ch, true));
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == '"' {
+                in_quotes = false;
+            }
+        } else {
+            in_quotes = ch == '"' && opens_quote(prev);
+            out.push((i, ch, in_quotes));
+        }
+        prev = Some(ch);
+    }
+    out
+}
+
+/// `s` with the contents of quoted values blanked out, for operator detection
+fn mask_quoted(s: &str) -> String {
+    scan_quoted(s)
+        .into_iter()
+        .map(|(_, ch, quoted)| if quoted { '_' } else { ch })
+        .collect()
+}
+
+/// Split `s` on `separator` outside quoted values and parentheses. Parentheses are
+/// ignored when unbalanced, since `text:` values may contain stray ones.
+fn split_top_level<'a>(s: &'a str, separator: &str) -> Vec<&'a str> {
+    let track_depth = !has_unbalanced_parens(&mask_quoted(s));
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, ch, quoted) in scan_quoted(s) {
+        if quoted || i < start {
+            continue;
+        }
+        match ch {
+            '(' if track_depth => depth += 1,
+            ')' if track_depth => depth -= 1,
+            _ if depth == 0 && s[i..].starts_with(separator) => {
+                parts.push(&s[start..i]);
+                start = i + separator.len();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// The literal value of a selector argument: quoted values are unescaped, anything
+/// else is taken as-is
+fn literal(value: &str) -> String {
+    unquote(value).unwrap_or_else(|| value.to_string())
+}
+
+fn unquote(value: &str) -> Option<String> {
+    let mut chars = value.strip_prefix('"')?.chars();
+    let mut out = String::new();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => out.push(chars.next()?),
+            '"' => return chars.as_str().is_empty().then_some(out),
+            _ => out.push(ch),
+        }
+    }
+    None
+}
+
 /// Check if a string has unbalanced parentheses
 fn has_unbalanced_parens(s: &str) -> bool {
     let mut depth = 0;
@@ -90,26 +374,93 @@ fn has_unbalanced_parens(s: &str) -> bool {
     depth != 0 // Unbalanced if depth is not zero
 }
 
+/// Byte length of a parenthesized group's contents, given the text following its `(`
+fn group_len(rest: &str) -> Option<usize> {
+    let mut depth = 1;
+    for (i, ch, quoted) in scan_quoted(rest) {
+        match ch {
+            _ if quoted => {}
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn ends_with_relational_prefix(current: &str) -> bool {
+    let current = current.to_lowercase();
+    RELATIONAL_PREFIXES
+        .iter()
+        .any(|prefix| current.ends_with(prefix))
+}
+
 /// Tokenize a selector string into tokens for boolean expression parsing
 fn tokenize(input: &str) -> Result<Vec<Token>, String> {
     let mut tokens = Vec::new();
     let mut chars = input.chars().peekable();
     let mut current = String::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
 
     while let Some(ch) = chars.next() {
+        // Quoted values are taken verbatim, operators included
+        if in_quotes {
+            current.push(ch);
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == '"' {
+                in_quotes = false;
+            }
+            continue;
+        }
+        if ch == '"' && opens_quote(current.chars().last()) {
+            in_quotes = true;
+            current.push(ch);
+            continue;
+        }
+
         // Special handling for text: selectors - they can contain any characters
         // except && which is the boolean AND operator we use to chain selectors.
         // This allows text: values like "RPA Hospital (MGP)? : r/foo" to work correctly.
-        let in_text_selector = current.trim().starts_with("text:");
+        // A quoted text: value ends at its closing quote.
+        let in_text_selector = current
+            .trim()
+            .strip_prefix("text:")
+            .is_some_and(|value| !value.trim_start().starts_with('"'));
 
         match ch {
-            // Parentheses - these are operators/delimiters (unless inside text:)
-            '(' if !in_text_selector => {
-                if !current.is_empty() {
-                    tokens.push(Token::Selector(current.trim().to_string()));
-                    current.clear();
+            // A parenthesized chain is a single operand: `(a >> b) && c`
+            '(' if !in_text_selector && current.trim().is_empty() => {
+                let rest: String = chars.clone().collect();
+                match group_len(&rest) {
+                    Some(len) if split_top_level(&rest[..len], ">>").len() > 1 => {
+                        tokens.push(Token::Selector(format!("({})", &rest[..len])));
+                        for _ in rest[..=len].chars() {
+                            chars.next();
+                        }
+                    }
+                    _ => tokens.push(Token::LParen),
+                }
+            }
+            // A parenthesized group right after a relational prefix (`has:(...)`) is
+            // the wrapped selector, not a grouping of the surrounding expression
+            '(' if !in_text_selector && ends_with_relational_prefix(&current) => {
+                let rest: String = chars.clone().collect();
+                // Up to and including the closing `)`, or everything if it is missing
+                let end = group_len(&rest).map_or(rest.len(), |len| len + 1);
+                current.push('(');
+                current.push_str(&rest[..end]);
+                for _ in rest[..end].chars() {
+                    chars.next();
                 }
-                tokens.push(Token::LParen);
             }
             ')' if !in_text_selector => {
                 if !current.is_empty() {
@@ -220,8 +571,12 @@ fn parse_boolean_expression(tokens: Vec<Token>) -> Result<Selector, String> {
     for token in tokens {
         match token {
             Token::Selector(s) => {
-                // Parse the atomic selector
-                output_queue.push(parse_atomic_selector(&s));
+                // A parenthesized group may hold a chain, e.g. `(a >> b) && c`
+                if s.starts_with('(') || split_top_level(&s, ">>").len() > 1 {
+                    output_queue.push(Selector::from(s.as_str()));
+                } else {
+                    output_queue.push(parse_atomic_selector(&s));
+                }
             }
             Token::LParen => {
                 operator_stack.push(token);
@@ -335,33 +690,35 @@ fn apply_operator(op: Token, output_queue: &mut Vec<Selector>) -> Result<(), Str
 fn parse_atomic_selector(s: &str) -> Selector {
     let s = s.trim();
 
+    // Relational selectors wrap a whole selector, so they are matched before the
+    // legacy pipe syntax below can split the wrapped one
+    if let Some(selector) = parse_relational_selector(s) {
+        return selector;
+    }
+
     // Check if this is a legacy pipe syntax (role|name) - backward compatibility
     // Only treat as legacy if it contains exactly ONE pipe and no boolean operators
-    if s.contains('|') && !s.contains("||") && s.matches('|').count() == 1 {
-        let parts: Vec<&str> = s.split('|').collect();
-        if parts.len() == 2 {
-            let role_part = parts[0].trim();
-            let name_part = parts[1].trim();
-
-            let role = role_part
-                .strip_prefix("role:")
-                .unwrap_or(role_part)
-                .to_string();
-
-            let mut name = name_part.strip_prefix("name:").unwrap_or(name_part);
-            name = name.strip_prefix("contains:").unwrap_or(name);
-
-            return Selector::Role {
-                role,
-                name: Some(name.to_string()),
-            };
-        }
+    let pipes = split_top_level(s, "|");
+    if pipes.len() == 2 && !mask_quoted(s).contains("||") {
+        let role_part = pipes[0].trim();
+        let name_part = pipes[1].trim();
+
+        let role = literal(role_part.strip_prefix("role:").unwrap_or(role_part));
+
+        let name = name_part.strip_prefix("name:").unwrap_or(name_part);
+        let name = unquote(name)
+            .unwrap_or_else(|| name.strip_prefix("contains:").unwrap_or(name).to_string());
+
+        return Selector::Role {
+            role,
+            name: Some(name),
+        };
     }
 
     // Parse all other atomic selector types
     match s {
         _ if s.starts_with("role:") => Selector::Role {
-            role: s[5..].to_string(),
+            role: literal(&s[5..]),
             name: None,
         },
         "app" | "application" | "window" | "button" | "checkbox" | "menu" | "menuitem"
@@ -378,15 +735,15 @@ fn parse_atomic_selector(s: &str) -> Selector {
         },
         _ if s.starts_with("Name:") || s.starts_with("name:") => {
             let parts: Vec<&str> = s.splitn(2, ':').collect();
-            Selector::Name(parts[1].to_string())
+            Selector::Name(literal(parts[1]))
         }
         _ if s.to_lowercase().starts_with("classname:") => {
             let parts: Vec<&str> = s.splitn(2, ':').collect();
-            Selector::ClassName(parts[1].to_string())
+            Selector::ClassName(literal(parts[1]))
         }
         _ if s.to_lowercase().starts_with("nativeid:") => {
             let parts: Vec<&str> = s.splitn(2, ':').collect();
-            Selector::NativeId(parts[1].trim().to_string())
+            Selector::NativeId(literal(parts[1].trim()))
         }
         _ if s.to_lowercase().starts_with("visible:") => {
             let value = s[8..].trim().to_lowercase();
@@ -398,48 +755,31 @@ fn parse_atomic_selector(s: &str) -> Selector {
             } else {
                 "processname:".len()
             };
-            let process_name = s[prefix_len..].trim().to_string();
+            let process_name = literal(s[prefix_len..].trim());
             Selector::Process(process_name)
         }
         _ if s.to_lowercase().starts_with("attr:") => {
-            let attr_part = &s["attr:".len()..];
+            let attr_part = s["attr:".len()..].trim();
             let mut attributes = BTreeMap::new();
 
-            if attr_part.contains('=') {
-                let parts: Vec<&str> = attr_part.splitn(2, '=').collect();
-                if parts.len() == 2 {
-                    attributes.insert(parts[0].trim().to_string(), parts[1].trim().to_string());
+            // `attr:key=value;other=value`, where a bare `attr:key` means `key=true`
+            if !attr_part.is_empty() {
+                for pair in split_top_level(attr_part, ";") {
+                    match split_top_level(pair, "=").split_first() {
+                        Some((key, [])) => {
+                            attributes.insert(literal(key.trim()), "true".to_string());
+                        }
+                        Some((key, _)) => {
+                            let value = &pair[key.len() + 1..];
+                            attributes.insert(literal(key.trim()), literal(value.trim()));
+                        }
+                        None => {}
+                    }
                 }
-            } else {
-                attributes.insert(attr_part.trim().to_string(), "true".to_string());
             }
 
             Selector::Attributes(attributes)
         }
-        _ if s.to_lowercase().starts_with("rightof:") => {
-            let inner_selector_str = &s["rightof:".len()..];
-            Selector::RightOf(Box::new(Selector::from(inner_selector_str)))
-        }
-        _ if s.to_lowercase().starts_with("leftof:") => {
-            let inner_selector_str = &s["leftof:".len()..];
-            Selector::LeftOf(Box::new(Selector::from(inner_selector_str)))
-        }
-        _ if s.to_lowercase().starts_with("above:") => {
-            let inner_selector_str = &s["above:".len()..];
-            Selector::Above(Box::new(Selector::from(inner_selector_str)))
-        }
-        _ if s.to_lowercase().starts_with("below:") => {
-            let inner_selector_str = &s["below:".len()..];
-            Selector::Below(Box::new(Selector::from(inner_selector_str)))
-        }
-        _ if s.to_lowercase().starts_with("near:") => {
-            let inner_selector_str = &s["near:".len()..];
-            Selector::Near(Box::new(Selector::from(inner_selector_str)))
-        }
-        _ if s.to_lowercase().starts_with("has:") => {
-            let inner_selector_str = &s["has:".len()..];
-            Selector::Has(Box::new(Selector::from(inner_selector_str)))
-        }
         _ if s.to_lowercase().starts_with("nth=") || s.to_lowercase().starts_with("nth:") => {
             let index_str = if s.to_lowercase().starts_with("nth:") {
                 &s["nth:".len()..]
@@ -453,8 +793,24 @@ fn parse_atomic_selector(s: &str) -> Selector {
                 Selector::Invalid(format!("Invalid index for nth selector: '{index_str}'"))
             }
         }
-        _ if s.starts_with("id:") => Selector::Id(s[3..].to_string()),
-        _ if s.starts_with("text:") => Selector::Text(s[5..].to_string()),
+        _ if s.starts_with("id:") => Selector::Id(literal(&s[3..])),
+        _ if s.starts_with("text:") => Selector::Text(literal(&s[5..])),
+        _ if s.starts_with("path:") => Selector::Path(literal(&s[5..])),
+        _ if s.to_lowercase().starts_with("localizedrole:") => {
+            Selector::LocalizedRole(literal(&s["localizedrole:".len()..]))
+        }
+        _ if s.to_lowercase().starts_with("filter:") => {
+            let id_str = s["filter:".len()..].trim();
+            match id_str.parse::<usize>() {
+                Ok(id) => Selector::Filter(id),
+                Err(_) => match crate::filters::filter_id(id_str) {
+                    Some(id) => Selector::Filter(id),
+                    None => Selector::Invalid(format!(
+                        "Unknown filter '{id_str}'. Built-in filters are hasValue, enabled and inViewport; others must be registered first"
+                    )),
+                },
+            }
+        }
         _ if s.contains(':') => {
             let parts: Vec<&str> = s.splitn(2, ':').collect();
             Selector::Role {
@@ -471,47 +827,78 @@ fn parse_atomic_selector(s: &str) -> Selector {
     }
 }
 
+/// Parse `rightof:`, `leftof:`, `above:`, `below:`, `near:` and `has:` selectors
+fn parse_relational_selector(s: &str) -> Option<Selector> {
+    let lower = s.to_lowercase();
+    let prefix = RELATIONAL_PREFIXES
+        .iter()
+        .find(|prefix| lower.starts_with(*prefix))?;
+    let inner = Box::new(Selector::from(&s[prefix.len()..]));
+    Some(match *prefix {
+        "rightof:" => Selector::RightOf(inner),
+        "leftof:" => Selector::LeftOf(inner),
+        "above:" => Selector::Above(inner),
+        "below:" => Selector::Below(inner),
+        "near:" => Selector::Near(inner),
+        _ => Selector::Has(inner),
+    })
+}
+
 impl From<&str> for Selector {
     fn from(s: &str) -> Self {
         let s = s.trim();
 
+        // A fully parenthesized selector is the selector itself
+        if let Some(inner) = s.strip_prefix('(').and_then(|rest| rest.strip_suffix(')')) {
+            if !has_unbalanced_parens(&mask_quoted(inner)) {
+                return Selector::from(inner);
+            }
+        }
+
         // Handle chained selectors first (>> has highest priority)
-        if s.contains(">>") {
-            let parts: Vec<&str> = s.split(">>").map(|p| p.trim()).collect();
-            if parts.len() > 1 {
-                // Strip outer parentheses from each part if present
-                let cleaned_parts: Vec<Selector> = parts
-                    .into_iter()
-                    .map(|part| {
-                        let trimmed = part.trim();
-                        // Check if the part is wrapped in parentheses
-                        if trimmed.starts_with('(') && trimmed.ends_with(')') {
-                            // Check if these are truly outer parentheses (balanced)
-                            let inner = &trimmed[1..trimmed.len() - 1];
-                            // Only strip if the parentheses are balanced at this level
-                            if !has_unbalanced_parens(inner) {
-                                return Selector::from(inner);
-                            }
+        let parts: Vec<&str> = split_top_level(s, ">>")
+            .into_iter()
+            .map(|p| p.trim())
+            .collect();
+        if parts.len() > 1 {
+            // Strip outer parentheses from each part if present
+            let cleaned_parts: Vec<Selector> = parts
+                .into_iter()
+                .map(|part| {
+                    // Check if the part is wrapped in parentheses
+                    if part.starts_with('(') && part.ends_with(')') {
+                        // Check if these are truly outer parentheses (balanced)
+                        let inner = &part[1..part.len() - 1];
+                        // Only strip if the parentheses are balanced at this level
+                        if !has_unbalanced_parens(&mask_quoted(inner)) {
+                            return Selector::from(inner);
                         }
-                        Selector::from(trimmed)
-                    })
-                    .collect();
-                return Selector::Chain(cleaned_parts);
-            }
+                    }
+                    Selector::from(part)
+                })
+                .collect();
+            return Selector::Chain(cleaned_parts);
         }
 
-        // Check if this contains boolean operators (&&, ||, !, parentheses, or comma for OR)
-        let has_boolean_ops = s.contains("&&")
-            || s.contains("||")
-            || s.contains('(')
-            || s.contains(')')
-            || s.contains('!')
-            || (s.contains(',') && !s.starts_with("attr:")); // comma is OR unless in attr: context
+        // Check if this contains boolean operators (&&, ||, !, parentheses, or comma for OR),
+        // ignoring anything inside quoted values
+        let masked = mask_quoted(s);
+        let has_boolean_ops = masked.contains("&&")
+            || masked.contains("||")
+            || masked.contains('(')
+            || masked.contains(')')
+            || masked.contains('!')
+            || (masked.contains(',') && !s.starts_with("attr:")); // comma is OR unless in attr: context
 
         if has_boolean_ops {
             // Check if this is ONLY a text: selector with special characters (not a boolean expression)
-            // text: is special because the value after the colon can legitimately contain these characters
-            if s.starts_with("text:") && !s[5..].contains("text:") {
+            // text: is special because the value after the colon can legitimately contain these characters,
+            // except && which always joins selectors
+            if s.starts_with("text:")
+                && !s[5..].contains("text:")
+                && !s[5..].trim_start().starts_with('"')
+                && !masked.contains("&&")
+            {
                 // This is a simple text: selector with special chars in the text, not a boolean expression
                 return parse_atomic_selector(s);
             }
@@ -536,6 +923,18 @@ impl From<&str> for Selector {
 #[path = "selector_tests.rs"]
 mod selector_tests;
 
+#[cfg(test)]
+#[path = "selector_print_tests.rs"]
+mod selector_print_tests;
+
+#[cfg(test)]
+#[path = "selector_parse_tests.rs"]
+mod selector_parse_tests;
+
+#[path = "selector_parse.rs"]
+mod parse;
+pub use parse::{SelectorParseError, SelectorParseMode};
+
 #[cfg(test)]
 mod debug_selector_test {
     use crate::selector::Selector;
@@ -0,0 +1,659 @@
+//! Checked selector parsing with source spans
+//!
+//! `Selector::from` never fails: malformed input becomes `Selector::Invalid`, stray
+//! parentheses are absorbed into values and unknown `foo:bar` selectors are read as
+//! `role:foo` named `bar`. `Selector::parse` checks the input the same way the parser
+//! splits it and reports the first problem with the byte range it covers, what was
+//! expected there and, where possible, a fix.
+
+use super::{
+    ends_with_relational_prefix, group_len, has_unbalanced_parens, mask_quoted, opens_quote,
+    scan_quoted, split_top_level, unquote, Selector, RELATIONAL_PREFIXES,
+};
+use crate::utils::edit_distance;
+use crate::AutomationError;
+use serde::Serialize;
+use std::ops::Range;
+
+/// How `Selector::parse_with` treats input that the lenient parser has to guess at
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SelectorParseMode {
+    /// Accept everything `Selector::from` understands
+    #[default]
+    Lenient,
+    /// Also reject unknown `prefix:` selectors (instead of reading them as `role:prefix`)
+    /// and `visible:` values other than `true`/`false`
+    Strict,
+}
+
+/// A selector syntax error, located by byte offsets into the parsed string
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, thiserror::Error)]
+#[error("{message} at {}..{}", span.start, span.end)]
+pub struct SelectorParseError {
+    pub message: String,
+    /// Byte range of the offending input; empty at the position of a missing token
+    pub span: Range<usize>,
+    /// Tokens or selector kinds that would have been valid at `span`
+    pub expected: Vec<String>,
+    /// A suggested replacement or fix
+    pub suggestion: Option<String>,
+}
+
+impl SelectorParseError {
+    fn new(message: impl Into<String>, span: Range<usize>) -> Self {
+        Self {
+            message: message.into(),
+            span,
+            expected: Vec::new(),
+            suggestion: None,
+        }
+    }
+
+    fn expected<S: Into<String>>(mut self, expected: impl IntoIterator<Item = S>) -> Self {
+        self.expected = expected.into_iter().map(Into::into).collect();
+        self
+    }
+
+    fn suggest(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+
+    /// Render the error below `input` with a caret marker, e.g.
+    ///
+    /// ```text
+    /// role:Button && && name:OK
+    ///                ^^ expected a selector between `&&` and `&&`
+    ///   expected: selector, `(`, `!`
+    /// ```
+    pub fn render(&self, input: &str) -> String {
+        let start = floor_char_boundary(input, self.span.start);
+        let end = floor_char_boundary(input, self.span.end).max(start);
+        let column = input[..start].chars().count();
+        let width = input[start..end].chars().count().max(1);
+        let mut out = format!(
+            "{input}\n{}{} {}",
+            " ".repeat(column),
+            "^".repeat(width),
+            self.message
+        );
+        if !self.expected.is_empty() {
+            out.push_str(&format!("\n  expected: {}", self.expected.join(", ")));
+        }
+        if let Some(suggestion) = &self.suggestion {
+            out.push_str(&format!("\n  suggestion: {suggestion}"));
+        }
+        out
+    }
+}
+
+impl From<SelectorParseError> for AutomationError {
+    fn from(error: SelectorParseError) -> Self {
+        AutomationError::InvalidSelector(error.to_string())
+    }
+}
+
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    let mut index = index.min(s.len());
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Prefixes understood by the parser, offered as expected tokens and typo fixes
+const KNOWN_PREFIXES: [&str; 20] = [
+    "role:",
+    "name:",
+    "id:",
+    "text:",
+    "nativeid:",
+    "classname:",
+    "process:",
+    "processname:",
+    "attr:",
+    "visible:",
+    "localizedrole:",
+    "path:",
+    "filter:",
+    "nth:",
+    "has:",
+    "rightof:",
+    "leftof:",
+    "above:",
+    "below:",
+    "near:",
+];
+
+/// Bare words the parser reads as roles (e.g. `button`)
+const BARE_ROLES: [&str; 10] = [
+    "app",
+    "application",
+    "window",
+    "button",
+    "checkbox",
+    "menu",
+    "menuitem",
+    "menubar",
+    "textfield",
+    "input",
+];
+
+const OPERAND_START: [&str; 3] = ["selector", "`(`", "`!`"];
+
+impl Selector {
+    /// Parse a selector string, reporting malformed input as a located error.
+    ///
+    /// Successful results are identical to `Selector::from`.
+    pub fn parse(input: &str) -> Result<Selector, SelectorParseError> {
+        Self::parse_with(input, SelectorParseMode::Lenient)
+    }
+
+    /// Like [`Selector::parse`], but also rejects unknown `prefix:` selectors
+    pub fn parse_strict(input: &str) -> Result<Selector, SelectorParseError> {
+        Self::parse_with(input, SelectorParseMode::Strict)
+    }
+
+    pub fn parse_with(
+        input: &str,
+        mode: SelectorParseMode,
+    ) -> Result<Selector, SelectorParseError> {
+        Checker { input, mode }.selector(0..input.len())?;
+
+        let selector = Selector::from(input);
+        match find_invalid(&selector) {
+            // Anything the checker let through that the parser still rejected
+            Some(reason) => Err(SelectorParseError::new(reason, 0..input.len())),
+            None => Ok(selector),
+        }
+    }
+}
+
+fn find_invalid(selector: &Selector) -> Option<&str> {
+    match selector {
+        Selector::Invalid(reason) => Some(reason),
+        Selector::Chain(parts) | Selector::And(parts) | Selector::Or(parts) => {
+            parts.iter().find_map(find_invalid)
+        }
+        Selector::Not(inner)
+        | Selector::Has(inner)
+        | Selector::RightOf(inner)
+        | Selector::LeftOf(inner)
+        | Selector::Above(inner)
+        | Selector::Below(inner)
+        | Selector::Near(inner) => find_invalid(inner),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Atom,
+    /// A parenthesized chain such as `(a >> b)`, checked as a whole selector
+    Group,
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+struct Checker<'a> {
+    input: &'a str,
+    mode: SelectorParseMode,
+}
+
+impl Checker<'_> {
+    /// Check the selector in `range`, mirroring `Selector::from`
+    fn selector(&self, range: Range<usize>) -> Result<(), SelectorParseError> {
+        let range = self.trim(range);
+        let text = &self.input[range.clone()];
+        if text.is_empty() {
+            return Err(SelectorParseError::new("empty selector", range).expected(OPERAND_START));
+        }
+
+        // A fully parenthesized selector is the selector itself
+        if let Some(inner) = text
+            .strip_prefix('(')
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            if !has_unbalanced_parens(&mask_quoted(inner)) {
+                return self.selector(range.start + 1..range.end - 1);
+            }
+        }
+
+        let parts = split_top_level(text, ">>");
+        if parts.len() == 1 {
+            return self.expression(range);
+        }
+        let mut offset = range.start;
+        for (i, part) in parts.iter().enumerate() {
+            let part_range = offset..offset + part.len();
+            if part.trim().is_empty() {
+                let (message, at) = if i == 0 {
+                    (
+                        "expected a selector before `>>`",
+                        part_range.end..part_range.end + 2,
+                    )
+                } else {
+                    (
+                        "expected a selector after `>>`",
+                        part_range.start..part_range.start,
+                    )
+                };
+                return Err(SelectorParseError::new(message, at)
+                    .expected(OPERAND_START)
+                    .suggest("remove the extra `>>`"));
+            }
+            self.selector(part_range)?;
+            offset += part.len() + ">>".len();
+        }
+        Ok(())
+    }
+
+    /// Check a boolean expression (`&&`, `||`, `,`, `!` and parentheses)
+    fn expression(&self, range: Range<usize>) -> Result<(), SelectorParseError> {
+        let tokens = self.lex(range)?;
+        let mut open_parens = Vec::new();
+        let mut expect_operand = true;
+        let mut previous: Option<(Token, Range<usize>)> = None;
+        let mut last_atom: Option<Range<usize>> = None;
+
+        for (token, span) in tokens.iter().cloned() {
+            match (expect_operand, token) {
+                (true, Token::Atom) => {
+                    self.atom(span.clone()).map_err(|error| {
+                        // `name:Hello, World`: the comma was meant to be part of the value
+                        match (&previous, &last_atom) {
+                            (Some((Token::Or, comma)), Some(value))
+                                if &self.input[comma.clone()] == ","
+                                    && error.message.starts_with("unknown selector `") =>
+                            {
+                                error.suggest(format!(
+                                    "quote values containing commas: {}",
+                                    quote_value(&self.input[value.start..span.end])
+                                ))
+                            }
+                            _ => error,
+                        }
+                    })?;
+                    expect_operand = false;
+                    last_atom = Some(span.clone());
+                }
+                (true, Token::Group) => {
+                    self.selector(span.start + 1..span.end - 1)?;
+                    expect_operand = false;
+                }
+                (true, Token::Not) => {}
+                (true, Token::LParen) => open_parens.push(span.start),
+                (true, _) => {
+                    let error = match &previous {
+                        None => SelectorParseError::new(
+                            format!("expected a selector before {}", self.describe(&span)),
+                            span.clone(),
+                        )
+                        .suggest(format!("remove the leading {}", self.describe(&span))),
+                        Some((_, prev)) => SelectorParseError::new(
+                            format!(
+                                "expected a selector between {} and {}",
+                                self.describe(prev),
+                                self.describe(&span)
+                            ),
+                            span.clone(),
+                        ),
+                    };
+                    return Err(error.expected(OPERAND_START));
+                }
+                (false, Token::And | Token::Or) => expect_operand = true,
+                (false, Token::RParen) => {
+                    if open_parens.pop().is_none() {
+                        let error = SelectorParseError::new("unmatched `)`", span.clone())
+                            .expected(["`&&`", "`||`", "`>>`"]);
+                        return Err(match &previous {
+                            // `name:foo(bar)`: the `(` became part of the value
+                            Some((Token::Atom, prev)) if self.input[prev.clone()].contains('(') => {
+                                error.suggest(format!(
+                                    "quote values containing parentheses: {}",
+                                    quote_value(&self.input[prev.start..span.end])
+                                ))
+                            }
+                            _ => error.suggest("remove the `)` or add a matching `(`"),
+                        });
+                    }
+                }
+                (false, _) => {
+                    let error = SelectorParseError::new(
+                        format!("expected an operator before {}", self.describe(&span)),
+                        span.clone(),
+                    )
+                    .expected(["`&&`", "`||`", "`>>`", "`)`"]);
+                    return Err(match &previous {
+                        // `name:Hello!`: the `!` was meant to be part of the value
+                        Some((Token::Atom, prev))
+                            if token == Token::Not && prev.end == span.start =>
+                        {
+                            let end = tokens
+                                .iter()
+                                .find(|(t, s)| *t == Token::Atom && s.start == span.end)
+                                .map_or(span.end, |(_, s)| s.end);
+                            error.suggest(format!(
+                                "quote values containing `!`: {}",
+                                quote_value(&self.input[prev.start..end])
+                            ))
+                        }
+                        _ => error.suggest("join the selectors with `&&` or `||`"),
+                    });
+                }
+            }
+            previous = Some((token, span));
+        }
+
+        if let Some((_, span)) = previous.as_ref().filter(|_| expect_operand) {
+            return Err(SelectorParseError::new(
+                format!("expected a selector after {}", self.describe(span)),
+                span.end..span.end,
+            )
+            .expected(OPERAND_START)
+            .suggest(format!("remove the trailing {}", self.describe(span))));
+        }
+        if let Some(&start) = open_parens.last() {
+            return Err(unclosed_paren(start));
+        }
+        Ok(())
+    }
+
+    /// Split `range` into tokens the way `tokenize` does, keeping byte spans
+    fn lex(&self, range: Range<usize>) -> Result<Vec<(Token, Range<usize>)>, SelectorParseError> {
+        let text = &self.input[range.clone()];
+        let base = range.start;
+        let masked = mask_quoted(text);
+        let has_boolean_ops = ["&&", "||", "(", ")", "!"]
+            .iter()
+            .any(|op| masked.contains(op))
+            || (masked.contains(',') && !text.starts_with("attr:"));
+
+        let mut tokens = Vec::new();
+        let mut atom_start: Option<usize> = None;
+        let mut quote_start: Option<usize> = None;
+        let mut escaped = false;
+        let mut chars = text.char_indices().peekable();
+        let flush = |tokens: &mut Vec<(Token, Range<usize>)>, start: &mut Option<usize>, end| {
+            if let Some(start) = start.take() {
+                let end = start + text[start..end].trim_end().len();
+                tokens.push((Token::Atom, base + start..base + end));
+            }
+        };
+
+        while let Some((i, ch)) = chars.next() {
+            if quote_start.is_some() {
+                if escaped {
+                    escaped = false;
+                } else if ch == '\\' {
+                    escaped = true;
+                } else if ch == '"' {
+                    quote_start = None;
+                }
+                continue;
+            }
+            let current = atom_start.map_or("", |start| &text[start..i]);
+            if ch == '"' && opens_quote(current.chars().last()) {
+                quote_start = Some(i);
+                atom_start.get_or_insert(i);
+                continue;
+            }
+            // Without operators the whole input is one selector, commas in `attr:` included
+            let in_text = !has_boolean_ops
+                || current
+                    .strip_prefix("text:")
+                    .is_some_and(|value| !value.trim_start().starts_with('"'));
+            let next = chars.peek().map(|&(_, c)| c);
+
+            match ch {
+                '(' if !in_text && atom_start.is_none() => {
+                    let rest = &text[i + 1..];
+                    match group_len(rest) {
+                        Some(len) if split_top_level(&rest[..len], ">>").len() > 1 => {
+                            let end = i + 1 + len + 1;
+                            tokens.push((Token::Group, base + i..base + end));
+                            while chars.peek().is_some_and(|&(j, _)| j < end) {
+                                chars.next();
+                            }
+                        }
+                        _ => tokens.push((Token::LParen, base + i..base + i + 1)),
+                    }
+                }
+                // The wrapped selector of `has:(...)` belongs to the atom; see `atom`
+                '(' if !in_text && ends_with_relational_prefix(current) => {
+                    let Some(len) = group_len(&text[i + 1..]) else {
+                        return Err(unclosed_paren(base + i));
+                    };
+                    let end = i + 1 + len + 1;
+                    while chars.peek().is_some_and(|&(j, _)| j < end) {
+                        chars.next();
+                    }
+                }
+                ')' if !in_text => {
+                    flush(&mut tokens, &mut atom_start, i);
+                    tokens.push((Token::RParen, base + i..base + i + 1));
+                }
+                '&' if has_boolean_ops && next == Some('&') => {
+                    flush(&mut tokens, &mut atom_start, i);
+                    chars.next();
+                    tokens.push((Token::And, base + i..base + i + 2));
+                }
+                '|' if !in_text && next == Some('|') => {
+                    flush(&mut tokens, &mut atom_start, i);
+                    chars.next();
+                    tokens.push((Token::Or, base + i..base + i + 2));
+                }
+                ',' if !in_text => {
+                    flush(&mut tokens, &mut atom_start, i);
+                    tokens.push((Token::Or, base + i..base + i + 1));
+                }
+                '!' if !in_text => {
+                    flush(&mut tokens, &mut atom_start, i);
+                    tokens.push((Token::Not, base + i..base + i + 1));
+                }
+                ' ' | '\t' | '\n' | '\r' if atom_start.is_none() => {}
+                _ => {
+                    atom_start.get_or_insert(i);
+                }
+            }
+        }
+
+        if let Some(start) = quote_start {
+            return Err(SelectorParseError::new(
+                "unterminated quoted value",
+                base + start..range.end,
+            )
+            .expected(["`\"`"])
+            .suggest("close the value with `\"`, escaping inner quotes as `\\\"`"));
+        }
+        flush(&mut tokens, &mut atom_start, text.len());
+        Ok(tokens)
+    }
+
+    /// Check a single selector such as `role:Button` or `has:(name:OK)`
+    fn atom(&self, span: Range<usize>) -> Result<(), SelectorParseError> {
+        let text = &self.input[span.clone()];
+        let lower = text.to_lowercase();
+
+        if let Some(prefix) = RELATIONAL_PREFIXES.iter().find(|p| lower.starts_with(*p)) {
+            let inner = span.start + prefix.len()..span.end;
+            if self.input[inner.clone()].trim().is_empty() {
+                return Err(SelectorParseError::new(
+                    format!("`{prefix}` needs a selector to relate to"),
+                    inner.start..inner.start,
+                )
+                .expected(OPERAND_START)
+                .suggest(format!("{prefix}(role:Button)")));
+            }
+            return self.selector(inner);
+        }
+
+        // Legacy `role|name`
+        if split_top_level(text, "|").len() == 2 && !mask_quoted(text).contains("||") {
+            return Ok(());
+        }
+
+        let Some(colon) = text.find(':') else {
+            return self.bare(span);
+        };
+        let prefix = &lower[..=colon];
+        let known = match prefix {
+            "role:" | "id:" | "text:" | "path:" => text.starts_with(prefix),
+            "name:" => text.starts_with("name:") || text.starts_with("Name:"),
+            _ => KNOWN_PREFIXES.contains(&prefix),
+        };
+        if !known {
+            return self.unknown_prefix(span, colon);
+        }
+
+        let value_span = span.start + colon + 1..span.end;
+        let value = &self.input[value_span.clone()];
+        if prefix != "attr:" {
+            self.check_quoted(value_span.clone())?;
+        }
+        match prefix {
+            "nth:" => check_index(value, value_span),
+            "filter:" => {
+                if value.parse::<usize>().is_ok() || crate::filters::filter_id(value).is_some() {
+                    Ok(())
+                } else {
+                    Err(
+                        SelectorParseError::new(format!("unknown filter `{value}`"), value_span)
+                            .expected(["filter id", "hasValue", "enabled", "inViewport"]),
+                    )
+                }
+            }
+            "visible:"
+                if self.mode == SelectorParseMode::Strict
+                    && !matches!(value.trim().to_lowercase().as_str(), "true" | "false") =>
+            {
+                Err(
+                    SelectorParseError::new(format!("invalid visibility `{value}`"), value_span)
+                        .expected(["true", "false"]),
+                )
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check a selector without a `prefix:`
+    fn bare(&self, span: Range<usize>) -> Result<(), SelectorParseError> {
+        let text = &self.input[span.clone()];
+        if text.to_lowercase().starts_with("nth=") {
+            return check_index(&text[4..], span.start + 4..span.end);
+        }
+        if BARE_ROLES.contains(&text)
+            || text.starts_with("AX")
+            || text.starts_with('#')
+            || text.starts_with('/')
+            || text == ".."
+        {
+            return Ok(());
+        }
+
+        let error = SelectorParseError::new(format!("unknown selector `{text}`"), span)
+            .expected(KNOWN_PREFIXES);
+        let as_prefix = format!("{}:", text.to_lowercase());
+        Err(if KNOWN_PREFIXES.contains(&as_prefix.as_str()) {
+            error.suggest(format!("add a value after the prefix: `{as_prefix}...`"))
+        } else {
+            error.suggest(format!(
+                "add a prefix, e.g. `{}`",
+                Selector::Name(text.to_string())
+            ))
+        })
+    }
+
+    fn unknown_prefix(&self, span: Range<usize>, colon: usize) -> Result<(), SelectorParseError> {
+        if self.mode == SelectorParseMode::Lenient {
+            // Read as `role:<prefix>` named `<value>`
+            return Ok(());
+        }
+        let text = &self.input[span.clone()];
+        let prefix = &text[..=colon];
+        let error = SelectorParseError::new(
+            format!("unknown selector prefix `{prefix}`"),
+            span.start..span.start + colon + 1,
+        )
+        .expected(KNOWN_PREFIXES);
+        Err(match closest_prefix(&prefix.to_lowercase()) {
+            Some(known) => error.suggest(format!("did you mean `{known}`?")),
+            None => error.suggest(format!(
+                "write `{}` to match a `{}` element by name",
+                Selector::Role {
+                    role: text[..colon].to_string(),
+                    name: Some(text[colon + 1..].to_string()),
+                },
+                &text[..colon]
+            )),
+        })
+    }
+
+    /// A quoted value has to end at its closing quote
+    fn check_quoted(&self, value_span: Range<usize>) -> Result<(), SelectorParseError> {
+        let span = self.trim(value_span);
+        let value = &self.input[span.clone()];
+        if !value.starts_with('"') || unquote(value).is_some() {
+            return Ok(());
+        }
+        let close = scan_quoted(value)
+            .into_iter()
+            .skip(1)
+            .find(|&(_, ch, quoted)| ch == '"' && quoted)
+            .map_or(value.len(), |(i, _, _)| i + 1);
+        Err(SelectorParseError::new(
+            "unexpected text after closing quote",
+            span.start + close..span.end,
+        )
+        .expected(["`&&`", "`||`", "`>>`"])
+        .suggest("escape quotes inside the value as `\\\"`"))
+    }
+
+    fn trim(&self, range: Range<usize>) -> Range<usize> {
+        let text = &self.input[range.clone()];
+        let start = range.start + (text.len() - text.trim_start().len());
+        start..start + text.trim().len()
+    }
+
+    fn describe(&self, span: &Range<usize>) -> String {
+        format!("`{}`", &self.input[span.clone()])
+    }
+}
+
+fn unclosed_paren(start: usize) -> SelectorParseError {
+    SelectorParseError::new("unclosed `(`", start..start + 1)
+        .expected(["`)`"])
+        .suggest("add a closing `)`")
+}
+
+fn check_index(value: &str, span: Range<usize>) -> Result<(), SelectorParseError> {
+    value.parse::<i32>().map(|_| ()).map_err(|_| {
+        SelectorParseError::new(format!("invalid index `{value}`"), span)
+            .expected(["integer"])
+            .suggest("use `nth:0` for the first match or `nth:-1` for the last")
+    })
+}
+
+/// `prefix:value` with the value quoted, e.g. `name:"Save (Ctrl+S)"`
+fn quote_value(atom: &str) -> String {
+    match atom.split_once(':') {
+        Some((prefix, value)) => {
+            let quoted = Selector::Name(value.to_string()).to_string();
+            format!("{prefix}:{}", &quoted["name:".len()..])
+        }
+        None => atom.to_string(),
+    }
+}
+
+/// The known prefix closest to `prefix`, if it is a plausible typo
+fn closest_prefix(prefix: &str) -> Option<&'static str> {
+    KNOWN_PREFIXES
+        .iter()
+        .map(|known| (edit_distance(prefix, known), *known))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, known)| known)
+}
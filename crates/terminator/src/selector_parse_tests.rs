@@ -0,0 +1,215 @@
+use super::selector_print_tests::selector;
+use super::{Selector, SelectorParseError, SelectorParseMode};
+use crate::AutomationError;
+use proptest::prelude::*;
+
+fn error(input: &str) -> SelectorParseError {
+    Selector::parse(input).expect_err(input)
+}
+
+fn strict_error(input: &str) -> SelectorParseError {
+    Selector::parse_strict(input).expect_err(input)
+}
+
+/// The input text covered by the error's span
+fn spanned<'a>(input: &'a str, error: &SelectorParseError) -> &'a str {
+    &input[error.span.clone()]
+}
+
+#[test]
+fn test_parse_matches_from_for_valid_selectors() {
+    for input in [
+        "role:Button && name:Submit",
+        "(role:Button || role:Link) && !visible:false",
+        "role:Window >> (role:Pane >> name:Inner) && nth:0",
+        "role:Button|name:Save",
+        "text:Hello (World) || !",
+        r#"name:"Save & Close (Ctrl+S)""#,
+        "attr:aria-label=a,b",
+        "has:(role:List >> name:Item) && rightof:name:Label",
+        "nth=-1",
+        "#main >> ..",
+        "button",
+        "Name:OK",
+        "custom:thing",
+    ] {
+        assert_eq!(
+            Selector::parse(input),
+            Ok(Selector::from(input)),
+            "input {input:?}"
+        );
+    }
+}
+
+#[test]
+fn test_reports_missing_operands() {
+    let input = "role:Button && && name:OK";
+    let e = error(input);
+    assert_eq!(e.span, 15..17);
+    assert_eq!(e.message, "expected a selector between `&&` and `&&`");
+    assert_eq!(e.expected, ["selector", "`(`", "`!`"]);
+
+    let input = "role:Button ||";
+    let e = error(input);
+    assert_eq!(e.span, 14..14);
+    assert_eq!(e.message, "expected a selector after `||`");
+
+    let e = error("&& name:OK");
+    assert_eq!(e.span, 0..2);
+    assert_eq!(e.suggestion.as_deref(), Some("remove the leading `&&`"));
+
+    let input = "role:Window >>  >> name:OK";
+    let e = error(input);
+    assert_eq!(e.span, 14..14);
+    assert_eq!(e.message, "expected a selector after `>>`");
+
+    assert_eq!(error("").message, "empty selector");
+    assert_eq!(
+        error("role:Button && ()").message,
+        "expected a selector between `(` and `)`"
+    );
+}
+
+#[test]
+fn test_reports_parentheses() {
+    let input = "(role:Button && name:OK";
+    let e = error(input);
+    assert_eq!(e.span, 0..1);
+    assert_eq!(e.message, "unclosed `(`");
+
+    // `Selector::from` reads this as the name `foo(bar`
+    let input = "name:foo(bar) && role:Button";
+    let e = error(input);
+    assert_eq!(spanned(input, &e), ")");
+    assert_eq!(
+        e.suggestion.as_deref(),
+        Some(r#"quote values containing parentheses: name:"foo(bar)""#)
+    );
+
+    let input = "has:(role:List && name:Item";
+    assert_eq!(error(input).span, 4..5);
+}
+
+#[test]
+fn test_reports_unquoted_syntax_in_values() {
+    let input = "name:Hello!";
+    let e = error(input);
+    assert_eq!(spanned(input, &e), "!");
+    assert_eq!(
+        e.suggestion.as_deref(),
+        Some(r#"quote values containing `!`: name:"Hello!""#)
+    );
+
+    let input = "name:Hello, World";
+    let e = error(input);
+    assert_eq!(spanned(input, &e), "World");
+    assert_eq!(
+        e.suggestion.as_deref(),
+        Some(r#"quote values containing commas: name:"Hello, World""#)
+    );
+
+    let input = r#"name:"Save" As && role:Button"#;
+    let e = error(input);
+    assert_eq!(spanned(input, &e), " As");
+    assert_eq!(e.message, "unexpected text after closing quote");
+
+    let input = r#"role:Button && name:"Unfinished"#;
+    let e = error(input);
+    assert_eq!(spanned(input, &e), r#""Unfinished"#);
+    assert_eq!(e.message, "unterminated quoted value");
+}
+
+#[test]
+fn test_reports_invalid_values_and_unknown_selectors() {
+    let input = "role:List >> nth:first";
+    let e = error(input);
+    assert_eq!(spanned(input, &e), "first");
+    assert_eq!(e.expected, ["integer"]);
+
+    let input = "filter:x";
+    assert_eq!(spanned(input, &error(input)), "x");
+
+    let input = "role:Button && Submit";
+    let e = error(input);
+    assert_eq!(spanned(input, &e), "Submit");
+    assert_eq!(
+        e.suggestion.as_deref(),
+        Some("add a prefix, e.g. `name:Submit`")
+    );
+
+    let e = error("role");
+    assert_eq!(
+        e.suggestion.as_deref(),
+        Some("add a value after the prefix: `role:...`")
+    );
+
+    let input = "has:";
+    let e = error(input);
+    assert_eq!(e.span, 4..4);
+}
+
+#[test]
+fn test_strict_mode_rejects_unknown_prefixes() {
+    let input = "role:Button && nmae:Submit";
+    assert!(Selector::parse(input).is_ok());
+    let e = strict_error(input);
+    assert_eq!(spanned(input, &e), "nmae:");
+    assert_eq!(e.suggestion.as_deref(), Some("did you mean `name:`?"));
+    assert!(e.expected.contains(&"name:".to_string()));
+
+    let e = strict_error("Button:OK");
+    assert_eq!(
+        e.suggestion.as_deref(),
+        Some("write `role:Button|name:OK` to match a `Button` element by name")
+    );
+
+    // Case-sensitive prefixes are reported rather than read as a role
+    assert_eq!(
+        strict_error("ID:main").suggestion.as_deref(),
+        Some("did you mean `id:`?")
+    );
+
+    assert!(Selector::parse("visible:yes").is_ok());
+    assert_eq!(strict_error("visible:yes").expected, ["true", "false"]);
+
+    assert!(Selector::parse_with("role:Button|name:OK", SelectorParseMode::Strict).is_ok());
+}
+
+#[test]
+fn test_render_and_conversion() {
+    let input = "role:Button && && name:OK";
+    assert_eq!(
+        error(input).render(input),
+        "role:Button && && name:OK\n               ^^ expected a selector between `&&` and `&&`\n  expected: selector, `(`, `!`"
+    );
+
+    // Columns count characters, not bytes
+    let input = "name:é)";
+    assert!(error(input)
+        .render(input)
+        .contains("\n      ^ unmatched `)`"));
+
+    let e = error("role:Button ||");
+    assert_eq!(e.to_string(), "expected a selector after `||` at 14..14");
+    assert!(matches!(
+        AutomationError::from(e),
+        AutomationError::InvalidSelector(message) if message.contains("14..14")
+    ));
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1000))]
+
+    #[test]
+    fn prop_printed_selectors_parse(selector in selector()) {
+        let printed = selector.to_string();
+        prop_assert_eq!(Selector::parse(&printed), Ok(selector), "printed as {:?}", printed);
+    }
+
+    #[test]
+    fn prop_parse_never_panics(input in "[a-z:|&!(),>\" .=-]{0,24}") {
+        if let Ok(selector) = Selector::parse(&input) {
+            prop_assert_eq!(selector, Selector::from(input.as_str()));
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use super::Selector;
+use proptest::prelude::*;
+use std::collections::BTreeMap;
+
+fn assert_round_trip(selector: &Selector) {
+    let printed = selector.to_string();
+    assert_eq!(
+        &Selector::from(printed.as_str()),
+        selector,
+        "printed as {printed:?}"
+    );
+}
+
+#[test]
+fn test_prints_plain_selectors() {
+    assert_eq!(
+        Selector::from("role:Button && name:Submit").to_string(),
+        "role:Button && name:Submit"
+    );
+    assert_eq!(
+        Selector::from("(role:Window && name:Best Plan Pro) >> nativeid:dob").to_string(),
+        "role:Window && name:Best Plan Pro >> nativeid:dob"
+    );
+    assert_eq!(
+        Selector::from("role:Button|name:Save").to_string(),
+        "role:Button|name:Save"
+    );
+    assert_eq!(
+        Selector::from("process:chrome").to_string(),
+        "process:chrome"
+    );
+    assert_eq!(Selector::from("nth=-1").to_string(), "nth:-1");
+    assert_eq!(Selector::from("#main").to_string(), "id:main");
+    assert_eq!(Selector::from("/a/b").to_string(), "/a/b");
+}
+
+#[test]
+fn test_quotes_values_containing_syntax() {
+    let selector = Selector::Name("Save & Close (Ctrl+S)".to_string());
+    assert_eq!(selector.to_string(), r#"name:"Save & Close (Ctrl+S)""#);
+    assert_round_trip(&selector);
+
+    let selector = Selector::Text(r#"say "hi" \o/"#.to_string());
+    assert_eq!(selector.to_string(), r#"text:"say \"hi\" \\o/""#);
+    assert_round_trip(&selector);
+
+    assert_round_trip(&Selector::Role {
+        role: "Button".to_string(),
+        name: Some("contains:OK".to_string()),
+    });
+    assert_round_trip(&Selector::NativeId(" padded ".to_string()));
+    assert_round_trip(&Selector::Name(String::new()));
+}
+
+#[test]
+fn test_parenthesizes_by_precedence() {
+    let selector = Selector::from("(role:Button || role:Link) && !(name:Cancel && visible:false)");
+    assert_eq!(
+        selector.to_string(),
+        "(role:Button || role:Link) && !(name:Cancel && visible:false)"
+    );
+
+    let selector = Selector::Has(Box::new(Selector::Chain(vec![
+        Selector::Role {
+            role: "List".to_string(),
+            name: None,
+        },
+        Selector::Name("Item".to_string()),
+    ])));
+    assert_eq!(selector.to_string(), "has:(role:List >> name:Item)");
+    assert_round_trip(&selector);
+
+    let selector = Selector::And(vec![
+        Selector::Chain(vec![
+            Selector::Process("notepad".to_string()),
+            Selector::Parent,
+        ]),
+        Selector::RightOf(Box::new(Selector::And(vec![
+            Selector::Name("A".to_string()),
+            Selector::Visible(true),
+        ]))),
+    ]);
+    assert_eq!(
+        selector.to_string(),
+        "(process:notepad >> ..) && rightof:(name:A && visible:true)"
+    );
+    assert_round_trip(&selector);
+}
+
+#[test]
+fn test_prints_attributes_filters_and_localized_roles() {
+    let attributes = BTreeMap::from([
+        ("aria-label".to_string(), "a;b".to_string()),
+        ("x=y".to_string(), "true".to_string()),
+    ]);
+    let selector = Selector::Attributes(attributes);
+    assert_eq!(selector.to_string(), r#"attr:aria-label="a;b";"x=y"=true"#);
+    assert_round_trip(&selector);
+
+    assert_round_trip(&Selector::Attributes(BTreeMap::new()));
+    assert_round_trip(&Selector::Filter(3));
+    assert_round_trip(&Selector::LocalizedRole("bouton".to_string()));
+    assert_round_trip(&Selector::Path("relative:path".to_string()));
+}
+
+fn value() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[a-zA-Z0-9 ]{0,10}",
+        "[a-zA-Z0-9 _.:/#&|,!()<>\"\\\\;=é名\t\n\u{a0}-]{0,10}",
+    ]
+}
+
+fn leaf() -> impl Strategy<Value = Selector> {
+    prop_oneof![
+        (value(), proptest::option::of(value()))
+            .prop_map(|(role, name)| Selector::Role { role, name }),
+        value().prop_map(Selector::Id),
+        value().prop_map(Selector::Name),
+        value().prop_map(Selector::Text),
+        value().prop_map(Selector::Path),
+        value().prop_map(Selector::NativeId),
+        proptest::collection::btree_map(value(), value(), 0..3).prop_map(Selector::Attributes),
+        any::<usize>().prop_map(Selector::Filter),
+        value().prop_map(Selector::ClassName),
+        any::<bool>().prop_map(Selector::Visible),
+        value().prop_map(Selector::LocalizedRole),
+        value().prop_map(Selector::Process),
+        any::<i32>().prop_map(Selector::Nth),
+        Just(Selector::Parent),
+    ]
+}
+
+/// Build an And/Or the way the parser does: nested operands of the same kind are flattened
+fn flatten(parts: Vec<Selector>, and: bool) -> Selector {
+    let mut flat = Vec::new();
+    for part in parts {
+        match part {
+            Selector::And(inner) if and => flat.extend(inner),
+            Selector::Or(inner) if !and => flat.extend(inner),
+            other => flat.push(other),
+        }
+    }
+    if and {
+        Selector::And(flat)
+    } else {
+        Selector::Or(flat)
+    }
+}
+
+pub(super) fn selector() -> impl Strategy<Value = Selector> {
+    leaf().prop_recursive(4, 32, 3, |inner| {
+        prop_oneof![
+            proptest::collection::vec(inner.clone(), 2..4).prop_map(Selector::Chain),
+            proptest::collection::vec(inner.clone(), 2..4).prop_map(|p| flatten(p, true)),
+            proptest::collection::vec(inner.clone(), 2..4).prop_map(|p| flatten(p, false)),
+            inner.clone().prop_map(|s| Selector::Not(Box::new(s))),
+            inner.clone().prop_map(|s| Selector::Has(Box::new(s))),
+            inner.clone().prop_map(|s| Selector::RightOf(Box::new(s))),
+            inner.clone().prop_map(|s| Selector::LeftOf(Box::new(s))),
+            inner.clone().prop_map(|s| Selector::Above(Box::new(s))),
+            inner.clone().prop_map(|s| Selector::Below(Box::new(s))),
+            inner.prop_map(|s| Selector::Near(Box::new(s))),
+        ]
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2000))]
+
+    #[test]
+    fn prop_print_parse_round_trip(selector in selector()) {
+        let printed = selector.to_string();
+        prop_assert_eq!(Selector::from(printed.as_str()), selector, "printed as {:?}", printed);
+    }
+
+    #[test]
+    fn prop_print_is_canonical(selector in selector()) {
+        let printed = selector.to_string();
+        prop_assert_eq!(Selector::from(printed.as_str()).to_string(), printed);
+    }
+}
@@ -0,0 +1,622 @@
+//! Tests for the in-memory mock engine (`--features mock`)
+//!
+//! These exercise `Desktop`, `Locator` and the selector engine end-to-end against
+//! the fixture in `fixtures/notepad.json`, so they run on any platform.
+
+use crate::platforms::mock::{MockDesktop, MockEngine, MockEvents};
+use crate::{
+    ActionabilityOptions, AutomationError, Desktop, LocatorFilter, Selector, TreeEvent,
+    UIElementAttributes, UINode, WaitCondition,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+const FIXTURE: &str = include_str!("fixtures/notepad.json");
+const SHORT: Option<Duration> = Some(Duration::from_millis(50));
+
+fn setup() -> (MockDesktop, Desktop) {
+    let fixture = MockDesktop::from_json(FIXTURE).expect("fixture should parse");
+    let desktop = Desktop::with_engine(Arc::new(MockEngine::new(fixture.clone())));
+    (fixture, desktop)
+}
+
+#[tokio::test]
+async fn test_locator_finds_elements_by_role_and_name() {
+    let (_, desktop) = setup();
+
+    let editor = desktop
+        .locator("process:notepad >> role:Document")
+        .first(SHORT)
+        .await
+        .unwrap();
+    assert_eq!(editor.id().as_deref(), Some("editor"));
+
+    // Name matching is a case-insensitive contains, like on Windows
+    let save = desktop
+        .locator("process:notepad >> role:button && name:save")
+        .first(SHORT)
+        .await
+        .unwrap();
+    assert_eq!(save.name().as_deref(), Some("Save"));
+
+    let buttons = desktop
+        .locator("process:CalculatorApp >> role:Button")
+        .all(SHORT, None)
+        .await
+        .unwrap();
+    assert_eq!(buttons.len(), 2);
+}
+
+#[tokio::test]
+async fn test_locator_times_out_when_missing() {
+    let (_, desktop) = setup();
+    let result = desktop
+        .locator("process:notepad >> role:Button && name:Print")
+        .first(SHORT)
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_find_elements_requires_process_scope() {
+    let (_, desktop) = setup();
+    let result = desktop.locator("role:Button").all(SHORT, None).await;
+    assert!(matches!(result, Err(AutomationError::InvalidSelector(_))));
+}
+
+#[tokio::test]
+async fn test_nth_and_spatial_selectors() {
+    let (_, desktop) = setup();
+
+    let second = desktop
+        .locator("process:CalculatorApp >> role:Button >> nth=1")
+        .first(SHORT)
+        .await
+        .unwrap();
+    assert_eq!(second.name().as_deref(), Some("Two"));
+
+    let right_of_one = desktop
+        .locator(Selector::Chain(vec![
+            Selector::Process("CalculatorApp".to_string()),
+            Selector::RightOf(Box::new(Selector::Name("One".to_string()))),
+        ]))
+        .first(SHORT)
+        .await
+        .unwrap();
+    assert_eq!(right_of_one.name().as_deref(), Some("Two"));
+}
+
+#[tokio::test]
+async fn test_type_text_and_set_value_update_state() {
+    let (fixture, desktop) = setup();
+    let editor = desktop
+        .locator("process:notepad >> role:Document")
+        .first(SHORT)
+        .await
+        .unwrap();
+
+    editor.type_text("hello", false).unwrap();
+    editor.type_text(" world", false).unwrap();
+    assert_eq!(editor.get_value().unwrap().as_deref(), Some("hello world"));
+    assert!(editor.is_focused().unwrap());
+
+    editor.set_value("replaced").unwrap();
+    assert_eq!(editor.get_value().unwrap().as_deref(), Some("replaced"));
+
+    let actions: Vec<String> = fixture.actions().into_iter().map(|a| a.action).collect();
+    assert!(actions.contains(&"type_text".to_string()));
+    assert_eq!(actions.last().map(String::as_str), Some("set_value"));
+}
+
+#[tokio::test]
+async fn test_click_toggles_and_selects() {
+    let (_, desktop) = setup();
+
+    let wrap = desktop
+        .locator("process:notepad >> role:CheckBox")
+        .first(SHORT)
+        .await
+        .unwrap();
+    assert!(!wrap.is_toggled().unwrap());
+    wrap.click().unwrap();
+    assert!(wrap.is_toggled().unwrap());
+    wrap.set_toggled(false).unwrap();
+    assert!(!wrap.is_toggled().unwrap());
+
+    let utf8 = desktop
+        .locator("process:notepad >> role:RadioButton && name:UTF-8")
+        .first(SHORT)
+        .await
+        .unwrap();
+    let ansi = desktop
+        .locator("process:notepad >> role:RadioButton && name:ANSI")
+        .first(SHORT)
+        .await
+        .unwrap();
+    assert!(utf8.is_selected().unwrap());
+    ansi.click().unwrap();
+    assert!(ansi.is_selected().unwrap());
+    assert!(!utf8.is_selected().unwrap());
+}
+
+#[tokio::test]
+async fn test_disabled_element_rejects_actions() {
+    let (_, desktop) = setup();
+    let save = desktop
+        .locator("process:notepad >> role:Button && name:Save")
+        .first(SHORT)
+        .await
+        .unwrap();
+    assert!(!save.is_enabled().unwrap());
+    assert!(matches!(
+        save.click(),
+        Err(AutomationError::ElementNotEnabled(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_focus_tracking() {
+    let (_, desktop) = setup();
+    let file = desktop
+        .locator("process:notepad >> role:MenuItem && name:File")
+        .first(SHORT)
+        .await
+        .unwrap();
+    file.focus().unwrap();
+    assert_eq!(
+        desktop.focused_element().unwrap().id().as_deref(),
+        Some("menu-file")
+    );
+
+    let edit = desktop
+        .locator("process:notepad >> role:MenuItem && name:Edit")
+        .first(SHORT)
+        .await
+        .unwrap();
+    edit.click().unwrap();
+    assert!(!file.is_focused().unwrap());
+    assert!(edit.is_focused().unwrap());
+}
+
+#[tokio::test]
+async fn test_element_scoped_locator_and_navigation() {
+    let (_, desktop) = setup();
+    let group = desktop
+        .locator("process:notepad >> role:Group")
+        .first(SHORT)
+        .await
+        .unwrap();
+
+    let radios = group
+        .locator(Selector::Role {
+            role: "RadioButton".to_string(),
+            name: None,
+        })
+        .unwrap()
+        .all(SHORT, None)
+        .await
+        .unwrap();
+    assert_eq!(radios.len(), 2);
+
+    let parent = radios[0].parent().unwrap().unwrap();
+    assert_eq!(parent, group);
+    assert_eq!(group.children().unwrap().len(), 2);
+    assert_eq!(
+        radios[0].window().unwrap().unwrap().id().as_deref(),
+        Some("notepad-window")
+    );
+}
+
+#[tokio::test]
+async fn test_tree_mutation_is_visible_to_waiting_locators() {
+    let (fixture, desktop) = setup();
+
+    let inserter = fixture.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(100));
+        inserter
+            .insert(
+                "notepad-window",
+                UINode {
+                    id: Some("dialog".to_string()),
+                    attributes: UIElementAttributes {
+                        role: "Window".to_string(),
+                        name: Some("Save As".to_string()),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+    });
+
+    let dialog = desktop
+        .locator("process:notepad >> role:Dialog && name:Save As")
+        .first(Some(Duration::from_secs(2)))
+        .await
+        .unwrap();
+    assert_eq!(dialog.id().as_deref(), Some("dialog"));
+
+    fixture.remove("dialog").unwrap();
+    assert!(matches!(
+        dialog.click(),
+        Err(AutomationError::ElementDetached(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_window_tree_snapshot_has_selectors() {
+    let (_, desktop) = setup();
+    let tree = desktop.get_window_tree(4242, None, None).unwrap();
+    assert_eq!(tree.id.as_deref(), Some("notepad-window"));
+
+    let editor = tree
+        .children
+        .iter()
+        .find(|c| c.attributes.role == "Document")
+        .unwrap();
+    assert_eq!(
+        editor.selector.as_deref(),
+        Some("role:Window && name:Untitled - Notepad >> role:Document && name:Text Editor")
+    );
+}
+
+#[tokio::test]
+async fn test_click_at_coordinates_hits_deepest_element() {
+    let (fixture, desktop) = setup();
+    desktop.click_at_coordinates(1040.0, 420.0, false).unwrap();
+    let last = fixture.actions().pop().unwrap();
+    assert_eq!(last.element_id, "num1");
+    assert_eq!(last.action, "click");
+}
+
+#[tokio::test]
+async fn test_filter_selectors_and_locator_predicates() {
+    let (_, desktop) = setup();
+
+    // Built-in filter from a selector string: Save is disabled in the fixture
+    let enabled = desktop
+        .locator("process:notepad >> role:Button && filter:enabled")
+        .all(SHORT, None)
+        .await;
+    assert!(enabled.unwrap_or_default().is_empty());
+
+    let radios = desktop
+        .locator("process:notepad >> role:RadioButton >> filter:enabled")
+        .all(SHORT, None)
+        .await
+        .unwrap();
+    assert_eq!(radios.len(), 2);
+
+    // Closure predicates receive live elements
+    let wide = desktop
+        .locator("process:notepad")
+        .locator("role:*")
+        .filter(|el| Ok(el.bounds()?.2 > 200.0))
+        .all(SHORT, None)
+        .await
+        .unwrap();
+    let ids: Vec<_> = wide.iter().filter_map(|el| el.id()).collect();
+    assert_eq!(ids, vec!["menu-bar", "editor", "encoding"]);
+
+    let first = desktop
+        .locator("process:notepad >> role:RadioButton")
+        .filter(|el| Ok(el.name().as_deref() == Some("ANSI")))
+        .first(SHORT)
+        .await
+        .unwrap();
+    assert_eq!(first.id().as_deref(), Some("ansi"));
+
+    // Named predicates are usable from selector strings
+    desktop
+        .register_filter("mockIsCheckBox", |el| Ok(el.role() == "CheckBox"))
+        .unwrap();
+    let checkbox = desktop
+        .locator("process:notepad >> role:* && filter:mockIsCheckBox")
+        .first(SHORT)
+        .await
+        .unwrap();
+    assert_eq!(checkbox.id().as_deref(), Some("word-wrap"));
+
+    // A filter step narrows the previous step's results rather than searching below them
+    let window = desktop
+        .locator("process:notepad >> filter:inViewport")
+        .first(SHORT)
+        .await
+        .unwrap();
+    assert_eq!(window.id().as_deref(), Some("notepad-window"));
+}
+
+#[tokio::test]
+async fn test_hit_element_must_be_target_or_inner_parent() {
+    use crate::actionability::is_related;
+
+    let (_, desktop) = setup();
+    let find = |id: &str| desktop.locator(format!("process:notepad >> #{id}").as_str());
+    let window = desktop
+        .locator("process:notepad")
+        .first(SHORT)
+        .await
+        .unwrap();
+    assert_eq!(window.id().as_deref(), Some("notepad-window"));
+    let menu_bar = find("menu-bar").first(SHORT).await.unwrap();
+    let menu_file = find("menu-file").first(SHORT).await.unwrap();
+
+    assert!(is_related(&menu_file, &menu_file));
+    assert!(is_related(&menu_file, &menu_bar));
+    // A container reporting the hit for its child
+    assert!(is_related(&menu_bar, &menu_file));
+    // Further ancestors, top-level windows and the root may be covering the target
+    assert!(!is_related(&window, &menu_file));
+    assert!(!is_related(&window, &menu_bar));
+    assert!(!is_related(&desktop.root(), &window));
+}
+
+#[tokio::test]
+async fn test_actionability_checks_before_actions() {
+    let (fixture, desktop) = setup();
+    let wrap = desktop
+        .locator("process:notepad >> role:CheckBox && name:Word wrap")
+        .first(SHORT)
+        .await
+        .unwrap();
+
+    // A modal dialog on top of the checkbox must not receive the click meant for it
+    fixture.add_application(
+        "notepad",
+        UINode {
+            id: Some("modal".to_string()),
+            attributes: UIElementAttributes {
+                role: "Window".to_string(),
+                name: Some("Unsaved changes".to_string()),
+                bounds: Some((100.0, 600.0, 400.0, 150.0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    let Err(err) = wrap.click() else {
+        panic!("click through a modal dialog should fail");
+    };
+    assert!(matches!(err, AutomationError::ElementObscured(_)), "{err}");
+    assert!(
+        err.to_string().contains("'Unsaved changes' Window"),
+        "{err}"
+    );
+    assert!(!wrap.is_toggled().unwrap());
+
+    // With a timeout the checks are retried until the dialog goes away
+    let closer = fixture.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(150));
+        closer.remove("modal").unwrap();
+    });
+    wrap.click_with_options(
+        &ActionabilityOptions::for_click().with_timeout(Duration::from_secs(2)),
+    )
+    .unwrap();
+    assert!(wrap.is_toggled().unwrap());
+
+    // Typing requires a text input; forcing skips the checks
+    assert!(matches!(
+        wrap.type_text("x", false),
+        Err(AutomationError::ElementNotEditable(_))
+    ));
+    wrap.type_text_with_options("x", false, true, true, false, &ActionabilityOptions::none())
+        .unwrap();
+
+    // Clicking an element whose bounds keep changing fails the stability check
+    let mover = fixture.clone();
+    let moving = Arc::new(std::sync::atomic::AtomicBool::new(true));
+    let still_moving = moving.clone();
+    let handle = std::thread::spawn(move || {
+        let mut x = 110.0;
+        while still_moving.load(std::sync::atomic::Ordering::Relaxed) {
+            x += 1.0;
+            mover
+                .update("word-wrap", |attrs| {
+                    attrs.bounds = Some((x, 660.0, 100.0, 20.0))
+                })
+                .unwrap();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    });
+    let result = wrap.click();
+    moving.store(false, std::sync::atomic::Ordering::Relaxed);
+    handle.join().unwrap();
+    assert!(matches!(result, Err(AutomationError::ElementNotStable(_))));
+}
+
+#[tokio::test]
+async fn test_locator_combinators_and_wait_conditions() {
+    let (fixture, desktop) = setup();
+    let radios = desktop.locator("process:notepad >> role:RadioButton");
+
+    assert_eq!(radios.count().await.unwrap(), 2);
+    assert_eq!(
+        desktop
+            .locator("process:notepad >> name:Nope")
+            .count()
+            .await
+            .unwrap(),
+        0
+    );
+    let id = |el: crate::UIElement| el.id();
+    assert_eq!(
+        id(radios.nth(0).first(SHORT).await.unwrap()).as_deref(),
+        Some("utf8")
+    );
+    assert_eq!(
+        id(radios.last().first(SHORT).await.unwrap()).as_deref(),
+        Some("ansi")
+    );
+
+    // filter_by maps onto text:, has: and !has: selectors
+    let ansi = radios.filter_by(LocatorFilter {
+        has_text: Some("ANSI".to_string()),
+        ..Default::default()
+    });
+    assert_eq!(ansi.count().await.unwrap(), 1);
+    let groups = desktop.locator("process:notepad >> role:Group");
+    let with_radios = groups.filter_by(LocatorFilter {
+        has: Some(Selector::Role {
+            role: "RadioButton".to_string(),
+            name: None,
+        }),
+        ..Default::default()
+    });
+    assert_eq!(
+        id(with_radios.first(SHORT).await.unwrap()).as_deref(),
+        Some("encoding")
+    );
+    let without_ansi = groups.filter_by(LocatorFilter {
+        has_not: Some(Selector::Name("ANSI".to_string())),
+        ..Default::default()
+    });
+    assert_eq!(without_ansi.count().await.unwrap(), 0);
+
+    // or/and combine whole locators
+    let checkbox = desktop.locator("process:notepad >> role:CheckBox");
+    assert_eq!(radios.or(&checkbox).count().await.unwrap(), 3);
+    let utf8 = radios.and(&desktop.locator("process:notepad >> name:UTF-8"));
+    assert_eq!(
+        id(utf8.first(SHORT).await.unwrap()).as_deref(),
+        Some("utf8")
+    );
+
+    // Value and text conditions poll until the state changes
+    let editor = desktop.locator("process:notepad >> role:Document");
+    let writer = fixture.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(100));
+        writer
+            .update("editor", |attrs| attrs.value = Some("hello".to_string()))
+            .unwrap();
+        writer
+            .update("ansi", |attrs| {
+                attrs.name = Some("Windows-1252".to_string())
+            })
+            .unwrap();
+    });
+    let timeout = Some(Duration::from_secs(2));
+    let found = editor
+        .wait_for(WaitCondition::ValueEquals("hello".to_string()), timeout)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.id().as_deref(), Some("editor"));
+    groups
+        .wait_for(
+            WaitCondition::TextContains("Windows-1252".to_string()),
+            timeout,
+        )
+        .await
+        .unwrap();
+
+    // Counting and detaching follow tree mutations
+    let remover = fixture.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(100));
+        remover.remove("utf8").unwrap();
+    });
+    radios.wait_for_count(1, timeout).await.unwrap();
+    let gone = desktop.locator("process:notepad >> name:UTF-8");
+    gone.wait_for_detached(SHORT).await.unwrap();
+    gone.wait_for_hidden(SHORT).await.unwrap();
+    assert!(gone
+        .wait_for(WaitCondition::Detached, SHORT)
+        .await
+        .unwrap()
+        .is_none());
+    // A hidden element that is still in the tree is returned
+    fixture
+        .update("ansi", |attrs| attrs.bounds = Some((0.0, 0.0, 0.0, 0.0)))
+        .unwrap();
+    let hidden = desktop
+        .locator("process:notepad >> name:Windows-1252")
+        .wait_for(WaitCondition::Hidden, SHORT)
+        .await
+        .unwrap();
+    assert_eq!(hidden.and_then(|el| el.id()).as_deref(), Some("ansi"));
+    assert!(matches!(
+        radios
+            .wait_for_detached(Some(Duration::from_millis(150)))
+            .await,
+        Err(AutomationError::Timeout(_))
+    ));
+    assert!(matches!(
+        radios
+            .wait_for_count(5, Some(Duration::from_millis(150)))
+            .await,
+        Err(AutomationError::Timeout(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_waits_wake_on_tree_events() {
+    let (fixture, desktop) = setup();
+    let save = desktop.locator("process:notepad >> role:Button && name:Save");
+    let timeout = Some(Duration::from_secs(5));
+
+    // With scripted events a silent change is only noticed once an event arrives
+    // (or on the safety-net re-check), not on a 100ms poll
+    fixture.set_events(MockEvents::Scripted);
+    let app = fixture.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        app.update("save", |attrs| attrs.enabled = Some(true))
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(250));
+        app.emit(TreeEvent::PropertyChanged);
+    });
+    let start = std::time::Instant::now();
+    save.wait_for(WaitCondition::Enabled, timeout)
+        .await
+        .unwrap();
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(250), "{elapsed:?}");
+    assert!(
+        elapsed < crate::events::EVENT_FALLBACK_INTERVAL,
+        "{elapsed:?}"
+    );
+    // The subscription is released when the wait ends
+    assert_eq!(fixture.subscriber_count(), 0);
+
+    // Mutations raise events automatically by default
+    fixture.set_events(MockEvents::Automatic);
+    let app = fixture.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        app.insert(
+            "notepad-window",
+            UINode {
+                id: Some("open".to_string()),
+                attributes: UIElementAttributes {
+                    role: "Button".to_string(),
+                    name: Some("Open".to_string()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    });
+    let start = std::time::Instant::now();
+    desktop
+        .locator("process:notepad >> name:Open")
+        .wait_for(WaitCondition::Exists, timeout)
+        .await
+        .unwrap();
+    assert!(start.elapsed() < Duration::from_millis(500));
+
+    // Without events the wait falls back to polling
+    fixture.set_events(MockEvents::Unsupported);
+    let app = fixture.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        app.remove("save").unwrap();
+    });
+    let start = std::time::Instant::now();
+    save.wait_for_detached(timeout).await.unwrap();
+    assert!(start.elapsed() < Duration::from_millis(500));
+}
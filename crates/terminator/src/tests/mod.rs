@@ -4,13 +4,16 @@ mod close_tab_tests;
 mod e2e_tests;
 mod firefox_window_tests;
 mod functional_verification_tests;
+#[cfg(all(test, target_os = "windows"))]
 mod get_applications_tests;
 #[cfg(test)]
 mod high_level_inputs_tests;
 #[cfg(all(test, target_os = "windows"))]
 mod id_stability_tests;
+#[cfg(all(test, feature = "mock"))]
+mod mock_engine_tests;
 mod notepad_selector_test;
-#[cfg(test)]
+#[cfg(all(test, target_os = "windows"))]
 mod parent_navigation_tests;
 #[cfg(test)]
 mod performance_tests;
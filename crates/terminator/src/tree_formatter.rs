@@ -6,10 +6,13 @@
 #![allow(clippy::type_complexity)]
 
 use crate::element::{SerializableUIElement, UIElementAttributes};
+use crate::errors::AutomationError;
 use crate::types::{OmniparserItem, VisionElement};
 use crate::OcrElement;
 use crate::UINode;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 
 /// Result of UI tree formatting - includes both the formatted string and bounds mapping
 #[derive(Debug, Clone)]
@@ -77,6 +80,55 @@ impl ElementSource {
         };
         Some((source, num))
     }
+
+    /// Rank used to pick the primary source of a fused element (lower is more reliable).
+    /// Accessibility and DOM bounds come from the application itself, OCR boxes are
+    /// pixel-accurate for text, and vision model boxes are approximate.
+    pub fn reliability_rank(&self) -> u8 {
+        match self {
+            ElementSource::Uia => 0,
+            ElementSource::Dom => 1,
+            ElementSource::Ocr => 2,
+            ElementSource::Omniparser => 3,
+            ElementSource::Gemini => 4,
+        }
+    }
+}
+
+/// Options for clustered tree output
+#[derive(Debug, Clone)]
+pub struct ClusterConfig {
+    /// Cluster radius as a multiple of the smaller element dimension
+    pub relative_radius: f64,
+    /// Fixed cluster radius in pixels, used instead of `relative_radius` when set
+    pub radius: Option<f64>,
+    /// Fuse elements from different sources that describe the same thing
+    pub fuse_sources: bool,
+    /// Minimum intersection-over-union for two boxes to be fused
+    pub fusion_iou: f64,
+    /// Minimum text similarity (0.0-1.0) for overlapping boxes to be fused below `fusion_iou`
+    pub fusion_text_similarity: f64,
+    /// Share of the smaller box that must lie inside the larger one to fuse on text below `fusion_iou`
+    pub fusion_containment: f64,
+    /// Largest area ratio between two boxes fused on text below `fusion_iou`
+    pub fusion_max_area_ratio: f64,
+    /// Vertical distance in pixels within which elements count as one line in reading order
+    pub line_tolerance: f64,
+}
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        Self {
+            relative_radius: 1.5,
+            radius: None,
+            fuse_sources: true,
+            fusion_iou: 0.5,
+            fusion_text_similarity: 0.8,
+            fusion_containment: 0.8,
+            fusion_max_area_ratio: 8.0,
+            line_tolerance: 8.0,
+        }
+    }
 }
 
 /// A unified element representation for clustering across all sources
@@ -88,6 +140,8 @@ pub struct UnifiedElement {
     pub text: Option<String>,         // name/text/content
     pub description: Option<String>,  // Gemini description, DOM identifier
     pub bounds: (f64, f64, f64, f64), // x, y, width, height
+    /// Other sources fused into this element as (source, index); `source` is the most reliable one
+    pub aliases: Vec<(ElementSource, u32)>,
 }
 
 impl UnifiedElement {
@@ -96,6 +150,14 @@ impl UnifiedElement {
         format!("{}{}", self.source.prefix(), self.index)
     }
 
+    /// Get the prefixed index strings of the fused aliases (e.g., "d1", "o3")
+    pub fn alias_indices(&self) -> Vec<String> {
+        self.aliases
+            .iter()
+            .map(|(source, index)| format!("{}{}", source.prefix(), index))
+            .collect()
+    }
+
     /// Get the center point of the element
     pub fn center(&self) -> (f64, f64) {
         let (x, y, w, h) = self.bounds;
@@ -108,7 +170,8 @@ impl UnifiedElement {
 pub struct ClusteredFormattingResult {
     /// The formatted YAML string with clusters
     pub formatted: String,
-    /// Mapping of prefixed index (e.g., "u1", "d2") to (source, original_index, bounds)
+    /// Mapping of prefixed index (e.g., "u1", "d2") to (source, original_index, bounds).
+    /// Aliases of a fused element map to its most reliable source.
     pub index_to_source_and_bounds: HashMap<String, (ElementSource, u32, (f64, f64, f64, f64))>,
 }
 
@@ -116,24 +179,7 @@ pub struct ClusteredFormattingResult {
 pub fn serializable_to_ui_node(element: &SerializableUIElement) -> UINode {
     UINode {
         id: element.id.clone(),
-        attributes: UIElementAttributes {
-            role: element.role.clone(),
-            name: element.name.clone(),
-            label: element.label.clone(),
-            text: element.text.clone(),
-            value: element.value.clone(),
-            description: element.description.clone(),
-            application_name: element.window_and_application_name.clone(),
-            properties: HashMap::new(),
-            is_keyboard_focusable: element.is_keyboard_focusable,
-            is_focused: element.is_focused,
-            is_toggled: element.is_toggled,
-            bounds: element.bounds,
-            enabled: element.enabled,
-            is_selected: element.is_selected,
-            child_count: element.child_count,
-            index_in_parent: element.index_in_parent,
-        },
+        attributes: serializable_attributes(element),
         children: element
             .children
             .as_ref()
@@ -143,6 +189,28 @@ pub fn serializable_to_ui_node(element: &SerializableUIElement) -> UINode {
     }
 }
 
+/// Attributes of a single SerializableUIElement (children are not converted)
+pub(crate) fn serializable_attributes(element: &SerializableUIElement) -> UIElementAttributes {
+    UIElementAttributes {
+        role: element.role.clone(),
+        name: element.name.clone(),
+        label: element.label.clone(),
+        text: element.text.clone(),
+        value: element.value.clone(),
+        description: element.description.clone(),
+        application_name: element.window_and_application_name.clone(),
+        properties: HashMap::new(),
+        is_keyboard_focusable: element.is_keyboard_focusable,
+        is_focused: element.is_focused,
+        is_toggled: element.is_toggled,
+        bounds: element.bounds,
+        enabled: element.enabled,
+        is_selected: element.is_selected,
+        child_count: element.child_count,
+        index_in_parent: element.index_in_parent,
+    }
+}
+
 /// Convert UINode to SerializableUIElement for unified formatting
 fn ui_node_to_serializable(node: &UINode) -> SerializableUIElement {
     SerializableUIElement {
@@ -218,24 +286,13 @@ fn format_node(
     index_to_bounds: &mut HashMap<u32, (String, String, (f64, f64, f64, f64), Option<String>)>,
     next_index: &mut u32,
 ) {
-    // Build the indent string
-    let indent_str = if indent > 0 {
-        "  ".repeat(indent)
-    } else {
-        String::new()
-    };
-
     // Add indent
-    output.push_str(&indent_str);
+    output.push_str(&"  ".repeat(indent));
 
-    // Add index first if element has bounds (clickable), otherwise dash prefix
-    if let Some((x, y, w, h)) = node.bounds {
+    // Elements with bounds (clickable) get the next index
+    let index = node.bounds.map(|(x, y, w, h)| {
         let idx = *next_index;
         *next_index += 1;
-        output.push_str(&format!("#{idx} "));
-
-        // Format: [ROLE]
-        output.push_str(&format!("[{}]", node.role));
 
         // Store in cache: index → (role, name, bounds, selector)
         let name = node.name.clone().unwrap_or_default();
@@ -243,15 +300,34 @@ fn format_node(
             idx,
             (node.role.clone(), name, (x, y, w, h), node.selector.clone()),
         );
-    } else {
-        // No bounds - use dash prefix and [ROLE]
-        output.push_str(&format!("- [{}]", node.role));
+        idx
+    });
+
+    output.push_str(&format_node_line(node, index));
+    output.push('\n');
+
+    // Recursively format children
+    if let Some(ref children) = node.children {
+        for child in children {
+            format_node(child, indent + 1, output, index_to_bounds, next_index);
+        }
+    }
+}
+
+/// Format a single element line (without indent or trailing newline)
+fn format_node_line(node: &SerializableUIElement, index: Option<u32>) -> String {
+    let mut line = String::new();
+
+    // Add index first if element has bounds (clickable), otherwise dash prefix
+    match index {
+        Some(idx) => line.push_str(&format!("#{idx} [{}]", node.role)),
+        None => line.push_str(&format!("- [{}]", node.role)),
     }
 
     // Add name if present
     if let Some(ref name) = node.name {
         if !name.is_empty() {
-            output.push_str(&format!(" {name}"));
+            line.push_str(&format!(" {name}"));
         }
     }
 
@@ -311,17 +387,10 @@ fn format_node(
 
     // Add context if any parts exist
     if !context_parts.is_empty() {
-        output.push_str(&format!(" ({})", context_parts.join(", ")));
+        line.push_str(&format!(" ({})", context_parts.join(", ")));
     }
 
-    output.push('\n');
-
-    // Recursively format children
-    if let Some(ref children) = node.children {
-        for child in children {
-            format_node(child, indent + 1, output, index_to_bounds, next_index);
-        }
-    }
+    line
 }
 
 /// Format an OCR tree as compact YAML with indexed words for click targeting
@@ -427,6 +496,496 @@ fn format_ocr_node(
     }
 }
 
+// ============================================================================
+// Budgeted Tree Output - Progressive disclosure for large trees
+// ============================================================================
+
+/// Approximate number of characters per token used to estimate output size
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Share of the character budget kept back for "… N more" summary lines (in percent)
+const SUMMARY_RESERVE_PERCENT: usize = 10;
+
+/// Minimum run of same-role siblings that gets collapsed under a budget
+const REPEAT_COLLAPSE_MIN: usize = 8;
+
+/// Number of siblings kept at the head of a collapsed run
+const REPEAT_KEEP: usize = 3;
+
+/// Roles that are prioritised when the tree does not fit the budget
+const INTERACTIVE_ROLES: &[&str] = &[
+    "button",
+    "checkbox",
+    "combobox",
+    "edit",
+    "hyperlink",
+    "link",
+    "listitem",
+    "menuitem",
+    "radiobutton",
+    "slider",
+    "spinner",
+    "splitbutton",
+    "tabitem",
+    "textbox",
+    "treeitem",
+];
+
+/// Limits for [`format_tree_with_budget`]
+#[derive(Debug, Clone, Default)]
+pub struct TreeBudget {
+    /// Approximate token limit for the formatted output (~4 characters per token)
+    pub max_tokens: Option<usize>,
+    /// Maximum number of element lines in the formatted output
+    pub max_elements: Option<usize>,
+    /// Handle of a collapsed group from a previous output (e.g. "@0.3:3-242~5f1c09ab").
+    /// When set, only that group is formatted, with the same indices it has in the full tree.
+    /// A handle whose group has changed since it was issued is rejected.
+    pub expand: Option<String>,
+}
+
+impl TreeBudget {
+    /// Whether any output limit is set
+    pub fn is_limited(&self) -> bool {
+        self.max_tokens.is_some() || self.max_elements.is_some()
+    }
+}
+
+/// A run of siblings left out of a budgeted tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollapsedGroup {
+    /// Handle that expands the group when passed as [`TreeBudget::expand`]
+    pub handle: String,
+    /// Number of hidden siblings
+    pub siblings: usize,
+    /// Number of hidden elements, including descendants of the siblings
+    pub elements: usize,
+    /// Role shared by all hidden siblings, if any
+    pub role: Option<String>,
+}
+
+/// Result of budgeted tree formatting
+#[derive(Debug, Clone)]
+pub struct BudgetedTreeResult {
+    /// The formatted YAML-like string
+    pub formatted: String,
+    /// Mapping of index to (role, name, bounds, selector) for the elements shown.
+    /// Indices match the unbudgeted output, so maps from several expansions can be merged.
+    pub index_to_bounds: HashMap<u32, (String, String, (f64, f64, f64, f64), Option<String>)>,
+    /// Number of indexed elements shown
+    pub element_count: u32,
+    /// Groups that were collapsed, in output order
+    pub collapsed: Vec<CollapsedGroup>,
+    /// Estimated token count of `formatted`
+    pub estimated_tokens: usize,
+}
+
+/// Flattened node used while selecting what fits the budget
+struct BudgetNode<'a> {
+    node: &'a SerializableUIElement,
+    path: Vec<usize>,
+    depth: usize,
+    index: Option<u32>,
+    /// Index the node or its first indexed descendant gets
+    first_index: u32,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    line: String,
+    subtree_size: usize,
+}
+
+/// Format a tree within a token and/or element budget
+///
+/// Interactive, visible and focused elements are kept first, and long runs of
+/// same-role siblings are cut to their first few entries. Everything left out is
+/// summarised in place with an expand handle:
+///
+/// ```text
+/// #4 [List] Inbox (bounds: [0,40,300,900])
+///   #5 [ListItem] Message 1 (bounds: [0,40,300,30])
+///   - … 240 more [ListItem] rows (expand: @0.2:3-242~5f1c09ab)
+/// ```
+///
+/// Passing a handle back through [`TreeBudget::expand`] formats only that group.
+/// The part after `~` fingerprints the group's elements and first index, so a
+/// handle is refused once the tree has changed around it.
+/// Indices always match what [`format_tree_as_compact_yaml`] would assign for the
+/// whole tree, so index-to-bounds maps stay consistent across expansions.
+pub fn format_tree_with_budget(
+    tree: &SerializableUIElement,
+    budget: &TreeBudget,
+) -> Result<BudgetedTreeResult, AutomationError> {
+    // Resolve the roots to format and the index each of them starts at
+    let mut nodes = Vec::new();
+    let mut roots = Vec::new();
+    match budget.expand.as_deref() {
+        Some(handle) => {
+            let (path, (first, last), fingerprint) = parse_tree_handle(handle)?;
+            let mut next_index = 1u32;
+            let mut node = tree;
+            for &position in &path {
+                let children = node.children.as_deref().unwrap_or(&[]);
+                let child = children.get(position).ok_or_else(|| {
+                    AutomationError::InvalidArgument(format!(
+                        "Tree handle '{handle}' does not match the current tree"
+                    ))
+                })?;
+                next_index += u32::from(node.bounds.is_some());
+                next_index += children[..position].iter().map(count_indexed).sum::<u32>();
+                node = child;
+            }
+            let children = node.children.as_deref().unwrap_or(&[]);
+            if first > last || last >= children.len() {
+                return Err(AutomationError::InvalidArgument(format!(
+                    "Tree handle '{handle}' does not match the current tree ({} children at that level)",
+                    children.len()
+                )));
+            }
+            next_index += u32::from(node.bounds.is_some());
+            next_index += children[..first].iter().map(count_indexed).sum::<u32>();
+            if group_fingerprint(next_index, &children[first..=last]) != fingerprint {
+                return Err(AutomationError::InvalidArgument(format!(
+                    "Tree handle '{handle}' is from an older version of the tree; get the tree again for current handles"
+                )));
+            }
+            for (position, child) in children.iter().enumerate().take(last + 1).skip(first) {
+                let mut child_path = path.clone();
+                child_path.push(position);
+                roots.push(flatten_budget_node(
+                    child,
+                    child_path,
+                    0,
+                    None,
+                    &mut next_index,
+                    &mut nodes,
+                ));
+            }
+        }
+        None => {
+            let mut next_index = 1u32;
+            roots.push(flatten_budget_node(
+                tree,
+                Vec::new(),
+                0,
+                None,
+                &mut next_index,
+                &mut nodes,
+            ));
+        }
+    }
+
+    let included = select_within_budget(&nodes, &roots, budget);
+
+    let mut output = String::new();
+    let mut index_to_bounds = HashMap::new();
+    let mut collapsed = Vec::new();
+    render_budget_siblings(
+        &nodes,
+        &roots,
+        &included,
+        &mut output,
+        &mut index_to_bounds,
+        &mut collapsed,
+    );
+
+    Ok(BudgetedTreeResult {
+        estimated_tokens: output.len().div_ceil(CHARS_PER_TOKEN),
+        element_count: index_to_bounds.len() as u32,
+        formatted: output,
+        index_to_bounds,
+        collapsed,
+    })
+}
+
+/// Format a UINode tree within a budget by converting to SerializableUIElement first
+pub fn format_ui_node_with_budget(
+    tree: &UINode,
+    budget: &TreeBudget,
+) -> Result<BudgetedTreeResult, AutomationError> {
+    format_tree_with_budget(&ui_node_to_serializable(tree), budget)
+}
+
+/// A tree handle: child path of the parent, sibling range and fingerprint
+type TreeHandle<'a> = (Vec<usize>, (usize, usize), &'a str);
+
+/// Parse a handle of the form "@<path>:<first>-<last>~<fingerprint>", where path is a
+/// dotted list of child positions from the root ("@:2-9~…" addresses the root's own children)
+fn parse_tree_handle(handle: &str) -> Result<TreeHandle<'_>, AutomationError> {
+    let invalid = || {
+        AutomationError::InvalidArgument(format!(
+            "Invalid tree handle '{handle}': expected '@<path>:<first>-<last>~<fingerprint>' as shown in the tree output"
+        ))
+    };
+    let rest = handle.trim().strip_prefix('@').ok_or_else(invalid)?;
+    let (rest, fingerprint) = rest.split_once('~').ok_or_else(invalid)?;
+    let (path, range) = rest.split_once(':').ok_or_else(invalid)?;
+    let path = if path.is_empty() {
+        Vec::new()
+    } else {
+        path.split('.')
+            .map(|p| p.parse::<usize>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    let (first, last) = range.split_once('-').ok_or_else(invalid)?;
+    let first = first.parse::<usize>().map_err(|_| invalid())?;
+    let last = last.parse::<usize>().map_err(|_| invalid())?;
+    Ok((path, (first, last), fingerprint))
+}
+
+/// Short hash of a sibling group's roles, names and child counts and of the
+/// index its first element gets
+fn group_fingerprint<'a>(
+    first_index: u32,
+    siblings: impl IntoIterator<Item = &'a SerializableUIElement>,
+) -> String {
+    let mut hasher = DefaultHasher::new();
+    first_index.hash(&mut hasher);
+    for sibling in siblings {
+        sibling.role.hash(&mut hasher);
+        sibling.name.hash(&mut hasher);
+        sibling
+            .children
+            .as_ref()
+            .map_or(0, Vec::len)
+            .hash(&mut hasher);
+    }
+    format!("{:08x}", hasher.finish() as u32)
+}
+
+/// Number of indexed (bounded) elements in a subtree
+fn count_indexed(node: &SerializableUIElement) -> u32 {
+    u32::from(node.bounds.is_some())
+        + node
+            .children
+            .as_deref()
+            .unwrap_or(&[])
+            .iter()
+            .map(count_indexed)
+            .sum::<u32>()
+}
+
+fn flatten_budget_node<'a>(
+    node: &'a SerializableUIElement,
+    path: Vec<usize>,
+    depth: usize,
+    parent: Option<usize>,
+    next_index: &mut u32,
+    nodes: &mut Vec<BudgetNode<'a>>,
+) -> usize {
+    let first_index = *next_index;
+    let index = node.bounds.map(|_| {
+        let idx = *next_index;
+        *next_index += 1;
+        idx
+    });
+    let id = nodes.len();
+    nodes.push(BudgetNode {
+        node,
+        first_index,
+        line: format_node_line(node, index),
+        path: path.clone(),
+        depth,
+        index,
+        parent,
+        children: Vec::new(),
+        subtree_size: 1,
+    });
+    if let Some(ref children) = node.children {
+        for (position, child) in children.iter().enumerate() {
+            let mut child_path = path.clone();
+            child_path.push(position);
+            let child_id =
+                flatten_budget_node(child, child_path, depth + 1, Some(id), next_index, nodes);
+            nodes[id].children.push(child_id);
+            nodes[id].subtree_size += nodes[child_id].subtree_size;
+        }
+    }
+    id
+}
+
+/// Priority of a node when the budget cannot fit everything (higher first)
+fn budget_priority(node: &SerializableUIElement) -> u32 {
+    let mut score = 0;
+    if node.is_focused == Some(true) {
+        score += 4;
+    }
+    let role = node.role.to_lowercase();
+    if node.is_keyboard_focusable == Some(true) || INTERACTIVE_ROLES.contains(&role.as_str()) {
+        score += 4;
+    }
+    if matches!(node.bounds, Some((_, _, w, h)) if w > 0.0 && h > 0.0) {
+        score += 2;
+    }
+    if node.name.as_deref().is_some_and(|n| !n.is_empty())
+        || node.text.as_deref().is_some_and(|t| !t.is_empty())
+    {
+        score += 1;
+    }
+    score
+}
+
+/// Decide which nodes are shown. A shown node always has all its ancestors shown.
+fn select_within_budget(nodes: &[BudgetNode], roots: &[usize], budget: &TreeBudget) -> Vec<bool> {
+    if !budget.is_limited() {
+        return vec![true; nodes.len()];
+    }
+
+    // Cut long runs of same-role siblings down to their first few entries. The roots
+    // of an expansion are exempt, since they are the group that was asked for.
+    let mut blocked = vec![false; nodes.len()];
+    for node in nodes {
+        let mut run_start = 0;
+        for i in 1..=node.children.len() {
+            let run_ended = i == node.children.len()
+                || nodes[node.children[i]].node.role != nodes[node.children[run_start]].node.role;
+            if run_ended {
+                if i - run_start >= REPEAT_COLLAPSE_MIN {
+                    for &child in &node.children[run_start + REPEAT_KEEP..i] {
+                        blocked[child] = true;
+                    }
+                }
+                run_start = i;
+            }
+        }
+    }
+    // Nodes are in pre-order, so parents are resolved before their children
+    for id in 0..nodes.len() {
+        if let Some(parent) = nodes[id].parent {
+            blocked[id] |= blocked[parent];
+        }
+    }
+
+    let char_limit = budget
+        .max_tokens
+        .map(|tokens| tokens * CHARS_PER_TOKEN * (100 - SUMMARY_RESERVE_PERCENT) / 100);
+    let element_limit = budget.max_elements;
+    let line_cost = |id: usize| nodes[id].depth * 2 + nodes[id].line.len() + 1;
+
+    let mut included = vec![false; nodes.len()];
+    let mut used_chars = 0;
+    let mut used_elements = 0;
+
+    // The first root is always shown so the output is never empty
+    if let Some(&first) = roots.first() {
+        included[first] = true;
+        used_chars += line_cost(first);
+        used_elements += 1;
+    }
+
+    let mut candidates: Vec<usize> = (0..nodes.len()).filter(|&id| !blocked[id]).collect();
+    candidates.sort_by_key(|&id| (std::cmp::Reverse(budget_priority(nodes[id].node)), id));
+
+    for id in candidates {
+        if included[id] {
+            continue;
+        }
+        // The node needs all of its hidden ancestors to be shown as well
+        let mut chain = vec![id];
+        let mut parent = nodes[id].parent;
+        while let Some(p) = parent {
+            if included[p] {
+                break;
+            }
+            chain.push(p);
+            parent = nodes[p].parent;
+        }
+        let cost: usize = chain.iter().map(|&c| line_cost(c)).sum();
+        if char_limit.is_some_and(|limit| used_chars + cost > limit)
+            || element_limit.is_some_and(|limit| used_elements + chain.len() > limit)
+        {
+            continue;
+        }
+        used_chars += cost;
+        used_elements += chain.len();
+        for c in chain {
+            included[c] = true;
+        }
+    }
+
+    included
+}
+
+fn render_budget_siblings(
+    nodes: &[BudgetNode],
+    siblings: &[usize],
+    included: &[bool],
+    output: &mut String,
+    index_to_bounds: &mut HashMap<u32, (String, String, (f64, f64, f64, f64), Option<String>)>,
+    collapsed: &mut Vec<CollapsedGroup>,
+) {
+    let mut i = 0;
+    while i < siblings.len() {
+        let id = siblings[i];
+        if included[id] {
+            let entry = &nodes[id];
+            output.push_str(&"  ".repeat(entry.depth));
+            output.push_str(&entry.line);
+            output.push('\n');
+            if let (Some(idx), Some(bounds)) = (entry.index, entry.node.bounds) {
+                index_to_bounds.insert(
+                    idx,
+                    (
+                        entry.node.role.clone(),
+                        entry.node.name.clone().unwrap_or_default(),
+                        bounds,
+                        entry.node.selector.clone(),
+                    ),
+                );
+            }
+            render_budget_siblings(
+                nodes,
+                &entry.children,
+                included,
+                output,
+                index_to_bounds,
+                collapsed,
+            );
+            i += 1;
+            continue;
+        }
+
+        // Summarise the run of hidden siblings starting here
+        let run_end = siblings[i..]
+            .iter()
+            .position(|&s| included[s])
+            .map_or(siblings.len(), |offset| i + offset);
+        let run = &siblings[i..run_end];
+        let first = &nodes[run[0]];
+        let last = &nodes[run[run.len() - 1]];
+        let parent_path = &first.path[..first.path.len() - 1];
+        let handle = format!(
+            "@{}:{}-{}~{}",
+            parent_path
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+                .join("."),
+            first.path[first.path.len() - 1],
+            last.path[last.path.len() - 1],
+            group_fingerprint(first.first_index, run.iter().map(|&s| nodes[s].node))
+        );
+        let role = first.node.role.clone();
+        let shared_role = run.iter().all(|&s| nodes[s].node.role == role);
+        let description = if shared_role {
+            format!("[{role}] rows")
+        } else {
+            "elements".to_string()
+        };
+        output.push_str(&"  ".repeat(first.depth));
+        output.push_str(&format!(
+            "- … {} more {description} (expand: {handle})\n",
+            run.len()
+        ));
+        collapsed.push(CollapsedGroup {
+            handle,
+            siblings: run.len(),
+            elements: run.iter().map(|&s| nodes[s].subtree_size).sum(),
+            role: shared_role.then_some(role),
+        });
+        i = run_end;
+    }
+}
+
 // ============================================================================
 // Clustering Functions
 // ============================================================================
@@ -448,16 +1007,150 @@ fn min_edge_distance(b1: (f64, f64, f64, f64), b2: (f64, f64, f64, f64)) -> f64
 }
 
 /// Determine if two elements should be clustered together
-/// Uses relative threshold based on smaller element dimension
-fn should_cluster(b1: (f64, f64, f64, f64), b2: (f64, f64, f64, f64)) -> bool {
-    let smaller_dim = f64::min(f64::min(b1.2, b1.3), f64::min(b2.2, b2.3));
-    // Threshold: 1.5x the smaller dimension
-    let threshold = smaller_dim * 1.5;
+/// Uses a threshold relative to the smaller element dimension unless a fixed radius is set
+fn should_cluster(
+    b1: (f64, f64, f64, f64),
+    b2: (f64, f64, f64, f64),
+    config: &ClusterConfig,
+) -> bool {
+    let threshold = config.radius.unwrap_or_else(|| {
+        let smaller_dim = f64::min(f64::min(b1.2, b1.3), f64::min(b2.2, b2.3));
+        smaller_dim * config.relative_radius
+    });
     min_edge_distance(b1, b2) < threshold
 }
 
+/// Intersection-over-union of two bounding boxes
+fn bounds_iou(b1: (f64, f64, f64, f64), b2: (f64, f64, f64, f64)) -> f64 {
+    let intersection = bounds_intersection(b1, b2);
+    let union = b1.2 * b1.3 + b2.2 * b2.3 - intersection;
+    if union <= 0.0 {
+        0.0
+    } else {
+        intersection / union
+    }
+}
+
+/// Area shared by two boxes
+fn bounds_intersection(b1: (f64, f64, f64, f64), b2: (f64, f64, f64, f64)) -> f64 {
+    let ix = f64::max(
+        0.0,
+        f64::min(b1.0 + b1.2, b2.0 + b2.2) - f64::max(b1.0, b2.0),
+    );
+    let iy = f64::max(
+        0.0,
+        f64::min(b1.1 + b1.3, b2.1 + b2.3) - f64::max(b1.1, b2.1),
+    );
+    ix * iy
+}
+
+/// Similarity of two texts in 0.0-1.0, ignoring case and whitespace differences
+fn text_similarity(a: &str, b: &str) -> f64 {
+    let normalize = |s: &str| {
+        s.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    };
+    let (a, b) = (normalize(a), normalize(b));
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 0.0;
+    }
+    1.0 - crate::utils::edit_distance(&a, &b) as f64 / longest as f64
+}
+
+/// Determine if two elements from different sources describe the same thing
+fn should_fuse(a: &UnifiedElement, b: &UnifiedElement, config: &ClusterConfig) -> Option<f64> {
+    let iou = bounds_iou(a.bounds, b.bounds);
+    if iou >= config.fusion_iou {
+        return Some(iou);
+    }
+    // Below the IoU threshold, only a box nested in one of similar size with
+    // the same text, like an OCR word inside its button
+    let (area_a, area_b) = (a.bounds.2 * a.bounds.3, b.bounds.2 * b.bounds.3);
+    let (smaller, larger) = (area_a.min(area_b), area_a.max(area_b));
+    if smaller <= 0.0 || larger / smaller > config.fusion_max_area_ratio {
+        return None;
+    }
+    if bounds_intersection(a.bounds, b.bounds) / smaller < config.fusion_containment {
+        return None;
+    }
+    let texts_match = match (a.text.as_deref(), b.text.as_deref()) {
+        (Some(ta), Some(tb)) => text_similarity(ta, tb) >= config.fusion_text_similarity,
+        _ => false,
+    };
+    texts_match.then_some(iou)
+}
+
+/// Fuse elements from different sources into single elements.
+/// The most reliable source becomes the primary; the others are kept as aliases.
+fn fuse_elements(mut elements: Vec<UnifiedElement>, config: &ClusterConfig) -> Vec<UnifiedElement> {
+    elements.sort_by_key(|e| (e.source.reliability_rank(), e.index));
+
+    let mut fused: Vec<UnifiedElement> = Vec::new();
+    for elem in elements {
+        // Each fused element holds at most one element per source
+        let best = fused
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| {
+                f.source != elem.source && !f.aliases.iter().any(|(s, _)| *s == elem.source)
+            })
+            .filter_map(|(i, f)| should_fuse(f, &elem, config).map(|iou| (i, iou)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i);
+
+        match best {
+            Some(i) => {
+                let target = &mut fused[i];
+                target.aliases.push((elem.source, elem.index));
+                if target.text.as_deref().is_none_or(str::is_empty) {
+                    target.text = elem.text;
+                }
+                if target.description.is_none() {
+                    target.description = elem.description;
+                }
+            }
+            None => fused.push(elem),
+        }
+    }
+    fused
+}
+
+/// Sort items in reading order: top to bottom by line, then left to right within a line
+fn sort_reading_order<T>(
+    items: &mut Vec<T>,
+    bounds: impl Fn(&T) -> (f64, f64, f64, f64),
+    line_tolerance: f64,
+) {
+    items.sort_by(|a, b| bounds(a).1.total_cmp(&bounds(b).1));
+
+    let mut keyed: Vec<(usize, f64, T)> = Vec::with_capacity(items.len());
+    let mut line = 0;
+    let mut line_top: Option<f64> = None;
+    for item in items.drain(..) {
+        let (x, y, _, _) = bounds(&item);
+        match line_top {
+            Some(top) if y - top <= line_tolerance => {}
+            Some(_) => {
+                line += 1;
+                line_top = Some(y);
+            }
+            None => line_top = Some(y),
+        }
+        keyed.push((line, x, item));
+    }
+
+    keyed.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    items.extend(keyed.into_iter().map(|(_, _, item)| item));
+}
+
 /// Cluster elements by spatial proximity using union-find approach
-fn cluster_elements(elements: Vec<UnifiedElement>) -> Vec<Vec<UnifiedElement>> {
+fn cluster_elements(
+    elements: Vec<UnifiedElement>,
+    config: &ClusterConfig,
+) -> Vec<Vec<UnifiedElement>> {
     if elements.is_empty() {
         return vec![];
     }
@@ -486,7 +1179,7 @@ fn cluster_elements(elements: Vec<UnifiedElement>) -> Vec<Vec<UnifiedElement>> {
     // Build clusters by checking all pairs
     for i in 0..n {
         for j in (i + 1)..n {
-            if should_cluster(elements[i].bounds, elements[j].bounds) {
+            if should_cluster(elements[i].bounds, elements[j].bounds, config) {
                 union(&mut parent, i, j);
             }
         }
@@ -505,61 +1198,62 @@ fn cluster_elements(elements: Vec<UnifiedElement>) -> Vec<Vec<UnifiedElement>> {
         .map(|indices| {
             let mut cluster: Vec<UnifiedElement> =
                 indices.into_iter().map(|i| elements[i].clone()).collect();
-            // Sort within cluster by reading order (Y then X)
-            cluster.sort_by(|a, b| {
-                let (_, ay, _, _) = a.bounds;
-                let (_, by, _, _) = b.bounds;
-                let (ax, _, _, _) = a.bounds;
-                let (bx, _, _, _) = b.bounds;
-                ay.partial_cmp(&by)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-                    .then(ax.partial_cmp(&bx).unwrap_or(std::cmp::Ordering::Equal))
-            });
+            sort_reading_order(&mut cluster, |e| e.bounds, config.line_tolerance);
             cluster
         })
         .collect();
 
     // Sort clusters by the position of their first element (reading order)
-    clusters.sort_by(|a, b| {
-        let a_first = a.first().map(|e| e.bounds).unwrap_or((0.0, 0.0, 0.0, 0.0));
-        let b_first = b.first().map(|e| e.bounds).unwrap_or((0.0, 0.0, 0.0, 0.0));
-        a_first
-            .1
-            .partial_cmp(&b_first.1)
-            .unwrap_or(std::cmp::Ordering::Equal)
-            .then(
-                a_first
-                    .0
-                    .partial_cmp(&b_first.0)
-                    .unwrap_or(std::cmp::Ordering::Equal),
-            )
-    });
+    sort_reading_order(
+        &mut clusters,
+        |c| c.first().map(|e| e.bounds).unwrap_or((0.0, 0.0, 0.0, 0.0)),
+        config.line_tolerance,
+    );
 
     clusters
 }
 
+/// Format clustered tree output from cached bounds data with the default [`ClusterConfig`]
+pub fn format_clustered_tree_from_caches(
+    uia_bounds: &HashMap<u32, (String, String, (f64, f64, f64, f64), Option<String>)>,
+    dom_bounds: &HashMap<u32, (String, String, (f64, f64, f64, f64))>,
+    ocr_bounds: &HashMap<u32, (String, (f64, f64, f64, f64))>,
+    omniparser_items: &HashMap<u32, OmniparserItem>,
+    vision_items: &HashMap<u32, VisionElement>,
+) -> ClusteredFormattingResult {
+    format_clustered_tree_with_config(
+        uia_bounds,
+        dom_bounds,
+        ocr_bounds,
+        omniparser_items,
+        vision_items,
+        &ClusterConfig::default(),
+    )
+}
+
 /// Format clustered tree output from cached bounds data
 ///
 /// This function takes cached bounds from each source and produces a clustered output.
-/// Elements are grouped by spatial proximity.
+/// Elements from different sources that overlap (by IoU, or by overlap plus similar text)
+/// are fused into one line led by the most reliable source, then grouped by spatial
+/// proximity and listed in reading order.
 ///
 /// Output format:
 /// ```text
 /// # Cluster @(100,200)
-/// - [Button] #u1 "Submit" (bounds: [100,200,80,30])
-/// - [button] #d1 "Submit" (bounds: [100,200,80,30])
-/// - [OcrWord] #o1 "Submit" (bounds: [102,205,76,25])
+/// - [Button] #u1 "Submit" (also: #d1, #o1) (bounds: [100,200,80,30])
 ///
 /// # Cluster @(100,280)
-/// - [Text] #u2 "Username"
+/// - [Text] #u2 "Username" (bounds: [100,280,80,20])
 /// - [input] #d2 (bounds: [100,300,200,30])
 /// ```
-pub fn format_clustered_tree_from_caches(
+pub fn format_clustered_tree_with_config(
     uia_bounds: &HashMap<u32, (String, String, (f64, f64, f64, f64), Option<String>)>,
     dom_bounds: &HashMap<u32, (String, String, (f64, f64, f64, f64))>,
     ocr_bounds: &HashMap<u32, (String, (f64, f64, f64, f64))>,
     omniparser_items: &HashMap<u32, OmniparserItem>,
     vision_items: &HashMap<u32, VisionElement>,
+    config: &ClusterConfig,
 ) -> ClusteredFormattingResult {
     let mut all_elements: Vec<UnifiedElement> = Vec::new();
 
@@ -576,6 +1270,7 @@ pub fn format_clustered_tree_from_caches(
             },
             description: None,
             bounds: *bounds,
+            aliases: Vec::new(),
         });
     }
 
@@ -592,6 +1287,7 @@ pub fn format_clustered_tree_from_caches(
             },
             description: None,
             bounds: *bounds,
+            aliases: Vec::new(),
         });
     }
 
@@ -604,6 +1300,7 @@ pub fn format_clustered_tree_from_caches(
             text: Some(text.clone()),
             description: None,
             bounds: *bounds,
+            aliases: Vec::new(),
         });
     }
 
@@ -623,6 +1320,7 @@ pub fn format_clustered_tree_from_caches(
                 text: item.content.clone(),
                 description: None,
                 bounds,
+                aliases: Vec::new(),
             });
         }
     }
@@ -643,22 +1341,30 @@ pub fn format_clustered_tree_from_caches(
                 text: item.content.clone(),
                 description: item.description.clone(),
                 bounds,
+                aliases: Vec::new(),
             });
         }
     }
 
-    // Build the index mapping before clustering
+    if config.fuse_sources {
+        all_elements = fuse_elements(all_elements, config);
+    }
+
+    // Build the index mapping before clustering; aliases resolve to the primary source
     let mut index_to_source_and_bounds: HashMap<
         String,
         (ElementSource, u32, (f64, f64, f64, f64)),
     > = HashMap::new();
     for elem in &all_elements {
-        let key = elem.prefixed_index();
-        index_to_source_and_bounds.insert(key, (elem.source, elem.index, elem.bounds));
+        let target = (elem.source, elem.index, elem.bounds);
+        index_to_source_and_bounds.insert(elem.prefixed_index(), target);
+        for alias in elem.alias_indices() {
+            index_to_source_and_bounds.insert(alias, target);
+        }
     }
 
     // Cluster the elements
-    let clusters = cluster_elements(all_elements);
+    let clusters = cluster_elements(all_elements, config);
 
     // Format output
     let mut output = String::new();
@@ -702,6 +1408,16 @@ pub fn format_clustered_tree_from_caches(
                 }
             }
 
+            // Add handles of fused elements from other sources
+            if !elem.aliases.is_empty() {
+                let aliases: Vec<String> = elem
+                    .alias_indices()
+                    .iter()
+                    .map(|a| format!("#{a}"))
+                    .collect();
+                output.push_str(&format!("(also: {}) ", aliases.join(", ")));
+            }
+
             // Add bounds
             let (x, y, w, h) = elem.bounds;
             output.push_str(&format!(
@@ -906,4 +1622,274 @@ mod tests {
             Some("role:Button && name:Click Me".to_string())
         );
     }
+
+    fn budget_element(
+        role: &str,
+        name: &str,
+        bounds: Option<(f64, f64, f64, f64)>,
+        children: Vec<SerializableUIElement>,
+    ) -> SerializableUIElement {
+        SerializableUIElement {
+            id: None,
+            role: role.to_string(),
+            name: Some(name.to_string()),
+            bounds,
+            value: None,
+            description: None,
+            window_and_application_name: None,
+            window_title: None,
+            url: None,
+            process_id: None,
+            process_name: None,
+            children: if children.is_empty() {
+                None
+            } else {
+                Some(children)
+            },
+            label: None,
+            text: None,
+            is_keyboard_focusable: None,
+            is_focused: None,
+            is_toggled: None,
+            enabled: None,
+            is_selected: None,
+            child_count: None,
+            index_in_parent: None,
+            selector: None,
+        }
+    }
+
+    fn inbox_tree(rows: usize) -> SerializableUIElement {
+        let items = (0..rows)
+            .map(|i| {
+                budget_element(
+                    "ListItem",
+                    &format!("Message {i}"),
+                    Some((0.0, 40.0 + i as f64 * 30.0, 300.0, 30.0)),
+                    vec![],
+                )
+            })
+            .collect();
+        budget_element(
+            "Window",
+            "Mail",
+            Some((0.0, 0.0, 800.0, 600.0)),
+            vec![
+                budget_element("Button", "Compose", Some((0.0, 0.0, 80.0, 30.0)), vec![]),
+                budget_element("List", "Inbox", Some((0.0, 40.0, 300.0, 500.0)), items),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_budget_collapses_repeated_siblings() {
+        let tree = inbox_tree(243);
+        let budget = TreeBudget {
+            max_elements: Some(50),
+            ..Default::default()
+        };
+        let result = format_tree_with_budget(&tree, &budget).unwrap();
+        let full = format_tree_as_compact_yaml(&tree, 0);
+
+        assert!(result
+            .formatted
+            .contains("- … 240 more [ListItem] rows (expand: @1:3-242~"));
+        assert_eq!(result.collapsed.len(), 1);
+        assert_eq!(result.collapsed[0].siblings, 240);
+        assert_eq!(result.collapsed[0].role.as_deref(), Some("ListItem"));
+        // Shown elements keep the indices of the full output
+        assert_eq!(result.element_count, 6);
+        for (idx, entry) in &result.index_to_bounds {
+            assert_eq!(full.index_to_bounds.get(idx), Some(entry));
+        }
+    }
+
+    #[test]
+    fn test_budget_expand_handle_keeps_indices() {
+        let tree = inbox_tree(243);
+        let full = format_tree_as_compact_yaml(&tree, 0);
+        let limit = TreeBudget {
+            max_elements: Some(50),
+            ..Default::default()
+        };
+        let handle = format_tree_with_budget(&tree, &limit).unwrap().collapsed[0]
+            .handle
+            .clone();
+        let budget = TreeBudget {
+            expand: Some(handle.clone()),
+            ..limit.clone()
+        };
+        let result = format_tree_with_budget(&tree, &budget).unwrap();
+
+        // The expanded group starts at the 4th row: Window, Compose, Inbox, 3 rows before it
+        assert!(result.formatted.starts_with("#7 [ListItem] Message 3"));
+        assert_eq!(result.element_count, 50);
+        for (idx, entry) in &result.index_to_bounds {
+            assert_eq!(full.index_to_bounds.get(idx), Some(entry));
+        }
+        // The rest of the group is collapsed again with a narrower handle
+        assert!(result.collapsed[0].handle.starts_with("@1:53-242~"));
+
+        let (_, fingerprint) = handle.split_once('~').unwrap();
+        for bad in [
+            format!("@1:3-999~{fingerprint}"),
+            "@1:3-242".to_string(),
+            "1.3".to_string(),
+        ] {
+            let bad = TreeBudget {
+                expand: Some(bad),
+                ..Default::default()
+            };
+            assert!(format_tree_with_budget(&tree, &bad).is_err());
+        }
+
+        // A handle from before a row changed no longer expands
+        let mut changed = inbox_tree(243);
+        changed.children.as_mut().unwrap()[1]
+            .children
+            .as_mut()
+            .unwrap()[10]
+            .name = Some("Message 10 (edited)".to_string());
+        let error = format_tree_with_budget(&changed, &budget).unwrap_err();
+        assert!(error.to_string().contains("older version"), "{error}");
+    }
+
+    #[test]
+    fn test_budget_prefers_interactive_elements() {
+        let mut panel_children: Vec<_> = (0..20)
+            .map(|i| budget_element("Text", &format!("Paragraph {i}"), None, vec![]))
+            .collect();
+        panel_children.push(budget_element(
+            "Button",
+            "Save",
+            Some((10.0, 10.0, 60.0, 20.0)),
+            vec![],
+        ));
+        let tree = budget_element("Window", "Editor", None, panel_children);
+
+        let budget = TreeBudget {
+            max_tokens: Some(18),
+            ..Default::default()
+        };
+        let result = format_tree_with_budget(&tree, &budget).unwrap();
+        assert!(result.formatted.contains("#1 [Button] Save"));
+        assert!(result
+            .formatted
+            .contains("- … 20 more [Text] rows (expand: @:0-19~"));
+        assert!(result.estimated_tokens <= 30);
+
+        // Without limits the output matches the plain formatter
+        let unlimited = format_tree_with_budget(&tree, &TreeBudget::default()).unwrap();
+        assert_eq!(
+            unlimited.formatted,
+            format_tree_as_compact_yaml(&tree, 0).formatted
+        );
+    }
+
+    #[test]
+    fn test_clustered_fuses_sources() {
+        let mut uia = HashMap::new();
+        uia.insert(
+            1,
+            (
+                "Button".to_string(),
+                "Submit".to_string(),
+                (100.0, 200.0, 80.0, 30.0),
+                None,
+            ),
+        );
+        let mut dom = HashMap::new();
+        dom.insert(
+            1,
+            (
+                "button".to_string(),
+                "Submit".to_string(),
+                (101.0, 201.0, 80.0, 30.0),
+            ),
+        );
+        let mut ocr = HashMap::new();
+        // Tight text box inside the button: low IoU, but same text
+        ocr.insert(1, ("Submit".to_string(), (120.0, 207.0, 40.0, 16.0)));
+        ocr.insert(2, ("Cancel".to_string(), (300.0, 207.0, 40.0, 16.0)));
+        // Same text but only grazing the button, or inside a far larger box
+        ocr.insert(3, ("Submit".to_string(), (170.0, 220.0, 40.0, 16.0)));
+        uia.insert(
+            2,
+            (
+                "Pane".to_string(),
+                "Cancel".to_string(),
+                (0.0, 0.0, 800.0, 600.0),
+                None,
+            ),
+        );
+
+        let result =
+            format_clustered_tree_from_caches(&uia, &dom, &ocr, &HashMap::new(), &HashMap::new());
+        assert!(result
+            .formatted
+            .contains("- [Button] #u1 \"Submit\" (also: #d1, #o1)"));
+        assert!(result.formatted.contains("#o2 \"Cancel\""));
+        assert!(result.formatted.contains("#o3 \"Submit\""));
+        // Aliases resolve to the most reliable source
+        let (source, index, bounds) = result.index_to_source_and_bounds["o1"];
+        assert_eq!(source, ElementSource::Uia);
+        assert_eq!(index, 1);
+        assert_eq!(bounds, (100.0, 200.0, 80.0, 30.0));
+
+        let unfused = format_clustered_tree_with_config(
+            &uia,
+            &dom,
+            &ocr,
+            &HashMap::new(),
+            &HashMap::new(),
+            &ClusterConfig {
+                fuse_sources: false,
+                ..Default::default()
+            },
+        );
+        assert!(unfused.formatted.contains("#d1 \"Submit\""));
+        assert_eq!(
+            unfused.index_to_source_and_bounds["o1"].0,
+            ElementSource::Ocr
+        );
+    }
+
+    #[test]
+    fn test_clustered_radius_and_reading_order() {
+        let mut ocr = HashMap::new();
+        // Same line with a slight vertical offset; the right word sits higher
+        ocr.insert(1, ("World".to_string(), (200.0, 100.0, 50.0, 20.0)));
+        ocr.insert(2, ("Hello".to_string(), (100.0, 104.0, 50.0, 20.0)));
+        ocr.insert(3, ("Footer".to_string(), (100.0, 400.0, 50.0, 20.0)));
+        let empty_uia = HashMap::new();
+        let empty_dom = HashMap::new();
+
+        let default = format_clustered_tree_from_caches(
+            &empty_uia,
+            &empty_dom,
+            &ocr,
+            &HashMap::new(),
+            &HashMap::new(),
+        );
+        // The 50px gap exceeds the default radius (1.5 x 20px), so each word is its own cluster
+        assert_eq!(default.formatted.matches("# Cluster").count(), 3);
+        let hello = default.formatted.find("Hello").unwrap();
+        let world = default.formatted.find("World").unwrap();
+        let footer = default.formatted.find("Footer").unwrap();
+        assert!(hello < world && world < footer);
+
+        let wide = format_clustered_tree_with_config(
+            &empty_uia,
+            &empty_dom,
+            &ocr,
+            &HashMap::new(),
+            &HashMap::new(),
+            &ClusterConfig {
+                radius: Some(60.0),
+                ..Default::default()
+            },
+        );
+        assert_eq!(wide.formatted.matches("# Cluster").count(), 2);
+        assert!(wide.formatted.find("Hello").unwrap() < wide.formatted.find("World").unwrap());
+    }
 }
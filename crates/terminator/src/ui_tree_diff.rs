@@ -1,6 +1,10 @@
+use crate::UINode;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use similar::{ChangeTag, TextDiff};
+use std::collections::HashMap;
+use std::fmt;
 
 /// Remove id and element_id fields from UI tree JSON
 /// Port of Python's remove_ids() function from sequential_processor.py
@@ -103,6 +107,340 @@ pub fn simple_ui_tree_diff(
     }
 }
 
+/// Boolean element state tracked by [`UiChange::StateChanged`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UiState {
+    Enabled,
+    Toggled,
+    Focused,
+}
+
+/// Identifies the node a [`UiChange`] refers to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UiNodeRef {
+    pub role: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub automation_id: Option<String>,
+    /// Path from the root, e.g. `Window[Untitled - Notepad] > Pane > Button[Save]`
+    pub path: String,
+}
+
+/// A typed change between two UI trees, as computed by [`semantic_ui_tree_diff`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum UiChange {
+    /// A node (and its whole subtree) appeared
+    Added { node: UiNodeRef },
+    /// A node (and its whole subtree) disappeared
+    Removed { node: UiNodeRef },
+    /// A node now lives under a different parent; `from`/`to` are the parent paths
+    Moved {
+        node: UiNodeRef,
+        from: String,
+        to: String,
+    },
+    Renamed {
+        node: UiNodeRef,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    ValueChanged {
+        node: UiNodeRef,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    StateChanged {
+        node: UiNodeRef,
+        state: UiState,
+        from: bool,
+        to: bool,
+    },
+}
+
+impl UiChange {
+    /// The node this change refers to (in the new tree, except for removals)
+    pub fn node(&self) -> &UiNodeRef {
+        match self {
+            UiChange::Added { node }
+            | UiChange::Removed { node }
+            | UiChange::Moved { node, .. }
+            | UiChange::Renamed { node, .. }
+            | UiChange::ValueChanged { node, .. }
+            | UiChange::StateChanged { node, .. } => node,
+        }
+    }
+}
+
+impl fmt::Display for UiChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let or_empty = |v: &Option<String>| format!("{:?}", v.as_deref().unwrap_or(""));
+        match self {
+            UiChange::Added { node } => write!(f, "added {}", node.path),
+            UiChange::Removed { node } => write!(f, "removed {}", node.path),
+            UiChange::Moved { node, from, to } => {
+                write!(f, "moved {} from {from} to {to}", node.path)
+            }
+            UiChange::Renamed { node, from, to } => write!(
+                f,
+                "renamed {} from {} to {}",
+                node.path,
+                or_empty(from),
+                or_empty(to)
+            ),
+            UiChange::ValueChanged { node, from, to } => write!(
+                f,
+                "value of {} changed from {} to {}",
+                node.path,
+                or_empty(from),
+                or_empty(to)
+            ),
+            UiChange::StateChanged {
+                node,
+                state,
+                from,
+                to,
+            } => write!(f, "{state:?} of {} changed from {from} to {to}", node.path),
+        }
+    }
+}
+
+/// A node of a tree flattened in document order
+struct FlatNode<'a> {
+    node: &'a UINode,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    path: String,
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().filter(|v| !v.is_empty())
+}
+
+fn automation_id(node: &UINode) -> Option<String> {
+    match node.attributes.properties.get("AutomationId") {
+        Some(Some(Value::String(id))) if !id.is_empty() => Some(id.clone()),
+        _ => None,
+    }
+}
+
+fn flatten(root: &UINode) -> Vec<FlatNode<'_>> {
+    fn visit<'a>(node: &'a UINode, parent: Option<usize>, out: &mut Vec<FlatNode<'a>>) {
+        let segment = match non_empty(&node.attributes.name) {
+            Some(name) => format!("{}[{name}]", node.attributes.role),
+            None => node.attributes.role.clone(),
+        };
+        let path = match parent {
+            Some(p) => format!("{} > {segment}", out[p].path),
+            None => segment,
+        };
+        let index = out.len();
+        out.push(FlatNode {
+            node,
+            parent,
+            children: Vec::new(),
+            path,
+        });
+        if let Some(p) = parent {
+            out[p].children.push(index);
+        }
+        for child in &node.children {
+            visit(child, Some(index), out);
+        }
+    }
+
+    let mut out = Vec::new();
+    visit(root, None, &mut out);
+    out
+}
+
+/// Key that identifies a node across snapshots: automation id when present, otherwise its name
+fn identity_key(node: &UINode) -> Option<(String, String)> {
+    let role = node.attributes.role.clone();
+    automation_id(node)
+        .map(|id| (role.clone(), format!("#{id}")))
+        .or_else(|| non_empty(&node.attributes.name).map(|name| (role, format!("@{name}"))))
+}
+
+fn node_ref(flat: &FlatNode) -> UiNodeRef {
+    UiNodeRef {
+        role: flat.node.attributes.role.clone(),
+        name: non_empty(&flat.node.attributes.name).map(str::to_string),
+        automation_id: automation_id(flat.node),
+        path: flat.path.clone(),
+    }
+}
+
+/// Match old nodes to new nodes, returning `old index -> new index`
+fn match_nodes(old: &[FlatNode], new: &[FlatNode]) -> HashMap<usize, usize> {
+    let mut old_to_new: HashMap<usize, usize> = HashMap::new();
+    let mut new_matched = vec![false; new.len()];
+
+    // Pass 1: nodes with a stable identity, preferring the candidate under the
+    // same (already matched) parent, then the same path, then document order
+    let mut candidates: HashMap<(String, String), Vec<usize>> = HashMap::new();
+    for (i, flat) in new.iter().enumerate() {
+        if let Some(key) = identity_key(flat.node) {
+            candidates.entry(key).or_default().push(i);
+        }
+    }
+    for (i, flat) in old.iter().enumerate() {
+        let Some(pool) = identity_key(flat.node).and_then(|key| candidates.get_mut(&key)) else {
+            continue;
+        };
+        let mapped_parent = flat.parent.and_then(|p| old_to_new.get(&p).copied());
+        let best = pool
+            .iter()
+            .position(|&n| mapped_parent.is_some() && new[n].parent == mapped_parent)
+            .or_else(|| pool.iter().position(|&n| new[n].path == flat.path))
+            .unwrap_or(0);
+        if best < pool.len() {
+            let n = pool.remove(best);
+            old_to_new.insert(i, n);
+            new_matched[n] = true;
+        }
+    }
+
+    // Pass 2: remaining nodes by role and position under matched parents, which
+    // pairs up unnamed nodes and detects renames
+    if !old.is_empty()
+        && !new.is_empty()
+        && !old_to_new.contains_key(&0)
+        && !new_matched[0]
+        && old[0].node.attributes.role == new[0].node.attributes.role
+    {
+        old_to_new.insert(0, 0);
+        new_matched[0] = true;
+    }
+    for (i, flat) in old.iter().enumerate() {
+        let Some(&n) = old_to_new.get(&i) else {
+            continue;
+        };
+        let mut unmatched_new: Vec<usize> = new[n]
+            .children
+            .iter()
+            .copied()
+            .filter(|&c| !new_matched[c])
+            .collect();
+        for &c in &flat.children {
+            if old_to_new.contains_key(&c) {
+                continue;
+            }
+            let old_child = old[c].node;
+            let old_id = automation_id(old_child);
+            let found = unmatched_new.iter().position(|&nc| {
+                let new_child = new[nc].node;
+                new_child.attributes.role == old_child.attributes.role
+                    && match (&old_id, automation_id(new_child)) {
+                        (Some(a), Some(b)) => *a == b,
+                        _ => true,
+                    }
+            });
+            if let Some(pos) = found {
+                let nc = unmatched_new.remove(pos);
+                old_to_new.insert(c, nc);
+                new_matched[nc] = true;
+            }
+        }
+    }
+
+    old_to_new
+}
+
+/// Compute a structural diff between two UI trees
+///
+/// Nodes are matched by role and automation id or name, then by role and
+/// position under an already matched parent. Ids and bounds are ignored.
+/// Added and removed subtrees are reported once, at their root.
+pub fn semantic_ui_tree_diff(old_tree: &UINode, new_tree: &UINode) -> Vec<UiChange> {
+    let old = flatten(old_tree);
+    let new = flatten(new_tree);
+    let old_to_new = match_nodes(&old, &new);
+    let mut new_to_old = vec![None; new.len()];
+    for (&o, &n) in &old_to_new {
+        new_to_old[n] = Some(o);
+    }
+
+    let mut changes = Vec::new();
+
+    for (i, flat) in old.iter().enumerate() {
+        let parent_kept = flat.parent.is_none_or(|p| old_to_new.contains_key(&p));
+        if !old_to_new.contains_key(&i) && parent_kept {
+            changes.push(UiChange::Removed {
+                node: node_ref(flat),
+            });
+        }
+    }
+
+    for (n, flat) in new.iter().enumerate() {
+        let Some(o) = new_to_old[n] else {
+            if flat.parent.is_none_or(|p| new_to_old[p].is_some()) {
+                changes.push(UiChange::Added {
+                    node: node_ref(flat),
+                });
+            }
+            continue;
+        };
+        let (before, after) = (&old[o].node.attributes, &flat.node.attributes);
+        let node = node_ref(flat);
+
+        let old_parent = old[o].parent;
+        let new_parent = flat.parent;
+        if old_parent.and_then(|p| old_to_new.get(&p).copied()) != new_parent {
+            changes.push(UiChange::Moved {
+                node: node.clone(),
+                from: old_parent.map(|p| old[p].path.clone()).unwrap_or_default(),
+                to: new_parent.map(|p| new[p].path.clone()).unwrap_or_default(),
+            });
+        }
+        if non_empty(&before.name) != non_empty(&after.name) {
+            changes.push(UiChange::Renamed {
+                node: node.clone(),
+                from: non_empty(&before.name).map(str::to_string),
+                to: non_empty(&after.name).map(str::to_string),
+            });
+        }
+        if non_empty(&before.value) != non_empty(&after.value) {
+            changes.push(UiChange::ValueChanged {
+                node: node.clone(),
+                from: non_empty(&before.value).map(str::to_string),
+                to: non_empty(&after.value).map(str::to_string),
+            });
+        }
+        let states = [
+            (
+                UiState::Enabled,
+                before.enabled.unwrap_or(true),
+                after.enabled.unwrap_or(true),
+            ),
+            (
+                UiState::Toggled,
+                before.is_toggled.unwrap_or(false),
+                after.is_toggled.unwrap_or(false),
+            ),
+            (
+                UiState::Focused,
+                before.is_focused.unwrap_or(false),
+                after.is_focused.unwrap_or(false),
+            ),
+        ];
+        for (state, from, to) in states {
+            if from != to {
+                changes.push(UiChange::StateChanged {
+                    node: node.clone(),
+                    state,
+                    from,
+                    to,
+                });
+            }
+        }
+    }
+
+    changes
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,4 +562,183 @@ mod tests {
         assert!(diff_text.contains("Click"));
         assert!(diff_text.contains("Submit"));
     }
+
+    fn node(role: &str, name: &str, children: Vec<UINode>) -> UINode {
+        UINode {
+            id: Some(format!("{role}-{name}")),
+            attributes: crate::UIElementAttributes {
+                role: role.to_string(),
+                name: Some(name.to_string()),
+                ..Default::default()
+            },
+            children,
+            selector: None,
+        }
+    }
+
+    fn notepad(children: Vec<UINode>) -> UINode {
+        node("Window", "Notepad", children)
+    }
+
+    #[test]
+    fn test_semantic_diff_ignores_ids_and_bounds() {
+        let before = notepad(vec![node("Button", "Save", vec![])]);
+        let mut after = before.clone();
+        after.id = Some("other".to_string());
+        after.children[0].attributes.bounds = Some((1.0, 2.0, 3.0, 4.0));
+
+        assert!(semantic_ui_tree_diff(&before, &after).is_empty());
+    }
+
+    #[test]
+    fn test_semantic_diff_rename_is_one_change() {
+        let before = notepad(vec![node(
+            "Pane",
+            "Toolbar",
+            vec![
+                node("Button", "Save", vec![]),
+                node("Button", "Open", vec![]),
+            ],
+        )]);
+        let mut after = before.clone();
+        after.children[0].children[0].attributes.name = Some("Save As".to_string());
+
+        let changes = semantic_ui_tree_diff(&before, &after);
+        assert_eq!(changes.len(), 1);
+        match &changes[0] {
+            UiChange::Renamed { node, from, to } => {
+                assert_eq!(
+                    node.path,
+                    "Window[Notepad] > Pane[Toolbar] > Button[Save As]"
+                );
+                assert_eq!(from.as_deref(), Some("Save"));
+                assert_eq!(to.as_deref(), Some("Save As"));
+            }
+            other => panic!("expected a rename, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_semantic_diff_moved_subtree_is_one_change() {
+        let menu = node("Menu", "File", vec![node("MenuItem", "Exit", vec![])]);
+        let before = notepad(vec![
+            node("Pane", "Left", vec![menu.clone()]),
+            node("Pane", "Right", vec![]),
+        ]);
+        let after = notepad(vec![
+            node("Pane", "Left", vec![]),
+            node("Pane", "Right", vec![menu]),
+        ]);
+
+        let changes = semantic_ui_tree_diff(&before, &after);
+        assert_eq!(
+            changes,
+            vec![UiChange::Moved {
+                node: UiNodeRef {
+                    role: "Menu".to_string(),
+                    name: Some("File".to_string()),
+                    automation_id: None,
+                    path: "Window[Notepad] > Pane[Right] > Menu[File]".to_string(),
+                },
+                from: "Window[Notepad] > Pane[Left]".to_string(),
+                to: "Window[Notepad] > Pane[Right]".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_semantic_diff_reports_subtree_roots_only() {
+        let dialog = node(
+            "Dialog",
+            "Save As",
+            vec![
+                node("Edit", "File name", vec![]),
+                node("Button", "Save", vec![]),
+            ],
+        );
+        let before = notepad(vec![node("Document", "Text", vec![])]);
+        let after = notepad(vec![node("Document", "Text", vec![]), dialog]);
+
+        let added = semantic_ui_tree_diff(&before, &after);
+        assert_eq!(added.len(), 1);
+        assert!(
+            matches!(&added[0], UiChange::Added { node } if node.path == "Window[Notepad] > Dialog[Save As]")
+        );
+
+        let removed = semantic_ui_tree_diff(&after, &before);
+        assert_eq!(removed.len(), 1);
+        assert!(matches!(&removed[0], UiChange::Removed { node } if node.role == "Dialog"));
+    }
+
+    #[test]
+    fn test_semantic_diff_matches_unnamed_nodes_by_position() {
+        let unnamed = |role: &str, value: &str| {
+            let mut n = node(role, "", vec![]);
+            n.attributes.value = Some(value.to_string());
+            n
+        };
+        let before = notepad(vec![unnamed("Edit", "a"), unnamed("Edit", "b")]);
+        let after = notepad(vec![
+            node("Text", "Hint", vec![]),
+            unnamed("Edit", "a"),
+            unnamed("Edit", "c"),
+        ]);
+
+        let changes = semantic_ui_tree_diff(&before, &after);
+        assert_eq!(changes.len(), 2, "{changes:?}");
+        assert!(matches!(&changes[0], UiChange::Added { node } if node.role == "Text"));
+        assert!(matches!(
+            &changes[1],
+            UiChange::ValueChanged { from, to, .. }
+                if from.as_deref() == Some("b") && to.as_deref() == Some("c")
+        ));
+    }
+
+    #[test]
+    fn test_semantic_diff_state_changes_serialize_to_json() {
+        let mut before = notepad(vec![node("CheckBox", "Word wrap", vec![])]);
+        before.children[0].attributes.enabled = Some(true);
+        let mut after = before.clone();
+        after.children[0].attributes.enabled = Some(false);
+        after.children[0].attributes.is_toggled = Some(true);
+
+        let changes = semantic_ui_tree_diff(&before, &after);
+        let json = serde_json::to_value(&changes).unwrap();
+        assert_eq!(json[0]["op"], "state_changed");
+        assert_eq!(json[0]["state"], "enabled");
+        assert_eq!(json[0]["from"], true);
+        assert_eq!(json[0]["to"], false);
+        assert_eq!(json[1]["state"], "toggled");
+        assert_eq!(
+            json[1]["node"]["path"],
+            "Window[Notepad] > CheckBox[Word wrap]"
+        );
+
+        let round_trip: Vec<UiChange> = serde_json::from_value(json).unwrap();
+        assert_eq!(round_trip, changes);
+        assert_eq!(
+            changes[1].to_string(),
+            "Toggled of Window[Notepad] > CheckBox[Word wrap] changed from false to true"
+        );
+    }
+
+    #[test]
+    fn test_semantic_diff_prefers_automation_id() {
+        let with_id = |name: &str, id: &str| {
+            let mut n = node("Edit", name, vec![]);
+            n.attributes
+                .properties
+                .insert("AutomationId".to_string(), Some(json!(id)));
+            n
+        };
+        let before = notepad(vec![with_id("Total", "total"), with_id("Tax", "tax")]);
+        let after = notepad(vec![with_id("Tax", "tax"), with_id("Grand total", "total")]);
+
+        let changes = semantic_ui_tree_diff(&before, &after);
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(
+            &changes[0],
+            UiChange::Renamed { node, .. } if node.automation_id.as_deref() == Some("total")
+        ));
+    }
 }
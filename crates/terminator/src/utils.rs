@@ -18,6 +18,22 @@ pub fn normalize(s: &str) -> String {
         .to_lowercase()
 }
 
+/// Levenshtein distance between `a` and `b`, in chars
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
 /// Find the PID for a process by name (case-insensitive substring match).
 ///
 /// This is the shared implementation used by both MCP agent and Node.js SDK.
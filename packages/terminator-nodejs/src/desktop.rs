@@ -8,6 +8,7 @@ use crate::{
 };
 use napi::bindgen_prelude::Either;
 use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Env, JsFunction};
 use napi_derive::napi;
 use std::sync::{Arc, Once};
 use terminator::Desktop as TerminatorDesktop;
@@ -1574,6 +1575,28 @@ impl Desktop {
         Ok(Locator::from(loc))
     }
 
+    /// Register a predicate usable as `filter:<name>` in selector strings, e.g.
+    /// `role:Button && filter:wide` after registering `wide`. It stays registered
+    /// for the lifetime of the process; registering the same name again replaces it.
+    /// The predicate must be synchronous. If it throws, the search using it rejects
+    /// with the thrown message.
+    ///
+    /// @param {string} name - The filter name; built-in names such as `enabled` are reserved.
+    /// @param {(element: Element) => boolean} predicate - Called with each candidate element.
+    #[napi(ts_args_type = "name: string, predicate: (element: Element) => boolean")]
+    pub fn register_filter(
+        &self,
+        env: Env,
+        name: String,
+        predicate: JsFunction,
+    ) -> napi::Result<()> {
+        let predicate = crate::locator::js_filter_predicate(&env, predicate)?;
+        self.inner
+            .register_filter(&name, predicate)
+            .map(|_| ())
+            .map_err(map_error)
+    }
+
     /// Create a process-scoped locator for finding UI elements.
     /// This is the recommended way to create locators - always scope to a specific process.
     ///
@@ -2,7 +2,8 @@ use napi::bindgen_prelude::FromNapiValue;
 use napi::{self};
 use napi_derive::napi;
 use terminator::{
-    UIElement as TerminatorUIElement, UIElementAttributes as TerminatorUIElementAttributes,
+    ActionabilityOptions, UIElement as TerminatorUIElement,
+    UIElementAttributes as TerminatorUIElementAttributes,
 };
 
 use crate::{
@@ -61,6 +62,9 @@ pub struct ActionOptions {
     /// Whether to restore the original focus and caret position after the action. Defaults to false.
     /// When true, saves the currently focused element and caret position before the action, then restores them after.
     pub restore_focus: Option<bool>,
+    /// Skip actionability checks (visible, enabled, stable, not covered by another element)
+    /// before click, doubleClick and rightClick. Defaults to false.
+    pub force: Option<bool>,
 }
 
 /// Options for typeText method
@@ -89,6 +93,17 @@ pub struct TypeTextOptions {
     pub ui_diff_before_after: Option<bool>,
     /// Max depth for tree capture when doing UI diff.
     pub ui_diff_max_depth: Option<u32>,
+    /// Skip actionability checks (visible, enabled, editable) before typing. Defaults to false.
+    pub force: Option<bool>,
+}
+
+/// Checks run before an action, or none when `force` is set
+fn actionability(force: Option<bool>, checks: ActionabilityOptions) -> ActionabilityOptions {
+    if force.unwrap_or(false) {
+        ActionabilityOptions::none()
+    } else {
+        checks
+    }
 }
 
 /// Result of screenshot capture for Element methods
@@ -295,6 +310,7 @@ impl Element {
 
         // Check if custom position is specified
         let use_position = opts.click_position.is_some();
+        let checks = actionability(opts.force, ActionabilityOptions::for_click());
         let (x_pct, y_pct) = opts
             .click_position
             .map(|p| (p.x_percentage, p.y_percentage))
@@ -324,7 +340,7 @@ impl Element {
                 desktop
                     .execute_on_element_with_ui_diff(
                         element_clone,
-                        |el| async move { el.click() },
+                        |el| async move { el.click_with_options(&checks) },
                         Some(diff_options),
                     )
                     .await
@@ -358,7 +374,7 @@ impl Element {
                     .click_at_position(x_pct, y_pct, click_type)
                     .map_err(map_error)?
             } else {
-                self.inner.click().map_err(map_error)?
+                self.inner.click_with_options(&checks).map_err(map_error)?
             };
             ClickResult {
                 method: click_res.method,
@@ -417,7 +433,10 @@ impl Element {
         let _ = self.inner.activate_window();
         let mut result: ClickResult = self
             .inner
-            .double_click()
+            .double_click_with_options(&actionability(
+                opts.force,
+                ActionabilityOptions::for_click(),
+            ))
             .map(ClickResult::from)
             .map_err(map_error)?;
 
@@ -463,7 +482,13 @@ impl Element {
             let _ = self.inner.highlight_before_action("right_click");
         }
         let _ = self.inner.activate_window();
-        let result = self.inner.right_click().map_err(map_error);
+        let result = self
+            .inner
+            .right_click_with_options(&actionability(
+                opts.force,
+                ActionabilityOptions::for_click(),
+            ))
+            .map_err(map_error);
 
         // Capture screenshots if requested
         let _screenshots = capture_element_screenshots(
@@ -596,12 +621,13 @@ impl Element {
         let try_click_before = opts.try_click_before.unwrap_or(true);
         // Pass restore_focus=false to platform layer since we handle it ourselves
         self.inner
-            .type_text_with_state_and_focus_restore(
+            .type_text_with_options(
                 &text,
                 opts.use_clipboard.unwrap_or(false),
                 try_focus_before,
                 try_click_before,
                 false, // We handle focus restore ourselves since we saved BEFORE activate_window
+                &actionability(opts.force, ActionabilityOptions::for_typing()),
             )
             .map_err(map_error)?;
 
@@ -51,6 +51,9 @@ pub fn map_error(err: AutomationError) -> napi::Error {
         AutomationError::ElementObscured(msg) => {
             napi::Error::new(Status::InvalidArg, format!("ELEMENT_OBSCURED: {msg}"))
         }
+        AutomationError::ElementNotEditable(msg) => {
+            napi::Error::new(Status::InvalidArg, format!("ELEMENT_NOT_EDITABLE: {msg}"))
+        }
         AutomationError::ScrollFailed(msg) => {
             napi::Error::new(Status::GenericFailure, format!("SCROLL_FAILED: {msg}"))
         }
@@ -1,11 +1,72 @@
 use napi_derive::napi;
 use terminator::locator::WaitCondition as TerminatorWaitCondition;
 use terminator::Locator as TerminatorLocator;
+use terminator::LocatorFilter;
 
 use crate::map_error;
 use crate::Element;
 use crate::Selector;
 use napi::bindgen_prelude::Either;
+use napi::threadsafe_function::{
+    ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+};
+use napi::{Env, JsFunction};
+use std::sync::Arc;
+use std::time::Duration;
+use terminator::AutomationError;
+
+/// How long a search waits for a JavaScript filter predicate to return
+const FILTER_CALLBACK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Wraps a filter predicate so it returns a boolean, or the message of
+/// whatever it threw
+const FILTER_GUARD_SCRIPT: &str = "(predicate) => (element) => { \
+    try { return !!predicate(element); } \
+    catch (error) { return String(error instanceof Error ? error.message : error); } }";
+
+/// Adapt a synchronous JavaScript predicate for use as a Rust filter, callable
+/// from the search threads. The predicate does not keep Node's event loop alive.
+pub(crate) fn js_filter_predicate(
+    env: &Env,
+    predicate: JsFunction,
+) -> napi::Result<
+    impl Fn(&terminator::UIElement) -> Result<bool, AutomationError> + Send + Sync + 'static,
+> {
+    // Call the predicate through a JS guard so a throw comes back as a
+    // message instead of an uncaught exception, and any return value is
+    // read by truthiness
+    let guard: JsFunction = env.run_script(FILTER_GUARD_SCRIPT)?;
+    let guarded: JsFunction = guard.call(None, &[predicate])?.try_into()?;
+    let mut predicate: ThreadsafeFunction<Element, ErrorStrategy::Fatal> = guarded
+        .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<Element>| Ok(vec![ctx.value]))?;
+    predicate.unref(env)?;
+    let predicate = Arc::new(predicate);
+    Ok(move |element: &terminator::UIElement| {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let status = predicate.call_with_return_value(
+            Element::from(element.clone()),
+            ThreadsafeFunctionCallMode::Blocking,
+            move |outcome: Either<bool, String>| {
+                let _ = tx.send(outcome);
+                Ok(())
+            },
+        );
+        if status != napi::Status::Ok {
+            return Err(AutomationError::PlatformError(format!(
+                "Failed to call filter predicate: {status}"
+            )));
+        }
+        match rx.recv_timeout(FILTER_CALLBACK_TIMEOUT) {
+            Ok(Either::A(keep)) => Ok(keep),
+            Ok(Either::B(message)) => Err(AutomationError::PlatformError(format!(
+                "Filter predicate threw: {message}"
+            ))),
+            Err(_) => Err(AutomationError::Timeout(
+                "Filter predicate did not return in time".to_string(),
+            )),
+        }
+    })
+}
 
 /// Locator for finding UI elements by selector.
 #[napi(js_name = "Locator")]
@@ -33,7 +94,6 @@ impl Locator {
     /// @returns {Promise<Element>} The first matching element.
     #[napi]
     pub async fn first(&self, timeout_ms: Option<f64>) -> napi::Result<Element> {
-        use std::time::Duration;
         let timeout = Duration::from_millis(timeout_ms.unwrap_or(10000.0) as u64);
         self.inner
             .first(Some(timeout))
@@ -49,7 +109,6 @@ impl Locator {
     /// @returns {Promise<Array<Element>>} List of matching elements.
     #[napi]
     pub async fn all(&self, timeout_ms: f64, depth: Option<u32>) -> napi::Result<Vec<Element>> {
-        use std::time::Duration;
         let timeout = Duration::from_millis(timeout_ms as u64);
         let depth = depth.map(|d| d as usize);
         self.inner
@@ -101,13 +160,99 @@ impl Locator {
         Ok(Locator::from(loc))
     }
 
+    /// Keep only the elements for which `predicate` returns a truthy value.
+    /// The predicate must be synchronous; it runs for every candidate while an
+    /// async method (`first`, `all`, `validate`, `waitFor`) is searching.
+    /// If it throws, that search rejects with the thrown message.
+    ///
+    /// @param {(element: Element) => boolean} predicate - Called with each candidate element.
+    /// @returns {Locator} A new locator that only matches accepted elements.
+    #[napi(ts_args_type = "predicate: (element: Element) => boolean")]
+    pub fn filter(&self, env: Env, predicate: JsFunction) -> napi::Result<Locator> {
+        let loc = self.inner.filter(js_filter_predicate(&env, predicate)?);
+        Ok(Locator::from(loc))
+    }
+
+    /// Narrow the matches by text content or descendants.
+    ///
+    /// @param {LocatorFilterOptions} options - `hasText`, `has` and/or `hasNot` (selector strings).
+    /// @returns {Locator} A new locator that only matches elements passing every given option.
+    #[napi]
+    pub fn filter_by(&self, options: LocatorFilterOptions) -> Locator {
+        let loc = self.inner.filter_by(LocatorFilter {
+            has_text: options.has_text,
+            has: options.has.as_deref().map(Into::into),
+            has_not: options.has_not.as_deref().map(Into::into),
+        });
+        Locator::from(loc)
+    }
+
+    /// Select the n-th match (0-based). Negative indices count from the end.
+    ///
+    /// @param {number} index - Index of the match.
+    /// @returns {Locator} A new locator matching a single element.
+    #[napi]
+    pub fn nth(&self, index: i32) -> Locator {
+        Locator::from(self.inner.nth(index))
+    }
+
+    /// Select the last match.
+    ///
+    /// @returns {Locator} A new locator matching the last element.
+    #[napi]
+    pub fn last(&self) -> Locator {
+        Locator::from(self.inner.last())
+    }
+
+    /// Match elements matched by this locator or by `other`.
+    ///
+    /// @param {Locator} other - The alternative locator.
+    /// @returns {Locator} A new locator matching either.
+    #[napi(js_name = "or")]
+    pub fn or_locator(&self, other: &Locator) -> Locator {
+        Locator::from(self.inner.or(&other.inner))
+    }
+
+    /// Match elements matched by both this locator and `other`.
+    ///
+    /// @param {Locator} other - The other locator.
+    /// @returns {Locator} A new locator matching both.
+    #[napi(js_name = "and")]
+    pub fn and_locator(&self, other: &Locator) -> Locator {
+        Locator::from(self.inner.and(&other.inner))
+    }
+
+    /// (async) Count the elements currently matching, without waiting.
+    ///
+    /// @returns {Promise<number>} Number of matching elements.
+    #[napi]
+    pub async fn count(&self) -> napi::Result<u32> {
+        self.inner
+            .count()
+            .await
+            .map(|count| count as u32)
+            .map_err(map_error)
+    }
+
+    /// (async) Wait until exactly `count` elements match.
+    ///
+    /// @param {number} count - Expected number of matches.
+    /// @param {number} timeoutMs - Timeout in milliseconds (required).
+    #[napi]
+    pub async fn wait_for_count(&self, count: u32, timeout_ms: f64) -> napi::Result<()> {
+        let timeout = Duration::from_millis(timeout_ms as u64);
+        self.inner
+            .wait_for_count(count as usize, Some(timeout))
+            .await
+            .map_err(map_error)
+    }
+
     /// (async) Validate element existence without throwing an error.
     ///
     /// @param {number} timeoutMs - Timeout in milliseconds (required).
     /// @returns {Promise<ValidationResult>} Validation result with exists flag and optional element.
     #[napi]
     pub async fn validate(&self, timeout_ms: f64) -> napi::Result<ValidationResult> {
-        use std::time::Duration;
         let timeout = Duration::from_millis(timeout_ms as u64);
         match self.inner.validate(Some(timeout)).await {
             Ok(Some(element)) => Ok(ValidationResult {
@@ -130,19 +275,50 @@ impl Locator {
 
     /// (async) Wait for an element to meet a specific condition.
     ///
-    /// @param {string} condition - Condition to wait for: 'exists', 'visible', 'enabled', 'focused'
+    /// @param {string} condition - Condition to wait for: 'exists', 'visible', 'enabled', 'focused',
+    ///   'stable', 'valueEquals', 'textContains', 'hidden', 'detached'
     /// @param {number} timeoutMs - Timeout in milliseconds (required).
-    /// @returns {Promise<Element>} The element when condition is met.
+    /// @param {string} [value] - Expected value for 'valueEquals' and 'textContains'.
+    /// @returns {Promise<Element | null>} The element when condition is met; null only for
+    ///   'hidden' and 'detached' once no matching element is left.
     #[napi]
-    pub async fn wait_for(&self, condition: String, timeout_ms: f64) -> napi::Result<Element> {
-        use std::time::Duration;
-        let wait_condition = parse_condition(&condition)?;
+    pub async fn wait_for(
+        &self,
+        condition: String,
+        timeout_ms: f64,
+        value: Option<String>,
+    ) -> napi::Result<Option<Element>> {
+        let wait_condition = parse_condition(&condition, value)?;
         let timeout = Duration::from_millis(timeout_ms as u64);
 
         self.inner
             .wait_for(wait_condition, Some(timeout))
             .await
-            .map(Element::from)
+            .map(|element| element.map(Element::from))
+            .map_err(map_error)
+    }
+
+    /// (async) Wait until no matching element is visible (it is hidden or gone).
+    ///
+    /// @param {number} timeoutMs - Timeout in milliseconds (required).
+    #[napi]
+    pub async fn wait_for_hidden(&self, timeout_ms: f64) -> napi::Result<()> {
+        let timeout = Duration::from_millis(timeout_ms as u64);
+        self.inner
+            .wait_for_hidden(Some(timeout))
+            .await
+            .map_err(map_error)
+    }
+
+    /// (async) Wait until no element matches.
+    ///
+    /// @param {number} timeoutMs - Timeout in milliseconds (required).
+    #[napi]
+    pub async fn wait_for_detached(&self, timeout_ms: f64) -> napi::Result<()> {
+        let timeout = Duration::from_millis(timeout_ms as u64);
+        self.inner
+            .wait_for_detached(Some(timeout))
+            .await
             .map_err(map_error)
     }
 }
@@ -157,16 +333,47 @@ pub struct ValidationResult {
     /// Error message if validation failed (not element not found, but actual error)
     pub error: Option<String>,
 }
+
+/// Options for `Locator.filterBy`
+#[napi(object)]
+pub struct LocatorFilterOptions {
+    /// Keep elements whose text, or a descendant's text, contains this string
+    pub has_text: Option<String>,
+    /// Keep elements with a descendant matching this selector
+    pub has: Option<String>,
+    /// Keep elements without a descendant matching this selector
+    pub has_not: Option<String>,
+}
+
 /// Convert string condition to WaitCondition enum
-fn parse_condition(condition: &str) -> napi::Result<TerminatorWaitCondition> {
+fn parse_condition(
+    condition: &str,
+    value: Option<String>,
+) -> napi::Result<TerminatorWaitCondition> {
+    let require_value = || {
+        value.clone().ok_or_else(|| {
+            napi::Error::new(
+                napi::Status::InvalidArg,
+                format!("Condition '{condition}' requires a value"),
+            )
+        })
+    };
     match condition.to_lowercase().as_str() {
         "exists" => Ok(TerminatorWaitCondition::Exists),
         "visible" => Ok(TerminatorWaitCondition::Visible),
         "enabled" => Ok(TerminatorWaitCondition::Enabled),
         "focused" => Ok(TerminatorWaitCondition::Focused),
+        "stable" => Ok(TerminatorWaitCondition::Stable),
+        "valueequals" => Ok(TerminatorWaitCondition::ValueEquals(require_value()?)),
+        "textcontains" => Ok(TerminatorWaitCondition::TextContains(require_value()?)),
+        "hidden" => Ok(TerminatorWaitCondition::Hidden),
+        "detached" => Ok(TerminatorWaitCondition::Detached),
         _ => Err(napi::Error::new(
             napi::Status::InvalidArg,
-            format!("Invalid condition '{condition}'. Valid: exists, visible, enabled, focused"),
+            format!(
+                "Invalid condition '{condition}'. Valid: exists, visible, enabled, focused, \
+                 stable, valueEquals, textContains, hidden, detached"
+            ),
         )),
     }
 }
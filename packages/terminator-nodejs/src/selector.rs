@@ -11,7 +11,7 @@ pub struct Selector {
 
 impl std::fmt::Display for Selector {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.inner)
+        write!(f, "{}", self.inner)
     }
 }
 
@@ -0,0 +1,169 @@
+const { spawnSync } = require("child_process");
+const path = require("path");
+const { Desktop } = require("../index.js");
+
+const INDEX = JSON.stringify(path.join(__dirname, "..", "index.js"));
+
+/**
+ * Run a script in a fresh Node process that must exit on its own
+ */
+function exitsOnItsOwn(body) {
+  const script = `
+    const { Desktop } = require(${INDEX});
+    (async () => { ${body} })().then(() => console.log("done"));
+  `;
+  const result = spawnSync(process.execPath, ["-e", script], {
+    encoding: "utf8",
+    timeout: 30000,
+  });
+  if (result.error && result.error.code === "ETIMEDOUT") {
+    throw new Error("Process did not exit after its work finished");
+  }
+  if (result.status !== 0 || !result.stdout.includes("done")) {
+    throw new Error(`Process failed: ${result.stderr}`);
+  }
+}
+
+/**
+ * Test that Locator.filter() keeps only accepted elements
+ */
+async function testFilter() {
+  console.log("🔍 Testing Locator.filter()...");
+
+  try {
+    const desktop = new Desktop();
+    const windows = await desktop.locator("role:window").all(5000);
+    if (windows.length === 0) {
+      throw new Error("No windows found for testing");
+    }
+    const name = windows[0].name();
+
+    const matches = await desktop
+      .locator("role:window")
+      .filter((element) => element.name() === name)
+      .all(5000);
+    if (matches.length === 0 || matches.some((el) => el.name() !== name)) {
+      throw new Error(`Expected only windows named '${name}'`);
+    }
+
+    try {
+      await desktop
+        .locator("role:window")
+        .filter(() => {
+          throw new Error("boom");
+        })
+        .first(2000);
+      throw new Error("Expected the throwing predicate to reject the search");
+    } catch (error) {
+      if (!error.message.includes("boom")) {
+        throw error;
+      }
+    }
+
+    console.log(`✅ Filtered to ${matches.length} window(s) named '${name}'`);
+    return true;
+  } catch (error) {
+    console.error("❌ Filter test failed:", error.message);
+    return false;
+  }
+}
+
+/**
+ * Test that Desktop.registerFilter() makes a named filter usable in selectors
+ */
+async function testRegisterFilter() {
+  console.log("🔍 Testing Desktop.registerFilter()...");
+
+  try {
+    const desktop = new Desktop();
+    desktop.registerFilter("nodeWideWindow", (element) => element.bounds().width > 100);
+
+    const element = await desktop
+      .locator("role:window && filter:nodeWideWindow")
+      .first(5000);
+    if (element.bounds().width <= 100) {
+      throw new Error("Named filter matched a narrow window");
+    }
+
+    try {
+      desktop.registerFilter("enabled", () => true);
+      throw new Error("Expected a built-in filter name to be rejected");
+    } catch (error) {
+      if (error.message.startsWith("Expected")) {
+        throw error;
+      }
+    }
+
+    console.log(`✅ Named filter matched: ${element.name()}`);
+    return true;
+  } catch (error) {
+    console.error("❌ registerFilter test failed:", error.message);
+    return false;
+  }
+}
+
+/**
+ * Test that filter predicates don't keep the process alive
+ */
+async function testFilterLetsProcessExit() {
+  console.log("🚪 Testing that filters don't keep Node running...");
+
+  try {
+    exitsOnItsOwn(`
+      const desktop = new Desktop();
+      await desktop.locator("role:window").filter(() => true).first(5000);
+    `);
+    exitsOnItsOwn(`
+      const desktop = new Desktop();
+      desktop.registerFilter("anyWindow", () => true);
+      await desktop.locator("role:window && filter:anyWindow").first(5000);
+    `);
+
+    console.log("✅ Process exited after using filters");
+    return true;
+  } catch (error) {
+    console.error("❌ Filter exit test failed:", error.message);
+    return false;
+  }
+}
+
+/**
+ * Main test runner
+ */
+async function runFilterTests() {
+  console.log("🚀 Starting locator filter tests...\n");
+
+  let passed = 0;
+  let total = 0;
+
+  for (const test of [testFilter, testRegisterFilter, testFilterLetsProcessExit]) {
+    total++;
+    if (await test()) {
+      passed++;
+    }
+    console.log(); // Empty line
+  }
+
+  if (passed === total) {
+    console.log(`🎉 All filter tests passed! (${passed}/${total})`);
+    process.exit(0);
+  } else {
+    console.log(`❌ Some tests failed: ${passed}/${total} passed`);
+    process.exit(1);
+  }
+}
+
+module.exports = {
+  testFilter,
+  testRegisterFilter,
+  testFilterLetsProcessExit,
+  runFilterTests,
+};
+
+// Run tests if this file is executed directly
+if (require.main === module) {
+  runFilterTests().catch((error) => {
+    console.error("💥 Test runner crashed:", error);
+    process.exit(1);
+  });
+}
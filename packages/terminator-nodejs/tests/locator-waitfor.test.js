@@ -122,6 +122,20 @@ async function testWaitForConditions() {
       console.log(`✅ Condition '${condition}' met`);
     }
 
+    // Nothing matches this name, so it is already hidden and detached
+    for (const condition of ["hidden", "detached"]) {
+      console.log(`Test: waitFor('${condition}')`);
+      const element = await desktop
+        .locator("role:window && name:terminator-missing-window")
+        .waitFor(condition, 2000);
+
+      if (element !== null) {
+        throw new Error(`Expected no element for condition '${condition}'`);
+      }
+
+      console.log(`✅ Condition '${condition}' met`);
+    }
+
     return true;
   } catch (error) {
     console.error("❌ WaitFor conditions test failed:", error.message);
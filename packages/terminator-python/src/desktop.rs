@@ -121,15 +121,32 @@ impl Desktop {
     /// Create a locator for finding UI elements.
     ///
     /// Args:
-    ///     selector (str): The selector string to find elements.
+    ///     selector (str | Selector): The selector string or Selector.
     ///
     /// Returns:
     ///     Locator: A locator for finding elements.
-    pub fn locator(&self, selector: &str) -> PyResult<Locator> {
-        let locator = self.inner.locator(selector);
+    pub fn locator(&self, selector: crate::selector::SelectorArg) -> PyResult<Locator> {
+        let locator = self
+            .inner
+            .locator(::terminator_core::selector::Selector::from(selector));
         Ok(Locator { inner: locator })
     }
 
+    #[pyo3(name = "register_filter", text_signature = "($self, name, predicate)")]
+    /// Register a predicate usable as `filter:<name>` in selector strings, e.g.
+    /// `role:Button && filter:wide` after registering `wide`. It stays registered
+    /// for the lifetime of the process; registering the same name again replaces it.
+    ///
+    /// Args:
+    ///     name (str): The filter name; built-in names such as `enabled` are reserved.
+    ///     predicate (Callable[[UIElement], object]): Called with each candidate element.
+    pub fn register_filter(&self, name: &str, predicate: PyObject) -> PyResult<()> {
+        self.inner
+            .register_filter(name, crate::locator::py_filter_predicate(predicate))
+            .map(|_| ())
+            .map_err(automation_error_to_pyerr)
+    }
+
     #[pyo3(name = "run_command", signature = (windows_command=None, unix_command=None))]
     #[pyo3(text_signature = "($self, windows_command, unix_command)")]
     /// (async) Run a shell command.
@@ -3,12 +3,22 @@ use crate::types::{
     Bounds, ClickResult, FontStyle, HighlightHandle, TextPosition, UIElementAttributes,
 };
 use ::terminator_core::element::UIElement as TerminatorUIElement;
+use ::terminator_core::ActionabilityOptions;
 use pyo3::prelude::*;
 use pyo3_async_runtimes::tokio as pyo3_tokio;
 use pyo3_async_runtimes::TaskLocals;
 use pyo3_stub_gen::derive::*;
 use serde::ser::{Serialize, SerializeStruct, Serializer};
 
+/// Checks run before an action, or none when `force` is set
+fn actionability(force: Option<bool>, checks: ActionabilityOptions) -> ActionabilityOptions {
+    if force.unwrap_or(false) {
+        ActionabilityOptions::none()
+    } else {
+        checks
+    }
+}
+
 /// Represents a UI element in the desktop UI tree.
 #[gen_stub_pyclass]
 #[pyclass(name = "UIElement")]
@@ -128,37 +138,51 @@ impl UIElement {
         })
     }
 
-    #[pyo3(name = "click", text_signature = "($self)")]
+    #[pyo3(name = "click", signature = (force=None))]
+    #[pyo3(text_signature = "($self, force)")]
     /// Click on this element.
     ///
+    /// Args:
+    ///     force (Optional[bool]): Skip the actionability checks (visible, enabled, stable, not covered).
+    ///
     /// Returns:
     ///     ClickResult: Result of the click operation.
-    pub fn click(&self) -> PyResult<ClickResult> {
+    pub fn click(&self, force: Option<bool>) -> PyResult<ClickResult> {
         self.inner
-            .click()
+            .click_with_options(&actionability(force, ActionabilityOptions::for_click()))
             .map(ClickResult::from)
             .map_err(automation_error_to_pyerr)
     }
 
-    #[pyo3(name = "double_click", text_signature = "($self)")]
+    #[pyo3(name = "double_click", signature = (force=None))]
+    #[pyo3(text_signature = "($self, force)")]
     /// Double click on this element.
     ///
+    /// Args:
+    ///     force (Optional[bool]): Skip the actionability checks (visible, enabled, stable, not covered).
+    ///
     /// Returns:
     ///     ClickResult: Result of the click operation.
-    pub fn double_click(&self) -> PyResult<ClickResult> {
+    pub fn double_click(&self, force: Option<bool>) -> PyResult<ClickResult> {
         self.inner
-            .double_click()
+            .double_click_with_options(&actionability(force, ActionabilityOptions::for_click()))
             .map(ClickResult::from)
             .map_err(automation_error_to_pyerr)
     }
 
-    #[pyo3(name = "right_click", text_signature = "($self)")]
+    #[pyo3(name = "right_click", signature = (force=None))]
+    #[pyo3(text_signature = "($self, force)")]
     /// Right click on this element.
     ///
+    /// Args:
+    ///     force (Optional[bool]): Skip the actionability checks (visible, enabled, stable, not covered).
+    ///
     /// Returns:
     ///     None
-    pub fn right_click(&self) -> PyResult<()> {
-        self.inner.right_click().map_err(automation_error_to_pyerr)
+    pub fn right_click(&self, force: Option<bool>) -> PyResult<()> {
+        self.inner
+            .right_click_with_options(&actionability(force, ActionabilityOptions::for_click()))
+            .map_err(automation_error_to_pyerr)
     }
 
     #[pyo3(name = "hover", text_signature = "($self)")]
@@ -212,19 +236,33 @@ impl UIElement {
             .map_err(automation_error_to_pyerr)
     }
 
-    #[pyo3(name = "type_text", signature = (text, use_clipboard=None))]
-    #[pyo3(text_signature = "($self, text, use_clipboard)")]
+    #[pyo3(name = "type_text", signature = (text, use_clipboard=None, force=None))]
+    #[pyo3(text_signature = "($self, text, use_clipboard, force)")]
     /// Type text into this element.
     ///
     /// Args:
     ///     text (str): The text to type.
     ///     use_clipboard (Optional[bool]): Whether to use clipboard for pasting.
+    ///     force (Optional[bool]): Skip the actionability checks (visible, enabled, editable).
     ///
     /// Returns:
     ///     None
-    pub fn type_text(&self, text: &str, use_clipboard: Option<bool>) -> PyResult<()> {
+    pub fn type_text(
+        &self,
+        text: &str,
+        use_clipboard: Option<bool>,
+        force: Option<bool>,
+    ) -> PyResult<()> {
         self.inner
-            .type_text(text, use_clipboard.unwrap_or(false))
+            .type_text_with_options(
+                text,
+                use_clipboard.unwrap_or(false),
+                true,
+                true,
+                false,
+                &actionability(force, ActionabilityOptions::for_typing()),
+            )
+            .map(|_| ())
             .map_err(automation_error_to_pyerr)
     }
 
@@ -425,14 +463,17 @@ impl UIElement {
     /// Create a locator from this element.
     ///
     /// Args:
-    ///     selector (str): The selector string.
+    ///     selector (str | Selector): The selector string or Selector.
     ///
     /// Returns:
     ///     Locator: A new locator for finding elements.
-    pub fn locator(&self, selector: &str) -> PyResult<crate::locator::Locator> {
+    pub fn locator(
+        &self,
+        selector: crate::selector::SelectorArg,
+    ) -> PyResult<crate::locator::Locator> {
         let locator = self
             .inner
-            .locator(selector)
+            .locator(::terminator_core::selector::Selector::from(selector))
             .map_err(automation_error_to_pyerr)?;
         Ok(crate::locator::Locator { inner: locator })
     }
@@ -59,6 +59,11 @@ create_exception!(
     ElementObscuredError,
     pyo3::exceptions::PyRuntimeError
 );
+create_exception!(
+    terminator,
+    ElementNotEditableError,
+    pyo3::exceptions::PyRuntimeError
+);
 create_exception!(
     terminator,
     ScrollFailedError,
@@ -91,6 +96,7 @@ pub fn automation_error_to_pyerr(e: AutomationError) -> pyo3::PyErr {
         AutomationError::ElementNotEnabled(_) => ElementNotEnabledError::new_err(msg),
         AutomationError::ElementNotStable(_) => ElementNotStableError::new_err(msg),
         AutomationError::ElementObscured(_) => ElementObscuredError::new_err(msg),
+        AutomationError::ElementNotEditable(_) => ElementNotEditableError::new_err(msg),
         AutomationError::ScrollFailed(_) => ScrollFailedError::new_err(msg),
         AutomationError::OperationCancelled(_) => OperationCancelledError::new_err(msg),
         AutomationError::VerificationFailed(_) => InternalError::new_err(msg),
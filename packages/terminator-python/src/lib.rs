@@ -8,19 +8,26 @@ mod desktop;
 mod element;
 mod exceptions;
 mod locator;
+mod selector;
 mod types;
+mod window_manager;
 
 use desktop::Desktop;
 use element::UIElement;
 use exceptions::*;
 use locator::Locator;
+use selector::Selector;
 use types::*;
+use window_manager::{WindowInfo, WindowManager};
 
 #[pymodule]
 fn terminator(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<Desktop>()?;
     m.add_class::<UIElement>()?;
     m.add_class::<Locator>()?;
+    m.add_class::<Selector>()?;
+    m.add_class::<WindowManager>()?;
+    m.add_class::<WindowInfo>()?;
     m.add_class::<ScreenshotResult>()?;
     m.add_class::<Monitor>()?;
     m.add_class::<ClickResult>()?;
@@ -1,11 +1,41 @@
 use crate::element::UIElement;
 use crate::exceptions::automation_error_to_pyerr;
-use ::terminator_core::locator::Locator as TerminatorLocator;
+use ::terminator_core::locator::{Locator as TerminatorLocator, LocatorFilter, WaitCondition};
+use ::terminator_core::selector::Selector as TerminatorSelector;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use pyo3_async_runtimes::tokio as pyo3_tokio;
 use pyo3_async_runtimes::TaskLocals;
 use pyo3_stub_gen::derive::*;
 
+/// Adapt a Python callable for use as a Rust filter: its result is read by
+/// truthiness, and an exception it raises fails the search
+pub(crate) fn py_filter_predicate(
+    predicate: PyObject,
+) -> impl Fn(&::terminator_core::UIElement) -> Result<bool, ::terminator_core::AutomationError>
+       + Send
+       + Sync
+       + 'static {
+    move |element| {
+        Python::with_gil(|py| {
+            predicate
+                .call1(
+                    py,
+                    (UIElement {
+                        inner: element.clone(),
+                    },),
+                )?
+                .bind(py)
+                .is_truthy()
+        })
+        .map_err(|e| {
+            ::terminator_core::AutomationError::PlatformError(format!(
+                "Filter predicate failed: {e}"
+            ))
+        })
+    }
+}
+
 /// Locator for finding UI elements by selector.
 #[gen_stub_pyclass]
 #[pyclass(name = "Locator")]
@@ -106,12 +136,14 @@ impl Locator {
     /// Chain another selector.
     ///
     /// Args:
-    ///     selector (str): The selector string.
+    ///     selector (str | Selector): The selector string or Selector.
     ///
     /// Returns:
     ///     Locator: A new locator with the chained selector.
-    pub fn locator(&self, selector: &str) -> PyResult<Locator> {
-        let locator = self.inner.locator(selector);
+    pub fn locator(&self, selector: crate::selector::SelectorArg) -> PyResult<Locator> {
+        let locator = self
+            .inner
+            .locator(::terminator_core::selector::Selector::from(selector));
         Ok(Locator { inner: locator })
     }
 
@@ -128,4 +160,227 @@ impl Locator {
             inner: self.inner.clone().within(element.inner.clone()),
         }
     }
+
+    #[pyo3(name = "filter", text_signature = "($self, predicate)")]
+    /// Keep only the elements for which `predicate` returns a truthy value.
+    /// The predicate runs for every candidate while the locator is searching.
+    ///
+    /// Args:
+    ///     predicate (Callable[[UIElement], object]): Called with each candidate element.
+    ///
+    /// Returns:
+    ///     Locator: A new locator that only matches accepted elements.
+    pub fn filter(&self, predicate: PyObject) -> Locator {
+        Locator {
+            inner: self.inner.filter(py_filter_predicate(predicate)),
+        }
+    }
+
+    #[pyo3(name = "filter_by", signature = (has_text=None, has=None, has_not=None))]
+    #[pyo3(text_signature = "($self, has_text, has, has_not)")]
+    /// Narrow the matches by text content or descendants.
+    ///
+    /// Args:
+    ///     has_text (Optional[str]): Keep elements whose text, or a descendant's text, contains this.
+    ///     has (Optional[str | Selector]): Keep elements with a descendant matching this selector.
+    ///     has_not (Optional[str | Selector]): Keep elements without such a descendant.
+    ///
+    /// Returns:
+    ///     Locator: A new locator that only matches elements passing every given option.
+    pub fn filter_by(
+        &self,
+        has_text: Option<String>,
+        has: Option<crate::selector::SelectorArg>,
+        has_not: Option<crate::selector::SelectorArg>,
+    ) -> Locator {
+        Locator {
+            inner: self.inner.filter_by(LocatorFilter {
+                has_text,
+                has: has.map(TerminatorSelector::from),
+                has_not: has_not.map(TerminatorSelector::from),
+            }),
+        }
+    }
+
+    #[pyo3(name = "nth", text_signature = "($self, index)")]
+    /// Select the n-th match (0-based). Negative indices count from the end.
+    ///
+    /// Args:
+    ///     index (int): Index of the match.
+    ///
+    /// Returns:
+    ///     Locator: A new locator matching a single element.
+    pub fn nth(&self, index: i32) -> Locator {
+        Locator {
+            inner: self.inner.nth(index),
+        }
+    }
+
+    #[pyo3(name = "last", text_signature = "($self)")]
+    /// Select the last match.
+    ///
+    /// Returns:
+    ///     Locator: A new locator matching the last element.
+    pub fn last(&self) -> Locator {
+        Locator {
+            inner: self.inner.last(),
+        }
+    }
+
+    #[pyo3(name = "or_", text_signature = "($self, other)")]
+    /// Match elements matched by this locator or by `other`.
+    ///
+    /// Args:
+    ///     other (Locator): The alternative locator.
+    ///
+    /// Returns:
+    ///     Locator: A new locator matching either.
+    pub fn or_(&self, other: &Locator) -> Locator {
+        Locator {
+            inner: self.inner.or(&other.inner),
+        }
+    }
+
+    #[pyo3(name = "and_", text_signature = "($self, other)")]
+    /// Match elements matched by both this locator and `other`.
+    ///
+    /// Args:
+    ///     other (Locator): The other locator.
+    ///
+    /// Returns:
+    ///     Locator: A new locator matching both.
+    pub fn and_(&self, other: &Locator) -> Locator {
+        Locator {
+            inner: self.inner.and(&other.inner),
+        }
+    }
+
+    #[pyo3(name = "count", text_signature = "($self)")]
+    /// (async) Count the elements currently matching, without waiting.
+    ///
+    /// Returns:
+    ///     int: Number of matching elements.
+    pub fn count<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let locator = self.inner.clone();
+        pyo3_tokio::future_into_py_with_locals(py, TaskLocals::with_running_loop(py)?, async move {
+            locator.count().await.map_err(automation_error_to_pyerr)
+        })
+    }
+
+    #[pyo3(name = "wait_for", signature = (condition, timeout_ms=None, value=None))]
+    #[pyo3(text_signature = "($self, condition, timeout_ms, value)")]
+    /// (async) Wait for the locator to meet a condition.
+    ///
+    /// Args:
+    ///     condition (str): One of 'exists', 'visible', 'enabled', 'focused', 'stable',
+    ///         'value_equals', 'text_contains', 'hidden', 'detached'.
+    ///     timeout_ms (Optional[int]): Timeout in milliseconds.
+    ///     value (Optional[str]): Expected value for 'value_equals' and 'text_contains'.
+    ///
+    /// Returns:
+    ///     Optional[UIElement]: The element when the condition is met; None only for
+    ///         'hidden' and 'detached' once no matching element is left.
+    pub fn wait_for<'py>(
+        &self,
+        py: Python<'py>,
+        condition: &str,
+        timeout_ms: Option<u64>,
+        value: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let condition = parse_condition(condition, value)?;
+        let locator = self.inner.clone();
+        pyo3_tokio::future_into_py_with_locals(py, TaskLocals::with_running_loop(py)?, async move {
+            let element = locator
+                .wait_for(condition, timeout_ms.map(std::time::Duration::from_millis))
+                .await
+                .map_err(automation_error_to_pyerr)?;
+            Ok(element.map(|inner| UIElement { inner }))
+        })
+    }
+
+    #[pyo3(name = "wait_for_hidden", signature = (timeout_ms=None))]
+    #[pyo3(text_signature = "($self, timeout_ms)")]
+    /// (async) Wait until no matching element is visible (it is hidden or gone).
+    ///
+    /// Args:
+    ///     timeout_ms (Optional[int]): Timeout in milliseconds.
+    pub fn wait_for_hidden<'py>(
+        &self,
+        py: Python<'py>,
+        timeout_ms: Option<u64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let locator = self.inner.clone();
+        pyo3_tokio::future_into_py_with_locals(py, TaskLocals::with_running_loop(py)?, async move {
+            locator
+                .wait_for_hidden(timeout_ms.map(std::time::Duration::from_millis))
+                .await
+                .map_err(automation_error_to_pyerr)
+        })
+    }
+
+    #[pyo3(name = "wait_for_detached", signature = (timeout_ms=None))]
+    #[pyo3(text_signature = "($self, timeout_ms)")]
+    /// (async) Wait until no element matches.
+    ///
+    /// Args:
+    ///     timeout_ms (Optional[int]): Timeout in milliseconds.
+    pub fn wait_for_detached<'py>(
+        &self,
+        py: Python<'py>,
+        timeout_ms: Option<u64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let locator = self.inner.clone();
+        pyo3_tokio::future_into_py_with_locals(py, TaskLocals::with_running_loop(py)?, async move {
+            locator
+                .wait_for_detached(timeout_ms.map(std::time::Duration::from_millis))
+                .await
+                .map_err(automation_error_to_pyerr)
+        })
+    }
+
+    #[pyo3(name = "wait_for_count", signature = (count, timeout_ms=None))]
+    #[pyo3(text_signature = "($self, count, timeout_ms)")]
+    /// (async) Wait until exactly `count` elements match.
+    ///
+    /// Args:
+    ///     count (int): Expected number of matches.
+    ///     timeout_ms (Optional[int]): Timeout in milliseconds.
+    pub fn wait_for_count<'py>(
+        &self,
+        py: Python<'py>,
+        count: usize,
+        timeout_ms: Option<u64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let locator = self.inner.clone();
+        pyo3_tokio::future_into_py_with_locals(py, TaskLocals::with_running_loop(py)?, async move {
+            locator
+                .wait_for_count(count, timeout_ms.map(std::time::Duration::from_millis))
+                .await
+                .map_err(automation_error_to_pyerr)
+        })
+    }
+}
+
+/// Convert a condition name to a WaitCondition
+fn parse_condition(condition: &str, value: Option<String>) -> PyResult<WaitCondition> {
+    let require_value = || {
+        value.clone().ok_or_else(|| {
+            PyValueError::new_err(format!("Condition '{condition}' requires a value"))
+        })
+    };
+    match condition.to_lowercase().replace('_', "").as_str() {
+        "exists" => Ok(WaitCondition::Exists),
+        "visible" => Ok(WaitCondition::Visible),
+        "enabled" => Ok(WaitCondition::Enabled),
+        "focused" => Ok(WaitCondition::Focused),
+        "stable" => Ok(WaitCondition::Stable),
+        "valueequals" => Ok(WaitCondition::ValueEquals(require_value()?)),
+        "textcontains" => Ok(WaitCondition::TextContains(require_value()?)),
+        "hidden" => Ok(WaitCondition::Hidden),
+        "detached" => Ok(WaitCondition::Detached),
+        _ => Err(PyValueError::new_err(format!(
+            "Invalid condition '{condition}'. Valid: exists, visible, enabled, focused, \
+             stable, value_equals, text_contains, hidden, detached"
+        ))),
+    }
 }
@@ -0,0 +1,314 @@
+use ::terminator_core::selector::Selector as TerminatorSelector;
+use pyo3::prelude::*;
+use pyo3_stub_gen::derive::*;
+use pyo3_stub_gen::{PyStubType, TypeInfo};
+use std::collections::{BTreeMap, HashMap};
+
+/// Typed selector for locating UI elements, an alternative to selector strings.
+///
+/// Selectors combine with `&` (all must match), `|` (any may match) and `~` (must not match):
+///
+///     Selector.role("Button") & ~Selector.name("Cancel")
+#[gen_stub_pyclass]
+#[pyclass(name = "Selector", eq, frozen, hash)]
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Selector {
+    pub inner: TerminatorSelector,
+}
+
+impl From<TerminatorSelector> for Selector {
+    fn from(inner: TerminatorSelector) -> Self {
+        Selector { inner }
+    }
+}
+
+/// A selector string or a `Selector`, as accepted by `locator()`
+#[derive(FromPyObject)]
+pub enum SelectorArg {
+    Selector(Selector),
+    Text(String),
+}
+
+impl From<SelectorArg> for TerminatorSelector {
+    fn from(arg: SelectorArg) -> Self {
+        match arg {
+            SelectorArg::Selector(selector) => selector.inner,
+            SelectorArg::Text(text) => text.as_str().into(),
+        }
+    }
+}
+
+impl PyStubType for SelectorArg {
+    fn type_output() -> TypeInfo {
+        TypeInfo::builtin("str") | TypeInfo::unqualified("Selector")
+    }
+}
+
+impl Selector {
+    /// Joins two selectors with `And`/`Or`, flattening nested ones of the same kind
+    fn combine(
+        &self,
+        other: SelectorArg,
+        make: fn(Vec<TerminatorSelector>) -> TerminatorSelector,
+        parts_of: fn(&TerminatorSelector) -> Option<&Vec<TerminatorSelector>>,
+    ) -> Selector {
+        let mut parts = Vec::new();
+        for selector in [self.inner.clone(), other.into()] {
+            match parts_of(&selector) {
+                Some(nested) => parts.extend(nested.iter().cloned()),
+                None => parts.push(selector),
+            }
+        }
+        Selector::from(make(parts))
+    }
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl Selector {
+    #[new]
+    #[pyo3(text_signature = "(selector)")]
+    /// Parse a selector string, e.g. `role:Button && name:Save`.
+    ///
+    /// Args:
+    ///     selector (str): The selector string.
+    ///
+    /// Returns:
+    ///     Selector: The parsed selector.
+    pub fn new(selector: &str) -> Self {
+        Selector::from(TerminatorSelector::from(selector))
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "name", text_signature = "(name)")]
+    /// Match elements by their accessibility name.
+    ///
+    /// Args:
+    ///     name (str): The element name.
+    ///
+    /// Returns:
+    ///     Selector: A new selector.
+    pub fn name(name: String) -> Self {
+        Selector::from(TerminatorSelector::Name(name))
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "role", signature = (role, name=None), text_signature = "(role, name=None)")]
+    /// Match elements by role, and optionally name.
+    ///
+    /// Args:
+    ///     role (str): The element role, e.g. "Button".
+    ///     name (Optional[str]): The element name.
+    ///
+    /// Returns:
+    ///     Selector: A new selector.
+    pub fn role(role: String, name: Option<String>) -> Self {
+        Selector::from(TerminatorSelector::Role { role, name })
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "id", text_signature = "(id)")]
+    /// Match elements by accessibility id.
+    ///
+    /// Args:
+    ///     id (str): The element id.
+    ///
+    /// Returns:
+    ///     Selector: A new selector.
+    pub fn id(id: String) -> Self {
+        Selector::from(TerminatorSelector::Id(id))
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "text", text_signature = "(text)")]
+    /// Match elements by the text they display.
+    ///
+    /// Args:
+    ///     text (str): The displayed text.
+    ///
+    /// Returns:
+    ///     Selector: A new selector.
+    pub fn text(text: String) -> Self {
+        Selector::from(TerminatorSelector::Text(text))
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "path", text_signature = "(path)")]
+    /// Match elements by an XPath-like path.
+    ///
+    /// Args:
+    ///     path (str): The path.
+    ///
+    /// Returns:
+    ///     Selector: A new selector.
+    pub fn path(path: String) -> Self {
+        Selector::from(TerminatorSelector::Path(path))
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "native_id", text_signature = "(id)")]
+    /// Match elements by native automation id (AutomationId on Windows).
+    ///
+    /// Args:
+    ///     id (str): The native id.
+    ///
+    /// Returns:
+    ///     Selector: A new selector.
+    pub fn native_id(id: String) -> Self {
+        Selector::from(TerminatorSelector::NativeId(id))
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "class_name", text_signature = "(name)")]
+    /// Match elements by class name.
+    ///
+    /// Args:
+    ///     name (str): The class name.
+    ///
+    /// Returns:
+    ///     Selector: A new selector.
+    pub fn class_name(name: String) -> Self {
+        Selector::from(TerminatorSelector::ClassName(name))
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "attributes", text_signature = "(attributes)")]
+    /// Match elements by a map of attributes.
+    ///
+    /// Args:
+    ///     attributes (Dict[str, str]): Attribute names and values.
+    ///
+    /// Returns:
+    ///     Selector: A new selector.
+    pub fn attributes(attributes: HashMap<String, String>) -> Self {
+        let map: BTreeMap<String, String> = attributes.into_iter().collect();
+        Selector::from(TerminatorSelector::Attributes(map))
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "nth", text_signature = "(index)")]
+    /// Select the nth match: 0 is the first, -1 the last.
+    ///
+    /// Args:
+    ///     index (int): The index, negative to count from the end.
+    ///
+    /// Returns:
+    ///     Selector: A new selector.
+    pub fn nth(index: i32) -> Self {
+        Selector::from(TerminatorSelector::Nth(index))
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "has", text_signature = "(inner)")]
+    /// Match elements with a descendant matching `inner`, like Playwright's :has().
+    ///
+    /// Args:
+    ///     inner (str | Selector): The descendant selector.
+    ///
+    /// Returns:
+    ///     Selector: A new selector.
+    pub fn has(inner: SelectorArg) -> Self {
+        Selector::from(TerminatorSelector::Has(Box::new(inner.into())))
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "parent", text_signature = "()")]
+    /// Navigate to the parent element, like `..`.
+    ///
+    /// Returns:
+    ///     Selector: A new selector.
+    pub fn parent() -> Self {
+        Selector::from(TerminatorSelector::Parent)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "process", text_signature = "(process_name)")]
+    /// Scope the search to a process, usually the first part of a chain.
+    ///
+    /// Args:
+    ///     process_name (str): The process name, e.g. "notepad".
+    ///
+    /// Returns:
+    ///     Selector: A new selector.
+    pub fn process(process_name: String) -> Self {
+        Selector::from(TerminatorSelector::Process(process_name))
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "window", text_signature = "(title)")]
+    /// Scope the search to a window, usually chained after a process.
+    ///
+    /// Args:
+    ///     title (str): The window title.
+    ///
+    /// Returns:
+    ///     Selector: A new selector.
+    pub fn window(title: String) -> Self {
+        Selector::from(TerminatorSelector::Role {
+            role: "Window".to_string(),
+            name: Some(title),
+        })
+    }
+
+    #[pyo3(name = "chain", text_signature = "($self, other)")]
+    /// Search for `other` within the elements this selector matches.
+    ///
+    /// Args:
+    ///     other (str | Selector): The selector to chain.
+    ///
+    /// Returns:
+    ///     Selector: A new selector.
+    pub fn chain(&self, other: SelectorArg) -> Selector {
+        Selector::from(TerminatorSelector::Chain(vec![
+            self.inner.clone(),
+            other.into(),
+        ]))
+    }
+
+    #[pyo3(name = "visible", text_signature = "($self, is_visible)")]
+    /// Filter the matches by visibility.
+    ///
+    /// Args:
+    ///     is_visible (bool): Whether the elements must be visible.
+    ///
+    /// Returns:
+    ///     Selector: A new selector.
+    pub fn visible(&self, is_visible: bool) -> Selector {
+        Selector::from(TerminatorSelector::Chain(vec![
+            self.inner.clone(),
+            TerminatorSelector::Visible(is_visible),
+        ]))
+    }
+
+    /// Match elements that match both selectors.
+    pub fn __and__(&self, other: SelectorArg) -> Selector {
+        self.combine(other, TerminatorSelector::And, |s| match s {
+            TerminatorSelector::And(parts) => Some(parts),
+            _ => None,
+        })
+    }
+
+    /// Match elements that match either selector.
+    pub fn __or__(&self, other: SelectorArg) -> Selector {
+        self.combine(other, TerminatorSelector::Or, |s| match s {
+            TerminatorSelector::Or(parts) => Some(parts),
+            _ => None,
+        })
+    }
+
+    /// Match elements that don't match this selector.
+    pub fn __invert__(&self) -> Selector {
+        match &self.inner {
+            TerminatorSelector::Not(inner) => Selector::from((**inner).clone()),
+            inner => Selector::from(TerminatorSelector::Not(Box::new(inner.clone()))),
+        }
+    }
+
+    pub fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("Selector({:?})", self.inner.to_string())
+    }
+}
@@ -0,0 +1,545 @@
+use pyo3::prelude::*;
+#[cfg(target_os = "windows")]
+use pyo3_async_runtimes::tokio as pyo3_tokio;
+#[cfg(target_os = "windows")]
+use pyo3_async_runtimes::TaskLocals;
+use pyo3_stub_gen::derive::*;
+#[cfg(target_os = "windows")]
+use std::sync::Arc;
+
+#[cfg(target_os = "windows")]
+use ::terminator_core::WindowManager as CoreWindowManager;
+
+/// Information about a window.
+#[gen_stub_pyclass]
+#[pyclass(name = "WindowInfo")]
+#[derive(Clone)]
+pub struct WindowInfo {
+    /// Window handle
+    #[pyo3(get)]
+    pub hwnd: i64,
+    /// Process name (e.g., "notepad.exe")
+    #[pyo3(get)]
+    pub process_name: String,
+    /// Process ID
+    #[pyo3(get)]
+    pub process_id: u32,
+    /// Z-order position (0 = topmost)
+    #[pyo3(get)]
+    pub z_order: u32,
+    /// Whether the window is minimized
+    #[pyo3(get)]
+    pub is_minimized: bool,
+    /// Whether the window is maximized
+    #[pyo3(get)]
+    pub is_maximized: bool,
+    /// Whether the window has WS_EX_TOPMOST style
+    #[pyo3(get)]
+    pub is_always_on_top: bool,
+    /// Window title
+    #[pyo3(get)]
+    pub title: String,
+}
+
+#[cfg(target_os = "windows")]
+impl From<::terminator_core::WindowInfo> for WindowInfo {
+    fn from(info: ::terminator_core::WindowInfo) -> Self {
+        Self {
+            hwnd: info.hwnd as i64,
+            process_name: info.process_name,
+            process_id: info.process_id,
+            z_order: info.z_order,
+            is_minimized: info.is_minimized,
+            is_maximized: info.is_maximized,
+            is_always_on_top: info.is_always_on_top,
+            title: info.title,
+        }
+    }
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl WindowInfo {
+    pub fn __repr__(&self) -> String {
+        format!(
+            "WindowInfo(hwnd={}, process_name={:?}, title={:?})",
+            self.hwnd, self.process_name, self.title
+        )
+    }
+}
+
+/// Window manager for controlling window states (Windows only).
+///
+/// Enumerates windows with Z-order tracking, brings windows to front
+/// (bypassing focus-stealing prevention), minimizes/maximizes them, and
+/// captures and restores window states around a workflow.
+#[gen_stub_pyclass]
+#[pyclass(name = "WindowManager")]
+pub struct WindowManager {
+    #[cfg(target_os = "windows")]
+    inner: Arc<CoreWindowManager>,
+}
+
+impl Default for WindowManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Maps a window manager error message to a Python exception
+#[cfg(target_os = "windows")]
+fn platform_error(context: &str, error: String) -> PyErr {
+    crate::exceptions::PlatformError::new_err(format!("{context}: {error}"))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn unsupported() -> PyErr {
+    crate::exceptions::UnsupportedPlatformError::new_err(
+        "WindowManager is only supported on Windows",
+    )
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl WindowManager {
+    #[new]
+    /// Create a new WindowManager.
+    pub fn new() -> Self {
+        Self {
+            #[cfg(target_os = "windows")]
+            inner: Arc::new(CoreWindowManager::new()),
+        }
+    }
+
+    #[pyo3(name = "update_window_cache", text_signature = "($self)")]
+    /// (async) Refresh the cached list of windows.
+    ///
+    /// Returns:
+    ///     None
+    pub fn update_window_cache<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        #[cfg(target_os = "windows")]
+        {
+            let wm = self.inner.clone();
+            pyo3_tokio::future_into_py_with_locals(
+                py,
+                TaskLocals::with_running_loop(py)?,
+                async move {
+                    wm.update_window_cache()
+                        .await
+                        .map_err(|e| platform_error("Failed to update window cache", e))
+                },
+            )
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = py;
+            Err(unsupported())
+        }
+    }
+
+    #[pyo3(
+        name = "get_topmost_window_for_process",
+        text_signature = "($self, process)"
+    )]
+    /// (async) Get the topmost window of a process by name.
+    ///
+    /// Args:
+    ///     process (str): The process name, e.g. "notepad".
+    ///
+    /// Returns:
+    ///     Optional[WindowInfo]: The window, if the process has one.
+    pub fn get_topmost_window_for_process<'py>(
+        &self,
+        py: Python<'py>,
+        process: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        #[cfg(target_os = "windows")]
+        {
+            let wm = self.inner.clone();
+            pyo3_tokio::future_into_py_with_locals(
+                py,
+                TaskLocals::with_running_loop(py)?,
+                async move {
+                    Ok(wm
+                        .get_topmost_window_for_process(&process)
+                        .await
+                        .map(WindowInfo::from))
+                },
+            )
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = (py, process);
+            Err(unsupported())
+        }
+    }
+
+    #[pyo3(name = "get_topmost_window_for_pid", text_signature = "($self, pid)")]
+    /// (async) Get the topmost window of a process by id.
+    ///
+    /// Args:
+    ///     pid (int): The process id.
+    ///
+    /// Returns:
+    ///     Optional[WindowInfo]: The window, if the process has one.
+    pub fn get_topmost_window_for_pid<'py>(
+        &self,
+        py: Python<'py>,
+        pid: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        #[cfg(target_os = "windows")]
+        {
+            let wm = self.inner.clone();
+            pyo3_tokio::future_into_py_with_locals(
+                py,
+                TaskLocals::with_running_loop(py)?,
+                async move {
+                    Ok(wm
+                        .get_topmost_window_for_pid(pid)
+                        .await
+                        .map(WindowInfo::from))
+                },
+            )
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = (py, pid);
+            Err(unsupported())
+        }
+    }
+
+    #[pyo3(name = "get_always_on_top_windows", text_signature = "($self)")]
+    /// (async) Get all visible always-on-top windows.
+    ///
+    /// Returns:
+    ///     List[WindowInfo]: The windows.
+    pub fn get_always_on_top_windows<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        #[cfg(target_os = "windows")]
+        {
+            let wm = self.inner.clone();
+            pyo3_tokio::future_into_py_with_locals(
+                py,
+                TaskLocals::with_running_loop(py)?,
+                async move {
+                    Ok(wm
+                        .get_always_on_top_windows()
+                        .await
+                        .into_iter()
+                        .map(WindowInfo::from)
+                        .collect::<Vec<_>>())
+                },
+            )
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = py;
+            Err(unsupported())
+        }
+    }
+
+    #[pyo3(
+        name = "minimize_always_on_top_windows",
+        text_signature = "($self, target_hwnd)"
+    )]
+    /// (async) Minimize always-on-top windows other than the target.
+    ///
+    /// Args:
+    ///     target_hwnd (int): Handle of the window to keep.
+    ///
+    /// Returns:
+    ///     int: The number of windows minimized.
+    pub fn minimize_always_on_top_windows<'py>(
+        &self,
+        py: Python<'py>,
+        target_hwnd: i64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        #[cfg(target_os = "windows")]
+        {
+            let wm = self.inner.clone();
+            pyo3_tokio::future_into_py_with_locals(
+                py,
+                TaskLocals::with_running_loop(py)?,
+                async move {
+                    wm.minimize_always_on_top_windows(target_hwnd as isize)
+                        .await
+                        .map_err(|e| platform_error("Failed to minimize always-on-top windows", e))
+                },
+            )
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = (py, target_hwnd);
+            Err(unsupported())
+        }
+    }
+
+    #[pyo3(name = "minimize_all_except", text_signature = "($self, target_hwnd)")]
+    /// (async) Minimize all visible windows except the target.
+    ///
+    /// Args:
+    ///     target_hwnd (int): Handle of the window to keep.
+    ///
+    /// Returns:
+    ///     int: The number of windows minimized.
+    pub fn minimize_all_except<'py>(
+        &self,
+        py: Python<'py>,
+        target_hwnd: i64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        #[cfg(target_os = "windows")]
+        {
+            let wm = self.inner.clone();
+            pyo3_tokio::future_into_py_with_locals(
+                py,
+                TaskLocals::with_running_loop(py)?,
+                async move {
+                    wm.minimize_all_except(target_hwnd as isize)
+                        .await
+                        .map_err(|e| platform_error("Failed to minimize windows", e))
+                },
+            )
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = (py, target_hwnd);
+            Err(unsupported())
+        }
+    }
+
+    #[pyo3(name = "maximize_if_needed", text_signature = "($self, hwnd)")]
+    /// (async) Maximize a window unless it already is.
+    ///
+    /// Args:
+    ///     hwnd (int): The window handle.
+    ///
+    /// Returns:
+    ///     bool: True if the window was maximized by this call.
+    pub fn maximize_if_needed<'py>(
+        &self,
+        py: Python<'py>,
+        hwnd: i64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        #[cfg(target_os = "windows")]
+        {
+            let wm = self.inner.clone();
+            pyo3_tokio::future_into_py_with_locals(
+                py,
+                TaskLocals::with_running_loop(py)?,
+                async move {
+                    wm.maximize_if_needed(hwnd as isize)
+                        .await
+                        .map_err(|e| platform_error("Failed to maximize window", e))
+                },
+            )
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = (py, hwnd);
+            Err(unsupported())
+        }
+    }
+
+    #[pyo3(name = "bring_window_to_front", text_signature = "($self, hwnd)")]
+    /// (async) Bring a window to the foreground, bypassing focus-stealing prevention.
+    ///
+    /// Args:
+    ///     hwnd (int): The window handle.
+    ///
+    /// Returns:
+    ///     bool: True if the window is now in the foreground.
+    pub fn bring_window_to_front<'py>(
+        &self,
+        py: Python<'py>,
+        hwnd: i64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        #[cfg(target_os = "windows")]
+        {
+            let wm = self.inner.clone();
+            pyo3_tokio::future_into_py_with_locals(
+                py,
+                TaskLocals::with_running_loop(py)?,
+                async move {
+                    wm.bring_window_to_front(hwnd as isize)
+                        .await
+                        .map_err(|e| platform_error("Failed to bring window to front", e))
+                },
+            )
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = (py, hwnd);
+            Err(unsupported())
+        }
+    }
+
+    #[pyo3(name = "minimize_if_needed", text_signature = "($self, hwnd)")]
+    /// (async) Minimize a window unless it already is.
+    ///
+    /// Args:
+    ///     hwnd (int): The window handle.
+    ///
+    /// Returns:
+    ///     bool: True if the window was minimized by this call.
+    pub fn minimize_if_needed<'py>(
+        &self,
+        py: Python<'py>,
+        hwnd: i64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        #[cfg(target_os = "windows")]
+        {
+            let wm = self.inner.clone();
+            pyo3_tokio::future_into_py_with_locals(
+                py,
+                TaskLocals::with_running_loop(py)?,
+                async move {
+                    wm.minimize_if_needed(hwnd as isize)
+                        .await
+                        .map_err(|e| platform_error("Failed to minimize window", e))
+                },
+            )
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = (py, hwnd);
+            Err(unsupported())
+        }
+    }
+
+    #[pyo3(name = "capture_initial_state", text_signature = "($self)")]
+    /// (async) Capture the current window states, to restore after a workflow.
+    ///
+    /// Returns:
+    ///     None
+    pub fn capture_initial_state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        #[cfg(target_os = "windows")]
+        {
+            let wm = self.inner.clone();
+            pyo3_tokio::future_into_py_with_locals(
+                py,
+                TaskLocals::with_running_loop(py)?,
+                async move {
+                    wm.capture_initial_state()
+                        .await
+                        .map_err(|e| platform_error("Failed to capture initial state", e))
+                },
+            )
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = py;
+            Err(unsupported())
+        }
+    }
+
+    #[pyo3(name = "restore_all_windows", text_signature = "($self)")]
+    /// (async) Restore minimized windows and the target window to their captured state.
+    ///
+    /// Returns:
+    ///     int: The number of windows restored.
+    pub fn restore_all_windows<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        #[cfg(target_os = "windows")]
+        {
+            let wm = self.inner.clone();
+            pyo3_tokio::future_into_py_with_locals(
+                py,
+                TaskLocals::with_running_loop(py)?,
+                async move {
+                    wm.restore_all_windows()
+                        .await
+                        .map_err(|e| platform_error("Failed to restore windows", e))
+                },
+            )
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = py;
+            Err(unsupported())
+        }
+    }
+
+    #[pyo3(name = "clear_captured_state", text_signature = "($self)")]
+    /// (async) Forget the captured window states.
+    ///
+    /// Returns:
+    ///     None
+    pub fn clear_captured_state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        #[cfg(target_os = "windows")]
+        {
+            let wm = self.inner.clone();
+            pyo3_tokio::future_into_py_with_locals(
+                py,
+                TaskLocals::with_running_loop(py)?,
+                async move {
+                    {
+                        wm.clear_captured_state().await;
+                        Ok(())
+                    }
+                },
+            )
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = py;
+            Err(unsupported())
+        }
+    }
+
+    #[pyo3(name = "is_uwp_app", text_signature = "($self, pid)")]
+    /// (async) Check whether a process is a UWP/Modern app.
+    ///
+    /// Args:
+    ///     pid (int): The process id.
+    ///
+    /// Returns:
+    ///     bool: True for UWP apps.
+    pub fn is_uwp_app<'py>(&self, py: Python<'py>, pid: u32) -> PyResult<Bound<'py, PyAny>> {
+        #[cfg(target_os = "windows")]
+        {
+            let wm = self.inner.clone();
+            pyo3_tokio::future_into_py_with_locals(
+                py,
+                TaskLocals::with_running_loop(py)?,
+                async move { Ok(wm.is_uwp_app(pid).await) },
+            )
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = (py, pid);
+            Err(unsupported())
+        }
+    }
+
+    #[pyo3(name = "set_target_window", text_signature = "($self, hwnd)")]
+    /// (async) Track a window as the target for restoration.
+    ///
+    /// Args:
+    ///     hwnd (int): The window handle.
+    ///
+    /// Returns:
+    ///     None
+    pub fn set_target_window<'py>(
+        &self,
+        py: Python<'py>,
+        hwnd: i64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        #[cfg(target_os = "windows")]
+        {
+            let wm = self.inner.clone();
+            pyo3_tokio::future_into_py_with_locals(
+                py,
+                TaskLocals::with_running_loop(py)?,
+                async move {
+                    {
+                        wm.set_target_window(hwnd as isize).await;
+                        Ok(())
+                    }
+                },
+            )
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = (py, hwnd);
+            Err(unsupported())
+        }
+    }
+}
@@ -98,16 +98,26 @@ class Desktop:
         Args:
             name (str): The name of the application to activate.
         """
-    def locator(self, selector:builtins.str) -> Locator:
+    def locator(self, selector:builtins.str | Selector) -> Locator:
         r"""
         Create a locator for finding UI elements.
         
         Args:
-            selector (str): The selector string to find elements.
+            selector (str | Selector): The selector string or Selector.
         
         Returns:
             Locator: A locator for finding elements.
         """
+    def register_filter(self, name:builtins.str, predicate:typing.Callable[[UIElement], typing.Any]) -> None:
+        r"""
+        Register a predicate usable as `filter:<name>` in selector strings, e.g.
+        `role:Button && filter:wide` after registering `wide`. It stays registered
+        for the lifetime of the process; registering the same name again replaces it.
+        
+        Args:
+            name (str): The filter name; built-in names such as `enabled` are reserved.
+            predicate (Callable[[UIElement], object]): Called with each candidate element.
+        """
     async def run_command(self, windows_command:typing.Optional[builtins.str]=None, unix_command:typing.Optional[builtins.str]=None) -> CommandOutput:
         r"""
         (async) Run a shell command.
@@ -371,12 +381,12 @@ class Locator:
         Returns:
             Locator: A new locator with the specified timeout.
         """
-    def locator(self, selector:builtins.str) -> Locator:
+    def locator(self, selector:builtins.str | Selector) -> Locator:
         r"""
         Chain another selector.
         
         Args:
-            selector (str): The selector string.
+            selector (str | Selector): The selector string or Selector.
         
         Returns:
             Locator: A new locator with the chained selector.
@@ -391,6 +401,109 @@ class Locator:
         Returns:
             Locator: A new locator with the specified root element.
         """
+    def filter(self, predicate:typing.Callable[[UIElement], typing.Any]) -> Locator:
+        r"""
+        Keep only the elements for which `predicate` returns a truthy value.
+        The predicate runs for every candidate while the locator is searching.
+        
+        Args:
+            predicate (Callable[[UIElement], object]): Called with each candidate element.
+        
+        Returns:
+            Locator: A new locator that only matches accepted elements.
+        """
+    def filter_by(self, has_text:typing.Optional[builtins.str]=None, has:typing.Optional[builtins.str | Selector]=None, has_not:typing.Optional[builtins.str | Selector]=None) -> Locator:
+        r"""
+        Narrow the matches by text content or descendants.
+        
+        Args:
+            has_text (Optional[str]): Keep elements whose text, or a descendant's text, contains this.
+            has (Optional[str | Selector]): Keep elements with a descendant matching this selector.
+            has_not (Optional[str | Selector]): Keep elements without such a descendant.
+        
+        Returns:
+            Locator: A new locator that only matches elements passing every given option.
+        """
+    def nth(self, index:builtins.int) -> Locator:
+        r"""
+        Select the n-th match (0-based). Negative indices count from the end.
+        
+        Args:
+            index (int): Index of the match.
+        
+        Returns:
+            Locator: A new locator matching a single element.
+        """
+    def last(self) -> Locator:
+        r"""
+        Select the last match.
+        
+        Returns:
+            Locator: A new locator matching the last element.
+        """
+    def or_(self, other:Locator) -> Locator:
+        r"""
+        Match elements matched by this locator or by `other`.
+        
+        Args:
+            other (Locator): The alternative locator.
+        
+        Returns:
+            Locator: A new locator matching either.
+        """
+    def and_(self, other:Locator) -> Locator:
+        r"""
+        Match elements matched by both this locator and `other`.
+        
+        Args:
+            other (Locator): The other locator.
+        
+        Returns:
+            Locator: A new locator matching both.
+        """
+    async def count(self) -> builtins.int:
+        r"""
+        (async) Count the elements currently matching, without waiting.
+        
+        Returns:
+            int: Number of matching elements.
+        """
+    async def wait_for(self, condition:builtins.str, timeout_ms:typing.Optional[builtins.int]=None, value:typing.Optional[builtins.str]=None) -> typing.Optional[UIElement]:
+        r"""
+        (async) Wait for the locator to meet a condition.
+        
+        Args:
+            condition (str): One of 'exists', 'visible', 'enabled', 'focused', 'stable',
+                'value_equals', 'text_contains', 'hidden', 'detached'.
+            timeout_ms (Optional[int]): Timeout in milliseconds.
+            value (Optional[str]): Expected value for 'value_equals' and 'text_contains'.
+        
+        Returns:
+            Optional[UIElement]: The element when the condition is met; None only for
+                'hidden' and 'detached' once no matching element is left.
+        """
+    async def wait_for_hidden(self, timeout_ms:typing.Optional[builtins.int]=None) -> None:
+        r"""
+        (async) Wait until no matching element is visible (it is hidden or gone).
+        
+        Args:
+            timeout_ms (Optional[int]): Timeout in milliseconds.
+        """
+    async def wait_for_detached(self, timeout_ms:typing.Optional[builtins.int]=None) -> None:
+        r"""
+        (async) Wait until no element matches.
+        
+        Args:
+            timeout_ms (Optional[int]): Timeout in milliseconds.
+        """
+    async def wait_for_count(self, count:builtins.int, timeout_ms:typing.Optional[builtins.int]=None) -> None:
+        r"""
+        (async) Wait until exactly `count` elements match.
+        
+        Args:
+            count (int): Expected number of matches.
+            timeout_ms (Optional[int]): Timeout in milliseconds.
+        """
 
 class Monitor:
     r"""
@@ -426,6 +539,200 @@ class ScreenshotResult:
     def __repr__(self) -> builtins.str: ...
     def __str__(self) -> builtins.str: ...
 
+class Selector:
+    r"""
+    Typed selector for locating UI elements, an alternative to selector strings.
+    
+    Selectors combine with `&` (all must match), `|` (any may match) and `~` (must not match):
+    
+        Selector.role("Button") & ~Selector.name("Cancel")
+    """
+    def __new__(cls, selector:builtins.str) -> Selector:
+        r"""
+        Parse a selector string, e.g. `role:Button && name:Save`.
+        
+        Args:
+            selector (str): The selector string.
+        
+        Returns:
+            Selector: The parsed selector.
+        """
+    @staticmethod
+    def name(name:builtins.str) -> Selector:
+        r"""
+        Match elements by their accessibility name.
+        
+        Args:
+            name (str): The element name.
+        
+        Returns:
+            Selector: A new selector.
+        """
+    @staticmethod
+    def role(role:builtins.str, name:typing.Optional[builtins.str]=None) -> Selector:
+        r"""
+        Match elements by role, and optionally name.
+        
+        Args:
+            role (str): The element role, e.g. "Button".
+            name (Optional[str]): The element name.
+        
+        Returns:
+            Selector: A new selector.
+        """
+    @staticmethod
+    def id(id:builtins.str) -> Selector:
+        r"""
+        Match elements by accessibility id.
+        
+        Args:
+            id (str): The element id.
+        
+        Returns:
+            Selector: A new selector.
+        """
+    @staticmethod
+    def text(text:builtins.str) -> Selector:
+        r"""
+        Match elements by the text they display.
+        
+        Args:
+            text (str): The displayed text.
+        
+        Returns:
+            Selector: A new selector.
+        """
+    @staticmethod
+    def path(path:builtins.str) -> Selector:
+        r"""
+        Match elements by an XPath-like path.
+        
+        Args:
+            path (str): The path.
+        
+        Returns:
+            Selector: A new selector.
+        """
+    @staticmethod
+    def native_id(id:builtins.str) -> Selector:
+        r"""
+        Match elements by native automation id (AutomationId on Windows).
+        
+        Args:
+            id (str): The native id.
+        
+        Returns:
+            Selector: A new selector.
+        """
+    @staticmethod
+    def class_name(name:builtins.str) -> Selector:
+        r"""
+        Match elements by class name.
+        
+        Args:
+            name (str): The class name.
+        
+        Returns:
+            Selector: A new selector.
+        """
+    @staticmethod
+    def attributes(attributes:typing.Mapping[builtins.str, builtins.str]) -> Selector:
+        r"""
+        Match elements by a map of attributes.
+        
+        Args:
+            attributes (Dict[str, str]): Attribute names and values.
+        
+        Returns:
+            Selector: A new selector.
+        """
+    @staticmethod
+    def nth(index:builtins.int) -> Selector:
+        r"""
+        Select the nth match: 0 is the first, -1 the last.
+        
+        Args:
+            index (int): The index, negative to count from the end.
+        
+        Returns:
+            Selector: A new selector.
+        """
+    @staticmethod
+    def has(inner:builtins.str | Selector) -> Selector:
+        r"""
+        Match elements with a descendant matching `inner`, like Playwright's :has().
+        
+        Args:
+            inner (str | Selector): The descendant selector.
+        
+        Returns:
+            Selector: A new selector.
+        """
+    @staticmethod
+    def parent() -> Selector:
+        r"""
+        Navigate to the parent element, like `..`.
+        
+        Returns:
+            Selector: A new selector.
+        """
+    @staticmethod
+    def process(process_name:builtins.str) -> Selector:
+        r"""
+        Scope the search to a process, usually the first part of a chain.
+        
+        Args:
+            process_name (str): The process name, e.g. "notepad".
+        
+        Returns:
+            Selector: A new selector.
+        """
+    @staticmethod
+    def window(title:builtins.str) -> Selector:
+        r"""
+        Scope the search to a window, usually chained after a process.
+        
+        Args:
+            title (str): The window title.
+        
+        Returns:
+            Selector: A new selector.
+        """
+    def chain(self, other:builtins.str | Selector) -> Selector:
+        r"""
+        Search for `other` within the elements this selector matches.
+        
+        Args:
+            other (str | Selector): The selector to chain.
+        
+        Returns:
+            Selector: A new selector.
+        """
+    def visible(self, is_visible:builtins.bool) -> Selector:
+        r"""
+        Filter the matches by visibility.
+        
+        Args:
+            is_visible (bool): Whether the elements must be visible.
+        
+        Returns:
+            Selector: A new selector.
+        """
+    def __and__(self, other:builtins.str | Selector) -> Selector:
+        r"""
+        Match elements that match both selectors.
+        """
+    def __or__(self, other:builtins.str | Selector) -> Selector:
+        r"""
+        Match elements that match either selector.
+        """
+    def __invert__(self) -> Selector:
+        r"""
+        Match elements that don't match this selector.
+        """
+    def __str__(self) -> builtins.str: ...
+    def __repr__(self) -> builtins.str: ...
+
 class TreeBuildConfig:
     r"""
     Configuration for tree building performance and completeness
@@ -490,24 +797,33 @@ class UIElement:
         Returns:
             Bounds: The element's bounds.
         """
-    def click(self) -> ClickResult:
+    def click(self, force:typing.Optional[builtins.bool]=None) -> ClickResult:
         r"""
         Click on this element.
         
+        Args:
+            force (Optional[bool]): Skip the actionability checks (visible, enabled, stable, not covered).
+        
         Returns:
             ClickResult: Result of the click operation.
         """
-    def double_click(self) -> ClickResult:
+    def double_click(self, force:typing.Optional[builtins.bool]=None) -> ClickResult:
         r"""
         Double click on this element.
         
+        Args:
+            force (Optional[bool]): Skip the actionability checks (visible, enabled, stable, not covered).
+        
         Returns:
             ClickResult: Result of the click operation.
         """
-    def right_click(self) -> None:
+    def right_click(self, force:typing.Optional[builtins.bool]=None) -> None:
         r"""
         Right click on this element.
         
+        Args:
+            force (Optional[bool]): Skip the actionability checks (visible, enabled, stable, not covered).
+        
         Returns:
             None
         """
@@ -549,13 +865,14 @@ class UIElement:
         Returns:
             str: The element's text content.
         """
-    def type_text(self, text:builtins.str, use_clipboard:typing.Optional[builtins.bool]=None) -> None:
+    def type_text(self, text:builtins.str, use_clipboard:typing.Optional[builtins.bool]=None, force:typing.Optional[builtins.bool]=None) -> None:
         r"""
         Type text into this element.
         
         Args:
             text (str): The text to type.
             use_clipboard (Optional[bool]): Whether to use clipboard for pasting.
+            force (Optional[bool]): Skip the actionability checks (visible, enabled, editable).
         
         Returns:
             None
@@ -692,12 +1009,12 @@ class UIElement:
         Returns:
             Optional[UIElement]: The containing window element, if available.
         """
-    def locator(self, selector:builtins.str) -> Locator:
+    def locator(self, selector:builtins.str | Selector) -> Locator:
         r"""
         Create a locator from this element.
         
         Args:
-            selector (str): The selector string.
+            selector (str | Selector): The selector string or Selector.
         
         Returns:
             Locator: A new locator for finding elements.
@@ -863,6 +1180,182 @@ class UINode:
     def __repr__(self) -> builtins.str: ...
     def __str__(self) -> builtins.str: ...
 
+class WindowInfo:
+    r"""
+    Information about a window.
+    """
+    hwnd: builtins.int
+    r"""
+    Window handle
+    """
+    process_name: builtins.str
+    r"""
+    Process name (e.g., "notepad.exe")
+    """
+    process_id: builtins.int
+    r"""
+    Process ID
+    """
+    z_order: builtins.int
+    r"""
+    Z-order position (0 = topmost)
+    """
+    is_minimized: builtins.bool
+    r"""
+    Whether the window is minimized
+    """
+    is_maximized: builtins.bool
+    r"""
+    Whether the window is maximized
+    """
+    is_always_on_top: builtins.bool
+    r"""
+    Whether the window has WS_EX_TOPMOST style
+    """
+    title: builtins.str
+    r"""
+    Window title
+    """
+    def __repr__(self) -> builtins.str: ...
+
+class WindowManager:
+    r"""
+    Window manager for controlling window states (Windows only).
+    
+    Enumerates windows with Z-order tracking, brings windows to front
+    (bypassing focus-stealing prevention), minimizes/maximizes them, and
+    captures and restores window states around a workflow.
+    """
+    def __new__(cls) -> WindowManager:
+        r"""
+        Create a new WindowManager.
+        """
+    async def update_window_cache(self) -> None:
+        r"""
+        (async) Refresh the cached list of windows.
+        
+        Returns:
+            None
+        """
+    async def get_topmost_window_for_process(self, process:builtins.str) -> typing.Optional[WindowInfo]:
+        r"""
+        (async) Get the topmost window of a process by name.
+        
+        Args:
+            process (str): The process name, e.g. "notepad".
+        
+        Returns:
+            Optional[WindowInfo]: The window, if the process has one.
+        """
+    async def get_topmost_window_for_pid(self, pid:builtins.int) -> typing.Optional[WindowInfo]:
+        r"""
+        (async) Get the topmost window of a process by id.
+        
+        Args:
+            pid (int): The process id.
+        
+        Returns:
+            Optional[WindowInfo]: The window, if the process has one.
+        """
+    async def get_always_on_top_windows(self) -> typing.List[WindowInfo]:
+        r"""
+        (async) Get all visible always-on-top windows.
+        
+        Returns:
+            List[WindowInfo]: The windows.
+        """
+    async def minimize_always_on_top_windows(self, target_hwnd:builtins.int) -> int:
+        r"""
+        (async) Minimize always-on-top windows other than the target.
+        
+        Args:
+            target_hwnd (int): Handle of the window to keep.
+        
+        Returns:
+            int: The number of windows minimized.
+        """
+    async def minimize_all_except(self, target_hwnd:builtins.int) -> int:
+        r"""
+        (async) Minimize all visible windows except the target.
+        
+        Args:
+            target_hwnd (int): Handle of the window to keep.
+        
+        Returns:
+            int: The number of windows minimized.
+        """
+    async def maximize_if_needed(self, hwnd:builtins.int) -> bool:
+        r"""
+        (async) Maximize a window unless it already is.
+        
+        Args:
+            hwnd (int): The window handle.
+        
+        Returns:
+            bool: True if the window was maximized by this call.
+        """
+    async def bring_window_to_front(self, hwnd:builtins.int) -> bool:
+        r"""
+        (async) Bring a window to the foreground, bypassing focus-stealing prevention.
+        
+        Args:
+            hwnd (int): The window handle.
+        
+        Returns:
+            bool: True if the window is now in the foreground.
+        """
+    async def minimize_if_needed(self, hwnd:builtins.int) -> bool:
+        r"""
+        (async) Minimize a window unless it already is.
+        
+        Args:
+            hwnd (int): The window handle.
+        
+        Returns:
+            bool: True if the window was minimized by this call.
+        """
+    async def capture_initial_state(self) -> None:
+        r"""
+        (async) Capture the current window states, to restore after a workflow.
+        
+        Returns:
+            None
+        """
+    async def restore_all_windows(self) -> int:
+        r"""
+        (async) Restore minimized windows and the target window to their captured state.
+        
+        Returns:
+            int: The number of windows restored.
+        """
+    async def clear_captured_state(self) -> None:
+        r"""
+        (async) Forget the captured window states.
+        
+        Returns:
+            None
+        """
+    async def is_uwp_app(self, pid:builtins.int) -> bool:
+        r"""
+        (async) Check whether a process is a UWP/Modern app.
+        
+        Args:
+            pid (int): The process id.
+        
+        Returns:
+            bool: True for UWP apps.
+        """
+    async def set_target_window(self, hwnd:builtins.int) -> None:
+        r"""
+        (async) Track a window as the target for restoration.
+        
+        Args:
+            hwnd (int): The window handle.
+        
+        Returns:
+            None
+        """
+
 class ElementNotFoundError(RuntimeError): ...
 
 class InternalError(RuntimeError): ...